@@ -0,0 +1,173 @@
+//! Benchmarks of parsing, diffing and serializing whole docs of both stages.
+//!
+//! Run with `cargo bench`, an argument only runs the benchmarks whose name contains it.
+//! `FAPI_DIFF_BENCH_SOURCE` and `FAPI_DIFF_BENCH_TARGET` have to point to the `doc-html` directories of two
+//! versions with full docs, the run fails without them. The trimmed golden fixtures in `tests/fixtures/golden`
+//! work for a quick check that the benchmarks run, but their numbers say little about real docs.
+//!
+//! This is a small harness of its own instead of criterion, which isn't available in the offline registry
+//! the crate is built from. It only reports the mean time per run, without criterion's statistics.
+
+#![allow(clippy::expect_used)]
+
+use std::{
+    hint::black_box,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use fapi_diff::format::{
+    prototype::PrototypeDoc, runtime::RuntimeDoc, with_old_values, DiffOptions, Doc, FieldPolicy,
+};
+use serde::de::DeserializeOwned;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/golden");
+
+/// How long each benchmark is measured for, after a warmup of the same length.
+const MEASURE: Duration = Duration::from_secs(1);
+
+/// Shape of the CLI config that every compared item used to clone to read its options.
+#[cfg(feature = "bench-hooks")]
+#[derive(Clone)]
+#[allow(dead_code, clippy::struct_excessive_bools)] // only the cost of cloning it matters
+struct ClonedConfig {
+    stage: String,
+    source: String,
+    target: String,
+    descriptions: bool,
+    examples: bool,
+    full: bool,
+    local: bool,
+}
+
+#[cfg(feature = "bench-hooks")]
+thread_local! {static CONFIG: std::cell::RefCell<ClonedConfig> = std::cell::RefCell::new(ClonedConfig {
+    stage: "runtime".to_owned(),
+    source: "1.1.110".to_owned(),
+    target: "2.0.8".to_owned(),
+    descriptions: true,
+    examples: true,
+    full: true,
+    local: true,
+});}
+
+/// The `doc-html` directory in `var`, e.g. of `version`.
+fn doc_dir(var: &str, version: &str) -> PathBuf {
+    std::env::var_os(var).map_or_else(
+        || {
+            panic!(
+                "{var} isn't set, point it to the doc-html directory of a version with full docs \
+                 like {version}, or to {FIXTURES}/{version}/doc-html for a quick check"
+            )
+        },
+        PathBuf::from,
+    )
+}
+
+fn read(dir: &Path, file: &str) -> Vec<u8> {
+    std::fs::read(dir.join(file)).unwrap_or_else(|e| panic!("failed to read {file}: {e}"))
+}
+
+fn parse<T: DeserializeOwned>(raw: &[u8]) -> T {
+    serde_json::from_slice(raw).expect("failed to parse the doc")
+}
+
+/// Runs `f` repeatedly and prints the mean time per run.
+fn bench<R>(filter: Option<&str>, name: &str, mut f: impl FnMut() -> R) {
+    if filter.is_some_and(|filter| !name.contains(filter)) {
+        return;
+    }
+
+    let start = Instant::now();
+    while start.elapsed() < MEASURE {
+        black_box(f());
+    }
+
+    let mut runs = 0u32;
+    let start = Instant::now();
+    while start.elapsed() < MEASURE {
+        black_box(f());
+        runs += 1;
+    }
+
+    println!("{name:30} {:>12.3?} ({runs} runs)", start.elapsed() / runs);
+}
+
+/// Clones the config like every compared item used to when reading its options.
+#[cfg(feature = "bench-hooks")]
+fn clone_config() {
+    black_box(CONFIG.with_borrow(Clone::clone));
+}
+
+/// Compares diffing the runtime docs while cloning the whole config on every read of the options
+/// with reading the snapshot of [`DiffOptions`] alone.
+#[cfg(feature = "bench-hooks")]
+fn options(filter: Option<&str>, file: &str) {
+    let source = parse::<RuntimeDoc>(&read(&doc_dir("FAPI_DIFF_BENCH_SOURCE", "1.1.110"), file));
+    let target = parse::<RuntimeDoc>(&read(&doc_dir("FAPI_DIFF_BENCH_TARGET", "2.0.8"), file));
+    let options = DiffOptions {
+        policy: FieldPolicy::all(),
+        ..Default::default()
+    };
+
+    bench(filter, "options/diff-cloning-config", || {
+        DiffOptions::with_read_hook(clone_config, || options.apply(|| source.diff(&target)))
+    });
+
+    bench(filter, "options/diff-snapshot", || {
+        options.apply(|| source.diff(&target))
+    });
+}
+
+#[cfg(not(feature = "bench-hooks"))]
+fn options(filter: Option<&str>, _file: &str) {
+    if filter.is_none_or(|filter| "options".contains(filter)) {
+        println!(
+            "options: run with `--features bench-hooks` to compare cloning the config per read"
+        );
+    }
+}
+
+fn stage<T>(filter: Option<&str>, name: &str, file: &str)
+where
+    T: Doc + DeserializeOwned + serde::Serialize,
+    T::Diff: serde::Serialize,
+{
+    let source = read(&doc_dir("FAPI_DIFF_BENCH_SOURCE", "1.1.110"), file);
+    let target = read(&doc_dir("FAPI_DIFF_BENCH_TARGET", "2.0.8"), file);
+    let options = DiffOptions {
+        policy: FieldPolicy::all(),
+        ..Default::default()
+    };
+
+    bench(filter, &format!("{name}/parse"), || parse::<T>(&target));
+
+    let (source, target) = (parse::<T>(&source), parse::<T>(&target));
+    bench(filter, &format!("{name}/diff"), || {
+        options.apply(|| source.diff(&target))
+    });
+
+    let diff = options.apply(|| source.diff(&target));
+    bench(filter, &format!("{name}/serialize"), || {
+        with_old_values(true, || {
+            serde_json::to_vec(&diff).expect("failed to serialize the diff")
+        })
+    });
+
+    bench(filter, &format!("{name}/stream"), || {
+        let mut count = 0;
+        options
+            .apply(|| source.diff_entries(&target, &mut |_| count += 1))
+            .expect("failed to diff the docs");
+        count
+    });
+}
+
+fn main() {
+    let filter = std::env::args().skip(1).find(|arg| !arg.starts_with("--"));
+    let filter = filter.as_deref();
+
+    stage::<RuntimeDoc>(filter, "runtime", "runtime-api.json");
+    stage::<PrototypeDoc>(filter, "prototype", "prototype-api.json");
+    options(filter, "runtime-api.json");
+}
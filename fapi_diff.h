@@ -0,0 +1,31 @@
+#ifndef FAPI_DIFF_H
+#define FAPI_DIFF_H
+
+/*
+ * C ABI of fapi-diff, available when the library is built with `--features ffi`.
+ *
+ * All strings are NUL terminated UTF-8. Returned strings must be released with
+ * fapi_diff_free. On failure NULL is returned and fapi_diff_last_error describes the error.
+ */
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Diffs two runtime docs, options is NULL or a JSON object of diff options. */
+char *fapi_diff_runtime(const char *source, const char *target, const char *options);
+
+/* Diffs two prototype docs, options is NULL or a JSON object of diff options. */
+char *fapi_diff_prototype(const char *source, const char *target, const char *options);
+
+/* The error of the last failed call on this thread, NULL if it succeeded. Owned by the library. */
+const char *fapi_diff_last_error(void);
+
+/* Releases a string returned by fapi_diff_runtime or fapi_diff_prototype, NULL is ignored. */
+void fapi_diff_free(char *ptr);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
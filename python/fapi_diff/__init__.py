@@ -0,0 +1,97 @@
+"""Python bindings of fapi-diff, built on the C ABI of the `python` feature.
+
+Build the library with `cargo build --release --features python` and point `FAPI_DIFF_LIB`
+at the resulting `libfapi_diff.so` / `fapi_diff.dll` / `libfapi_diff.dylib`, or place it
+next to this module.
+
+These are `ctypes` bindings instead of PyO3 ones, the `python` feature builds the C ABI they call.
+"""
+
+import ctypes
+import json
+import os
+import sys
+from pathlib import Path
+from typing import Any, Union
+
+__all__ = ["FapiDiffError", "diff_prototype", "diff_runtime"]
+
+Doc = Union[dict, str, bytes]
+
+
+class FapiDiffError(Exception):
+    """A diff failed, the message is the error of the library."""
+
+
+def _lib_name() -> str:
+    if sys.platform == "win32":
+        return "fapi_diff.dll"
+    if sys.platform == "darwin":
+        return "libfapi_diff.dylib"
+    return "libfapi_diff.so"
+
+
+def _load() -> ctypes.CDLL:
+    path = os.environ.get("FAPI_DIFF_LIB") or str(Path(__file__).with_name(_lib_name()))
+    lib = ctypes.CDLL(path)
+
+    for name in ("fapi_diff_runtime", "fapi_diff_prototype"):
+        func = getattr(lib, name)
+        func.argtypes = [ctypes.c_char_p, ctypes.c_char_p, ctypes.c_char_p]
+        # a plain pointer, c_char_p would copy the string and lose the pointer to free
+        func.restype = ctypes.c_void_p
+
+    lib.fapi_diff_last_error.argtypes = []
+    lib.fapi_diff_last_error.restype = ctypes.c_char_p
+    lib.fapi_diff_free.argtypes = [ctypes.c_void_p]
+    lib.fapi_diff_free.restype = None
+
+    return lib
+
+
+_LIB = None
+
+
+def _encode(doc: Doc) -> bytes:
+    if isinstance(doc, dict):
+        return json.dumps(doc).encode()
+    if isinstance(doc, str):
+        return doc.encode()
+    return doc
+
+
+def _diff(name: str, source: Doc, target: Doc, options: dict) -> Any:
+    global _LIB
+    if _LIB is None:
+        _LIB = _load()
+
+    options = {"format": "json", **options}
+    ptr = getattr(_LIB, name)(_encode(source), _encode(target), json.dumps(options).encode())
+
+    if not ptr:
+        error = _LIB.fapi_diff_last_error()
+        raise FapiDiffError(error.decode() if error else "unknown error")
+
+    try:
+        raw = ctypes.string_at(ptr).decode()
+    finally:
+        _LIB.fapi_diff_free(ptr)
+
+    if options["format"] in ("json", "flat"):
+        return json.loads(raw)
+
+    return raw
+
+
+def diff_runtime(source: Doc, target: Doc, **options: Any) -> Any:
+    """Diffs two runtime docs, given as parsed dicts or raw JSON.
+
+    `options` are the JSON options of the C ABI, e.g. `categories=["names", "types"]`,
+    `exact_types=True` or `format="flat"`. JSON formats are returned parsed, others as `str`.
+    """
+    return _diff("fapi_diff_runtime", source, target, options)
+
+
+def diff_prototype(source: Doc, target: Doc, **options: Any) -> Any:
+    """Diffs two prototype docs like `diff_runtime`."""
+    return _diff("fapi_diff_prototype", source, target, options)
@@ -0,0 +1,159 @@
+//! Diffs of many pairs of doc files at once, spread over all cores.
+
+use std::{
+    num::NonZeroUsize,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+};
+
+use serde::Serialize;
+
+use crate::{
+    format::{
+        self,
+        prototype::{PrototypeDoc, PrototypeDocDiff},
+        runtime::{RuntimeDoc, RuntimeDocDiff},
+        DiffOptions,
+    },
+    progress::Progress,
+    Comparison, Docs, Result,
+};
+
+/// Diff of two docs of either stage, serialized like the diff it holds.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum StageDiff {
+    Runtime(Box<RuntimeDocDiff>),
+    Prototype(Box<PrototypeDocDiff>),
+}
+
+impl StageDiff {
+    #[must_use]
+    pub const fn stage(&self) -> Docs {
+        match self {
+            Self::Runtime(_) => Docs::Runtime,
+            Self::Prototype(_) => Docs::Prototype,
+        }
+    }
+
+    /// See [`RuntimeDocDiff::change_count`].
+    #[must_use]
+    pub fn change_count(&self) -> usize {
+        match self {
+            Self::Runtime(diff) => diff.change_count(),
+            Self::Prototype(diff) => diff.change_count(),
+        }
+    }
+}
+
+/// Reads and diffs the `(source, target)` doc files, the stage is read from the docs.
+fn diff_pair((source, target): &(PathBuf, PathBuf), options: DiffOptions) -> Result<StageDiff> {
+    let (source, target) = (std::fs::read(source)?, std::fs::read(target)?);
+    let stage = Docs::detect(&source)?;
+    stage.check_versions(&source, &target)?;
+
+    Ok(match stage {
+        Docs::Runtime => StageDiff::Runtime(Box::new(
+            Comparison::<RuntimeDoc>::new(&source, &target, options)?.diff,
+        )),
+        Docs::Prototype => StageDiff::Prototype(Box::new(
+            Comparison::<PrototypeDoc>::new(&source, &target, options)?.diff,
+        )),
+    })
+}
+
+/// Diffs each pair of doc files like [`diff_many_with`], returning the diffs in the order of the `pairs`.
+#[must_use]
+pub fn diff_many(pairs: &[(PathBuf, PathBuf)], options: &DiffOptions) -> Vec<Result<StageDiff>> {
+    let mut res = pairs.iter().map(|_| None).collect::<Vec<_>>();
+    diff_many_with(pairs, options, |i, diff| res[i] = Some(diff));
+
+    res.into_iter().flatten().collect()
+}
+
+/// Diffs each pair of doc files like [`diff_many_with_progress`] without reporting the progress.
+pub fn diff_many_with(
+    pairs: &[(PathBuf, PathBuf)],
+    options: &DiffOptions,
+    f: impl FnMut(usize, Result<StageDiff>),
+) {
+    diff_many_with_progress(pairs, options, |_| {}, f);
+}
+
+/// Message of a worker thread to the calling thread.
+enum Message {
+    Started(usize),
+    Done(usize, Result<StageDiff>),
+}
+
+/// Diffs each `(source, target)` pair of doc files on its own thread, at most one per core.
+///
+/// `f` is called on the calling thread with the index of each pair and its diff as soon as it's done,
+/// so the pairs finish in any order. At most one pair per thread is held in memory, threads wait for
+/// `f` before they read the next pair.
+///
+/// `progress` is called on the calling thread when a pair starts and finishes diffing,
+/// a pair finishes right before `f` is called with its diff.
+///
+/// The patterns of [`format::with_ignored`] and [`format::with_normalized`] of the calling thread
+/// apply to all pairs, warnings of all pairs are available from [`format::take_warnings`] afterwards.
+pub fn diff_many_with_progress(
+    pairs: &[(PathBuf, PathBuf)],
+    options: &DiffOptions,
+    mut progress: impl FnMut(Progress),
+    mut f: impl FnMut(usize, Result<StageDiff>),
+) {
+    let options = *options;
+    let patterns = format::Patterns::current();
+    let workers = std::thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(pairs.len());
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::sync_channel(workers);
+
+    let warnings = std::thread::scope(|s| {
+        let handles = (0..workers)
+            .map(|_| {
+                let (tx, patterns, next) = (tx.clone(), patterns.clone(), &next);
+
+                s.spawn(move || {
+                    patterns.apply(|| loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(pair) = pairs.get(i) else {
+                            break;
+                        };
+
+                        if tx.send(Message::Started(i)).is_err()
+                            || tx.send(Message::Done(i, diff_pair(pair, options))).is_err()
+                        {
+                            break;
+                        }
+                    });
+
+                    format::take_warnings()
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(tx);
+
+        for message in rx {
+            match message {
+                Message::Started(i) => progress(Progress::Started(i)),
+                Message::Done(i, diff) => {
+                    progress(Progress::Finished(i));
+                    f(i, diff);
+                }
+            }
+        }
+
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect::<Vec<_>>()
+    });
+
+    format::add_warnings(warnings);
+}
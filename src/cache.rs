@@ -0,0 +1,75 @@
+use std::{fmt::Write, path::PathBuf};
+
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+
+/// On disk cache of parsed docs, keyed by a hash of their raw content.
+///
+/// Docs are stored as `MessagePack`, the doc types rely on untagged and flattened
+/// serde representations which non self-describing formats can't handle.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Path of the cached doc, named after the SHA-256 of the raw content.
+    ///
+    /// Unlike `std`'s hashers the name is stable across Rust releases, so the cache survives toolchain updates.
+    #[must_use]
+    pub fn path<T>(&self, raw: &[u8]) -> PathBuf {
+        let mut hasher = Sha256::new();
+
+        // the layout of the cached docs may change between versions
+        for part in [env!("CARGO_PKG_VERSION"), std::any::type_name::<T>()] {
+            hasher.update(part);
+            hasher.update([0]);
+        }
+        hasher.update(raw);
+
+        let name = hasher
+            .finalize()
+            .iter()
+            .fold(String::with_capacity(64), |mut name, b| {
+                let _ = write!(name, "{b:02x}");
+                name
+            });
+
+        self.dir.join(format!("{name}.msgpack"))
+    }
+
+    /// Deserializes a raw JSON doc, using the cached version if the same doc was parsed before.
+    ///
+    /// Unreadable cache entries are ignored and replaced.
+    /// The strings of the doc are interned within an [`intern::scope`](crate::format::intern::scope)
+    /// whether it's cached or not.
+    pub fn load<T>(&self, raw: &[u8], what: &str) -> crate::Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        crate::format::intern::scope(|| {
+            let path = self.path::<T>(raw);
+
+            if let Ok(cached) = std::fs::read(&path) {
+                if let Ok(doc) = rmp_serde::from_slice(&cached) {
+                    return Ok(doc);
+                }
+            }
+
+            let doc = crate::parse(raw, what)?;
+
+            if let Ok(packed) = rmp_serde::to_vec_named(&doc) {
+                if std::fs::create_dir_all(&self.dir).is_ok() {
+                    std::fs::write(&path, packed).ok();
+                }
+            }
+
+            Ok(doc)
+        })
+    }
+}
@@ -0,0 +1,418 @@
+//! Shell completion scripts generated from the command line definition.
+//!
+//! Versions are completed dynamically with the versions cached by `fapi-diff versions`,
+//! see `fapi-diff versions --cached`.
+
+use std::fmt::Write as _;
+
+use clap::{Arg, Args, Command, ValueEnum};
+
+/// Ids of the positional arguments that take a version of the docs.
+const VERSION_ARGS: [&str; 3] = ["source", "target", "version"];
+
+/// Lists the versions for completions, errors are silenced as completions must not print them.
+const CACHED_VERSIONS: &str = "fapi-diff versions --cached";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+#[derive(Args, Clone)]
+pub struct CompletionsArgs {
+    /// Shell to generate the completion script for
+    #[clap(value_enum)]
+    pub shell: Shell,
+}
+
+/// An option of a subcommand.
+struct Opt {
+    /// `--long` and `-s` forms
+    flags: Vec<String>,
+    long: Option<String>,
+    short: Option<char>,
+    help: String,
+    takes_value: bool,
+    choices: Vec<String>,
+}
+
+/// A subcommand and what to complete for it.
+struct Sub {
+    /// The subcommand and the words that are a shorthand for it
+    names: Vec<String>,
+    help: String,
+    options: Vec<Opt>,
+    /// Possible values of the positional arguments
+    values: Vec<String>,
+    /// Whether a positional argument takes a version
+    versions: bool,
+}
+
+fn options(cmd: &Command) -> Vec<Opt> {
+    cmd.get_arguments()
+        .filter(|a| !a.is_positional() && !a.is_hide_set())
+        .map(|a| {
+            let long = a.get_long().map(str::to_owned);
+            let short = a.get_short();
+
+            Opt {
+                flags: long
+                    .iter()
+                    .map(|l| format!("--{l}"))
+                    .chain(short.map(|s| format!("-{s}")))
+                    .collect(),
+                long,
+                short,
+                help: help(a),
+                takes_value: a.get_action().takes_values(),
+                choices: choices(a),
+            }
+        })
+        .collect()
+}
+
+fn choices(arg: &Arg) -> Vec<String> {
+    arg.get_possible_values()
+        .iter()
+        .filter(|v| !v.is_hide_set())
+        .map(|v| v.get_name().to_owned())
+        .collect()
+}
+
+/// First line of the help of an argument.
+fn help(arg: &Arg) -> String {
+    arg.get_help()
+        .map(ToString::to_string)
+        .unwrap_or_default()
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_owned()
+}
+
+/// The visible subcommands, `compare` is also used for its `<stage>` shorthand.
+fn subcommands(cli: &Command) -> Vec<Sub> {
+    cli.get_subcommands()
+        .filter(|s| !s.is_hide_set())
+        .map(|s| {
+            let mut names = vec![s.get_name().to_owned()];
+            if s.get_name() == "compare" {
+                names.extend(stages());
+            }
+
+            let positionals = s
+                .get_arguments()
+                .filter(|a| a.is_positional())
+                .collect::<Vec<_>>();
+
+            Sub {
+                names,
+                help: s.get_about().map(ToString::to_string).unwrap_or_default(),
+                options: options(s),
+                values: positionals.iter().flat_map(|a| choices(a)).collect(),
+                versions: positionals
+                    .iter()
+                    .any(|a| VERSION_ARGS.contains(&a.get_id().as_str())),
+            }
+        })
+        .collect()
+}
+
+fn stages() -> Vec<String> {
+    fapi_diff::Docs::value_variants()
+        .iter()
+        .filter_map(|d| Some(d.to_possible_value()?.get_name().to_owned()))
+        .collect()
+}
+
+/// The completion script of `shell` for the command line `cli`.
+pub fn generate(shell: Shell, cli: &mut Command) -> String {
+    cli.build();
+
+    let top = options(cli);
+    let subs = subcommands(cli);
+    // words after these options are their values rather than the subcommand
+    let globals = top
+        .iter()
+        .filter(|o| o.takes_value)
+        .flat_map(|o| o.flags.clone())
+        .collect::<Vec<_>>();
+
+    match shell {
+        Shell::Bash => bash(&top, &subs, &globals),
+        Shell::Zsh => zsh(&top, &subs, &globals),
+        Shell::Fish => fish(&top, &subs),
+        Shell::Powershell => powershell(&top, &subs, &globals),
+    }
+}
+
+/// Words completed in place of the subcommand: the subcommands, their shorthands and the global options.
+fn top_words(top: &[Opt], subs: &[Sub]) -> Vec<String> {
+    subs.iter()
+        .flat_map(|s| s.names.clone())
+        .chain(top.iter().flat_map(|o| o.flags.clone()))
+        .collect()
+}
+
+fn bash(top: &[Opt], subs: &[Sub], globals: &[String]) -> String {
+    let mut out = String::new();
+
+    out.push_str("_fapi_diff() {\n");
+    out.push_str("    local cur=\"${COMP_WORDS[COMP_CWORD]}\" prev=\"${COMP_WORDS[COMP_CWORD-1]}\" cmd=\"\" i\n\n");
+    out.push_str("    for ((i = 1; i < COMP_CWORD; i++)); do\n");
+    out.push_str("        case \"${COMP_WORDS[i]}\" in\n");
+    if !globals.is_empty() {
+        let _ = writeln!(out, "            {}) ((i++)) ;;", globals.join("|"));
+    }
+    out.push_str("            -*) ;;\n");
+    out.push_str("            *) cmd=\"${COMP_WORDS[i]}\"; break ;;\n");
+    out.push_str("        esac\n    done\n\n");
+    out.push_str("    case \"$cmd\" in\n");
+    let _ = writeln!(
+        out,
+        "        \"\")\n            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n            ;;",
+        top_words(top, subs).join(" ")
+    );
+
+    for sub in subs {
+        let _ = writeln!(out, "        {})", sub.names.join("|"));
+        out.push_str("            case \"$prev\" in\n");
+        for opt in sub.options.iter().filter(|o| o.takes_value) {
+            let reply = if opt.choices.is_empty() {
+                "compgen -f -- \"$cur\"".to_owned()
+            } else {
+                format!("compgen -W \"{}\" -- \"$cur\"", opt.choices.join(" "))
+            };
+            let _ = writeln!(
+                out,
+                "                {}) COMPREPLY=($({reply})); return ;;",
+                opt.flags.join("|")
+            );
+        }
+        out.push_str("            esac\n\n");
+        let _ = writeln!(
+            out,
+            "            if [[ \"$cur\" == -* ]]; then\n                COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n                return\n            fi\n",
+            sub.options.iter().flat_map(|o| o.flags.clone()).collect::<Vec<_>>().join(" ")
+        );
+        let versions = if sub.versions {
+            format!(" $({CACHED_VERSIONS} 2>/dev/null)")
+        } else {
+            String::new()
+        };
+        let _ = writeln!(
+            out,
+            "            COMPREPLY=($(compgen -W \"{}{versions}\" -- \"$cur\") $(compgen -f -- \"$cur\"))\n            ;;",
+            sub.values.join(" ")
+        );
+    }
+
+    out.push_str("    esac\n}\n\ncomplete -F _fapi_diff fapi-diff\n");
+    out
+}
+
+fn zsh(top: &[Opt], subs: &[Sub], globals: &[String]) -> String {
+    let mut out = String::new();
+
+    out.push_str("#compdef fapi-diff\n\n_fapi-diff() {\n");
+    out.push_str("    local cmd=\"\" prev=\"${words[CURRENT-1]}\" i\n\n");
+    out.push_str("    for ((i = 2; i < CURRENT; i++)); do\n");
+    out.push_str("        case \"${words[i]}\" in\n");
+    if !globals.is_empty() {
+        let _ = writeln!(out, "            {}) ((i++)) ;;", globals.join("|"));
+    }
+    out.push_str("            -*) ;;\n");
+    out.push_str("            *) cmd=\"${words[i]}\"; break ;;\n");
+    out.push_str("        esac\n    done\n\n");
+    out.push_str("    case \"$cmd\" in\n");
+    let _ = writeln!(
+        out,
+        "        \"\")\n            compadd -- {}\n            ;;",
+        top_words(top, subs).join(" ")
+    );
+
+    for sub in subs {
+        let _ = writeln!(out, "        {})", sub.names.join("|"));
+        out.push_str("            case \"$prev\" in\n");
+        for opt in sub.options.iter().filter(|o| o.takes_value) {
+            let reply = if opt.choices.is_empty() {
+                "_files".to_owned()
+            } else {
+                format!("compadd -- {}", opt.choices.join(" "))
+            };
+            let _ = writeln!(
+                out,
+                "                {}) {reply}; return ;;",
+                opt.flags.join("|")
+            );
+        }
+        out.push_str("            esac\n\n");
+        let _ = writeln!(
+            out,
+            "            if [[ \"$PREFIX\" == -* ]]; then\n                compadd -- {}\n                return\n            fi\n",
+            sub.options.iter().flat_map(|o| o.flags.clone()).collect::<Vec<_>>().join(" ")
+        );
+        let versions = if sub.versions {
+            format!(" $({CACHED_VERSIONS} 2>/dev/null)")
+        } else {
+            String::new()
+        };
+        let _ = writeln!(
+            out,
+            "            compadd -- {}{versions}\n            _files\n            ;;",
+            sub.values.join(" ")
+        );
+    }
+
+    out.push_str("    esac\n}\n\n");
+    out.push_str("if [ \"$funcstack[1]\" = \"_fapi-diff\" ]; then\n    _fapi-diff \"$@\"\nelse\n    compdef _fapi-diff fapi-diff\nfi\n");
+    out
+}
+
+fn fish(top: &[Opt], subs: &[Sub]) -> String {
+    let quote = |s: &str| format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"));
+    let option = |out: &mut String, condition: &str, opt: &Opt| {
+        let _ = write!(out, "complete -c fapi-diff -n {}", quote(condition));
+        if let Some(long) = &opt.long {
+            let _ = write!(out, " -l {long}");
+        }
+        if let Some(short) = opt.short {
+            let _ = write!(out, " -s {short}");
+        }
+        if opt.takes_value {
+            if opt.choices.is_empty() {
+                out.push_str(" -rF");
+            } else {
+                let _ = write!(out, " -xa {}", quote(&opt.choices.join(" ")));
+            }
+        }
+        let _ = writeln!(out, " -d {}", quote(&opt.help));
+    };
+
+    let mut out = String::from("complete -c fapi-diff -f\n");
+
+    for opt in top {
+        option(&mut out, "__fish_use_subcommand", opt);
+    }
+    for sub in subs {
+        let (name, shorthands) = sub
+            .names
+            .split_first()
+            .map_or(("", &[][..]), |(n, s)| (n, s));
+        let _ = writeln!(
+            out,
+            "complete -c fapi-diff -n __fish_use_subcommand -a {name} -d {}",
+            quote(&sub.help)
+        );
+        for shorthand in shorthands {
+            let _ = writeln!(
+                out,
+                "complete -c fapi-diff -n __fish_use_subcommand -a {shorthand} -d {}",
+                quote(&format!("{name} {shorthand}"))
+            );
+        }
+    }
+
+    for sub in subs {
+        let condition = format!("__fish_seen_subcommand_from {}", sub.names.join(" "));
+
+        for opt in &sub.options {
+            option(&mut out, &condition, opt);
+        }
+
+        let mut values = sub.values.join(" ");
+        if sub.versions {
+            let _ = write!(values, " ({CACHED_VERSIONS} 2>/dev/null)");
+        }
+        let _ = writeln!(
+            out,
+            "complete -c fapi-diff -n {} -a {}",
+            quote(&condition),
+            quote(values.trim())
+        );
+        let _ = writeln!(out, "complete -c fapi-diff -n {} -F", quote(&condition));
+    }
+
+    out
+}
+
+fn powershell(top: &[Opt], subs: &[Sub], globals: &[String]) -> String {
+    let list = |words: &[String]| {
+        words
+            .iter()
+            .map(|w| format!("'{}'", w.replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut out = String::new();
+
+    out.push_str("Register-ArgumentCompleter -Native -CommandName 'fapi-diff' -ScriptBlock {\n");
+    out.push_str("    param($wordToComplete, $commandAst, $cursorPosition)\n\n");
+    out.push_str("    $words = @($commandAst.CommandElements | Where-Object { $_.Extent.EndOffset -lt $cursorPosition } | ForEach-Object { $_.ToString() })\n");
+    out.push_str("    $prev = $words[-1]\n    $cmd = ''\n");
+    out.push_str("    for ($i = 1; $i -lt $words.Count; $i++) {\n");
+    let _ = writeln!(
+        out,
+        "        if (@({}) -contains $words[$i]) {{ $i++; continue }}",
+        list(globals)
+    );
+    out.push_str("        if ($words[$i].StartsWith('-')) { continue }\n");
+    out.push_str("        $cmd = $words[$i]\n        break\n    }\n\n");
+    let _ = writeln!(
+        out,
+        "    $candidates = @()\n    if ($cmd -eq '') {{\n        $candidates = @({})\n    }}",
+        list(&top_words(top, subs))
+    );
+
+    for sub in subs {
+        let _ = writeln!(
+            out,
+            "    elseif (@({}) -contains $cmd) {{",
+            list(&sub.names)
+        );
+        let mut first = true;
+        for opt in sub.options.iter().filter(|o| o.takes_value) {
+            let keyword = if first { "if" } else { "elseif" };
+            first = false;
+            // options without choices fall back to the file completion of the shell
+            let _ = writeln!(
+                out,
+                "        {keyword} (@({}) -contains $prev) {{ $candidates = @({}) }}",
+                list(&opt.flags),
+                list(&opt.choices)
+            );
+        }
+        let keyword = if first { "if" } else { "elseif" };
+        let _ = writeln!(
+            out,
+            "        {keyword} ($wordToComplete.StartsWith('-')) {{ $candidates = @({}) }}",
+            list(
+                &sub.options
+                    .iter()
+                    .flat_map(|o| o.flags.clone())
+                    .collect::<Vec<_>>()
+            )
+        );
+        let versions = if sub.versions {
+            format!(" + @(& {CACHED_VERSIONS} 2>$null)")
+        } else {
+            String::new()
+        };
+        let _ = writeln!(
+            out,
+            "        else {{ $candidates = @({}){versions} }}\n    }}",
+            list(&sub.values)
+        );
+    }
+
+    out.push_str(
+        "\n    $candidates | Where-Object { $_ -like \"$wordToComplete*\" } | ForEach-Object {\n",
+    );
+    out.push_str("        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)\n");
+    out.push_str("    }\n}\n");
+    out
+}
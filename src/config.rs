@@ -0,0 +1,156 @@
+//! Defaults for the options of the subcommands, read from a `fapi-diff.toml` file.
+//!
+//! The file has a table per subcommand whose keys are the long names of its options:
+//!
+//! ```toml
+//! [compare]
+//! format = "markdown"
+//! full = true
+//! ignore = ["classes/LuaGuiElement/.*"]
+//! cache-dir = ".cache/fapi-diff"
+//! ```
+//!
+//! Options given on the command line take precedence over the file, also over the options of the file they
+//! conflict with or override, like `--pretty` over `compact = true`.
+
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context as _, Result};
+use clap::parser::ValueSource;
+
+/// Name of the config file looked up in the working directory.
+pub const FILE_NAME: &str = "fapi-diff.toml";
+
+/// The config file passed with `--config`, else `fapi-diff.toml` in the working directory if it exists.
+pub fn find(args: &[OsString]) -> Option<PathBuf> {
+    let mut args = args.iter().filter_map(|a| a.to_str());
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+
+    Some(PathBuf::from(FILE_NAME)).filter(|p| p.is_file())
+}
+
+/// Adds the options of the config file at `path` for the subcommand of `args` that `args` don't set.
+pub fn apply(path: &Path, command: &clap::Command, args: &mut Vec<OsString>) -> Result<()> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read the config file {}", path.display()))?;
+    let config = toml::from_str::<toml::Table>(&raw)
+        .with_context(|| format!("Invalid config file {}", path.display()))?;
+
+    // invalid arguments are reported when the arguments are parsed again
+    let Ok(matches) = command.clone().try_get_matches_from(args.iter()) else {
+        return Ok(());
+    };
+    let Some((name, matches)) = matches.subcommand() else {
+        return Ok(());
+    };
+    let Some(options) = config.get(name) else {
+        return Ok(());
+    };
+    let (Some(options), Some(subcommand)) = (options.as_table(), command.find_subcommand(name))
+    else {
+        bail!("[{name}] of the config file must be a table of options");
+    };
+
+    let given = given(subcommand, matches);
+    let mut defaults = Vec::new();
+    for (key, value) in options {
+        let id = key.replace('-', "_");
+        let Some(long) = subcommand
+            .get_arguments()
+            .find(|a| a.get_id() == id.as_str())
+            .and_then(clap::Arg::get_long)
+        else {
+            bail!("Unknown option `{key}` of `{name}` in the config file");
+        };
+
+        if matches.value_source(&id) == Some(ValueSource::CommandLine) {
+            continue;
+        }
+
+        let values = match value {
+            toml::Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        };
+        let mut option = Vec::new();
+        for value in values {
+            let value = match value {
+                toml::Value::Boolean(true) => {
+                    option.push(OsString::from(format!("--{long}")));
+                    continue;
+                }
+                toml::Value::Boolean(false) => continue,
+                toml::Value::String(s) => s.clone(),
+                toml::Value::Integer(i) => i.to_string(),
+                toml::Value::Float(f) => f.to_string(),
+                _ => bail!("Unsupported value of `{key}` of `{name}` in the config file"),
+            };
+            option.push(OsString::from(format!("--{long}={value}")));
+        }
+
+        if !displaces_command_line(command, subcommand, &given, args, &option) {
+            defaults.extend(option);
+        }
+    }
+
+    // options with optional values would take the next argument as their value,
+    // so the defaults go after all other options but before the arguments after `--`
+    insert(args, defaults);
+
+    Ok(())
+}
+
+/// Inserts `options` after all other options but before the arguments after `--`,
+/// options with optional values would take the next argument as their value.
+fn insert(args: &mut Vec<OsString>, options: impl IntoIterator<Item = OsString>) {
+    let end = args.iter().position(|a| a == "--").unwrap_or(args.len());
+    args.splice(end..end, options);
+}
+
+/// Ids of the options of `subcommand` given on the command line.
+fn given(subcommand: &clap::Command, matches: &clap::ArgMatches) -> Vec<clap::Id> {
+    subcommand
+        .get_arguments()
+        .map(clap::Arg::get_id)
+        .filter(|id| matches.value_source(id.as_str()) == Some(ValueSource::CommandLine))
+        .cloned()
+        .collect()
+}
+
+/// Whether adding `option` to `args` would conflict with or override one of the `given` options,
+/// like `compact = true` in the file with `--pretty` on the command line.
+fn displaces_command_line(
+    command: &clap::Command,
+    subcommand: &clap::Command,
+    given: &[clap::Id],
+    args: &[OsString],
+    option: &[OsString],
+) -> bool {
+    let mut with_option = args.to_vec();
+    insert(&mut with_option, option.iter().cloned());
+
+    match command.clone().try_get_matches_from(with_option) {
+        Ok(matches) => {
+            let after = matches
+                .subcommand()
+                .map(|(_, m)| self::given(subcommand, m))
+                .unwrap_or_default();
+
+            given.iter().any(|id| !after.contains(id))
+        }
+        Err(e) => e.kind() == clap::error::ErrorKind::ArgumentConflict,
+    }
+}
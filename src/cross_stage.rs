@@ -0,0 +1,96 @@
+//! Consistency checks between the runtime and the prototype docs of the same versions.
+//!
+//! Many names are documented in both stages, e.g. `MapPosition` is a runtime concept and a prototype type.
+//! When only one of them changes between two versions, the docs likely went out of sync.
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+use crate::{
+    format::{prototype::PrototypeDoc, runtime::RuntimeDoc, ItemStatus},
+    Comparison, Docs,
+};
+
+/// A name documented in both stages that changed in only one of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Inconsistency {
+    pub name: String,
+
+    /// Stage whose item changed
+    #[serde(serialize_with = "serialize_display")]
+    pub changed_in: Docs,
+
+    /// How the item changed in that stage
+    pub status: ItemStatus,
+}
+
+fn serialize_display<S: serde::Serializer>(
+    value: &impl std::fmt::Display,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+/// How an item changed between two docs, given whether it's in the source and the target.
+const fn status(in_source: bool, in_target: bool) -> ItemStatus {
+    match (in_source, in_target) {
+        (true, false) => ItemStatus::Removed,
+        (false, true) => ItemStatus::Added,
+        _ => ItemStatus::Changed,
+    }
+}
+
+/// Finds the runtime concepts and prototype types of the same name that changed in only one of the stages.
+///
+/// Sorted by name.
+#[must_use]
+pub fn check(
+    runtime: &Comparison<RuntimeDoc>,
+    prototype: &Comparison<PrototypeDoc>,
+) -> Vec<Inconsistency> {
+    let concepts = runtime
+        .source
+        .concepts
+        .keys()
+        .chain(runtime.target.concepts.keys())
+        .collect::<BTreeSet<_>>();
+    let types = prototype
+        .source
+        .types
+        .keys()
+        .chain(prototype.target.types.keys())
+        .collect::<BTreeSet<_>>();
+
+    concepts
+        .intersection(&types)
+        .filter_map(|name| {
+            let (changed_in, status) = match (
+                runtime.diff.concepts.contains_key(*name),
+                prototype.diff.types.contains_key(*name),
+            ) {
+                (true, false) => (
+                    Docs::Runtime,
+                    status(
+                        runtime.source.concepts.contains_key(*name),
+                        runtime.target.concepts.contains_key(*name),
+                    ),
+                ),
+                (false, true) => (
+                    Docs::Prototype,
+                    status(
+                        prototype.source.types.contains_key(*name),
+                        prototype.target.types.contains_key(*name),
+                    ),
+                ),
+                _ => return None,
+            };
+
+            Some(Inconsistency {
+                name: (*name).clone(),
+                changed_in,
+                status,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,186 @@
+//! SQL script that builds a relational database of docs and the changes between their versions.
+//!
+//! The script is meant for the `sqlite3` shell, `fapi-diff export-db` pipes it into it or writes it with `--script`.
+//! It recreates its tables, so it can be run against an existing database again.
+
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::render::{csv, tree::Node};
+
+/// Sections of the docs whose entries are rows of the `items` table.
+const SECTIONS: [&str; 8] = [
+    "classes",
+    "events",
+    "concepts",
+    "defines",
+    "global_objects",
+    "global_functions",
+    "prototypes",
+    "types",
+];
+
+const SCHEMA: &str = "\
+DROP TABLE IF EXISTS docs;
+DROP TABLE IF EXISTS items;
+DROP TABLE IF EXISTS members;
+DROP TABLE IF EXISTS changes;
+
+CREATE TABLE docs (
+    version TEXT NOT NULL,
+    stage TEXT NOT NULL,
+    api_version INTEGER NOT NULL,
+    PRIMARY KEY (version, stage)
+);
+
+CREATE TABLE items (
+    version TEXT NOT NULL,
+    stage TEXT NOT NULL,
+    category TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    data TEXT NOT NULL,
+    PRIMARY KEY (version, stage, category, name)
+);
+
+CREATE TABLE members (
+    version TEXT NOT NULL,
+    stage TEXT NOT NULL,
+    category TEXT NOT NULL,
+    item TEXT NOT NULL,
+    list TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    data TEXT NOT NULL,
+    PRIMARY KEY (version, stage, category, item, list, name)
+);
+
+CREATE TABLE changes (
+    from_version TEXT NOT NULL,
+    to_version TEXT NOT NULL,
+    stage TEXT NOT NULL,
+    path TEXT NOT NULL,
+    category TEXT NOT NULL,
+    item TEXT NOT NULL,
+    member TEXT NOT NULL,
+    field TEXT NOT NULL,
+    change_kind TEXT NOT NULL,
+    old TEXT,
+    new TEXT
+);
+
+CREATE INDEX changes_item ON changes (category, item, member);
+
+BEGIN;
+";
+
+/// Builder of the SQL script, see the [module docs](self).
+pub struct Database {
+    sql: String,
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Self {
+            sql: SCHEMA.to_owned(),
+        }
+    }
+}
+
+/// SQL literal of a text.
+fn text(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// SQL literal of a JSON value, strings are stored as they are, other values as JSON and missing values as `NULL`.
+fn json(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_owned(),
+        v => text(&csv::value(v)),
+    }
+}
+
+/// Named entries of a list of the docs.
+fn named(list: Option<&Value>) -> impl Iterator<Item = (&str, &Value)> {
+    list.and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|v| Some((v.get("name")?.as_str()?, v)))
+}
+
+impl Database {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the items and their members of a serialized doc of `version`.
+    pub fn add_doc(&mut self, version: &str, doc: &Value) {
+        let stage = doc.get("stage").and_then(Value::as_str).unwrap_or_default();
+        let api_version = doc.get("api_version").and_then(Value::as_u64).unwrap_or(0);
+        let (version, stage) = (text(version), text(stage));
+
+        let _ = writeln!(
+            self.sql,
+            "INSERT INTO docs VALUES ({version}, {stage}, {api_version});"
+        );
+
+        for category in SECTIONS {
+            for (name, item) in named(doc.get(category)) {
+                let _ = writeln!(
+                    self.sql,
+                    "INSERT OR REPLACE INTO items VALUES ({version}, {stage}, {}, {}, {}, {});",
+                    text(category),
+                    text(name),
+                    json(item.get("description").unwrap_or(&Value::Null)),
+                    text(&item.to_string()),
+                );
+
+                for list in csv::MEMBERS {
+                    for (member, value) in named(item.get(list)) {
+                        let _ = writeln!(
+                            self.sql,
+                            "INSERT OR REPLACE INTO members VALUES ({version}, {stage}, {}, {}, {}, {}, {}, {});",
+                            text(category),
+                            text(name),
+                            text(list),
+                            text(member),
+                            json(value.get("description").unwrap_or(&Value::Null)),
+                            text(&value.to_string()),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the changes of the `stage` docs from version `from` to version `to`.
+    pub fn add_changes(&mut self, from: &str, to: &str, stage: crate::Docs, diff: &Node) {
+        let (from, to, stage) = (text(from), text(to), text(&stage.to_string()));
+
+        for change in diff.changes() {
+            let [category, item, member, field] = csv::columns(&change.path);
+
+            let _ = writeln!(
+                self.sql,
+                "INSERT INTO changes VALUES ({from}, {to}, {stage}, {}, {}, {}, {}, {}, '{}', {}, {});",
+                text(&change.path.join("/")),
+                text(&category),
+                text(&item),
+                text(&member),
+                text(&field),
+                csv::change_kind(&change.old, &change.new),
+                json(&change.old),
+                json(&change.new),
+            );
+        }
+    }
+
+    /// The complete script.
+    #[must_use]
+    pub fn finish(mut self) -> String {
+        self.sql.push_str("COMMIT;\n");
+        self.sql
+    }
+}
@@ -0,0 +1,245 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    format::{runtime::Define, Common, ItemStatus},
+    Docs, Error,
+};
+
+/// The defines of a doc of any stage.
+#[derive(Deserialize)]
+struct Defines {
+    #[serde(default)]
+    defines: Vec<Define>,
+}
+
+/// Reads the full Lua paths of all define values of a raw doc of any stage.
+pub fn parse(raw: &[u8], what: &str) -> Result<BTreeSet<String>> {
+    Ok(paths(&crate::parse::<Defines>(raw, what)?.defines))
+}
+
+/// Full Lua paths of all define values, e.g. `defines.events.on_tick`.
+#[must_use]
+pub fn paths<'a>(defines: impl IntoIterator<Item = &'a Define>) -> BTreeSet<String> {
+    fn walk<'a>(
+        res: &mut BTreeSet<String>,
+        prefix: &str,
+        defines: impl IntoIterator<Item = &'a Define>,
+    ) {
+        for define in defines {
+            let path = format!("{prefix}.{}", define.name);
+
+            res.extend(define.values.keys().map(|v| format!("{path}.{v}")));
+            walk(res, &path, define.subkeys.values());
+        }
+    }
+
+    let mut res = BTreeSet::new();
+    walk(&mut res, "defines", defines);
+    res
+}
+
+/// Define values documented in only one of the stages of the same version, sorted by path.
+///
+/// Both stages document the same defines, so these are usually bugs of the docs.
+#[derive(Debug, Default, Serialize)]
+pub struct StageMismatch {
+    pub runtime_only: Vec<String>,
+    pub prototype_only: Vec<String>,
+}
+
+impl StageMismatch {
+    /// Compares the define values of the raw runtime and prototype docs of the same version.
+    ///
+    /// Fails if the docs aren't of these stages, of different versions or if one of them has no
+    /// defines at all, like the prototype docs before 2.0.
+    pub fn check(runtime: &[u8], prototype: &[u8]) -> Result<Self> {
+        let header = |raw: &[u8], stage: Docs| -> Result<Common> {
+            let what = format!("{stage} doc");
+            let header = crate::parse::<Common>(raw, &what)?;
+
+            let found = Docs::from(header.stage.clone());
+            if found != stage {
+                return Err(Error::StageMismatch {
+                    what,
+                    found,
+                    expected: stage,
+                }
+                .into());
+            }
+
+            Ok(header)
+        };
+
+        let (runtime_header, prototype_header) = (
+            header(runtime, Docs::Runtime)?,
+            header(prototype, Docs::Prototype)?,
+        );
+        if runtime_header.application_version != prototype_header.application_version {
+            bail!(
+                "the runtime doc is of version {} but the prototype doc of version {}",
+                runtime_header.application_version,
+                prototype_header.application_version
+            );
+        }
+
+        let (runtime, prototype) = (
+            parse(runtime, "runtime doc")?,
+            parse(prototype, "prototype doc")?,
+        );
+        for (stage, paths) in [(Docs::Runtime, &runtime), (Docs::Prototype, &prototype)] {
+            if paths.is_empty() {
+                bail!("the {stage} doc has no defines to compare");
+            }
+        }
+
+        Ok(Self {
+            runtime_only: runtime.difference(&prototype).cloned().collect(),
+            prototype_only: prototype.difference(&runtime).cloned().collect(),
+        })
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.runtime_only.is_empty() && self.prototype_only.is_empty()
+    }
+}
+
+impl fmt::Display for StageMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for path in &self.runtime_only {
+            writeln!(f, "runtime only:   {path}")?;
+        }
+
+        for path in &self.prototype_only {
+            writeln!(f, "prototype only: {path}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Define values that were added or removed, sorted by path.
+#[derive(Debug, Default)]
+pub struct DefinesDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl DefinesDiff {
+    #[must_use]
+    pub fn new(source: &BTreeSet<String>, target: &BTreeSet<String>) -> Self {
+        Self {
+            added: target.difference(source).cloned().collect(),
+            removed: source.difference(target).cloned().collect(),
+        }
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl fmt::Display for DefinesDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for path in &self.added {
+            writeln!(f, "+ {path}")?;
+        }
+
+        for path in &self.removed {
+            writeln!(f, "- {path}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Full Lua paths of all defines and define values, including the defines with subkeys.
+fn all_paths(defines: &Value) -> BTreeSet<String> {
+    fn walk<'a>(
+        res: &mut BTreeSet<String>,
+        prefix: &str,
+        defines: impl IntoIterator<Item = &'a Define>,
+    ) {
+        for define in defines {
+            let path = format!("{prefix}.{}", define.name);
+
+            res.extend(define.values.keys().map(|v| format!("{path}.{v}")));
+            walk(res, &path, define.subkeys.values());
+            res.insert(path);
+        }
+    }
+
+    let defines = Vec::<Define>::deserialize(defines).unwrap_or_default();
+    let mut res = BTreeSet::new();
+    walk(&mut res, "defines", &defines);
+    res
+}
+
+/// Flattens the nested `defines` section of a serialized diff into the changed defines and define values,
+/// keyed by their full Lua path like `defines.events.on_tick`.
+///
+/// Defines are only listed as changed if their own fields changed, changes of their values are listed separately.
+/// Whether items were added or removed is looked up in the serialized `source` and `target` docs.
+#[must_use]
+pub fn flatten(diff: &Value, source: &Value, target: &Value) -> BTreeMap<String, ItemStatus> {
+    fn walk(
+        res: &mut BTreeMap<String, ItemStatus>,
+        status: &impl Fn(&str) -> ItemStatus,
+        prefix: &str,
+        defines: &Value,
+    ) {
+        let Some(defines) = defines.as_object() else {
+            return;
+        };
+
+        for (name, changes) in defines {
+            let path = format!("{prefix}.{name}");
+            let mut changed = false;
+
+            for (field, change) in changes
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_object)
+                .flatten()
+            {
+                match field.as_str() {
+                    "values" => {
+                        for value in change.as_object().into_iter().flat_map(|v| v.keys()) {
+                            let path = format!("{path}.{value}");
+                            res.insert(path.clone(), status(&path));
+                        }
+                    }
+                    "subkeys" => walk(res, status, &path, change),
+                    _ => changed = true,
+                }
+            }
+
+            match status(&path) {
+                ItemStatus::Changed if !changed => {}
+                status => {
+                    res.insert(path, status);
+                }
+            }
+        }
+    }
+
+    let (source, target) = (all_paths(&source["defines"]), all_paths(&target["defines"]));
+    let status = |path: &str| match (source.contains(path), target.contains(path)) {
+        (false, true) => ItemStatus::Added,
+        (true, false) => ItemStatus::Removed,
+        _ => ItemStatus::Changed,
+    };
+
+    let mut res = BTreeMap::new();
+    walk(&mut res, &status, "defines", &diff["defines"]);
+    res
+}
@@ -0,0 +1,60 @@
+//! Options shared by the bindings that take a diff request as plain strings.
+
+use serde::Deserialize;
+
+use crate::{
+    format::{Context, DiffOptions, FieldCategory, FieldPolicy},
+    render::OutputFormat,
+};
+
+/// Options of a diff, passed as a JSON object.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Field categories to diff, the CLI defaults are used if not specified
+    categories: Option<Vec<FieldCategory>>,
+    exact_types: bool,
+    inherit: bool,
+    resolve_inheritance: bool,
+    metrics: bool,
+    anchor_links: bool,
+    context: Context,
+    word_diff: bool,
+    keep_link_changes: bool,
+    directional: bool,
+    pub format: OutputFormat,
+    pub with_old: bool,
+}
+
+impl Options {
+    /// Parses the options, `None` or an empty string are the defaults.
+    pub fn parse(raw: Option<&str>) -> serde_json::Result<Self> {
+        match raw {
+            Some(raw) if !raw.trim().is_empty() => serde_json::from_str(raw),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn diff_options(&self) -> DiffOptions {
+        let policy = self
+            .categories
+            .as_ref()
+            .map_or_else(FieldPolicy::default, |c| {
+                c.iter().fold(FieldPolicy::none(), |p, c| p.include(*c))
+            });
+
+        DiffOptions {
+            policy,
+            exact_types: self.exact_types,
+            inherit: self.inherit,
+            resolve_inheritance: self.resolve_inheritance,
+            metrics: self.metrics,
+            anchor_links: self.anchor_links,
+            context: self.context,
+            word_diff: self.word_diff,
+            keep_link_changes: self.keep_link_changes,
+            directional: self.directional,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Errors of the library API.
+
+use crate::Docs;
+
+/// Why diffing or rendering docs failed.
+///
+/// The [`std::error::Error::source`] chain holds the underlying IO or JSON error.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// The common info header of a doc is missing or malformed, `what` names the doc.
+    #[error("Failed to get common info header from {what}: {source}")]
+    Header {
+        what: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// A doc doesn't match the expected schema, `what` names the doc.
+    ///
+    /// `path` is the JSON path of the offending value like `classes[42].methods[3].return_values[0].type`.
+    #[error(
+        "Failed to deserialize {what}{}: {source}",
+        .path.as_ref().map(|p| format!(" at {p}")).unwrap_or_default()
+    )]
+    Parse {
+        what: String,
+        path: Option<String>,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// A doc is of another stage than the one being diffed.
+    #[error("{what} is a {found} doc, not a {expected} doc")]
+    StageMismatch {
+        what: String,
+        found: Docs,
+        expected: Docs,
+    },
+
+    /// The api version of a doc isn't supported for its stage.
+    #[error(
+        "{what} api format is too {}! Only api version {supported} are supported",
+        if *.version < *.min { "old" } else { "new" }
+    )]
+    UnsupportedVersion {
+        what: String,
+        version: u8,
+        min: u8,
+        supported: &'static str,
+    },
+
+    /// A zip archive is malformed or doesn't contain the doc `file`.
+    #[error("Failed to read {file} from the archive: {reason}")]
+    Archive { file: String, reason: String },
+
+    /// A stored diff has a newer `diff_format_version` than this version of the crate writes.
+    #[error(
+        "{what} has diff format version {version}, only versions up to {supported} are supported"
+    )]
+    UnsupportedDiffFormat {
+        what: String,
+        version: u64,
+        supported: u32,
+    },
+
+    /// A partial doc can't be completed into a full doc, see [`crate::partial::complete`].
+    #[error("Failed to complete the partial {what}: {reason}")]
+    Partial { what: String, reason: String },
+
+    /// A query path doesn't lead to a value of the doc, `segment` is the first one that matched nothing.
+    #[error("Nothing found at {path}, {segment:?} doesn't match anything")]
+    UnknownPath { path: String, segment: String },
+
+    /// The source runtime doc has a newer api version than the target.
+    #[error("Source api format is newer than target api format")]
+    SourceNewer,
+
+    /// Serializing or rendering a diff failed.
+    #[error("Failed to render the diff: {0:#}")]
+    Render(anyhow::Error),
+}
+
+impl Error {
+    /// A [`Error::Parse`] of the raw doc `raw`, locating the offending value.
+    #[must_use]
+    pub fn parse(what: &str, raw: &[u8], source: serde_json::Error) -> Self {
+        Self::Parse {
+            what: what.to_owned(),
+            path: crate::json_path::locate(raw, source.line(), source.column()),
+            source,
+        }
+    }
+
+    /// Line and column of a JSON syntax or schema error in the doc, starting at 1.
+    #[must_use]
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::Header { source, .. } | Self::Parse { source, .. } if source.line() > 0 => {
+                Some((source.line(), source.column()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Render(e.into())
+    }
+}
+
+impl From<anyhow::Error> for Error {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Render(e)
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,252 @@
+//! Changes of the payloads of events and whether they break existing event handlers.
+
+use std::{collections::BTreeSet, fmt};
+
+use serde::Serialize;
+
+use crate::format::runtime::{ComplexType, Event, Parameter, RuntimeDoc, Type};
+
+/// How an event or a field of its payload changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadChange {
+    EventAdded,
+    EventRemoved,
+    /// `defines.events` gained a value for the event
+    IdAdded,
+    /// `defines.events` lost the value of the event, handlers can't be registered by it anymore
+    IdRemoved,
+    FieldAdded,
+    FieldRemoved,
+    /// The type lost some of its options, e.g. a union with fewer options
+    TypeNarrowed,
+    /// The type gained options, e.g. a union with more options
+    TypeWidened,
+    /// The type changed in any other way
+    TypeChanged,
+    NowOptional,
+    NowRequired,
+}
+
+impl PayloadChange {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::EventAdded => "event_added",
+            Self::EventRemoved => "event_removed",
+            Self::IdAdded => "id_added",
+            Self::IdRemoved => "id_removed",
+            Self::FieldAdded => "field_added",
+            Self::FieldRemoved => "field_removed",
+            Self::TypeNarrowed => "type_narrowed",
+            Self::TypeWidened => "type_widened",
+            Self::TypeChanged => "type_changed",
+            Self::NowOptional => "now_optional",
+            Self::NowRequired => "now_required",
+        }
+    }
+
+    /// Whether handlers written for the old payload can break.
+    #[must_use]
+    pub const fn is_breaking(self) -> bool {
+        matches!(
+            self,
+            Self::EventRemoved
+                | Self::IdRemoved
+                | Self::FieldRemoved
+                | Self::TypeNarrowed
+                | Self::TypeChanged
+                | Self::NowOptional
+        )
+    }
+}
+
+/// A single change of an event, `field` is empty for changes of the event itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EventChange {
+    pub event: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub field: String,
+    pub change: PayloadChange,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<String>,
+    pub breaking: bool,
+}
+
+/// Payload changes of all events between two runtime docs, sorted by event and field.
+#[derive(Debug, Default, Serialize)]
+pub struct EventsDiff {
+    pub changes: Vec<EventChange>,
+    /// Events with at least one breaking change
+    pub breaking: BTreeSet<String>,
+}
+
+/// Options of a type, the type itself if it isn't a union.
+fn options(ty: &Type) -> Vec<&Type> {
+    match ty.normalize() {
+        Type::Complex(c) => match c.as_ref() {
+            ComplexType::Union { options, .. } => options.iter().map(Type::normalize).collect(),
+            _ => vec![ty.normalize()],
+        },
+        ty @ Type::Simple(_) => vec![ty],
+    }
+}
+
+/// Short form of a type, simple types are their name and complex types compact JSON.
+fn describe(ty: &Type) -> String {
+    match ty.normalize() {
+        Type::Simple(s) => s.to_string(),
+        ty @ Type::Complex(_) => serde_json::to_string(ty).unwrap_or_default(),
+    }
+}
+
+impl EventsDiff {
+    #[must_use]
+    pub fn new(source: &RuntimeDoc, target: &RuntimeDoc) -> Self {
+        let mut res = Self::default();
+        let ids = |doc: &RuntimeDoc| {
+            crate::defines::paths(doc.defines.values())
+                .into_iter()
+                .filter_map(|p| p.strip_prefix("defines.events.").map(str::to_owned))
+                .collect::<BTreeSet<_>>()
+        };
+        let (source_ids, target_ids) = (ids(source), ids(target));
+
+        let names = source
+            .events
+            .keys()
+            .chain(target.events.keys())
+            .collect::<BTreeSet<_>>();
+
+        for name in names {
+            match (source.events.get(name), target.events.get(name)) {
+                (Some(_), None) => res.push(name, "", PayloadChange::EventRemoved, None, None),
+                (None, Some(_)) => res.push(name, "", PayloadChange::EventAdded, None, None),
+                (Some(old), Some(new)) => {
+                    match (source_ids.contains(name), target_ids.contains(name)) {
+                        (true, false) => res.push(name, "", PayloadChange::IdRemoved, None, None),
+                        (false, true) => res.push(name, "", PayloadChange::IdAdded, None, None),
+                        _ => {}
+                    }
+
+                    res.fields(name, old, new);
+                }
+                (None, None) => {}
+            }
+        }
+
+        res
+    }
+
+    fn fields(&mut self, event: &str, old: &Event, new: &Event) {
+        let fields = old
+            .data
+            .keys()
+            .chain(new.data.keys())
+            .collect::<BTreeSet<_>>();
+
+        for field in fields {
+            match (old.data.get(field), new.data.get(field)) {
+                (Some(old), None) => self.push(
+                    event,
+                    field,
+                    PayloadChange::FieldRemoved,
+                    Some(describe(&old.type_)),
+                    None,
+                ),
+                (None, Some(new)) => self.push(
+                    event,
+                    field,
+                    PayloadChange::FieldAdded,
+                    None,
+                    Some(describe(&new.type_)),
+                ),
+                (Some(old), Some(new)) => self.field(event, field, old, new),
+                (None, None) => {}
+            }
+        }
+    }
+
+    fn field(&mut self, event: &str, field: &str, old: &Parameter, new: &Parameter) {
+        if old.type_.normalize() != new.type_.normalize() {
+            let (old_options, new_options) = (options(&old.type_), options(&new.type_));
+            let change = if new_options.iter().all(|o| old_options.contains(o)) {
+                PayloadChange::TypeNarrowed
+            } else if old_options.iter().all(|o| new_options.contains(o)) {
+                PayloadChange::TypeWidened
+            } else {
+                PayloadChange::TypeChanged
+            };
+
+            self.push(
+                event,
+                field,
+                change,
+                Some(describe(&old.type_)),
+                Some(describe(&new.type_)),
+            );
+        }
+
+        if old.optional != new.optional {
+            let change = if new.optional {
+                PayloadChange::NowOptional
+            } else {
+                PayloadChange::NowRequired
+            };
+
+            self.push(event, field, change, None, None);
+        }
+    }
+
+    fn push(
+        &mut self,
+        event: &str,
+        field: &str,
+        change: PayloadChange,
+        old: Option<String>,
+        new: Option<String>,
+    ) {
+        if change.is_breaking() {
+            self.breaking.insert(event.to_owned());
+        }
+
+        self.changes.push(EventChange {
+            event: event.to_owned(),
+            field: field.to_owned(),
+            change,
+            old,
+            new,
+            breaking: change.is_breaking(),
+        });
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// One line per change like `! on_built_entity.stack: field_removed (LuaItemStack)`,
+/// breaking changes are marked with `!`.
+impl fmt::Display for EventsDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in &self.changes {
+            let mark = if change.breaking { '!' } else { ' ' };
+            let name = if change.field.is_empty() {
+                change.event.clone()
+            } else {
+                format!("{}.{}", change.event, change.field)
+            };
+            write!(f, "{mark} {name}: {}", change.change.as_str())?;
+            match (&change.old, &change.new) {
+                (Some(old), Some(new)) => writeln!(f, " ({old} → {new})")?,
+                (Some(value), None) | (None, Some(value)) => writeln!(f, " ({value})")?,
+                (None, None) => writeln!(f)?,
+            }
+        }
+
+        Ok(())
+    }
+}
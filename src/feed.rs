@@ -0,0 +1,89 @@
+use std::{fmt::Write as _, time::SystemTime};
+
+/// A single entry of an Atom feed, usually the summary of the diff between two versions.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub id: String,
+    pub title: String,
+    pub link: Option<String>,
+    pub updated: SystemTime,
+    /// Plain text content
+    pub content: String,
+}
+
+/// Renders an Atom feed with the given entries, in the order given.
+#[must_use]
+pub fn atom(id: &str, title: &str, entries: &[Entry]) -> String {
+    let updated = entries
+        .iter()
+        .map(|e| e.updated)
+        .max()
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    let mut res = String::new();
+    res.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    res.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    let _ = writeln!(res, "  <id>{}</id>", escape(id));
+    let _ = writeln!(res, "  <title>{}</title>", escape(title));
+    let _ = writeln!(res, "  <updated>{}</updated>", rfc3339(updated));
+    let _ = writeln!(
+        res,
+        "  <generator>{} {}</generator>",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    );
+
+    for entry in entries {
+        res.push_str("  <entry>\n");
+        let _ = writeln!(res, "    <id>{}</id>", escape(&entry.id));
+        let _ = writeln!(res, "    <title>{}</title>", escape(&entry.title));
+        let _ = writeln!(res, "    <updated>{}</updated>", rfc3339(entry.updated));
+
+        if let Some(link) = &entry.link {
+            let _ = writeln!(res, "    <link href=\"{}\"/>", escape(link));
+        }
+
+        let _ = writeln!(
+            res,
+            "    <content type=\"text\">{}</content>",
+            escape(&entry.content)
+        );
+        res.push_str("  </entry>\n");
+    }
+
+    res.push_str("</feed>\n");
+    res
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Formats a timestamp as UTC, e.g. `2024-06-03T12:00:00Z`.
+fn rfc3339(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // days to civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
@@ -0,0 +1,141 @@
+//! C ABI of the diff engine, for calling it in-process from other languages.
+//!
+//! All strings are NUL terminated UTF-8. Returned strings are owned by the caller
+//! and must be released with [`fapi_diff_free`]. On failure `NULL` is returned and
+//! [`fapi_diff_last_error`] describes the error. The declarations are in `fapi_diff.h`.
+
+#![allow(unsafe_code)]
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+};
+
+use anyhow::Result;
+
+use crate::{embed::Options, Docs};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Borrows a C string, `what` names it in the error message.
+///
+/// # Safety
+///
+/// `ptr` must be `NULL` or point to a NUL terminated string that outlives `'a`.
+unsafe fn str_arg<'a>(ptr: *const c_char, what: &str) -> Result<Option<&'a str>> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+
+    // SAFETY: upheld by the caller
+    match unsafe { CStr::from_ptr(ptr) }.to_str() {
+        Ok(s) => Ok(Some(s)),
+        Err(e) => anyhow::bail!("{what} is not valid UTF-8: {e}"),
+    }
+}
+
+/// Runs `f`, converting its result into an owned C string or `NULL` and the last error.
+fn respond(f: impl FnOnce() -> Result<String>) -> *mut c_char {
+    let res = f().and_then(|s| Ok(CString::new(s)?));
+
+    LAST_ERROR.with_borrow_mut(|last| match res {
+        Ok(s) => {
+            *last = None;
+            s.into_raw()
+        }
+        Err(e) => {
+            *last = CString::new(format!("{e:#}").replace('\0', "")).ok();
+            std::ptr::null_mut()
+        }
+    })
+}
+
+/// # Safety
+///
+/// See [`fapi_diff_runtime`].
+unsafe fn diff(
+    stage: Docs,
+    source: *const c_char,
+    target: *const c_char,
+    options: *const c_char,
+) -> *mut c_char {
+    respond(|| {
+        // SAFETY: upheld by the caller
+        let (source, target, options) = unsafe {
+            (
+                str_arg(source, "source")?,
+                str_arg(target, "target")?,
+                str_arg(options, "options")?,
+            )
+        };
+
+        let (Some(source), Some(target)) = (source, target) else {
+            anyhow::bail!("source and target must not be NULL");
+        };
+        let options = Options::parse(options)?;
+
+        Ok(stage.diff(
+            source.as_bytes(),
+            target.as_bytes(),
+            options.diff_options(),
+            options.format,
+            options.with_old,
+        )?)
+    })
+}
+
+/// Diffs two runtime docs.
+///
+/// `options` is `NULL` or a JSON object with the same fields as the options of the wasm `diff` function.
+///
+/// # Safety
+///
+/// `source`, `target` and `options` must each be `NULL` or point to a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn fapi_diff_runtime(
+    source: *const c_char,
+    target: *const c_char,
+    options: *const c_char,
+) -> *mut c_char {
+    // SAFETY: upheld by the caller
+    unsafe { diff(Docs::Runtime, source, target, options) }
+}
+
+/// Diffs two prototype docs, like [`fapi_diff_runtime`].
+///
+/// # Safety
+///
+/// See [`fapi_diff_runtime`].
+#[no_mangle]
+pub unsafe extern "C" fn fapi_diff_prototype(
+    source: *const c_char,
+    target: *const c_char,
+    options: *const c_char,
+) -> *mut c_char {
+    // SAFETY: upheld by the caller
+    unsafe { diff(Docs::Prototype, source, target, options) }
+}
+
+/// The error of the last failed call on this thread, or `NULL` if the last call succeeded.
+///
+/// The string is owned by the library and valid until the next call on this thread.
+#[no_mangle]
+pub extern "C" fn fapi_diff_last_error() -> *const c_char {
+    LAST_ERROR.with_borrow(|last| last.as_ref().map_or(std::ptr::null(), |e| e.as_ptr()))
+}
+
+/// Releases a string returned by the library, `NULL` is ignored.
+///
+/// # Safety
+///
+/// `ptr` must be `NULL` or a string returned by [`fapi_diff_runtime`] or
+/// [`fapi_diff_prototype`] that wasn't released yet.
+#[no_mangle]
+pub unsafe extern "C" fn fapi_diff_free(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        // SAFETY: upheld by the caller
+        drop(unsafe { CString::from_raw(ptr) });
+    }
+}
@@ -1,34 +1,538 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, BTreeSet},
+};
+
 use serde::{Deserialize, Serialize};
 use structdiff::{Difference, StructDiff};
 
+use crate::links::Links;
+
+/// Implements [`Invert`] for diff enums whose variants all wrap invertible values.
+macro_rules! impl_invert {
+    ($($diff:ident { $($variant:ident),* $(,)? })*) => {
+        $(impl $crate::format::Invert for $diff {
+            fn invert(self) -> Self {
+                match self {
+                    $(Self::$variant(v) => Self::$variant(v.invert()),)*
+                }
+            }
+        })*
+    };
+}
+
+/// Declares a diff enum `$diff` together with `$diff_ref`, its [`StructDiff::DiffRef`] counterpart.
+///
+/// The variants of `$diff_ref` hold the [`Borrowed::Ref`] of the fields of `$diff`,
+/// it serializes exactly like `$diff` and converts into it.
+macro_rules! diff_enum {
+    (
+        $(#[doc = $doc:literal])*
+        #[derive($($derive:ident),* $(,)?)]
+        $(#[$attr:meta])*
+        pub enum $diff:ident / $diff_ref:ident {
+            $($(#[$variant_attr:meta])* $variant:ident($ty:ty)),* $(,)?
+        }
+    ) => {
+        $(#[doc = $doc])*
+        #[derive($($derive),*)]
+        $(#[$attr])*
+        pub enum $diff {
+            $($(#[$variant_attr])* $variant($ty),)*
+        }
+
+        #[doc = concat!("Borrowing form of [`", stringify!($diff), "`], see [`StructDiff::diff_ref`].")]
+        #[derive(Serialize, Clone)]
+        $(#[$attr])*
+        pub enum $diff_ref<'a> {
+            $($(#[$variant_attr])* $variant(<$ty as $crate::format::Borrowed>::Ref<'a>),)*
+        }
+
+        impl From<$diff_ref<'_>> for $diff {
+            fn from(diff: $diff_ref<'_>) -> Self {
+                match diff {
+                    $($diff_ref::$variant(v) => Self::$variant($crate::format::Borrowed::from_ref(v)),)*
+                }
+            }
+        }
+
+        impl $crate::format::Borrowed for $diff {
+            type Ref<'a> = $diff_ref<'a>;
+
+            fn from_ref(diff: Self::Ref<'_>) -> Self {
+                diff.into()
+            }
+        }
+    };
+}
+
+pub mod intern;
+pub mod lazy;
 pub mod prototype;
+mod reformat;
+mod renames;
 pub mod runtime;
+pub mod words;
+
+pub use diff_helper::{
+    missing_old_values, with_ignored, with_old_values, Change, DiffableVec, DiffableVecDiff,
+    ItemCounts, ItemStatus, Named, TextChange,
+};
+pub use intern::Interned;
+pub use reformat::with_normalized;
+pub use renames::{with_rename_marker, RENAME_MARKER};
+pub use words::WordChange;
 
 mod diff_helper {
-    use std::collections::HashMap;
+    use std::{
+        any::{Any, TypeId},
+        borrow::Cow,
+        cell::{Cell, RefCell},
+        collections::{BTreeMap, HashMap},
+        sync::{Mutex, OnceLock, PoisonError},
+    };
 
-    use serde::{Deserialize, Serialize};
+    use regex::RegexSet;
+    use serde::{ser::SerializeStruct, Deserialize, Serialize};
     use structdiff::StructDiff;
 
-    pub(super) trait Named {
+    thread_local! {static WITH_OLD: Cell<bool> = const { Cell::new(false) };}
+    thread_local! {static MISSING_OLD: Cell<bool> = const { Cell::new(false) };}
+    thread_local! {static IGNORED: RefCell<Option<RegexSet>> = const { RefCell::new(None) };}
+    thread_local! {static PATH: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };}
+
+    /// Runs `f` with serialization of the old values of [`Change`]s enabled or disabled.
+    pub fn with_old_values<R>(enabled: bool, f: impl FnOnce() -> R) -> R {
+        let prev = WITH_OLD.replace(enabled);
+        let res = f();
+        WITH_OLD.set(prev);
+
+        res
+    }
+
+    /// Runs `f`, which deserializes diffs, and tells whether any of their [`Change`]s had no old value
+    /// because the diff was serialized without [`with_old_values`].
+    pub fn missing_old_values<R>(f: impl FnOnce() -> R) -> (R, bool) {
+        let prev = MISSING_OLD.replace(false);
+        let res = f();
+
+        (res, MISSING_OLD.replace(prev))
+    }
+
+    /// Runs `f` ignoring the items of [`DiffableVec`]s that match any of `patterns`.
+    ///
+    /// A pattern has to match either the whole name of an item or its whole path like
+    /// `classes/LuaGuiElement/attributes/style`. Ignored items are neither diffed nor listed as added or removed.
+    pub fn with_ignored<R>(patterns: &[String], f: impl FnOnce() -> R) -> Result<R, regex::Error> {
+        let set = if patterns.is_empty() {
+            None
+        } else {
+            Some(RegexSet::new(
+                patterns.iter().map(|p| format!("^(?:{p})$")),
+            )?)
+        };
+
+        Ok(with_ignored_set(set, f))
+    }
+
+    /// The patterns of [`with_ignored`] on this thread.
+    pub(super) fn ignored() -> Option<RegexSet> {
+        IGNORED.with_borrow(Clone::clone)
+    }
+
+    /// Runs `f` ignoring the items matching the compiled patterns `set`, see [`with_ignored`].
+    pub(super) fn with_ignored_set<R>(set: Option<RegexSet>, f: impl FnOnce() -> R) -> R {
+        let prev = IGNORED.replace(set);
+        let res = f();
+        IGNORED.set(prev);
+
+        res
+    }
+
+    /// Runs `f` with `segment` appended to the path of the diffed items, e.g. a field name.
+    pub fn in_path<R>(segment: &str, f: impl FnOnce() -> R) -> R {
+        PATH.with_borrow_mut(|p| p.push(segment.to_owned()));
+        let res = f();
+        PATH.with_borrow_mut(Vec::pop);
+
+        res
+    }
+
+    /// Path of the item that's currently diffed like `classes/LuaEntity`, `None` outside of items.
+    pub(super) fn current_path() -> Option<String> {
+        PATH.with_borrow(|p| (!p.is_empty()).then(|| p.join("/")))
+    }
+
+    /// Whether the item `name` at the current path is ignored, see [`with_ignored`].
+    pub fn is_ignored(name: &str) -> bool {
+        IGNORED.with_borrow(|set| {
+            set.as_ref().is_some_and(|set| {
+                set.is_match(name)
+                    || PATH.with_borrow(|p| {
+                        let mut path = p.join("/");
+                        path.push('/');
+                        path.push_str(name);
+
+                        set.is_match(&path)
+                    })
+            })
+        })
+    }
+
+    /// Old and new value of a changed field.
+    ///
+    /// Only the new value is serialized unless enabled through [`with_old_values`].
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Change<T> {
+        pub old: T,
+        pub new: T,
+    }
+
+    impl<T> Change<T> {
+        pub const fn new(old: T, new: T) -> Self {
+            Self { old, new }
+        }
+    }
+
+    impl<'a, T: Clone> Change<Cow<'a, T>> {
+        /// Change between two values of the diffed docs, see [`super::Borrowed`].
+        pub const fn borrowed(old: &'a T, new: &'a T) -> Self {
+            Self::new(Cow::Borrowed(old), Cow::Borrowed(new))
+        }
+
+        /// Change between two values that don't exist in the diffed docs as they are.
+        pub const fn owned(old: T, new: T) -> Self {
+            Self::new(Cow::Owned(old), Cow::Owned(new))
+        }
+    }
+
+    impl<T> Serialize for Change<T>
+    where
+        T: Serialize,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            if !WITH_OLD.get() {
+                return self.new.serialize(serializer);
+            }
+
+            let mut state = serializer.serialize_struct("Change", 2)?;
+            state.serialize_field("old", &self.old)?;
+            state.serialize_field("new", &self.new)?;
+            state.end()
+        }
+    }
+
+    impl<'de, T> Deserialize<'de> for Change<T>
+    where
+        T: Deserialize<'de> + Default,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            #[derive(Deserialize)]
+            #[serde(untagged)]
+            enum Repr<T> {
+                Full { old: T, new: T },
+                New(T),
+            }
+
+            Ok(match Repr::deserialize(deserializer)? {
+                Repr::Full { old, new } => Self { old, new },
+                Repr::New(new) => {
+                    MISSING_OLD.set(true);
+
+                    Self {
+                        old: T::default(),
+                        new,
+                    }
+                }
+            })
+        }
+    }
+
+    /// Change of a text like a description, with its words that changed if enabled
+    /// through [`super::DiffOptions::word_diff`].
+    ///
+    /// Serializes like a [`Change`] without the words, and as `new`, `words` and optionally `old` with them.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct TextChange<T> {
+        pub change: Change<T>,
+        pub words: Option<Vec<super::WordChange>>,
+    }
+
+    impl<'a, T: Clone + AsRef<str>> TextChange<Cow<'a, T>> {
+        /// Change between two texts of the diffed docs, see [`super::Borrowed`].
+        ///
+        /// Added and removed texts have no words, they'd only repeat the text.
+        pub fn borrowed(old: &'a T, new: &'a T) -> Self {
+            let (old_text, new_text) = (old.as_ref(), new.as_ref());
+            let words = (super::DiffOptions::current().word_diff
+                && !old_text.is_empty()
+                && !new_text.is_empty())
+            .then(|| super::words::diff(old_text, new_text))
+            .flatten();
+
+            Self {
+                change: Change::borrowed(old, new),
+                words,
+            }
+        }
+    }
+
+    impl<T> Serialize for TextChange<T>
+    where
+        T: Serialize,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            let Some(words) = &self.words else {
+                return self.change.serialize(serializer);
+            };
+
+            let with_old = WITH_OLD.get();
+            let mut state = serializer.serialize_struct("TextChange", 2 + usize::from(with_old))?;
+            if with_old {
+                state.serialize_field("old", &self.change.old)?;
+            }
+            state.serialize_field("new", &self.change.new)?;
+            state.serialize_field("words", words)?;
+            state.end()
+        }
+    }
+
+    impl<'de, T> Deserialize<'de> for TextChange<T>
+    where
+        T: Deserialize<'de> + Default,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            #[derive(Deserialize)]
+            #[serde(untagged, bound = "T: Deserialize<'de> + Default")]
+            enum Repr<T> {
+                Words {
+                    old: Option<T>,
+                    new: T,
+                    words: Vec<super::WordChange>,
+                },
+                Change(Change<T>),
+            }
+
+            Ok(match Repr::deserialize(deserializer)? {
+                Repr::Words { old, new, words } => Self {
+                    change: Change::new(
+                        old.unwrap_or_else(|| {
+                            MISSING_OLD.set(true);
+                            T::default()
+                        }),
+                        new,
+                    ),
+                    words: Some(words),
+                },
+                Repr::Change(change) => Self {
+                    change,
+                    words: None,
+                },
+            })
+        }
+    }
+
+    /// Doc items that are identified by their name, like classes and prototypes.
+    pub trait Named {
         fn name(&self) -> &str;
     }
 
-    #[derive(Debug, PartialEq, Eq, Clone)]
+    /// Items of a doc section keyed by their name, diffed item by item.
+    ///
+    /// Derefs to the map of the items. It's serialized as a list sorted by name and converts
+    /// from and into lists of items, items with the same name replace earlier ones.
+    #[derive(Debug, Clone)]
     pub struct DiffableVec<V> {
         map: HashMap<String, V>,
+        /// Hashes of the serialized items sorted by name, see [`fingerprint`]
+        fingerprints: Box<[(String, u64)]>,
+    }
+
+    impl<V: PartialEq> PartialEq for DiffableVec<V> {
+        fn eq(&self, other: &Self) -> bool {
+            self.map == other.map
+        }
+    }
+
+    impl<V: Eq> Eq for DiffableVec<V> {}
+
+    /// Hash of the serialized `item`, computed once when the item is read.
+    ///
+    /// Equal fingerprints mean equal items, so they're not diffed at all. Items that serialize
+    /// differently despite being equal, like ones with maps, are diffed as usual.
+    fn fingerprint<T: Serialize>(item: &T) -> Option<u64> {
+        struct HashWriter(std::hash::DefaultHasher);
+
+        impl std::io::Write for HashWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                std::hash::Hasher::write(&mut self.0, buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut writer = HashWriter(std::hash::DefaultHasher::new());
+        serde_json::to_writer(&mut writer, item).ok()?;
+
+        Some(std::hash::Hasher::finish(&writer.0))
+    }
+
+    /// How an item of a [`DiffableVec`] differs between two docs.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ItemStatus {
+        Added,
+        Removed,
+        Changed,
     }
 
-    pub type DiffableVecDiff<V> = HashMap<String, Vec<<V as StructDiff>::Diff>>;
+    /// Diffs of the changed items keyed by name, sorted so they're always serialized in the same order.
+    pub type DiffableVecDiff<V> = BTreeMap<String, Vec<<V as StructDiff>::Diff>>;
     pub type SingleDiff<V> = Vec<<V as StructDiff>::Diff>;
+    pub type DiffableVecDiffRef<'a, V> = BTreeMap<&'a str, Vec<<V as StructDiff>::DiffRef<'a>>>;
+    pub type SingleDiffRef<'a, V> = Vec<<V as StructDiff>::DiffRef<'a>>;
+
+    /// Clones the changed values of a borrowed diff, see [`DiffableVec::diff_ref`].
+    pub fn to_owned<T: StructDiff>(diff: DiffableVecDiffRef<'_, T>) -> DiffableVecDiff<T> {
+        diff.into_iter()
+            .map(|(name, d)| (name.to_owned(), d.into_iter().map(Into::into).collect()))
+            .collect()
+    }
+
+    /// Default value of `T` that lives as long as the program,
+    /// added and removed items are diffed against it.
+    pub fn empty<'a, T: Default + Send + Sync + 'static>() -> &'a T {
+        type Empty = HashMap<TypeId, &'static (dyn Any + Send + Sync)>;
+        static EMPTY: OnceLock<Mutex<Empty>> = OnceLock::new();
+
+        let value = *EMPTY
+            .get_or_init(Mutex::default)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::leak(Box::<T>::default()));
+
+        value
+            .downcast_ref()
+            .unwrap_or_else(|| Box::leak(Box::default()))
+    }
+
+    /// Diffs the items called `names` that `old` and `new` look up by name,
+    /// items without changes are left out.
+    ///
+    /// Items that `unchanged` tells apart as unchanged aren't diffed.
+    fn keyed_diff<'a, T>(
+        names: impl Iterator<Item = &'a str>,
+        old: impl Fn(&str) -> Option<&'a T>,
+        new: impl Fn(&str) -> Option<&'a T>,
+        unchanged: impl Fn(&str) -> bool,
+    ) -> DiffableVecDiffRef<'a, T>
+    where
+        T: StructDiff + Default + Send + Sync + 'static,
+    {
+        let mut names = names.collect::<Vec<_>>();
+        names.sort_unstable();
+        names.dedup();
+        names.retain(|name| !is_ignored(name) && !unchanged(name));
+
+        let mut diff = BTreeMap::new();
+
+        for name in names {
+            let (v, o) = (old(name), new(name));
+            let d = in_path(name, || {
+                v.unwrap_or_else(empty).diff_ref(o.unwrap_or_else(empty))
+            });
+
+            if !d.is_empty() {
+                diff.insert(name, d);
+            }
+        }
+
+        diff
+    }
+
+    /// Diffs two lists of named items like [`DiffableVec::diff_ref`], pairing the items by name.
+    ///
+    /// Of multiple items with the same name only the last one is diffed.
+    pub fn named_diff<'a, T>(orig: &'a [T], new: &'a [T]) -> DiffableVecDiffRef<'a, T>
+    where
+        T: StructDiff + Named + Default + Send + Sync + 'static,
+    {
+        let find = |values: &'a [T], name: &str| values.iter().rev().find(|v| v.name() == name);
+
+        keyed_diff(
+            orig.iter().chain(new).map(Named::name),
+            |name| find(orig, name),
+            |name| find(new, name),
+            |_| false,
+        )
+    }
+
+    /// Diff from an empty value to `value`, or the other way around if the value was `removed`.
+    pub fn diff_empty<T>(value: &T, removed: bool) -> Vec<T::DiffRef<'_>>
+    where
+        T: StructDiff + Default + Send + Sync + 'static,
+    {
+        if removed {
+            value.diff_ref(empty())
+        } else {
+            empty::<T>().diff_ref(value)
+        }
+    }
+
+    /// `empty` and `value` in the order of their change, the value first if it was `removed`.
+    pub const fn from_empty<T>(empty: T, value: T, removed: bool) -> (T, T) {
+        if removed {
+            (value, empty)
+        } else {
+            (empty, value)
+        }
+    }
+
+    /// Diffs each item of a list of named items with the default value like [`diff_empty`], including unchanged ones.
+    pub fn named_full<T>(values: &[T], removed: bool) -> DiffableVecDiffRef<'_, T>
+    where
+        T: StructDiff + Named + Default + Send + Sync + 'static,
+    {
+        values
+            .iter()
+            .filter(|v| !is_ignored(v.name()))
+            .map(|v| (v.name(), in_path(v.name(), || diff_empty(v, removed))))
+            .collect()
+    }
 
-    impl<T: Named> From<Vec<T>> for DiffableVec<T> {
+    impl<T: Named + Serialize> From<Vec<T>> for DiffableVec<T> {
         fn from(value: Vec<T>) -> Self {
+            let mut fingerprints = value
+                .iter()
+                .filter_map(|p| Some((p.name().to_owned(), fingerprint(p)?)))
+                // the last of items with the same name is kept, like in the map
+                .collect::<HashMap<_, _>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            fingerprints.sort_unstable();
+
             Self {
                 map: value
                     .into_iter()
                     .map(|p| (p.name().to_owned(), p))
                     .collect(),
+                fingerprints: fingerprints.into(),
             }
         }
     }
@@ -37,7 +541,77 @@ mod diff_helper {
         fn default() -> Self {
             Self {
                 map: HashMap::new(),
+                fingerprints: Box::default(),
+            }
+        }
+    }
+
+    impl<T> DiffableVec<T> {
+        /// Hash of the item `name` computed when it was read, `None` for items that couldn't be hashed.
+        #[must_use]
+        pub fn fingerprint(&self, name: &str) -> Option<u64> {
+            self.fingerprints
+                .binary_search_by(|(n, _)| n.as_str().cmp(name))
+                .ok()
+                .map(|i| self.fingerprints[i].1)
+        }
+
+        /// The item called `name`.
+        #[must_use]
+        pub fn get(&self, name: &str) -> Option<&T> {
+            self.map.get(name)
+        }
+
+        /// The items sorted by name, the order they're serialized in.
+        pub fn iter_sorted(&self) -> impl Iterator<Item = &T> {
+            let mut items = self.map.iter().collect::<Vec<_>>();
+            items.sort_unstable_by_key(|(name, _)| *name);
+
+            items.into_iter().map(|(_, item)| item)
+        }
+
+        /// Removes the item called `name` and returns it.
+        pub fn remove(&mut self, name: &str) -> Option<T> {
+            self.set_fingerprint(name, None);
+            self.map.remove(name)
+        }
+
+        fn set_fingerprint(&mut self, name: &str, fingerprint: Option<u64>) {
+            let mut fingerprints = std::mem::take(&mut self.fingerprints).into_vec();
+
+            match (
+                fingerprints.binary_search_by(|(n, _)| n.as_str().cmp(name)),
+                fingerprint,
+            ) {
+                (Ok(i), Some(f)) => fingerprints[i].1 = f,
+                (Ok(i), None) => {
+                    fingerprints.remove(i);
+                }
+                (Err(i), Some(f)) => fingerprints.insert(i, (name.to_owned(), f)),
+                (Err(_), None) => {}
             }
+
+            self.fingerprints = fingerprints.into();
+        }
+    }
+
+    impl<T: Named + Serialize> DiffableVec<T> {
+        /// Inserts the `item` under its name, returns the item it replaced.
+        pub fn insert(&mut self, item: T) -> Option<T> {
+            let name = item.name().to_owned();
+            self.set_fingerprint(&name, fingerprint(&item));
+
+            self.map.insert(name, item)
+        }
+    }
+
+    impl<T> From<DiffableVec<T>> for Vec<T> {
+        /// The items sorted by name.
+        fn from(value: DiffableVec<T>) -> Self {
+            let mut items = value.map.into_iter().collect::<Vec<_>>();
+            items.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+            items.into_iter().map(|(_, item)| item).collect()
         }
     }
 
@@ -51,37 +625,47 @@ mod diff_helper {
 
     impl<T> DiffableVec<T>
     where
-        T: StructDiff + Default,
+        T: StructDiff + Default + Clone + Send + Sync + 'static,
     {
         #[must_use]
         pub fn diff(&self, other: &Self) -> DiffableVecDiff<T> {
-            let mut diff = HashMap::new();
-
-            for (k, v) in &self.map {
-                if let Some(o) = other.map.get(k) {
-                    let d = v.diff(o);
-                    if !d.is_empty() {
-                        diff.insert(k.clone(), d);
-                    }
-                } else {
-                    diff.insert(k.clone(), v.diff(&T::default()));
-                }
-            }
+            to_owned::<T>(self.diff_ref(other))
+        }
 
-            for (k, v) in &other.map {
-                if !self.map.contains_key(k) {
-                    diff.insert(k.clone(), T::default().diff(v));
-                }
-            }
+        /// Diffs the items like [`DiffableVec::diff`] without cloning the changed values.
+        ///
+        /// Items with the same [`DiffableVec::fingerprint`] in both lists are skipped without diffing them.
+        #[must_use]
+        pub fn diff_ref<'a>(&'a self, other: &'a Self) -> DiffableVecDiffRef<'a, T> {
+            keyed_diff(
+                self.map.keys().chain(other.map.keys()).map(String::as_str),
+                |name| self.map.get(name),
+                |name| other.map.get(name),
+                |name| unchanged(self, other, name),
+            )
+        }
 
-            diff
+        /// Diffs the items one at a time, see [`diff_each`].
+        pub fn diff_each(
+            &self,
+            other: &Self,
+            f: impl for<'a> FnMut(&str, ItemStatus, SingleDiffRef<'a, T>),
+        ) -> crate::Result<()> {
+            diff_each(self, other, f)
         }
 
-        pub fn full(&self) -> DiffableVecDiff<T> {
-            self.map
-                .iter()
-                .map(|(k, v)| (k.clone(), v.diff(&T::default())))
-                .collect()
+        /// Sorted names of the items that are missing in `other`.
+        #[must_use]
+        pub fn removed(&self, other: &Self) -> Vec<String> {
+            let mut removed = self
+                .map
+                .keys()
+                .filter(|k| !other.map.contains_key(*k) && !is_ignored(k))
+                .cloned()
+                .collect::<Vec<_>>();
+            removed.sort_unstable();
+
+            removed
         }
     }
 
@@ -93,95 +677,1533 @@ mod diff_helper {
         where
             S: serde::Serializer,
         {
-            let value = self.values().collect::<Vec<_>>();
-            value.serialize(serializer)
+            // sorted by name to keep the output stable
+            serializer.collect_seq(self.iter_sorted())
         }
     }
 
     impl<'de, T> serde::Deserialize<'de> for DiffableVec<T>
     where
-        T: Deserialize<'de> + Named,
+        T: Deserialize<'de> + Named + Serialize,
     {
         fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where
             D: serde::Deserializer<'de>,
         {
-            let value = Vec::<T>::deserialize(deserializer)?;
-            Ok(Self {
-                map: value
-                    .into_iter()
-                    .map(|p| (p.name().to_owned(), p))
-                    .collect(),
-            })
+            Ok(Vec::<T>::deserialize(deserializer)?.into())
         }
     }
 
-    pub fn vec_diff<T: StructDiff + Default>(orig: &[T], new: &[T]) -> Vec<SingleDiff<T>> {
-        let mut diff = Vec::new();
+    /// Named items that can be looked up one at a time, parsed or not.
+    pub trait Items<T: Clone> {
+        /// Names of all items, in no particular order.
+        fn names(&self) -> Vec<&str>;
 
-        for (i, v) in orig.iter().enumerate() {
-            if let Some(n) = new.get(i) {
-                diff.push(v.diff(n));
-            } else {
-                diff.push(v.diff(&T::default()));
-            }
+        /// The item `name` if there is one, fails if it can't be deserialized.
+        fn item(&self, name: &str) -> crate::Result<Option<Cow<'_, T>>>;
+
+        /// See [`DiffableVec::fingerprint`], items without one are always diffed.
+        fn fingerprint(&self, _name: &str) -> Option<u64> {
+            None
         }
+    }
 
-        new.iter()
-            .skip(orig.len())
-            .for_each(|n| diff.push(T::default().diff(n)));
+    impl<T: Clone> Items<T> for DiffableVec<T> {
+        fn names(&self) -> Vec<&str> {
+            self.map.keys().map(String::as_str).collect()
+        }
 
-        diff
+        fn item(&self, name: &str) -> crate::Result<Option<Cow<'_, T>>> {
+            Ok(self.map.get(name).map(Cow::Borrowed))
+        }
+
+        fn fingerprint(&self, name: &str) -> Option<u64> {
+            Self::fingerprint(self, name)
+        }
     }
-}
 
-pub trait Doc {
-    type Diff;
+    impl<T: Clone, I: Items<T>> Items<T> for &I {
+        fn names(&self) -> Vec<&str> {
+            (**self).names()
+        }
 
-    #[must_use]
-    fn diff(&self, other: &Self) -> Self::Diff;
-}
+        fn item(&self, name: &str) -> crate::Result<Option<Cow<'_, T>>> {
+            (**self).item(name)
+        }
 
-pub trait Info {
-    fn print_info(&self);
-}
+        fn fingerprint(&self, name: &str) -> Option<u64> {
+            (**self).fingerprint(name)
+        }
+    }
 
-#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Difference, Clone)]
-#[serde(rename_all = "lowercase")]
-pub enum Application {
-    #[default]
-    Factorio,
-}
+    /// Whether the item `name` has the same fingerprint in both, meaning it's unchanged.
+    fn unchanged<T: Clone>(old: &impl Items<T>, new: &impl Items<T>, name: &str) -> bool {
+        old.fingerprint(name)
+            .is_some_and(|f| new.fingerprint(name) == Some(f))
+    }
 
-#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Difference, Clone)]
-#[serde(rename_all = "lowercase")]
-pub enum Stage {
-    #[default]
-    Prototype,
-    Runtime,
-}
+    /// Numbers of added, removed and changed items of a section, see [`count_each`].
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+    pub struct ItemCounts {
+        pub added: usize,
+        pub removed: usize,
+        pub changed: usize,
+    }
 
-#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Difference, Clone)]
-pub struct Common {
-    pub application: Application,
-    pub stage: Stage,
-    pub application_version: String,
-    pub api_version: u8,
-}
+    impl ItemCounts {
+        #[must_use]
+        pub const fn total(&self) -> usize {
+            self.added + self.removed + self.changed
+        }
+    }
 
-impl Info for Common {
+    /// Counts the items [`diff_each`] would pass with a non-empty diff, without keeping any diffs.
+    ///
+    /// Added and removed items aren't diffed at all, items in both docs are compared first
+    /// and only diffed if they differ, to apply the current options to their differences.
+    /// Also returns the number of items whose only changes are link only changes that were left out,
+    /// see [`super::reformat::counting_links`].
+    /// Fails if an item can't be deserialized.
+    pub fn count_each<T: StructDiff + PartialEq + Default + Clone + Send + Sync + 'static>(
+        old: &impl Items<T>,
+        new: &impl Items<T>,
+    ) -> crate::Result<(ItemCounts, usize)> {
+        let mut names = old.names();
+        names.extend(new.names());
+        names.sort_unstable();
+        names.dedup();
+        names.retain(|name| !is_ignored(name));
+
+        let (mut counts, mut links_only) = (ItemCounts::default(), 0);
+        for name in names {
+            if unchanged(old, new, name) {
+                continue;
+            }
+
+            match (old.item(name)?, new.item(name)?) {
+                (Some(v), Some(o)) if *v != *o => {
+                    let links = super::reformat::links_only();
+                    if !in_path(name, || v.diff_ref(&o).is_empty()) {
+                        counts.changed += 1;
+                    } else if super::reformat::links_only() > links {
+                        links_only += 1;
+                    }
+                }
+                (Some(_), None) => counts.removed += 1,
+                (None, Some(_)) => counts.added += 1,
+                _ => {}
+            }
+        }
+
+        Ok((counts, links_only))
+    }
+
+    /// Diffs the items one at a time, sorted by name, without collecting the diffs.
+    ///
+    /// `f` is called for every item that isn't ignored, including unchanged ones with an empty diff.
+    /// The diffs borrow from the items, which may only be parsed for the call.
+    /// Fails as soon as an item can't be deserialized.
+    pub fn diff_each<T: StructDiff + Default + Clone + Send + Sync + 'static>(
+        old: &impl Items<T>,
+        new: &impl Items<T>,
+        mut f: impl for<'a> FnMut(&str, ItemStatus, SingleDiffRef<'a, T>),
+    ) -> crate::Result<()> {
+        let mut names = old.names();
+        names.extend(new.names());
+        names.sort_unstable();
+        names.dedup();
+        names.retain(|name| !is_ignored(name));
+
+        for name in names {
+            if unchanged(old, new, name) {
+                f(name, ItemStatus::Changed, Vec::new());
+                continue;
+            }
+
+            let (v, o) = (old.item(name)?, new.item(name)?);
+            let status = match (&v, &o) {
+                (Some(_), Some(_)) => ItemStatus::Changed,
+                (Some(_), None) => ItemStatus::Removed,
+                (None, Some(_)) => ItemStatus::Added,
+                (None, None) => continue,
+            };
+
+            let diff = in_path(name, || {
+                v.as_deref()
+                    .unwrap_or_else(empty)
+                    .diff_ref(o.as_deref().unwrap_or_else(empty))
+            });
+
+            f(name, status, diff);
+        }
+
+        Ok(())
+    }
+
+    pub fn vec_diff<'a, T>(orig: &'a [T], new: &'a [T]) -> Vec<SingleDiffRef<'a, T>>
+    where
+        T: StructDiff + Default + Send + Sync + 'static,
+    {
+        let mut diff = Vec::new();
+
+        for (i, v) in orig.iter().enumerate() {
+            diff.push(v.diff_ref(new.get(i).unwrap_or_else(empty)));
+        }
+
+        new.iter()
+            .skip(orig.len())
+            .for_each(|n| diff.push(empty::<T>().diff_ref(n)));
+
+        diff
+    }
+
+    /// Types of union options, which have no meaningful order.
+    pub trait Canonical: std::hash::Hash {
+        /// Name of the type if it's a simple type.
+        fn simple_name(&self) -> Option<&str>;
+    }
+
+    /// The values in their canonical order: simple types sorted by name, followed by the others
+    /// sorted by their structural hash, so the same values always end up in the same order.
+    pub fn canonical_order<T: Canonical>(values: &[T]) -> Vec<&T> {
+        let mut res = values.iter().collect::<Vec<_>>();
+        res.sort_by_cached_key(|v| {
+            let mut hasher = std::hash::DefaultHasher::new();
+            v.hash(&mut hasher);
+
+            (
+                v.simple_name().is_none(),
+                v.simple_name().map(str::to_owned),
+                std::hash::Hasher::finish(&hasher),
+            )
+        });
+
+        res
+    }
+
+    /// Like [`vec_diff`] but for values without a meaningful order, so reordering doesn't show up as changes.
+    ///
+    /// Equal values are paired with each other, the remaining values are paired by their position
+    /// in the [`canonical_order`], so the diffs are in the same order for the same values.
+    pub fn canonical_vec_diff<'a, T>(orig: &'a [T], new: &'a [T]) -> Vec<SingleDiffRef<'a, T>>
+    where
+        T: Canonical + PartialEq + StructDiff + Default + Send + Sync + 'static,
+    {
+        let removed = canonical_order(orig)
+            .into_iter()
+            .filter(|v| !new.contains(v))
+            .collect::<Vec<_>>();
+        let added = canonical_order(new)
+            .into_iter()
+            .filter(|n| !orig.contains(n))
+            .collect::<Vec<_>>();
+
+        let mut diff = Vec::new();
+
+        for (i, v) in removed.iter().enumerate() {
+            diff.push(v.diff_ref(added.get(i).copied().unwrap_or_else(empty)));
+        }
+
+        added
+            .iter()
+            .skip(removed.len())
+            .for_each(|n| diff.push(empty::<T>().diff_ref(n)));
+
+        diff
+    }
+
+    /// Whether two values are the same value in different versions, see [`vec_diff_matched`].
+    pub type Same<T> = dyn Fn(&T, &T) -> bool;
+
+    /// Like [`vec_diff`] but pairs values by their identity, so reordering doesn't show up as changes.
+    ///
+    /// `same` are tried in turn, each pairs the values left unpaired by the ones before.
+    /// Values still without a counterpart are paired by position with the remaining unpaired values.
+    /// The diffs are in the order of `orig`, followed by the added values.
+    pub fn vec_diff_matched<'a, T>(
+        orig: &'a [T],
+        new: &'a [T],
+        same: &[&Same<T>],
+    ) -> Vec<SingleDiffRef<'a, T>>
+    where
+        T: StructDiff + Default + Send + Sync + 'static,
+    {
+        let mut pairs = vec![None; orig.len()];
+        for same in same {
+            for (i, v) in orig.iter().enumerate() {
+                if pairs[i].is_some() {
+                    continue;
+                }
+
+                pairs[i] = (0..new.len()).find(|j| !pairs.contains(&Some(*j)) && same(v, &new[*j]));
+            }
+        }
+
+        for i in 0..orig.len().min(new.len()) {
+            if pairs[i].is_none() && !pairs.contains(&Some(i)) {
+                pairs[i] = Some(i);
+            }
+        }
+
+        let mut diff = orig
+            .iter()
+            .zip(&pairs)
+            .map(|(v, pair)| v.diff_ref(pair.map_or_else(empty, |i| &new[i])))
+            .collect::<Vec<_>>();
+
+        new.iter()
+            .enumerate()
+            .filter(|(i, _)| !pairs.contains(&Some(*i)))
+            .for_each(|(_, n)| diff.push(empty::<T>().diff_ref(n)));
+
+        diff
+    }
+}
+
+thread_local! {static OPTIONS: Cell<DiffOptions> = Cell::default();}
+#[cfg(feature = "bench-hooks")]
+thread_local! {static ON_READ: Cell<Option<fn()>> = const { Cell::new(None) };}
+thread_local! {static WARNINGS: RefCell<Vec<Warning>> = const { RefCell::new(Vec::new()) };}
+thread_local! {static METRICS: RefCell<BTreeMap<String, Metrics>> = const { RefCell::new(BTreeMap::new()) };}
+
+/// Options that control which changes are reported.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffOptions {
+    pub policy: FieldPolicy,
+
+    /// Report type changes that don't change the meaning of the type,
+    /// e.g. wrapping a type in a single option union or reordering the options of a union
+    pub exact_types: bool,
+
+    /// Annotate subclasses of classes whose members changed
+    pub inherit: bool,
+
+    /// Diff the effective properties of prototypes, including the inherited ones
+    pub resolve_inheritance: bool,
+
+    /// Count the added, removed and changed members of each changed class and prototype
+    pub metrics: bool,
+
+    /// Link changed items to their page in the official docs of the target version
+    pub anchor_links: bool,
+
+    /// Unchanged parts of the changed items to include alongside their changes
+    pub context: Context,
+
+    /// Add the changed words to changes of descriptions
+    pub word_diff: bool,
+
+    /// Report description changes that only affect links even if most descriptions changed that way,
+    /// they're only counted otherwise
+    pub keep_link_changes: bool,
+
+    /// Report changes of the `optional` flag and of whether attributes can be written by their direction,
+    /// e.g. `now_required` or `now_read_only`, see [`Optionality`] and [`Writability`]
+    pub directional: bool,
+}
+
+/// Unchanged parts of the changed items that are included in a diff, see [`DiffOptions::context`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Context {
+    /// Only the changed fields
+    #[default]
+    None,
+    /// The complete updated item, removed items have none
+    FullItem,
+}
+
+impl DiffOptions {
+    /// Runs `f` with these options applied to all diffs.
+    pub fn apply<R>(self, f: impl FnOnce() -> R) -> R {
+        let prev = OPTIONS.replace(self);
+        let res = f();
+        OPTIONS.set(prev);
+
+        res
+    }
+
+    /// Options of the currently running diff.
+    #[must_use]
+    pub fn current() -> Self {
+        #[cfg(feature = "bench-hooks")]
+        if let Some(on_read) = ON_READ.get() {
+            on_read();
+        }
+
+        OPTIONS.get()
+    }
+
+    /// Runs `f` calling `on_read` whenever the options are read, so benchmarks can add a cost to every read.
+    #[cfg(feature = "bench-hooks")]
+    #[doc(hidden)]
+    pub fn with_read_hook<R>(on_read: fn(), f: impl FnOnce() -> R) -> R {
+        let prev = ON_READ.replace(Some(on_read));
+        let res = f();
+        ON_READ.set(prev);
+
+        res
+    }
+}
+
+/// Version of the schema of serialized diffs, older diffs are upgraded by [`crate::migrate`].
+pub const DIFF_FORMAT_VERSION: u32 = 3;
+
+/// Version of diffs serialized before the version was part of them.
+const fn unversioned() -> u32 {
+    1
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // signature required by serde
+const fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// The patterns of [`with_ignored`], [`with_normalized`] and [`with_rename_marker`] of the current thread,
+/// so that diffs on other threads can use them too.
+#[derive(Clone)]
+pub(crate) struct Patterns {
+    ignored: Option<regex::RegexSet>,
+    rules: Vec<(regex::Regex, String)>,
+    rename_marker: Option<regex::Regex>,
+}
+
+impl Patterns {
+    pub(crate) fn current() -> Self {
+        Self {
+            ignored: diff_helper::ignored(),
+            rules: reformat::rules(),
+            rename_marker: renames::marker(),
+        }
+    }
+
+    /// Runs `f` with the patterns installed on the current thread.
+    pub(crate) fn apply<R>(self, f: impl FnOnce() -> R) -> R {
+        diff_helper::with_ignored_set(self.ignored, || {
+            reformat::with_rules(self.rules, || renames::with_marker(self.rename_marker, f))
+        })
+    }
+}
+
+/// A problem encountered while diffing, see [`take_warnings`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Warning {
+    /// Path of the item that was being diffed, e.g. `classes/LuaEntity/operators/index`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{path}: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Records a problem encountered while diffing the item at the current path.
+pub(crate) fn warn(msg: impl Into<String>) {
+    let warning = Warning {
+        path: diff_helper::current_path(),
+        message: msg.into(),
+    };
+
+    WARNINGS.with_borrow_mut(|w| w.push(warning));
+}
+
+/// Records the problems taken from another thread, see [`take_warnings`].
+pub(crate) fn add_warnings(warnings: impl IntoIterator<Item = Warning>) {
+    WARNINGS.with_borrow_mut(|w| w.extend(warnings));
+}
+
+/// Takes all problems encountered while diffing so far.
+#[must_use]
+pub fn take_warnings() -> Vec<Warning> {
+    WARNINGS.take()
+}
+
+/// Counters of the added, removed and changed members of an item, e.g. `methods_added`.
+///
+/// Only lists with changes are counted.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Metrics(pub BTreeMap<String, usize>);
+
+impl Metrics {
+    /// Counts the members of the list `key` that changed between `old` and `new`, `diff` is their diff.
+    fn count<T>(
+        &mut self,
+        key: &str,
+        old: &diff_helper::DiffableVec<T>,
+        new: &diff_helper::DiffableVec<T>,
+        diff: &diff_helper::DiffableVecDiffRef<'_, T>,
+    ) where
+        T: StructDiff,
+    {
+        let (added, removed) = diff_helper::in_path(key, || {
+            (
+                new.keys()
+                    .filter(|k| !old.contains_key(*k) && !diff_helper::is_ignored(k))
+                    .count(),
+                old.keys()
+                    .filter(|k| !new.contains_key(*k) && !diff_helper::is_ignored(k))
+                    .count(),
+            )
+        });
+        let changed = diff
+            .keys()
+            .filter(|k| old.contains_key(**k) && new.contains_key(**k))
+            .count();
+
+        self.0.insert(format!("{key}_added"), added);
+        self.0.insert(format!("{key}_removed"), removed);
+        self.0.insert(format!("{key}_changed"), changed);
+    }
+
+    /// Records the metrics of the item `name` if enabled through [`DiffOptions::metrics`].
+    fn record(self, name: &str) {
+        if self.0.values().any(|v| *v > 0) && DiffOptions::current().metrics {
+            METRICS.with_borrow_mut(|m| m.insert(name.to_owned(), self));
+        }
+    }
+
+    /// Takes the metrics of all items diffed so far, keyed by item name.
+    fn take() -> BTreeMap<String, Self> {
+        METRICS.take()
+    }
+}
+
+/// Number of added, removed and changed items of the `sections` of a diff,
+/// see [`runtime::RuntimeDocDiff::change_count`].
+///
+/// Each section has the names of its items in the diff, its added and removed items and
+/// the [`Metrics`] of its changed items if it records them.
+fn change_count<'a>(
+    sections: impl IntoIterator<
+        Item = (
+            Vec<&'a String>,
+            &'a [String],
+            &'a [String],
+            Option<&'a BTreeMap<String, Metrics>>,
+        ),
+    >,
+) -> usize {
+    sections
+        .into_iter()
+        .map(|(names, added, removed, metrics)| {
+            let names = names
+                .into_iter()
+                .chain(added)
+                .chain(removed)
+                .collect::<BTreeSet<_>>();
+
+            names
+                .into_iter()
+                .map(|name| {
+                    if added.contains(name) || removed.contains(name) {
+                        return 1;
+                    }
+
+                    metrics
+                        .and_then(|m| m.get(name))
+                        .map(|m| m.0.values().sum())
+                        .filter(|n| *n > 0)
+                        .unwrap_or(1)
+                })
+                .sum::<usize>()
+        })
+        .sum()
+}
+
+/// Pages of the changed items of each section that exist in the target docs,
+/// see [`DiffOptions::anchor_links`].
+fn item_urls<'a>(
+    links: &Links,
+    sections: impl IntoIterator<Item = (&'a str, Vec<&'a String>, &'a [String])>,
+) -> BTreeMap<String, BTreeMap<String, String>> {
+    sections
+        .into_iter()
+        .map(|(section, names, removed)| {
+            let urls = names
+                .into_iter()
+                .filter(|name| !removed.contains(name))
+                .filter_map(|name| Some((name.clone(), links.url(&[section, name])?)))
+                .collect::<BTreeMap<_, _>>();
+
+            (section.to_owned(), urls)
+        })
+        .filter(|(_, urls)| !urls.is_empty())
+        .collect()
+}
+
+/// The complete item `name` of `target` if enabled in [`DiffOptions::context`],
+/// removed items have none.
+fn full_item<T: Serialize + Clone>(
+    kind: &str,
+    name: &str,
+    target: &impl diff_helper::Items<T>,
+) -> Option<serde_json::Value> {
+    if DiffOptions::current().context != Context::FullItem {
+        return None;
+    }
+
+    // the item was just diffed, so it can be deserialized
+    match serde_json::to_value(target.item(name).ok()??) {
+        Ok(item) => Some(item),
+        Err(e) => {
+            warn(format!("failed to serialize the {kind} {name}: {e}"));
+            None
+        }
+    }
+}
+
+/// The complete changed items of a section, see [`DiffOptions::context`].
+fn item_context<'a, T: Serialize + Clone>(
+    kind: &str,
+    names: impl IntoIterator<Item = &'a String>,
+    target: &impl diff_helper::Items<T>,
+) -> BTreeMap<String, serde_json::Value> {
+    names
+        .into_iter()
+        .filter_map(|name| Some((name.clone(), full_item(kind, name, target)?)))
+        .collect()
+}
+
+impl Invert for Metrics {
+    fn invert(self) -> Self {
+        let swap = |key: String| match key.rsplit_once('_') {
+            Some((list, "added")) => format!("{list}_removed"),
+            Some((list, "removed")) => format!("{list}_added"),
+            _ => key,
+        };
+
+        Self(self.0.into_iter().map(|(k, v)| (swap(k), v)).collect())
+    }
+}
+
+impl<T: Invert> Invert for BTreeMap<String, T> {
+    fn invert(self) -> Self {
+        self.into_iter().map(|(k, v)| (k, v.invert())).collect()
+    }
+}
+
+pub trait Doc {
+    type Diff;
+
+    /// Adjusts the doc to the current [`DiffOptions`] before it's diffed.
+    fn prepare(&mut self) {}
+
+    #[must_use]
+    fn diff(&self, other: &Self) -> Self::Diff;
+
+    /// Diffs the docs item by item, passing each changed item to `emit` as soon as it's diffed.
+    ///
+    /// Produces the same changes as [`Doc::diff`] without keeping the whole diff in memory.
+    /// Old values are serialized according to [`with_old_values`].
+    /// Fails if an item can't be deserialized, which only happens for items parsed on demand.
+    fn diff_entries(&self, other: &Self, emit: &mut dyn FnMut(DiffEntry)) -> crate::Result<()>;
+
+    /// Counts the changed items per section without building their diffs.
+    ///
+    /// Counts the items [`Doc::diff_entries`] passes for changes of the items themselves,
+    /// items that only changed through other items, like events whose filter concept changed, aren't counted.
+    fn count_changes(&self, other: &Self) -> crate::Result<ChangeCounts>;
+}
+
+/// Numbers of changed items per section of a diff, see [`Doc::count_changes`].
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ChangeCounts {
+    /// Number of changed items of all sections
+    pub total: usize,
+    /// Counts of each section, e.g. `classes`, sections without changes are left out
+    pub sections: BTreeMap<&'static str, ItemCounts>,
+}
+
+impl ChangeCounts {
+    /// Adds the counts of the section `name`.
+    pub fn add(&mut self, name: &'static str, counts: ItemCounts) {
+        if counts.total() > 0 {
+            self.total += counts.total();
+            self.sections.insert(name, counts);
+        }
+    }
+
+    /// Counts of the sections counted by [`diff_helper::count_each`] in [`reformat::counting_links`].
+    ///
+    /// Items whose only changes are link only changes are counted as changed unless they were `collapsed`.
+    pub(crate) fn from_sections(
+        sections: impl IntoIterator<Item = (&'static str, (ItemCounts, usize))>,
+        collapsed: bool,
+    ) -> Self {
+        let mut res = Self::default();
+        for (name, (mut counts, links_only)) in sections {
+            if !collapsed {
+                counts.changed += links_only;
+            }
+
+            res.add(name, counts);
+        }
+
+        res
+    }
+}
+
+/// A single changed item of a diff, see [`Doc::diff_entries`].
+#[derive(Debug, Serialize)]
+pub struct DiffEntry {
+    /// Section of the docs the item belongs to, e.g. `class` or `prototype`
+    pub kind: &'static str,
+    pub name: String,
+    pub status: ItemStatus,
+    pub changes: serde_json::Value,
+    /// Page of the item in the target docs, see [`DiffOptions::anchor_links`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// The complete item in the target docs, see [`DiffOptions::context`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<serde_json::Value>,
+}
+
+impl DiffEntry {
+    /// Key of the section of the item in the docs, e.g. `classes` for `class`.
+    #[must_use]
+    pub fn section(&self) -> &'static str {
+        match self.kind {
+            "class" => "classes",
+            "event" => "events",
+            "concept" => "concepts",
+            "define" => "defines",
+            "global_object" => "global_objects",
+            "global_function" => "global_functions",
+            "prototype" => "prototypes",
+            "type" => "types",
+            _ => self.kind,
+        }
+    }
+
+    /// Entry standing in for the description changes that only affected links,
+    /// see [`DiffOptions::keep_link_changes`].
+    fn reformatted_descriptions(count: usize) -> Self {
+        Self {
+            kind: "reformatting",
+            name: "descriptions".to_owned(),
+            status: ItemStatus::Changed,
+            changes: serde_json::json!({ "links_only": count }),
+            url: None,
+            context: None,
+        }
+    }
+
+    /// Links the item to its page in the docs, removed items aren't linked.
+    pub fn link(&mut self, links: &Links) {
+        if self.status != ItemStatus::Removed {
+            self.url = links.url(&[self.section(), &self.name]);
+        }
+    }
+
+    /// Serializes the changes of an item, items without changes are skipped.
+    ///
+    /// `target` holds the items of the target docs, see [`DiffOptions::context`].
+    fn emit<D: Serialize, T: Serialize + Clone>(
+        emit: &mut dyn FnMut(Self),
+        kind: &'static str,
+        name: &str,
+        status: ItemStatus,
+        changes: &[D],
+        target: &impl diff_helper::Items<T>,
+    ) {
+        if changes.is_empty() {
+            return;
+        }
+
+        match serde_json::to_value(changes) {
+            Ok(changes) => emit(Self {
+                kind,
+                name: name.to_owned(),
+                status,
+                changes,
+                url: None,
+                context: full_item(kind, name, target),
+            }),
+            Err(e) => warn(format!(
+                "failed to serialize the changes of {kind} {name}: {e}"
+            )),
+        }
+    }
+}
+
+/// Diffs that can be reversed, turning a diff from `a` to `b` into a diff from `b` to `a`.
+///
+/// Relies on the old values of all changes, diffs deserialized without them can't be inverted.
+/// Changes of the kind of a complex type only list the fields of the new kind,
+/// their inversion lists them as removed instead of listing the fields of the old kind.
+pub trait Invert {
+    #[must_use]
+    fn invert(self) -> Self;
+}
+
+impl<T> Invert for Change<T> {
+    fn invert(self) -> Self {
+        Self::new(self.new, self.old)
+    }
+}
+
+impl<T> Invert for TextChange<T> {
+    fn invert(self) -> Self {
+        Self {
+            change: self.change.invert(),
+            words: self.words.invert(),
+        }
+    }
+}
+
+impl<T: Invert> Invert for Vec<T> {
+    fn invert(self) -> Self {
+        self.into_iter().map(Invert::invert).collect()
+    }
+}
+
+impl<T: Invert> Invert for Option<T> {
+    fn invert(self) -> Self {
+        self.map(Invert::invert)
+    }
+}
+
+impl<T, S> Invert for std::collections::HashMap<String, T, S>
+where
+    T: Invert,
+    S: std::hash::BuildHasher + Default,
+{
+    fn invert(self) -> Self {
+        self.into_iter().map(|(k, v)| (k, v.invert())).collect()
+    }
+}
+
+/// Diffs with a form that borrows the changed values from the diffed docs instead of cloning them.
+///
+/// The borrowed form serializes exactly like the diff itself, see [`StructDiff::diff_ref`].
+pub trait Borrowed: Sized {
+    type Ref<'a>: Serialize + Clone
+    where
+        Self: 'a;
+
+    /// Clones the borrowed values into an owned diff.
+    fn from_ref(diff: Self::Ref<'_>) -> Self;
+}
+
+impl<T: Clone + Serialize> Borrowed for Change<T> {
+    type Ref<'a>
+        = Change<std::borrow::Cow<'a, T>>
+    where
+        Self: 'a;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        Self::new(diff.old.into_owned(), diff.new.into_owned())
+    }
+}
+
+impl<T: Clone + Serialize> Borrowed for TextChange<T> {
+    type Ref<'a>
+        = TextChange<std::borrow::Cow<'a, T>>
+    where
+        Self: 'a;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        Self {
+            change: Change::from_ref(diff.change),
+            words: diff.words,
+        }
+    }
+}
+
+impl<T: Borrowed> Borrowed for Vec<T> {
+    type Ref<'a>
+        = Vec<T::Ref<'a>>
+    where
+        Self: 'a;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        diff.into_iter().map(T::from_ref).collect()
+    }
+}
+
+impl<T: Borrowed> Borrowed for Option<T> {
+    type Ref<'a>
+        = Option<T::Ref<'a>>
+    where
+        Self: 'a;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        diff.map(T::from_ref)
+    }
+}
+
+impl<T, S> Borrowed for std::collections::HashMap<String, T, S>
+where
+    T: Borrowed,
+    S: std::hash::BuildHasher + Default + 'static,
+{
+    type Ref<'a>
+        = std::collections::HashMap<&'a str, T::Ref<'a>>
+    where
+        Self: 'a;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        diff.into_iter()
+            .map(|(k, v)| (k.to_owned(), T::from_ref(v)))
+            .collect()
+    }
+}
+
+impl<T: Borrowed> Borrowed for BTreeMap<String, T> {
+    type Ref<'a>
+        = BTreeMap<&'a str, T::Ref<'a>>
+    where
+        Self: 'a;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        diff.into_iter()
+            .map(|(k, v)| (k.to_owned(), T::from_ref(v)))
+            .collect()
+    }
+}
+
+impl Borrowed for VisibilityChange {
+    type Ref<'a> = Self;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        diff
+    }
+}
+
+pub trait Info {
+    fn print_info(&self);
+}
+
+/// Categories of fields that can be included in or excluded from a diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldCategory {
+    Names,
+    Types,
+    Descriptions,
+    Examples,
+    Images,
+    Order,
+    Lists,
+    Visibility,
+}
+
+/// Top level sections of the docs of both stages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+#[value(rename_all = "snake_case")]
+pub enum Section {
+    Classes,
+    Events,
+    Concepts,
+    Defines,
+    GlobalObjects,
+    GlobalFunctions,
+    Prototypes,
+    Types,
+}
+
+impl Section {
+    /// Key of the section in the docs and diffs, e.g. `global_objects`.
+    #[must_use]
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Classes => "classes",
+            Self::Events => "events",
+            Self::Concepts => "concepts",
+            Self::Defines => "defines",
+            Self::GlobalObjects => "global_objects",
+            Self::GlobalFunctions => "global_functions",
+            Self::Prototypes => "prototypes",
+            Self::Types => "types",
+        }
+    }
+
+    /// Pattern for [`with_ignored`] matching all items of the section.
+    #[must_use]
+    pub fn ignore_pattern(self) -> String {
+        format!("{}/[^/]+", self.key())
+    }
+}
+
+impl FieldCategory {
+    const fn bit(self) -> u16 {
+        1 << self as u16
+    }
+
+    /// Whether fields of this category are diffed in the current run.
+    #[must_use]
+    pub fn is_enabled(self) -> bool {
+        DiffOptions::current().policy.allows(self)
+    }
+}
+
+/// Set of [`FieldCategory`]s that should be diffed.
+///
+/// Fields that don't belong to any category are always diffed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldPolicy {
+    enabled: u16,
+}
+
+impl Default for FieldPolicy {
+    fn default() -> Self {
+        Self::none()
+            .include(FieldCategory::Names)
+            .include(FieldCategory::Types)
+            .include(FieldCategory::Visibility)
+    }
+}
+
+impl FieldPolicy {
+    #[must_use]
+    pub const fn none() -> Self {
+        Self { enabled: 0 }
+    }
+
+    #[must_use]
+    pub fn all() -> Self {
+        <FieldCategory as clap::ValueEnum>::value_variants()
+            .iter()
+            .fold(Self::none(), |p, c| p.include(*c))
+    }
+
+    #[must_use]
+    pub const fn include(self, category: FieldCategory) -> Self {
+        Self {
+            enabled: self.enabled | category.bit(),
+        }
+    }
+
+    #[must_use]
+    pub const fn exclude(self, category: FieldCategory) -> Self {
+        Self {
+            enabled: self.enabled & !category.bit(),
+        }
+    }
+
+    #[must_use]
+    pub const fn allows(self, category: FieldCategory) -> bool {
+        self.enabled & category.bit() != 0
+    }
+
+    /// The policy with the `include`d categories added and the `exclude`d ones removed.
+    ///
+    /// Excluding a category takes precedence, it's never diffed even if it's included as well.
+    #[must_use]
+    pub fn overridden(self, include: &[FieldCategory], exclude: &[FieldCategory]) -> Self {
+        let included = include.iter().fold(self, |p, c| p.include(*c));
+        exclude.iter().fold(included, |p, c| p.exclude(*c))
+    }
+}
+
+/// Application the docs belong to, mods can ship docs in the same format for their own API.
+#[derive(
+    Debug, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Difference, Clone,
+)]
+#[serde(from = "String", into = "String")]
+pub enum Application {
+    #[default]
+    Factorio,
+    Other(String),
+}
+
+impl From<String> for Application {
+    fn from(name: String) -> Self {
+        match name.as_str() {
+            "factorio" => Self::Factorio,
+            _ => Self::Other(name),
+        }
+    }
+}
+
+impl From<Application> for String {
+    fn from(application: Application) -> Self {
+        match application {
+            Application::Factorio => "factorio".to_owned(),
+            Application::Other(name) => name,
+        }
+    }
+}
+
+impl std::fmt::Display for Application {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Factorio => write!(f, "Factorio"),
+            Self::Other(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+#[derive(
+    Debug, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Difference, Clone,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    #[default]
+    Prototype,
+    Runtime,
+}
+
+#[derive(
+    Debug, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Difference, Clone,
+)]
+pub struct Common {
+    pub application: Application,
+    pub stage: Stage,
+    pub application_version: String,
+    pub api_version: u8,
+}
+
+/// The docs a diff was created from, so stored diffs describe themselves.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Header {
+    pub application: Application,
+    pub stage: Stage,
+    pub from_version: String,
+    pub to_version: String,
+    pub api_versions: ApiVersions,
+    /// Name and version of the crate that created the diff, e.g. `fapi-diff 0.1.0`
+    pub generated_with: String,
+}
+
+/// `api_version`s of the source and target docs of a diff.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApiVersions {
+    pub from: u8,
+    pub to: u8,
+}
+
+impl Header {
+    /// Header of a diff from the `source` to the `target` docs, created by this version of the crate.
+    #[must_use]
+    pub fn new(source: &Common, target: &Common) -> Self {
+        Self {
+            application: target.application.clone(),
+            stage: target.stage.clone(),
+            from_version: source.application_version.clone(),
+            to_version: target.application_version.clone(),
+            api_versions: ApiVersions {
+                from: source.api_version,
+                to: target.api_version,
+            },
+            generated_with: concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION"))
+                .to_owned(),
+        }
+    }
+}
+
+impl Invert for Header {
+    fn invert(self) -> Self {
+        Self {
+            from_version: self.to_version,
+            to_version: self.from_version,
+            api_versions: ApiVersions {
+                from: self.api_versions.to,
+                to: self.api_versions.from,
+            },
+            ..self
+        }
+    }
+}
+
+impl Info for Common {
     fn print_info(&self) {
         eprintln!(
-            "{:?} @ {}: {:?}",
+            "{} @ {}: {:?}",
             self.application, self.application_version, self.stage
         );
     }
 }
 
+/// Sort order of an item, the docs use both integer and fractional orders.
+///
+/// Orders are compared with a small tolerance and serialized in their original form.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Order {
+    Int(i64),
+    Float(f64),
+}
+
+impl Order {
+    const TOLERANCE: f64 = 1e-6;
+
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub const fn as_f64(self) -> f64 {
+        match self {
+            Self::Int(i) => i as f64,
+            Self::Float(f) => f,
+        }
+    }
+
+    /// The order rounded to the comparison tolerance.
+    #[allow(clippy::cast_possible_truncation)]
+    fn key(self) -> i64 {
+        (self.as_f64() / Self::TOLERANCE).round() as i64
+    }
+}
+
+impl Default for Order {
+    fn default() -> Self {
+        Self::Int(0)
+    }
+}
+
+impl PartialEq for Order {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Order {}
+
+impl PartialOrd for Order {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Order {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl std::hash::Hash for Order {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// Game expansion needed to use an item, as listed in its `visibility`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Visibility {
+    Base,
+    SpaceAge,
+    /// Expansions this version doesn't know about yet
+    Unknown(String),
+}
+
+impl From<String> for Visibility {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "base" => Self::Base,
+            "space_age" => Self::SpaceAge,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<Visibility> for String {
+    fn from(value: Visibility) -> Self {
+        match value {
+            Visibility::Base => "base".to_owned(),
+            Visibility::SpaceAge => "space_age".to_owned(),
+            Visibility::Unknown(value) => value,
+        }
+    }
+}
+
+/// Expansions that were added to or removed from the visibility of an item.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VisibilityChange {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<Visibility>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<Visibility>,
+}
+
+impl VisibilityChange {
+    /// Changes between two visibilities, `None` if they're the same
+    /// or visibility isn't diffed in the current run.
+    #[must_use]
+    pub fn new(old: &BTreeSet<Visibility>, new: &BTreeSet<Visibility>) -> Option<Self> {
+        if old == new || !FieldCategory::Visibility.is_enabled() {
+            return None;
+        }
+
+        Some(Self {
+            added: new.difference(old).cloned().collect(),
+            removed: old.difference(new).cloned().collect(),
+        })
+    }
+}
+
+impl Invert for VisibilityChange {
+    fn invert(self) -> Self {
+        Self {
+            added: self.removed,
+            removed: self.added,
+        }
+    }
+}
+
+/// Direction of a change of whether a parameter, property or attribute is optional,
+/// reported instead of the `optional` flag with [`DiffOptions::directional`].
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Optionality {
+    /// Can't be left out anymore, breaking when passed in
+    NowRequired,
+    /// Can be left out now, breaking when returned or read
+    NowOptional,
+}
+
+impl Optionality {
+    /// Direction of the change to `optional`.
+    #[must_use]
+    pub const fn to(optional: bool) -> Self {
+        if optional {
+            Self::NowOptional
+        } else {
+            Self::NowRequired
+        }
+    }
+}
+
+impl Invert for Optionality {
+    fn invert(self) -> Self {
+        match self {
+            Self::NowRequired => Self::NowOptional,
+            Self::NowOptional => Self::NowRequired,
+        }
+    }
+}
+
+impl Borrowed for Optionality {
+    type Ref<'a> = Self;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        diff
+    }
+}
+
+/// Direction of a change of whether an attribute can be written,
+/// reported alongside the write type with [`DiffOptions::directional`].
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Writability {
+    /// Can't be written anymore, breaking for mods that write it
+    NowReadOnly,
+    NowWritable,
+}
+
+impl Writability {
+    /// Direction of the change to `writable`.
+    #[must_use]
+    pub const fn to(writable: bool) -> Self {
+        if writable {
+            Self::NowWritable
+        } else {
+            Self::NowReadOnly
+        }
+    }
+}
+
+impl Invert for Writability {
+    fn invert(self) -> Self {
+        match self {
+            Self::NowReadOnly => Self::NowWritable,
+            Self::NowWritable => Self::NowReadOnly,
+        }
+    }
+}
+
+impl Borrowed for Writability {
+    type Ref<'a> = Self;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        diff
+    }
+}
+
+/// Images that were added to or removed from an item and images whose caption changed,
+/// identified by their file names.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImagesChange {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<Image>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<Image>,
+    /// Changed captions keyed by file name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub captions: BTreeMap<String, Change<Option<String>>>,
+}
+
+impl ImagesChange {
+    /// Changes between two lists of images, `None` if they have the same images with the same captions
+    /// or images aren't diffed in the current run. Images that only moved aren't changes.
+    #[must_use]
+    pub fn new(old: &[Image], new: &[Image]) -> Option<Self> {
+        fn find<'a>(images: &'a [Image], filename: &str) -> Option<&'a Image> {
+            images.iter().find(|i| i.filename == filename)
+        }
+
+        if old == new || !FieldCategory::Images.is_enabled() {
+            return None;
+        }
+
+        let res = Self {
+            added: new
+                .iter()
+                .filter(|i| find(old, &i.filename).is_none())
+                .cloned()
+                .collect(),
+            removed: old
+                .iter()
+                .filter(|i| find(new, &i.filename).is_none())
+                .cloned()
+                .collect(),
+            captions: old
+                .iter()
+                .filter_map(|o| {
+                    let n = find(new, &o.filename)?;
+                    (o.caption != n.caption).then(|| {
+                        (
+                            o.filename.clone(),
+                            Change::new(o.caption.clone(), n.caption.clone()),
+                        )
+                    })
+                })
+                .collect(),
+        };
+
+        (res != Self::default()).then_some(res)
+    }
+}
+
+impl Invert for ImagesChange {
+    fn invert(self) -> Self {
+        Self {
+            added: self.removed,
+            removed: self.added,
+            captions: self.captions.invert(),
+        }
+    }
+}
+
+impl Borrowed for ImagesChange {
+    type Ref<'a> = Self;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        diff
+    }
+}
+
+/// A complex type this version doesn't know about, kept as its raw JSON to be diffed as a whole.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtherComplexType {
+    /// Kind of the complex type, e.g. `union`
+    pub complex_type: String,
+    /// The whole complex type, including its `complex_type`
+    pub raw: serde_json::Value,
+}
+
+impl Serialize for OtherComplexType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.raw.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for OtherComplexType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = serde_json::Value::deserialize(deserializer)?;
+        let complex_type = raw
+            .get("complex_type")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| serde::de::Error::missing_field("complex_type"))?
+            .to_owned();
+
+        Ok(Self { complex_type, raw })
+    }
+}
+
+// JSON values can't be hashed or ordered, their serialized forms can
+impl std::hash::Hash for OtherComplexType {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.raw.to_string().hash(state);
+    }
+}
+
+impl PartialOrd for OtherComplexType {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OtherComplexType {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (&self.complex_type, self.raw.to_string())
+            .cmp(&(&other.complex_type, other.raw.to_string()))
+    }
+}
+
 #[derive(
-    Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Difference, Clone, Default, Hash,
+    Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Difference, Clone, Default, Hash,
 )]
 pub struct Image {
     pub filename: String,
     pub caption: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{FieldCategory, FieldPolicy};
+
+    #[test]
+    fn exclude_wins_over_include_of_the_same_category() {
+        let descriptions = [FieldCategory::Descriptions];
+
+        for base in [
+            FieldPolicy::none(),
+            FieldPolicy::default(),
+            FieldPolicy::all(),
+        ] {
+            let policy = base.overridden(&descriptions, &descriptions);
+            assert!(!policy.allows(FieldCategory::Descriptions));
+        }
+    }
+
+    #[test]
+    fn exclude_wins_over_all_categories() {
+        let policy = FieldPolicy::all().overridden(&[], &[FieldCategory::Images]);
+
+        assert!(!policy.allows(FieldCategory::Images));
+        assert!(policy.allows(FieldCategory::Descriptions));
+        assert!(policy.allows(FieldCategory::Examples));
+    }
+
+    #[test]
+    fn exclude_wins_over_defaults() {
+        let policy = FieldPolicy::default().overridden(&[], &[FieldCategory::Types]);
+
+        assert!(!policy.allows(FieldCategory::Types));
+        assert!(policy.allows(FieldCategory::Names));
+        assert!(policy.allows(FieldCategory::Visibility));
+    }
+
+    #[test]
+    fn include_only_adds_the_named_categories() {
+        let policy = FieldPolicy::default().overridden(&[FieldCategory::Examples], &[]);
+
+        assert!(policy.allows(FieldCategory::Examples));
+        assert!(!policy.allows(FieldCategory::Descriptions));
+        assert_eq!(
+            policy,
+            FieldPolicy::default().include(FieldCategory::Examples)
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::{borrow::Borrow, cell::RefCell, collections::HashSet, fmt, ops::Deref, sync::Arc};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+thread_local! {
+    /// Strings interned by the current [`scope`] of this thread, `None` outside of one.
+    static INTERNER: RefCell<Option<HashSet<Arc<str>>>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with the strings created in it interned on this thread.
+///
+/// Scopes nest, the interned strings are shared by everything up to the outermost scope
+/// and dropped when it ends. Strings outlive it as long as they're used,
+/// only the table to look them up is gone, so long-running hosts don't keep every string they've seen.
+pub fn scope<R>(f: impl FnOnce() -> R) -> R {
+    /// Drops the table at the end of the outermost scope, even when `f` panics.
+    struct Outermost;
+
+    impl Drop for Outermost {
+        fn drop(&mut self) {
+            INTERNER.with_borrow_mut(|strings| *strings = None);
+        }
+    }
+
+    let outermost = INTERNER.with_borrow_mut(|strings| {
+        strings.is_none().then(|| {
+            *strings = Some(HashSet::new());
+            Outermost
+        })
+    });
+    let result = f();
+    drop(outermost);
+
+    result
+}
+
+/// Number of strings interned by the current [`scope`] of this thread, `0` outside of one.
+#[must_use]
+pub fn interned_count() -> usize {
+    INTERNER.with_borrow(|strings| strings.as_ref().map_or(0, HashSet::len))
+}
+
+/// A shared string that's stored only once per [`scope`] no matter how often it occurs.
+///
+/// Used for the strings that repeat a lot within and across docs like type names,
+/// names and descriptions so holding both docs of a diff at once doesn't multiply them.
+/// Outside of a scope every string gets its own copy.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interned(Arc<str>);
+
+impl Interned {
+    /// Returns the shared copy of `s` within the current [`scope`], adding it to the interned strings if it's new.
+    #[must_use]
+    pub fn new(s: &str) -> Self {
+        let shared = INTERNER.with_borrow_mut(|strings| {
+            let Some(strings) = strings else {
+                return Arc::from(s);
+            };
+
+            strings.get(s).cloned().unwrap_or_else(|| {
+                let shared: Arc<str> = Arc::from(s);
+                strings.insert(shared.clone());
+                shared
+            })
+        });
+
+        Self(shared)
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Default for Interned {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl Deref for Interned {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Interned {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Interned {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Interned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Interned {
+    fn from(s: &str) -> Self {
+        Self::new(s)
+    }
+}
+
+impl From<String> for Interned {
+    fn from(s: String) -> Self {
+        Self::new(&s)
+    }
+}
+
+impl From<Interned> for String {
+    fn from(s: Interned) -> Self {
+        s.0.as_ref().to_owned()
+    }
+}
+
+impl PartialEq<str> for Interned {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Interned {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Interned {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl Serialize for Interned {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Interned {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = Interned;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                Ok(Interned::new(s))
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
@@ -0,0 +1,203 @@
+use std::{borrow::Cow, collections::HashMap, marker::PhantomData};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::value::RawValue;
+
+use super::{
+    diff_helper::Items,
+    prototype::{self, Prototype, PrototypeItems, TypeConcept},
+    runtime::{self, Class, Concept, Define, Event, Method, Parameter, RuntimeItems},
+    ChangeCounts, Common, DiffEntry, Section,
+};
+
+/// A doc of any stage whose items are kept as raw JSON
+/// and only deserialized while they're diffed.
+pub struct LazyDoc<'a> {
+    what: String,
+    common: Common,
+    sections: HashMap<String, HashMap<String, &'a RawValue>>,
+}
+
+#[derive(Deserialize)]
+struct Name {
+    name: String,
+}
+
+impl<'a> LazyDoc<'a> {
+    /// Indexes the items of a raw doc by name, `what` names the doc in the error message.
+    pub fn parse(raw: &'a [u8], what: &str) -> crate::Result<Self> {
+        let top = match serde_json::from_slice::<HashMap<String, &'a RawValue>>(raw) {
+            Ok(top) => top,
+            Err(e) => return Err(crate::Error::parse(what, raw, e)),
+        };
+        let mut sections = HashMap::new();
+
+        // only the sections of named items are diffed, other lists are skipped
+        let keys = <Section as clap::ValueEnum>::value_variants()
+            .iter()
+            .map(|s| s.key())
+            .collect::<Vec<_>>();
+
+        for (key, value) in top {
+            if !keys.contains(&key.as_str()) {
+                continue;
+            }
+
+            let items = serde_json::from_str::<Vec<&'a RawValue>>(value.get())
+                .map_err(|e| parse_error(what, key.clone(), value, e))?;
+            let mut section = HashMap::new();
+
+            for (i, item) in items.into_iter().enumerate() {
+                let Name { name } = serde_json::from_str(item.get())
+                    .map_err(|e| parse_error(what, format!("{key}[{i}]"), item, e))?;
+
+                section.insert(name, item);
+            }
+
+            sections.insert(key, section);
+        }
+
+        Ok(Self {
+            what: what.to_owned(),
+            common: crate::parse(raw, what)?,
+            sections,
+        })
+    }
+
+    fn section<T>(&self, key: &'static str) -> LazyItems<'_, 'a, T> {
+        LazyItems {
+            what: &self.what,
+            section: key,
+            items: self.sections.get(key),
+            item: PhantomData,
+        }
+    }
+
+    /// Header of the doc.
+    #[must_use]
+    pub const fn info(&self) -> &Common {
+        &self.common
+    }
+
+    /// Diffs two runtime docs item by item, see [`super::Doc::diff_entries`].
+    pub fn runtime_entries(
+        &self,
+        other: &Self,
+        emit: &mut dyn FnMut(DiffEntry),
+    ) -> crate::Result<()> {
+        runtime::entries(self, other, emit)
+    }
+
+    /// Diffs two prototype docs item by item, see [`super::Doc::diff_entries`].
+    pub fn prototype_entries(
+        &self,
+        other: &Self,
+        emit: &mut dyn FnMut(DiffEntry),
+    ) -> crate::Result<()> {
+        prototype::entries(self, other, emit)
+    }
+
+    /// Counts the changed items of two runtime docs, see [`super::Doc::count_changes`].
+    pub fn runtime_counts(&self, other: &Self) -> crate::Result<ChangeCounts> {
+        runtime::counts(self, other)
+    }
+
+    /// Counts the changed items of two prototype docs, see [`super::Doc::count_changes`].
+    pub fn prototype_counts(&self, other: &Self) -> crate::Result<ChangeCounts> {
+        prototype::counts(self, other)
+    }
+}
+
+/// A section of a [`LazyDoc`], items are deserialized whenever they're looked up.
+struct LazyItems<'s, 'a, T> {
+    what: &'s str,
+    section: &'static str,
+    items: Option<&'s HashMap<String, &'a RawValue>>,
+    item: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned + Clone> Items<T> for LazyItems<'_, '_, T> {
+    fn names(&self) -> Vec<&str> {
+        self.items
+            .map(|i| i.keys().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    fn item(&self, name: &str) -> crate::Result<Option<Cow<'_, T>>> {
+        let Some(raw) = self.items.and_then(|i| i.get(name)) else {
+            return Ok(None);
+        };
+
+        match serde_json::from_str(raw.get()) {
+            Ok(item) => Ok(Some(Cow::Owned(item))),
+            Err(e) => Err(parse_error(
+                self.what,
+                format!("{}[{name:?}]", self.section),
+                raw,
+                e,
+            )),
+        }
+    }
+}
+
+/// A [`crate::Error::Parse`] of the raw value at `path` of the doc `what`.
+fn parse_error(what: &str, path: String, raw: &RawValue, e: serde_json::Error) -> crate::Error {
+    // the path within the value, prefixed with the value itself
+    let inner = crate::json_path::locate(raw.get().as_bytes(), e.line(), e.column());
+
+    crate::Error::Parse {
+        what: what.to_owned(),
+        path: Some(
+            [Some(path), inner]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join("."),
+        ),
+        source: e,
+    }
+}
+
+impl RuntimeItems for LazyDoc<'_> {
+    fn api_version(&self) -> u8 {
+        self.common.api_version
+    }
+
+    fn classes(&self) -> impl Items<Class> {
+        self.section("classes")
+    }
+
+    fn events(&self) -> impl Items<Event> {
+        self.section("events")
+    }
+
+    fn concepts(&self) -> impl Items<Concept> {
+        self.section("concepts")
+    }
+
+    fn defines(&self) -> impl Items<Define> {
+        self.section("defines")
+    }
+
+    fn global_objects(&self) -> impl Items<Parameter> {
+        self.section("global_objects")
+    }
+
+    fn global_functions(&self) -> impl Items<Method> {
+        self.section("global_functions")
+    }
+}
+
+impl PrototypeItems for LazyDoc<'_> {
+    fn prototypes(&self) -> impl Items<Prototype> {
+        self.section("prototypes")
+    }
+
+    fn types(&self) -> impl Items<TypeConcept> {
+        self.section("types")
+    }
+
+    fn defines(&self) -> impl Items<Define> {
+        self.section("defines")
+    }
+}
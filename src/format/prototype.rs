@@ -1,11 +1,23 @@
-use std::{collections::HashMap, ops::Deref};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    ops::Deref,
+    sync::LazyLock,
+};
 
 use serde::{de::Visitor, Deserialize, Serialize};
 use structdiff::{Difference, StructDiff};
 
+use crate::links::Links;
+
 use super::{
-    diff_helper::{self, vec_diff, DiffableVec, DiffableVecDiff, SingleDiff},
-    Image,
+    diff_helper::{
+        self, canonical_order, canonical_vec_diff, count_each, diff_each, diff_empty, empty,
+        from_empty, in_path, vec_diff, Canonical, DiffableVec, DiffableVecDiff, ItemCounts, Items,
+        SingleDiff,
+    },
+    reformat::{collapsing_links, counting_links, streaming_links, text_changed, texts_changed},
+    Change, ChangeCounts, DiffOptions, FieldCategory, Image, ImagesChange, Interned, Invert,
+    Metrics, Optionality, Order, OtherComplexType, TextChange, Visibility, VisibilityChange,
 };
 
 impl<T> diff_helper::Named for T
@@ -40,16 +52,217 @@ impl Deref for PrototypeDoc {
     }
 }
 
+impl PrototypeDoc {
+    /// Diffs all sections of the docs, see [`super::Doc::diff`].
+    fn diff_sections(&self, other: &Self) -> PrototypeDocDiff {
+        let _ = Metrics::take();
+
+        let mut diff = PrototypeDocDiff {
+            diff_format_version: super::DIFF_FORMAT_VERSION,
+            header: Some(super::Header::new(self, other)),
+            prototypes: in_path("prototypes", || self.prototypes.diff(&other.prototypes)),
+            types: in_path("types", || self.types.diff(&other.types)),
+            defines: in_path("defines", || self.defines.diff(&other.defines)),
+            added: ItemNames {
+                prototypes: in_path("prototypes", || other.prototypes.removed(&self.prototypes)),
+                types: in_path("types", || other.types.removed(&self.types)),
+                defines: in_path("defines", || other.defines.removed(&self.defines)),
+            },
+            removed: ItemNames {
+                prototypes: in_path("prototypes", || self.prototypes.removed(&other.prototypes)),
+                types: in_path("types", || self.types.removed(&other.types)),
+                defines: in_path("defines", || self.defines.removed(&other.defines)),
+            },
+            metrics: Metrics::take(),
+            urls: BTreeMap::new(),
+            context: BTreeMap::new(),
+            reformatted_descriptions: 0,
+        };
+
+        if super::DiffOptions::current().anchor_links {
+            diff.urls = diff.item_urls(&Links::of(other));
+        }
+
+        if super::DiffOptions::current().context != super::Context::None {
+            diff.context = diff.item_context(other);
+        }
+
+        diff
+    }
+}
+
 impl super::Doc for PrototypeDoc {
     type Diff = PrototypeDocDiff;
 
-    fn diff(&self, other: &Self) -> Self::Diff {
-        Self::Diff {
-            prototypes: self.prototypes.diff(&other.prototypes),
-            types: self.types.diff(&other.types),
-            defines: self.defines.diff(&other.defines),
+    fn prepare(&mut self) {
+        if super::DiffOptions::current().resolve_inheritance {
+            self.prototypes = self.resolved_prototypes();
         }
     }
+
+    fn diff(&self, other: &Self) -> Self::Diff {
+        let (mut diff, reformatted) = collapsing_links(|| self.diff_sections(other));
+        diff.reformatted_descriptions = reformatted;
+
+        diff
+    }
+
+    fn diff_entries(
+        &self,
+        other: &Self,
+        emit: &mut dyn FnMut(super::DiffEntry),
+    ) -> crate::Result<()> {
+        entries(self, other, emit)
+    }
+
+    fn count_changes(&self, other: &Self) -> crate::Result<ChangeCounts> {
+        counts(self, other)
+    }
+}
+
+/// The items of a prototype doc, parsed up front or on demand.
+pub(super) trait PrototypeItems {
+    fn prototypes(&self) -> impl Items<Prototype>;
+    fn types(&self) -> impl Items<TypeConcept>;
+    fn defines(&self) -> impl Items<crate::format::runtime::Define>;
+}
+
+impl PrototypeItems for PrototypeDoc {
+    fn prototypes(&self) -> impl Items<Prototype> {
+        &self.prototypes
+    }
+
+    fn types(&self) -> impl Items<TypeConcept> {
+        &self.types
+    }
+
+    fn defines(&self) -> impl Items<crate::format::runtime::Define> {
+        &self.defines
+    }
+}
+
+/// Diffs the docs item by item, see [`super::Doc::diff_entries`].
+pub(super) fn entries(
+    source: &impl PrototypeItems,
+    target: &impl PrototypeItems,
+    emit: &mut dyn FnMut(super::DiffEntry),
+) -> crate::Result<()> {
+    let mut res = Ok(());
+    let reformatted = streaming_links(
+        // counting is cheaper than building the entries, failures are reported by the second pass
+        || {
+            item_counts(source, target).ok();
+        },
+        || res = item_entries(source, target, emit),
+    );
+    res?;
+
+    if reformatted > 0 {
+        emit(super::DiffEntry::reformatted_descriptions(reformatted));
+    }
+
+    Ok(())
+}
+
+/// Counts the changed items per section, see [`super::Doc::count_changes`].
+pub(super) fn counts(
+    source: &impl PrototypeItems,
+    target: &impl PrototypeItems,
+) -> crate::Result<ChangeCounts> {
+    let (sections, collapsed) = counting_links(|| item_counts(source, target));
+
+    Ok(ChangeCounts::from_sections(sections?, collapsed))
+}
+
+fn item_counts(
+    source: &impl PrototypeItems,
+    target: &impl PrototypeItems,
+) -> crate::Result<Vec<(&'static str, (ItemCounts, usize))>> {
+    Ok(vec![
+        (
+            "prototypes",
+            in_path("prototypes", || {
+                count_each(&source.prototypes(), &target.prototypes())
+            })?,
+        ),
+        (
+            "types",
+            in_path("types", || count_each(&source.types(), &target.types()))?,
+        ),
+        (
+            "defines",
+            in_path("defines", || {
+                count_each(&source.defines(), &target.defines())
+            })?,
+        ),
+    ])
+}
+
+fn item_entries(
+    source: &impl PrototypeItems,
+    target: &impl PrototypeItems,
+    emit: &mut dyn FnMut(super::DiffEntry),
+) -> crate::Result<()> {
+    let target_prototypes = target.prototypes();
+    in_path("prototypes", || {
+        diff_each(
+            &source.prototypes(),
+            &target_prototypes,
+            |name, status, d| {
+                super::DiffEntry::emit(emit, "prototype", name, status, &d, &target_prototypes);
+            },
+        )
+    })?;
+    let target_types = target.types();
+    in_path("types", || {
+        diff_each(&source.types(), &target_types, |name, status, d| {
+            super::DiffEntry::emit(emit, "type", name, status, &d, &target_types);
+        })
+    })?;
+    let target_defines = target.defines();
+    in_path("defines", || {
+        diff_each(&source.defines(), &target_defines, |name, status, d| {
+            super::DiffEntry::emit(emit, "define", name, status, &d, &target_defines);
+        })
+    })?;
+
+    Ok(())
+}
+
+impl PrototypeDoc {
+    /// Prototypes with the properties of all their parents added.
+    ///
+    /// Properties of a prototype take precedence over inherited ones with the same name.
+    #[must_use]
+    pub fn resolved_prototypes(&self) -> DiffableVec<Prototype> {
+        self.prototypes
+            .values()
+            .map(|prototype| {
+                let mut resolved = prototype.clone();
+                let mut properties = prototype.properties.values().cloned().collect::<Vec<_>>();
+                let mut visited = HashSet::from([prototype.name.as_str()]);
+                let mut parent = self.prototypes.get(&prototype.parent);
+
+                while let Some(p) = parent {
+                    if !visited.insert(p.name.as_str()) {
+                        break;
+                    }
+
+                    for property in p.properties.values() {
+                        if !properties.iter().any(|o| o.name == property.name) {
+                            properties.push(property.clone());
+                        }
+                    }
+
+                    parent = self.prototypes.get(&p.parent);
+                }
+
+                resolved.properties = properties.into();
+                resolved
+            })
+            .collect::<Vec<_>>()
+            .into()
+    }
 }
 
 impl super::Info for PrototypeDoc {
@@ -62,11 +275,140 @@ impl super::Info for PrototypeDoc {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 pub struct PrototypeDocDiff {
+    /// Schema version of the diff, see [`super::DIFF_FORMAT_VERSION`]
+    #[serde(default = "super::unversioned")]
+    pub diff_format_version: u32,
+    /// The compared docs, missing in diffs of older versions of this crate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<super::Header>,
     pub prototypes: DiffableVecDiff<Prototype>,
     pub types: DiffableVecDiff<TypeConcept>,
     pub defines: DiffableVecDiff<crate::format::runtime::Define>,
+    #[serde(default)]
+    pub added: ItemNames,
+    #[serde(default)]
+    pub removed: ItemNames,
+    /// Counters of the changed prototypes, see [`super::DiffOptions::metrics`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metrics: BTreeMap<String, Metrics>,
+    /// Pages of the changed items in the target docs, see [`super::DiffOptions::anchor_links`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub urls: BTreeMap<String, BTreeMap<String, String>>,
+    /// The complete changed items in the target docs, see [`super::DiffOptions::context`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub context: BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+    /// Number of descriptions whose only changes were their links,
+    /// see [`super::DiffOptions::keep_link_changes`]
+    #[serde(default, skip_serializing_if = "super::is_zero")]
+    pub reformatted_descriptions: usize,
+}
+
+/// Names of top level items, e.g. the ones that were added or removed.
+#[derive(Serialize, Deserialize, Default)]
+pub struct ItemNames {
+    pub prototypes: Vec<String>,
+    pub types: Vec<String>,
+    pub defines: Vec<String>,
+}
+
+impl PrototypeDocDiff {
+    /// Number of added, removed and changed items, see [`crate::format::runtime::RuntimeDocDiff::change_count`].
+    ///
+    /// Changed prototypes count their changed members instead if the diff has [`super::DiffOptions::metrics`].
+    #[must_use]
+    pub fn change_count(&self) -> usize {
+        super::change_count([
+            (
+                self.prototypes.keys().collect(),
+                self.added.prototypes.as_slice(),
+                self.removed.prototypes.as_slice(),
+                Some(&self.metrics),
+            ),
+            (
+                self.types.keys().collect(),
+                self.added.types.as_slice(),
+                self.removed.types.as_slice(),
+                None,
+            ),
+            (
+                self.defines.keys().collect(),
+                self.added.defines.as_slice(),
+                self.removed.defines.as_slice(),
+                None,
+            ),
+        ])
+    }
+
+    /// Pages of the changed items, removed items aren't linked.
+    fn item_urls(&self, links: &Links) -> BTreeMap<String, BTreeMap<String, String>> {
+        super::item_urls(
+            links,
+            [
+                (
+                    "prototypes",
+                    self.prototypes.keys().collect(),
+                    self.removed.prototypes.as_slice(),
+                ),
+                (
+                    "types",
+                    self.types.keys().collect(),
+                    self.removed.types.as_slice(),
+                ),
+                (
+                    "defines",
+                    self.defines.keys().collect(),
+                    self.removed.defines.as_slice(),
+                ),
+            ],
+        )
+    }
+
+    /// The complete changed items of each section, removed items have none.
+    fn item_context(
+        &self,
+        target: &PrototypeDoc,
+    ) -> BTreeMap<String, BTreeMap<String, serde_json::Value>> {
+        [
+            (
+                "prototypes",
+                super::item_context("prototype", self.prototypes.keys(), &target.prototypes),
+            ),
+            (
+                "types",
+                super::item_context("type", self.types.keys(), &target.types),
+            ),
+            (
+                "defines",
+                super::item_context("define", self.defines.keys(), &target.defines),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, items)| !items.is_empty())
+        .map(|(section, items)| (section.to_owned(), items))
+        .collect()
+    }
+}
+
+impl Invert for PrototypeDocDiff {
+    fn invert(self) -> Self {
+        Self {
+            // missing fields were filled in while deserializing
+            diff_format_version: super::DIFF_FORMAT_VERSION,
+            header: self.header.invert(),
+            prototypes: self.prototypes.invert(),
+            types: self.types.invert(),
+            defines: self.defines.invert(),
+            added: self.removed,
+            removed: self.added,
+            metrics: self.metrics.invert(),
+            // the links and items are the ones of the original target docs
+            urls: BTreeMap::new(),
+            context: BTreeMap::new(),
+            reformatted_descriptions: self.reformatted_descriptions,
+        }
+    }
 }
 
 impl super::Info for PrototypeDocDiff {
@@ -77,9 +419,9 @@ impl super::Info for PrototypeDocDiff {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Clone, Default, Hash)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Default, Hash)]
 pub struct Common {
-    pub description: String,
+    pub description: Interned,
 
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub lists: Vec<String>,
@@ -91,59 +433,72 @@ pub struct Common {
     pub images: Vec<Image>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum CommonDiff {
-    Description(String),
-    Lists(Vec<String>),
-    Examples(Vec<String>),
-    Images(Vec<Image>),
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum CommonDiff / CommonDiffRef {
+        Description(TextChange<Interned>),
+        Lists(Change<Vec<String>>),
+        Examples(Change<Vec<String>>),
+        Images(ImagesChange),
+    }
 }
 
 impl StructDiff for Common {
     type Diff = CommonDiff;
 
-    type DiffRef<'target> = CommonDiff;
+    type DiffRef<'target> = CommonDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
-        let cli = crate::CLI.with_borrow(Clone::clone);
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
-        if (cli.descriptions || cli.full) && self.description != updated.description {
-            res.push(CommonDiff::Description(updated.description.clone()));
+        if FieldCategory::Descriptions.is_enabled()
+            && text_changed(&self.description, &updated.description)
+        {
+            res.push(CommonDiffRef::Description(TextChange::borrowed(
+                &self.description,
+                &updated.description,
+            )));
         }
 
-        if cli.full && self.lists != updated.lists {
-            res.push(CommonDiff::Lists(updated.lists.clone()));
+        if FieldCategory::Lists.is_enabled() && texts_changed(&self.lists, &updated.lists) {
+            res.push(CommonDiffRef::Lists(Change::borrowed(
+                &self.lists,
+                &updated.lists,
+            )));
         }
 
-        if (cli.examples || cli.full) && self.examples != updated.examples {
-            res.push(CommonDiff::Examples(updated.examples.clone()));
+        if FieldCategory::Examples.is_enabled() && texts_changed(&self.examples, &updated.examples)
+        {
+            res.push(CommonDiffRef::Examples(Change::borrowed(
+                &self.examples,
+                &updated.examples,
+            )));
         }
 
-        if cli.full && self.images != updated.images {
-            res.push(CommonDiff::Images(updated.images.clone()));
+        if let Some(images) = ImagesChange::new(&self.images, &updated.images) {
+            res.push(CommonDiffRef::Images(images));
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Clone, Default, Hash)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Default, Hash)]
 pub struct NamedCommon {
     #[serde(flatten)]
     common: Common,
 
-    pub name: String,
-    pub order: i16,
+    pub name: Interned,
+    pub order: Order,
 }
 
 impl diff_helper::Named for NamedCommon {
@@ -152,43 +507,55 @@ impl diff_helper::Named for NamedCommon {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum NamedCommonDiff {
-    Name(String),
-    Order(i16),
-    // common fields
-    Description(String),
-    Lists(Vec<String>),
-    Examples(Vec<String>),
-    Images(Vec<Image>),
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum NamedCommonDiff / NamedCommonDiffRef {
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        // common fields
+        Description(TextChange<Interned>),
+        Lists(Change<Vec<String>>),
+        Examples(Change<Vec<String>>),
+        Images(ImagesChange),
+    }
 }
 
 impl StructDiff for NamedCommon {
     type Diff = NamedCommonDiff;
 
-    type DiffRef<'target> = NamedCommonDiff;
+    type DiffRef<'target> = NamedCommonDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
-        if self.name != updated.name {
-            res.push(NamedCommonDiff::Name(updated.name.clone()));
+        if FieldCategory::Names.is_enabled() && self.name != updated.name {
+            res.push(NamedCommonDiffRef::Name(Change::borrowed(
+                &self.name,
+                &updated.name,
+            )));
         }
 
-        if crate::CLI.with_borrow(|c| c.full) && self.order != updated.order {
-            res.push(NamedCommonDiff::Order(updated.order));
+        if FieldCategory::Order.is_enabled() && self.order != updated.order {
+            res.push(NamedCommonDiffRef::Order(Change::borrowed(
+                &self.order,
+                &updated.order,
+            )));
         }
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
+            let common_diff = self.common.diff_ref(&updated.common);
 
             for d in common_diff {
                 let d = match d {
-                    CommonDiff::Description(desc) => Self::Diff::Description(desc),
-                    CommonDiff::Lists(lists) => Self::Diff::Lists(lists),
-                    CommonDiff::Examples(examples) => Self::Diff::Examples(examples),
-                    CommonDiff::Images(images) => Self::Diff::Images(images),
+                    CommonDiffRef::Description(desc) => NamedCommonDiffRef::Description(desc),
+                    CommonDiffRef::Lists(lists) => NamedCommonDiffRef::Lists(lists),
+                    CommonDiffRef::Examples(examples) => NamedCommonDiffRef::Examples(examples),
+                    CommonDiffRef::Images(images) => NamedCommonDiffRef::Images(images),
                 };
                 res.push(d);
             }
@@ -197,10 +564,6 @@ impl StructDiff for NamedCommon {
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
@@ -219,8 +582,8 @@ pub struct Prototype {
     #[serde(flatten)]
     common: NamedCommon,
 
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub visibility: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub visibility: BTreeSet<Visibility>,
 
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub parent: String,
@@ -247,101 +610,134 @@ pub struct Prototype {
     pub custom_properties: Option<CustomProperties>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum PrototypeDiff {
-    // common fields
-    Name(String),
-    Order(i16),
-    Description(String),
-    Lists(Vec<String>),
-    Examples(Vec<String>),
-    Images(Vec<Image>),
-    // other fields
-    Visibility(Vec<String>),
-    Parent(String),
-    Abstract(bool),
-    Typename(String),
-    InstanceLimit(String),
-    Deprecated(bool),
-    Properties(DiffableVecDiff<Property>),
-    CustomProperties(SingleDiff<CustomProperties>),
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum PrototypeDiff / PrototypeDiffRef {
+        // common fields
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        Description(TextChange<Interned>),
+        Lists(Change<Vec<String>>),
+        Examples(Change<Vec<String>>),
+        Images(ImagesChange),
+        // other fields
+        Visibility(VisibilityChange),
+        Parent(Change<String>),
+        Abstract(Change<bool>),
+        Typename(Change<String>),
+        InstanceLimit(Change<String>),
+        Deprecated(Change<bool>),
+        Properties(DiffableVecDiff<Property>),
+        CustomProperties(CustomPropertiesChange),
+    }
 }
 
 impl StructDiff for Prototype {
     type Diff = PrototypeDiff;
 
-    type DiffRef<'target> = PrototypeDiff;
+    type DiffRef<'target> = PrototypeDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
+            let common_diff = self.common.diff_ref(&updated.common);
 
             for d in common_diff {
                 let d = match d {
-                    NamedCommonDiff::Name(name) => Self::Diff::Name(name),
-                    NamedCommonDiff::Order(order) => Self::Diff::Order(order),
-                    NamedCommonDiff::Description(desc) => Self::Diff::Description(desc),
-                    NamedCommonDiff::Lists(lists) => Self::Diff::Lists(lists),
-                    NamedCommonDiff::Examples(examples) => Self::Diff::Examples(examples),
-                    NamedCommonDiff::Images(images) => Self::Diff::Images(images),
+                    NamedCommonDiffRef::Name(name) => PrototypeDiffRef::Name(name),
+                    NamedCommonDiffRef::Order(order) => PrototypeDiffRef::Order(order),
+                    NamedCommonDiffRef::Description(desc) => PrototypeDiffRef::Description(desc),
+                    NamedCommonDiffRef::Lists(lists) => PrototypeDiffRef::Lists(lists),
+                    NamedCommonDiffRef::Examples(examples) => PrototypeDiffRef::Examples(examples),
+                    NamedCommonDiffRef::Images(images) => PrototypeDiffRef::Images(images),
                 };
                 res.push(d);
             }
         }
 
-        if self.visibility != updated.visibility {
-            res.push(PrototypeDiff::Visibility(updated.visibility.clone()));
+        if let Some(change) = VisibilityChange::new(&self.visibility, &updated.visibility) {
+            res.push(PrototypeDiffRef::Visibility(change));
         }
 
         if self.parent != updated.parent {
-            res.push(PrototypeDiff::Parent(updated.parent.clone()));
+            res.push(PrototypeDiffRef::Parent(Change::borrowed(
+                &self.parent,
+                &updated.parent,
+            )));
         }
 
         if self.abstract_ != updated.abstract_ {
-            res.push(PrototypeDiff::Abstract(updated.abstract_));
+            res.push(PrototypeDiffRef::Abstract(Change::borrowed(
+                &self.abstract_,
+                &updated.abstract_,
+            )));
         }
 
         if self.typename != updated.typename {
-            res.push(PrototypeDiff::Typename(updated.typename.clone()));
+            res.push(PrototypeDiffRef::Typename(Change::borrowed(
+                &self.typename,
+                &updated.typename,
+            )));
         }
 
         if self.instance_limit != updated.instance_limit {
-            res.push(PrototypeDiff::InstanceLimit(updated.instance_limit.clone()));
+            res.push(PrototypeDiffRef::InstanceLimit(Change::borrowed(
+                &self.instance_limit,
+                &updated.instance_limit,
+            )));
         }
 
         if self.deprecated != updated.deprecated {
-            res.push(PrototypeDiff::Deprecated(updated.deprecated));
+            res.push(PrototypeDiffRef::Deprecated(Change::borrowed(
+                &self.deprecated,
+                &updated.deprecated,
+            )));
         }
 
-        let properties_diff = self.properties.diff(&updated.properties);
+        let mut metrics = Metrics::default();
+        let properties_diff = in_path("properties", || {
+            self.properties.diff_ref(&updated.properties)
+        });
+        if self.properties != updated.properties {
+            metrics.count(
+                "properties",
+                &self.properties,
+                &updated.properties,
+                &properties_diff,
+            );
+        }
         if !properties_diff.is_empty() {
-            res.push(PrototypeDiff::Properties(properties_diff));
+            res.push(PrototypeDiffRef::Properties(properties_diff));
         }
 
-        if self.custom_properties != updated.custom_properties {
-            res.push(PrototypeDiff::CustomProperties(
-                updated
-                    .custom_properties
-                    .as_ref()
-                    .map(|cp| {
-                        cp.diff(
-                            self.custom_properties
-                                .as_ref()
-                                .unwrap_or(&CustomProperties::default()),
-                        )
-                    })
-                    .unwrap_or_default(),
-            ));
+        let custom_properties_diff = match (&self.custom_properties, &updated.custom_properties) {
+            (None, Some(cp)) => Some(CustomPropertiesChangeRef::Added(
+                empty::<CustomProperties>().diff_ref(cp),
+            )),
+            (Some(cp), None) => Some(CustomPropertiesChangeRef::Removed(cp.diff_ref(empty()))),
+            (Some(cp), Some(updated_cp)) if cp != updated_cp => {
+                let diff = cp.diff_ref(updated_cp);
+                (!diff.is_empty()).then_some(CustomPropertiesChangeRef::Changed(diff))
+            }
+            _ => None,
+        };
+        if let Some(diff) = custom_properties_diff {
+            res.push(PrototypeDiffRef::CustomProperties(diff));
         }
 
-        res
-    }
+        metrics.record(if updated.name.is_empty() {
+            &self.name
+        } else {
+            &updated.name
+        });
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
+        res
     }
 
     fn apply_single(&mut self, _diff: Self::Diff) {
@@ -351,7 +747,7 @@ impl StructDiff for Prototype {
 
 struct InstanceLimitVisitor;
 
-impl<'de> Visitor<'de> for InstanceLimitVisitor {
+impl Visitor<'_> for InstanceLimitVisitor {
     type Value = String;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -415,84 +811,167 @@ pub struct TypeConcept {
     pub properties: DiffableVec<Property>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum TypeConceptDiff / TypeConceptDiffRef {
+        // common fields
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        Description(TextChange<Interned>),
+        Lists(Change<Vec<String>>),
+        Examples(Change<Vec<String>>),
+        Images(ImagesChange),
+        // other fields
+        Parent(Change<String>),
+        Abstract(Change<bool>),
+        Inline(Change<bool>),
+        Type(<Type as StructDiff>::Diff),
+        Properties(DiffableVecDiff<Property>),
+        Structure(StructureChange),
+    }
+}
+
+/// A type that gained or lost its inline structure, i.e. became or stopped being a `struct` with properties.
+///
+/// Holds the properties of the type as a struct, they aren't reported as added or removed on their own.
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
-pub enum TypeConceptDiff {
-    // common fields
-    Name(String),
-    Order(i16),
-    Description(String),
-    Lists(Vec<String>),
-    Examples(Vec<String>),
-    Images(Vec<Image>),
-    // other fields
-    Parent(String),
-    Abstract(bool),
-    Inline(bool),
-    Type(<Type as StructDiff>::Diff),
-    Properties(DiffableVecDiff<Property>),
+pub enum StructureChange {
+    Gained(DiffableVec<Property>),
+    Lost(DiffableVec<Property>),
+}
+
+impl StructureChange {
+    /// How the structure changed from `old` to `new`, `None` if both have or lack one.
+    ///
+    /// Added and removed types aren't transitions, so they have no structure change.
+    fn new(old: &TypeConcept, new: &TypeConcept) -> Option<Self> {
+        if old.name.is_empty() || new.name.is_empty() || old.has_structure() == new.has_structure()
+        {
+            return None;
+        }
+
+        Some(if new.has_structure() {
+            Self::Gained(new.properties.clone())
+        } else {
+            Self::Lost(old.properties.clone())
+        })
+    }
+}
+
+impl Invert for StructureChange {
+    fn invert(self) -> Self {
+        match self {
+            Self::Gained(properties) => Self::Lost(properties),
+            Self::Lost(properties) => Self::Gained(properties),
+        }
+    }
+}
+
+impl super::Borrowed for StructureChange {
+    type Ref<'a> = Self;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        diff
+    }
+}
+
+impl TypeConcept {
+    /// Whether the type has properties and is a `struct` or a union with a `struct` option.
+    #[must_use]
+    pub fn has_structure(&self) -> bool {
+        fn is_struct(type_: &Type) -> bool {
+            match type_ {
+                Type::Complex(c) => match c.as_ref() {
+                    ComplexType::Struct => true,
+                    ComplexType::Union { options, .. } => options.iter().any(is_struct),
+                    _ => false,
+                },
+                Type::Simple(_) => false,
+            }
+        }
+
+        is_struct(&self.type_) && !self.properties.is_empty()
+    }
 }
 
 impl StructDiff for TypeConcept {
     type Diff = TypeConceptDiff;
 
-    type DiffRef<'target> = TypeConceptDiff;
+    type DiffRef<'target> = TypeConceptDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
-
-            if !common_diff.is_empty() {
-                for d in common_diff {
-                    let d = match d {
-                        NamedCommonDiff::Name(name) => Self::Diff::Name(name),
-                        NamedCommonDiff::Order(order) => Self::Diff::Order(order),
-                        NamedCommonDiff::Description(desc) => Self::Diff::Description(desc),
-                        NamedCommonDiff::Lists(lists) => Self::Diff::Lists(lists),
-                        NamedCommonDiff::Examples(examples) => Self::Diff::Examples(examples),
-                        NamedCommonDiff::Images(images) => Self::Diff::Images(images),
-                    };
-                    res.push(d);
-                }
+            let common_diff = self.common.diff_ref(&updated.common);
+
+            for d in common_diff {
+                let d = match d {
+                    NamedCommonDiffRef::Name(name) => TypeConceptDiffRef::Name(name),
+                    NamedCommonDiffRef::Order(order) => TypeConceptDiffRef::Order(order),
+                    NamedCommonDiffRef::Description(desc) => TypeConceptDiffRef::Description(desc),
+                    NamedCommonDiffRef::Lists(lists) => TypeConceptDiffRef::Lists(lists),
+                    NamedCommonDiffRef::Examples(examples) => {
+                        TypeConceptDiffRef::Examples(examples)
+                    }
+                    NamedCommonDiffRef::Images(images) => TypeConceptDiffRef::Images(images),
+                };
+                res.push(d);
             }
         }
 
         if self.parent != updated.parent {
-            res.push(Self::Diff::Parent(updated.parent.clone()));
+            res.push(TypeConceptDiffRef::Parent(Change::borrowed(
+                &self.parent,
+                &updated.parent,
+            )));
         }
 
         if self.abstract_ != updated.abstract_ {
-            res.push(Self::Diff::Abstract(updated.abstract_));
+            res.push(TypeConceptDiffRef::Abstract(Change::borrowed(
+                &self.abstract_,
+                &updated.abstract_,
+            )));
         }
 
         if self.inline != updated.inline {
-            res.push(Self::Diff::Inline(updated.inline));
+            res.push(TypeConceptDiffRef::Inline(Change::borrowed(
+                &self.inline,
+                &updated.inline,
+            )));
         }
 
-        if self.type_ != updated.type_ {
-            let diff = self.type_.diff(&updated.type_);
+        if FieldCategory::Types.is_enabled() && self.type_ != updated.type_ {
+            let mut diff = self.type_.diff_ref(&updated.type_);
             if !diff.is_empty() {
                 assert!(diff.len() == 1, "type diff should have only one element");
                 if !diff[0].skip() {
-                    res.push(Self::Diff::Type(diff[0].clone()));
+                    res.push(TypeConceptDiffRef::Type(diff.swap_remove(0)));
                 }
             }
         }
 
-        let properties_diff = self.properties.diff(&updated.properties);
-        if !properties_diff.is_empty() {
-            res.push(Self::Diff::Properties(properties_diff));
+        // the properties of a gained or lost structure are part of the structure change
+        if let Some(structure) = StructureChange::new(self, updated) {
+            res.push(TypeConceptDiffRef::Structure(structure));
+        } else {
+            let properties_diff = in_path("properties", || {
+                self.properties.diff_ref(&updated.properties)
+            });
+            if !properties_diff.is_empty() {
+                res.push(TypeConceptDiffRef::Properties(properties_diff));
+            }
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
@@ -506,11 +985,14 @@ impl Deref for TypeConcept {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Hash, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Default)]
 pub struct Property {
     #[serde(flatten)]
     common: NamedCommon,
 
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub visibility: BTreeSet<Visibility>,
+
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub alt_name: String,
 
@@ -524,83 +1006,107 @@ pub struct Property {
     pub default: Option<PropertyDefault>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum PropertyDiff {
-    // common fields
-    Name(String),
-    Order(i16),
-    Description(String),
-    Lists(Vec<String>),
-    Examples(Vec<String>),
-    Images(Vec<Image>),
-    // other fields
-    AltName(String),
-    Override(bool),
-    Type(<Type as StructDiff>::Diff),
-    Optional(bool),
-    Default(Option<PropertyDefault>),
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum PropertyDiff / PropertyDiffRef {
+        // common fields
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        Description(TextChange<Interned>),
+        Lists(Change<Vec<String>>),
+        Examples(Change<Vec<String>>),
+        Images(ImagesChange),
+        // other fields
+        Visibility(VisibilityChange),
+        AltName(Change<String>),
+        Override(Change<bool>),
+        Type(<Type as StructDiff>::Diff),
+        Optional(Change<bool>),
+        Optionality(Optionality),
+        Default(Change<Option<PropertyDefault>>),
+    }
 }
 
 impl StructDiff for Property {
     type Diff = PropertyDiff;
 
-    type DiffRef<'target> = Self::Diff;
+    type DiffRef<'target> = PropertyDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
-
-            if !common_diff.is_empty() {
-                for d in common_diff {
-                    let d = match d {
-                        NamedCommonDiff::Name(name) => Self::Diff::Name(name),
-                        NamedCommonDiff::Order(order) => Self::Diff::Order(order),
-                        NamedCommonDiff::Description(desc) => Self::Diff::Description(desc),
-                        NamedCommonDiff::Lists(lists) => Self::Diff::Lists(lists),
-                        NamedCommonDiff::Examples(examples) => Self::Diff::Examples(examples),
-                        NamedCommonDiff::Images(images) => Self::Diff::Images(images),
-                    };
-                    res.push(d);
-                }
+            let common_diff = self.common.diff_ref(&updated.common);
+
+            for d in common_diff {
+                let d = match d {
+                    NamedCommonDiffRef::Name(name) => PropertyDiffRef::Name(name),
+                    NamedCommonDiffRef::Order(order) => PropertyDiffRef::Order(order),
+                    NamedCommonDiffRef::Description(desc) => PropertyDiffRef::Description(desc),
+                    NamedCommonDiffRef::Lists(lists) => PropertyDiffRef::Lists(lists),
+                    NamedCommonDiffRef::Examples(examples) => PropertyDiffRef::Examples(examples),
+                    NamedCommonDiffRef::Images(images) => PropertyDiffRef::Images(images),
+                };
+                res.push(d);
             }
         }
 
+        if let Some(change) = VisibilityChange::new(&self.visibility, &updated.visibility) {
+            res.push(PropertyDiffRef::Visibility(change));
+        }
+
         if self.alt_name != updated.alt_name {
-            res.push(Self::Diff::AltName(updated.alt_name.clone()));
+            res.push(PropertyDiffRef::AltName(Change::borrowed(
+                &self.alt_name,
+                &updated.alt_name,
+            )));
         }
 
         if self.override_ != updated.override_ {
-            res.push(Self::Diff::Override(updated.override_));
+            res.push(PropertyDiffRef::Override(Change::borrowed(
+                &self.override_,
+                &updated.override_,
+            )));
         }
 
-        if self.type_ != updated.type_ {
-            let diff = self.type_.diff(&updated.type_);
+        if FieldCategory::Types.is_enabled() && self.type_ != updated.type_ {
+            let mut diff = self.type_.diff_ref(&updated.type_);
             if !diff.is_empty() {
                 assert!(diff.len() == 1, "type diff should have only one element");
                 if !diff[0].skip() {
-                    res.push(Self::Diff::Type(diff[0].clone()));
+                    res.push(PropertyDiffRef::Type(diff.swap_remove(0)));
                 }
             }
         }
 
         if self.optional != updated.optional {
-            res.push(Self::Diff::Optional(updated.optional));
+            if DiffOptions::current().directional {
+                res.push(PropertyDiffRef::Optionality(Optionality::to(
+                    updated.optional,
+                )));
+            } else {
+                res.push(PropertyDiffRef::Optional(Change::borrowed(
+                    &self.optional,
+                    &updated.optional,
+                )));
+            }
         }
 
         if self.default != updated.default {
-            res.push(Self::Diff::Default(updated.default.clone()));
+            res.push(PropertyDiffRef::Default(Change::borrowed(
+                &self.default,
+                &updated.default,
+            )));
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
@@ -614,14 +1120,16 @@ impl Deref for Property {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Hash, Difference, Clone)]
+#[derive(
+    Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Difference, Clone,
+)]
 #[serde(untagged)]
 pub enum PropertyDefault {
     String(String),
     Literal(Literal),
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Default, Clone, Hash)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default, Clone, Hash)]
 pub struct CustomProperties {
     #[serde(flatten)]
     common: Common,
@@ -630,59 +1138,86 @@ pub struct CustomProperties {
     pub value_type: Type,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum CustomPropertiesDiff {
-    // common fields
-    Description(String),
-    Lists(Vec<String>),
-    Examples(Vec<String>),
-    Images(Vec<Image>),
-    // other fields
-    KeyType(<Type as StructDiff>::Diff),
-    ValueType(<Type as StructDiff>::Diff),
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum CustomPropertiesDiff / CustomPropertiesDiffRef {
+        // common fields
+        Description(TextChange<Interned>),
+        Lists(Change<Vec<String>>),
+        Examples(Change<Vec<String>>),
+        Images(ImagesChange),
+        // other fields
+        KeyType(<Type as StructDiff>::Diff),
+        ValueType(<Type as StructDiff>::Diff),
+    }
+}
+
+diff_enum! {
+    /// How the custom properties of a prototype changed.
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum CustomPropertiesChange / CustomPropertiesChangeRef {
+        Added(SingleDiff<CustomProperties>),
+        Removed(SingleDiff<CustomProperties>),
+        Changed(SingleDiff<CustomProperties>),
+    }
+}
+
+impl Invert for CustomPropertiesChange {
+    fn invert(self) -> Self {
+        match self {
+            Self::Added(d) => Self::Removed(d.invert()),
+            Self::Removed(d) => Self::Added(d.invert()),
+            Self::Changed(d) => Self::Changed(d.invert()),
+        }
+    }
 }
 
 impl StructDiff for CustomProperties {
     type Diff = CustomPropertiesDiff;
 
-    type DiffRef<'target> = Self::Diff;
+    type DiffRef<'target> = CustomPropertiesDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
-
-            if !common_diff.is_empty() {
-                for d in common_diff {
-                    let d = match d {
-                        CommonDiff::Description(desc) => Self::Diff::Description(desc),
-                        CommonDiff::Lists(lists) => Self::Diff::Lists(lists),
-                        CommonDiff::Examples(examples) => Self::Diff::Examples(examples),
-                        CommonDiff::Images(images) => Self::Diff::Images(images),
-                    };
-                    res.push(d);
-                }
+            let common_diff = self.common.diff_ref(&updated.common);
+
+            for d in common_diff {
+                let d = match d {
+                    CommonDiffRef::Description(desc) => CustomPropertiesDiffRef::Description(desc),
+                    CommonDiffRef::Lists(lists) => CustomPropertiesDiffRef::Lists(lists),
+                    CommonDiffRef::Examples(examples) => {
+                        CustomPropertiesDiffRef::Examples(examples)
+                    }
+                    CommonDiffRef::Images(images) => CustomPropertiesDiffRef::Images(images),
+                };
+                res.push(d);
             }
         }
 
-        if self.key_type != updated.key_type {
-            let diff = self.key_type.diff(&updated.key_type);
+        if FieldCategory::Types.is_enabled() && self.key_type != updated.key_type {
+            let mut diff = self.key_type.diff_ref(&updated.key_type);
             if !diff.is_empty() {
                 assert!(diff.len() == 1, "type diff should have only one element");
                 if !diff[0].skip() {
-                    res.push(Self::Diff::KeyType(diff[0].clone()));
+                    res.push(CustomPropertiesDiffRef::KeyType(diff.swap_remove(0)));
                 }
             }
         }
 
-        if self.value_type != updated.value_type {
-            let diff = self.value_type.diff(&updated.value_type);
+        if FieldCategory::Types.is_enabled() && self.value_type != updated.value_type {
+            let mut diff = self.value_type.diff_ref(&updated.value_type);
             if !diff.is_empty() {
                 assert!(diff.len() == 1, "type diff should have only one element");
                 if !diff[0].skip() {
-                    res.push(Self::Diff::ValueType(diff[0].clone()));
+                    res.push(CustomPropertiesDiffRef::ValueType(diff.swap_remove(0)));
                 }
             }
         }
@@ -690,10 +1225,6 @@ impl StructDiff for CustomProperties {
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
@@ -707,16 +1238,16 @@ impl Deref for CustomProperties {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 #[serde(untagged)]
 pub enum Type {
-    Simple(String),
+    Simple(Interned),
     Complex(Box<ComplexType>),
 }
 
 impl Type {
     #[must_use]
-    pub fn as_simple(&self) -> Option<String> {
+    pub fn as_simple(&self) -> Option<Interned> {
         match self {
             Self::Simple(s) => Some(s.clone()),
             Self::Complex(_) => None,
@@ -730,24 +1261,70 @@ impl Type {
             Self::Simple(_) => None,
         }
     }
+
+    /// Canonical form of the type with wrappers that don't change its meaning removed.
+    ///
+    /// A `type` wrapper without a description and a union with a single option
+    /// are equivalent to the type they wrap.
+    /// The `builtin` complex type is equivalent to the simple type `builtin`.
+    /// Only the outermost type is normalized, nested types get normalized when they are diffed.
+    #[must_use]
+    pub fn normalize(&self) -> &Self {
+        static BUILTIN: LazyLock<Type> = LazyLock::new(|| Type::Simple("builtin".into()));
+
+        let Self::Complex(c) = self else {
+            return self;
+        };
+
+        match c.as_ref() {
+            ComplexType::Type { value, description } if description.is_empty() => value.normalize(),
+            ComplexType::Union { options, .. } => match options.as_slice() {
+                [option] => option.normalize(),
+                _ => self,
+            },
+            ComplexType::Builtin => &BUILTIN,
+            _ => self,
+        }
+    }
 }
 
 impl Default for Type {
     fn default() -> Self {
-        Self::Simple(String::new())
+        Self::Simple(Interned::default())
+    }
+}
+
+impl Canonical for Type {
+    fn simple_name(&self) -> Option<&str> {
+        match self {
+            Self::Simple(s) => Some(s),
+            Self::Complex(_) => None,
+        }
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-#[serde(untagged, rename_all = "snake_case")]
-pub enum TypeDiff {
-    Simple(String),
-    Complex(SingleDiff<ComplexType>),
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(untagged, rename_all = "snake_case")]
+    pub enum TypeDiff / TypeDiffRef {
+        Simple(Change<Type>),
+        Complex(SingleDiff<ComplexType>),
+    }
 }
 
 impl TypeDiff {
     #[must_use]
-    pub fn skip(&self) -> bool {
+    pub const fn skip(&self) -> bool {
+        match self {
+            Self::Simple(_) => false,
+            Self::Complex(c) => c.is_empty(),
+        }
+    }
+}
+
+impl TypeDiffRef<'_> {
+    #[must_use]
+    pub const fn skip(&self) -> bool {
         match self {
             Self::Simple(_) => false,
             Self::Complex(c) => c.is_empty(),
@@ -758,43 +1335,62 @@ impl TypeDiff {
 impl StructDiff for Type {
     type Diff = TypeDiff;
 
-    type DiffRef<'target> = Self::Diff;
+    type DiffRef<'target> = TypeDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
+        if !super::DiffOptions::current().exact_types {
+            let (normalized, u_normalized) = (self.normalize(), updated.normalize());
+
+            if normalized != self || u_normalized != updated {
+                return normalized.diff_ref(u_normalized);
+            }
+        }
+
         let mut res = Vec::new();
         match (self, updated) {
             (Self::Simple(s), Self::Simple(updated_s)) => {
                 if s != updated_s {
-                    res.push(Self::Diff::Simple(updated_s.clone()));
+                    res.push(TypeDiffRef::Simple(Change::borrowed(self, updated)));
                 }
             }
-            (Self::Complex(c), Self::Complex(updated_c)) => {
-                let diff = c.diff(updated_c);
+            (Self::Complex(c), Self::Complex(updated_c)) if c.kind() == updated_c.kind() => {
+                let diff = c.diff_ref(updated_c);
                 if !diff.is_empty() {
-                    res.push(Self::Diff::Complex(diff));
+                    res.push(TypeDiffRef::Complex(diff));
                 }
             }
-            (_, Self::Simple(updated_s)) => {
-                res.push(Self::Diff::Simple(updated_s.clone()));
+            // added and removed types are reported field by field, the same way in both directions
+            (Self::Simple(s), Self::Complex(updated_c)) if s.is_empty() => {
+                res.push(TypeDiffRef::Complex(ComplexType::diff_whole(
+                    updated_c, false,
+                )));
             }
-            (_, Self::Complex(updated_c)) => {
-                res.push(Self::Diff::Complex(ComplexType::default().diff(updated_c)));
+            (Self::Complex(c), Self::Simple(updated_s)) if updated_s.is_empty() => {
+                res.push(TypeDiffRef::Complex(ComplexType::diff_whole(c, true)));
+            }
+            // other changes of the kind are reported as a whole so they read the same in both directions
+            _ => {
+                if let Self::Complex(updated_c) = updated {
+                    updated_c.warn_unknown();
+                }
+
+                res.push(TypeDiffRef::Simple(Change::borrowed(self, updated)));
             }
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 #[serde(tag = "complex_type", rename_all = "snake_case")]
 pub enum ComplexType {
     Array {
@@ -817,6 +1413,9 @@ pub enum ComplexType {
     },
     Literal(Literal),
     Struct,
+    Builtin, // might be an error in the input, equivalent to the simple type string `builtin`
+    #[serde(untagged)]
+    Other(OtherComplexType),
 }
 
 impl ComplexType {
@@ -883,36 +1482,145 @@ impl Default for ComplexType {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-#[serde(rename_all = "snake_case")]
-pub enum ComplexTypeDiff {
-    ComplexType(String),
-    Value(<Type as StructDiff>::Diff),
-    Key(<Type as StructDiff>::Diff),
-    Values(Vec<<Type as StructDiff>::Diff>),
-    Options(Vec<<Type as StructDiff>::Diff>),
-    FullFormat(bool),
-    Description(String),
-    #[serde(rename = "value")]
-    Literal(LiteralValue),
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ComplexTypeDiff / ComplexTypeDiffRef {
+        ComplexType(Change<String>),
+        Value(<Type as StructDiff>::Diff),
+        Key(<Type as StructDiff>::Diff),
+        Values(Vec<<Type as StructDiff>::Diff>),
+        Options(Vec<<Type as StructDiff>::Diff>),
+        FullFormat(Change<bool>),
+        Description(TextChange<String>),
+        #[serde(rename(serialize = "value"))] // clashes with `Value` when deserializing
+        Literal(Change<LiteralValue>),
+        /// The raw JSON of a complex type this version doesn't know
+        Raw(Change<serde_json::Value>),
+    }
+}
+
+impl ComplexType {
+    /// Name of the complex type as used in the docs.
+    #[must_use]
+    pub fn kind(&self) -> &str {
+        match self {
+            Self::Array { .. } => "array",
+            Self::Dictionary { .. } => "dictionary",
+            Self::Tuple { .. } => "tuple",
+            Self::Union { .. } => "union",
+            Self::Type { .. } => "type",
+            Self::Literal(_) => "literal",
+            Self::Struct => "struct",
+            Self::Builtin => "builtin",
+            Self::Other(o) => &o.complex_type,
+        }
+    }
+
+    /// Warns about the type if this version doesn't know its kind.
+    fn warn_unknown(&self) {
+        if let Self::Other(o) = self {
+            super::warn(format!("unknown complex type {}", o.complex_type));
+        }
+    }
+
+    /// Diff between an empty type and `type_`, all fields are reported as new, or as removed if `removed`.
+    fn diff_whole(type_: &Self, removed: bool) -> Vec<ComplexTypeDiffRef<'_>> {
+        let (old, new) = from_empty(String::new(), type_.kind().to_owned(), removed);
+        let mut res = vec![ComplexTypeDiffRef::ComplexType(Change::owned(old, new))];
+
+        match type_ {
+            Self::Array { value } => {
+                res.push(ComplexTypeDiffRef::Value(
+                    diff_empty(value, removed).swap_remove(0),
+                ));
+            }
+            Self::Dictionary { key, value } => {
+                res.push(ComplexTypeDiffRef::Key(
+                    diff_empty(key, removed).swap_remove(0),
+                ));
+                res.push(ComplexTypeDiffRef::Value(
+                    diff_empty(value, removed).swap_remove(0),
+                ));
+            }
+            Self::Tuple { values } => {
+                res.push(ComplexTypeDiffRef::Values(
+                    values
+                        .iter()
+                        .map(|v| diff_empty(v, removed).swap_remove(0))
+                        .collect(),
+                ));
+            }
+            Self::Union {
+                options,
+                full_format,
+            } => {
+                res.push(ComplexTypeDiffRef::Options(
+                    canonical_order(options)
+                        .into_iter()
+                        .map(|o| diff_empty(o, removed).swap_remove(0))
+                        .collect(),
+                ));
+                let (old, new) = from_empty(&false, full_format, removed);
+                res.push(ComplexTypeDiffRef::FullFormat(Change::borrowed(old, new)));
+            }
+            Self::Type { value, description } => {
+                res.push(ComplexTypeDiffRef::Value(
+                    diff_empty(value, removed).swap_remove(0),
+                ));
+
+                if FieldCategory::Descriptions.is_enabled() {
+                    let (old, new) = from_empty(empty(), description, removed);
+                    res.push(ComplexTypeDiffRef::Description(TextChange::borrowed(
+                        old, new,
+                    )));
+                }
+            }
+            Self::Literal(l) => {
+                let diff = diff_empty(l, removed);
+                for d in diff {
+                    match d {
+                        LiteralDiffRef::Value(v) => res.push(ComplexTypeDiffRef::Literal(v)),
+                        LiteralDiffRef::Description(d) => {
+                            if FieldCategory::Descriptions.is_enabled() {
+                                res.push(ComplexTypeDiffRef::Description(d));
+                            }
+                        }
+                    }
+                }
+            }
+            Self::Other(o) => {
+                super::warn(format!("unknown complex type {}", o.complex_type));
+                let (old, new) = from_empty(serde_json::Value::Null, o.raw.clone(), removed);
+                res.push(ComplexTypeDiffRef::Raw(Change::owned(old, new)));
+            }
+            Self::Struct | Self::Builtin => {}
+        }
+
+        res
+    }
 }
 
 impl StructDiff for ComplexType {
     type Diff = ComplexTypeDiff;
 
-    type DiffRef<'target> = Self::Diff;
+    type DiffRef<'target> = ComplexTypeDiffRef<'target>;
 
-    #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         match (self, updated) {
             (Self::Array { value }, Self::Array { value: u_value }) => {
                 if value != u_value {
-                    let diff = value.diff(u_value);
+                    let mut diff = value.diff_ref(u_value);
 
                     if !diff.is_empty() && !diff[0].skip() {
-                        res.push(Self::Diff::Value(diff[0].clone()));
+                        res.push(ComplexTypeDiffRef::Value(diff.swap_remove(0)));
                     }
                 }
             }
@@ -924,32 +1632,31 @@ impl StructDiff for ComplexType {
                 },
             ) => {
                 if key != u_key {
-                    let diff = key.diff(u_key);
+                    let mut diff = key.diff_ref(u_key);
 
                     if !diff.is_empty() && !diff[0].skip() {
-                        res.push(Self::Diff::Key(diff[0].clone()));
+                        res.push(ComplexTypeDiffRef::Key(diff.swap_remove(0)));
                     }
                 }
 
                 if value != u_value {
-                    let diff = value.diff(u_value);
+                    let mut diff = value.diff_ref(u_value);
 
                     if !diff.is_empty() && !diff[0].skip() {
-                        res.push(Self::Diff::Value(diff[0].clone()));
+                        res.push(ComplexTypeDiffRef::Value(diff.swap_remove(0)));
                     }
                 }
             }
             (Self::Tuple { values }, Self::Tuple { values: u_values }) => {
                 if values != u_values {
                     let diff = vec_diff(values, u_values)
-                        .iter()
+                        .into_iter()
                         .flatten()
                         .filter(|v| !v.skip())
-                        .cloned()
                         .collect::<Vec<_>>();
 
                     if !diff.is_empty() {
-                        res.push(Self::Diff::Values(diff));
+                        res.push(ComplexTypeDiffRef::Values(diff));
                     }
                 }
             }
@@ -964,20 +1671,28 @@ impl StructDiff for ComplexType {
                 },
             ) => {
                 if options != u_options {
-                    let diff = vec_diff(options, u_options)
-                        .iter()
+                    // the order of the options has no meaning unless exact types are reported
+                    let diff = if super::DiffOptions::current().exact_types {
+                        vec_diff(options, u_options)
+                    } else {
+                        canonical_vec_diff(options, u_options)
+                    };
+                    let diff = diff
+                        .into_iter()
                         .flatten()
                         .filter(|o| !o.skip())
-                        .cloned()
                         .collect::<Vec<_>>();
 
                     if !diff.is_empty() {
-                        res.push(Self::Diff::Options(diff));
+                        res.push(ComplexTypeDiffRef::Options(diff));
                     }
                 }
 
                 if full_format != u_full_format {
-                    res.push(Self::Diff::FullFormat(*u_full_format));
+                    res.push(ComplexTypeDiffRef::FullFormat(Change::borrowed(
+                        full_format,
+                        u_full_format,
+                    )));
                 }
             }
             (
@@ -988,29 +1703,34 @@ impl StructDiff for ComplexType {
                 },
             ) => {
                 if value != u_value {
-                    let diff = value.diff(u_value);
+                    let mut diff = value.diff_ref(u_value);
 
                     if !diff.is_empty() && !diff[0].skip() {
-                        res.push(Self::Diff::Value(diff[0].clone()));
+                        res.push(ComplexTypeDiffRef::Value(diff.swap_remove(0)));
                     }
                 }
 
-                if crate::CLI.with_borrow(|c| c.descriptions || c.full)
-                    && description != updated_description
+                if FieldCategory::Descriptions.is_enabled()
+                    && text_changed(description, updated_description)
                 {
-                    res.push(Self::Diff::Description(updated_description.clone()));
+                    res.push(ComplexTypeDiffRef::Description(TextChange::borrowed(
+                        description,
+                        updated_description,
+                    )));
                 }
             }
             (Self::Literal(l), Self::Literal(updated_l)) => {
                 if l != updated_l {
-                    let diff = l.diff(updated_l);
+                    let diff = l.diff_ref(updated_l);
                     if !diff.is_empty() {
                         for d in diff {
                             match d {
-                                LiteralDiff::Value(v) => res.push(Self::Diff::Literal(v)),
-                                LiteralDiff::Description(d) => {
-                                    if crate::CLI.with_borrow(|c| c.descriptions || c.full) {
-                                        res.push(Self::Diff::Description(d));
+                                LiteralDiffRef::Value(v) => {
+                                    res.push(ComplexTypeDiffRef::Literal(v));
+                                }
+                                LiteralDiffRef::Description(d) => {
+                                    if FieldCategory::Descriptions.is_enabled() {
+                                        res.push(ComplexTypeDiffRef::Description(d));
                                     }
                                 }
                             }
@@ -1018,79 +1738,30 @@ impl StructDiff for ComplexType {
                     }
                 }
             }
-            (Self::Struct, Self::Struct) => {}
-            _ => match updated {
-                Self::Array { value } => {
-                    res.push(Self::Diff::ComplexType("array".to_owned()));
-                    res.push(Self::Diff::Value(Type::default().diff(value)[0].clone()));
-                }
-                Self::Dictionary { key, value } => {
-                    res.push(Self::Diff::ComplexType("dictionary".to_owned()));
-                    res.push(Self::Diff::Key(Type::default().diff(key)[0].clone()));
-                    res.push(Self::Diff::Value(Type::default().diff(value)[0].clone()));
-                }
-                Self::Tuple { values } => {
-                    res.push(Self::Diff::ComplexType("tuple".to_owned()));
-                    res.push(Self::Diff::Values(
-                        values
-                            .iter()
-                            .map(|v| Type::default().diff(v)[0].clone())
-                            .collect(),
-                    ));
-                }
-                Self::Union {
-                    options,
-                    full_format,
-                } => {
-                    res.push(Self::Diff::ComplexType("union".to_owned()));
-                    res.push(Self::Diff::Options(
-                        options
-                            .iter()
-                            .map(|o| Type::default().diff(o)[0].clone())
-                            .collect(),
-                    ));
-                    res.push(Self::Diff::FullFormat(*full_format));
-                }
-                Self::Type { value, description } => {
-                    res.push(Self::Diff::ComplexType("type".to_owned()));
-                    res.push(Self::Diff::Value(Type::default().diff(value)[0].clone()));
+            (Self::Struct, Self::Struct) | (Self::Builtin, Self::Builtin) => {}
+            (Self::Other(o), Self::Other(u)) if o.complex_type == u.complex_type => {
+                super::warn(format!("unknown complex type {}", u.complex_type));
 
-                    if crate::CLI.with_borrow(|c| c.descriptions || c.full) {
-                        res.push(Self::Diff::Description(description.clone()));
-                    }
+                if o.raw != u.raw {
+                    res.push(ComplexTypeDiffRef::Raw(Change::borrowed(&o.raw, &u.raw)));
                 }
-                Self::Literal(l) => {
-                    res.push(Self::Diff::ComplexType("literal".to_owned()));
-
-                    let diff = Literal::default().diff(l);
-                    for d in diff {
-                        match d {
-                            LiteralDiff::Value(v) => res.push(Self::Diff::Literal(v)),
-                            LiteralDiff::Description(d) => {
-                                if crate::CLI.with_borrow(|c| c.descriptions || c.full) {
-                                    res.push(Self::Diff::Description(d));
-                                }
-                            }
-                        }
-                    }
-                }
-                Self::Struct => res.push(Self::Diff::ComplexType("struct".to_owned())),
-            },
+            }
+            // changes of the kind are reported by `Type` as a whole
+            _ => res.push(ComplexTypeDiffRef::ComplexType(Change::owned(
+                self.kind().to_owned(),
+                updated.kind().to_owned(),
+            ))),
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Hash, Default, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Clone)]
 pub struct Literal {
     pub value: LiteralValue,
 
@@ -1098,44 +1769,52 @@ pub struct Literal {
     pub description: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum LiteralDiff {
-    Value(LiteralValue),
-    Description(String),
+diff_enum! {
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum LiteralDiff / LiteralDiffRef {
+        Value(Change<LiteralValue>),
+        Description(TextChange<String>),
+    }
 }
 
 impl StructDiff for Literal {
     type Diff = LiteralDiff;
 
-    type DiffRef<'target> = Self::Diff;
+    type DiffRef<'target> = LiteralDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.value != updated.value {
-            res.push(Self::Diff::Value(updated.value.clone()));
+            res.push(LiteralDiffRef::Value(Change::borrowed(
+                &self.value,
+                &updated.value,
+            )));
         }
 
-        if crate::CLI.with_borrow(|c| c.descriptions || c.full)
-            && self.description != updated.description
+        if FieldCategory::Descriptions.is_enabled()
+            && text_changed(&self.description, &updated.description)
         {
-            res.push(Self::Diff::Description(updated.description.clone()));
+            res.push(LiteralDiffRef::Description(TextChange::borrowed(
+                &self.description,
+                &updated.description,
+            )));
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, PartialOrd, Difference, Clone)]
+#[derive(Debug, Serialize, Deserialize, Difference, Clone)]
 #[serde(untagged)]
 pub enum LiteralValue {
     String(String),
@@ -1159,9 +1838,46 @@ impl std::hash::Hash for LiteralValue {
     }
 }
 
+// floats are compared by their total order, consistent with hashing their bits
+impl PartialEq for LiteralValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
 impl Eq for LiteralValue {}
 
+impl PartialOrd for LiteralValue {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LiteralValue {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match (self, other) {
+            (Self::String(a), Self::String(b)) => a.cmp(b),
+            (Self::UInt(a), Self::UInt(b)) => a.cmp(b),
+            (Self::Int(a), Self::Int(b)) => a.cmp(b),
+            (Self::Float(a), Self::Float(b)) => a.total_cmp(b),
+            (Self::Boolean(a), Self::Boolean(b)) => a.cmp(b),
+            _ => self.variant().cmp(&other.variant()),
+        }
+    }
+}
+
 impl LiteralValue {
+    /// Position of the variant, values of different variants are ordered by it.
+    const fn variant(&self) -> u8 {
+        match self {
+            Self::String(_) => 0,
+            Self::UInt(_) => 1,
+            Self::Int(_) => 2,
+            Self::Float(_) => 3,
+            Self::Boolean(_) => 4,
+        }
+    }
+
     #[must_use]
     pub fn as_string(&self) -> Option<String> {
         match self {
@@ -1208,3 +1924,26 @@ impl Default for LiteralValue {
         Self::String(String::new())
     }
 }
+
+impl_invert! {
+    CommonDiff { Description, Lists, Examples, Images }
+    NamedCommonDiff { Name, Order, Description, Lists, Examples, Images }
+    PrototypeDiff {
+        Name, Order, Description, Lists, Examples, Images, Visibility, Parent, Abstract, Typename,
+        InstanceLimit, Deprecated, Properties, CustomProperties,
+    }
+    TypeConceptDiff {
+        Name, Order, Description, Lists, Examples, Images, Parent, Abstract, Inline, Type,
+        Properties, Structure,
+    }
+    PropertyDiff {
+        Name, Order, Description, Lists, Examples, Images, Visibility, AltName, Override, Type,
+        Optional, Optionality, Default,
+    }
+    CustomPropertiesDiff { Description, Lists, Examples, Images, KeyType, ValueType }
+    TypeDiff { Simple, Complex }
+    ComplexTypeDiff {
+        ComplexType, Value, Key, Values, Options, FullFormat, Description, Literal, Raw,
+    }
+    LiteralDiff { Value, Description }
+}
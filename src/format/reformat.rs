@@ -0,0 +1,235 @@
+//! Comparison of texts whose markup changes between releases: normalizing the markup before comparing
+//! and detecting releases that rewrite the links of many descriptions without changing their text.
+
+use std::{borrow::Cow, cell::Cell, cell::RefCell};
+
+use regex::Regex;
+
+use super::{DiffOptions, FieldCategory, WARNINGS};
+
+/// Fewest link only changes that are collapsed.
+const MIN_COLLAPSED: usize = 10;
+
+/// Rules of `--normalize-links`: markdown links and `[Class::method]` references are replaced
+/// by their labels and runs of whitespace by a single space.
+const BUILTIN_RULES: [(&str, &str); 3] = [
+    (r"\[([^\[\]]*)\]\([^()]*\)", "$1"),
+    (r"\[([A-Za-z_][\w.:]*)\]", "$1"),
+    (r"\s+", " "),
+];
+
+thread_local! {static COUNTS: Cell<Counts> = Cell::default();}
+thread_local! {static RULES: RefCell<Vec<(Regex, String)>> = const { RefCell::new(Vec::new()) };}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Counts {
+    /// Whether link only changes are left out of the diff
+    collapse: bool,
+    /// Changed texts
+    texts: usize,
+    /// Changed texts that only differ in their links
+    links_only: usize,
+}
+
+impl Counts {
+    /// Whether the link only changes are a reformatting of the whole docs.
+    const fn is_reformatting(self) -> bool {
+        self.links_only >= MIN_COLLAPSED && self.links_only * 2 > self.texts
+    }
+}
+
+/// The text with its markdown links like `[LuaEntity](runtime:LuaEntity)` replaced by their labels.
+fn without_links(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('[') {
+        res.push_str(&rest[..start]);
+        let link = &rest[start + 1..];
+
+        let label = link.split_once(']').and_then(|(label, after)| {
+            let (_, after) = after.strip_prefix('(')?.split_once(')')?;
+            (!label.contains('[')).then_some((label, after))
+        });
+
+        if let Some((label, after)) = label {
+            res.push_str(label);
+            rest = after;
+        } else {
+            res.push('[');
+            rest = link;
+        }
+    }
+
+    res.push_str(rest);
+    res
+}
+
+/// Runs `f` comparing descriptions, examples and lists after normalizing their markup.
+///
+/// `builtin` enables the rules of `--normalize-links`, `rules` are applied after them and have the form
+/// `PATTERN=>REPLACEMENT` or just `PATTERN` to remove all matches.
+/// The replacement can refer to capture groups like `$1`.
+pub fn with_normalized<R>(
+    builtin: bool,
+    rules: &[String],
+    f: impl FnOnce() -> R,
+) -> Result<R, regex::Error> {
+    let builtin: &[_] = if builtin { &BUILTIN_RULES } else { &[] };
+    let custom = rules
+        .iter()
+        .map(|rule| rule.split_once("=>").unwrap_or((rule, "")));
+    let rules = builtin
+        .iter()
+        .copied()
+        .chain(custom)
+        .map(|(pattern, replacement)| Ok((Regex::new(pattern)?, replacement.to_owned())))
+        .collect::<Result<Vec<_>, regex::Error>>()?;
+
+    Ok(with_rules(rules, f))
+}
+
+/// The rules of [`with_normalized`] on this thread.
+pub(super) fn rules() -> Vec<(Regex, String)> {
+    RULES.with_borrow(Clone::clone)
+}
+
+/// Runs `f` normalizing texts with the compiled `rules`, see [`with_normalized`].
+pub(super) fn with_rules<R>(rules: Vec<(Regex, String)>, f: impl FnOnce() -> R) -> R {
+    let prev = RULES.replace(rules);
+    let res = f();
+    RULES.set(prev);
+
+    res
+}
+
+/// The text with all normalization rules applied, see [`with_normalized`].
+fn normalized(text: &str) -> Cow<'_, str> {
+    RULES.with_borrow(|rules| {
+        rules.iter().fold(
+            Cow::Borrowed(text),
+            |text, (regex, replacement)| match regex.replace_all(&text, replacement.as_str()) {
+                Cow::Borrowed(_) => text,
+                Cow::Owned(replaced) => Cow::Owned(replaced),
+            },
+        )
+    })
+}
+
+/// Whether two texts differ after normalizing their markup.
+fn differs(old: &str, new: &str) -> bool {
+    old != new && (RULES.with_borrow(Vec::is_empty) || normalized(old) != normalized(new))
+}
+
+/// Whether texts like the lists or examples of an item changed after normalizing their markup.
+pub(super) fn texts_changed<T: AsRef<str>>(old: &[T], new: &[T]) -> bool {
+    old.len() != new.len()
+        || old
+            .iter()
+            .zip(new)
+            .any(|(old, new)| differs(old.as_ref(), new.as_ref()))
+}
+
+/// Whether a text like a description changed, counting changes that only affect its links.
+///
+/// Link only changes don't count as changes while they're collapsed, see [`collapsing_links`].
+pub(super) fn text_changed<T: AsRef<str> + PartialEq>(old: &T, new: &T) -> bool {
+    if !differs(old.as_ref(), new.as_ref()) {
+        return false;
+    }
+
+    let mut counts = COUNTS.get();
+    let links_only = without_links(old.as_ref()) == without_links(new.as_ref());
+    counts.texts += 1;
+    counts.links_only += usize::from(links_only);
+    COUNTS.set(counts);
+
+    !(links_only && counts.collapse)
+}
+
+/// Whether link only changes can be collapsed in the current run.
+fn enabled() -> bool {
+    FieldCategory::Descriptions.is_enabled() && !DiffOptions::current().keep_link_changes
+}
+
+/// Runs `diff` leaving out the description changes that only affect links
+/// if they make up most of the changed descriptions, returns how many were left out.
+///
+/// `diff` runs a second time with all changes if only a few are link only changes.
+pub(super) fn collapsing_links<R>(mut diff: impl FnMut() -> R) -> (R, usize) {
+    if !enabled() {
+        return (diff(), 0);
+    }
+
+    let warnings = WARNINGS.with_borrow(Vec::len);
+    let prev = COUNTS.replace(Counts {
+        collapse: true,
+        ..Counts::default()
+    });
+    let res = diff();
+    let counts = COUNTS.replace(Counts::default());
+
+    let res = if counts.links_only == 0 || counts.is_reformatting() {
+        (res, counts.links_only)
+    } else {
+        drop(res);
+        WARNINGS.with_borrow_mut(|w| w.truncate(warnings));
+        (diff(), 0)
+    };
+    COUNTS.set(prev);
+
+    res
+}
+
+/// Number of changed texts that only differ in their links in the current run so far.
+pub(super) fn links_only() -> usize {
+    COUNTS.get().links_only
+}
+
+/// [`collapsing_links`] for counts, `count` runs once leaving out link only changes,
+/// returns whether they make up most of the changed descriptions and stay left out.
+///
+/// `count` has to tell apart the items that only changed in their links, see [`links_only`],
+/// they count as changed unless the link only changes stay left out.
+pub(super) fn counting_links<R>(count: impl FnOnce() -> R) -> (R, bool) {
+    if !enabled() {
+        return (count(), false);
+    }
+
+    let prev = COUNTS.replace(Counts {
+        collapse: true,
+        ..Counts::default()
+    });
+    let res = count();
+    let counts = COUNTS.replace(prev);
+
+    (res, counts.is_reformatting())
+}
+
+/// [`collapsing_links`] for diffs whose output can't be taken back,
+/// `count` diffs without output to find the link only changes before `diff` runs.
+pub(super) fn streaming_links(count: impl FnOnce(), diff: impl FnOnce()) -> usize {
+    if !enabled() {
+        diff();
+        return 0;
+    }
+
+    let warnings = WARNINGS.with_borrow(Vec::len);
+    let prev = COUNTS.replace(Counts::default());
+    count();
+    WARNINGS.with_borrow_mut(|w| w.truncate(warnings));
+
+    let counts = COUNTS.get();
+    COUNTS.set(Counts {
+        collapse: counts.is_reformatting(),
+        ..Counts::default()
+    });
+    diff();
+    COUNTS.set(prev);
+
+    if counts.is_reformatting() {
+        counts.links_only
+    } else {
+        0
+    }
+}
@@ -0,0 +1,82 @@
+//! Items that were renamed between releases, recognized by a note in the description of
+//! the old or the new item like `Renamed to [BoundingArea](runtime:BoundingArea)`.
+
+use std::cell::RefCell;
+
+use regex::Regex;
+
+/// Marker of renamed items, its first group captures the name of the other item.
+pub const RENAME_MARKER: &str = r"(?i)\brenamed\s+(?:to|from)\s+\[?`?([A-Za-z_][\w.]*)";
+
+thread_local! {static MARKER: RefCell<Option<Regex>> = RefCell::new(Regex::new(RENAME_MARKER).ok());}
+
+/// Runs `f` recognizing renamed items by `marker` instead of [`RENAME_MARKER`], an empty marker
+/// turns recognizing renamed items off.
+///
+/// The first group of the marker has to capture the name of the other item.
+pub fn with_rename_marker<R>(marker: &str, f: impl FnOnce() -> R) -> Result<R, regex::Error> {
+    let marker = if marker.is_empty() {
+        None
+    } else {
+        Some(Regex::new(marker)?)
+    };
+
+    Ok(with_marker(marker, f))
+}
+
+/// The marker of [`with_rename_marker`] on this thread.
+pub(super) fn marker() -> Option<Regex> {
+    MARKER.with_borrow(Clone::clone)
+}
+
+/// Runs `f` with the compiled `marker`, see [`with_rename_marker`].
+pub(super) fn with_marker<R>(marker: Option<Regex>, f: impl FnOnce() -> R) -> R {
+    let prev = MARKER.replace(marker);
+    let res = f();
+    MARKER.set(prev);
+
+    res
+}
+
+/// Pairs of removed and added items whose descriptions name each other as renamed, as `(old, new)`.
+///
+/// Both are given as `(name, description)`, each item is part of at most one pair.
+pub(super) fn find<'a>(
+    removed: impl IntoIterator<Item = (&'a str, &'a str)>,
+    added: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Vec<(String, String)> {
+    MARKER.with_borrow(|marker| {
+        let Some(marker) = marker else {
+            return Vec::new();
+        };
+        let names = |description: &str| {
+            marker
+                .captures_iter(description)
+                .filter_map(|c| Some(c.get(1)?.as_str().to_owned()))
+                .collect::<Vec<_>>()
+        };
+
+        let removed = removed
+            .into_iter()
+            .map(|(name, description)| (name, names(description)))
+            .collect::<Vec<_>>();
+        let mut added = added
+            .into_iter()
+            .map(|(name, description)| (name, names(description)))
+            .collect::<Vec<_>>();
+
+        let mut res = Vec::new();
+        for (old, old_names) in removed {
+            let Some(i) = added.iter().position(|(new, new_names)| {
+                old_names.iter().any(|n| n == new) || new_names.iter().any(|n| n == old)
+            }) else {
+                continue;
+            };
+
+            let (new, _) = added.remove(i);
+            res.push((old.to_owned(), new.to_owned()));
+        }
+
+        res
+    })
+}
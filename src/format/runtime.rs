@@ -1,14 +1,31 @@
-use std::{collections::HashMap, ops::Deref};
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    ops::Deref,
+    sync::LazyLock,
+};
 
 use serde::{Deserialize, Serialize};
 use structdiff::StructDiff;
 
+use crate::links::Links;
+
 use super::{
-    diff_helper::{vec_diff, DiffableVec, DiffableVecDiff, Named, SingleDiff},
-    prototype::LiteralValue,
-    Image,
+    diff_helper::{
+        canonical_order, canonical_vec_diff, count_each, diff_each, diff_empty, empty, from_empty,
+        in_path, named_diff, named_full, to_owned, vec_diff, vec_diff_matched, Canonical,
+        DiffableVec, DiffableVecDiff, DiffableVecDiffRef, ItemCounts, Items, Named, SingleDiff,
+    },
+    prototype::{LiteralDiffRef, LiteralValue},
+    reformat::{collapsing_links, counting_links, streaming_links, text_changed, texts_changed},
+    Borrowed, Change, ChangeCounts, Context, DiffEntry, DiffOptions, FieldCategory, Image,
+    ImagesChange, Interned, Invert, Metrics, Optionality, Order, OtherComplexType, TextChange,
+    Visibility, VisibilityChange, Writability,
 };
 
+// api versions of the source and target doc of the running diff
+thread_local! {static API_VERSIONS: Cell<(u8, u8)> = const { Cell::new((0, 0)) };}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct RuntimeDoc {
     #[serde(flatten)]
@@ -30,21 +47,503 @@ impl Deref for RuntimeDoc {
     }
 }
 
+impl RuntimeDoc {
+    /// Diffs all sections of the docs, see [`super::Doc::diff`].
+    fn diff_sections(&self, other: &Self) -> RuntimeDocDiff {
+        API_VERSIONS.set((self.api_version, other.api_version));
+        let _ = Metrics::take();
+
+        let concepts = in_path("concepts", || self.concepts.diff_ref(&other.concepts));
+        let mut events = in_path("events", || self.events.diff_ref(&other.events));
+        link_event_filters(&mut events, other, &concepts);
+
+        let mut classes = in_path("classes", || self.classes.diff_ref(&other.classes));
+        if super::DiffOptions::current().inherit {
+            link_inherited_changes(&mut classes, other);
+        }
+        let status = |name: &str| {
+            raised_event_status(name, &self.events, &other.events, |e| {
+                events.get(e).is_some_and(|d| is_event_change(d))
+            })
+        };
+        for diff in classes.values_mut() {
+            link_raised_events(diff, &status);
+        }
+
+        let mut diff = RuntimeDocDiff {
+            diff_format_version: super::DIFF_FORMAT_VERSION,
+            header: Some(super::Header::new(self, other)),
+            classes: to_owned::<Class>(classes),
+            events: to_owned::<Event>(events),
+            concepts: to_owned::<Concept>(concepts),
+            defines: in_path("defines", || self.defines.diff(&other.defines)),
+            global_objects: in_path("global_objects", || {
+                self.global_objects.diff(&other.global_objects)
+            }),
+            global_functions: in_path("global_functions", || {
+                self.global_functions.diff(&other.global_functions)
+            }),
+            added: ItemNames {
+                classes: in_path("classes", || other.classes.removed(&self.classes)),
+                events: in_path("events", || other.events.removed(&self.events)),
+                concepts: in_path("concepts", || other.concepts.removed(&self.concepts)),
+                defines: in_path("defines", || other.defines.removed(&self.defines)),
+                global_objects: in_path("global_objects", || {
+                    other.global_objects.removed(&self.global_objects)
+                }),
+                global_functions: in_path("global_functions", || {
+                    other.global_functions.removed(&self.global_functions)
+                }),
+            },
+            removed: ItemNames {
+                classes: in_path("classes", || self.classes.removed(&other.classes)),
+                events: in_path("events", || self.events.removed(&other.events)),
+                concepts: in_path("concepts", || self.concepts.removed(&other.concepts)),
+                defines: in_path("defines", || self.defines.removed(&other.defines)),
+                global_objects: in_path("global_objects", || {
+                    self.global_objects.removed(&other.global_objects)
+                }),
+                global_functions: in_path("global_functions", || {
+                    self.global_functions.removed(&other.global_functions)
+                }),
+            },
+            renamed: BTreeMap::new(),
+            metrics: Metrics::take(),
+            urls: BTreeMap::new(),
+            context: BTreeMap::new(),
+            reformatted_descriptions: 0,
+        };
+
+        diff.link_renamed_concepts(self, other);
+
+        if super::DiffOptions::current().anchor_links {
+            diff.urls = diff.item_urls(&Links::of(other));
+        }
+
+        if super::DiffOptions::current().context != Context::None {
+            diff.context = diff.item_context(other);
+        }
+
+        diff
+    }
+}
+
 impl super::Doc for RuntimeDoc {
     type Diff = RuntimeDocDiff;
 
     fn diff(&self, other: &Self) -> Self::Diff {
-        Self::Diff {
-            classes: self.classes.diff(&other.classes),
-            events: self.events.diff(&other.events),
-            concepts: self.concepts.diff(&other.concepts),
-            defines: self.defines.diff(&other.defines),
-            global_objects: self.global_objects.diff(&other.global_objects),
-            global_functions: self.global_functions.diff(&other.global_functions),
+        let (mut diff, reformatted) = collapsing_links(|| self.diff_sections(other));
+        diff.reformatted_descriptions = reformatted;
+
+        diff
+    }
+
+    fn diff_entries(&self, other: &Self, emit: &mut dyn FnMut(DiffEntry)) -> crate::Result<()> {
+        entries(self, other, emit)
+    }
+
+    fn count_changes(&self, other: &Self) -> crate::Result<ChangeCounts> {
+        counts(self, other)
+    }
+}
+
+/// The items of a runtime doc, parsed up front or on demand.
+pub(super) trait RuntimeItems {
+    fn api_version(&self) -> u8;
+    fn classes(&self) -> impl Items<Class>;
+    fn events(&self) -> impl Items<Event>;
+    fn concepts(&self) -> impl Items<Concept>;
+    fn defines(&self) -> impl Items<Define>;
+    fn global_objects(&self) -> impl Items<Parameter>;
+    fn global_functions(&self) -> impl Items<Method>;
+}
+
+impl RuntimeItems for RuntimeDoc {
+    fn api_version(&self) -> u8 {
+        self.api_version
+    }
+
+    fn classes(&self) -> impl Items<Class> {
+        &self.classes
+    }
+
+    fn events(&self) -> impl Items<Event> {
+        &self.events
+    }
+
+    fn concepts(&self) -> impl Items<Concept> {
+        &self.concepts
+    }
+
+    fn defines(&self) -> impl Items<Define> {
+        &self.defines
+    }
+
+    fn global_objects(&self) -> impl Items<Parameter> {
+        &self.global_objects
+    }
+
+    fn global_functions(&self) -> impl Items<Method> {
+        &self.global_functions
+    }
+}
+
+/// Diffs the docs item by item, see [`super::Doc::diff_entries`].
+pub(super) fn entries(
+    source: &impl RuntimeItems,
+    target: &impl RuntimeItems,
+    emit: &mut dyn FnMut(DiffEntry),
+) -> crate::Result<()> {
+    let mut res = Ok(());
+    let reformatted = streaming_links(
+        // counting is cheaper than building the entries, failures are reported by the second pass
+        || {
+            item_counts(source, target).ok();
+        },
+        || res = item_entries(source, target, emit),
+    );
+    res?;
+
+    if reformatted > 0 {
+        emit(DiffEntry::reformatted_descriptions(reformatted));
+    }
+
+    Ok(())
+}
+
+/// Counts the changed items per section, see [`super::Doc::count_changes`].
+pub(super) fn counts(
+    source: &impl RuntimeItems,
+    target: &impl RuntimeItems,
+) -> crate::Result<ChangeCounts> {
+    let (sections, collapsed) = counting_links(|| item_counts(source, target));
+
+    Ok(ChangeCounts::from_sections(sections?, collapsed))
+}
+
+fn item_counts(
+    source: &impl RuntimeItems,
+    target: &impl RuntimeItems,
+) -> crate::Result<Vec<(&'static str, (ItemCounts, usize))>> {
+    API_VERSIONS.set((source.api_version(), target.api_version()));
+
+    Ok(vec![
+        (
+            "concepts",
+            in_path("concepts", || {
+                count_each(&source.concepts(), &target.concepts())
+            })?,
+        ),
+        (
+            "events",
+            in_path("events", || count_each(&source.events(), &target.events()))?,
+        ),
+        (
+            "classes",
+            in_path("classes", || {
+                count_each(&source.classes(), &target.classes())
+            })?,
+        ),
+        (
+            "defines",
+            in_path("defines", || {
+                count_each(&source.defines(), &target.defines())
+            })?,
+        ),
+        (
+            "global_objects",
+            in_path("global_objects", || {
+                count_each(&source.global_objects(), &target.global_objects())
+            })?,
+        ),
+        (
+            "global_functions",
+            in_path("global_functions", || {
+                count_each(&source.global_functions(), &target.global_functions())
+            })?,
+        ),
+    ])
+}
+
+fn item_entries(
+    source: &impl RuntimeItems,
+    target: &impl RuntimeItems,
+    emit: &mut dyn FnMut(DiffEntry),
+) -> crate::Result<()> {
+    API_VERSIONS.set((source.api_version(), target.api_version()));
+
+    let mut concepts = HashSet::new();
+    let target_concepts = target.concepts();
+    in_path("concepts", || {
+        diff_each(&source.concepts(), &target_concepts, |name, status, d| {
+            if !d.is_empty() {
+                concepts.insert(name.to_owned());
+            }
+
+            DiffEntry::emit(emit, "concept", name, status, &d, &target_concepts);
+        })
+    })?;
+
+    let (source_events, target_events) = (source.events(), target.events());
+    let mut changed_events = HashSet::new();
+    in_path("events", || {
+        diff_each(&source_events, &target_events, |name, status, mut d| {
+            if is_event_change(&d) {
+                changed_events.insert(name.to_owned());
+            }
+
+            // the event was just diffed, so it can be deserialized
+            let related = target_events
+                .item(name)
+                .ok()
+                .flatten()
+                .and_then(|e| filter_change(&e, |c| concepts.contains(c)));
+
+            if let Some(related) = related {
+                d.push(EventDiffRef::RelatedChanges(vec![related]));
+            }
+
+            DiffEntry::emit(emit, "event", name, status, &d, &target_events);
+        })
+    })?;
+
+    // inherited changes need to know all changed classes and parents up front
+    let (source_classes, target_classes) = (source.classes(), target.classes());
+    let mut changed = HashSet::new();
+    let mut parents = HashMap::new();
+    let inherit = super::DiffOptions::current().inherit;
+    if inherit {
+        in_path("classes", || {
+            diff_each(&source_classes, &target_classes, |name, _, d| {
+                if d.iter().any(is_member_change) {
+                    changed.insert(name.to_owned());
+                }
+            })
+        })?;
+
+        parents = class_parents(&target_classes);
+    }
+
+    let raised_status = |name: &str| {
+        raised_event_status(name, &source_events, &target_events, |e| {
+            changed_events.contains(e)
+        })
+    };
+    in_path("classes", || {
+        diff_each(&source_classes, &target_classes, |name, status, mut d| {
+            link_raised_events(&mut d, &raised_status);
+
+            if inherit {
+                let related = parent_changes(name, &parents, |p| changed.contains(p));
+
+                if !related.is_empty() {
+                    d.push(ClassDiffRef::RelatedChanges(related));
+                }
+            }
+
+            DiffEntry::emit(emit, "class", name, status, &d, &target_classes);
+        })
+    })?;
+
+    let target_defines = target.defines();
+    in_path("defines", || {
+        diff_each(&source.defines(), &target_defines, |name, status, d| {
+            DiffEntry::emit(emit, "define", name, status, &d, &target_defines);
+        })
+    })?;
+    let target_objects = target.global_objects();
+    in_path("global_objects", || {
+        diff_each(
+            &source.global_objects(),
+            &target_objects,
+            |name, status, d| {
+                DiffEntry::emit(emit, "global_object", name, status, &d, &target_objects);
+            },
+        )
+    })?;
+    let target_functions = target.global_functions();
+    in_path("global_functions", || {
+        diff_each(
+            &source.global_functions(),
+            &target_functions,
+            |name, status, d| {
+                DiffEntry::emit(emit, "global_function", name, status, &d, &target_functions);
+            },
+        )
+    })?;
+
+    Ok(())
+}
+
+/// Marks events whose filter concept changed, the event itself may be unchanged.
+fn link_event_filters<'a>(
+    events: &mut DiffableVecDiffRef<'a, Event>,
+    target: &'a RuntimeDoc,
+    concepts: &DiffableVecDiffRef<'_, Concept>,
+) {
+    for (name, event) in target.events.iter() {
+        if let Some(related) = filter_change(event, |c| concepts.contains_key(c)) {
+            events
+                .entry(name)
+                .or_default()
+                .push(EventDiffRef::RelatedChanges(vec![related]));
+        }
+    }
+}
+
+/// How the event `name` changed, `changed` tells whether an event in both docs changed itself.
+///
+/// All events are diffed before, so they can be deserialized.
+fn raised_event_status(
+    name: &str,
+    source: &impl Items<Event>,
+    target: &impl Items<Event>,
+    changed: impl Fn(&str) -> bool,
+) -> RaisedEventStatus {
+    match (
+        matches!(source.item(name), Ok(Some(_))),
+        matches!(target.item(name), Ok(Some(_))),
+    ) {
+        (false, true) => RaisedEventStatus::Added,
+        (true, false) => RaisedEventStatus::Removed,
+        (false, false) => RaisedEventStatus::Undocumented,
+        (true, true) if changed(name) => RaisedEventStatus::Changed,
+        (true, true) => RaisedEventStatus::Unchanged,
+    }
+}
+
+/// Whether an event diff has changes of the event itself, not only of related items.
+fn is_event_change(diff: &[EventDiffRef]) -> bool {
+    diff.iter()
+        .any(|d| !matches!(d, EventDiffRef::RelatedChanges(_)))
+}
+
+/// Events of the changed `raises` entries, sorted by name.
+fn raised_events(
+    raises: &DiffableVecDiffRef<'_, EventRaised>,
+    status: &impl Fn(&str) -> RaisedEventStatus,
+) -> Vec<RaisedEvent> {
+    let mut res = raises
+        .keys()
+        .map(|event| RaisedEvent {
+            event: (*event).to_owned(),
+            status: status(event),
+        })
+        .collect::<Vec<_>>();
+    res.sort_by(|a, b| a.event.cmp(&b.event));
+
+    res
+}
+
+/// Links the changed `raises` entries of the methods and attributes of a class to their events.
+fn link_raised_events(diff: &mut [ClassDiffRef<'_>], status: &impl Fn(&str) -> RaisedEventStatus) {
+    for d in diff {
+        match d {
+            ClassDiffRef::Methods(methods) => {
+                for method in methods.values_mut() {
+                    let links = method.iter().find_map(|m| match m {
+                        MethodDiffRef::Raises(raises) => Some(raised_events(raises, status)),
+                        _ => None,
+                    });
+
+                    if let Some(links) = links.filter(|l| !l.is_empty()) {
+                        method.push(MethodDiffRef::RaisedEvents(links));
+                    }
+                }
+            }
+            ClassDiffRef::Attributes(attributes) => {
+                for attribute in attributes.values_mut() {
+                    let links = attribute.iter().find_map(|a| match a {
+                        AttributeDiffRef::Raises(raises) => Some(raised_events(raises, status)),
+                        _ => None,
+                    });
+
+                    if let Some(links) = links.filter(|l| !l.is_empty()) {
+                        attribute.push(AttributeDiffRef::RaisedEvents(links));
+                    }
+                }
+            }
+            _ => {}
         }
     }
 }
 
+fn filter_change(event: &Event, changed: impl Fn(&str) -> bool) -> Option<RelatedChange> {
+    event
+        .filter
+        .as_ref()
+        .filter(|f| changed(f))
+        .map(|f| RelatedChange::FilterConcept(f.clone()))
+}
+
+/// Marks classes that inherit from classes whose members or parent changed.
+fn link_inherited_changes<'a>(classes: &mut DiffableVecDiffRef<'a, Class>, target: &'a RuntimeDoc) {
+    let changed = classes
+        .iter()
+        .filter(|(_, diff)| diff.iter().any(is_member_change))
+        .map(|(name, _)| *name)
+        .collect::<HashSet<_>>();
+    let parents = class_parents(&target.classes);
+
+    for name in target.classes.keys() {
+        let related = parent_changes(name, &parents, |p| changed.contains(p));
+
+        if !related.is_empty() {
+            classes
+                .entry(name)
+                .or_default()
+                .push(ClassDiffRef::RelatedChanges(related));
+        }
+    }
+}
+
+const fn is_member_change(diff: &ClassDiffRef) -> bool {
+    matches!(
+        diff,
+        ClassDiffRef::Parent(_)
+            | ClassDiffRef::Methods(_)
+            | ClassDiffRef::Attributes(_)
+            | ClassDiffRef::Operators(_)
+    )
+}
+
+/// Parent of each class that has one, the classes are diffed before, so they can be deserialized.
+fn class_parents(classes: &impl Items<Class>) -> HashMap<String, String> {
+    classes
+        .names()
+        .into_iter()
+        .filter_map(|name| {
+            let parent = classes.item(name).ok()??.parent.clone()?;
+            Some((name.to_owned(), parent))
+        })
+        .collect()
+}
+
+/// Changed classes in the parent chain of the class `name`.
+fn parent_changes(
+    name: &str,
+    parents: &HashMap<String, String>,
+    changed: impl Fn(&str) -> bool,
+) -> Vec<RelatedChange> {
+    let mut related = Vec::new();
+    let mut visited = HashSet::from([name]);
+    let mut parent = parents.get(name);
+
+    while let Some(p) = parent {
+        if !visited.insert(p) {
+            break;
+        }
+
+        if changed(p) {
+            related.push(RelatedChange::ParentClass(p.clone()));
+        }
+
+        parent = parents.get(p);
+    }
+
+    related
+}
+
 impl super::Info for RuntimeDoc {
     fn print_info(&self) {
         self.common.print_info();
@@ -58,14 +557,253 @@ impl super::Info for RuntimeDoc {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 pub struct RuntimeDocDiff {
+    /// Schema version of the diff, see [`super::DIFF_FORMAT_VERSION`]
+    #[serde(default = "super::unversioned")]
+    pub diff_format_version: u32,
+    /// The compared docs, missing in diffs of older versions of this crate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<super::Header>,
     pub classes: DiffableVecDiff<Class>,
     pub events: DiffableVecDiff<Event>,
     pub concepts: DiffableVecDiff<Concept>,
     pub defines: DiffableVecDiff<Define>,
     pub global_objects: DiffableVecDiff<Parameter>,
     pub global_functions: DiffableVecDiff<Method>,
+    #[serde(default)]
+    pub added: ItemNames,
+    #[serde(default)]
+    pub removed: ItemNames,
+    /// Renamed items of each section keyed by their old names, see [`super::with_rename_marker`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub renamed: BTreeMap<String, BTreeMap<String, String>>,
+    /// Counters of the changed classes, see [`super::DiffOptions::metrics`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metrics: BTreeMap<String, Metrics>,
+    /// Pages of the changed items in the target docs, see [`super::DiffOptions::anchor_links`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub urls: BTreeMap<String, BTreeMap<String, String>>,
+    /// The complete changed items in the target docs, see [`super::DiffOptions::context`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub context: BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+    /// Number of descriptions whose only changes were their links,
+    /// see [`super::DiffOptions::keep_link_changes`]
+    #[serde(default, skip_serializing_if = "super::is_zero")]
+    pub reformatted_descriptions: usize,
+}
+
+impl RuntimeDocDiff {
+    /// Replaces removed and added concepts that were renamed, see [`super::with_rename_marker`],
+    /// by the changes between their old and new versions.
+    fn link_renamed_concepts(&mut self, source: &RuntimeDoc, target: &RuntimeDoc) {
+        let described = |doc: &'_ RuntimeDoc, names: &[String]| {
+            names
+                .iter()
+                .filter_map(|name| {
+                    let concept = doc.concepts.get(name)?;
+                    Some((name.clone(), concept.description.to_string()))
+                })
+                .collect::<Vec<_>>()
+        };
+        let (removed, added) = (
+            described(source, &self.removed.concepts),
+            described(target, &self.added.concepts),
+        );
+        let renames = super::renames::find(
+            removed.iter().map(|(n, d)| (n.as_str(), d.as_str())),
+            added.iter().map(|(n, d)| (n.as_str(), d.as_str())),
+        );
+
+        for (old, new) in renames {
+            let (Some(old_concept), Some(new_concept)) =
+                (source.concepts.get(&old), target.concepts.get(&new))
+            else {
+                continue;
+            };
+
+            let changes = in_path("concepts", || {
+                in_path(&new, || old_concept.diff(new_concept))
+            });
+            self.concepts.remove(&old);
+            self.concepts.insert(new.clone(), changes);
+            self.removed.concepts.retain(|c| *c != old);
+            self.added.concepts.retain(|c| *c != new);
+            self.renamed
+                .entry("concepts".to_owned())
+                .or_default()
+                .insert(old, new);
+        }
+    }
+
+    /// Number of added, removed and changed items.
+    ///
+    /// Changed classes count their added, removed and changed members instead if the diff has
+    /// [`super::DiffOptions::metrics`], so small changes of many members aren't hidden in few classes.
+    #[must_use]
+    pub fn change_count(&self) -> usize {
+        super::change_count([
+            (
+                self.classes.keys().collect(),
+                self.added.classes.as_slice(),
+                self.removed.classes.as_slice(),
+                Some(&self.metrics),
+            ),
+            (
+                self.events.keys().collect(),
+                self.added.events.as_slice(),
+                self.removed.events.as_slice(),
+                None,
+            ),
+            (
+                self.concepts.keys().collect(),
+                self.added.concepts.as_slice(),
+                self.removed.concepts.as_slice(),
+                None,
+            ),
+            (
+                self.defines.keys().collect(),
+                self.added.defines.as_slice(),
+                self.removed.defines.as_slice(),
+                None,
+            ),
+            (
+                self.global_objects.keys().collect(),
+                self.added.global_objects.as_slice(),
+                self.removed.global_objects.as_slice(),
+                None,
+            ),
+            (
+                self.global_functions.keys().collect(),
+                self.added.global_functions.as_slice(),
+                self.removed.global_functions.as_slice(),
+                None,
+            ),
+        ])
+    }
+
+    /// Pages of the changed items, removed items aren't linked.
+    fn item_urls(&self, links: &Links) -> BTreeMap<String, BTreeMap<String, String>> {
+        super::item_urls(
+            links,
+            [
+                (
+                    "classes",
+                    self.classes.keys().collect(),
+                    self.removed.classes.as_slice(),
+                ),
+                (
+                    "events",
+                    self.events.keys().collect(),
+                    self.removed.events.as_slice(),
+                ),
+                (
+                    "concepts",
+                    self.concepts.keys().collect(),
+                    self.removed.concepts.as_slice(),
+                ),
+                (
+                    "defines",
+                    self.defines.keys().collect(),
+                    self.removed.defines.as_slice(),
+                ),
+                (
+                    "global_objects",
+                    self.global_objects.keys().collect(),
+                    self.removed.global_objects.as_slice(),
+                ),
+                (
+                    "global_functions",
+                    self.global_functions.keys().collect(),
+                    self.removed.global_functions.as_slice(),
+                ),
+            ],
+        )
+    }
+
+    /// The complete changed items of each section, removed items have none.
+    fn item_context(
+        &self,
+        target: &RuntimeDoc,
+    ) -> BTreeMap<String, BTreeMap<String, serde_json::Value>> {
+        [
+            (
+                "classes",
+                super::item_context("class", self.classes.keys(), &target.classes),
+            ),
+            (
+                "events",
+                super::item_context("event", self.events.keys(), &target.events),
+            ),
+            (
+                "concepts",
+                super::item_context("concept", self.concepts.keys(), &target.concepts),
+            ),
+            (
+                "defines",
+                super::item_context("define", self.defines.keys(), &target.defines),
+            ),
+            (
+                "global_objects",
+                super::item_context(
+                    "global_object",
+                    self.global_objects.keys(),
+                    &target.global_objects,
+                ),
+            ),
+            (
+                "global_functions",
+                super::item_context(
+                    "global_function",
+                    self.global_functions.keys(),
+                    &target.global_functions,
+                ),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, items)| !items.is_empty())
+        .map(|(section, items)| (section.to_owned(), items))
+        .collect()
+    }
+}
+
+impl Invert for RuntimeDocDiff {
+    fn invert(self) -> Self {
+        Self {
+            // missing fields were filled in while deserializing
+            diff_format_version: super::DIFF_FORMAT_VERSION,
+            header: self.header.invert(),
+            classes: self.classes.invert(),
+            events: self.events.invert(),
+            concepts: self.concepts.invert(),
+            defines: self.defines.invert(),
+            global_objects: self.global_objects.invert(),
+            global_functions: self.global_functions.invert(),
+            added: self.removed,
+            removed: self.added,
+            renamed: self
+                .renamed
+                .into_iter()
+                .map(|(section, names)| (section, names.into_iter().map(|(o, n)| (n, o)).collect()))
+                .collect(),
+            metrics: self.metrics.invert(),
+            // the links and items are the ones of the original target docs
+            urls: BTreeMap::new(),
+            context: BTreeMap::new(),
+            reformatted_descriptions: self.reformatted_descriptions,
+        }
+    }
+}
+
+/// Names of top level items, e.g. the ones that were added or removed.
+#[derive(Serialize, Deserialize, Default)]
+pub struct ItemNames {
+    pub classes: Vec<String>,
+    pub events: Vec<String>,
+    pub concepts: Vec<String>,
+    pub defines: Vec<String>,
+    pub global_objects: Vec<String>,
+    pub global_functions: Vec<String>,
 }
 
 impl super::Info for RuntimeDocDiff {
@@ -82,15 +820,15 @@ impl super::Info for RuntimeDocDiff {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Clone, Default, Hash)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Default, Hash)]
 pub struct Common {
-    pub name: String,
+    pub name: Interned,
 
     #[serde(default)] // is actually not optional, 1.1.108 forgot it in one place tho
-    pub order: i16, // could be a float
+    pub order: Order,
 
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub description: String,
+    #[serde(default, skip_serializing_if = "Interned::is_empty")]
+    pub description: Interned,
 }
 
 pub type DefineValue = Common;
@@ -101,47 +839,59 @@ impl Named for Common {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum CommonDiff {
-    Name(String),
-    Order(i16),
-    Description(String),
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum CommonDiff / CommonDiffRef {
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        Description(TextChange<Interned>),
+    }
 }
 
 impl StructDiff for Common {
     type Diff = CommonDiff;
-    type DiffRef<'target> = CommonDiff;
+    type DiffRef<'target> = CommonDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
-        let cli = crate::CLI.with_borrow(Clone::clone);
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
-        if self.name != updated.name {
-            res.push(Self::Diff::Name(updated.name.clone()));
+        if self.name != updated.name && FieldCategory::Names.is_enabled() {
+            res.push(CommonDiffRef::Name(Change::borrowed(
+                &self.name,
+                &updated.name,
+            )));
         }
 
-        if self.description != updated.description && (cli.descriptions || cli.full) {
-            res.push(Self::Diff::Description(updated.description.clone()));
+        if FieldCategory::Descriptions.is_enabled()
+            && text_changed(&self.description, &updated.description)
+        {
+            res.push(CommonDiffRef::Description(TextChange::borrowed(
+                &self.description,
+                &updated.description,
+            )));
         }
 
-        if self.order != updated.order && cli.full {
-            res.push(Self::Diff::Order(updated.order));
+        if self.order != updated.order && FieldCategory::Order.is_enabled() {
+            res.push(CommonDiffRef::Order(Change::borrowed(
+                &self.order,
+                &updated.order,
+            )));
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Clone, Default, Hash)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Default, Hash)]
 pub struct BasicMember {
     #[serde(flatten)]
     common: Common,
@@ -164,59 +914,67 @@ impl Deref for BasicMember {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum BasicMemberDiff {
-    // common fields
-    Name(String),
-    Order(i16),
-    Description(String),
-    // basic member fields
-    Lists(Vec<String>),
-    Examples(Vec<String>),
-    Images(Vec<Image>),
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum BasicMemberDiff / BasicMemberDiffRef {
+        // common fields
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        Description(TextChange<Interned>),
+        // basic member fields
+        Lists(Change<Vec<String>>),
+        Examples(Change<Vec<String>>),
+        Images(ImagesChange),
+    }
 }
 
 impl StructDiff for BasicMember {
     type Diff = BasicMemberDiff;
-    type DiffRef<'target> = BasicMemberDiff;
+    type DiffRef<'target> = BasicMemberDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
-        let cli = crate::CLI.with_borrow(Clone::clone);
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
+            let common_diff = self.common.diff_ref(&updated.common);
 
             for d in common_diff {
                 let d = match d {
-                    CommonDiff::Name(name) => Self::Diff::Name(name),
-                    CommonDiff::Order(order) => Self::Diff::Order(order),
-                    CommonDiff::Description(desc) => Self::Diff::Description(desc),
+                    CommonDiffRef::Name(name) => BasicMemberDiffRef::Name(name),
+                    CommonDiffRef::Order(order) => BasicMemberDiffRef::Order(order),
+                    CommonDiffRef::Description(desc) => BasicMemberDiffRef::Description(desc),
                 };
                 res.push(d);
             }
         }
 
-        if self.lists != updated.lists && cli.full {
-            res.push(Self::Diff::Lists(updated.lists.clone()));
+        if FieldCategory::Lists.is_enabled() && texts_changed(&self.lists, &updated.lists) {
+            res.push(BasicMemberDiffRef::Lists(Change::borrowed(
+                &self.lists,
+                &updated.lists,
+            )));
         }
 
-        if self.examples != updated.examples && (cli.examples || cli.full) {
-            res.push(Self::Diff::Examples(updated.examples.clone()));
+        if FieldCategory::Examples.is_enabled() && texts_changed(&self.examples, &updated.examples)
+        {
+            res.push(BasicMemberDiffRef::Examples(Change::borrowed(
+                &self.examples,
+                &updated.examples,
+            )));
         }
 
-        if self.images != updated.images && cli.full {
-            res.push(Self::Diff::Images(updated.images.clone()));
+        if let Some(images) = ImagesChange::new(&self.images, &updated.images) {
+            res.push(BasicMemberDiffRef::Images(images));
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
@@ -227,8 +985,8 @@ pub struct Class {
     #[serde(flatten)]
     common: BasicMember,
 
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub visibility: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub visibility: BTreeSet<Visibility>,
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub parent: Option<String>,
@@ -257,90 +1015,112 @@ impl Deref for Class {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum ClassDiff {
-    // common fields
-    Name(String),
-    Order(i16),
-    Description(String),
-    // basic member fields
-    Lists(Vec<String>),
-    Examples(Vec<String>),
-    Images(Vec<Image>),
-    // class fields
-    Visibility(Vec<String>),
-    Parent(Option<String>),
-    Abstract(bool),
-    Methods(DiffableVecDiff<Method>),
-    Attributes(DiffableVecDiff<Attribute>),
-    Operators(DiffableVecDiff<Operator>),
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ClassDiff / ClassDiffRef {
+        // common fields
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        Description(TextChange<Interned>),
+        // basic member fields
+        Lists(Change<Vec<String>>),
+        Examples(Change<Vec<String>>),
+        Images(ImagesChange),
+        // class fields
+        Visibility(VisibilityChange),
+        Parent(Change<Option<String>>),
+        Abstract(Change<bool>),
+        Methods(DiffableVecDiff<Method>),
+        Attributes(DiffableVecDiff<Attribute>),
+        Operators(DiffableVecDiff<Operator>),
+        // cross references
+        RelatedChanges(Vec<RelatedChange>),
+    }
 }
 
 impl StructDiff for Class {
     type Diff = ClassDiff;
-    type DiffRef<'target> = ClassDiff;
+    type DiffRef<'target> = ClassDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
+        let mut metrics = Metrics::default();
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
+            let common_diff = self.common.diff_ref(&updated.common);
 
             for d in common_diff {
                 let d = match d {
-                    BasicMemberDiff::Name(name) => Self::Diff::Name(name),
-                    BasicMemberDiff::Order(order) => Self::Diff::Order(order),
-                    BasicMemberDiff::Description(desc) => Self::Diff::Description(desc),
-                    BasicMemberDiff::Lists(notes) => Self::Diff::Lists(notes),
-                    BasicMemberDiff::Examples(examples) => Self::Diff::Examples(examples),
-                    BasicMemberDiff::Images(images) => Self::Diff::Images(images),
+                    BasicMemberDiffRef::Name(name) => ClassDiffRef::Name(name),
+                    BasicMemberDiffRef::Order(order) => ClassDiffRef::Order(order),
+                    BasicMemberDiffRef::Description(desc) => ClassDiffRef::Description(desc),
+                    BasicMemberDiffRef::Lists(notes) => ClassDiffRef::Lists(notes),
+                    BasicMemberDiffRef::Examples(examples) => ClassDiffRef::Examples(examples),
+                    BasicMemberDiffRef::Images(images) => ClassDiffRef::Images(images),
                 };
                 res.push(d);
             }
         }
 
-        if self.visibility != updated.visibility {
-            res.push(Self::Diff::Visibility(updated.visibility.clone()));
+        if let Some(change) = VisibilityChange::new(&self.visibility, &updated.visibility) {
+            res.push(ClassDiffRef::Visibility(change));
         }
 
         if self.parent != updated.parent {
-            res.push(Self::Diff::Parent(updated.parent.clone()));
+            res.push(ClassDiffRef::Parent(Change::borrowed(
+                &self.parent,
+                &updated.parent,
+            )));
         }
 
         if self.abstract_ != updated.abstract_ {
-            res.push(Self::Diff::Abstract(updated.abstract_));
+            res.push(ClassDiffRef::Abstract(Change::borrowed(
+                &self.abstract_,
+                &updated.abstract_,
+            )));
         }
 
         if self.methods != updated.methods {
-            let diff = self.methods.diff(&updated.methods);
+            let diff = in_path("methods", || self.methods.diff_ref(&updated.methods));
+            metrics.count("methods", &self.methods, &updated.methods, &diff);
 
             if !diff.is_empty() {
-                res.push(Self::Diff::Methods(diff));
+                res.push(ClassDiffRef::Methods(diff));
             }
         }
 
         if self.attributes != updated.attributes {
-            let diff = self.attributes.diff(&updated.attributes);
+            let diff = in_path("attributes", || {
+                self.attributes.diff_ref(&updated.attributes)
+            });
+            metrics.count("attributes", &self.attributes, &updated.attributes, &diff);
 
             if !diff.is_empty() {
-                res.push(Self::Diff::Attributes(diff));
+                res.push(ClassDiffRef::Attributes(diff));
             }
         }
 
         if self.operators != updated.operators {
-            let diff = self.operators.diff(&updated.operators);
+            let diff = in_path("operators", || self.operators.diff_ref(&updated.operators));
+            metrics.count("operators", &self.operators, &updated.operators, &diff);
 
             if !diff.is_empty() {
-                res.push(Self::Diff::Operators(diff));
+                res.push(ClassDiffRef::Operators(diff));
             }
         }
 
-        res
-    }
+        metrics.record(if updated.name.is_empty() {
+            &self.name
+        } else {
+            &updated.name
+        });
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
+        res
     }
 
     fn apply_single(&mut self, _diff: Self::Diff) {
@@ -348,15 +1128,18 @@ impl StructDiff for Class {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(untagged)]
 pub enum Operator {
     Method(Method),
     Attribute(Attribute),
+    Unknown(UnknownOperator),
+}
 
-    #[default]
-    #[serde(skip)]
-    Unknown,
+impl Default for Operator {
+    fn default() -> Self {
+        Self::Unknown(UnknownOperator::default())
+    }
 }
 
 impl Deref for Operator {
@@ -366,64 +1149,171 @@ impl Deref for Operator {
         match self {
             Self::Method(m) => m,
             Self::Attribute(a) => a,
-            Self::Unknown => panic!("unknown operator"),
+            Self::Unknown(u) => &u.common,
         }
     }
 }
 
+/// An operator that's neither a method nor an attribute, kept as its raw JSON.
+///
+/// The common fields are read if possible, otherwise only the name. The default is no operator at all.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct UnknownOperator {
+    common: BasicMember,
+    pub raw: serde_json::Value,
+}
+
+impl Serialize for UnknownOperator {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.raw.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for UnknownOperator {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = serde_json::Value::deserialize(deserializer)?;
+        let common = BasicMember::deserialize(&raw).unwrap_or_else(|_| BasicMember {
+            common: Common {
+                name: raw
+                    .get("name")
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or_default()
+                    .into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        Ok(Self { common, raw })
+    }
+}
+
 impl Named for Operator {
     fn name(&self) -> &str {
         &self.name
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+impl Operator {
+    /// Form the operator is documented in, `None` if it's unknown.
+    #[must_use]
+    pub const fn kind(&self) -> Option<OperatorKind> {
+        match self {
+            Self::Method(_) => Some(OperatorKind::Method),
+            Self::Attribute(_) => Some(OperatorKind::Attribute),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+/// Whether an operator is documented like a method or like an attribute.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
-pub enum OperatorDiff {
-    Method(SingleDiff<Method>),
-    Attribute(SingleDiff<Attribute>),
+pub enum OperatorKind {
+    Method,
+    Attribute,
+}
+
+/// An operator that switched its form, e.g. `index` turning from an attribute into a method.
+///
+/// Both kinds are always serialized, the diff of the operator in its new form follows it.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct KindChange {
+    pub from: OperatorKind,
+    pub to: OperatorKind,
+}
+
+impl Invert for KindChange {
+    fn invert(self) -> Self {
+        Self {
+            from: self.to,
+            to: self.from,
+        }
+    }
+}
+
+impl Borrowed for KindChange {
+    type Ref<'a> = Self;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        diff
+    }
+}
+
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum OperatorDiff / OperatorDiffRef {
+        KindChanged(KindChange),
+        Method(SingleDiff<Method>),
+        Attribute(SingleDiff<Attribute>),
+        /// The raw JSON of an operator that is or was unknown
+        Unknown(Change<serde_json::Value>),
+    }
 }
 
 impl StructDiff for Operator {
     type Diff = OperatorDiff;
-    type DiffRef<'target> = OperatorDiff;
+    type DiffRef<'target> = OperatorDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
+        if let (Some(from), Some(to)) = (self.kind(), updated.kind()) {
+            if from != to {
+                res.push(OperatorDiffRef::KindChanged(KindChange { from, to }));
+            }
+        }
+
         match (self, updated) {
             (Self::Method(m), Self::Method(u)) => {
-                let diff = m.diff(u);
+                let diff = m.diff_ref(u);
 
                 if !diff.is_empty() {
-                    res.push(Self::Diff::Method(diff));
+                    res.push(OperatorDiffRef::Method(diff));
                 }
             }
             (Self::Attribute(a), Self::Attribute(u)) => {
-                let diff = a.diff(u);
+                let diff = a.diff_ref(u);
 
                 if !diff.is_empty() {
-                    res.push(Self::Diff::Attribute(diff));
+                    res.push(OperatorDiffRef::Attribute(diff));
                 }
             }
             (_, Self::Method(u)) => {
-                res.push(Self::Diff::Method(Method::default().diff(u)));
+                res.push(OperatorDiffRef::Method(empty::<Method>().diff_ref(u)));
             }
             (_, Self::Attribute(u)) => {
-                res.push(Self::Diff::Attribute(Attribute::default().diff(u)));
+                res.push(OperatorDiffRef::Attribute(empty::<Attribute>().diff_ref(u)));
+            }
+            // the operator was removed, reported like removed methods and attributes
+            (Self::Method(m), Self::Unknown(u)) if u.raw.is_null() => {
+                res.push(OperatorDiffRef::Method(m.diff_ref(empty::<Method>())));
             }
-            (_, Self::Unknown) => {
-                eprintln!("unknown operator");
+            (Self::Attribute(a), Self::Unknown(u)) if u.raw.is_null() => {
+                res.push(OperatorDiffRef::Attribute(a.diff_ref(empty::<Attribute>())));
+            }
+            (_, Self::Unknown(u)) => {
+                if !u.raw.is_null() {
+                    super::warn(format!("unknown operator {}", u.raw));
+                }
+
+                if self != updated {
+                    res.push(OperatorDiffRef::Unknown(Change::owned(
+                        serde_json::to_value(self).unwrap_or_default(),
+                        u.raw.clone(),
+                    )));
+                }
             }
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
@@ -454,64 +1344,134 @@ impl Named for Event {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum EventDiff / EventDiffRef {
+        // common fields
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        Description(TextChange<Interned>),
+        // basic member fields
+        Lists(Change<Vec<String>>),
+        Examples(Change<Vec<String>>),
+        Images(ImagesChange),
+        // event fields
+        Data(DiffableVecDiff<Parameter>),
+        Filter(Change<Option<String>>),
+        // cross references
+        RelatedChanges(Vec<RelatedChange>),
+    }
+}
+
+/// Reference to a changed item that affects the item it's attached to.
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "snake_case")]
-pub enum EventDiff {
-    // common fields
-    Name(String),
-    Order(i16),
-    Description(String),
-    // basic member fields
-    Lists(Vec<String>),
-    Examples(Vec<String>),
-    Images(Vec<Image>),
-    // event fields
-    Data(DiffableVecDiff<Parameter>),
-    Filter(Option<String>),
+pub enum RelatedChange {
+    /// The concept used as the filter of an event changed
+    FilterConcept(String),
+    /// The members or parent of a (transitive) parent class changed
+    ParentClass(String),
+}
+
+impl Invert for RelatedChange {
+    fn invert(self) -> Self {
+        self
+    }
+}
+
+impl Borrowed for RelatedChange {
+    type Ref<'a> = Self;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        diff
+    }
+}
+
+/// How the event of a changed `raises` entry changed in the same release.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RaisedEventStatus {
+    Unchanged,
+    Added,
+    Removed,
+    Changed,
+    /// Neither doc has an event of that name
+    Undocumented,
+}
+
+/// Event named by a changed `raises` entry of a method or attribute, see [`link_raised_events`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RaisedEvent {
+    pub event: String,
+    pub status: RaisedEventStatus,
+}
+
+impl Invert for RaisedEvent {
+    fn invert(self) -> Self {
+        let status = match self.status {
+            RaisedEventStatus::Added => RaisedEventStatus::Removed,
+            RaisedEventStatus::Removed => RaisedEventStatus::Added,
+            status => status,
+        };
+
+        Self { status, ..self }
+    }
+}
+
+impl Borrowed for RaisedEvent {
+    type Ref<'a> = Self;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        diff
+    }
 }
 
 impl StructDiff for Event {
     type Diff = EventDiff;
-    type DiffRef<'target> = EventDiff;
+    type DiffRef<'target> = EventDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
+            let common_diff = self.common.diff_ref(&updated.common);
 
             for d in common_diff {
                 let d = match d {
-                    BasicMemberDiff::Name(name) => Self::Diff::Name(name),
-                    BasicMemberDiff::Order(order) => Self::Diff::Order(order),
-                    BasicMemberDiff::Description(desc) => Self::Diff::Description(desc),
-                    BasicMemberDiff::Lists(notes) => Self::Diff::Lists(notes),
-                    BasicMemberDiff::Examples(examples) => Self::Diff::Examples(examples),
-                    BasicMemberDiff::Images(images) => Self::Diff::Images(images),
+                    BasicMemberDiffRef::Name(name) => EventDiffRef::Name(name),
+                    BasicMemberDiffRef::Order(order) => EventDiffRef::Order(order),
+                    BasicMemberDiffRef::Description(desc) => EventDiffRef::Description(desc),
+                    BasicMemberDiffRef::Lists(notes) => EventDiffRef::Lists(notes),
+                    BasicMemberDiffRef::Examples(examples) => EventDiffRef::Examples(examples),
+                    BasicMemberDiffRef::Images(images) => EventDiffRef::Images(images),
                 };
                 res.push(d);
             }
         }
 
         if self.data != updated.data {
-            let diff = self.data.diff(&updated.data);
+            let diff = in_path("data", || self.data.diff_ref(&updated.data));
 
             if !diff.is_empty() {
-                res.push(Self::Diff::Data(diff));
+                res.push(EventDiffRef::Data(diff));
             }
         }
 
         if self.filter != updated.filter {
-            res.push(Self::Diff::Filter(updated.filter.clone()));
+            res.push(EventDiffRef::Filter(Change::borrowed(
+                &self.filter,
+                &updated.filter,
+            )));
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
@@ -540,59 +1500,61 @@ impl Named for Concept {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum ConceptDiff {
-    // common fields
-    Name(String),
-    Order(i16),
-    Description(String),
-    // basic member fields
-    Lists(Vec<String>),
-    Examples(Vec<String>),
-    Images(Vec<Image>),
-    // concept fields
-    Type(TypeDiff),
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ConceptDiff / ConceptDiffRef {
+        // common fields
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        Description(TextChange<Interned>),
+        // basic member fields
+        Lists(Change<Vec<String>>),
+        Examples(Change<Vec<String>>),
+        Images(ImagesChange),
+        // concept fields
+        Type(TypeDiff),
+    }
 }
 
 impl StructDiff for Concept {
     type Diff = ConceptDiff;
-    type DiffRef<'target> = ConceptDiff;
+    type DiffRef<'target> = ConceptDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
+            let common_diff = self.common.diff_ref(&updated.common);
 
             for d in common_diff {
                 let d = match d {
-                    BasicMemberDiff::Name(name) => Self::Diff::Name(name),
-                    BasicMemberDiff::Order(order) => Self::Diff::Order(order),
-                    BasicMemberDiff::Description(desc) => Self::Diff::Description(desc),
-                    BasicMemberDiff::Lists(notes) => Self::Diff::Lists(notes),
-                    BasicMemberDiff::Examples(examples) => Self::Diff::Examples(examples),
-                    BasicMemberDiff::Images(images) => Self::Diff::Images(images),
+                    BasicMemberDiffRef::Name(name) => ConceptDiffRef::Name(name),
+                    BasicMemberDiffRef::Order(order) => ConceptDiffRef::Order(order),
+                    BasicMemberDiffRef::Description(desc) => ConceptDiffRef::Description(desc),
+                    BasicMemberDiffRef::Lists(notes) => ConceptDiffRef::Lists(notes),
+                    BasicMemberDiffRef::Examples(examples) => ConceptDiffRef::Examples(examples),
+                    BasicMemberDiffRef::Images(images) => ConceptDiffRef::Images(images),
                 };
                 res.push(d);
             }
         }
 
-        if self.type_ != updated.type_ {
-            let diff = self.type_.diff(&updated.type_);
+        if self.type_ != updated.type_ && FieldCategory::Types.is_enabled() {
+            let mut diff = in_path("type", || self.type_.diff_ref(&updated.type_));
 
             if !diff.is_empty() && !diff[0].skip() {
-                res.push(Self::Diff::Type(diff[0].clone()));
+                res.push(ConceptDiffRef::Type(diff.swap_remove(0)));
             }
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
@@ -607,7 +1569,7 @@ pub struct Define {
     pub values: DiffableVec<DefineValue>,
 
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub subkeys: DiffableVec<Define>,
+    pub subkeys: DiffableVec<Self>,
 }
 
 impl Deref for Define {
@@ -624,74 +1586,76 @@ impl Named for Define {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum DefineDiff {
-    // common fields
-    Name(String),
-    Order(i16),
-    Description(String),
-    // basic member fields
-    Lists(Vec<String>),
-    Examples(Vec<String>),
-    Images(Vec<Image>),
-    // define fields
-    Values(DiffableVecDiff<DefineValue>),
-    Subkeys(DiffableVecDiff<Define>),
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum DefineDiff / DefineDiffRef {
+        // common fields
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        Description(TextChange<Interned>),
+        // basic member fields
+        Lists(Change<Vec<String>>),
+        Examples(Change<Vec<String>>),
+        Images(ImagesChange),
+        // define fields
+        Values(DiffableVecDiff<DefineValue>),
+        Subkeys(DiffableVecDiff<Define>),
+    }
 }
 
 impl StructDiff for Define {
     type Diff = DefineDiff;
-    type DiffRef<'target> = DefineDiff;
+    type DiffRef<'target> = DefineDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
+            let common_diff = self.common.diff_ref(&updated.common);
 
             for d in common_diff {
                 let d = match d {
-                    BasicMemberDiff::Name(name) => Self::Diff::Name(name),
-                    BasicMemberDiff::Order(order) => Self::Diff::Order(order),
-                    BasicMemberDiff::Description(desc) => Self::Diff::Description(desc),
-                    BasicMemberDiff::Lists(notes) => Self::Diff::Lists(notes),
-                    BasicMemberDiff::Examples(examples) => Self::Diff::Examples(examples),
-                    BasicMemberDiff::Images(images) => Self::Diff::Images(images),
+                    BasicMemberDiffRef::Name(name) => DefineDiffRef::Name(name),
+                    BasicMemberDiffRef::Order(order) => DefineDiffRef::Order(order),
+                    BasicMemberDiffRef::Description(desc) => DefineDiffRef::Description(desc),
+                    BasicMemberDiffRef::Lists(notes) => DefineDiffRef::Lists(notes),
+                    BasicMemberDiffRef::Examples(examples) => DefineDiffRef::Examples(examples),
+                    BasicMemberDiffRef::Images(images) => DefineDiffRef::Images(images),
                 };
                 res.push(d);
             }
         }
 
         if self.values != updated.values {
-            let diff = self.values.diff(&updated.values);
+            let diff = in_path("values", || self.values.diff_ref(&updated.values));
 
             if !diff.is_empty() {
-                res.push(Self::Diff::Values(diff));
+                res.push(DefineDiffRef::Values(diff));
             }
         }
 
         if self.subkeys != updated.subkeys {
-            let diff = self.subkeys.diff(&updated.subkeys);
+            let diff = in_path("subkeys", || self.subkeys.diff_ref(&updated.subkeys));
 
             if !diff.is_empty() {
-                res.push(Self::Diff::Subkeys(diff));
+                res.push(DefineDiffRef::Subkeys(diff));
             }
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Clone, Default, Hash)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Default, Hash)]
 pub struct EventRaised {
     #[serde(flatten)]
     common: Common,
@@ -714,7 +1678,7 @@ impl Named for EventRaised {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Clone, Default, Hash)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Default, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum TimeFrame {
     #[default]
@@ -723,68 +1687,76 @@ pub enum TimeFrame {
     FutureTick,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum EventRaisedDiff {
-    // common fields
-    Name(String),
-    Order(i16),
-    Description(String),
-    // event raised fields
-    Timeframe(TimeFrame),
-    Optional(bool),
+diff_enum! {
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum EventRaisedDiff / EventRaisedDiffRef {
+        // common fields
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        Description(TextChange<Interned>),
+        // event raised fields
+        Timeframe(Change<TimeFrame>),
+        Optional(Change<bool>),
+    }
 }
 
 impl StructDiff for EventRaised {
     type Diff = EventRaisedDiff;
-    type DiffRef<'target> = EventRaisedDiff;
+    type DiffRef<'target> = EventRaisedDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
+            let common_diff = self.common.diff_ref(&updated.common);
 
             for d in common_diff {
                 let d = match d {
-                    CommonDiff::Name(name) => Self::Diff::Name(name),
-                    CommonDiff::Order(order) => Self::Diff::Order(order),
-                    CommonDiff::Description(desc) => Self::Diff::Description(desc),
+                    CommonDiffRef::Name(name) => EventRaisedDiffRef::Name(name),
+                    CommonDiffRef::Order(order) => EventRaisedDiffRef::Order(order),
+                    CommonDiffRef::Description(desc) => EventRaisedDiffRef::Description(desc),
                 };
                 res.push(d);
             }
         }
 
         if self.timeframe != updated.timeframe {
-            res.push(Self::Diff::Timeframe(updated.timeframe.clone()));
+            res.push(EventRaisedDiffRef::Timeframe(Change::borrowed(
+                &self.timeframe,
+                &updated.timeframe,
+            )));
         }
 
         if self.optional != updated.optional {
-            res.push(Self::Diff::Optional(updated.optional));
+            res.push(EventRaisedDiffRef::Optional(Change::borrowed(
+                &self.optional,
+                &updated.optional,
+            )));
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 #[serde(untagged)]
 pub enum Type {
-    Simple(String),
+    Simple(Interned),
     Complex(Box<ComplexType>),
 }
 
 impl Type {
     #[must_use]
-    pub fn as_simple(&self) -> Option<String> {
+    pub fn as_simple(&self) -> Option<Interned> {
         match self {
             Self::Simple(s) => Some(s.clone()),
             Self::Complex(_) => None,
@@ -798,25 +1770,71 @@ impl Type {
             Self::Simple(_) => None,
         }
     }
+
+    /// Canonical form of the type with wrappers that don't change its meaning removed.
+    ///
+    /// A `type` wrapper without a description and a union with a single option
+    /// are equivalent to the type they wrap.
+    /// The `builtin` complex type is equivalent to the simple type `builtin`.
+    /// Only the outermost type is normalized, nested types get normalized when they are diffed.
+    #[must_use]
+    pub fn normalize(&self) -> &Self {
+        static BUILTIN: LazyLock<Type> = LazyLock::new(|| Type::Simple("builtin".into()));
+
+        let Self::Complex(c) = self else {
+            return self;
+        };
+
+        match c.as_ref() {
+            ComplexType::Type { value, description } if description.is_empty() => value.normalize(),
+            ComplexType::Union { options, .. } => match options.as_slice() {
+                [option] => option.normalize(),
+                _ => self,
+            },
+            ComplexType::Builtin => &BUILTIN,
+            _ => self,
+        }
+    }
 }
 
 impl Default for Type {
     fn default() -> Self {
-        Self::Simple(String::default())
+        Self::Simple(Interned::default())
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(untagged)]
-#[serde(rename_all = "snake_case")]
-pub enum TypeDiff {
-    Simple(String),
-    Complex(SingleDiff<ComplexType>),
+impl Canonical for Type {
+    fn simple_name(&self) -> Option<&str> {
+        match self {
+            Self::Simple(s) => Some(s),
+            Self::Complex(_) => None,
+        }
+    }
+}
+
+diff_enum! {
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(untagged)]
+    #[serde(rename_all = "snake_case")]
+    pub enum TypeDiff / TypeDiffRef {
+        Simple(Change<Type>),
+        Complex(SingleDiff<ComplexType>),
+    }
 }
 
 impl TypeDiff {
     #[must_use]
-    pub fn skip(&self) -> bool {
+    pub const fn skip(&self) -> bool {
+        match self {
+            Self::Simple(_) => false,
+            Self::Complex(c) => c.is_empty(),
+        }
+    }
+}
+
+impl TypeDiffRef<'_> {
+    #[must_use]
+    pub const fn skip(&self) -> bool {
         match self {
             Self::Simple(_) => false,
             Self::Complex(c) => c.is_empty(),
@@ -826,47 +1844,64 @@ impl TypeDiff {
 
 impl StructDiff for Type {
     type Diff = TypeDiff;
-    type DiffRef<'target> = TypeDiff;
+    type DiffRef<'target> = TypeDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
+        if !super::DiffOptions::current().exact_types {
+            let (normalized, u_normalized) = (self.normalize(), updated.normalize());
+
+            if normalized != self || u_normalized != updated {
+                return normalized.diff_ref(u_normalized);
+            }
+        }
+
         let mut res = Vec::new();
 
         match (self, updated) {
             (Self::Simple(s), Self::Simple(u_s)) => {
                 if s != u_s {
-                    res.push(Self::Diff::Simple(u_s.clone()));
+                    res.push(TypeDiffRef::Simple(Change::borrowed(self, updated)));
                 }
             }
-            (Self::Complex(c), Self::Complex(u_c)) => {
+            (Self::Complex(c), Self::Complex(u_c)) if c.kind() == u_c.kind() => {
                 if c != u_c {
-                    let diff = c.diff(u_c);
+                    let diff = c.diff_ref(u_c);
 
                     if !diff.is_empty() {
-                        res.push(Self::Diff::Complex(diff));
+                        res.push(TypeDiffRef::Complex(diff));
                     }
                 }
             }
-            (_, Self::Simple(u_s)) => {
-                res.push(Self::Diff::Simple(u_s.clone()));
+            // added and removed types are reported field by field, the same way in both directions
+            (Self::Simple(s), Self::Complex(u_c)) if s.is_empty() => {
+                res.push(TypeDiffRef::Complex(ComplexType::diff_whole(u_c, false)));
             }
-            (_, Self::Complex(u_c)) => {
-                res.push(Self::Diff::Complex(ComplexType::Unknown.diff(u_c)));
+            (Self::Complex(c), Self::Simple(u_s)) if u_s.is_empty() => {
+                res.push(TypeDiffRef::Complex(ComplexType::diff_whole(c, true)));
+            }
+            // other changes of the kind are reported as a whole so they read the same in both directions
+            _ => {
+                if let Self::Complex(u_c) = updated {
+                    u_c.warn_unknown();
+                }
+
+                res.push(TypeDiffRef::Simple(Change::borrowed(self, updated)));
             }
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 #[serde(tag = "complex_type", rename_all = "snake_case")]
 pub enum ComplexType {
     Type {
@@ -915,35 +1950,186 @@ pub enum ComplexType {
     },
     Builtin, // might be an error in the input, should probably be just a simple type string
 
-    #[serde(skip)]
-    Unknown,
-}
+    #[serde(untagged)]
+    Other(OtherComplexType),
+}
+
+diff_enum! {
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ComplexTypeDiff / ComplexTypeDiffRef {
+        ComplexType(Change<String>),
+        Value(TypeDiff),
+        Key(TypeDiff),
+        Options(Vec<TypeDiff>),
+        FullFormat(Change<bool>),
+        Description(TextChange<String>),
+        Attributes(DiffableVecDiff<Attribute>),
+        FunctionParameters(Vec<TypeDiff>),
+        TableTupleParameters(DiffableVecDiff<Parameter>),
+        VariantParameterGroups(DiffableVecDiff<ParameterGroup>),
+        VariantParameterDescription(TextChange<String>),
+        Values(Vec<TypeDiff>),
+        #[serde(rename(serialize = "value"))] // clashes with `Value` when deserializing
+        Literal(Change<LiteralValue>),
+        /// The raw JSON of a complex type this version doesn't know
+        Raw(Change<serde_json::Value>),
+    }
+}
+
+impl ComplexType {
+    /// Name of the complex type as used in the docs.
+    #[must_use]
+    pub fn kind(&self) -> &str {
+        match self {
+            Self::Type { .. } => "type",
+            Self::Union { .. } => "union",
+            Self::Array { .. } => "array",
+            Self::Dictionary { .. } => "dictionary",
+            Self::LuaCustomTable { .. } => "LuaCustomTable",
+            Self::Function { .. } => "function",
+            Self::Literal(_) => "literal",
+            Self::LuaLazyLoadedValue { .. } => "LuaLazyLoadedValue",
+            Self::LuaStruct { .. } => "LuaStruct",
+            Self::Table { .. } => "table",
+            Self::Tuple { .. } => "tuple",
+            Self::Builtin => "builtin",
+            Self::Other(o) => &o.complex_type,
+        }
+    }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum ComplexTypeDiff {
-    ComplexType(String),
-    Value(TypeDiff),
-    Key(TypeDiff),
-    Options(Vec<TypeDiff>),
-    FullFormat(bool),
-    Description(String),
-    Attributes(DiffableVecDiff<Attribute>),
-    FunctionParameters(Vec<TypeDiff>),
-    TableTupleParameters(DiffableVecDiff<Parameter>),
-    VariantParameterGroups(DiffableVecDiff<ParameterGroup>),
-    VariantParameterDescription(String),
-    Values(Vec<TypeDiff>),
-    #[serde(rename = "value")]
-    Literal(LiteralValue),
+    /// Warns about the type if this version doesn't know its kind.
+    fn warn_unknown(&self) {
+        if let Self::Other(o) = self {
+            super::warn(format!("unknown complex type {}", o.complex_type));
+        }
+    }
+
+    /// Diff between an empty type and `type_`, all fields are reported as new, or as removed if `removed`.
+    #[allow(clippy::too_many_lines)]
+    fn diff_whole(type_: &Self, removed: bool) -> Vec<ComplexTypeDiffRef<'_>> {
+        let (old, new) = from_empty(String::new(), type_.kind().to_owned(), removed);
+        let mut res = vec![ComplexTypeDiffRef::ComplexType(Change::owned(old, new))];
+
+        match type_ {
+            Self::Type { value, description } => {
+                res.push(ComplexTypeDiffRef::Value(
+                    diff_empty(value, removed).swap_remove(0),
+                ));
+
+                if FieldCategory::Descriptions.is_enabled() {
+                    let (old, new) = from_empty(empty(), description, removed);
+                    res.push(ComplexTypeDiffRef::Description(TextChange::borrowed(
+                        old, new,
+                    )));
+                }
+            }
+            Self::Union {
+                options,
+                full_format,
+            } => {
+                res.push(ComplexTypeDiffRef::Options(
+                    canonical_order(options)
+                        .into_iter()
+                        .map(|o| diff_empty(o, removed).swap_remove(0))
+                        .collect(),
+                ));
+                let (old, new) = from_empty(&false, full_format, removed);
+                res.push(ComplexTypeDiffRef::FullFormat(Change::borrowed(old, new)));
+            }
+            Self::Array { value } | Self::LuaLazyLoadedValue { value } => {
+                res.push(ComplexTypeDiffRef::Value(
+                    diff_empty(value, removed).swap_remove(0),
+                ));
+            }
+            Self::Dictionary { key, value } | Self::LuaCustomTable { key, value } => {
+                res.push(ComplexTypeDiffRef::Key(
+                    diff_empty(key, removed).swap_remove(0),
+                ));
+                res.push(ComplexTypeDiffRef::Value(
+                    diff_empty(value, removed).swap_remove(0),
+                ));
+            }
+            Self::Function { parameters } => {
+                res.push(ComplexTypeDiffRef::FunctionParameters(
+                    parameters
+                        .iter()
+                        .flat_map(|p| diff_empty(p, removed))
+                        .collect(),
+                ));
+            }
+            Self::Literal(l) => {
+                let diff = diff_empty(l, removed);
+                for d in diff {
+                    match d {
+                        LiteralDiffRef::Value(v) => {
+                            res.push(ComplexTypeDiffRef::Literal(v));
+                        }
+                        LiteralDiffRef::Description(d) => {
+                            if FieldCategory::Descriptions.is_enabled() {
+                                res.push(ComplexTypeDiffRef::Description(d));
+                            }
+                        }
+                    }
+                }
+            }
+            Self::LuaStruct { attributes } => {
+                res.push(ComplexTypeDiffRef::Attributes(in_path(
+                    "attributes",
+                    || named_full(attributes, removed),
+                )));
+            }
+            Self::Table {
+                parameters,
+                variant_parameter_groups,
+                variant_parameter_description,
+            } => {
+                res.push(ComplexTypeDiffRef::TableTupleParameters(in_path(
+                    "parameters",
+                    || named_full(parameters, removed),
+                )));
+                res.push(ComplexTypeDiffRef::VariantParameterGroups(in_path(
+                    "variant_parameter_groups",
+                    || named_full(variant_parameter_groups, removed),
+                )));
+
+                if FieldCategory::Descriptions.is_enabled() {
+                    let (old, new) = from_empty(empty(), variant_parameter_description, removed);
+                    res.push(ComplexTypeDiffRef::VariantParameterDescription(
+                        TextChange::borrowed(old, new),
+                    ));
+                }
+            }
+            Self::Tuple { values } => {
+                res.push(ComplexTypeDiffRef::Values(
+                    values
+                        .iter()
+                        .map(|v| diff_empty(v, removed).swap_remove(0))
+                        .collect(),
+                ));
+            }
+            Self::Other(o) => {
+                super::warn(format!("unknown complex type {}", o.complex_type));
+                let (old, new) = from_empty(serde_json::Value::Null, o.raw.clone(), removed);
+                res.push(ComplexTypeDiffRef::Raw(Change::owned(old, new)));
+            }
+            Self::Builtin => {}
+        }
+
+        res
+    }
 }
 
 impl StructDiff for ComplexType {
     type Diff = ComplexTypeDiff;
-    type DiffRef<'target> = ComplexTypeDiff;
+    type DiffRef<'target> = ComplexTypeDiffRef<'target>;
 
-    #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         match (self, updated) {
@@ -955,15 +2141,18 @@ impl StructDiff for ComplexType {
                 },
             ) => {
                 if value != u_value {
-                    let diff = value.diff(u_value);
+                    let mut diff = value.diff_ref(u_value);
 
                     if !diff.is_empty() && !diff[0].skip() {
-                        res.push(Self::Diff::Value(diff[0].clone()));
+                        res.push(ComplexTypeDiffRef::Value(diff.swap_remove(0)));
                     }
                 }
 
-                if crate::CLI.with_borrow(|c| c.descriptions || c.full) && description != u_desc {
-                    res.push(Self::Diff::Description(u_desc.clone()));
+                if FieldCategory::Descriptions.is_enabled() && text_changed(description, u_desc) {
+                    res.push(ComplexTypeDiffRef::Description(TextChange::borrowed(
+                        description,
+                        u_desc,
+                    )));
                 }
             }
             (
@@ -977,27 +2166,37 @@ impl StructDiff for ComplexType {
                 },
             ) => {
                 if options != u_options {
-                    res.push(Self::Diff::Options(
+                    // the order of the options has no meaning unless exact types are reported
+                    let diff = if super::DiffOptions::current().exact_types {
                         vec_diff(options, u_options)
-                            .iter()
-                            .flatten()
-                            .filter(|o| !o.skip())
-                            .cloned()
-                            .collect(),
-                    ));
+                    } else {
+                        canonical_vec_diff(options, u_options)
+                    };
+                    let diff = diff
+                        .into_iter()
+                        .flatten()
+                        .filter(|o| !o.skip())
+                        .collect::<Vec<_>>();
+
+                    if !diff.is_empty() {
+                        res.push(ComplexTypeDiffRef::Options(diff));
+                    }
                 }
 
                 if full_format != u_full_format {
-                    res.push(Self::Diff::FullFormat(*u_full_format));
+                    res.push(ComplexTypeDiffRef::FullFormat(Change::borrowed(
+                        full_format,
+                        u_full_format,
+                    )));
                 }
             }
             (Self::Array { value }, Self::Array { value: u_value })
             | (Self::LuaLazyLoadedValue { value }, Self::LuaLazyLoadedValue { value: u_value }) => {
                 if value != u_value {
-                    let diff = value.diff(u_value);
+                    let mut diff = value.diff_ref(u_value);
 
                     if !diff.is_empty() && !diff[0].skip() {
-                        res.push(Self::Diff::Value(diff[0].clone()));
+                        res.push(ComplexTypeDiffRef::Value(diff.swap_remove(0)));
                     }
                 }
             }
@@ -1016,18 +2215,18 @@ impl StructDiff for ComplexType {
                 },
             ) => {
                 if key != u_key {
-                    let diff = key.diff(u_key);
+                    let mut diff = key.diff_ref(u_key);
 
                     if !diff.is_empty() && !diff[0].skip() {
-                        res.push(Self::Diff::Key(diff[0].clone()));
+                        res.push(ComplexTypeDiffRef::Key(diff.swap_remove(0)));
                     }
                 }
 
                 if value != u_value {
-                    let diff = value.diff(u_value);
+                    let mut diff = value.diff_ref(u_value);
 
                     if !diff.is_empty() && !diff[0].skip() {
-                        res.push(Self::Diff::Value(diff[0].clone()));
+                        res.push(ComplexTypeDiffRef::Value(diff.swap_remove(0)));
                     }
                 }
             }
@@ -1038,28 +2237,27 @@ impl StructDiff for ComplexType {
                 },
             ) => {
                 if parameters != u_params {
-                    res.push(Self::Diff::FunctionParameters(
+                    res.push(ComplexTypeDiffRef::FunctionParameters(
                         vec_diff(parameters, u_params)
-                            .iter()
+                            .into_iter()
                             .flatten()
                             .filter(|p| !p.skip())
-                            .cloned()
                             .collect(),
                     ));
                 }
             }
             (Self::Literal(l), Self::Literal(u_l)) => {
                 if l != u_l {
-                    let diff = l.diff(u_l);
+                    let diff = l.diff_ref(u_l);
 
                     for d in diff {
                         match d {
-                            super::prototype::LiteralDiff::Value(v) => {
-                                res.push(Self::Diff::Literal(v));
+                            LiteralDiffRef::Value(v) => {
+                                res.push(ComplexTypeDiffRef::Literal(v));
                             }
-                            super::prototype::LiteralDiff::Description(d) => {
-                                if crate::CLI.with_borrow(|c| c.descriptions || c.full) {
-                                    res.push(Self::Diff::Description(d));
+                            LiteralDiffRef::Description(d) => {
+                                if FieldCategory::Descriptions.is_enabled() {
+                                    res.push(ComplexTypeDiffRef::Description(d));
                                 }
                             }
                         }
@@ -1073,12 +2271,10 @@ impl StructDiff for ComplexType {
                 },
             ) => {
                 if attributes != u_attrs {
-                    let orig: DiffableVec<Attribute> = attributes.clone().into();
-                    let updated: DiffableVec<Attribute> = u_attrs.clone().into();
-                    let diff = orig.diff(&updated);
+                    let diff = in_path("attributes", || named_diff(attributes, u_attrs));
 
                     if !diff.is_empty() {
-                        res.push(Self::Diff::Attributes(diff));
+                        res.push(ComplexTypeDiffRef::Attributes(diff));
                     }
                 }
             }
@@ -1095,166 +2291,63 @@ impl StructDiff for ComplexType {
                 },
             ) => {
                 if param != u_param {
-                    let orig: DiffableVec<Parameter> = param.clone().into();
-                    let updated: DiffableVec<Parameter> = u_param.clone().into();
-                    let diff = orig.diff(&updated);
+                    let diff = in_path("parameters", || named_diff(param, u_param));
 
                     if !diff.is_empty() {
-                        res.push(Self::Diff::TableTupleParameters(diff));
+                        res.push(ComplexTypeDiffRef::TableTupleParameters(diff));
                     }
                 }
 
                 if vparam_g != u_vparam_g {
-                    let orig: DiffableVec<ParameterGroup> = vparam_g.clone().into();
-                    let updated: DiffableVec<ParameterGroup> = u_vparam_g.clone().into();
-                    res.push(Self::Diff::VariantParameterGroups(orig.diff(&updated)));
+                    res.push(ComplexTypeDiffRef::VariantParameterGroups(in_path(
+                        "variant_parameter_groups",
+                        || named_diff(vparam_g, u_vparam_g),
+                    )));
                 }
 
-                if crate::CLI.with_borrow(|c| c.descriptions || c.full)
-                    && vparam_desc != u_vparam_desc
+                if FieldCategory::Descriptions.is_enabled()
+                    && text_changed(vparam_desc, u_vparam_desc)
                 {
-                    res.push(Self::Diff::VariantParameterDescription(
-                        u_vparam_desc.clone(),
+                    res.push(ComplexTypeDiffRef::VariantParameterDescription(
+                        TextChange::borrowed(vparam_desc, u_vparam_desc),
                     ));
                 }
             }
             (Self::Tuple { values }, Self::Tuple { values: u_values }) => {
                 if values != u_values {
-                    res.push(Self::Diff::Values(
+                    res.push(ComplexTypeDiffRef::Values(
                         vec_diff(values, u_values)
-                            .iter()
+                            .into_iter()
                             .flatten()
                             .filter(|v| !v.skip())
-                            .cloned()
                             .collect(),
                     ));
                 }
             }
             (Self::Builtin, Self::Builtin) => {}
-            _ => match updated {
-                Self::Type { value, description } => {
-                    res.push(Self::Diff::ComplexType("type".to_owned()));
-                    res.push(Self::Diff::Value(Type::default().diff(value)[0].clone()));
-
-                    if crate::CLI.with_borrow(|c| c.descriptions || c.full) {
-                        res.push(Self::Diff::Description(description.clone()));
-                    }
-                }
-                Self::Union {
-                    options,
-                    full_format,
-                } => {
-                    res.push(Self::Diff::ComplexType("union".to_owned()));
-                    res.push(Self::Diff::Options(
-                        options
-                            .iter()
-                            .map(|o| Type::default().diff(o)[0].clone())
-                            .collect(),
-                    ));
-                    res.push(Self::Diff::FullFormat(*full_format));
-                }
-                Self::Array { value } => {
-                    res.push(Self::Diff::ComplexType("array".to_owned()));
-                    res.push(Self::Diff::Value(Type::default().diff(value)[0].clone()));
-                }
-                Self::Dictionary { key, value } => {
-                    res.push(Self::Diff::ComplexType("dictionary".to_owned()));
-                    res.push(Self::Diff::Key(Type::default().diff(key)[0].clone()));
-                    res.push(Self::Diff::Value(Type::default().diff(value)[0].clone()));
-                }
-                Self::LuaCustomTable { key, value } => {
-                    res.push(Self::Diff::ComplexType("LuaCustomTable".to_owned()));
-                    res.push(Self::Diff::Key(Type::default().diff(key)[0].clone()));
-                    res.push(Self::Diff::Value(Type::default().diff(value)[0].clone()));
-                }
-                Self::Function { parameters } => {
-                    res.push(Self::Diff::ComplexType("function".to_owned()));
-                    res.push(Self::Diff::FunctionParameters(
-                        parameters
-                            .iter()
-                            .flat_map(|p| Type::default().diff(p))
-                            .collect(),
-                    ));
-                }
-                Self::Literal(l) => {
-                    res.push(Self::Diff::ComplexType("literal".to_owned()));
-
-                    let diff = super::prototype::Literal::default().diff(l);
-                    for d in diff {
-                        match d {
-                            super::prototype::LiteralDiff::Value(v) => {
-                                res.push(Self::Diff::Literal(v));
-                            }
-                            super::prototype::LiteralDiff::Description(d) => {
-                                if crate::CLI.with_borrow(|c| c.descriptions || c.full) {
-                                    res.push(Self::Diff::Description(d));
-                                }
-                            }
-                        }
-                    }
-                }
-                Self::LuaLazyLoadedValue { value } => {
-                    res.push(Self::Diff::ComplexType("LuaLazyLoadedValue".to_owned()));
-                    res.push(Self::Diff::Value(Type::default().diff(value)[0].clone()));
-                }
-                Self::LuaStruct { attributes } => {
-                    res.push(Self::Diff::ComplexType("LuaStruct".to_owned()));
-
-                    let attributes: DiffableVec<Attribute> = attributes.clone().into();
-                    res.push(Self::Diff::Attributes(attributes.full()));
-                }
-                Self::Table {
-                    parameters,
-                    variant_parameter_groups,
-                    variant_parameter_description,
-                } => {
-                    res.push(Self::Diff::ComplexType("table".to_owned()));
-
-                    let params: DiffableVec<Parameter> = parameters.clone().into();
-                    res.push(Self::Diff::TableTupleParameters(params.full()));
-
-                    let groups: DiffableVec<ParameterGroup> =
-                        variant_parameter_groups.clone().into();
-                    res.push(Self::Diff::VariantParameterGroups(groups.full()));
-
-                    if crate::CLI.with_borrow(|c| c.descriptions || c.full) {
-                        res.push(Self::Diff::VariantParameterDescription(
-                            variant_parameter_description.clone(),
-                        ));
-                    }
-                }
-                Self::Tuple { values } => {
-                    res.push(Self::Diff::ComplexType("tuple".to_owned()));
+            (Self::Other(o), Self::Other(u)) if o.complex_type == u.complex_type => {
+                super::warn(format!("unknown complex type {}", u.complex_type));
 
-                    res.push(Self::Diff::Values(
-                        values
-                            .iter()
-                            .map(|v| Type::default().diff(v)[0].clone())
-                            .collect(),
-                    ));
-                }
-                Self::Builtin => {
-                    res.push(Self::Diff::ComplexType("builtin".to_owned()));
+                if o.raw != u.raw {
+                    res.push(ComplexTypeDiffRef::Raw(Change::borrowed(&o.raw, &u.raw)));
                 }
-                Self::Unknown => {
-                    eprintln!("unknown complex type");
-                }
-            },
+            }
+            // changes of the kind are reported by `Type` as a whole
+            _ => res.push(ComplexTypeDiffRef::ComplexType(Change::owned(
+                self.kind().to_owned(),
+                updated.kind().to_owned(),
+            ))),
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Clone, Default, Hash)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Default, Hash)]
 pub struct Parameter {
     #[serde(flatten)]
     common: Common,
@@ -1280,65 +2373,77 @@ impl Named for Parameter {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum ParameterDiff {
-    // common fields
-    Name(String),
-    Order(i16),
-    Description(String),
-    // parameter fields
-    Type(TypeDiff),
-    Optional(bool),
+diff_enum! {
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ParameterDiff / ParameterDiffRef {
+        // common fields
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        Description(TextChange<Interned>),
+        // parameter fields
+        Type(TypeDiff),
+        Optional(Change<bool>),
+        Optionality(Optionality),
+    }
 }
 
 impl StructDiff for Parameter {
     type Diff = ParameterDiff;
-    type DiffRef<'target> = ParameterDiff;
+    type DiffRef<'target> = ParameterDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
+            let common_diff = self.common.diff_ref(&updated.common);
 
             for d in common_diff {
                 let d = match d {
-                    CommonDiff::Name(name) => Self::Diff::Name(name),
-                    CommonDiff::Order(order) => Self::Diff::Order(order),
-                    CommonDiff::Description(desc) => Self::Diff::Description(desc),
+                    CommonDiffRef::Name(name) => ParameterDiffRef::Name(name),
+                    CommonDiffRef::Order(order) => ParameterDiffRef::Order(order),
+                    CommonDiffRef::Description(desc) => ParameterDiffRef::Description(desc),
                 };
                 res.push(d);
             }
         }
 
-        if self.type_ != updated.type_ {
-            let diff = self.type_.diff(&updated.type_);
+        if self.type_ != updated.type_ && FieldCategory::Types.is_enabled() {
+            let mut diff = in_path("type", || self.type_.diff_ref(&updated.type_));
 
             if !diff.is_empty() && !diff[0].skip() {
-                res.push(Self::Diff::Type(diff[0].clone()));
+                res.push(ParameterDiffRef::Type(diff.swap_remove(0)));
             }
         }
 
         if self.optional != updated.optional {
-            res.push(Self::Diff::Optional(updated.optional));
+            if DiffOptions::current().directional {
+                res.push(ParameterDiffRef::Optionality(Optionality::to(
+                    updated.optional,
+                )));
+            } else {
+                res.push(ParameterDiffRef::Optional(Change::borrowed(
+                    &self.optional,
+                    &updated.optional,
+                )));
+            }
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Clone, Default, Hash)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Default, Hash)]
 pub struct ReturnParameter {
-    pub order: i16, // could be a float
+    pub order: Order,
     pub description: String,
 
     #[serde(rename = "type")]
@@ -1346,57 +2451,75 @@ pub struct ReturnParameter {
     pub optional: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum ReturnParameterDiff {
-    Order(i16),
-    Description(String),
-    Type(TypeDiff),
-    Optional(bool),
+diff_enum! {
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ReturnParameterDiff / ReturnParameterDiffRef {
+        Order(Change<Order>),
+        Description(TextChange<String>),
+        Type(TypeDiff),
+        Optional(Change<bool>),
+        Optionality(Optionality),
+    }
 }
 
 impl StructDiff for ReturnParameter {
     type Diff = ReturnParameterDiff;
-    type DiffRef<'target> = ReturnParameterDiff;
+    type DiffRef<'target> = ReturnParameterDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
-        if self.order != updated.order && crate::CLI.with_borrow(|c| c.full) {
-            res.push(Self::Diff::Order(updated.order));
+        if self.order != updated.order && FieldCategory::Order.is_enabled() {
+            res.push(ReturnParameterDiffRef::Order(Change::borrowed(
+                &self.order,
+                &updated.order,
+            )));
         }
 
-        if self.description != updated.description
-            && crate::CLI.with_borrow(|c| c.descriptions || c.full)
+        if FieldCategory::Descriptions.is_enabled()
+            && text_changed(&self.description, &updated.description)
         {
-            res.push(Self::Diff::Description(updated.description.clone()));
+            res.push(ReturnParameterDiffRef::Description(TextChange::borrowed(
+                &self.description,
+                &updated.description,
+            )));
         }
 
-        if self.type_ != updated.type_ {
-            let diff = self.type_.diff(&updated.type_);
+        if self.type_ != updated.type_ && FieldCategory::Types.is_enabled() {
+            let mut diff = in_path("type", || self.type_.diff_ref(&updated.type_));
 
             if !diff.is_empty() && !diff[0].skip() {
-                res.push(Self::Diff::Type(diff[0].clone()));
+                res.push(ReturnParameterDiffRef::Type(diff.swap_remove(0)));
             }
         }
 
         if self.optional != updated.optional {
-            res.push(Self::Diff::Optional(updated.optional));
+            if DiffOptions::current().directional {
+                res.push(ReturnParameterDiffRef::Optionality(Optionality::to(
+                    updated.optional,
+                )));
+            } else {
+                res.push(ReturnParameterDiffRef::Optional(Change::borrowed(
+                    &self.optional,
+                    &updated.optional,
+                )));
+            }
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Clone, Default, Hash)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Default, Hash)]
 pub struct ParameterGroup {
     #[serde(flatten)]
     common: Common,
@@ -1418,54 +2541,56 @@ impl Named for ParameterGroup {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum ParameterGroupDiff {
-    // common fields
-    Name(String),
-    Order(i16),
-    Description(String),
-    // parameter group fields
-    Parameters(DiffableVecDiff<Parameter>),
+diff_enum! {
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ParameterGroupDiff / ParameterGroupDiffRef {
+        // common fields
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        Description(TextChange<Interned>),
+        // parameter group fields
+        Parameters(DiffableVecDiff<Parameter>),
+    }
 }
 
 impl StructDiff for ParameterGroup {
     type Diff = ParameterGroupDiff;
-    type DiffRef<'target> = ParameterGroupDiff;
+    type DiffRef<'target> = ParameterGroupDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
+            let common_diff = self.common.diff_ref(&updated.common);
 
             for d in common_diff {
                 let d = match d {
-                    CommonDiff::Name(name) => Self::Diff::Name(name),
-                    CommonDiff::Order(order) => Self::Diff::Order(order),
-                    CommonDiff::Description(desc) => Self::Diff::Description(desc),
+                    CommonDiffRef::Name(name) => ParameterGroupDiffRef::Name(name),
+                    CommonDiffRef::Order(order) => ParameterGroupDiffRef::Order(order),
+                    CommonDiffRef::Description(desc) => ParameterGroupDiffRef::Description(desc),
                 };
                 res.push(d);
             }
         }
 
         if self.parameters != updated.parameters {
-            let orig: DiffableVec<Parameter> = self.parameters.clone().into();
-            let updated: DiffableVec<Parameter> = updated.parameters.clone().into();
-            let diff = orig.diff(&updated);
+            let diff = in_path("parameters", || {
+                named_diff(&self.parameters, &updated.parameters)
+            });
 
             if !diff.is_empty() {
-                res.push(Self::Diff::Parameters(diff));
+                res.push(ParameterGroupDiffRef::Parameters(diff));
             }
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
@@ -1476,8 +2601,8 @@ pub struct Method {
     #[serde(flatten)]
     common: BasicMember,
 
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub visibility: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub visibility: BTreeSet<Visibility>,
 
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub raises: DiffableVec<EventRaised>,
@@ -1515,134 +2640,158 @@ impl Named for Method {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum MethodDiff {
-    // common fields
-    Name(String),
-    Order(i16),
-    Description(String),
-    // basic member fields
-    Lists(Vec<String>),
-    Examples(Vec<String>),
-    Images(Vec<Image>),
-    // method fields
-    Visibility(Vec<String>),
-    Raises(DiffableVecDiff<EventRaised>),
-    Subclasses(Vec<String>),
-    Parameters(DiffableVecDiff<Parameter>),
-    VariantParameterGroups(DiffableVecDiff<ParameterGroup>),
-    VariantParameterDescription(String),
-    VariadicParameter(Option<SingleDiff<VariadicParameter>>),
-    Format(SingleDiff<MethodFormat>),
-    ReturnValues(Vec<SingleDiff<ReturnParameter>>),
+diff_enum! {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum MethodDiff / MethodDiffRef {
+        // common fields
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        Description(TextChange<Interned>),
+        // basic member fields
+        Lists(Change<Vec<String>>),
+        Examples(Change<Vec<String>>),
+        Images(ImagesChange),
+        // method fields
+        Visibility(VisibilityChange),
+        Raises(DiffableVecDiff<EventRaised>),
+        RaisedEvents(Vec<RaisedEvent>),
+        Subclasses(Change<Vec<String>>),
+        Parameters(DiffableVecDiff<Parameter>),
+        VariantParameterGroups(DiffableVecDiff<ParameterGroup>),
+        VariantParameterDescription(TextChange<String>),
+        VariadicParameter(Option<SingleDiff<VariadicParameter>>),
+        Format(SingleDiff<MethodFormat>),
+        ReturnValues(Vec<SingleDiff<ReturnParameter>>),
+    }
 }
 
 impl StructDiff for Method {
     type Diff = MethodDiff;
-    type DiffRef<'target> = MethodDiff;
+    type DiffRef<'target> = MethodDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
+            let common_diff = self.common.diff_ref(&updated.common);
 
             for d in common_diff {
                 let d = match d {
-                    BasicMemberDiff::Name(name) => Self::Diff::Name(name),
-                    BasicMemberDiff::Order(order) => Self::Diff::Order(order),
-                    BasicMemberDiff::Description(desc) => Self::Diff::Description(desc),
-                    BasicMemberDiff::Lists(notes) => Self::Diff::Lists(notes),
-                    BasicMemberDiff::Examples(examples) => Self::Diff::Examples(examples),
-                    BasicMemberDiff::Images(images) => Self::Diff::Images(images),
+                    BasicMemberDiffRef::Name(name) => MethodDiffRef::Name(name),
+                    BasicMemberDiffRef::Order(order) => MethodDiffRef::Order(order),
+                    BasicMemberDiffRef::Description(desc) => MethodDiffRef::Description(desc),
+                    BasicMemberDiffRef::Lists(notes) => MethodDiffRef::Lists(notes),
+                    BasicMemberDiffRef::Examples(examples) => MethodDiffRef::Examples(examples),
+                    BasicMemberDiffRef::Images(images) => MethodDiffRef::Images(images),
                 };
                 res.push(d);
             }
         }
 
-        if self.visibility != updated.visibility {
-            res.push(Self::Diff::Visibility(updated.visibility.clone()));
+        if let Some(change) = VisibilityChange::new(&self.visibility, &updated.visibility) {
+            res.push(MethodDiffRef::Visibility(change));
         }
 
         if self.raises != updated.raises {
-            let diff = self.raises.diff(&updated.raises);
+            let diff = in_path("raises", || self.raises.diff_ref(&updated.raises));
 
             if !diff.is_empty() {
-                res.push(Self::Diff::Raises(diff));
+                res.push(MethodDiffRef::Raises(diff));
             }
         }
 
         if self.subclasses != updated.subclasses {
-            res.push(Self::Diff::Subclasses(updated.subclasses.clone()));
+            res.push(MethodDiffRef::Subclasses(Change::borrowed(
+                &self.subclasses,
+                &updated.subclasses,
+            )));
         }
 
         if self.parameters != updated.parameters {
-            let diff = self.parameters.diff(&updated.parameters);
+            let diff = in_path("parameters", || {
+                self.parameters.diff_ref(&updated.parameters)
+            });
 
             if !diff.is_empty() {
-                res.push(Self::Diff::Parameters(diff));
+                res.push(MethodDiffRef::Parameters(diff));
             }
         }
 
         if self.variant_parameter_groups != updated.variant_parameter_groups {
-            let diff = self
-                .variant_parameter_groups
-                .diff(&updated.variant_parameter_groups);
+            let diff = in_path("variant_parameter_groups", || {
+                self.variant_parameter_groups
+                    .diff_ref(&updated.variant_parameter_groups)
+            });
 
             if !diff.is_empty() {
-                res.push(Self::Diff::VariantParameterGroups(diff));
+                res.push(MethodDiffRef::VariantParameterGroups(diff));
             }
         }
 
-        if self.variant_parameter_description != updated.variant_parameter_description
-            && crate::CLI.with_borrow(|c| c.descriptions || c.full)
+        if FieldCategory::Descriptions.is_enabled()
+            && text_changed(
+                &self.variant_parameter_description,
+                &updated.variant_parameter_description,
+            )
         {
-            res.push(Self::Diff::VariantParameterDescription(
-                updated.variant_parameter_description.clone(),
+            res.push(MethodDiffRef::VariantParameterDescription(
+                TextChange::borrowed(
+                    &self.variant_parameter_description,
+                    &updated.variant_parameter_description,
+                ),
             ));
         }
 
         if self.variadic_parameter != updated.variadic_parameter {
             match (&self.variadic_parameter, &updated.variadic_parameter) {
                 (Some(v), Some(u_v)) => {
-                    let diff = v.diff(u_v);
+                    let diff = v.diff_ref(u_v);
 
                     if !diff.is_empty() {
-                        res.push(Self::Diff::VariadicParameter(Some(diff)));
+                        res.push(MethodDiffRef::VariadicParameter(Some(diff)));
                     }
                 }
-                (None, Some(u_v)) => res.push(Self::Diff::VariadicParameter(Some(
-                    VariadicParameter::default().diff(u_v),
+                (None, Some(u_v)) => res.push(MethodDiffRef::VariadicParameter(Some(
+                    empty::<VariadicParameter>().diff_ref(u_v),
                 ))),
                 (_, None) => {
-                    res.push(Self::Diff::VariadicParameter(None));
+                    res.push(MethodDiffRef::VariadicParameter(None));
                 }
             }
         }
 
         if self.format != updated.format {
-            let diff = self.format.diff(&updated.format);
+            let diff = self.format.diff_ref(&updated.format);
 
             if !diff.is_empty() {
-                res.push(Self::Diff::Format(diff));
+                res.push(MethodDiffRef::Format(diff));
             }
         }
 
         if self.return_values != updated.return_values {
-            res.push(Self::Diff::ReturnValues(vec_diff(
+            // the order always matches the position, the description and type tell return values apart
+            let diff = vec_diff_matched(
                 &self.return_values,
                 &updated.return_values,
-            )));
+                &[
+                    &|a, b| !a.description.is_empty() && a.description == b.description,
+                    &|a, b| a.type_ == b.type_,
+                ],
+            );
+
+            if diff.iter().any(|d| !d.is_empty()) {
+                res.push(MethodDiffRef::ReturnValues(diff));
+            }
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
@@ -1657,50 +2806,57 @@ pub struct VariadicParameter {
     pub description: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub enum VariadicParameterDiff {
-    Type(Option<TypeDiff>),
-    Description(String),
+diff_enum! {
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    pub enum VariadicParameterDiff / VariadicParameterDiffRef {
+        Type(Option<TypeDiff>),
+        Description(TextChange<String>),
+    }
 }
 
 impl StructDiff for VariadicParameter {
     type Diff = VariadicParameterDiff;
-    type DiffRef<'target> = VariadicParameterDiff;
+    type DiffRef<'target> = VariadicParameterDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
-        if self.type_ != updated.type_ {
+        if self.type_ != updated.type_ && FieldCategory::Types.is_enabled() {
             match (&self.type_, &updated.type_) {
                 (Some(t), Some(u_t)) => {
-                    let diff = t.diff(u_t);
+                    let mut diff = t.diff_ref(u_t);
 
                     if !diff.is_empty() && !diff[0].skip() {
-                        res.push(Self::Diff::Type(Some(diff[0].clone())));
+                        res.push(VariadicParameterDiffRef::Type(Some(diff.swap_remove(0))));
                     }
                 }
                 (None, Some(u_t)) => {
-                    res.push(Self::Diff::Type(Some(Type::default().diff(u_t)[0].clone())));
+                    res.push(VariadicParameterDiffRef::Type(Some(
+                        empty::<Type>().diff_ref(u_t).swap_remove(0),
+                    )));
                 }
                 (_, None) => {
-                    res.push(Self::Diff::Type(None));
+                    res.push(VariadicParameterDiffRef::Type(None));
                 }
             }
         }
 
-        if self.description != updated.description
-            && crate::CLI.with_borrow(|c| c.descriptions || c.full)
+        if FieldCategory::Descriptions.is_enabled()
+            && text_changed(&self.description, &updated.description)
         {
-            res.push(Self::Diff::Description(updated.description.clone()));
+            res.push(VariadicParameterDiffRef::Description(TextChange::borrowed(
+                &self.description,
+                &updated.description,
+            )));
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
@@ -1714,46 +2870,54 @@ pub struct MethodFormat {
     pub table_optional: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub enum MethodFormatDiff {
-    TakesTable(bool),
-    TableOptional(Option<bool>),
+diff_enum! {
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    pub enum MethodFormatDiff / MethodFormatDiffRef {
+        TakesTable(Change<bool>),
+        TableOptional(Change<Option<bool>>),
+    }
 }
 
 impl StructDiff for MethodFormat {
     type Diff = MethodFormatDiff;
-    type DiffRef<'target> = MethodFormatDiff;
+    type DiffRef<'target> = MethodFormatDiffRef<'target>;
 
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.takes_table != updated.takes_table {
-            res.push(Self::Diff::TakesTable(updated.takes_table));
+            res.push(MethodFormatDiffRef::TakesTable(Change::borrowed(
+                &self.takes_table,
+                &updated.takes_table,
+            )));
         }
 
         if self.table_optional != updated.table_optional {
-            res.push(Self::Diff::TableOptional(updated.table_optional));
+            res.push(MethodFormatDiffRef::TableOptional(Change::borrowed(
+                &self.table_optional,
+                &updated.table_optional,
+            )));
         }
 
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
-        unimplemented!()
-    }
-
     fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Clone, Default, Hash)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Default, Hash)]
 pub struct Attribute {
     #[serde(flatten)]
     common: BasicMember,
 
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub visibility: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub visibility: BTreeSet<Visibility>,
 
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub raises: Vec<EventRaised>,
@@ -1786,195 +2950,336 @@ impl Deref for Attribute {
     }
 }
 
+impl Attribute {
+    /// Whether the attribute can be written, in either api version.
+    #[must_use]
+    pub const fn writable(&self) -> bool {
+        matches!(self.write, Some(true)) || self.write_type.is_some()
+    }
+
+    /// Whether the attribute can be read and written, in either api version.
+    #[must_use]
+    pub const fn access(&self) -> Access {
+        Access::new(
+            matches!(self.read, Some(true)) || self.read_type.is_some(),
+            self.writable(),
+        )
+    }
+}
+
+/// Whether an attribute can be read and written.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Access {
+    None,
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl Access {
+    #[must_use]
+    pub const fn new(read: bool, write: bool) -> Self {
+        match (read, write) {
+            (false, false) => Self::None,
+            (true, false) => Self::Read,
+            (false, true) => Self::Write,
+            (true, true) => Self::ReadWrite,
+        }
+    }
+
+    #[must_use]
+    pub const fn read(self) -> bool {
+        matches!(self, Self::Read | Self::ReadWrite)
+    }
+
+    #[must_use]
+    pub const fn write(self) -> bool {
+        matches!(self, Self::Write | Self::ReadWrite)
+    }
+}
+
+/// An attribute that can be read or written in other ways, e.g. turning read-only.
+///
+/// Reported next to the changes of the read and write flags or types it combines,
+/// both modes are always serialized.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct AccessChange {
+    pub from: Access,
+    pub to: Access,
+}
+
+impl AccessChange {
+    /// Whether the attribute lost read or write access, breaking mods that use it that way.
+    #[must_use]
+    pub const fn is_breaking(&self) -> bool {
+        (self.from.read() && !self.to.read()) || (self.from.write() && !self.to.write())
+    }
+}
+
+impl Invert for AccessChange {
+    fn invert(self) -> Self {
+        Self {
+            from: self.to,
+            to: self.from,
+        }
+    }
+}
+
+impl Borrowed for AccessChange {
+    type Ref<'a> = Self;
+
+    fn from_ref(diff: Self::Ref<'_>) -> Self {
+        diff
+    }
+}
+
 impl Named for Attribute {
     fn name(&self) -> &str {
         &self.name
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum AttributeDiff {
-    // common fields
-    Name(String),
-    Order(i16),
-    Description(String),
-    // basic member fields
-    Lists(Vec<String>),
-    Examples(Vec<String>),
-    Images(Vec<Image>),
-    // attribute fields
-    Visibility(Vec<String>),
-    Raises(DiffableVecDiff<EventRaised>),
-    Subclasses(Vec<String>),
-    Optional(bool),
-    // v5 fields
-    Type(TypeDiff),
-    Read(bool),
-    Write(bool),
-    // v6 fields
-    ReadType(Option<TypeDiff>),
-    WriteType(Option<TypeDiff>),
+diff_enum! {
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum AttributeDiff / AttributeDiffRef {
+        // common fields
+        Name(Change<Interned>),
+        Order(Change<Order>),
+        Description(TextChange<Interned>),
+        // basic member fields
+        Lists(Change<Vec<String>>),
+        Examples(Change<Vec<String>>),
+        Images(ImagesChange),
+        // attribute fields
+        Visibility(VisibilityChange),
+        Raises(DiffableVecDiff<EventRaised>),
+        RaisedEvents(Vec<RaisedEvent>),
+        Subclasses(Change<Vec<String>>),
+        Optional(Change<bool>),
+        Optionality(Optionality),
+        // v5 fields
+        Type(TypeDiff),
+        Read(Change<bool>),
+        Write(Change<bool>),
+        // v6 fields
+        ReadType(Option<TypeDiff>),
+        WriteType(Option<TypeDiff>),
+        Writability(Writability),
+        AccessChanged(AccessChange),
+    }
+}
+
+/// Diff of the type an attribute can be read or written as, `None` if it has no such access.
+///
+/// Gaining or losing access is diffed against an empty type, so it reads the same in both directions.
+fn access_type_diff<'a>(
+    source: Option<&'a Type>,
+    target: Option<&'a Type>,
+) -> Option<TypeDiffRef<'a>> {
+    match (source, target) {
+        (None, None) => None,
+        (Some(_), Some(_)) if !FieldCategory::Types.is_enabled() => None,
+        (source, target) => {
+            let mut diff = source
+                .unwrap_or_else(empty)
+                .diff_ref(target.unwrap_or_else(empty));
+
+            (!diff.is_empty() && !diff[0].skip()).then(|| diff.swap_remove(0))
+        }
+    }
 }
 
 impl StructDiff for Attribute {
     type Diff = AttributeDiff;
-    type DiffRef<'target> = AttributeDiff;
+    type DiffRef<'target> = AttributeDiffRef<'target>;
 
-    #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
     fn diff(&self, updated: &Self) -> Vec<Self::Diff> {
+        self.diff_ref(updated).into_iter().map(Into::into).collect()
+    }
+
+    #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
+    fn diff_ref<'target>(&'target self, updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
         let mut res = Vec::new();
 
         if self.common != updated.common {
-            let common_diff = self.common.diff(&updated.common);
+            let common_diff = self.common.diff_ref(&updated.common);
 
             for d in common_diff {
                 let d = match d {
-                    BasicMemberDiff::Name(name) => Self::Diff::Name(name),
-                    BasicMemberDiff::Order(order) => Self::Diff::Order(order),
-                    BasicMemberDiff::Description(desc) => Self::Diff::Description(desc),
-                    BasicMemberDiff::Lists(notes) => Self::Diff::Lists(notes),
-                    BasicMemberDiff::Examples(examples) => Self::Diff::Examples(examples),
-                    BasicMemberDiff::Images(images) => Self::Diff::Images(images),
+                    BasicMemberDiffRef::Name(name) => AttributeDiffRef::Name(name),
+                    BasicMemberDiffRef::Order(order) => AttributeDiffRef::Order(order),
+                    BasicMemberDiffRef::Description(desc) => AttributeDiffRef::Description(desc),
+                    BasicMemberDiffRef::Lists(notes) => AttributeDiffRef::Lists(notes),
+                    BasicMemberDiffRef::Examples(examples) => AttributeDiffRef::Examples(examples),
+                    BasicMemberDiffRef::Images(images) => AttributeDiffRef::Images(images),
                 };
                 res.push(d);
             }
         }
 
-        if self.visibility != updated.visibility {
-            res.push(Self::Diff::Visibility(updated.visibility.clone()));
+        if let Some(change) = VisibilityChange::new(&self.visibility, &updated.visibility) {
+            res.push(AttributeDiffRef::Visibility(change));
         }
 
         if self.raises != updated.raises {
-            let orig: DiffableVec<EventRaised> = self.raises.clone().into();
-            let updated: DiffableVec<EventRaised> = updated.raises.clone().into();
-            let diff = orig.diff(&updated);
+            let diff = in_path("raises", || named_diff(&self.raises, &updated.raises));
 
             if !diff.is_empty() {
-                res.push(Self::Diff::Raises(diff));
+                res.push(AttributeDiffRef::Raises(diff));
             }
         }
 
         if self.subclasses != updated.subclasses {
-            res.push(Self::Diff::Subclasses(updated.subclasses.clone()));
+            res.push(AttributeDiffRef::Subclasses(Change::borrowed(
+                &self.subclasses,
+                &updated.subclasses,
+            )));
         }
 
         if self.optional != updated.optional {
-            res.push(Self::Diff::Optional(updated.optional));
+            if DiffOptions::current().directional {
+                res.push(AttributeDiffRef::Optionality(Optionality::to(
+                    updated.optional,
+                )));
+            } else {
+                res.push(AttributeDiffRef::Optional(Change::borrowed(
+                    &self.optional,
+                    &updated.optional,
+                )));
+            }
         }
 
-        let src_ver = crate::SRC_INF.with_borrow(|s| s.api_version);
-        let trgt_ver = crate::TRGT_INF.with_borrow(|t| t.api_version);
+        let (src_ver, trgt_ver) = API_VERSIONS.get();
 
         match (src_ver, trgt_ver) {
             (5, 5) => {
-                if self.type_ != updated.type_ {
+                if self.type_ != updated.type_ && FieldCategory::Types.is_enabled() {
                     if let (Some(src_t), Some(trgt_t)) = (&self.type_, &updated.type_) {
-                        let diff = src_t.diff(trgt_t);
+                        let mut diff = src_t.diff_ref(trgt_t);
 
                         if !diff.is_empty() && !diff[0].skip() {
-                            res.push(Self::Diff::Type(diff[0].clone()));
+                            res.push(AttributeDiffRef::Type(diff.swap_remove(0)));
                         }
                     }
                 }
 
                 if self.read != updated.read {
                     if let Some(trgt_read) = updated.read {
-                        res.push(Self::Diff::Read(trgt_read));
+                        res.push(AttributeDiffRef::Read(Change::owned(
+                            self.read.unwrap_or_default(),
+                            trgt_read,
+                        )));
                     }
                 }
 
-                if self.write != updated.write {
+                // reported by its direction instead
+                if self.write != updated.write && !DiffOptions::current().directional {
                     if let Some(trgt_write) = updated.write {
-                        res.push(Self::Diff::Write(trgt_write));
+                        res.push(AttributeDiffRef::Write(Change::owned(
+                            self.write.unwrap_or_default(),
+                            trgt_write,
+                        )));
                     }
                 }
             }
             (5, 6) => {
-                let src_r = self.read.unwrap_or_default();
-                let src_w = self.write.unwrap_or_default();
-                let src_t = self.type_.clone().unwrap_or_default();
-
-                match (src_r, &updated.read_type) {
-                    (true, None) => {
-                        res.push(Self::Diff::ReadType(None));
-                    }
-                    (false, Some(trgt_rt)) => {
-                        let diff = Type::default().diff(trgt_rt);
-
-                        if !diff.is_empty() && !diff[0].skip() {
-                            res.push(Self::Diff::ReadType(Some(diff[0].clone())));
-                        }
-                    }
-                    (true, Some(trgt_rt)) => {
-                        let diff = src_t.diff(trgt_rt);
+                let src_type = self.type_.as_ref().unwrap_or_else(empty);
+                let src_read = self.read.unwrap_or_default().then_some(src_type);
+                let src_write = self.write.unwrap_or_default().then_some(src_type);
 
-                        if !diff.is_empty() && !diff[0].skip() {
-                            res.push(Self::Diff::ReadType(Some(diff[0].clone())));
-                        }
-                    }
-                    _ => {}
+                if let Some(diff) = access_type_diff(src_read, updated.read_type.as_ref()) {
+                    res.push(AttributeDiffRef::ReadType(Some(diff)));
                 }
+                if let Some(diff) = access_type_diff(src_write, updated.write_type.as_ref()) {
+                    res.push(AttributeDiffRef::WriteType(Some(diff)));
+                }
+            }
+            (6, 5) => {
+                let trgt_type = updated.type_.as_ref().unwrap_or_else(empty);
+                let trgt_read = updated.read.unwrap_or_default().then_some(trgt_type);
+                let trgt_write = updated.write.unwrap_or_default().then_some(trgt_type);
 
-                match (src_w, &updated.write_type) {
-                    (true, None) => {
-                        res.push(Self::Diff::WriteType(None));
-                    }
-                    (false, Some(trgt_wt)) => {
-                        let diff = Type::default().diff(trgt_wt);
-
-                        if !diff.is_empty() && !diff[0].skip() {
-                            res.push(Self::Diff::WriteType(Some(diff[0].clone())));
-                        }
-                    }
-                    (true, Some(trgt_wt)) => {
-                        let diff = src_t.diff(trgt_wt);
-
-                        if !diff.is_empty() && !diff[0].skip() {
-                            res.push(Self::Diff::WriteType(Some(diff[0].clone())));
-                        }
-                    }
-                    _ => {}
+                if let Some(diff) = access_type_diff(self.read_type.as_ref(), trgt_read) {
+                    res.push(AttributeDiffRef::ReadType(Some(diff)));
+                }
+                if let Some(diff) = access_type_diff(self.write_type.as_ref(), trgt_write) {
+                    res.push(AttributeDiffRef::WriteType(Some(diff)));
                 }
             }
             (6, 6) => {
-                if self.read_type != updated.read_type {
-                    if let Some(trgt_rt) = &updated.read_type {
-                        let diff = self.read_type.clone().unwrap_or_default().diff(trgt_rt);
-
-                        if !diff.is_empty() && !diff[0].skip() {
-                            res.push(Self::Diff::ReadType(Some(diff[0].clone())));
-                        }
-                    } else {
-                        res.push(Self::Diff::ReadType(None));
-                    };
+                if let Some(diff) =
+                    access_type_diff(self.read_type.as_ref(), updated.read_type.as_ref())
+                {
+                    res.push(AttributeDiffRef::ReadType(Some(diff)));
                 }
-
-                if self.write_type != updated.write_type {
-                    if let Some(trgt_wt) = &updated.write_type {
-                        let diff = self.write_type.clone().unwrap_or_default().diff(trgt_wt);
-
-                        if !diff.is_empty() && !diff[0].skip() {
-                            res.push(Self::Diff::WriteType(Some(diff[0].clone())));
-                        }
-                    } else {
-                        res.push(Self::Diff::WriteType(None));
-                    };
+                if let Some(diff) =
+                    access_type_diff(self.write_type.as_ref(), updated.write_type.as_ref())
+                {
+                    res.push(AttributeDiffRef::WriteType(Some(diff)));
                 }
             }
             (_, _) => {
-                eprintln!("unsupported src / target versions: {src_ver} / {trgt_ver}");
+                super::warn(format!(
+                    "unsupported src / target versions: {src_ver} / {trgt_ver}"
+                ));
             }
         }
 
+        // added and removed attributes are diffed against an empty one without any access
+        let (from, to) = (self.access(), updated.access());
+        if from != to && from != Access::None && to != Access::None {
+            res.push(AttributeDiffRef::AccessChanged(AccessChange { from, to }));
+        }
+
+        if DiffOptions::current().directional && self.writable() != updated.writable() {
+            res.push(AttributeDiffRef::Writability(Writability::to(
+                updated.writable(),
+            )));
+        }
+
         res
     }
 
-    fn diff_ref<'target>(&'target self, _updated: &'target Self) -> Vec<Self::DiffRef<'target>> {
+    fn apply_single(&mut self, _diff: Self::Diff) {
         unimplemented!()
     }
+}
 
-    fn apply_single(&mut self, _diff: Self::Diff) {
-        unimplemented!()
+impl_invert! {
+    CommonDiff { Name, Order, Description }
+    BasicMemberDiff { Name, Order, Description, Lists, Examples, Images }
+    ClassDiff {
+        Name, Order, Description, Lists, Examples, Images, Visibility, Parent, Abstract, Methods,
+        Attributes, Operators, RelatedChanges,
+    }
+    OperatorDiff { KindChanged, Method, Attribute, Unknown }
+    EventDiff {
+        Name, Order, Description, Lists, Examples, Images, Data, Filter, RelatedChanges,
+    }
+    ConceptDiff { Name, Order, Description, Lists, Examples, Images, Type }
+    DefineDiff { Name, Order, Description, Lists, Examples, Images, Values, Subkeys }
+    EventRaisedDiff { Name, Order, Description, Timeframe, Optional }
+    TypeDiff { Simple, Complex }
+    ComplexTypeDiff {
+        ComplexType, Value, Key, Options, FullFormat, Description, Attributes, FunctionParameters,
+        TableTupleParameters, VariantParameterGroups, VariantParameterDescription, Values, Literal,
+        Raw,
+    }
+    ParameterDiff { Name, Order, Description, Type, Optional, Optionality }
+    ReturnParameterDiff { Order, Description, Type, Optional, Optionality }
+    ParameterGroupDiff { Name, Order, Description, Parameters }
+    MethodDiff {
+        Name, Order, Description, Lists, Examples, Images, Visibility, Raises, RaisedEvents,
+        Subclasses, Parameters, VariantParameterGroups, VariantParameterDescription, VariadicParameter, Format,
+        ReturnValues,
+    }
+    VariadicParameterDiff { Type, Description }
+    MethodFormatDiff { TakesTable, TableOptional }
+    AttributeDiff {
+        Name, Order, Description, Lists, Examples, Images, Visibility, Raises, RaisedEvents,
+        Subclasses, Optional, Optionality, Type, Read, Write, ReadType, WriteType, Writability,
+        AccessChanged,
     }
 }
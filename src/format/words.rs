@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+
+use super::Invert;
+
+/// Largest product of the numbers of differing words of both texts that is diffed word by word,
+/// bigger rewrites are only reported as a whole.
+const MAX_CELLS: usize = 1 << 22;
+
+/// A run of words of a text change, adjacent words with the same status are merged.
+///
+/// Whitespace is kept, joining the old or the new runs gives the old or the new text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WordChange {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+impl Invert for WordChange {
+    fn invert(self) -> Self {
+        match self {
+            Self::Same(s) => Self::Same(s),
+            Self::Removed(s) => Self::Added(s),
+            Self::Added(s) => Self::Removed(s),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Same,
+    Removed,
+    Added,
+}
+
+/// Splits a text into runs of whitespace and runs of other characters.
+fn tokens(text: &str) -> Vec<&str> {
+    let mut res = Vec::new();
+    let mut start = 0;
+    let mut space = None;
+
+    for (i, c) in text.char_indices() {
+        if space != Some(c.is_whitespace()) {
+            if i > start {
+                res.push(&text[start..i]);
+            }
+
+            start = i;
+            space = Some(c.is_whitespace());
+        }
+    }
+
+    if start < text.len() {
+        res.push(&text[start..]);
+    }
+
+    res
+}
+
+/// Word level diff of two texts, `None` if they differ in too many words to diff them that way.
+#[must_use]
+pub fn diff(old: &str, new: &str) -> Option<Vec<WordChange>> {
+    let (old, new) = (tokens(old), tokens(new));
+
+    let prefix = old.iter().zip(&new).take_while(|(o, n)| o == n).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(o, n)| o == n)
+        .count();
+    let (old_mid, new_mid) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_CELLS {
+        return None;
+    }
+
+    // lengths of the longest common subsequences of the remaining words,
+    // lcs[i * width + j] is the one of old_mid[i..] and new_mid[j..]
+    let width = new_mid.len() + 1;
+    let mut lcs = vec![0usize; (old_mid.len() + 1) * width];
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            lcs[i * width + j] = if old_mid[i] == new_mid[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut runs: Vec<(Status, String)> = Vec::new();
+    let mut push = |status, token: &str| match runs.last_mut() {
+        Some((last, run)) if *last == status => run.push_str(token),
+        _ => runs.push((status, token.to_owned())),
+    };
+
+    for token in &old[..prefix] {
+        push(Status::Same, token);
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old_mid.len() || j < new_mid.len() {
+        if i < old_mid.len() && j < new_mid.len() && old_mid[i] == new_mid[j] {
+            push(Status::Same, old_mid[i]);
+            i += 1;
+            j += 1;
+        } else if i < old_mid.len()
+            && (j == new_mid.len() || lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            push(Status::Removed, old_mid[i]);
+            i += 1;
+        } else {
+            push(Status::Added, new_mid[j]);
+            j += 1;
+        }
+    }
+
+    for token in &old[old.len() - suffix..] {
+        push(Status::Same, token);
+    }
+
+    Some(
+        runs.into_iter()
+            .map(|(status, run)| match status {
+                Status::Same => WordChange::Same(run),
+                Status::Removed => WordChange::Removed(run),
+                Status::Added => WordChange::Added(run),
+            })
+            .collect(),
+    )
+}
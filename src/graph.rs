@@ -0,0 +1,182 @@
+//! Inheritance graphs of the classes of runtime docs and the prototypes and types of prototype docs.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::{
+    format::{prototype::PrototypeDoc, runtime::RuntimeDoc, ItemStatus},
+    render::tree,
+    Docs,
+};
+
+/// Output format of a [`Graph`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    #[default]
+    Dot,
+    /// Mermaid flowchart, e.g. for markdown files on GitHub
+    Mermaid,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Node {
+    /// Section of the docs the item belongs to, e.g. `classes`
+    section: &'static str,
+    parent: Option<String>,
+    /// Change of the item between two docs, `None` for unchanged items and graphs of a single doc
+    status: Option<ItemStatus>,
+}
+
+/// Inheritance hierarchy of a doc, or of two docs with the changes of the items between them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Graph {
+    nodes: BTreeMap<String, Node>,
+}
+
+impl Graph {
+    /// Reads the hierarchy of a raw doc of any stage.
+    pub fn parse(raw: &[u8], what: &str) -> crate::Result<Self> {
+        let mut res = Self::default();
+        let mut add = |section, name: &str, parent: Option<&str>| {
+            res.nodes.insert(
+                name.to_owned(),
+                Node {
+                    section,
+                    parent: parent.filter(|p| !p.is_empty()).map(str::to_owned),
+                    status: None,
+                },
+            );
+        };
+
+        match Docs::detect(raw)? {
+            Docs::Runtime => {
+                for (name, class) in crate::parse::<RuntimeDoc>(raw, what)?.classes.iter() {
+                    add("classes", name, class.parent.as_deref());
+                }
+            }
+            Docs::Prototype => {
+                let doc = crate::parse::<PrototypeDoc>(raw, what)?;
+
+                for (name, prototype) in doc.prototypes.iter() {
+                    add("prototypes", name, Some(&prototype.parent));
+                }
+                for (name, ty) in doc.types.iter() {
+                    add("types", name, Some(&ty.parent));
+                }
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Combines the hierarchies of two docs, marking the items that changed in their `diff`.
+    ///
+    /// Items that exist in both docs keep the parent they have in the `target` doc.
+    #[must_use]
+    pub fn delta(source: Self, target: Self, diff: &tree::Node) -> Self {
+        let mut nodes = source.nodes;
+        nodes.extend(target.nodes);
+
+        for (name, node) in &mut nodes {
+            node.status = diff.get(&[node.section, name]).map(|n| n.status);
+        }
+
+        Self { nodes }
+    }
+
+    /// Keeps only the changed items and their ancestors.
+    #[must_use]
+    pub fn changed_only(mut self) -> Self {
+        let mut keep = std::collections::BTreeSet::new();
+
+        for (name, node) in &self.nodes {
+            if node.status.is_none() {
+                continue;
+            }
+
+            let mut current = Some(name);
+            while let Some(name) = current.filter(|n| keep.insert((*n).clone())) {
+                current = self.nodes.get(name).and_then(|n| n.parent.as_ref());
+            }
+        }
+
+        self.nodes.retain(|name, _| keep.contains(name));
+        self
+    }
+
+    #[must_use]
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.dot(),
+            GraphFormat::Mermaid => self.mermaid(),
+        }
+    }
+
+    /// Graphviz DOT with an edge from each item to its parent, changed items are colored.
+    #[must_use]
+    pub fn dot(&self) -> String {
+        let mut res =
+            String::from("digraph inheritance {\n    rankdir=BT;\n    node [shape=box];\n");
+
+        for (name, node) in &self.nodes {
+            let color = match node.status {
+                Some(ItemStatus::Added) => " [style=filled, fillcolor=palegreen]",
+                Some(ItemStatus::Removed) => " [style=filled, fillcolor=lightpink]",
+                Some(ItemStatus::Changed) => " [style=filled, fillcolor=lightgoldenrod]",
+                None => "",
+            };
+            let _ = writeln!(res, "    {name:?}{color};");
+        }
+
+        for (name, parent) in self.edges() {
+            let _ = writeln!(res, "    {name:?} -> {parent:?};");
+        }
+
+        res.push_str("}\n");
+        res
+    }
+
+    /// Mermaid flowchart with an edge from each item to its parent, changed items are colored.
+    #[must_use]
+    pub fn mermaid(&self) -> String {
+        let mut res = String::from("flowchart BT\n");
+
+        for name in self.nodes.keys() {
+            let _ = writeln!(res, "    {name}");
+        }
+
+        for (name, parent) in self.edges() {
+            let _ = writeln!(res, "    {name} --> {parent}");
+        }
+
+        for (status, class, style) in [
+            (ItemStatus::Added, "added", "fill:#98fb98"),
+            (ItemStatus::Removed, "removed", "fill:#ffb6c1"),
+            (ItemStatus::Changed, "changed", "fill:#eedd82"),
+        ] {
+            let names = self
+                .nodes
+                .iter()
+                .filter(|(_, n)| n.status == Some(status))
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>();
+
+            if !names.is_empty() {
+                let _ = writeln!(res, "    classDef {class} {style}");
+                let _ = writeln!(res, "    class {} {class}", names.join(","));
+            }
+        }
+
+        res
+    }
+
+    /// Edges from the items to their parents, parents that aren't part of the graph are left out.
+    fn edges(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.nodes.iter().filter_map(|(name, node)| {
+            let parent = node.parent.as_deref()?;
+            self.nodes
+                .contains_key(parent)
+                .then_some((name.as_str(), parent))
+        })
+    }
+}
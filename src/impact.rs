@@ -0,0 +1,465 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    defines::{self, DefinesDiff},
+    format::{
+        prototype::{PropertyDiff, PrototypeDiff, PrototypeDoc, PrototypeDocDiff},
+        runtime::{
+            AttributeDiff, ClassDiff, Define, EventDiff, MethodDiff, RuntimeDoc, RuntimeDocDiff,
+        },
+    },
+};
+
+/// How an API used by a mod changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Impact {
+    Removed,
+    Changed,
+}
+
+/// A changed API, identified by its path in the docs, e.g. `classes/LuaEntity/methods/teleport`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Api {
+    pub path: String,
+    pub impact: Impact,
+}
+
+impl Api {
+    const fn new(path: String, removed: bool) -> Self {
+        Self {
+            path,
+            impact: if removed {
+                Impact::Removed
+            } else {
+                Impact::Changed
+            },
+        }
+    }
+}
+
+/// Changed APIs, indexed by the identifiers they are used with in Lua code.
+#[derive(Debug, Default)]
+pub struct ApiIndex {
+    /// `LuaEntity`
+    classes: HashMap<String, Vec<Api>>,
+    /// `.teleport` / `:teleport`, the class of the receiver is unknown
+    members: HashMap<String, Vec<Api>>,
+    /// `log(`
+    functions: HashMap<String, Vec<Api>>,
+    /// `defines.events.on_tick`
+    defines: HashMap<String, Vec<Api>>,
+    /// `type = "car"`
+    typenames: HashMap<String, Vec<Api>>,
+    /// `flags =` in data stage files
+    properties: HashMap<String, Vec<Api>>,
+}
+
+impl ApiIndex {
+    /// Indexes the removed runtime APIs and the ones whose signature or type changed.
+    ///
+    /// Documentation only changes and added APIs don't affect mods, they aren't indexed.
+    pub fn add_runtime(&mut self, source: &RuntimeDoc, target: &RuntimeDoc, diff: &RuntimeDocDiff) {
+        for (class, class_diff) in &diff.classes {
+            let Some(source_class) = source.classes.get(class) else {
+                continue;
+            };
+            let Some(target_class) = target.classes.get(class) else {
+                push(
+                    &mut self.classes,
+                    class,
+                    Api::new(format!("classes/{class}"), true),
+                );
+                continue;
+            };
+
+            for d in class_diff {
+                let (section, members): (_, Vec<(&String, bool)>) = match d {
+                    ClassDiff::Methods(m) => (
+                        "methods",
+                        m.iter()
+                            .filter(|(k, _)| source_class.methods.contains_key(*k))
+                            .filter_map(|(k, d)| {
+                                let removed = !target_class.methods.contains_key(k);
+                                (removed || d.iter().any(is_signature_change))
+                                    .then_some((k, removed))
+                            })
+                            .collect(),
+                    ),
+                    ClassDiff::Attributes(a) => (
+                        "attributes",
+                        a.iter()
+                            .filter(|(k, _)| source_class.attributes.contains_key(*k))
+                            .filter_map(|(k, d)| {
+                                let removed = !target_class.attributes.contains_key(k);
+                                (removed || d.iter().any(is_type_change)).then_some((k, removed))
+                            })
+                            .collect(),
+                    ),
+                    _ => continue,
+                };
+
+                for (member, removed) in members {
+                    push(
+                        &mut self.members,
+                        member,
+                        Api::new(format!("classes/{class}/{section}/{member}"), removed),
+                    );
+                }
+            }
+        }
+
+        for (event, event_diff) in &diff.events {
+            if !source.events.contains_key(event) {
+                continue;
+            }
+
+            let removed = !target.events.contains_key(event);
+            if removed
+                || event_diff
+                    .iter()
+                    .any(|d| matches!(d, EventDiff::Data(_) | EventDiff::Filter(_)))
+            {
+                push(
+                    &mut self.defines,
+                    &format!("defines.events.{event}"),
+                    Api::new(format!("events/{event}"), removed),
+                );
+            }
+        }
+
+        for (function, function_diff) in &diff.global_functions {
+            if !source.global_functions.contains_key(function) {
+                continue;
+            }
+
+            let removed = !target.global_functions.contains_key(function);
+            if removed || function_diff.iter().any(is_signature_change) {
+                push(
+                    &mut self.functions,
+                    function,
+                    Api::new(format!("global_functions/{function}"), removed),
+                );
+            }
+        }
+
+        self.add_defines(&source.defines, &target.defines);
+    }
+
+    pub fn add_prototype(
+        &mut self,
+        source: &PrototypeDoc,
+        target: &PrototypeDoc,
+        diff: &PrototypeDocDiff,
+    ) {
+        for (prototype, prototype_diff) in &diff.prototypes {
+            let Some(old) = source.prototypes.get(prototype) else {
+                continue;
+            };
+            let Some(new) = target.prototypes.get(prototype) else {
+                if !old.typename.is_empty() {
+                    push(
+                        &mut self.typenames,
+                        &old.typename,
+                        Api::new(format!("prototypes/{prototype}"), true),
+                    );
+                }
+                continue;
+            };
+
+            for d in prototype_diff {
+                let PrototypeDiff::Properties(properties) = d else {
+                    continue;
+                };
+
+                for (property, property_diff) in properties {
+                    // added properties can't be used yet
+                    if !old.properties.contains_key(property) {
+                        continue;
+                    }
+                    if new.properties.contains_key(property)
+                        && !property_diff.iter().any(|d| {
+                            matches!(
+                                d,
+                                PropertyDiff::Type(_)
+                                    | PropertyDiff::Optional(_)
+                                    | PropertyDiff::Optionality(_)
+                                    | PropertyDiff::AltName(_)
+                            )
+                        })
+                    {
+                        continue;
+                    }
+
+                    push(
+                        &mut self.properties,
+                        property,
+                        Api::new(
+                            format!("prototypes/{prototype}/properties/{property}"),
+                            !new.properties.contains_key(property),
+                        ),
+                    );
+                }
+            }
+        }
+
+        self.add_defines(&source.defines, &target.defines);
+    }
+
+    /// Indexes all define values of `source` that don't exist in `target`.
+    fn add_defines(&mut self, source: &HashMap<String, Define>, target: &HashMap<String, Define>) {
+        let diff = DefinesDiff::new(
+            &defines::paths(source.values()),
+            &defines::paths(target.values()),
+        );
+
+        for path in diff.removed {
+            let api = Api::new(path.replace('.', "/"), true);
+            push(&mut self.defines, &path, api);
+        }
+    }
+
+    /// Changed APIs the Lua `source` refers to, grouped by line.
+    fn lookup(&self, source: &str, data_stage: bool) -> Vec<(usize, String, Vec<Api>)> {
+        let mut res = Vec::new();
+
+        for (line_idx, line) in source.lines().enumerate() {
+            // good enough, strings containing `--` are rare
+            let code = line.split("--").next().unwrap_or_default();
+
+            for token in tokens(code) {
+                let apis = match token {
+                    Token::Ident {
+                        name,
+                        member,
+                        call,
+                        assign,
+                        chain,
+                    } => {
+                        let mut apis = Vec::new();
+
+                        if member {
+                            apis.extend(self.members.get(name).into_iter().flatten());
+                        } else {
+                            apis.extend(self.classes.get(name).into_iter().flatten());
+
+                            if call {
+                                apis.extend(self.functions.get(name).into_iter().flatten());
+                            }
+
+                            if assign && data_stage {
+                                apis.extend(self.properties.get(name).into_iter().flatten());
+                            }
+                        }
+
+                        if chain.starts_with("defines.") {
+                            apis.extend(self.defines.get(chain).into_iter().flatten());
+                        }
+
+                        (chain.to_owned(), apis)
+                    }
+                    Token::Typename(name) => (
+                        name.to_owned(),
+                        self.typenames.get(name).into_iter().flatten().collect(),
+                    ),
+                };
+
+                if !apis.1.is_empty() {
+                    res.push((line_idx + 1, apis.0, apis.1.into_iter().cloned().collect()));
+                }
+            }
+        }
+
+        res
+    }
+}
+
+/// Whether a method diff changes how the method is called or what it returns.
+const fn is_signature_change(diff: &MethodDiff) -> bool {
+    matches!(
+        diff,
+        MethodDiff::Parameters(_)
+            | MethodDiff::VariantParameterGroups(_)
+            | MethodDiff::VariadicParameter(_)
+            | MethodDiff::Format(_)
+            | MethodDiff::ReturnValues(_)
+    )
+}
+
+/// Whether an attribute diff changes its type or how it can be accessed.
+const fn is_type_change(diff: &AttributeDiff) -> bool {
+    matches!(
+        diff,
+        AttributeDiff::Type(_)
+            | AttributeDiff::ReadType(_)
+            | AttributeDiff::WriteType(_)
+            | AttributeDiff::Optional(_)
+            | AttributeDiff::Optionality(_)
+            | AttributeDiff::Read(_)
+            | AttributeDiff::Write(_)
+            | AttributeDiff::Writability(_)
+            | AttributeDiff::AccessChanged(_)
+    )
+}
+
+fn push(map: &mut HashMap<String, Vec<Api>>, key: &str, api: Api) {
+    map.entry(key.to_owned()).or_default().push(api);
+}
+
+/// A use of a changed API in a mod.
+#[derive(Debug, Clone, Serialize)]
+pub struct Usage {
+    pub file: PathBuf,
+    pub line: usize,
+    /// The code referring to the API, e.g. `defines.events.on_tick`
+    pub code: String,
+    pub apis: Vec<Api>,
+}
+
+/// Finds all uses of changed APIs in the Lua files of the mod in `dir`.
+pub fn scan(dir: &Path, index: &ApiIndex) -> std::io::Result<Vec<Usage>> {
+    let mut files = Vec::new();
+    collect_lua_files(dir, &mut files)?;
+    files.sort();
+
+    let mut res = Vec::new();
+
+    for file in files {
+        let source = std::fs::read_to_string(&file)?;
+        let relative = file.strip_prefix(dir).unwrap_or(&file).to_path_buf();
+
+        let path = relative.to_string_lossy();
+        let data_stage = path.starts_with("data") || path.starts_with("prototypes");
+
+        for (line, code, apis) in index.lookup(&source, data_stage) {
+            res.push(Usage {
+                file: relative.clone(),
+                line,
+                code,
+                apis,
+            });
+        }
+    }
+
+    Ok(res)
+}
+
+fn collect_lua_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            collect_lua_files(&path, files)?;
+        } else if path.extension().is_some_and(|e| e == "lua") {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+enum Token<'a> {
+    Ident {
+        name: &'a str,
+        /// accessed with `.` or `:`
+        member: bool,
+        /// followed by `(`
+        call: bool,
+        /// followed by `=` but not `==`
+        assign: bool,
+        /// the whole dotted chain up to this identifier, e.g. `defines.events.on_tick`
+        chain: &'a str,
+    },
+    /// string value assigned to `type`
+    Typename(&'a str),
+}
+
+fn tokens(code: &str) -> Vec<Token<'_>> {
+    let bytes = code.as_bytes();
+    let mut res = Vec::new();
+    let mut chain_start = None;
+    let mut i = 0;
+
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let skip_ws = |mut j: usize| {
+        while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+            j += 1;
+        }
+        j
+    };
+
+    while i < bytes.len() {
+        let b = bytes[i];
+
+        if b == b'"' || b == b'\'' {
+            // skip string literals
+            i += 1;
+            while i < bytes.len() && bytes[i] != b {
+                i += 1;
+            }
+            i += 1;
+            chain_start = None;
+            continue;
+        }
+
+        if b == b'.' && bytes.get(i + 1) == Some(&b'.') {
+            // `..` concatenates, the next identifier isn't a member
+            while i < bytes.len() && bytes[i] == b'.' {
+                i += 1;
+            }
+            chain_start = None;
+            continue;
+        }
+
+        if !(b.is_ascii_alphabetic() || b == b'_') {
+            if !matches!(b, b'.' | b':') {
+                chain_start = None;
+            }
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < bytes.len() && is_ident(bytes[i]) {
+            i += 1;
+        }
+
+        let member = start > 0
+            && matches!(bytes[start - 1], b'.' | b':')
+            && !(start > 1 && bytes[start - 2] == b'.');
+        if !member {
+            chain_start = Some(start);
+        }
+
+        let next = skip_ws(i);
+        let name = &code[start..i];
+        let assign = bytes.get(next) == Some(&b'=') && bytes.get(next + 1) != Some(&b'=');
+
+        if name == "type" && assign && !member {
+            let value_start = skip_ws(next + 1);
+
+            if let Some(&quote @ (b'"' | b'\'')) = bytes.get(value_start) {
+                if let Some(len) = code[value_start + 1..].find(quote as char) {
+                    res.push(Token::Typename(
+                        &code[value_start + 1..value_start + 1 + len],
+                    ));
+                }
+            }
+        }
+
+        res.push(Token::Ident {
+            name,
+            member,
+            call: bytes.get(next) == Some(&b'('),
+            assign,
+            chain: &code[chain_start.unwrap_or(start)..i],
+        });
+    }
+
+    res
+}
@@ -0,0 +1,109 @@
+//! Locating errors of `serde_json` in the raw JSON.
+
+use std::fmt::Write as _;
+
+/// A container the scan is currently in.
+enum Frame {
+    Object {
+        key: Option<String>,
+        expect_key: bool,
+    },
+    Array {
+        index: usize,
+    },
+}
+
+/// Path like `classes[42].methods[3].type` of the value at `line` and `column` (as reported by
+/// [`serde_json::Error`]) in `raw`, `None` if the position is at the top level or outside of `raw`.
+///
+/// Errors about a whole value are reported at its end, so the path is the one of the value that was read last.
+#[must_use]
+pub fn locate(raw: &[u8], line: usize, column: usize) -> Option<String> {
+    let end = offset(raw, line, column)?;
+    let mut stack = Vec::new();
+    let mut i = 0;
+
+    while i <= end {
+        match raw[i] {
+            b'{' => stack.push(Frame::Object {
+                key: None,
+                expect_key: true,
+            }),
+            b'[' => stack.push(Frame::Array { index: 0 }),
+            b'}' | b']' => {
+                stack.pop();
+            }
+            b',' => match stack.last_mut() {
+                Some(Frame::Object { expect_key, .. }) => *expect_key = true,
+                Some(Frame::Array { index }) => *index += 1,
+                None => {}
+            },
+            b':' => {
+                if let Some(Frame::Object { expect_key, .. }) = stack.last_mut() {
+                    *expect_key = false;
+                }
+            }
+            b'"' => {
+                let start = i;
+                i += 1;
+
+                while i < raw.len() && raw[i] != b'"' {
+                    i += if raw[i] == b'\\' { 2 } else { 1 };
+                }
+
+                if let Some(Frame::Object {
+                    key,
+                    expect_key: true,
+                }) = stack.last_mut()
+                {
+                    *key = raw
+                        .get(start..=i)
+                        .and_then(|s| serde_json::from_slice(s).ok());
+                }
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    let path = stack.iter().fold(String::new(), |mut path, frame| {
+        match frame {
+            Frame::Object { key: Some(key), .. } => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+            Frame::Array { index } => {
+                let _ = write!(path, "[{index}]");
+            }
+            Frame::Object { key: None, .. } => {}
+        }
+
+        path
+    });
+
+    (!path.is_empty()).then_some(path)
+}
+
+/// Byte offset of a 1-based line and column.
+fn offset(raw: &[u8], line: usize, column: usize) -> Option<usize> {
+    if line == 0 {
+        return None;
+    }
+
+    let line_start = if line == 1 {
+        0
+    } else {
+        raw.iter()
+            .enumerate()
+            .filter(|(_, b)| **b == b'\n')
+            .nth(line - 2)
+            .map(|(i, _)| i + 1)?
+    };
+
+    let offset = line_start + column.saturating_sub(1);
+
+    (offset < raw.len()).then_some(offset)
+}
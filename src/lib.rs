@@ -0,0 +1,586 @@
+use serde::de::DeserializeOwned;
+
+pub use batch::{diff_many, diff_many_with, diff_many_with_progress, StageDiff};
+pub use error::{Error, Result};
+
+use format::{lazy::LazyDoc, DiffEntry, DiffOptions, Doc};
+
+mod batch;
+pub mod cache;
+pub mod cross_stage;
+#[cfg(feature = "sql-export")]
+pub mod database;
+pub mod defines;
+#[cfg(any(feature = "wasm", feature = "ffi"))]
+mod embed;
+mod error;
+pub mod events;
+pub mod feed;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod format;
+pub mod graph;
+pub mod impact;
+mod json_path;
+pub mod links;
+pub mod migrate;
+pub mod partial;
+pub mod progress;
+pub mod query;
+pub mod render;
+#[cfg(feature = "scrape")]
+pub mod scrape;
+pub mod self_check;
+pub mod split;
+pub mod stats;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod versions;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod zip;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Docs {
+    Prototype,
+    Runtime,
+}
+
+impl clap::ValueEnum for Docs {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Prototype, Self::Runtime]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        match self {
+            Self::Prototype => Some(clap::builder::PossibleValue::new("prototype")),
+            Self::Runtime => Some(clap::builder::PossibleValue::new("runtime")),
+        }
+    }
+}
+
+impl From<format::Stage> for Docs {
+    fn from(stage: format::Stage) -> Self {
+        match stage {
+            format::Stage::Prototype => Self::Prototype,
+            format::Stage::Runtime => Self::Runtime,
+        }
+    }
+}
+
+impl std::fmt::Display for Docs {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Prototype => write!(f, "prototype"),
+            Self::Runtime => write!(f, "runtime"),
+        }
+    }
+}
+
+impl Docs {
+    /// Reads the stage of a raw doc from its common info header.
+    pub fn detect(raw: &[u8]) -> Result<Self> {
+        match serde_json::from_slice::<format::Common>(raw) {
+            Ok(info) => Ok(info.stage.into()),
+            Err(source) => Err(Error::Header {
+                what: "doc".to_owned(),
+                source,
+            }),
+        }
+    }
+
+    /// Reads the common info headers of both docs and checks that their api versions are supported.
+    pub fn check_versions(
+        self,
+        source: &[u8],
+        target: &[u8],
+    ) -> Result<(format::Common, format::Common)> {
+        let header = |raw, what: &str| {
+            serde_json::from_slice::<format::Common>(raw).map_err(|source| Error::Header {
+                what: what.to_owned(),
+                source,
+            })
+        };
+        let source_info = header(source, "source")?;
+        let target_info = header(target, "target")?;
+
+        let (min, max, supported) = match self {
+            Self::Prototype => (4, 6, "4, 5 and 6"),
+            Self::Runtime => (5, 6, "5 and 6"),
+        };
+
+        for (what, info) in [("Source", &source_info), ("Target", &target_info)] {
+            if info.api_version < min || info.api_version > max {
+                return Err(Error::UnsupportedVersion {
+                    what: what.to_owned(),
+                    version: info.api_version,
+                    min,
+                    supported,
+                });
+            }
+        }
+
+        for (what, info) in [("Source", &source_info), ("Target", &target_info)] {
+            let stage = Self::from(info.stage.clone());
+
+            if stage != self {
+                return Err(Error::StageMismatch {
+                    what: what.to_owned(),
+                    found: stage,
+                    expected: self,
+                });
+            }
+        }
+
+        if self == Self::Runtime && source_info.api_version > target_info.api_version {
+            return Err(Error::SourceNewer);
+        }
+
+        Ok((source_info, target_info))
+    }
+
+    /// Diffs two raw docs of this stage and renders the diff.
+    pub fn diff(
+        self,
+        source: &[u8],
+        target: &[u8],
+        options: DiffOptions,
+        format: render::OutputFormat,
+        with_old: bool,
+    ) -> Result<String> {
+        self.check_versions(source, target)?;
+
+        if format == render::OutputFormat::Ndjson {
+            let mut out = Vec::new();
+            self.stream(source, target, options, with_old, &mut out)?;
+
+            return String::from_utf8(out).map_err(|e| Error::Render(e.into()));
+        }
+
+        match self {
+            Self::Prototype => Comparison::<format::prototype::PrototypeDoc>::new(
+                source, target, options,
+            )?
+            .render(format, with_old, render::JsonStyle::default()),
+            Self::Runtime => Comparison::<format::runtime::RuntimeDoc>::new(
+                source, target, options,
+            )?
+            .render(format, with_old, render::JsonStyle::default()),
+        }
+    }
+
+    /// Diffs two raw docs of this stage, writing each changed item as a line of JSON to `out`.
+    ///
+    /// Items are only deserialized while they're diffed, so neither doc is kept in memory in full.
+    /// Returns the number of changed items.
+    pub fn stream(
+        self,
+        source: &[u8],
+        target: &[u8],
+        options: DiffOptions,
+        with_old: bool,
+        out: &mut impl std::io::Write,
+    ) -> Result<usize> {
+        // items are parsed while they're diffed, so they share their strings until the end
+        format::intern::scope(|| self.stream_scoped(source, target, options, with_old, out))
+    }
+
+    fn stream_scoped(
+        self,
+        source: &[u8],
+        target: &[u8],
+        options: DiffOptions,
+        with_old: bool,
+        out: &mut impl std::io::Write,
+    ) -> Result<usize> {
+        if self == Self::Prototype && options.resolve_inheritance {
+            // resolving inheritance needs all prototypes at once
+            return Comparison::<format::prototype::PrototypeDoc>::stream(
+                parse(source, "source")?,
+                parse(target, "target")?,
+                options,
+                with_old,
+                out,
+            );
+        }
+
+        let source = LazyDoc::parse(source, "source")?;
+        let target = LazyDoc::parse(target, "target")?;
+
+        options.apply(|| {
+            write_entries(out, with_old, target.info(), |emit| match self {
+                Self::Prototype => source.prototype_entries(&target, emit),
+                Self::Runtime => source.runtime_entries(&target, emit),
+            })
+        })
+    }
+
+    /// Counts the changed items per section of two raw docs of this stage without building their diffs.
+    ///
+    /// Items are only deserialized while they're compared like in [`Docs::stream`],
+    /// see [`Doc::count_changes`] for what's counted.
+    pub fn count(
+        self,
+        source: &[u8],
+        target: &[u8],
+        options: DiffOptions,
+    ) -> Result<format::ChangeCounts> {
+        self.check_versions(source, target)?;
+
+        format::intern::scope(|| self.count_scoped(source, target, options))
+    }
+
+    fn count_scoped(
+        self,
+        source: &[u8],
+        target: &[u8],
+        options: DiffOptions,
+    ) -> Result<format::ChangeCounts> {
+        if self == Self::Prototype && options.resolve_inheritance {
+            // resolving inheritance needs all prototypes at once
+            let mut source = parse::<format::prototype::PrototypeDoc>(source, "source")?;
+            let mut target = parse::<format::prototype::PrototypeDoc>(target, "target")?;
+
+            return options.apply(|| {
+                source.prepare();
+                target.prepare();
+
+                source.count_changes(&target)
+            });
+        }
+
+        let source = LazyDoc::parse(source, "source")?;
+        let target = LazyDoc::parse(target, "target")?;
+
+        options.apply(|| match self {
+            Self::Prototype => source.prototype_counts(&target),
+            Self::Runtime => source.runtime_counts(&target),
+        })
+    }
+}
+
+/// Diffs the runtime and prototype docs of two versions at once.
+///
+/// Both stages are given as raw `(source, target)` docs,
+/// the diffs are combined into `{"runtime": ..., "prototype": ...}`.
+/// With `cross_stage` the [`cross_stage::check`] results are added as `"cross_stage": [...]`.
+pub fn diff_all(
+    runtime: (&[u8], &[u8]),
+    prototype: (&[u8], &[u8]),
+    options: DiffOptions,
+    with_old: bool,
+    cross_stage: bool,
+) -> Result<serde_json::Value> {
+    Docs::Runtime.check_versions(runtime.0, runtime.1)?;
+    Docs::Prototype.check_versions(prototype.0, prototype.1)?;
+
+    let runtime = Comparison::<format::runtime::RuntimeDoc>::new(runtime.0, runtime.1, options)?;
+    let prototype =
+        Comparison::<format::prototype::PrototypeDoc>::new(prototype.0, prototype.1, options)?;
+
+    format::with_old_values(with_old, || {
+        let mut res = serde_json::Map::new();
+        res.insert("runtime".to_owned(), serde_json::to_value(&runtime.diff)?);
+        res.insert(
+            "prototype".to_owned(),
+            serde_json::to_value(&prototype.diff)?,
+        );
+
+        if cross_stage {
+            res.insert(
+                "cross_stage".to_owned(),
+                serde_json::to_value(cross_stage::check(&runtime, &prototype))?,
+            );
+        }
+
+        Ok(res.into())
+    })
+}
+
+/// Writes each [`DiffEntry`] produced by `f` as a line of JSON, returns the number of entries.
+///
+/// `target` is the header of the target doc, entries link to it if enabled in the current options.
+fn write_entries(
+    out: &mut impl std::io::Write,
+    with_old: bool,
+    target: &format::Common,
+    f: impl FnOnce(&mut dyn FnMut(DiffEntry)) -> Result<()>,
+) -> Result<usize> {
+    let links = DiffOptions::current()
+        .anchor_links
+        .then(|| links::Links::of(target));
+    let mut count = 0;
+    let mut res = Ok(());
+
+    format::with_old_values(with_old, || {
+        f(&mut |mut entry| {
+            if let Some(links) = &links {
+                entry.link(links);
+            }
+
+            if res.is_ok() {
+                count += 1;
+                res = serde_json::to_writer(&mut *out, &entry)
+                    .map_err(Error::from)
+                    .and_then(|()| Ok(writeln!(out)?));
+            }
+        })
+    })?;
+
+    res.map(|()| count)
+}
+
+/// Versions in a local docs directory with one sub directory per version, oldest first.
+///
+/// Directories whose names aren't dotted version numbers like `1.1.107` are skipped.
+pub fn local_versions(dir: &std::path::Path) -> std::io::Result<Vec<String>> {
+    let mut versions = Vec::new();
+
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+            continue;
+        };
+
+        if let Some(parts) = versions::parts(&name) {
+            if entry.file_type()?.is_dir() {
+                versions.push((parts, name));
+            }
+        }
+    }
+
+    versions.sort();
+
+    Ok(versions.into_iter().map(|(_, name)| name).collect())
+}
+
+/// Deserializes a raw JSON doc, `what` names the doc in the error message.
+///
+/// The strings of the doc are interned within an [`intern::scope`](format::intern::scope).
+pub fn parse<T: DeserializeOwned>(raw: &[u8], what: &str) -> Result<T> {
+    format::intern::scope(|| serde_json::from_slice(raw).map_err(|e| Error::parse(what, raw, e)))
+}
+
+/// A doc without any items that has the same header as the raw doc `like`.
+///
+/// Diffing it against `like` lists every item of `like` as added with all its fields.
+pub fn empty_doc(like: &[u8]) -> Result<Vec<u8>> {
+    let mut doc = parse::<serde_json::Map<String, serde_json::Value>>(like, "target")?;
+
+    for value in doc.values_mut().filter(|v| v.is_array()) {
+        *value = serde_json::Value::Array(Vec::new());
+    }
+
+    Ok(serde_json::to_vec(&doc)?)
+}
+
+/// Two docs of the same stage and their diff.
+pub struct Comparison<T: Doc> {
+    pub source: T,
+    pub target: T,
+    pub diff: T::Diff,
+}
+
+impl<T> Comparison<T>
+where
+    T: Doc + std::ops::Deref<Target = format::Common> + DeserializeOwned + serde::Serialize,
+    T::Diff: serde::Serialize,
+{
+    /// Deserializes both docs and diffs them with the given options.
+    pub fn new(source: &[u8], target: &[u8], options: DiffOptions) -> Result<Self> {
+        Self::with_cache(source, target, options, None)
+    }
+
+    /// Deserializes both docs like [`Comparison::new`], through `cache` if given.
+    pub fn with_cache(
+        source: &[u8],
+        target: &[u8],
+        options: DiffOptions,
+        cache: Option<&cache::Cache>,
+    ) -> Result<Self> {
+        let load = |raw, what| cache.map_or_else(|| parse(raw, what), |c| c.load(raw, what));
+
+        // both docs share their strings, the table to look them up is dropped once they're parsed
+        let (source, target) = format::intern::scope(|| {
+            Ok::<_, Error>((load(source, "source")?, load(target, "target")?))
+        })?;
+
+        Ok(Self::from_docs(source, target, options))
+    }
+
+    /// Diffs both docs with the given options.
+    pub fn from_docs(mut source: T, mut target: T, options: DiffOptions) -> Self {
+        let diff = options.apply(|| {
+            source.prepare();
+            target.prepare();
+
+            source.diff(&target)
+        });
+
+        Self {
+            source,
+            target,
+            diff,
+        }
+    }
+
+    /// Diffs both docs like [`Comparison::from_docs`], writing each changed item
+    /// as a line of JSON to `out` as soon as it's diffed. Returns the number of changed items.
+    pub fn stream(
+        mut source: T,
+        mut target: T,
+        options: DiffOptions,
+        with_old: bool,
+        out: &mut impl std::io::Write,
+    ) -> Result<usize> {
+        options.apply(|| {
+            source.prepare();
+            target.prepare();
+
+            write_entries(out, with_old, &target, |emit| {
+                source.diff_entries(&target, emit)
+            })
+        })
+    }
+
+    pub fn render(
+        &self,
+        format: render::OutputFormat,
+        with_old: bool,
+        style: render::JsonStyle,
+    ) -> Result<String> {
+        render::render(
+            format,
+            with_old,
+            style,
+            &self.diff,
+            &self.source,
+            &self.target,
+        )
+        .map_err(Error::Render)
+    }
+
+    /// Renders the diff with the type names mapped to another language, see [`render::render_mapped`].
+    pub fn render_mapped(
+        &self,
+        format: render::OutputFormat,
+        with_old: bool,
+        style: render::JsonStyle,
+        types: &render::type_map::TypeMapper,
+    ) -> Result<String> {
+        render::render_mapped(
+            format,
+            with_old,
+            style,
+            types,
+            &self.diff,
+            &self.source,
+            &self.target,
+        )
+        .map_err(Error::Render)
+    }
+
+    /// Renders the diff as a changelog grouped by theme, see [`render::render_themed`].
+    pub fn render_themed(
+        &self,
+        format: render::OutputFormat,
+        themes: &render::themes::Themes,
+    ) -> Result<String> {
+        render::render_themed(format, themes, &self.diff, &self.source, &self.target)
+            .map_err(Error::Render)
+    }
+
+    /// Renders the diff with a user template, see [`render::template::render`].
+    #[cfg(feature = "templates")]
+    pub fn render_template(&self, template: &render::template::Template) -> Result<String> {
+        render::template::render(template, &self.diff, &self.source, &self.target)
+            .map_err(Error::Render)
+    }
+
+    /// Writes the changed items as lines of JSON like [`Comparison::stream`], diffing the docs again.
+    pub fn write_ndjson(
+        &self,
+        options: DiffOptions,
+        with_old: bool,
+        out: &mut impl std::io::Write,
+    ) -> Result<usize> {
+        options.apply(|| {
+            write_entries(out, with_old, &self.target, |emit| {
+                self.source.diff_entries(&self.target, emit)
+            })
+        })
+    }
+
+    /// Writes each changed item to its own file in `dir` plus an index, see [`split::write`].
+    ///
+    /// Diffs the docs again like [`Comparison::write_ndjson`] to get the changes of each item.
+    pub fn write_split(
+        &self,
+        dir: &std::path::Path,
+        format: render::OutputFormat,
+        options: DiffOptions,
+        with_old: bool,
+        style: render::JsonStyle,
+    ) -> Result<Vec<split::IndexEntry>> {
+        let mut entries = Vec::new();
+        options.apply(|| {
+            let links = DiffOptions::current()
+                .anchor_links
+                .then(|| links::Links::of(&self.target));
+
+            format::with_old_values(with_old, || {
+                self.source.diff_entries(&self.target, &mut |mut entry| {
+                    if let Some(links) = &links {
+                        entry.link(links);
+                    }
+
+                    entries.push(entry);
+                })
+            })
+        })?;
+
+        let changes = render::flat::flatten(
+            &format::with_old_values(false, || serde_json::to_value(&self.diff))?,
+            &serde_json::to_value(&self.source)?,
+            &serde_json::to_value(&self.target)?,
+        );
+
+        split::write(dir, format, style, &entries, &changes)
+    }
+
+    /// Changed defines and define values keyed by their full Lua path, see [`defines::flatten`].
+    pub fn flat_defines(&self) -> Result<std::collections::BTreeMap<String, format::ItemStatus>> {
+        render::flat_defines(&self.diff, &self.source, &self.target).map_err(Error::Render)
+    }
+
+    /// Items that became deprecated or stopped being deprecated, see [`render::deprecation::find`].
+    pub fn deprecations(&self) -> Result<Vec<render::deprecation::Deprecation>> {
+        render::deprecations(&self.source, &self.target).map_err(Error::Render)
+    }
+
+    /// The diff as a tree of changes, see [`render::tree`].
+    pub fn tree(&self) -> Result<render::tree::Node> {
+        render::tree(&self.diff, &self.source, &self.target).map_err(Error::Render)
+    }
+
+    pub fn summary(&self) -> Result<render::summary::Summary> {
+        render::summary(&self.diff, &self.source, &self.target).map_err(Error::Render)
+    }
+
+    pub fn print(
+        &self,
+        format: render::OutputFormat,
+        with_old: bool,
+        style: render::JsonStyle,
+    ) -> Result<()> {
+        render::print(
+            format,
+            with_old,
+            style,
+            &self.diff,
+            &self.source,
+            &self.target,
+        )
+        .map_err(Error::Render)
+    }
+}
@@ -0,0 +1,69 @@
+use crate::format::{Application, Common};
+
+/// Pages of the official docs of a version at <https://lua-api.factorio.com>.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Links {
+    version: String,
+    api_version: u8,
+    /// Only the docs of Factorio itself are hosted there, not those of mods
+    official: bool,
+}
+
+/// Lists of members that have their own anchor on the page of their item.
+const MEMBERS: [&str; 4] = ["methods", "attributes", "operators", "properties"];
+
+impl Links {
+    /// `version` and `api_version` of the docs the links point to.
+    #[must_use]
+    pub const fn new(version: String, api_version: u8) -> Self {
+        Self {
+            version,
+            api_version,
+            official: true,
+        }
+    }
+
+    /// Links to the docs with the header `info`, docs of other applications than Factorio have no links.
+    #[must_use]
+    pub fn of(info: &Common) -> Self {
+        Self {
+            official: info.application == Application::Factorio,
+            ..Self::new(info.application_version.clone(), info.api_version)
+        }
+    }
+
+    /// Page of the item or member at `path` in the docs, e.g. `["classes", "LuaEntity", "methods", "teleport"]`.
+    ///
+    /// Paths below members link to the member, paths without an item or of sections
+    /// without pages in the docs have no link.
+    #[must_use]
+    pub fn url(&self, path: &[&str]) -> Option<String> {
+        let [section, item, rest @ ..] = path else {
+            return None;
+        };
+
+        if !self.official {
+            return None;
+        }
+
+        let page = match *section {
+            "classes" | "prototypes" | "types" => {
+                let page = format!("{section}/{item}.html");
+
+                match rest {
+                    [list, member, ..] if MEMBERS.contains(list) => format!("{page}#{member}"),
+                    _ => page,
+                }
+            }
+            "concepts" if self.api_version >= 6 => format!("concepts/{item}.html"),
+            "concepts" | "events" => format!("{section}.html#{item}"),
+            "defines" => format!("defines.html#defines.{item}"),
+            _ => return None,
+        };
+
+        Some(format!(
+            "https://lua-api.factorio.com/{}/{page}",
+            self.version
+        ))
+    }
+}
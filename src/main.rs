@@ -1,257 +1,1945 @@
-use std::{cell::RefCell, path::Path, process::ExitCode};
+use std::{
+    collections::BTreeSet,
+    ffi::OsString,
+    io::Write as _,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::OnceLock,
+};
 
 use anyhow::Result;
 
-use clap::{crate_authors, crate_description, Parser};
-use format::{runtime::RuntimeDoc, Doc as _};
+use clap::{crate_authors, crate_description, Args, Parser, Subcommand, ValueEnum as _};
+use fapi_diff::{
+    cache::Cache,
+    defines::DefinesDiff,
+    events::EventsDiff,
+    format::{
+        self, missing_old_values,
+        prototype::{PrototypeDoc, PrototypeDocDiff},
+        runtime::{RuntimeDoc, RuntimeDocDiff},
+        with_old_values, Context, DiffOptions, FieldCategory, FieldPolicy, Invert, Section,
+    },
+    graph::{Graph, GraphFormat},
+    impact::{self, ApiIndex},
+    partial, progress, render, Comparison, Docs,
+};
 
-pub mod format;
+mod completions;
+mod config;
+mod manpage;
+#[cfg(feature = "notify")]
+mod notify;
+#[cfg(feature = "server")]
+mod serve;
 
-use crate::format::prototype::PrototypeDoc;
+#[derive(Parser, Clone)]
+#[clap(author = crate_authors!(), version, about = crate_description!())]
+pub struct Cli {
+    #[clap(subcommand)]
+    pub command: Command,
+
+    /// Format of the warnings printed to stderr, `json` prints one JSON object per line
+    #[clap(long, global = true, value_enum, default_value_t)]
+    pub log_format: LogFormat,
+
+    /// Read the default options of the subcommands from this file,
+    /// defaults to `fapi-diff.toml` in the working directory if it exists
+    #[clap(long, global = true, value_name = "FILE", verbatim_doc_comment)]
+    pub config: Option<PathBuf>,
+
+    /// Don't draw the progress bars of runs over many versions on stderr
+    #[clap(short, long, global = true)]
+    pub quiet: bool,
+}
+
+impl Cli {
+    /// Parses the command line, `fapi-diff <stage> ...` is a shorthand for `fapi-diff compare <stage> ...`.
+    ///
+    /// The stage of `fapi-diff [compare] <file> <file>` is detected from the source file.
+    /// Options the command line doesn't set are taken from the config file, see [`config`].
+    #[must_use]
+    pub fn parse_args() -> Self {
+        let mut args = std::env::args_os().collect::<Vec<_>>();
+
+        if args
+            .get(1)
+            .and_then(|a| a.to_str())
+            .is_some_and(|a| Docs::from_str(a, false).is_ok() || Path::new(a).is_file())
+        {
+            args.insert(1, OsString::from("compare"));
+        }
+
+        if args.get(1).is_some_and(|a| a == "compare") {
+            if let Some(file) = args.get(2).map(PathBuf::from).filter(|p| p.is_file()) {
+                let stage = std::fs::read(&file)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|raw| {
+                        if fapi_diff::zip::is_zip(&raw) {
+                            anyhow::bail!(
+                                "archives contain the docs of both stages, pass the stage first"
+                            );
+                        }
+
+                        Ok(Docs::detect(&raw)?)
+                    });
+
+                match stage {
+                    Ok(stage) => args.insert(2, OsString::from(stage.to_string())),
+                    Err(e) => <Self as clap::CommandFactory>::command()
+                        .error(
+                            clap::error::ErrorKind::InvalidValue,
+                            format!("Failed to detect the stage of {}: {e}", file.display()),
+                        )
+                        .exit(),
+                }
+            }
+        }
+
+        if let Some(path) = config::find(&args) {
+            let mut command = <Self as clap::CommandFactory>::command();
+            if let Err(e) = config::apply(&path, &command, &mut args) {
+                command
+                    .error(clap::error::ErrorKind::InvalidValue, format!("{e:#}"))
+                    .exit();
+            }
+        }
+
+        Self::parse_from(args)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// `warning: <path>: <message>`
+    #[default]
+    Text,
+    /// `{"level": "warning", "path": ..., "message": ...}`
+    Json,
+}
+
+static LOG_FORMAT: OnceLock<LogFormat> = OnceLock::new();
+static QUIET: OnceLock<bool> = OnceLock::new();
+
+/// Progress bars of a run over pairs with these `labels`, unless `--quiet`.
+fn progress_bars(labels: Vec<String>) -> Option<progress::Bars> {
+    if QUIET.get().copied().unwrap_or_default() {
+        return None;
+    }
+
+    progress::Bars::new(labels)
+}
+
+/// Prints the warnings recorded so far in the `--log-format`.
+fn print_warnings() {
+    for warning in format::take_warnings() {
+        print_warning(&warning);
+    }
+}
+
+fn print_warning(warning: &format::Warning) {
+    match LOG_FORMAT.get().copied().unwrap_or_default() {
+        LogFormat::Text => eprintln!("warning: {warning}"),
+        LogFormat::Json => eprintln!(
+            "{}",
+            serde_json::json!({
+                "level": "warning",
+                "path": warning.path,
+                "message": warning.message,
+            })
+        ),
+    }
+}
+
+/// Prints a warning that isn't about a single item, see [`print_warning`].
+fn warn(message: String) {
+    print_warning(&format::Warning {
+        path: None,
+        message,
+    });
+}
+
+/// Prints a warning like [`warn`] while the progress `bars` are cleared.
+fn warn_with(bars: Option<&progress::Bars>, message: String) {
+    match bars {
+        Some(bars) => bars.suspend(|| warn(message)),
+        None => warn(message),
+    }
+}
+
+#[derive(Subcommand, Clone)]
+pub enum Command {
+    /// Compare two versions of the docs (default if the first argument is a stage or a file)
+    Compare(CompareArgs),
+
+    /// Compare both the runtime and the prototype docs of two versions
+    CompareAll(CompareAllArgs),
+
+    /// Invert a diff, turning a diff from `a` to `b` into a diff from `b` to `a`
+    Invert(InvertArgs),
+
+    /// Upgrade a stored JSON diff of an older version of this tool to the current diff format
+    MigrateDiff(MigrateDiffArgs),
+
+    /// Apply a patch of `--format json-patch` to a doc, optionally only the changes of some items
+    Apply(ApplyArgs),
+
+    /// List the define values added or removed between two docs
+    Defines(DefinesArgs),
+
+    /// List the define values documented in only one of the stages of the same version
+    CheckDefines(CheckDefinesArgs),
+
+    /// Diff a doc against itself, failing if any change is found
+    SelfCheck(SelfCheckArgs),
+
+    /// List the changes of event payloads between two runtime docs, marking the ones that break handlers
+    Events(EventsArgs),
+
+    /// Generate an Atom feed with the changes between consecutive versions
+    Feed(FeedArgs),
+
+    /// Write the diffs between consecutive versions to a directory, keeping existing ones
+    Archive(ArchiveArgs),
+
+    /// Report usages of removed or changed APIs in a mod
+    Impact(ImpactArgs),
+
+    /// Diff two doc files again whenever either of them changes
+    Watch(WatchArgs),
+
+    /// Print counts of the items of a doc file and averages derived from them
+    Stats(StatsArgs),
+
+    /// Print an item of a doc file by its path like `classes.LuaEntity.methods.teleport`
+    Query(QueryArgs),
+
+    /// List the published versions of the docs and the latest stable and experimental releases
+    Versions(VersionsArgs),
+
+    /// Print the inheritance graph of a doc, or of two docs with the changed items colored
+    Graph(GraphArgs),
+
+    /// Print the completion script of a shell, versions are completed with the versions cached by `versions`
+    Completions(completions::CompletionsArgs),
+
+    /// Print the man page in roff format
+    Manpage,
+
+    /// Serve diffs over HTTP
+    #[cfg(feature = "server")]
+    Serve(serve::ServeArgs),
+
+    /// Build a sqlite database of all versions of a docs directory and the diffs between them
+    /// with the `sqlite3` shell, or write the SQL script that builds it
+    #[cfg(feature = "sql-export")]
+    ExportDb(ExportDbArgs),
+}
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Args, Clone)]
+pub struct CompareArgs {
+    /// Stage of the docs to use.
+    ///
+    /// Prototype stage supports format versions 4, 5 and 6.
+    /// Runtime stage supports format versions 5 and 6.
+    #[clap(value_parser, verbatim_doc_comment)]
+    pub stage: Docs,
+
+    /// Base version of the docs to use, or the URL of a doc.
+    /// `none` diffs against an empty doc, listing everything in the target as added
+    #[clap(value_parser, verbatim_doc_comment)]
+    pub source: String,
+
+    /// Target version of the docs to compare against, or the URL of a doc
+    /// If not specified, the latest version is used
+    #[clap(value_parser, default_value = "latest")]
+    pub target: String,
+
+    #[clap(flatten)]
+    pub diff: DiffFlags,
+
+    /// Read source and target from local files, either doc files or docs directories.
+    /// Implied if the source or the target is a file. URLs are downloaded either way
+    #[clap(short, long, action, verbatim_doc_comment)]
+    pub local: bool,
+
+    /// Output format of the diff
+    #[clap(long, value_enum, default_value_t)]
+    pub format: render::OutputFormat,
+
+    /// Include the old values of changed fields in the output
+    #[clap(long, action)]
+    pub with_old: bool,
+
+    #[clap(flatten)]
+    pub json: JsonFlags,
+
+    /// Replace the nested defines of the JSON diff with the changed defines and define values
+    /// keyed by their full Lua path, e.g. `"defines.events.on_tick": "added"`
+    #[clap(long, action, verbatim_doc_comment)]
+    pub flat_defines: bool,
+
+    /// Print a compact changelog instead of the diff
+    #[clap(long, action)]
+    pub summary: bool,
+
+    /// Group the markdown or bbcode changelog by theme (GUI, entities, trains, ...) like release notes.
+    /// Optionally read the themes from a JSON file: `[{"name": "GUI", "prefixes": ["LuaGui", "on_gui"]}]`
+    #[clap(long, num_args = 0..=1, value_name = "FILE", conflicts_with_all = ["summary", "flat_defines"], verbatim_doc_comment)]
+    pub themes: Option<Option<PathBuf>>,
+
+    /// Write one file per changed item to this directory instead of printing the diff,
+    /// e.g. `classes/LuaEntity.json`, plus an `index.json` listing them. Supports the json and markdown formats
+    #[clap(long, value_name = "DIR", conflicts_with_all = ["summary", "flat_defines", "themes"], verbatim_doc_comment)]
+    pub split_output: Option<PathBuf>,
+
+    /// Map the type names of the flat, markdown, bbcode, csv and tsv output to another language
+    /// with a JSON file of type names: `{"uint": "number", "double": "number"}`
+    #[clap(long, value_name = "FILE", conflicts_with_all = ["summary", "flat_defines", "themes", "split_output"], verbatim_doc_comment)]
+    pub type_map: Option<PathBuf>,
+
+    /// Lay out the changelog with this template instead of a built-in format,
+    /// see the docs of `fapi_diff::render::template` for its syntax
+    #[cfg(feature = "templates")]
+    #[clap(long, value_name = "FILE", conflicts_with_all = ["summary", "format", "with_old", "flat_defines", "themes", "split_output", "type_map", "count_only"], verbatim_doc_comment)]
+    pub template: Option<PathBuf>,
+
+    /// Only print the numbers of added, removed and changed items per section as JSON,
+    /// without building the diffs of the changed items
+    #[clap(long, action, conflicts_with_all = ["summary", "format", "with_old", "flat_defines", "themes", "split_output", "type_map"], verbatim_doc_comment)]
+    pub count_only: bool,
+
+    /// Cache parsed docs in this directory to speed up repeated runs
+    #[clap(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Post a summary of the diff to this Discord webhook
+    #[cfg(feature = "notify")]
+    #[clap(long)]
+    pub discord_webhook: Option<String>,
+}
+
+#[derive(Args, Clone)]
+pub struct CompareAllArgs {
+    /// Base version of the docs to use
+    #[clap(value_parser)]
+    pub source: String,
+
+    /// Target version of the docs to compare against
+    /// If not specified, the latest version is used
+    #[clap(value_parser, default_value = "latest")]
+    pub target: String,
+
+    #[clap(flatten)]
+    pub diff: DiffFlags,
+
+    /// Read source and target from local directories containing both docs
+    #[clap(short, long, action)]
+    pub local: bool,
+
+    /// List the runtime concepts and prototype types of the same name that changed in only one of the docs
+    #[clap(long, action)]
+    pub cross_stage: bool,
+
+    /// Include the old values of changed fields in the output
+    #[clap(long, action)]
+    pub with_old: bool,
+
+    #[clap(flatten)]
+    pub json: JsonFlags,
+}
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Args, Clone)]
+pub struct DiffFlags {
+    /// Diff descriptions (shorthand for `--include descriptions`)
+    #[clap(short, long, action)]
+    pub descriptions: bool,
+
+    /// Diff examples (shorthand for `--include examples`)
+    #[clap(short, long, action)]
+    pub examples: bool,
+
+    /// Full diff (all field categories)
+    #[clap(short, long, action)]
+    pub full: bool,
+
+    /// Field categories to diff in addition to the defaults (names, types, visibility)
+    #[clap(long, value_delimiter = ',')]
+    pub include: Vec<FieldCategory>,
+
+    /// Field categories to never diff, takes precedence over all other flags
+    #[clap(long, value_delimiter = ',')]
+    pub exclude: Vec<FieldCategory>,
+
+    /// Report type changes that don't change the meaning of the type,
+    /// e.g. wrapping a type in a single option union or reordering the options of a union
+    #[clap(long, action)]
+    pub exact_types: bool,
+
+    /// Annotate classes that inherit from classes whose members changed
+    #[clap(long, action)]
+    pub inherit: bool,
+
+    /// Include inherited properties of prototypes,
+    /// showing the changes of the effective properties of each prototype
+    #[clap(long, action)]
+    pub resolve_inheritance: bool,
+
+    /// Add counters of the added, removed and changed members of each changed class / prototype
+    #[clap(long, action)]
+    pub with_metrics: bool,
+
+    /// Link changed items to their page in the official docs of the target version
+    #[clap(long, action)]
+    pub anchor_links: bool,
+
+    /// Unchanged parts of the changed items to include alongside their changes
+    #[clap(long, value_enum, default_value_t)]
+    pub context: Context,
+
+    /// Add the changed words to changes of descriptions
+    #[clap(long, action)]
+    pub word_diff: bool,
+
+    /// Report description changes that only affect links even if most changed descriptions
+    /// only changed their links, they're only counted otherwise
+    #[clap(long, action)]
+    pub keep_link_changes: bool,
+
+    /// Report parameters, properties and attributes that became required or optional
+    /// and attributes that became read-only or writable by the direction of the change,
+    /// e.g. `"optionality": "now_required"` instead of `"optional": false`
+    #[clap(long, action, verbatim_doc_comment)]
+    pub directional: bool,
+
+    /// Compare descriptions, examples and lists without their link markup,
+    /// e.g. `[LuaEntity](runtime:LuaEntity)` and `[LuaEntity::teleport]` are compared as their labels
+    #[clap(long, action)]
+    pub normalize_links: bool,
+
+    /// Regex removed from descriptions, examples and lists before comparing them,
+    /// `PATTERN=>REPLACEMENT` replaces the matches instead, can be given multiple times
+    #[clap(long, value_name = "RULE")]
+    pub normalize_rule: Vec<String>,
+
+    /// Regex of the note in the description of a removed or added concept that names the concept it was renamed to
+    /// or from, its first group captures the other name. Renamed concepts are diffed against their old versions
+    /// instead of being listed as removed and added, an empty regex turns this off
+    #[clap(long, value_name = "REGEX", default_value = format::RENAME_MARKER)]
+    pub rename_marker: String,
+
+    /// Ignore items whose name or path (like `classes/LuaGuiElement/attributes/style`)
+    /// fully matches this regex, can be given multiple times
+    #[clap(long, value_name = "PATTERN")]
+    pub ignore: Vec<String>,
+
+    /// Only diff these sections of the docs, e.g. `classes,events`
+    #[clap(long, value_enum, value_delimiter = ',', conflicts_with = "skip")]
+    pub only: Vec<Section>,
+
+    /// Don't diff these sections of the docs, e.g. `concepts,defines`
+    #[clap(long, value_enum, value_delimiter = ',')]
+    pub skip: Vec<Section>,
+}
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Args, Clone)]
+pub struct JsonFlags {
+    /// Indent the JSON output (default)
+    #[clap(long, action, overrides_with = "compact")]
+    pub pretty: bool,
+
+    /// Write the JSON output on a single line
+    #[clap(long, action, overrides_with = "pretty")]
+    pub compact: bool,
+
+    /// Sort the keys of all JSON objects, making the output stable between runs
+    #[clap(long, action)]
+    pub sort_keys: bool,
+
+    /// Byte-identical output for the same inputs on all platforms:
+    /// sorted keys and floats rounded to 6 decimals
+    #[clap(long, action, verbatim_doc_comment)]
+    pub reproducible: bool,
+}
+
+impl JsonFlags {
+    const fn style(&self) -> render::JsonStyle {
+        render::JsonStyle {
+            pretty: !self.compact,
+            sort_keys: self.sort_keys,
+            reproducible: self.reproducible,
+        }
+    }
+}
+
+#[derive(Args, Clone)]
+pub struct InvertArgs {
+    /// Stage of the docs the diff was created from
+    #[clap(value_parser)]
+    pub stage: Docs,
+
+    /// JSON diff to invert, must have been created with `--with-old`
+    #[clap(value_parser)]
+    pub diff: PathBuf,
+
+    #[clap(flatten)]
+    pub json: JsonFlags,
+}
+
+#[derive(Args, Clone)]
+pub struct MigrateDiffArgs {
+    /// JSON diff of `compare` or `compare-all`
+    #[clap(value_parser)]
+    pub diff: PathBuf,
+
+    /// Overwrite the diff instead of writing the upgraded diff to stdout
+    #[clap(long, action)]
+    pub in_place: bool,
+
+    #[clap(flatten)]
+    pub json: JsonFlags,
+}
+
+#[derive(Args, Clone)]
+pub struct ApplyArgs {
+    /// Doc to patch, the source doc of the patch
+    #[clap(value_parser)]
+    pub base: PathBuf,
+
+    /// RFC 6902 patch written by `--format json-patch`
+    #[clap(value_parser)]
+    pub patch: PathBuf,
+
+    /// Only apply the changes of these items or sections, e.g. `classes/LuaEntity,concepts/MapPosition`
+    #[clap(long, value_delimiter = ',')]
+    pub only: Vec<String>,
+
+    /// Write the patched doc to this file instead of stdout
+    #[clap(long)]
+    pub out: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub json: JsonFlags,
+}
+
+#[derive(Args, Clone)]
+pub struct DefinesArgs {
+    /// Base doc, either a JSON file of any stage or a local docs directory
+    /// whose runtime and prototype docs are combined
+    #[clap(value_parser, verbatim_doc_comment)]
+    pub source: PathBuf,
+
+    /// Target doc to compare against, same forms as the source
+    #[clap(value_parser)]
+    pub target: PathBuf,
+}
+
+#[derive(Args, Clone)]
+pub struct VersionsArgs {
+    /// Directory with one sub directory per version, its versions are marked as local
+    #[clap(long)]
+    pub docs_dir: Option<PathBuf>,
+
+    /// Only print the latest stable and experimental versions separated by a space, e.g. to diff them.
+    /// Fails if either has no docs
+    #[clap(long, action, conflicts_with = "json", verbatim_doc_comment)]
+    pub pair: bool,
+
+    /// Print the versions as JSON
+    #[clap(long, action)]
+    pub json: bool,
+
+    /// Only print the versions cached by the last run and the local versions, one per line, without going online.
+    /// Used by the shell completions
+    #[clap(long, action, conflicts_with_all = ["pair", "json"], verbatim_doc_comment)]
+    pub cached: bool,
+
+    #[clap(flatten)]
+    pub json_style: JsonFlags,
+}
+
+#[derive(Args, Clone)]
+pub struct CheckDefinesArgs {
+    /// Version of the docs, or a local docs directory or archive containing both stages
+    #[clap(value_parser)]
+    pub version: String,
+
+    /// Print the mismatches as JSON
+    #[clap(long, action)]
+    pub json: bool,
+
+    #[clap(flatten)]
+    pub json_style: JsonFlags,
+}
+
+#[derive(Args, Clone)]
+pub struct SelfCheckArgs {
+    /// Doc file to check, its stage is detected from the doc
+    #[clap(value_parser)]
+    pub file: PathBuf,
+
+    /// Print the problems as JSON
+    #[clap(long, action)]
+    pub json: bool,
+
+    #[clap(flatten)]
+    pub json_style: JsonFlags,
+}
+
+#[derive(Args, Clone)]
+pub struct EventsArgs {
+    /// Base version of the runtime docs
+    #[clap(value_parser)]
+    pub source: String,
+
+    /// Version of the runtime docs to compare against
+    #[clap(value_parser, default_value = "latest")]
+    pub target: String,
+
+    /// Read the docs from local files or docs directories
+    #[clap(short, long, action)]
+    pub local: bool,
+
+    /// Only list the changes that break existing event handlers
+    #[clap(long, action)]
+    pub breaking_only: bool,
+
+    /// Print the changes as JSON
+    #[clap(long, action)]
+    pub json: bool,
+
+    #[clap(flatten)]
+    pub json_style: JsonFlags,
+}
+
+#[derive(Args, Clone)]
+pub struct QueryArgs {
+    /// Doc JSON file of any stage
+    #[clap(value_parser)]
+    pub file: PathBuf,
+
+    /// Dot separated path of the item, `*` matches every item of a list
+    #[clap(value_parser)]
+    pub path: String,
+
+    /// Print the item as JSON
+    #[clap(long, action)]
+    pub json: bool,
+
+    #[clap(flatten)]
+    pub json_style: JsonFlags,
+}
+
+#[derive(Args, Clone)]
+pub struct StatsArgs {
+    /// Doc JSON file of any stage
+    #[clap(value_parser)]
+    pub file: PathBuf,
+
+    /// Print the stats as JSON
+    #[clap(long, action)]
+    pub json: bool,
+
+    #[clap(flatten)]
+    pub json_style: JsonFlags,
+}
+
+#[cfg(feature = "sql-export")]
+#[derive(Args, Clone)]
+pub struct ExportDbArgs {
+    /// Directory with one sub directory per version, laid out like the local docs
+    #[clap(value_parser)]
+    pub docs_dir: PathBuf,
+
+    /// Database file to write, its tables are replaced if it exists.
+    /// With `--script` the SQL script is written to it instead
+    #[clap(value_parser, verbatim_doc_comment)]
+    pub database: PathBuf,
+
+    #[clap(flatten)]
+    pub diff: DiffFlags,
+
+    /// The `sqlite3` shell the database is built with, it has to be installed unless `--script` is used
+    #[clap(long, default_value = "sqlite3")]
+    pub sqlite3: PathBuf,
+
+    /// Write the SQL script that builds the database instead of running it with the `sqlite3` shell
+    #[clap(long, action)]
+    pub script: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct GraphArgs {
+    /// Stage of the docs to use
+    #[clap(value_parser)]
+    pub stage: Docs,
+
+    /// Version of the docs to graph
+    #[clap(value_parser)]
+    pub source: String,
+
+    /// Version of the docs to compare against, colors the items by how they changed
+    #[clap(value_parser)]
+    pub target: Option<String>,
+
+    #[clap(flatten)]
+    pub diff: DiffFlags,
+
+    /// Read the docs from local files or docs directories
+    #[clap(short, long, action)]
+    pub local: bool,
+
+    /// Output format of the graph
+    #[clap(long, value_enum, default_value_t)]
+    pub format: GraphFormat,
+
+    /// Only include the changed items and their ancestors
+    #[clap(long, action, requires = "target")]
+    pub changed_only: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct FeedArgs {
+    /// Directory with one sub directory per version, laid out like the local docs
+    #[clap(long)]
+    pub docs_dir: PathBuf,
+
+    /// File to write the feed to
+    #[clap(long)]
+    pub out: PathBuf,
+
+    /// Title of the feed
+    #[clap(long, default_value = "Factorio API changes")]
+    pub title: String,
+
+    /// Don't take the update times of the entries from the modification times of the docs,
+    /// so the feed only depends on the contents of the docs
+    #[clap(long, action, verbatim_doc_comment)]
+    pub reproducible: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct ArchiveArgs {
+    /// Directory with one sub directory per version, laid out like the local docs
+    #[clap(long)]
+    pub docs_dir: PathBuf,
+
+    /// Directory to write the diffs to, as `<from>_<to>/<stage>.json`
+    #[clap(long)]
+    pub out: PathBuf,
+
+    #[clap(flatten)]
+    pub diff: DiffFlags,
+
+    /// Include the old values of changed fields in the diffs
+    #[clap(long, action)]
+    pub with_old: bool,
+
+    /// Leave out the version pairs with fewer changes than this in both stages together,
+    /// counting each added, removed or changed item and each changed member of classes and prototypes.
+    /// Diffs that already exist are diffed again to count their changes, but aren't written
+    #[clap(long, default_value_t = 0, verbatim_doc_comment)]
+    pub min_changes: usize,
+}
+
+#[derive(Args, Clone)]
+pub struct ImpactArgs {
+    /// Directory of the mod to scan for `.lua` files
+    #[clap(long = "mod")]
+    pub mod_dir: PathBuf,
+
+    /// Version of the docs the mod was written for
+    #[clap(long)]
+    pub from: String,
+
+    /// Version of the docs to check the mod against
+    #[clap(long, default_value = "latest")]
+    pub to: String,
+
+    /// Read the docs from local files
+    #[clap(short, long, action)]
+    pub local: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct WatchArgs {
+    /// Base doc file, its stage is detected on every change
+    #[clap(value_parser)]
+    pub source: PathBuf,
+
+    /// Target doc file to compare against
+    #[clap(value_parser)]
+    pub target: PathBuf,
+
+    #[clap(flatten)]
+    pub diff: DiffFlags,
+
+    /// Print the diff in this format instead of the summary
+    #[clap(long, value_enum)]
+    pub format: Option<render::OutputFormat>,
+
+    /// Include the old values of changed fields in the output
+    #[clap(long, action)]
+    pub with_old: bool,
+
+    /// Milliseconds between checks of the files for changes
+    ///
+    /// The files are polled and their content hashes compared, no file system notifications are used.
+    #[clap(long, default_value_t = 500)]
+    pub interval: u64,
+}
+
+impl DiffFlags {
+    #[must_use]
+    pub fn field_policy(&self) -> FieldPolicy {
+        let mut policy = if self.full {
+            FieldPolicy::all()
+        } else {
+            FieldPolicy::default()
+        };
+
+        if self.descriptions {
+            policy = policy.include(FieldCategory::Descriptions);
+        }
+
+        if self.examples {
+            policy = policy.include(FieldCategory::Examples);
+        }
+
+        policy.overridden(&self.include, &self.exclude)
+    }
+
+    #[must_use]
+    pub fn diff_options(&self) -> DiffOptions {
+        DiffOptions {
+            policy: self.field_policy(),
+            exact_types: self.exact_types,
+            inherit: self.inherit,
+            resolve_inheritance: self.resolve_inheritance,
+            metrics: self.with_metrics,
+            anchor_links: self.anchor_links,
+            context: self.context,
+            word_diff: self.word_diff,
+            keep_link_changes: self.keep_link_changes,
+            directional: self.directional,
+        }
+    }
+
+    /// Runs `f` ignoring the items matching the `--ignore` patterns and all items of the sections
+    /// excluded by `--only` and `--skip`, normalizing texts as set by `--normalize-links` and `--normalize-rule`
+    /// and recognizing renamed concepts by `--rename-marker`.
+    pub fn with_patterns<R>(&self, f: impl FnOnce() -> Result<R>) -> Result<R> {
+        let skipped = Section::value_variants()
+            .iter()
+            .filter(|s| self.skip.contains(s) || !(self.only.is_empty() || self.only.contains(s)));
+        let patterns = self
+            .ignore
+            .iter()
+            .cloned()
+            .chain(skipped.map(|s| s.ignore_pattern()))
+            .collect::<Vec<_>>();
+
+        let renamed = || {
+            format::with_rename_marker(&self.rename_marker, f)
+                .unwrap_or_else(|e| Err(anyhow::anyhow!("Invalid --rename-marker: {e}")))
+        };
+        let normalized = || {
+            format::with_normalized(self.normalize_links, &self.normalize_rule, renamed)
+                .unwrap_or_else(|e| Err(anyhow::anyhow!("Invalid --normalize-rule: {e}")))
+        };
+
+        match format::with_ignored(&patterns, normalized) {
+            Ok(res) => res,
+            Err(e) => anyhow::bail!("Invalid --ignore pattern: {e}"),
+        }
+    }
+}
+
+impl CompareArgs {
+    /// Whether the diff is written while diffing instead of being kept in memory.
+    #[must_use]
+    pub fn streamed(&self) -> bool {
+        #[cfg(feature = "notify")]
+        if self.discord_webhook.is_some() {
+            return false;
+        }
+        #[cfg(feature = "templates")]
+        if self.template.is_some() {
+            return false;
+        }
+
+        self.format == render::OutputFormat::Ndjson
+            && !self.summary
+            && self.themes.is_none()
+            && self.split_output.is_none()
+            && self.type_map.is_none()
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse_args();
+    LOG_FORMAT.set(cli.log_format).ok();
+    QUIET.set(cli.quiet).ok();
+
+    let res = match cli.command {
+        Command::Compare(args) => args.diff.with_patterns(|| compare(&args)),
+        Command::CompareAll(args) => args.diff.with_patterns(|| compare_all(&args)),
+        Command::Invert(args) => invert(&args),
+        Command::MigrateDiff(args) => migrate_diff(&args),
+        Command::Apply(args) => apply(&args),
+        Command::Defines(args) => defines(&args),
+        Command::CheckDefines(args) => check_defines(&args),
+        Command::SelfCheck(args) => self_check(&args),
+        Command::Events(args) => events(&args),
+        Command::Feed(args) => feed(&args),
+        Command::Archive(args) => args.diff.with_patterns(|| archive(&args)),
+        Command::Impact(args) => impact(&args),
+        Command::Watch(args) => args.diff.with_patterns(|| watch(&args)),
+        Command::Stats(args) => stats(&args),
+        Command::Query(args) => query(&args),
+        Command::Versions(args) => versions(&args),
+        Command::Graph(args) => args.diff.with_patterns(|| graph(&args)),
+        Command::Completions(args) => {
+            print!(
+                "{}",
+                completions::generate(args.shell, &mut <Cli as clap::CommandFactory>::command())
+            );
+            Ok(())
+        }
+        Command::Manpage => {
+            print!(
+                "{}",
+                manpage::generate(&mut <Cli as clap::CommandFactory>::command())
+            );
+            Ok(())
+        }
+        #[cfg(feature = "server")]
+        Command::Serve(args) => serve::run(&args),
+        #[cfg(feature = "sql-export")]
+        Command::ExportDb(args) => args.diff.with_patterns(|| export_db(&args)),
+    };
+
+    if let Err(e) = res {
+        eprintln!("{e}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Downloads the doc of a published version, or of any URL if `version` is one.
+fn get(stage: Docs, version: &str) -> Result<Box<[u8]>> {
+    let url = if is_url(version) {
+        version.to_owned()
+    } else {
+        format!("https://lua-api.factorio.com/{version}/{stage}-api.json")
+    };
+
+    let res = reqwest::blocking::get(url)?.error_for_status()?.bytes()?;
+
+    if fapi_diff::zip::is_zip(&res) {
+        return Ok(fapi_diff::zip::read_stage(&res, stage)?.into());
+    }
+
+    Ok((*res).into())
+}
+
+fn is_url(version: &str) -> bool {
+    version.starts_with("https://") || version.starts_with("http://")
+}
+
+/// Reads a doc from a local docs directory, or directly from a file.
+fn get_local(stage: Docs, path: &Path) -> Result<Box<[u8]>> {
+    if path.is_file() {
+        let raw = std::fs::read(path)?;
+
+        if fapi_diff::zip::is_zip(&raw) {
+            return Ok(fapi_diff::zip::read_stage(&raw, stage)?.into());
+        }
+
+        return Ok(raw.into());
+    }
+
+    let doc = path.join(format!("doc-html/{stage}-api.json"));
+
+    #[cfg(feature = "scrape")]
+    if stage == Docs::Runtime && !doc.exists() {
+        // versions before the machine-readable docs only have the HTML pages
+        if let Some(dir) = [path.join("doc-html"), path.to_owned()]
+            .into_iter()
+            .find(|d| fapi_diff::scrape::is_html_docs(d))
+        {
+            warn(format!(
+                "{} has no runtime-api.json, scraping its HTML pages",
+                path.display()
+            ));
+            return Ok(fapi_diff::scrape::runtime(&dir)?.into());
+        }
+    }
+
+    let res = std::fs::read(doc)?;
+
+    Ok(res.into())
+}
+
+/// Source version that stands for an empty doc, see [`CompareArgs::source`].
+const EMPTY_SOURCE: &str = "none";
+
+fn compare(args: &CompareArgs) -> Result<()> {
+    let stage = args.stage;
+    let cache = args.cache_dir.clone().map(Cache::new);
+    let (with_old, options) = (args.with_old, args.diff.diff_options());
+    let baseline = args.source == EMPTY_SOURCE;
+    let local = args.local
+        || [&args.source, &args.target]
+            .into_iter()
+            .any(|v| v != EMPTY_SOURCE && Path::new(v).is_file());
+    let fetch = |version: &str| {
+        if local && !is_url(version) {
+            get_local(stage, Path::new(version))
+        } else {
+            get(stage, version)
+        }
+    };
+
+    let target = fetch(&args.target)?;
+    let (source, target) = if baseline {
+        let target = partial::complete(&target, stage, None, "target")?.into_owned();
+        (fapi_diff::empty_doc(&target)?, target)
+    } else {
+        let source = fetch(&args.source)?;
+        let (source, target) = if local {
+            partial::complete_pair(&source, &target, stage)?
+        } else {
+            (source.as_ref().into(), target.as_ref().into())
+        };
+
+        (source.into_owned(), target.into_owned())
+    };
+
+    stage.check_versions(&source, &target)?;
+
+    if args.count_only {
+        // items are parsed while they're compared, the cache isn't used
+        let counts = stage.count(&source, &target, options)?;
+        println!("{}", args.json.style().to_string(&counts)?);
+
+        print_warnings();
+
+        return Ok(());
+    }
+
+    if args.streamed() {
+        let out = &mut std::io::BufWriter::new(std::io::stdout().lock());
+        // items are parsed while they're diffed, the cache isn't used
+        let count = if args.json.reproducible {
+            // each line is normalized once it's written
+            let mut lines = Vec::new();
+            let count = stage.stream(&source, &target, options, with_old, &mut lines)?;
+            let style = render::JsonStyle {
+                pretty: false,
+                ..args.json.style()
+            };
+            for line in lines.split(|&b| b == b'\n').filter(|l| !l.is_empty()) {
+                let entry = serde_json::from_slice::<serde_json::Value>(line)?;
+                writeln!(out, "{}", style.to_string(&entry)?)?;
+            }
+
+            count
+        } else {
+            stage.stream(&source, &target, options, with_old, out)?
+        };
+        out.flush()?;
 
-#[allow(clippy::struct_excessive_bools)]
-#[derive(Parser, Clone)]
-#[clap(author = crate_authors!(), version, about = crate_description!())]
-pub struct Cli {
-    /// Stage of the docs to use.
-    ///
-    /// Prototype stage supports format versions 4 and 5.
-    /// Runtime stage supports format version 5 only.
-    #[clap(value_parser, verbatim_doc_comment)]
-    pub stage: Docs,
+        print_warnings();
 
-    /// Base version of the docs to use
-    #[clap(value_parser)]
-    pub source: String,
+        eprintln!("=> {count} items changed");
 
-    /// Target version of the docs to compare against
-    /// If not specified, the latest version is used
-    #[clap(value_parser, default_value = "latest")]
-    pub target: String,
+        return Ok(());
+    }
 
-    /// Diff descriptions
-    #[clap(short, long, action)]
-    pub descriptions: bool,
+    let (d, s, t): (
+        Box<dyn format::Info>,
+        Box<dyn format::Info>,
+        Box<dyn format::Info>,
+    ) = match stage {
+        Docs::Prototype => {
+            let c =
+                Comparison::<PrototypeDoc>::with_cache(&source, &target, options, cache.as_ref())?;
+            output(args, &c)?;
 
-    /// Diff examples
-    #[clap(short, long, action)]
-    pub examples: bool,
+            (Box::new(c.diff), Box::new(c.source), Box::new(c.target))
+        }
+        Docs::Runtime => {
+            let c =
+                Comparison::<RuntimeDoc>::with_cache(&source, &target, options, cache.as_ref())?;
+            output(args, &c)?;
 
-    /// Full diff (descriptions, examples, ordering, images, lists)
-    #[clap(short, long, action)]
-    pub full: bool,
+            (Box::new(c.diff), Box::new(c.source), Box::new(c.target))
+        }
+    };
 
-    /// Read source and target from local files
-    #[clap(short, long, action)]
-    pub local: bool,
+    print_warnings();
+
+    s.print_info();
+    eprintln!();
+    t.print_info();
+    eprintln!();
+    d.print_info();
+
+    Ok(())
+}
+
+fn compare_all(args: &CompareAllArgs) -> Result<()> {
+    let fetch = |stage| {
+        if args.local {
+            Ok((
+                get_local(stage, Path::new(&args.source))?,
+                get_local(stage, Path::new(&args.target))?,
+            ))
+        } else {
+            Ok::<_, anyhow::Error>((get(stage, &args.source)?, get(stage, &args.target)?))
+        }
+    };
+
+    let (runtime, prototype) = (fetch(Docs::Runtime)?, fetch(Docs::Prototype)?);
+    let diff = fapi_diff::diff_all(
+        (&runtime.0, &runtime.1),
+        (&prototype.0, &prototype.1),
+        args.diff.diff_options(),
+        args.with_old,
+        args.cross_stage,
+    )?;
+
+    println!("{}", args.json.style().to_string(&diff)?);
+
+    print_warnings();
+
+    Ok(())
 }
 
-thread_local! {static CLI: RefCell<Cli> = RefCell::new(Cli::parse());}
-thread_local! {static SRC_INF: RefCell<format::Common> = RefCell::default();}
-thread_local! {static TRGT_INF: RefCell<format::Common> = RefCell::default();}
+/// Prints the diff or its summary and sends notifications.
+fn output<T>(args: &CompareArgs, c: &Comparison<T>) -> Result<()>
+where
+    T: format::Doc
+        + std::ops::Deref<Target = format::Common>
+        + serde::Serialize
+        + serde::de::DeserializeOwned,
+    T::Diff: serde::Serialize,
+{
+    #[cfg(feature = "templates")]
+    if let Some(path) = &args.template {
+        let template = render::template::Template::parse(&std::fs::read_to_string(path)?)
+            .map_err(|e| anyhow::anyhow!("Invalid template {}: {e}", path.display()))?;
+        print!("{}", c.render_template(&template)?);
 
-fn main() -> ExitCode {
-    let cli = CLI.with_borrow(std::clone::Clone::clone);
+        return Ok(());
+    }
 
-    if let Err(e) = cli.stage.compare(&cli.source, &cli.target) {
-        eprintln!("{e}");
-        return ExitCode::FAILURE;
+    if args.summary {
+        print!("{}", c.summary()?);
+    } else if let Some(themes) = &args.themes {
+        let themes = match themes {
+            Some(path) => serde_json::from_slice(&std::fs::read(path)?)
+                .map_err(|e| anyhow::anyhow!("Invalid themes file {}: {e}", path.display()))?,
+            None => render::themes::Themes::default(),
+        };
+
+        println!("{}", c.render_themed(args.format, &themes)?);
+    } else if let Some(dir) = &args.split_output {
+        c.write_split(
+            dir,
+            args.format,
+            args.diff.diff_options(),
+            args.with_old,
+            args.json.style(),
+        )?;
+    } else if let Some(path) = &args.type_map {
+        let types = serde_json::from_slice(&std::fs::read(path)?)
+            .map_err(|e| anyhow::anyhow!("Invalid type map {}: {e}", path.display()))?;
+
+        println!(
+            "{}",
+            c.render_mapped(args.format, args.with_old, args.json.style(), &types)?
+        );
+    } else if args.format == render::OutputFormat::Ndjson {
+        // only reached when the diff is needed in full for other outputs
+        let out = &mut std::io::BufWriter::new(std::io::stdout().lock());
+        c.write_ndjson(args.diff.diff_options(), args.with_old, out)?;
+        out.flush()?;
+    } else if args.flat_defines && args.format == render::OutputFormat::Json {
+        let mut diff = with_old_values(args.with_old, || serde_json::to_value(&c.diff))?;
+        diff["defines"] = serde_json::to_value(c.flat_defines()?)?;
+
+        println!("{}", args.json.style().to_string(&diff)?);
+    } else if let Err(e) = c.print(args.format, args.with_old, args.json.style()) {
+        anyhow::bail!("Failed to serialize diff: {e}");
+    }
+
+    #[cfg(feature = "notify")]
+    if let Some(webhook) = &args.discord_webhook {
+        let title = format!(
+            "{} {} API changes {} → {}",
+            c.target.application,
+            args.stage,
+            c.source.application_version,
+            c.target.application_version
+        );
+
+        if let Err(e) = notify::discord(webhook, &title, &c.summary()?) {
+            anyhow::bail!("Failed to notify Discord: {e}");
+        }
+    }
+
+    Ok(())
+}
+
+fn invert(args: &InvertArgs) -> Result<()> {
+    fn invert_diff<D>(raw: &[u8], style: render::JsonStyle) -> Result<String>
+    where
+        D: Invert + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let (diff, missing_old) = missing_old_values(|| fapi_diff::parse::<D>(raw, "diff"));
+        let diff = diff?;
+        if missing_old {
+            anyhow::bail!("the diff has no old values to invert, regenerate it with `--with-old`");
+        }
+        let diff = diff.invert();
+
+        Ok(with_old_values(true, || style.to_string(&diff))?)
+    }
+
+    let raw = std::fs::read(&args.diff)?;
+    let style = args.json.style();
+    let out = match args.stage {
+        Docs::Prototype => invert_diff::<PrototypeDocDiff>(&raw, style)?,
+        Docs::Runtime => invert_diff::<RuntimeDocDiff>(&raw, style)?,
     };
 
-    ExitCode::SUCCESS
+    println!("{out}");
+
+    Ok(())
+}
+
+fn migrate_diff(args: &MigrateDiffArgs) -> Result<()> {
+    let mut diff = fapi_diff::parse::<serde_json::Value>(&std::fs::read(&args.diff)?, "diff")?;
+    let version = fapi_diff::migrate::migrate(&mut diff, &args.diff.display().to_string())?;
+    let out = args.json.style().to_string(&diff)?;
+
+    if u64::from(format::DIFF_FORMAT_VERSION) > version {
+        eprintln!(
+            "Migrated diff from format version {version} to {}",
+            format::DIFF_FORMAT_VERSION
+        );
+    }
+
+    if args.in_place {
+        std::fs::write(&args.diff, out + "\n")?;
+    } else {
+        println!("{out}");
+    }
+
+    Ok(())
+}
+
+fn apply(args: &ApplyArgs) -> Result<()> {
+    let mut doc = fapi_diff::parse::<serde_json::Value>(&std::fs::read(&args.base)?, "base")?;
+    let operations =
+        fapi_diff::parse::<Vec<serde_json::Value>>(&std::fs::read(&args.patch)?, "patch")?;
+
+    let applied = render::patch::apply(&mut doc, &operations, &args.only)?;
+    let out = args.json.style().to_string(&doc)?;
+
+    match &args.out {
+        Some(path) => std::fs::write(path, out + "\n")?,
+        None => println!("{out}"),
+    }
+    eprintln!("{applied} of {} operations applied", operations.len());
+
+    Ok(())
+}
+
+fn stats(args: &StatsArgs) -> Result<()> {
+    let stats = fapi_diff::stats::Stats::parse(&std::fs::read(&args.file)?, "doc")?;
+
+    if args.json {
+        println!("{}", args.json_style.style().to_string(&stats)?);
+    } else {
+        print!("{stats}");
+    }
+
+    Ok(())
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Docs {
-    Prototype,
-    Runtime,
+fn query(args: &QueryArgs) -> Result<()> {
+    let value = fapi_diff::query::query(&std::fs::read(&args.file)?, &args.path, "doc")?;
+
+    if args.json {
+        println!("{}", args.json_style.style().to_string(&value)?);
+    } else {
+        print!("{}", fapi_diff::query::Text(&value));
+    }
+
+    Ok(())
 }
 
-impl clap::ValueEnum for Docs {
-    fn value_variants<'a>() -> &'a [Self] {
-        &[Self::Prototype, Self::Runtime]
+fn graph(args: &GraphArgs) -> Result<()> {
+    let stage = args.stage;
+    let fetch = |version: &str| {
+        if args.local || Path::new(version).is_file() {
+            get_local(stage, Path::new(version))
+        } else {
+            get(stage, version)
+        }
+    };
+
+    let source = fetch(&args.source)?;
+    let mut graph = Graph::parse(&source, "source")?;
+
+    if let Some(target) = &args.target {
+        let target = fetch(target)?;
+        stage.check_versions(&source, &target)?;
+
+        let options = args.diff.diff_options();
+        let diff = match stage {
+            Docs::Prototype => {
+                Comparison::<PrototypeDoc>::new(&source, &target, options)?.tree()?
+            }
+            Docs::Runtime => Comparison::<RuntimeDoc>::new(&source, &target, options)?.tree()?,
+        };
+        graph = Graph::delta(graph, Graph::parse(&target, "target")?, &diff);
+
+        if args.changed_only {
+            graph = graph.changed_only();
+        }
     }
 
-    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
-        match self {
-            Self::Prototype => Some(clap::builder::PossibleValue::new("prototype")),
-            Self::Runtime => Some(clap::builder::PossibleValue::new("runtime")),
+    print!("{}", graph.render(args.format));
+
+    Ok(())
+}
+
+fn defines(args: &DefinesArgs) -> Result<()> {
+    fn read(path: &Path, what: &str) -> Result<BTreeSet<String>> {
+        if !path.is_dir() {
+            let raw = std::fs::read(path)?;
+
+            // archives contain the docs of both stages like directories
+            if !fapi_diff::zip::is_zip(&raw) {
+                return fapi_diff::defines::parse(&raw, what);
+            }
+        }
+
+        let mut res = BTreeSet::new();
+
+        for stage in [Docs::Runtime, Docs::Prototype] {
+            if let Ok(raw) = get_local(stage, path) {
+                res.extend(fapi_diff::defines::parse(&raw, what)?);
+            }
         }
+
+        Ok(res)
     }
+
+    let diff = DefinesDiff::new(
+        &read(&args.source, "source")?,
+        &read(&args.target, "target")?,
+    );
+
+    print!("{diff}");
+    eprintln!(
+        "{} define values added, {} removed",
+        diff.added.len(),
+        diff.removed.len()
+    );
+
+    Ok(())
 }
 
-impl std::fmt::Display for Docs {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            Self::Prototype => write!(f, "prototype"),
-            Self::Runtime => write!(f, "runtime"),
+fn versions(args: &VersionsArgs) -> Result<()> {
+    use fapi_diff::versions::{self, Releases, Versions};
+
+    let fetch =
+        |url| -> Result<_> { Ok(reqwest::blocking::get(url)?.error_for_status()?.bytes()?) };
+
+    let local = args
+        .docs_dir
+        .as_deref()
+        .map(fapi_diff::local_versions)
+        .transpose()?
+        .unwrap_or_default();
+
+    if args.cached {
+        for version in versions::cached().iter().chain(&local) {
+            println!("{version}");
         }
+        return Ok(());
+    }
+
+    let published = versions::parse_index(&String::from_utf8_lossy(&fetch(versions::INDEX_URL)?));
+    if let Err(e) = versions::store(&published) {
+        warn(format!("Failed to cache the published versions: {e}"));
+    }
+    let releases = Releases::parse(&fetch(versions::RELEASES_URL)?)?;
+
+    let versions = Versions::new(&published, &local, releases);
+
+    if args.pair {
+        let Some((stable, experimental)) = versions.pair() else {
+            anyhow::bail!("the latest stable and experimental versions don't both have docs");
+        };
+        println!("{stable} {experimental}");
+    } else if args.json {
+        println!("{}", args.json_style.style().to_string(&versions)?);
+    } else {
+        print!("{versions}");
     }
+
+    Ok(())
 }
 
-impl Docs {
-    fn get(self, version: &str) -> Result<Box<[u8]>> {
-        let res = reqwest::blocking::get(format!(
-            "https://lua-api.factorio.com/{version}/{self}-api.json"
-        ))?
-        .bytes()?;
+fn check_defines(args: &CheckDefinesArgs) -> Result<()> {
+    let path = Path::new(&args.version);
+    let fetch = |stage| {
+        if path.exists() {
+            get_local(stage, path)
+        } else {
+            get(stage, &args.version)
+        }
+    };
+
+    let mismatch =
+        fapi_diff::defines::StageMismatch::check(&fetch(Docs::Runtime)?, &fetch(Docs::Prototype)?)?;
 
-        Ok((*res).into())
+    if args.json {
+        println!("{}", args.json_style.style().to_string(&mismatch)?);
+    } else {
+        print!("{mismatch}");
     }
+    eprintln!(
+        "{} define values only in the runtime docs, {} only in the prototype docs",
+        mismatch.runtime_only.len(),
+        mismatch.prototype_only.len()
+    );
+
+    Ok(())
+}
 
-    fn get_local(self, path: &Path) -> Result<Box<[u8]>> {
-        let res = std::fs::read(path.join(format!("doc-html/{self}-api.json")))?;
+fn self_check(args: &SelfCheckArgs) -> Result<()> {
+    let check = fapi_diff::self_check::SelfCheck::run(&std::fs::read(&args.file)?)?;
 
-        Ok(res.into())
+    if args.json {
+        println!("{}", args.json_style.style().to_string(&check)?);
+    } else {
+        print!("{check}");
     }
+    print_warnings();
 
-    #[allow(clippy::too_many_lines)]
-    pub fn compare(self, source: &str, target: &str) -> Result<()> {
-        let (source, target) = if CLI.with_borrow(|c| c.local) {
-            (
-                self.get_local(Path::new(&source))?,
-                self.get_local(Path::new(&target))?,
-            )
+    if !check.passed() {
+        anyhow::bail!(
+            "{} differs from itself, the diff isn't deterministic",
+            args.file.display()
+        );
+    }
+    eprintln!("{} has no changes compared to itself", args.file.display());
+
+    Ok(())
+}
+
+fn events(args: &EventsArgs) -> Result<()> {
+    let fetch = |version: &str| {
+        if args.local || Path::new(version).is_file() {
+            get_local(Docs::Runtime, Path::new(version))
         } else {
-            (self.get(source)?, self.get(target)?)
+            get(Docs::Runtime, version)
+        }
+    };
+
+    let (source, target) = (fetch(&args.source)?, fetch(&args.target)?);
+    Docs::Runtime.check_versions(&source, &target)?;
+
+    let mut diff = EventsDiff::new(
+        &fapi_diff::parse(&source, "source")?,
+        &fapi_diff::parse(&target, "target")?,
+    );
+    if args.breaking_only {
+        diff.changes.retain(|c| c.breaking);
+    }
+
+    if args.json {
+        println!("{}", args.json_style.style().to_string(&diff)?);
+    } else {
+        print!("{diff}");
+        eprintln!(
+            "{} event changes, {} events with breaking changes",
+            diff.changes.len(),
+            diff.breaking.len()
+        );
+    }
+
+    Ok(())
+}
+
+fn feed(args: &FeedArgs) -> Result<()> {
+    let versions = fapi_diff::local_versions(&args.docs_dir)?;
+    let mut entries = Vec::new();
+    let bars = progress_bars(versions.windows(2).map(|pair| pair.join(" → ")).collect());
+
+    for (i, pair) in versions.windows(2).enumerate() {
+        let [from, to] = pair else {
+            continue;
         };
+        if let Some(bars) = &bars {
+            bars.update(progress::Progress::Started(i));
+        }
 
-        let source_info = match serde_json::from_slice::<format::Common>(&source) {
-            Ok(s) => s,
-            Err(e) => {
-                anyhow::bail!("Failed to get common info header from source: {e}");
+        let mut sections = Vec::new();
+        let mut updated = std::time::SystemTime::UNIX_EPOCH;
+
+        for stage in [Docs::Runtime, Docs::Prototype] {
+            let target_path = args
+                .docs_dir
+                .join(to)
+                .join(format!("doc-html/{stage}-api.json"));
+            let (Ok(source), Ok(target)) = (
+                get_local(stage, &args.docs_dir.join(from)),
+                get_local(stage, &args.docs_dir.join(to)),
+            ) else {
+                continue;
+            };
+
+            if let Err(e) = stage.check_versions(&source, &target) {
+                warn_with(
+                    bars.as_ref(),
+                    format!("skipping {stage} docs {from} → {to}: {e}"),
+                );
+                continue;
             }
-        };
 
-        SRC_INF.replace(source_info.clone());
+            let summary = match stage {
+                Docs::Prototype => {
+                    Comparison::<PrototypeDoc>::new(&source, &target, DiffOptions::default())?
+                        .summary()?
+                }
+                Docs::Runtime => {
+                    Comparison::<RuntimeDoc>::new(&source, &target, DiffOptions::default())?
+                        .summary()?
+                }
+            };
 
-        let target_info = match serde_json::from_slice::<format::Common>(&target) {
-            Ok(s) => s,
-            Err(e) => {
-                anyhow::bail!("Failed to get common info header from target: {e}");
+            // reproducible feeds are updated at the epoch
+            let modified = std::fs::metadata(target_path).and_then(|m| m.modified());
+            if let (false, Ok(modified)) = (args.reproducible, modified) {
+                updated = updated.max(modified);
             }
+
+            sections.push(format!("{stage}:\n{summary}"));
+        }
+
+        if let Some(bars) = &bars {
+            bars.update(progress::Progress::Finished(i));
+        }
+        if sections.is_empty() {
+            continue;
+        }
+
+        entries.push(fapi_diff::feed::Entry {
+            id: format!("urn:fapi-diff:{from}:{to}"),
+            title: format!("{from} → {to}"),
+            link: Some(format!("https://lua-api.factorio.com/{to}/")),
+            updated,
+            content: sections.join("\n"),
+        });
+    }
+    drop(bars);
+
+    print_warnings();
+
+    // newest first
+    entries.reverse();
+
+    std::fs::write(
+        &args.out,
+        fapi_diff::feed::atom("urn:fapi-diff:feed", &args.title, &entries),
+    )?;
+    eprintln!(
+        "{} entries written to {}",
+        entries.len(),
+        args.out.display()
+    );
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_lines)]
+fn archive(args: &ArchiveArgs) -> Result<()> {
+    // stable output, so unchanged diffs stay unchanged when they're written again
+    let style = render::JsonStyle {
+        pretty: true,
+        sort_keys: true,
+        reproducible: true,
+    };
+    let versions = fapi_diff::local_versions(&args.docs_dir)?;
+    let (mut pending, mut pairs, mut skipped) = (Vec::new(), Vec::new(), 0);
+    // number of stages still being diffed of each version pair
+    let mut remaining = vec![0; versions.len().saturating_sub(1)];
+
+    for (i, pair) in versions.windows(2).enumerate() {
+        let [from, to] = pair else {
+            continue;
         };
 
-        TRGT_INF.replace(target_info.clone());
-
-        let (d, s, t): (
-            Box<dyn format::Info>,
-            Box<dyn format::Info>,
-            Box<dyn format::Info>,
-        ) = match self {
-            Self::Prototype => {
-                if source_info.api_version < 4 {
-                    anyhow::bail!(
-                        "Source api format is too old! Only api version 4, 5 and 6 are supported"
-                    );
-                }
+        let mut stages = Vec::new();
+        for stage in [Docs::Runtime, Docs::Prototype] {
+            let path = args
+                .out
+                .join(format!("{from}_{to}"))
+                .join(format!("{stage}.json"));
 
-                if target_info.api_version < 4 {
-                    anyhow::bail!(
-                        "Target api format is too old! Only api version 4, 5 and 6 are supported"
-                    );
-                }
+            let exists = path.exists();
+            if exists {
+                skipped += 1;
+            }
 
-                if source_info.api_version > 6 {
-                    anyhow::bail!(
-                        "Source api format is too new! Only api version 4, 5 and 6 are supported"
-                    );
-                }
+            let doc = |version: &str| {
+                args.docs_dir
+                    .join(version)
+                    .join(format!("doc-html/{stage}-api.json"))
+            };
+            let (source, target) = (doc(from), doc(to));
+            if !source.is_file() || !target.is_file() {
+                continue;
+            }
 
-                if target_info.api_version > 6 {
-                    anyhow::bail!(
-                        "Target api format is too new! Only api version 4, 5 and 6 are supported"
-                    );
-                }
+            stages.push((
+                path,
+                exists,
+                format!("{stage} docs {from} → {to}"),
+                source,
+                target,
+            ));
+        }
 
-                let source: PrototypeDoc = match serde_json::from_slice(&source) {
-                    Ok(s) => s,
-                    Err(e) => {
-                        anyhow::bail!("Failed to deserialize source: {e}");
-                    }
-                };
-                let target: PrototypeDoc = match serde_json::from_slice(&target) {
-                    Ok(t) => t,
-                    Err(e) => {
-                        anyhow::bail!("Failed to deserialize target: {e}");
-                    }
-                };
-
-                let diff = source.diff(&target);
-
-                match serde_json::to_string_pretty(&diff) {
-                    Ok(d) => println!("{d}"),
-                    Err(e) => {
-                        anyhow::bail!("Failed to serialize diff: {e}");
-                    }
-                }
+        // existing diffs aren't written again, but still count towards `--min-changes` of their version pair
+        if stages.iter().all(|(_, exists, ..)| *exists) {
+            continue;
+        }
+        for (path, exists, what, source, target) in stages {
+            if exists && args.min_changes == 0 {
+                continue;
+            }
+
+            pending.push((i, path, exists, what));
+            pairs.push((source, target));
+            remaining[i] += 1;
+        }
+    }
+
+    // the change counts of classes and prototypes need their metrics
+    let mut options = args.diff.diff_options();
+    options.metrics |= args.min_changes > 0;
+
+    let (mut written, mut below, mut error) = (0, 0, None);
+    let mut done = std::collections::BTreeMap::<_, Vec<_>>::new();
+    let bars = progress_bars(pending.iter().map(|(_, _, _, what)| what.clone()).collect());
+    let progress = |progress| {
+        if let Some(bars) = &bars {
+            bars.update(progress);
+        }
+    };
+    fapi_diff::diff_many_with_progress(&pairs, &options, progress, |i, diff| {
+        let (pair, path, exists, what) = &pending[i];
+        remaining[*pair] -= 1;
 
-                (Box::new(diff), Box::new(source), Box::new(target))
+        match diff {
+            Ok(diff) => done.entry(*pair).or_default().push((path, *exists, diff)),
+            Err(
+                e @ (fapi_diff::Error::UnsupportedVersion { .. }
+                | fapi_diff::Error::StageMismatch { .. }
+                | fapi_diff::Error::SourceNewer),
+            ) => warn_with(bars.as_ref(), format!("skipping {what}: {e}")),
+            Err(e) => {
+                error.get_or_insert_with(|| e.into());
             }
-            Self::Runtime => {
-                if source_info.api_version < 5 {
-                    anyhow::bail!(
-                        "Source api format is too old! Only api version 5 and 6 are supported"
-                    );
-                }
+        }
 
-                if target_info.api_version < 5 {
-                    anyhow::bail!(
-                        "Target api format is too old! Only api version 5 and 6 are supported"
-                    );
+        // all stages of a version pair are written or left out together
+        if remaining[*pair] > 0 {
+            return;
+        }
+        let diffs = done.remove(pair).unwrap_or_default();
+        if diffs
+            .iter()
+            .map(|(_, _, d)| d.change_count())
+            .sum::<usize>()
+            < args.min_changes
+        {
+            below += 1;
+            return;
+        }
+
+        for (path, _, mut diff) in diffs.into_iter().filter(|(_, exists, _)| !exists) {
+            if !args.diff.with_metrics {
+                match &mut diff {
+                    fapi_diff::StageDiff::Runtime(diff) => diff.metrics.clear(),
+                    fapi_diff::StageDiff::Prototype(diff) => diff.metrics.clear(),
                 }
+            }
 
-                if source_info.api_version > 6 {
-                    anyhow::bail!(
-                        "Source api format is too new! Only api version 5 and 6 are supported"
-                    );
+            let write = || {
+                let out = with_old_values(args.with_old, || style.to_string(&diff))?;
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir)?;
                 }
 
-                if target_info.api_version > 6 {
-                    anyhow::bail!(
-                        "Target api format is too new! Only api version 5 and 6 are supported"
-                    );
+                Ok::<_, anyhow::Error>(std::fs::write(path, out + "\n")?)
+            };
+
+            match write() {
+                Ok(()) => written += 1,
+                Err(e) => {
+                    error.get_or_insert(e);
                 }
+            }
+        }
+    });
+    drop(bars);
+
+    if let Some(e) = error {
+        return Err(e);
+    }
+
+    print_warnings();
+
+    eprintln!(
+        "{written} diffs written to {}, {skipped} already present",
+        args.out.display()
+    );
+    if below > 0 {
+        eprintln!(
+            "{below} version pairs left out with less than {} changes",
+            args.min_changes
+        );
+    }
 
-                if source_info.api_version > target_info.api_version {
-                    anyhow::bail!("Source api format is newer than target api format");
+    Ok(())
+}
+
+#[cfg(feature = "sql-export")]
+fn export_db(args: &ExportDbArgs) -> Result<()> {
+    let options = args.diff.diff_options();
+    let versions = fapi_diff::local_versions(&args.docs_dir)?;
+    let mut db = fapi_diff::database::Database::new();
+    let mut diffs = 0;
+
+    for stage in [Docs::Runtime, Docs::Prototype] {
+        let docs = versions
+            .iter()
+            .filter_map(|v| Some((v, get_local(stage, &args.docs_dir.join(v)).ok()?)))
+            .collect::<Vec<_>>();
+
+        for (version, raw) in &docs {
+            db.add_doc(version, &fapi_diff::parse(raw, version)?);
+        }
+
+        let bars = progress_bars(
+            docs.windows(2)
+                .filter_map(|pair| match pair {
+                    [(from, _), (to, _)] => Some(format!("{stage} docs {from} → {to}")),
+                    _ => None,
+                })
+                .collect(),
+        );
+
+        for (i, pair) in docs.windows(2).enumerate() {
+            let [(from, source), (to, target)] = pair else {
+                continue;
+            };
+
+            if let Some(bars) = &bars {
+                bars.update(progress::Progress::Started(i));
+            }
+
+            if let Err(e) = stage.check_versions(source, target) {
+                warn_with(
+                    bars.as_ref(),
+                    format!("skipping {stage} docs {from} → {to}: {e}"),
+                );
+                if let Some(bars) = &bars {
+                    bars.update(progress::Progress::Finished(i));
                 }
+                continue;
+            }
 
-                let source: RuntimeDoc = match serde_json::from_slice(&source) {
-                    Ok(s) => s,
-                    Err(e) => {
-                        anyhow::bail!(
-                            "Failed to deserialize source: {e}\n{}",
-                            std::str::from_utf8(&source).unwrap_or("[invalid utf-8]")
-                        );
-                    }
-                };
-                let target: RuntimeDoc = match serde_json::from_slice(&target) {
-                    Ok(s) => s,
-                    Err(e) => {
-                        anyhow::bail!("Failed to deserialize target: {e}");
-                    }
-                };
-
-                let diff = source.diff(&target);
-
-                match serde_json::to_string_pretty(&diff) {
-                    Ok(d) => println!("{d}"),
-                    Err(e) => {
-                        anyhow::bail!("Failed to serialize diff: {e}");
-                    }
+            let tree = match stage {
+                Docs::Prototype => {
+                    Comparison::<PrototypeDoc>::new(source, target, options)?.tree()?
                 }
+                Docs::Runtime => Comparison::<RuntimeDoc>::new(source, target, options)?.tree()?,
+            };
+            db.add_changes(from, to, stage, &tree);
+            diffs += 1;
 
-                (Box::new(diff), Box::new(source), Box::new(target))
+            if let Some(bars) = &bars {
+                bars.update(progress::Progress::Finished(i));
             }
+        }
+    }
+
+    print_warnings();
+
+    if args.script {
+        std::fs::write(&args.database, db.finish())?;
+        eprintln!(
+            "SQL script of {} versions and {diffs} diffs written to {}",
+            versions.len(),
+            args.database.display()
+        );
+
+        return Ok(());
+    }
+
+    let mut sqlite3 = std::process::Command::new(&args.sqlite3)
+        .arg(&args.database)
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to run {}, install the sqlite3 shell or write the SQL script with --script: {e}",
+                args.sqlite3.display()
+            )
+        })?;
+
+    if let Some(mut stdin) = sqlite3.stdin.take() {
+        stdin.write_all(db.finish().as_bytes())?;
+    }
+
+    let status = sqlite3.wait()?;
+    if !status.success() {
+        anyhow::bail!("{} failed with {status}", args.sqlite3.display());
+    }
+
+    eprintln!(
+        "{} versions and {diffs} diffs written to {}",
+        versions.len(),
+        args.database.display()
+    );
+
+    Ok(())
+}
+
+fn impact(args: &ImpactArgs) -> Result<()> {
+    let mut index = ApiIndex::default();
+    let mut add = |stage: Docs| -> Result<()> {
+        let (source, target) = if args.local {
+            (
+                get_local(stage, Path::new(&args.from))?,
+                get_local(stage, Path::new(&args.to))?,
+            )
+        } else {
+            (get(stage, &args.from)?, get(stage, &args.to)?)
         };
 
-        s.print_info();
-        eprintln!();
-        t.print_info();
-        eprintln!();
-        d.print_info();
+        stage.check_versions(&source, &target)?;
+
+        match stage {
+            Docs::Prototype => {
+                let c = Comparison::<PrototypeDoc>::new(&source, &target, DiffOptions::default())?;
+                index.add_prototype(&c.source, &c.target, &c.diff);
+            }
+            Docs::Runtime => {
+                let c = Comparison::<RuntimeDoc>::new(&source, &target, DiffOptions::default())?;
+                index.add_runtime(&c.source, &c.target, &c.diff);
+            }
+        }
 
         Ok(())
+    };
+
+    for stage in [Docs::Runtime, Docs::Prototype] {
+        if let Err(e) = add(stage) {
+            warn(format!("skipping {stage} docs: {e}"));
+        }
+    }
+
+    print_warnings();
+
+    let usages = match impact::scan(&args.mod_dir, &index) {
+        Ok(u) => u,
+        Err(e) => anyhow::bail!("Failed to scan {}: {e}", args.mod_dir.display()),
+    };
+
+    println!("{}", serde_json::to_string_pretty(&usages)?);
+    eprintln!("{} usages of changed APIs found", usages.len());
+
+    Ok(())
+}
+
+/// SHA-256 of a file's content, `None` while it can't be read.
+///
+/// Hashing the content instead of comparing modification times catches edits that keep the length and happen
+/// within the granularity of the file system's timestamps.
+fn file_state(path: &Path) -> Option<[u8; 32]> {
+    use sha2::Digest;
+
+    let content = std::fs::read(path).ok()?;
+
+    Some(sha2::Sha256::digest(content).into())
+}
+
+fn watch(args: &WatchArgs) -> Result<()> {
+    let diff = || -> Result<String> {
+        let source = std::fs::read(&args.source)?;
+        let target = std::fs::read(&args.target)?;
+        let stage = Docs::detect(&source)?;
+        let options = args.diff.diff_options();
+
+        if let Some(format) = args.format {
+            return Ok(stage.diff(&source, &target, options, format, args.with_old)?);
+        }
+
+        stage.check_versions(&source, &target)?;
+
+        Ok(match stage {
+            Docs::Prototype => Comparison::<PrototypeDoc>::new(&source, &target, options)?
+                .summary()?
+                .to_string(),
+            Docs::Runtime => Comparison::<RuntimeDoc>::new(&source, &target, options)?
+                .summary()?
+                .to_string(),
+        })
+    };
+
+    let interval = std::time::Duration::from_millis(args.interval);
+    let mut last = None;
+
+    loop {
+        let state = (file_state(&args.source), file_state(&args.target));
+
+        if last.as_ref() != Some(&state) {
+            last = Some(state);
+
+            // clear the terminal and move the cursor to the top left
+            print!("\x1b[2J\x1b[H");
+
+            match diff() {
+                Ok(out) => println!("{}", out.trim_end()),
+                Err(e) => println!("error: {e}"),
+            }
+
+            print_warnings();
+
+            eprintln!(
+                "\nwatching {} and {} for changes, press Ctrl+C to stop",
+                args.source.display(),
+                args.target.display()
+            );
+            std::io::stdout().flush()?;
+        }
+
+        std::thread::sleep(interval);
     }
 }
@@ -0,0 +1,148 @@
+//! A roff man page generated from the command line definition.
+
+use std::fmt::Write as _;
+
+use clap::{Arg, Command};
+
+/// Escapes text for roff, lines starting with a control character are kept as text.
+fn escape(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{line}")
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn help(arg: &Arg) -> String {
+    arg.get_long_help()
+        .or_else(|| arg.get_help())
+        .map(ToString::to_string)
+        .unwrap_or_default()
+}
+
+/// `\fB\-\-format\fR=\fIFORMAT\fR` or `\fI<STAGE>\fR`.
+fn synopsis(arg: &Arg) -> String {
+    let value = arg
+        .get_value_names()
+        .and_then(|names| names.first())
+        .map_or_else(|| arg.get_id().as_str().to_uppercase(), ToString::to_string);
+
+    if arg.is_positional() {
+        return format!("\\fI<{}>\\fR", escape(&value));
+    }
+
+    let flags = arg
+        .get_short()
+        .map(|s| format!("\\fB\\-{s}\\fR"))
+        .into_iter()
+        .chain(
+            arg.get_long()
+                .map(|l| format!("\\fB\\-\\-{}\\fR", escape(l))),
+        )
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if arg.get_action().takes_values() {
+        format!("{flags}=\\fI{}\\fR", escape(&value))
+    } else {
+        flags
+    }
+}
+
+/// Paragraphs of the visible arguments of `cmd`, their possible values and defaults.
+fn arguments(out: &mut String, cmd: &Command) {
+    for arg in cmd.get_arguments().filter(|a| !a.is_hide_set()) {
+        let _ = writeln!(out, ".TP\n{}", synopsis(arg));
+
+        let help = help(arg);
+        if !help.is_empty() {
+            let _ = writeln!(out, "{}", escape(&help));
+        }
+
+        let values = arg
+            .get_possible_values()
+            .iter()
+            .filter(|v| !v.is_hide_set())
+            .map(|v| v.get_name().to_owned())
+            .collect::<Vec<_>>();
+        if !values.is_empty() {
+            let _ = writeln!(
+                out,
+                ".br\n[possible values: {}]",
+                escape(&values.join(", "))
+            );
+        }
+
+        let defaults = arg
+            .get_default_values()
+            .iter()
+            .map(|v| v.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        if !defaults.is_empty() && arg.get_action().takes_values() {
+            let _ = writeln!(out, ".br\n[default: {}]", escape(&defaults.join(", ")));
+        }
+    }
+}
+
+/// The man page of the command line `cli` and its subcommands.
+pub fn generate(cli: &mut Command) -> String {
+    cli.build();
+
+    let name = cli.get_name().to_owned();
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        ".TH {} 1 \"\" \"{name} {}\"",
+        name.to_uppercase().replace('-', "\\-"),
+        cli.get_version().unwrap_or_default()
+    );
+    let _ = writeln!(
+        out,
+        ".SH NAME\n{} \\- {}",
+        escape(&name),
+        escape(&cli.get_about().map(ToString::to_string).unwrap_or_default())
+    );
+    let _ = writeln!(
+        out,
+        ".SH SYNOPSIS\n\\fB{}\\fR [\\fIOPTIONS\\fR] <\\fICOMMAND\\fR>",
+        escape(&name)
+    );
+
+    out.push_str(".SH OPTIONS\n");
+    arguments(&mut out, cli);
+
+    out.push_str(".SH COMMANDS\n");
+    for sub in cli.get_subcommands().filter(|s| !s.is_hide_set()) {
+        let _ = writeln!(out, ".SS {}", escape(sub.get_name()));
+        if let Some(about) = sub.get_long_about().or_else(|| sub.get_about()) {
+            let _ = writeln!(out, "{}", escape(&about.to_string()));
+        }
+
+        let usage = sub
+            .get_arguments()
+            .filter(|a| a.is_positional() && !a.is_hide_set())
+            .map(synopsis)
+            .collect::<Vec<_>>();
+        let _ = writeln!(
+            out,
+            ".PP\n\\fB{} {}\\fR [\\fIOPTIONS\\fR] {}",
+            escape(&name),
+            escape(sub.get_name()),
+            usage.join(" ")
+        );
+        arguments(&mut out, sub);
+    }
+
+    if let Some(authors) = cli.get_author() {
+        let _ = writeln!(out, ".SH AUTHORS\n{}", escape(authors));
+    }
+
+    out
+}
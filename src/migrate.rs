@@ -0,0 +1,176 @@
+//! Upgrades of stored JSON diffs to the current schema, see [`DIFF_FORMAT_VERSION`].
+//!
+//! Version 1 diffs have no `diff_format_version` and no top level `added` and `removed` lists.
+//! Their added and removed items are only part of the section diffs, so the lists are left empty.
+//!
+//! Version 2 diffs list the complete new images of an item whose images changed. Diffs with old values
+//! are converted to the added, removed and recaptioned images, in diffs without them all images are listed as added.
+
+use serde_json::{Map, Value};
+
+use crate::format::DIFF_FORMAT_VERSION;
+
+/// Sections of runtime and prototype diffs, the lists of added and removed items have one entry per section.
+const SECTIONS: [&[&str]; 2] = [
+    &[
+        "classes",
+        "events",
+        "concepts",
+        "defines",
+        "global_objects",
+        "global_functions",
+    ],
+    &["prototypes", "types", "defines"],
+];
+
+/// Upgrades a serialized runtime or prototype diff, or the combined output of `compare-all`.
+///
+/// Returns the version the diff had, diffs of the current version are returned unchanged.
+pub fn migrate(diff: &mut Value, what: &str) -> crate::Result<u64> {
+    let Some(map) = diff.as_object_mut() else {
+        return Err(not_a_diff(what));
+    };
+
+    // compare-all output holds a diff per stage next to other results like `cross_stage`
+    if !map.contains_key("diff_format_version")
+        && ["runtime", "prototype"]
+            .iter()
+            .any(|k| map.contains_key(*k))
+    {
+        let mut oldest = u64::from(DIFF_FORMAT_VERSION);
+        for stage in ["runtime", "prototype"] {
+            if let Some(diff) = map.get_mut(stage) {
+                oldest = oldest.min(migrate(diff, &format!("{what} ({stage})"))?);
+            }
+        }
+
+        return Ok(oldest);
+    }
+
+    let version = match map.get("diff_format_version") {
+        None => 1,
+        Some(v) => v.as_u64().ok_or_else(|| not_a_diff(what))?,
+    };
+
+    if version > u64::from(DIFF_FORMAT_VERSION) {
+        return Err(crate::Error::UnsupportedDiffFormat {
+            what: what.to_owned(),
+            version,
+            supported: DIFF_FORMAT_VERSION,
+        });
+    }
+
+    if version < 2 {
+        let sections = SECTIONS
+            .into_iter()
+            .find(|sections| {
+                sections
+                    .iter()
+                    .any(|s| map.contains_key(*s) && *s != "defines")
+            })
+            .ok_or_else(|| not_a_diff(what))?;
+
+        let names = sections
+            .iter()
+            .map(|s| ((*s).to_owned(), Value::Array(Vec::new())))
+            .collect::<Map<_, _>>();
+        for key in ["added", "removed"] {
+            map.entry(key).or_insert_with(|| names.clone().into());
+        }
+    }
+
+    if version < 3 {
+        for (key, value) in map.iter_mut() {
+            // the context holds complete items of the docs, not changes
+            if key != "context" {
+                images_v3(value);
+            }
+        }
+    }
+
+    map.insert("diff_format_version".to_owned(), DIFF_FORMAT_VERSION.into());
+
+    Ok(version)
+}
+
+/// Converts the image lists of a version 2 diff into image changes.
+fn images_v3(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match value {
+                    Value::Array(new) if key == "images" => {
+                        *value = serde_json::json!({ "added": std::mem::take(new) });
+                    }
+                    Value::Object(change)
+                        if key == "images"
+                            && change.contains_key("old")
+                            && change.contains_key("new") =>
+                    {
+                        *value = images_change(&change["old"], &change["new"]);
+                    }
+                    _ => images_v3(value),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(images_v3),
+        _ => {}
+    }
+}
+
+/// Image changes between two image lists with old values, like [`crate::format::ImagesChange::new`].
+fn images_change(old: &Value, new: &Value) -> Value {
+    let list = |v: &Value| v.as_array().cloned().unwrap_or_default();
+    let (old, new) = (list(old), list(new));
+    let find = |images: &[Value], image: &Value| {
+        images
+            .iter()
+            .find(|i| i.get("filename") == image.get("filename"))
+            .cloned()
+    };
+
+    let mut res = Map::new();
+    let added = new
+        .iter()
+        .filter(|i| find(&old, i).is_none())
+        .cloned()
+        .collect::<Vec<_>>();
+    let removed = old
+        .iter()
+        .filter(|i| find(&new, i).is_none())
+        .cloned()
+        .collect::<Vec<_>>();
+    let captions = old
+        .iter()
+        .filter_map(|o| {
+            let n = find(&new, o)?;
+            let (old, new) = (o.get("caption").cloned(), n.get("caption").cloned());
+            let filename = o.get("filename")?.as_str()?.to_owned();
+
+            (old != new).then(|| {
+                (
+                    filename,
+                    serde_json::json!({ "old": old.unwrap_or_default(), "new": new.unwrap_or_default() }),
+                )
+            })
+        })
+        .collect::<Map<_, _>>();
+
+    for (key, value) in [
+        ("added", Value::from(added)),
+        ("removed", Value::from(removed)),
+        ("captions", Value::from(captions)),
+    ] {
+        if value.as_array().is_some_and(|v| !v.is_empty())
+            || value.as_object().is_some_and(|v| !v.is_empty())
+        {
+            res.insert(key.to_owned(), value);
+        }
+    }
+
+    res.into()
+}
+
+fn not_a_diff(what: &str) -> crate::Error {
+    crate::Error::Render(anyhow::anyhow!("{what} is not a runtime or prototype diff"))
+}
@@ -0,0 +1,53 @@
+use anyhow::Result;
+use fapi_diff::render::summary::Summary;
+
+/// Maximum length of the content of a Discord message.
+const MESSAGE_LIMIT: usize = 2000;
+
+/// Posts the summary to a Discord webhook, split into as many messages as needed.
+pub fn discord(webhook: &str, title: &str, summary: &Summary) -> Result<()> {
+    let client = reqwest::blocking::Client::new();
+
+    for content in messages(title, &summary.to_string()) {
+        let res = client
+            .post(webhook)
+            .header("Content-Type", "application/json")
+            .body(serde_json::json!({ "content": content }).to_string())
+            .send()?;
+
+        if !res.status().is_success() {
+            anyhow::bail!("Discord webhook responded with {}", res.status());
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits the text into messages at line breaks, overlong lines are truncated.
+fn messages(title: &str, text: &str) -> Vec<String> {
+    let mut res = Vec::new();
+    let mut message = format!("**{title}**\n");
+
+    for line in text.lines() {
+        let line = if line.chars().count() >= MESSAGE_LIMIT {
+            let mut line = line.chars().take(MESSAGE_LIMIT - 2).collect::<String>();
+            line.push('…');
+            line
+        } else {
+            line.to_owned()
+        };
+
+        if message.chars().count() + line.chars().count() + 1 > MESSAGE_LIMIT {
+            res.push(std::mem::take(&mut message));
+        }
+
+        message.push_str(&line);
+        message.push('\n');
+    }
+
+    if !message.is_empty() {
+        res.push(message);
+    }
+
+    res
+}
@@ -0,0 +1,220 @@
+//! Doc files that hold only some items, like a single class, completed into full docs.
+
+use std::borrow::Cow;
+
+use serde_json::{Map, Value};
+
+use crate::{format, Docs, Error, Result};
+
+/// Api version of partial docs that can't take it from a full doc.
+const API_VERSION: u8 = 6;
+
+/// Application version of partial docs that can't take it from a full doc.
+const APPLICATION_VERSION: &str = "partial";
+
+/// Completes a partial doc of the `stage` into a full doc, full docs are returned as they are.
+///
+/// A partial doc has no common info header and is either a single item (like a class), a list of items
+/// or an object with some of the sections of a doc (like `{"classes": [...]}`). The section of an item is
+/// told by its fields, e.g. classes have `methods` and events have `data`. The header is taken from
+/// `like` if that's a full doc, usually the other doc of the diff, so their versions match.
+/// `what` names the doc in the error message.
+pub fn complete<'a>(
+    raw: &'a [u8],
+    stage: Docs,
+    like: Option<&[u8]>,
+    what: &str,
+) -> Result<Cow<'a, [u8]>> {
+    let doc = crate::parse::<Value>(raw, what)?;
+    if !is_partial(&doc) {
+        return Ok(Cow::Borrowed(raw));
+    }
+
+    let header = like.and_then(|l| serde_json::from_slice::<format::Common>(l).ok());
+
+    let doc = wrap(doc, stage, header, what)?;
+
+    Ok(Cow::Owned(serde_json::to_vec(&doc)?))
+}
+
+/// Raw `(source, target)` docs, borrowed if they're full docs.
+pub type Pair<'a> = (Cow<'a, [u8]>, Cow<'a, [u8]>);
+
+/// Completes the partial docs of a diff like [`complete`], each taking its header from the other doc.
+///
+/// If only one of the docs is partial, the items of the full doc that the partial doc doesn't have
+/// are left out, so only the items of the partial doc are compared instead of reporting all others
+/// as added or removed.
+pub fn complete_pair<'a>(source: &'a [u8], target: &'a [u8], stage: Docs) -> Result<Pair<'a>> {
+    let (source_doc, target_doc) = (
+        crate::parse::<Value>(source, "source")?,
+        crate::parse::<Value>(target, "target")?,
+    );
+
+    let (source, target) = match (is_partial(&source_doc), is_partial(&target_doc)) {
+        (false, false) => return Ok((Cow::Borrowed(source), Cow::Borrowed(target))),
+        (true, true) => (
+            wrap(source_doc, stage, None, "source")?.into(),
+            wrap(target_doc, stage, None, "target")?.into(),
+        ),
+        (true, false) => {
+            let source = wrap(source_doc, stage, header(&target_doc), "source")?;
+            let target = only_items_of(target_doc, &source);
+
+            (source.into(), target)
+        }
+        (false, true) => {
+            let target = wrap(target_doc, stage, header(&source_doc), "target")?;
+            let source = only_items_of(source_doc, &target);
+
+            (source, target.into())
+        }
+    };
+
+    Ok((
+        Cow::Owned(serde_json::to_vec::<Value>(&source)?),
+        Cow::Owned(serde_json::to_vec::<Value>(&target)?),
+    ))
+}
+
+fn header(doc: &Value) -> Option<format::Common> {
+    serde_json::from_value(doc.clone()).ok()
+}
+
+/// The full `doc` with only the items of the sections of `partial` that `partial` has too.
+fn only_items_of(mut doc: Value, partial: &Map<String, Value>) -> Value {
+    let Value::Object(sections) = &mut doc else {
+        return doc;
+    };
+
+    for (key, section) in sections.iter_mut() {
+        let Some(Value::Array(items)) = partial.get(key) else {
+            continue;
+        };
+        let names = items
+            .iter()
+            .filter_map(|i| i.get("name"))
+            .collect::<Vec<_>>();
+
+        if let Value::Array(section) = section {
+            section.retain(|i| i.get("name").is_some_and(|n| names.contains(&n)));
+        }
+    }
+
+    doc
+}
+
+/// Wraps the items of the partial `doc` into a doc with the `header`, a default header if `None`
+/// or of another stage.
+fn wrap(
+    doc: Value,
+    stage: Docs,
+    header: Option<format::Common>,
+    what: &str,
+) -> Result<Map<String, Value>> {
+    let header = header
+        .filter(|h| Docs::from(h.stage.clone()) == stage)
+        .unwrap_or_else(|| format::Common {
+            application: format::Application::Factorio,
+            stage: match stage {
+                Docs::Prototype => format::Stage::Prototype,
+                Docs::Runtime => format::Stage::Runtime,
+            },
+            application_version: APPLICATION_VERSION.to_owned(),
+            api_version: API_VERSION,
+        });
+
+    let mut res = match serde_json::to_value(header)? {
+        Value::Object(header) => header,
+        _ => Map::new(),
+    };
+    for section in sections(stage) {
+        res.insert((*section).to_owned(), Value::Array(Vec::new()));
+    }
+
+    let items = match doc {
+        Value::Object(doc) if doc.keys().any(|k| sections(stage).contains(&k.as_str())) => {
+            for (key, value) in doc {
+                if !sections(stage).contains(&key.as_str()) {
+                    return Err(partial(
+                        what,
+                        format!("{key} isn't a section of {stage} docs"),
+                    ));
+                }
+
+                res.insert(key, value);
+            }
+
+            Vec::new()
+        }
+        Value::Array(items) => items,
+        item => vec![item],
+    };
+
+    for item in items {
+        let Some(section) = section(stage, &item) else {
+            return Err(partial(
+                what,
+                format!("can't tell which section of {stage} docs the item belongs to"),
+            ));
+        };
+
+        if let Some(Value::Array(section)) = res.get_mut(section) {
+            section.push(item);
+        }
+    }
+
+    Ok(res)
+}
+
+/// Whether the doc lacks the common info header, docs with a broken header aren't partial.
+fn is_partial(doc: &Value) -> bool {
+    match doc {
+        Value::Object(doc) => !["application", "stage", "api_version"]
+            .iter()
+            .any(|k| doc.contains_key(*k)),
+        _ => true,
+    }
+}
+
+const fn sections(stage: Docs) -> &'static [&'static str] {
+    match stage {
+        Docs::Runtime => &[
+            "classes",
+            "events",
+            "concepts",
+            "defines",
+            "global_objects",
+            "global_functions",
+        ],
+        Docs::Prototype => &["prototypes", "types", "defines"],
+    }
+}
+
+/// Section of a single item of the `stage`, told by its fields.
+fn section(stage: Docs, item: &Value) -> Option<&'static str> {
+    let item = item.as_object()?;
+    let has = |key: &str| item.contains_key(key);
+
+    if !has("name") {
+        return None;
+    }
+
+    Some(match stage {
+        Docs::Runtime if has("methods") || has("attributes") || has("operators") => "classes",
+        Docs::Runtime if has("data") => "events",
+        Docs::Runtime if has("parameters") || has("return_values") => "global_functions",
+        Docs::Runtime if has("type") => "concepts",
+        Docs::Prototype if has("typename") => "prototypes",
+        Docs::Prototype if has("type") => "types",
+        _ if has("values") || has("subkeys") => "defines",
+        _ => return None,
+    })
+}
+
+fn partial(what: &str, reason: String) -> Error {
+    Error::Partial {
+        what: what.to_owned(),
+        reason,
+    }
+}
@@ -0,0 +1,131 @@
+//! Progress of runs over many pairs of docs, like [`crate::diff_many_with_progress`].
+
+use std::{
+    collections::BTreeSet,
+    io::{IsTerminal, Write},
+    sync::Mutex,
+};
+
+/// A pair of docs that started or finished diffing, identified by its index in the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    Started(usize),
+    Finished(usize),
+}
+
+/// Width of the bar of the finished pairs in characters.
+const WIDTH: usize = 30;
+
+/// Progress bars on stderr: a bar of the finished pairs and a line per pair being diffed.
+///
+/// Only drawn with the `progress` feature and if stderr is a terminal.
+/// The bars are cleared when they're dropped.
+#[derive(Debug)]
+pub struct Bars {
+    labels: Vec<String>,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    running: BTreeSet<usize>,
+    done: usize,
+    /// Number of lines drawn last
+    drawn: usize,
+}
+
+impl Bars {
+    /// Bars of a run over pairs with these `labels`, e.g. `runtime docs 1.1.0 → 1.1.1`.
+    ///
+    /// `None` if the bars can't be drawn.
+    #[must_use]
+    pub fn new(labels: Vec<String>) -> Option<Self> {
+        if !cfg!(feature = "progress") || !std::io::stderr().is_terminal() || labels.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            labels,
+            state: Mutex::default(),
+        })
+    }
+
+    pub fn update(&self, progress: Progress) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+
+        match progress {
+            Progress::Started(i) => {
+                state.running.insert(i);
+            }
+            Progress::Finished(i) => {
+                state.running.remove(&i);
+                state.done += 1;
+            }
+        }
+
+        self.draw(&mut state);
+    }
+
+    /// Clears the bars while `f` runs, e.g. to print warnings.
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        let Ok(mut state) = self.state.lock() else {
+            return f();
+        };
+
+        clear(&mut state);
+        let res = f();
+        self.draw(&mut state);
+
+        res
+    }
+
+    fn draw(&self, state: &mut State) {
+        clear(state);
+
+        let total = self.labels.len();
+        let filled = WIDTH * state.done.min(total) / total;
+        let mut lines = vec![format!(
+            "[{}{}] {}/{total}",
+            "=".repeat(filled),
+            " ".repeat(WIDTH - filled),
+            state.done
+        )];
+        lines.extend(
+            state
+                .running
+                .iter()
+                .filter_map(|&i| self.labels.get(i))
+                .map(|label| format!("  {label}")),
+        );
+
+        let mut err = std::io::stderr().lock();
+        for line in &lines {
+            writeln!(err, "{line}").ok();
+        }
+        err.flush().ok();
+
+        state.drawn = lines.len();
+    }
+}
+
+impl Drop for Bars {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.state.lock() {
+            clear(&mut state);
+        }
+    }
+}
+
+/// Erases the lines drawn last.
+fn clear(state: &mut State) {
+    let mut err = std::io::stderr().lock();
+    for _ in 0..state.drawn {
+        // up a line and erase it
+        write!(err, "\x1b[1A\x1b[2K").ok();
+    }
+    err.flush().ok();
+
+    state.drawn = 0;
+}
@@ -0,0 +1,160 @@
+//! Items of a single doc looked up by a path like `classes.LuaEntity.methods.teleport`.
+
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::{
+    format::{prototype::PrototypeDoc, runtime::RuntimeDoc},
+    Docs, Error, Result,
+};
+
+/// Matches every item of a list or every field of an object.
+pub const WILDCARD: &str = "*";
+
+/// Fields of items that hold their nested items, e.g. the values of defines.
+const NESTED: [&str; 2] = ["subkeys", "values"];
+
+/// The value at `path` of the raw doc `raw` of any stage.
+///
+/// The path is split at dots, each segment picks a field of an object, the item of a list with
+/// that name (or an image with that filename) or the item at that index. A segment that's no field
+/// of an item picks from its nested items instead, so `defines.events.on_tick` finds the define value.
+/// A [`WILDCARD`] segment picks from every item, the result is an object keyed by the item names.
+pub fn query(raw: &[u8], path: &str, what: &str) -> Result<Value> {
+    // parsing into the format first to reject docs that aren't docs and to normalize their items
+    let doc = match Docs::detect(raw)? {
+        Docs::Runtime => serde_json::to_value(crate::parse::<RuntimeDoc>(raw, what)?)?,
+        Docs::Prototype => serde_json::to_value(crate::parse::<PrototypeDoc>(raw, what)?)?,
+    };
+
+    let segments = path
+        .split('.')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+
+    resolve(&doc, &segments).ok_or_else(|| {
+        let found = (0..segments.len())
+            .take_while(|&i| resolve(&doc, &segments[..=i]).is_some())
+            .count();
+
+        Error::UnknownPath {
+            path: path.to_owned(),
+            segment: segments.get(found).copied().unwrap_or_default().to_owned(),
+        }
+    })
+}
+
+fn resolve(value: &Value, path: &[&str]) -> Option<Value> {
+    let Some((&segment, rest)) = path.split_first() else {
+        return Some(value.clone());
+    };
+
+    if segment == WILDCARD {
+        let items = match value {
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| (name(item).unwrap_or_else(|| i.to_string()), item))
+                .collect::<Vec<_>>(),
+            Value::Object(fields) => fields.iter().map(|(k, v)| (k.clone(), v)).collect(),
+            _ => return None,
+        };
+
+        let res = items
+            .into_iter()
+            .filter_map(|(name, item)| Some((name, resolve(item, rest)?)))
+            .collect::<Map<_, _>>();
+
+        return (!res.is_empty()).then_some(Value::Object(res));
+    }
+
+    resolve(child(value, segment)?, rest)
+}
+
+fn child<'a>(value: &'a Value, segment: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(fields) => fields.get(segment).or_else(|| {
+            NESTED
+                .iter()
+                .filter_map(|key| fields.get(*key))
+                .find_map(|nested| child(nested, segment))
+        }),
+        Value::Array(items) => items
+            .iter()
+            .find(|item| name(item).is_some_and(|n| n == segment))
+            .or_else(|| items.get(segment.parse::<usize>().ok()?)),
+        _ => None,
+    }
+}
+
+fn name(item: &Value) -> Option<String> {
+    ["name", "filename"]
+        .iter()
+        .find_map(|key| item.get(key)?.as_str())
+        .map(ToOwned::to_owned)
+}
+
+/// Human readable view of a value found by [`query`].
+///
+/// Scalars are printed as they are, lists of named items as their names.
+pub struct Text<'a>(pub &'a Value);
+
+impl fmt::Display for Text<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Value::Object(fields) = self.0 else {
+            return writeln!(f, "{}", inline(self.0));
+        };
+
+        if let Some(name) = fields.get("name").and_then(Value::as_str) {
+            writeln!(f, "{name}")?;
+        }
+
+        if let Some(description) = fields
+            .get("description")
+            .and_then(Value::as_str)
+            .filter(|d| !d.is_empty())
+        {
+            writeln!(f, "{description}")?;
+        }
+
+        let fields = fields
+            .iter()
+            .filter(|(k, v)| !matches!(k.as_str(), "name" | "description") && !is_empty(v))
+            .collect::<Vec<_>>();
+        let width = fields
+            .iter()
+            .map(|(k, _)| k.len())
+            .max()
+            .unwrap_or_default();
+
+        for (key, value) in fields {
+            writeln!(f, " - {key:width$} {}", inline(value))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(fields) => fields.is_empty(),
+        Value::Bool(_) | Value::Number(_) => false,
+    }
+}
+
+fn inline(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.iter().all(|i| name(i).is_some()) => {
+            items.iter().filter_map(name).collect::<Vec<_>>().join(", ")
+        }
+        Value::Object(fields) if fields.values().all(Value::is_object) => {
+            fields.keys().cloned().collect::<Vec<_>>().join(", ")
+        }
+        _ => value.to_string(),
+    }
+}
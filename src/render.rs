@@ -0,0 +1,407 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    format::{with_old_values, Common, ItemStatus},
+    links::Links,
+};
+
+pub mod bbcode;
+pub mod csv;
+pub mod deprecation;
+pub mod flat;
+pub mod markdown;
+pub mod patch;
+pub mod summary;
+#[cfg(feature = "templates")]
+pub mod template;
+pub mod themes;
+pub mod tree;
+pub mod type_map;
+pub mod visit;
+
+/// Output format of the diff.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Nested JSON mirroring the doc structure
+    #[default]
+    Json,
+
+    /// Flat JSON list of changes, each with a path and its old and new value
+    Flat,
+
+    /// Markdown changelog with a heading per section and changed item
+    Markdown,
+
+    /// Changelog for the Factorio forums in bbcode markup, linking to the official docs
+    #[value(name = "bbcode")]
+    #[serde(rename = "bbcode")]
+    BBCode,
+
+    /// One JSON object per changed item, written while diffing
+    Ndjson,
+
+    /// Comma separated table with one row per change for spreadsheets
+    Csv,
+
+    /// Tab separated table with one row per change for spreadsheets
+    Tsv,
+
+    /// RFC 6902 operations turning the source doc into the target doc
+    JsonPatch,
+
+    /// Interactive terminal browser
+    #[cfg(feature = "tui")]
+    Tui,
+}
+
+/// Serialization of the JSON output formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JsonStyle {
+    /// Indent nested values, one value per line
+    pub pretty: bool,
+
+    /// Sort the keys of all objects, changed items and members are keyed by their sorted names either way
+    pub sort_keys: bool,
+
+    /// Byte-stable output for the same inputs: sorted keys and floats normalized by [`normalize`]
+    pub reproducible: bool,
+}
+
+impl Default for JsonStyle {
+    fn default() -> Self {
+        Self {
+            pretty: true,
+            sort_keys: false,
+            reproducible: false,
+        }
+    }
+}
+
+impl JsonStyle {
+    /// Serializes `value` in this style.
+    pub fn to_string<T: Serialize>(self, value: &T) -> serde_json::Result<String> {
+        if self.sort_keys || self.reproducible {
+            // objects of `serde_json::Value` are sorted maps
+            let sorted = Self {
+                sort_keys: false,
+                reproducible: false,
+                ..self
+            };
+
+            let mut value = serde_json::to_value(value)?;
+            if self.reproducible {
+                normalize(&mut value);
+            }
+
+            return sorted.to_string(&value);
+        }
+
+        if self.pretty {
+            serde_json::to_string_pretty(value)
+        } else {
+            serde_json::to_string(value)
+        }
+    }
+}
+
+/// Precision floats are rounded to by [`normalize`], like the tolerance orders are compared with.
+const FLOAT_PRECISION: f64 = 1e6;
+
+/// Normalizes the floats in `value` so they're written the same on all platforms:
+/// rounded to 6 decimals and without negative zeros. Integers are kept as they are.
+pub fn normalize(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Number(n) if n.is_f64() => {
+            let f = n.as_f64().unwrap_or_default();
+            // `+ 0.0` turns -0.0 into 0.0
+            let rounded = (f * FLOAT_PRECISION).round() / FLOAT_PRECISION + 0.0;
+            if let Some(rounded) = serde_json::Number::from_f64(rounded) {
+                *n = rounded;
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(normalize),
+        serde_json::Value::Object(map) => map.values_mut().for_each(normalize),
+        _ => {}
+    }
+}
+
+/// Renders the diff in the requested format.
+///
+/// `source` and `target` are the compared docs, they are used to resolve old / new values.
+/// `with_old` includes the old values of changed fields in the JSON output,
+/// `style` controls how the JSON formats are serialized,
+/// a reproducible style also normalizes the values of the formats built from the flattened changes.
+pub fn render<D, S>(
+    format: OutputFormat,
+    with_old: bool,
+    style: JsonStyle,
+    diff: &D,
+    source: &S,
+    target: &S,
+) -> anyhow::Result<String>
+where
+    D: Serialize,
+    S: Serialize,
+{
+    render_mapped(
+        format,
+        with_old,
+        style,
+        &type_map::TypeMapper::default(),
+        diff,
+        source,
+        target,
+    )
+}
+
+/// Renders the diff in the requested format with the type names mapped by `types`.
+///
+/// Types are mapped in the formats built from the flattened changes: flat, markdown, bbcode, csv and tsv.
+/// The JSON formats stay true to the docs. See [`render`] for the other arguments.
+pub fn render_mapped<D, S>(
+    format: OutputFormat,
+    with_old: bool,
+    style: JsonStyle,
+    types: &type_map::TypeMapper,
+    diff: &D,
+    source: &S,
+    target: &S,
+) -> anyhow::Result<String>
+where
+    D: Serialize,
+    S: Serialize,
+{
+    let flatten = |diff, source, target| {
+        let mut changes = flatten(diff, source, target)?;
+        types.apply(&mut changes);
+        if style.reproducible {
+            for change in &mut changes {
+                normalize(&mut change.old);
+                normalize(&mut change.new);
+            }
+        }
+
+        anyhow::Ok(changes)
+    };
+
+    let out = match format {
+        OutputFormat::Json => with_old_values(with_old, || style.to_string(diff))?,
+        // the flat formats always resolve old values from the source doc
+        OutputFormat::Flat => style.to_string(&flatten(diff, source, target)?)?,
+        OutputFormat::Markdown => {
+            let mut out = Vec::new();
+            visit::Renderer::render(
+                &mut markdown::Markdown::new(&mut out)
+                    .with_flat_defines(flat_defines(diff, source, target)?)
+                    .with_deprecations(deprecations(source, target)?),
+                &flatten(diff, source, target)?,
+            )?;
+
+            String::from_utf8(out)?
+        }
+        OutputFormat::BBCode => {
+            let links = links(&serde_json::to_value(target)?);
+
+            let mut out = Vec::new();
+            visit::Renderer::render(
+                &mut bbcode::BBCode::new(&mut out, links)
+                    .with_deprecations(deprecations(source, target)?),
+                &flatten(diff, source, target)?,
+            )?;
+
+            String::from_utf8(out)?
+        }
+        OutputFormat::JsonPatch => {
+            let diff_value = with_old_values(false, || serde_json::to_value(diff))?;
+            let mut source_value = serde_json::to_value(source)?;
+            // renamed items are found by their new names, the patch renames them
+            for (section, old, new) in flat::renames(&diff_value) {
+                for item in source_value
+                    .get_mut(section)
+                    .and_then(serde_json::Value::as_array_mut)
+                    .into_iter()
+                    .flatten()
+                    .filter(|i| i.get("name").and_then(serde_json::Value::as_str) == Some(old))
+                {
+                    item["name"] = new.into();
+                }
+            }
+
+            style.to_string(&patch::operations(
+                &flat::flatten(
+                    &diff_value,
+                    &serde_json::to_value(source)?,
+                    &serde_json::to_value(target)?,
+                ),
+                &source_value,
+                &serde_json::to_value(target)?,
+            ))?
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            let target_value = serde_json::to_value(target)?;
+            let stage = target_value
+                .get("stage")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or_default()
+                .to_owned();
+
+            let mut out = Vec::new();
+            let csv = csv::Csv::new(&mut out, stage);
+            let mut csv = if format == OutputFormat::Tsv {
+                csv.tab_separated()
+            } else {
+                csv
+            };
+            visit::Renderer::render(&mut csv, &flatten(diff, source, target)?)?;
+
+            String::from_utf8(out)?
+        }
+        OutputFormat::Ndjson => {
+            anyhow::bail!("The ndjson format is written while diffing, use `Comparison::stream`")
+        }
+        #[cfg(feature = "tui")]
+        OutputFormat::Tui => anyhow::bail!("The tui format can only be shown interactively"),
+    };
+
+    Ok(out)
+}
+
+/// Renders the diff as a changelog grouped by the themes of the changed items, see [`themes::Themes`].
+///
+/// Only the markdown and bbcode formats can be grouped. Defines are listed by theme too,
+/// instead of by their full Lua path. See [`render`] for the other arguments.
+pub fn render_themed<D, S>(
+    format: OutputFormat,
+    themes: &themes::Themes,
+    diff: &D,
+    source: &S,
+    target: &S,
+) -> anyhow::Result<String>
+where
+    D: Serialize,
+    S: Serialize,
+{
+    let grouped = themes.group(flatten(diff, source, target)?);
+
+    let mut out = Vec::new();
+    match format {
+        OutputFormat::Markdown => visit::Renderer::render(
+            &mut markdown::Markdown::new(&mut out).with_deprecations(deprecations(source, target)?),
+            &grouped,
+        )?,
+        OutputFormat::BBCode => visit::Renderer::render(
+            &mut bbcode::BBCode::new(&mut out, links(&serde_json::to_value(target)?))
+                .with_deprecations(deprecations(source, target)?),
+            &grouped,
+        )?,
+        _ => anyhow::bail!("Only the markdown and bbcode formats can be grouped by theme"),
+    }
+
+    Ok(String::from_utf8(out)?)
+}
+
+/// Summarizes the diff as a compact textual changelog.
+///
+/// See [`render`] for the arguments.
+pub fn summary<D, S>(diff: &D, source: &S, target: &S) -> anyhow::Result<summary::Summary>
+where
+    D: Serialize,
+    S: Serialize,
+{
+    Ok(summary::Summary::new(&flatten(diff, source, target)?)
+        .with_deprecations(deprecations(source, target)?))
+}
+
+/// The diff as a tree of [`tree::Node`]s that can be walked without knowing the diff types.
+///
+/// See [`render`] for the arguments.
+pub fn tree<D, S>(diff: &D, source: &S, target: &S) -> anyhow::Result<tree::Node>
+where
+    D: Serialize,
+    S: Serialize,
+{
+    Ok(tree::Node::from_changes(&flatten(diff, source, target)?))
+}
+
+/// Items that became deprecated or stopped being deprecated between the docs, see [`deprecation::find`].
+pub fn deprecations<S: Serialize>(
+    source: &S,
+    target: &S,
+) -> anyhow::Result<Vec<deprecation::Deprecation>> {
+    Ok(deprecation::find(
+        &serde_json::to_value(source)?,
+        &serde_json::to_value(target)?,
+    ))
+}
+
+/// Flattens the diff, resolving old and new values from the docs.
+fn flatten<D, S>(diff: &D, source: &S, target: &S) -> anyhow::Result<Vec<flat::FlatChange>>
+where
+    D: Serialize,
+    S: Serialize,
+{
+    Ok(flat::flatten(
+        &with_old_values(false, || serde_json::to_value(diff))?,
+        &serde_json::to_value(source)?,
+        &serde_json::to_value(target)?,
+    ))
+}
+
+/// Changed defines and define values keyed by their full Lua path, see [`crate::defines::flatten`].
+///
+/// See [`render`] for the arguments.
+pub fn flat_defines<D, S>(
+    diff: &D,
+    source: &S,
+    target: &S,
+) -> anyhow::Result<BTreeMap<String, ItemStatus>>
+where
+    D: Serialize,
+    S: Serialize,
+{
+    Ok(crate::defines::flatten(
+        &with_old_values(false, || serde_json::to_value(diff))?,
+        &serde_json::to_value(source)?,
+        &serde_json::to_value(target)?,
+    ))
+}
+
+/// Links to the serialized `target` doc.
+fn links(target: &serde_json::Value) -> Links {
+    Common::deserialize(target).map_or_else(
+        |_| Links::new("latest".to_owned(), 0),
+        |info| Links::of(&info),
+    )
+}
+
+/// Prints the diff to stdout in the requested format.
+///
+/// See [`render`] for the arguments.
+pub fn print<D, S>(
+    format: OutputFormat,
+    with_old: bool,
+    style: JsonStyle,
+    diff: &D,
+    source: &S,
+    target: &S,
+) -> anyhow::Result<()>
+where
+    D: Serialize,
+    S: Serialize,
+{
+    #[cfg(feature = "tui")]
+    if format == OutputFormat::Tui {
+        return crate::tui::browse(
+            &with_old_values(false, || serde_json::to_value(diff))?,
+            &serde_json::to_value(source)?,
+            &serde_json::to_value(target)?,
+        );
+    }
+
+    println!("{}", render(format, with_old, style, diff, source, target)?);
+
+    Ok(())
+}
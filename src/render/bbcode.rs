@@ -0,0 +1,153 @@
+use std::io::Write;
+
+use anyhow::Result;
+
+use crate::links::Links;
+
+use super::{
+    deprecation::{Deprecation, Transition},
+    flat::FlatChange,
+    markdown::describe,
+    visit::{DiffNode, Renderer, Visitor},
+};
+
+/// `BBCode` changelog for the Factorio forums, mirroring [`super::markdown::Markdown`].
+///
+/// Changed items link to their page in the official docs of the target version.
+pub struct BBCode<W> {
+    out: W,
+    links: Links,
+    in_list: bool,
+    deprecations: Vec<Deprecation>,
+}
+
+impl<W: Write> BBCode<W> {
+    /// `links` point to the target docs.
+    pub const fn new(out: W, links: Links) -> Self {
+        Self {
+            out,
+            links,
+            in_list: false,
+            deprecations: Vec::new(),
+        }
+    }
+
+    /// Lists these deprecation changes before the changes, see [`super::deprecation::find`].
+    #[must_use]
+    pub fn with_deprecations(mut self, deprecations: Vec<Deprecation>) -> Self {
+        self.deprecations = deprecations;
+        self
+    }
+
+    fn close_list(&mut self) -> Result<()> {
+        if self.in_list {
+            writeln!(self.out, "[/list]")?;
+            self.in_list = false;
+        }
+
+        Ok(())
+    }
+
+    /// Heading of a changed item, starts the list of its changes.
+    fn item(&mut self, name: &str, url: Option<String>) -> Result<()> {
+        self.close_list()?;
+
+        match url {
+            Some(url) => writeln!(self.out, "[b][url={url}]{name}[/url][/b]")?,
+            None => writeln!(self.out, "[b]{name}[/b]")?,
+        }
+
+        writeln!(self.out, "[list]")?;
+        self.in_list = true;
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Renderer for BBCode<W> {
+    fn render(&mut self, diff: &dyn DiffNode) -> Result<()> {
+        if !self.deprecations.is_empty() {
+            writeln!(self.out, "[size=150][b]deprecations[/b][/size]")?;
+            writeln!(self.out, "[list]")?;
+
+            for deprecation in &self.deprecations {
+                let what = match deprecation.transition {
+                    Transition::Deprecated => "deprecated",
+                    Transition::Undeprecated => "no longer deprecated",
+                };
+                let path = deprecation
+                    .path
+                    .split('/')
+                    .map(|p| p.replace("~1", "/").replace("~0", "~"))
+                    .collect::<Vec<_>>();
+
+                match self
+                    .links
+                    .url(&path.iter().map(String::as_str).collect::<Vec<_>>())
+                {
+                    Some(url) => writeln!(
+                        self.out,
+                        "[*][url={url}]{}[/url]: {what}",
+                        deprecation.name()
+                    )?,
+                    None => writeln!(self.out, "[*]{}: {what}", deprecation.name())?,
+                }
+            }
+
+            writeln!(self.out, "[/list]")?;
+        }
+
+        diff.accept(self)?;
+        self.close_list()
+    }
+}
+
+impl<W: Write> Visitor for BBCode<W> {
+    fn enter(&mut self, path: &[&str]) -> Result<()> {
+        match path {
+            [section] => {
+                self.close_list()?;
+                writeln!(self.out, "[size=150][b]{section}[/b][/size]")?;
+            }
+            [section, item] => self.item(item, self.links.url(&[section, item]))?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn group(&mut self, name: &str) -> Result<()> {
+        self.close_list()?;
+        writeln!(self.out, "[size=200][b]{name}[/b][/size]")?;
+
+        Ok(())
+    }
+
+    fn change(&mut self, change: &FlatChange) -> Result<()> {
+        let path = change.path.split('/').collect::<Vec<_>>();
+        let what = describe(change, |v| format!("[i]{v}[/i]"));
+
+        if let [section, item] = path[..] {
+            // whole items are added or removed, they get their own heading
+            // removed items don't exist in the linked docs
+            let url = (!change.new.is_null())
+                .then(|| self.links.url(&[section, item]))
+                .flatten();
+            self.item(item, url)?;
+            writeln!(self.out, "[*]{what}")?;
+        } else {
+            let field = path.get(2..).unwrap_or_default().join(".");
+            writeln!(self.out, "[*][b]{field}[/b]: {what}")?;
+        }
+
+        Ok(())
+    }
+
+    fn leave(&mut self, path: &[&str]) -> Result<()> {
+        if path.len() == 2 {
+            self.close_list()?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use std::io::Write;
+
+use anyhow::Result;
+use serde_json::Value;
+
+use super::{
+    flat::FlatChange,
+    visit::{DiffNode, Renderer, Visitor},
+};
+
+/// Columns of the rows, one row per change.
+const HEADER: [&str; 8] = [
+    "stage",
+    "category",
+    "item",
+    "member",
+    "field",
+    "change_kind",
+    "old",
+    "new",
+];
+
+/// Keys of the lists whose entries are reported in the `member` column.
+pub(crate) const MEMBERS: [&str; 7] = [
+    "methods",
+    "attributes",
+    "operators",
+    "properties",
+    "data",
+    "values",
+    "custom_properties",
+];
+
+/// Table with one row per change for spreadsheets, comma or tab separated.
+pub struct Csv<W> {
+    out: W,
+    stage: String,
+    delimiter: char,
+}
+
+impl<W: Write> Csv<W> {
+    /// Comma separated rows of the changes of the `stage` docs.
+    pub const fn new(out: W, stage: String) -> Self {
+        Self {
+            out,
+            stage,
+            delimiter: ',',
+        }
+    }
+
+    /// Separates the columns with tabs instead of commas.
+    #[must_use]
+    pub const fn tab_separated(mut self) -> Self {
+        self.delimiter = '\t';
+        self
+    }
+
+    /// Quotes the cell if it contains the delimiter, quotes or line breaks.
+    fn cell(&self, text: &str) -> String {
+        if text.contains([self.delimiter, '"', '\n', '\r']) {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text.to_owned()
+        }
+    }
+
+    fn write_row(&mut self, cells: &[&str]) -> Result<()> {
+        let row = cells
+            .iter()
+            .map(|c| self.cell(c))
+            .collect::<Vec<_>>()
+            .join(&self.delimiter.to_string());
+
+        writeln!(self.out, "{row}")?;
+        Ok(())
+    }
+}
+
+/// Strings are written as they are, other values as compact JSON and missing values as empty cells.
+pub(crate) fn value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+impl<W: Write> Renderer for Csv<W> {
+    fn render(&mut self, diff: &dyn DiffNode) -> Result<()> {
+        self.write_row(&HEADER)?;
+        diff.accept(self)
+    }
+}
+
+/// Splits the unescaped path of a change into its `category`, `item`, `member` and `field` columns.
+pub(crate) fn columns(path: &[String]) -> [String; 4] {
+    let (member, field) = match path.get(2..) {
+        Some([list, member, rest @ ..]) if MEMBERS.contains(&list.as_str()) => {
+            (member.clone(), rest.join("/"))
+        }
+        Some(rest) => (String::new(), rest.join("/")),
+        None => (String::new(), String::new()),
+    };
+
+    [
+        path.first().cloned().unwrap_or_default(),
+        path.get(1).cloned().unwrap_or_default(),
+        member,
+        field,
+    ]
+}
+
+/// Whether the change added, removed or changed a value.
+pub(crate) const fn change_kind(old: &Value, new: &Value) -> &'static str {
+    match (old, new) {
+        (Value::Null, _) => "added",
+        (_, Value::Null) => "removed",
+        _ => "changed",
+    }
+}
+
+impl<W: Write> Visitor for Csv<W> {
+    fn change(&mut self, change: &FlatChange) -> Result<()> {
+        let path = change
+            .path
+            .split('/')
+            .map(|p| p.replace("~1", "/").replace("~0", "~"))
+            .collect::<Vec<_>>();
+        let [category, item, member, field] = columns(&path);
+
+        let stage = self.stage.clone();
+        self.write_row(&[
+            &stage,
+            &category,
+            &item,
+            &member,
+            &field,
+            change_kind(&change.old, &change.new),
+            &value(&change.old),
+            &value(&change.new),
+        ])
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Words in descriptions that mark an item as deprecated, matched case-insensitively.
+///
+/// The runtime docs have no `deprecated` flag, deprecations are only noted in the descriptions.
+const MARKERS: [&str; 1] = ["deprecated"];
+
+/// Whether an item became deprecated or stopped being deprecated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transition {
+    Deprecated,
+    Undeprecated,
+}
+
+impl fmt::Display for Transition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Deprecated => write!(f, "newly deprecated"),
+            Self::Undeprecated => write!(f, "undeprecated"),
+        }
+    }
+}
+
+/// An item or member in both docs whose deprecation changed.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Deprecation {
+    /// Path of the item like in [`super::flat::FlatChange::path`], e.g. `classes/LuaEntity/methods/teleport`
+    pub path: String,
+    pub transition: Transition,
+}
+
+impl Deprecation {
+    /// Readable name of the item, the path without its section, e.g. `LuaEntity.methods.teleport`.
+    #[must_use]
+    pub fn name(&self) -> String {
+        self.path.split('/').skip(1).collect::<Vec<_>>().join(".")
+    }
+}
+
+/// Whether the item is flagged as deprecated or its description says so.
+fn is_deprecated(item: &Map<String, Value>) -> bool {
+    if item.get("deprecated").and_then(Value::as_bool) == Some(true) {
+        return true;
+    }
+
+    item.get("description")
+        .and_then(Value::as_str)
+        .is_some_and(|d| {
+            let d = d.to_lowercase();
+            MARKERS.iter().any(|m| d.contains(m))
+        })
+}
+
+/// Finds the items and members in both serialized docs that became deprecated or stopped being deprecated.
+///
+/// Items are deprecated if their `deprecated` flag is set, like prototypes, or their description mentions it.
+/// Sorted by path.
+#[must_use]
+pub fn find(source: &Value, target: &Value) -> Vec<Deprecation> {
+    fn walk(path: &mut Vec<String>, source: &Value, target: &Value, res: &mut Vec<Deprecation>) {
+        let (Value::Object(source), Value::Object(target)) = (source, target) else {
+            return;
+        };
+
+        if !path.is_empty() && target.get("name").is_some_and(Value::is_string) {
+            let transition = match (is_deprecated(source), is_deprecated(target)) {
+                (false, true) => Some(Transition::Deprecated),
+                (true, false) => Some(Transition::Undeprecated),
+                _ => None,
+            };
+
+            if let Some(transition) = transition {
+                res.push(Deprecation {
+                    path: path.join("/"),
+                    transition,
+                });
+            }
+        }
+
+        for (key, value) in target {
+            let Some(old) = source.get(key) else {
+                continue;
+            };
+
+            path.push(key.replace('~', "~0").replace('/', "~1"));
+            walk(path, old, value, res);
+            path.pop();
+        }
+    }
+
+    let mut res = Vec::new();
+    walk(
+        &mut Vec::new(),
+        &super::flat::keyed(source.clone()),
+        &super::flat::keyed(target.clone()),
+        &mut res,
+    );
+    res.sort();
+
+    res
+}
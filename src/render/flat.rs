@@ -0,0 +1,722 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::format::runtime::{Access, AccessChange};
+
+/// Keys of lists that are diffed by position instead of by name.
+const POSITIONAL: [&str; 4] = ["options", "values", "parameters", "return_values"];
+
+/// A single change of a diff, identified by its path in the docs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FlatChange {
+    /// JSON-pointer-like path of the changed value,
+    /// e.g. `classes/LuaEntity/methods/teleport/parameters/position/type/options/2`
+    pub path: String,
+    pub old: Value,
+    pub new: Value,
+    /// Page of the changed item or member in the target docs, if the diff has anchor links
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl FlatChange {
+    /// Direction of a change of the `optional` flag or of whether an attribute can be written,
+    /// also of access changes that gained or lost write access,
+    /// i.e. `now_required`, `now_optional`, `now_read_only` or `now_writable`, see [`crate::format::Optionality`].
+    #[must_use]
+    pub fn direction(&self) -> Option<&'static str> {
+        if let Some(access) = self.access_change() {
+            return match (access.from.write(), access.to.write()) {
+                (true, false) => Some("now_read_only"),
+                (false, true) => Some("now_writable"),
+                _ => None,
+            };
+        }
+
+        let (Value::Bool(old), Value::Bool(new)) = (&self.old, &self.new) else {
+            return None;
+        };
+        if old == new {
+            return None;
+        }
+
+        match self.path.rsplit('/').next()? {
+            "optional" if *new => Some("now_optional"),
+            "optional" => Some("now_required"),
+            "write" if *new => Some("now_writable"),
+            "write" => Some("now_read_only"),
+            _ => None,
+        }
+    }
+
+    /// Path of the union type the change is part of, if it changed options of a union or a whole union.
+    ///
+    /// Return values are reported as a whole, their union type is at their `type`.
+    #[must_use]
+    pub fn union(&self) -> Option<String> {
+        let is_union = |v: &Value| v.get("complex_type").is_some_and(|t| t == "union");
+
+        if let Some(path) = union_path(&self.path).map(str::to_owned) {
+            return Some(path);
+        }
+
+        if is_union(&self.old) && is_union(&self.new) {
+            Some(self.path.clone())
+        } else if self.old.get("type").is_some_and(is_union)
+            && self.new.get("type").is_some_and(is_union)
+        {
+            Some(format!("{}/type", self.path))
+        } else {
+            None
+        }
+    }
+
+    /// Change of whether an attribute can be read and written, reported at its `access` path.
+    #[must_use]
+    pub fn access_change(&self) -> Option<AccessChange> {
+        if !self.path.ends_with("/access") {
+            return None;
+        }
+
+        Some(AccessChange {
+            from: Access::deserialize(&self.old).ok()?,
+            to: Access::deserialize(&self.new).ok()?,
+        })
+    }
+}
+
+/// Flattens a serialized diff into a list of changes.
+///
+/// The diff only determines where changes happened,
+/// the old and new values are looked up in the serialized `source` and `target` docs.
+#[must_use]
+pub fn flatten(diff: &Value, source: &Value, target: &Value) -> Vec<FlatChange> {
+    let (source, target) = docs(diff, source, target);
+
+    let mut res = Vec::new();
+    walk(
+        &mut Vec::new(),
+        diff,
+        Some(&source),
+        Some(&target),
+        &mut res,
+    );
+
+    // removed items and members don't exist in the linked docs
+    if diff.get("urls").is_some() {
+        let links = super::links(&target);
+
+        for change in res.iter_mut().filter(|c| !c.new.is_null()) {
+            let path = change
+                .path
+                .split('/')
+                .map(|p| p.replace("~1", "/").replace("~0", "~"))
+                .collect::<Vec<_>>();
+
+            change.url = links.url(&path.iter().map(String::as_str).collect::<Vec<_>>());
+        }
+    }
+
+    res
+}
+
+/// The source and target docs in the layout of the diff, keyed by name with renamed items under their new names.
+fn docs(diff: &Value, source: &Value, target: &Value) -> (Value, Value) {
+    let mut source = keyed(source.clone());
+    // renamed items are diffed against their old versions
+    for (section, old, new) in renames(diff) {
+        if let Some(items) = source.get_mut(section).and_then(Value::as_object_mut) {
+            if let Some(item) = items.remove(old) {
+                items.insert(new.to_owned(), item);
+            }
+        }
+    }
+
+    (source, keyed(target.clone()))
+}
+
+/// Whether an option added to or removed from a union widened or narrowed it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnionChangeKind {
+    /// The option was added, the union accepts more values
+    Widened,
+    /// The option was removed, the union accepts fewer values
+    Narrowed,
+}
+
+/// Whether a type describes values passed to the game, values read from it or both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Position {
+    /// Parameters, write types of attributes and all prototype stage types, written by mods
+    Input,
+    /// Return values, read types of attributes, event data and global objects, read by mods
+    Output,
+    /// Concepts and defines, which are used as parameters and return values alike,
+    /// and types of attributes that can be read and written
+    Both,
+}
+
+impl Position {
+    /// Position of the type at the given path of a [`FlatChange`], decided by its stage and section.
+    ///
+    /// Types of attributes without separate read and write types are [`Position::Both`]
+    /// since the path doesn't tell whether the attribute can be written.
+    #[must_use]
+    pub fn of(path: &str) -> Self {
+        let segments = path.split('/').collect::<Vec<_>>();
+
+        match segments[0] {
+            // the whole prototype stage is data written by mods
+            "prototypes" | "types" => Self::Input,
+            "concepts" | "defines" => Self::Both,
+            "events" | "global_objects" => Self::Output,
+            _ => {
+                let input = [
+                    "parameters",
+                    "variadic_parameter",
+                    "variant_parameter_groups",
+                    "write_type",
+                ];
+
+                if segments.iter().any(|s| input.contains(s)) {
+                    Self::Input
+                } else if segments.contains(&"attributes") && !segments.contains(&"read_type") {
+                    Self::Both
+                } else {
+                    Self::Output
+                }
+            }
+        }
+    }
+}
+
+/// An option added to or removed from a union type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnionChange {
+    /// Path of the union type, e.g. `classes/LuaEntity/methods/teleport/parameters/position/type`
+    pub path: String,
+    /// The added or removed option
+    pub option: Value,
+    pub kind: UnionChangeKind,
+    pub position: Position,
+}
+
+impl UnionChange {
+    /// Whether mods may break, i.e. outputs gained an option they don't handle yet
+    /// or inputs lost an option they might still pass. Types used both ways break either way.
+    #[must_use]
+    pub const fn is_breaking(&self) -> bool {
+        matches!(
+            (self.kind, self.position),
+            (UnionChangeKind::Widened, Position::Output)
+                | (UnionChangeKind::Narrowed, Position::Input)
+                | (_, Position::Both)
+        )
+    }
+}
+
+/// Options added to and removed from the unions changed by the flattened `changes` of a serialized diff.
+///
+/// Options are compared by value, so reordered options aren't changes
+/// and options that only gained or lost a description are the same option.
+/// Unions that replaced or were replaced by other types aren't analyzed.
+#[must_use]
+pub fn union_changes(
+    changes: &[FlatChange],
+    diff: &Value,
+    source: &Value,
+    target: &Value,
+) -> Vec<UnionChange> {
+    let (source, target) = docs(diff, source, target);
+
+    let mut paths = Vec::new();
+    for path in changes.iter().filter_map(FlatChange::union) {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    let mut res = Vec::new();
+    for path in paths.iter().map(String::as_str) {
+        let (Some(old), Some(new)) = (union_options(&source, path), union_options(&target, path))
+        else {
+            continue;
+        };
+
+        let position = match Position::of(path) {
+            // attributes that can't be written in either version are only read
+            Position::Both if path.starts_with("classes/") && !writable(&source, &target, path) => {
+                Position::Output
+            }
+            position => position,
+        };
+        let mut push = |options: &[&Value], others: &[&Value], kind| {
+            for option in options {
+                if !others.iter().any(|o| option_key(o) == option_key(option)) {
+                    res.push(UnionChange {
+                        path: path.to_owned(),
+                        option: (*option).clone(),
+                        kind,
+                        position,
+                    });
+                }
+            }
+        };
+        push(&new, &old, UnionChangeKind::Widened);
+        push(&old, &new, UnionChangeKind::Narrowed);
+    }
+
+    res
+}
+
+/// Path of the union a change at `path` is part of, if the change is in one of its options.
+fn union_path(path: &str) -> Option<&str> {
+    let mut end = path.len();
+
+    while let Some(idx) = path[..end].rfind("/options/") {
+        let index = path[idx + "/options/".len()..]
+            .split('/')
+            .next()
+            .unwrap_or_default();
+        if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) {
+            return Some(&path[..idx]);
+        }
+        end = idx;
+    }
+
+    None
+}
+
+/// The value at `path` in a doc in the layout of the diff.
+fn lookup<'a>(doc: &'a Value, path: &str) -> Option<&'a Value> {
+    let mut value = doc;
+    for segment in path.split('/') {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        value = match value {
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            value => value.get(&segment)?,
+        };
+    }
+
+    Some(value)
+}
+
+/// Whether the attribute the type at `path` belongs to can be written in either doc.
+fn writable(source: &Value, target: &Value, path: &str) -> bool {
+    let segments = path.split('/').collect::<Vec<_>>();
+    let Some(idx) = segments.iter().position(|s| *s == "attributes") else {
+        return false;
+    };
+    let Some(attribute) = segments.get(..idx + 2).map(|s| s.join("/")) else {
+        return false;
+    };
+
+    [source, target].into_iter().any(|doc| {
+        lookup(doc, &attribute).is_some_and(|a| {
+            a.get("write").and_then(Value::as_bool).unwrap_or_default()
+                || a.get("write_type").is_some_and(|t| !t.is_null())
+        })
+    })
+}
+
+/// Options of the union at `path` in a doc in the layout of the diff.
+fn union_options<'a>(doc: &'a Value, path: &str) -> Option<Vec<&'a Value>> {
+    let value = lookup(doc, path)?;
+
+    if value.get("complex_type")? != "union" {
+        return None;
+    }
+
+    Some(value.get("options")?.as_array()?.iter().collect())
+}
+
+/// Options with descriptions are compared by their described types.
+fn option_key(option: &Value) -> &Value {
+    match option.get("complex_type").and_then(Value::as_str) {
+        Some("type") => option.get("value").map_or(option, option_key),
+        _ => option,
+    }
+}
+
+/// The `(section, old name, new name)` of the items renamed in a serialized diff.
+pub(super) fn renames(diff: &Value) -> impl Iterator<Item = (&str, &str, &str)> {
+    diff.get("renamed")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .flat_map(|(section, names)| {
+            names
+                .as_object()
+                .into_iter()
+                .flatten()
+                .filter_map(move |(old, new)| Some((section.as_str(), old.as_str(), new.as_str()?)))
+        })
+}
+
+/// Converts all lists of named items into maps keyed by their names,
+/// matching the layout of the diff.
+pub(super) fn keyed(value: Value) -> Value {
+    match value {
+        Value::Array(items)
+            if !items.is_empty()
+                && items
+                    .iter()
+                    .all(|i| i.get("name").is_some_and(Value::is_string)) =>
+        {
+            Value::Object(
+                items
+                    .into_iter()
+                    .map(|i| {
+                        let name = i
+                            .get("name")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_owned();
+                        (name, keyed(i))
+                    })
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(keyed).collect()),
+        Value::Object(map) => Value::Object(map.into_iter().map(|(k, v)| (k, keyed(v))).collect()),
+        v => v,
+    }
+}
+
+/// Maps a key of the diff to the matching key in the docs.
+fn doc_key(key: &str) -> String {
+    match key {
+        "table_tuple_parameters" | "function_parameters" => "parameters".to_owned(),
+        "optionality" => "optional".to_owned(),
+        _ if key.starts_with(char::is_uppercase) => {
+            let mut res = String::with_capacity(key.len() + 2);
+
+            for (i, c) in key.chars().enumerate() {
+                if c.is_uppercase() && i > 0 {
+                    res.push('_');
+                }
+                res.push(c.to_ascii_lowercase());
+            }
+
+            res
+        }
+        _ => key.to_owned(),
+    }
+}
+
+/// Lists of enum variants are serialized as lists of single key objects.
+fn is_variant_list(items: &[Value]) -> bool {
+    !items.is_empty()
+        && items
+            .iter()
+            .all(|i| i.as_object().is_some_and(|o| o.len() == 1))
+}
+
+/// Whether the read or write type of an attribute was lost, it's diffed against an empty type then.
+/// Diffs from older versions mark it with `null`.
+fn lost_type(value: &Value) -> bool {
+    let new = value.get("new").unwrap_or(value);
+
+    new.is_null() || new == ""
+}
+
+/// Writability change of an attribute, the docs tell whether it can be written by its write type in api version 6.
+fn writability(
+    path: &mut Vec<String>,
+    source: Option<&Value>,
+    target: Option<&Value>,
+    res: &mut Vec<FlatChange>,
+) {
+    let writable = |attribute: Option<&Value>| {
+        attribute.map(|a| {
+            Value::Bool(
+                a.get("write").and_then(Value::as_bool).unwrap_or_default()
+                    || a.get("write_type").is_some_and(|t| !t.is_null()),
+            )
+        })
+    };
+
+    path.push("write".to_owned());
+    push(
+        path,
+        writable(source).as_ref(),
+        writable(target).as_ref(),
+        res,
+    );
+    path.pop();
+}
+
+#[allow(clippy::too_many_lines)]
+fn walk(
+    path: &mut Vec<String>,
+    diff: &Value,
+    source: Option<&Value>,
+    target: Option<&Value>,
+    res: &mut Vec<FlatChange>,
+) {
+    match diff {
+        // visibility changes list the added and removed expansions, the docs list all of them
+        Value::Object(map) if path.last().is_some_and(|k| k == "visibility") => {
+            for (key, values) in map {
+                for value in values.as_array().into_iter().flatten() {
+                    path.push(value.as_str().unwrap_or_default().to_owned());
+                    match key.as_str() {
+                        "added" => push(path, None, Some(value), res),
+                        _ => push(path, Some(value), None, res),
+                    }
+                    path.pop();
+                }
+            }
+        }
+        Value::Object(map) if path.last().is_some_and(|k| k == "images") => {
+            images(path, map, source, target, res);
+        }
+        // text changes with their changed words are a single change of the text
+        Value::Object(map) if map.contains_key("words") && target.is_some_and(Value::is_string) => {
+            push(path, source, target, res);
+        }
+        // a type that changed its kind is diffed as a whole
+        Value::Object(map) if map.contains_key("complex_type") => {
+            push(path, source, target, res);
+        }
+        Value::Object(map) => {
+            // named items and members are listed in the order of the docs, other keys by name
+            let mut children = map.iter().collect::<Vec<_>>();
+            children.sort_by(|(a, _), (b, _)| {
+                match (doc_order(a, source, target), doc_order(b, source, target)) {
+                    (Some(a), Some(b)) => a.total_cmp(&b),
+                    (a, b) => a.is_none().cmp(&b.is_none()),
+                }
+            });
+
+            for (name, value) in children {
+                // added, removed and renamed items are already reported by their own diffs,
+                // the version, header, metrics, links, context and counters aren't part of the docs
+                if path.is_empty()
+                    && [
+                        "diff_format_version",
+                        "header",
+                        "added",
+                        "removed",
+                        "renamed",
+                        "metrics",
+                        "urls",
+                        "context",
+                        "reformatted_descriptions",
+                    ]
+                    .contains(&name.as_str())
+                {
+                    continue;
+                }
+
+                // custom property changes are tagged, the docs are not
+                if name == "changed" && path.last().is_some_and(|k| k == "custom_properties") {
+                    walk(path, value, source, target, res);
+                    continue;
+                }
+
+                walk_child(path, name.clone(), value, source, target, res);
+            }
+        }
+        Value::Array(_)
+            if path
+                .last()
+                .is_some_and(|k| POSITIONAL.contains(&k.as_str()))
+                && source.is_some_and(Value::is_array)
+                && target.is_some_and(Value::is_array) =>
+        {
+            positional(path, source, target, res);
+        }
+        Value::Array(items) if is_variant_list(items) => {
+            // the kind of a complex type or an operator changed or is unknown, report it as a whole
+            if items.iter().any(|i| {
+                i.get("complex_type").is_some()
+                    || i.get("raw").is_some()
+                    || i.get("kind_changed").is_some()
+                    || (path.len() >= 2
+                        && path[path.len() - 2] == "operators"
+                        && i.get("unknown").is_some())
+            }) {
+                push(path, source, target, res);
+                return;
+            }
+
+            let access = items.iter().find_map(|i| i.get("access_changed"));
+
+            for (field, value) in items.iter().filter_map(Value::as_object).flatten() {
+                // cross references aren't changes themselves
+                if field == "related_changes" || field == "raised_events" {
+                    continue;
+                }
+
+                // a changed access mode is a single change of the attribute's `access`,
+                // the flags and the lost read or write types it combines aren't reported on their own
+                if let Some(access) = access {
+                    match field.as_str() {
+                        "access_changed" => {
+                            path.push("access".to_owned());
+                            push(path, access.get("from"), access.get("to"), res);
+                            path.pop();
+                            continue;
+                        }
+                        "read" | "write" | "writability" => continue,
+                        "read_type" | "write_type" if lost_type(value) => continue,
+                        _ => {}
+                    }
+                }
+
+                // operator diffs are tagged with their kind, the docs are not
+                if path.len() >= 2
+                    && path[path.len() - 2] == "operators"
+                    && (field == "method" || field == "attribute")
+                {
+                    walk(path, value, source, target, res);
+                    continue;
+                }
+
+                if field == "writability" {
+                    writability(path, source, target, res);
+                    continue;
+                }
+
+                // a gained or lost structure replaces the properties as a whole
+                if field == "structure" {
+                    path.push("properties".to_owned());
+                    push(
+                        path,
+                        source.and_then(|s| s.get("properties")),
+                        target.and_then(|t| t.get("properties")),
+                        res,
+                    );
+                    path.pop();
+                    continue;
+                }
+
+                walk_child(path, doc_key(field), value, source, target, res);
+            }
+        }
+        _ => push(path, source, target, res),
+    }
+}
+
+/// Image changes list the added, removed and recaptioned images by their file names,
+/// the docs list all images.
+fn images(
+    path: &mut Vec<String>,
+    diff: &serde_json::Map<String, Value>,
+    source: Option<&Value>,
+    target: Option<&Value>,
+    res: &mut Vec<FlatChange>,
+) {
+    let find = |images: Option<&'_ Value>, filename: &str| {
+        images
+            .and_then(Value::as_array)?
+            .iter()
+            .find(|i| i.get("filename").and_then(Value::as_str) == Some(filename))
+            .cloned()
+    };
+    let filenames = |key| {
+        diff.get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|i| i.get("filename").and_then(Value::as_str))
+    };
+
+    for filename in filenames("added") {
+        path.push(filename.to_owned());
+        push(path, None, find(target, filename).as_ref(), res);
+        path.pop();
+    }
+
+    for filename in filenames("removed") {
+        path.push(filename.to_owned());
+        push(path, find(source, filename).as_ref(), None, res);
+        path.pop();
+    }
+
+    for filename in diff
+        .get("captions")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .map(|(k, _)| k)
+    {
+        let caption = |images| find(images, filename).and_then(|i| i.get("caption").cloned());
+
+        path.push(filename.clone());
+        path.push("caption".to_owned());
+        push(
+            path,
+            caption(source).as_ref(),
+            caption(target).as_ref(),
+            res,
+        );
+        path.truncate(path.len() - 2);
+    }
+}
+
+/// The `order` of the item or member `name` in the target docs, or in the source docs if it was removed.
+fn doc_order(name: &str, source: Option<&Value>, target: Option<&Value>) -> Option<f64> {
+    [target, source]
+        .into_iter()
+        .flatten()
+        .find_map(|doc| doc.get(name)?.get("order")?.as_f64())
+}
+
+fn walk_child(
+    path: &mut Vec<String>,
+    key: String,
+    diff: &Value,
+    source: Option<&Value>,
+    target: Option<&Value>,
+    res: &mut Vec<FlatChange>,
+) {
+    let src = source.and_then(|s| s.get(&key));
+    let trgt = target.and_then(|t| t.get(&key));
+
+    path.push(key);
+    match (src, trgt) {
+        (None, Some(_)) | (Some(_), None) => push(path, src, trgt, res),
+        _ => walk(path, diff, src, trgt, res),
+    }
+    path.pop();
+}
+
+fn positional(
+    path: &mut Vec<String>,
+    source: Option<&Value>,
+    target: Option<&Value>,
+    res: &mut Vec<FlatChange>,
+) {
+    let empty = Vec::new();
+    let src = source.and_then(Value::as_array).unwrap_or(&empty);
+    let trgt = target.and_then(Value::as_array).unwrap_or(&empty);
+
+    for i in 0..src.len().max(trgt.len()) {
+        let (s, t) = (src.get(i), trgt.get(i));
+
+        if s != t {
+            path.push(i.to_string());
+            push(path, s, t, res);
+            path.pop();
+        }
+    }
+}
+
+fn push(path: &[String], old: Option<&Value>, new: Option<&Value>, res: &mut Vec<FlatChange>) {
+    let path = path
+        .iter()
+        .map(|p| p.replace('~', "~0").replace('/', "~1"))
+        .collect::<Vec<_>>()
+        .join("/");
+
+    res.push(FlatChange {
+        path,
+        old: old.cloned().unwrap_or_default(),
+        new: new.cloned().unwrap_or_default(),
+        url: None,
+    });
+}
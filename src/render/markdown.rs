@@ -0,0 +1,179 @@
+use std::{collections::BTreeMap, io::Write};
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::format::ItemStatus;
+
+use super::{
+    deprecation::{Deprecation, Transition},
+    flat::FlatChange,
+    visit::{DiffNode, Renderer, Visitor},
+};
+
+/// Markdown changelog with a heading per section and changed item.
+pub struct Markdown<W> {
+    out: W,
+    in_list: bool,
+    /// Headings are nested one level deeper inside groups
+    grouped: bool,
+    defines: Option<BTreeMap<String, ItemStatus>>,
+    deprecations: Vec<Deprecation>,
+}
+
+impl<W: Write> Markdown<W> {
+    pub const fn new(out: W) -> Self {
+        Self {
+            out,
+            in_list: false,
+            grouped: false,
+            defines: None,
+            deprecations: Vec::new(),
+        }
+    }
+
+    /// Lists these deprecation changes in a section before the changes, see [`super::deprecation::find`].
+    #[must_use]
+    pub fn with_deprecations(mut self, deprecations: Vec<Deprecation>) -> Self {
+        self.deprecations = deprecations;
+        self
+    }
+
+    /// Lists the defines section as these changes keyed by their full Lua path,
+    /// instead of a heading per top level define, see [`crate::defines::flatten`].
+    #[must_use]
+    pub fn with_flat_defines(mut self, defines: BTreeMap<String, ItemStatus>) -> Self {
+        self.defines = Some(defines);
+        self
+    }
+
+    fn write_flat_defines(&mut self) -> Result<()> {
+        for (path, status) in self.defines.iter().flatten() {
+            let status = match status {
+                ItemStatus::Added => "added",
+                ItemStatus::Removed => "removed",
+                ItemStatus::Changed => "changed",
+            };
+
+            writeln!(self.out, "- `{path}`: {status}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Renderer for Markdown<W> {
+    fn render(&mut self, diff: &dyn DiffNode) -> Result<()> {
+        if !self.deprecations.is_empty() {
+            writeln!(self.out, "## deprecations\n")?;
+
+            for deprecation in &self.deprecations {
+                let what = match deprecation.transition {
+                    Transition::Deprecated => "deprecated",
+                    Transition::Undeprecated => "no longer deprecated",
+                };
+
+                writeln!(self.out, "- `{}`: {what}", deprecation.name())?;
+            }
+
+            self.in_list = true;
+        }
+
+        diff.accept(self)
+    }
+}
+
+impl<W: Write> Visitor for Markdown<W> {
+    fn enter(&mut self, path: &[&str]) -> Result<()> {
+        let flat_defines = self.defines.is_some() && path.first() == Some(&"defines");
+
+        if flat_defines && path.len() > 1 {
+            return Ok(());
+        }
+
+        let level = "#".repeat(path.len() + 1 + usize::from(self.grouped));
+        let heading = match path {
+            [section] => format!("{level} {section}"),
+            [_, item] => format!("{level} `{item}`"),
+            _ => return Ok(()),
+        };
+
+        if self.in_list {
+            writeln!(self.out)?;
+            self.in_list = false;
+        }
+
+        writeln!(self.out, "{heading}\n")?;
+
+        if flat_defines {
+            self.write_flat_defines()?;
+            self.in_list = true;
+        }
+
+        Ok(())
+    }
+
+    fn group(&mut self, name: &str) -> Result<()> {
+        if self.in_list {
+            writeln!(self.out)?;
+            self.in_list = false;
+        }
+
+        writeln!(self.out, "## {name}\n")?;
+        self.grouped = true;
+
+        Ok(())
+    }
+
+    fn change(&mut self, change: &FlatChange) -> Result<()> {
+        let path = change.path.split('/').collect::<Vec<_>>();
+
+        if self.defines.is_some() && path[0] == "defines" {
+            // listed in full when the section is entered
+            return Ok(());
+        }
+
+        let what = describe(change, |v| format!("`{v}`"));
+
+        if path.len() <= 2 {
+            // whole items are added or removed, they get their own heading
+            self.enter(&path)?;
+            writeln!(self.out, "- {what}")?;
+        } else {
+            writeln!(self.out, "- `{}`: {what}", path[2..].join("."))?;
+        }
+
+        self.in_list = true;
+
+        Ok(())
+    }
+}
+
+/// Readable description of a change, scalar values are shown in full and formatted with `code`.
+///
+/// Optionality and writability changes are described by their direction, e.g. `now required`.
+pub(super) fn describe(change: &FlatChange, code: impl Fn(&str) -> String) -> String {
+    if let Some(direction) = change.direction() {
+        return direction.replace('_', " ");
+    }
+
+    let (old, new) = (&change.old, &change.new);
+    let scalar = |value: &Value| match value {
+        Value::String(s) => Some(code(s)),
+        Value::Bool(_) | Value::Number(_) => Some(code(&value.to_string())),
+        _ => None,
+    };
+
+    match (old, new) {
+        (Value::Null, new) => {
+            scalar(new).map_or_else(|| "added".to_owned(), |v| format!("added {v}"))
+        }
+        (old, Value::Null) => {
+            scalar(old).map_or_else(|| "removed".to_owned(), |v| format!("removed {v}"))
+        }
+        (old, new) => match (scalar(old), scalar(new)) {
+            (Some(old), Some(new)) => format!("{old} → {new}"),
+            _ => "changed".to_owned(),
+        },
+    }
+}
@@ -0,0 +1,411 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use super::flat::FlatChange;
+
+/// Reference token of a JSON pointer, in the order operations on the same list are applied.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Token {
+    Index(usize),
+    Key(String),
+    /// The end of a list, only the last token of an added value
+    Append,
+}
+
+impl Token {
+    fn pointer(tokens: &[Self]) -> String {
+        tokens.iter().fold(String::new(), |mut res, token| {
+            res.push('/');
+            match token {
+                Self::Index(i) => res.push_str(&i.to_string()),
+                Self::Key(key) => res.push_str(&key.replace('~', "~0").replace('/', "~1")),
+                Self::Append => res.push('-'),
+            }
+            res
+        })
+    }
+}
+
+/// A single RFC 6902 operation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Operation {
+    /// `add`, `remove` or `replace`
+    pub op: &'static str,
+    /// JSON pointer into the source doc
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+}
+
+/// Position of the element `segment` of a flat change path in `items` and its index in the docs.
+///
+/// Items with names are listed by their `order` in the docs, images by their file names,
+/// other lists are diffed by position or are lists of plain values like the expansions of `visibility`.
+fn find(items: &[Value], segment: &str) -> Option<(usize, usize)> {
+    let name = |item: &Value| item.get("name").and_then(Value::as_str).map(str::to_owned);
+
+    if !items.is_empty() && items.iter().all(|i| name(i).is_some()) {
+        let position = items
+            .iter()
+            .position(|i| name(i).as_deref() == Some(segment))?;
+
+        let mut ranked = items
+            .iter()
+            .map(|i| {
+                (
+                    i.get("order").and_then(Value::as_f64).unwrap_or(0.0),
+                    name(i),
+                )
+            })
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+        let index = ranked
+            .iter()
+            .position(|(_, n)| n.as_deref() == Some(segment))?;
+
+        return Some((position, index));
+    }
+
+    // images are listed by their file names
+    if let Some(i) = items
+        .iter()
+        .position(|i| i.get("filename").and_then(Value::as_str) == Some(segment))
+    {
+        return Some((i, i));
+    }
+
+    segment
+        .parse()
+        .ok()
+        .filter(|i| *i < items.len())
+        .or_else(|| items.iter().position(|i| i.as_str() == Some(segment)))
+        .map(|i| (i, i))
+}
+
+/// Unescaped segments of a flat change path.
+fn segments(path: &str) -> Vec<String> {
+    path.split('/')
+        .map(|p| p.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
+
+/// Tokens of the path of a change in the source doc, `None` if its parent doesn't exist in the source.
+fn tokens(segments: &[String], source: &Value) -> Option<Vec<Token>> {
+    let mut res = Vec::with_capacity(segments.len());
+    let mut current = Some(source);
+
+    for (i, segment) in segments.iter().enumerate() {
+        let last = i + 1 == segments.len();
+
+        current = match current? {
+            Value::Array(items) => match find(items, segment) {
+                Some((position, index)) => {
+                    res.push(Token::Index(index));
+                    items.get(position)
+                }
+                None if last => {
+                    res.push(Token::Append);
+                    None
+                }
+                None => return None,
+            },
+            Value::Object(map) => {
+                res.push(Token::Key(segment.clone()));
+                map.get(segment)
+            }
+            _ => return None,
+        };
+    }
+
+    Some(res)
+}
+
+/// The value at the path of a change in the target doc.
+fn lookup<'a>(segments: &[String], target: &'a Value) -> Option<&'a Value> {
+    segments
+        .iter()
+        .try_fold(target, |value, segment| match value {
+            Value::Array(items) => items.get(find(items, segment)?.0),
+            Value::Object(map) => map.get(segment),
+            _ => None,
+        })
+}
+
+/// Fields of attributes and operators that tell whether they can be read and written, `type`, `read` and `write` in api version 5
+/// and `read_type` and `write_type` in api version 6.
+const ACCESS_FIELDS: [&str; 5] = ["type", "read", "write", "read_type", "write_type"];
+
+/// Operations on the header fields like `application_version` and `api_version`, which aren't part of the diff.
+fn header(source: &Value, target: &Value) -> Vec<(Vec<Token>, &'static str, Option<Value>)> {
+    let fields = |doc: &Value| {
+        doc.as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(_, v)| !v.is_array() && !v.is_object())
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect::<std::collections::BTreeMap<_, _>>()
+    };
+    let (old, new) = (fields(source), fields(target));
+
+    let removed = old
+        .keys()
+        .filter(|k| !new.contains_key(*k))
+        .map(|k| (vec![Token::Key(k.clone())], "remove", None));
+    let changed = new
+        .iter()
+        .filter(|(k, v)| old.get(*k) != Some(v))
+        .map(|(k, v)| {
+            let op = if old.contains_key(k) {
+                "replace"
+            } else {
+                "add"
+            };
+            (vec![Token::Key(k.clone())], op, Some(v.clone()))
+        });
+
+    removed.chain(changed).collect()
+}
+
+/// Attributes and operators of the classes in both docs whose access fields have another shape in the target,
+/// like between api version 5 and 6, as the segments of their paths.
+fn reshaped(source: &Value, target: &Value) -> Vec<Vec<String>> {
+    fn named(list: Option<&Value>) -> impl Iterator<Item = (&str, &Value)> {
+        list.and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|i| Some((i.get("name")?.as_str()?, i)))
+    }
+
+    let shape = |attribute: &Value| {
+        ACCESS_FIELDS
+            .iter()
+            .filter(|f| attribute.get(**f).is_some())
+            .collect::<Vec<_>>()
+    };
+
+    let mut res = Vec::new();
+    for (class, new) in named(target.get("classes")) {
+        let Some((_, old)) = named(source.get("classes")).find(|(name, _)| *name == class) else {
+            continue;
+        };
+
+        // index and length operators have the same access fields as attributes
+        for list in ["attributes", "operators"] {
+            for (member, new) in named(new.get(list)) {
+                let old = named(old.get(list)).find(|(name, _)| *name == member);
+
+                if old.is_some_and(|(_, old)| shape(old) != shape(new)) {
+                    res.push(["classes", class, list, member].map(str::to_owned).to_vec());
+                }
+            }
+        }
+    }
+
+    res
+}
+
+/// Converts the changes of [`super::flat::flatten`] into RFC 6902 operations on the serialized `source` doc,
+/// the added and new values are taken from the serialized `target` doc.
+///
+/// Applying the operations in order turns the source doc into the target doc, except that
+/// added items are appended to their lists instead of being inserted at their `order`.
+/// The header fields and the access fields of attributes and operators whose shape changed between api versions
+/// are changed as well, the diff doesn't report them as such.
+#[must_use]
+pub fn operations(changes: &[FlatChange], source: &Value, target: &Value) -> Vec<Operation> {
+    let mut replaced = header(source, target);
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+
+    // the access fields of reshaped members are replaced as a whole,
+    // before any items are removed so their positions still match
+    let reshaped = reshaped(source, target);
+    for segments in &reshaped {
+        let (Some(tokens), Some(old), Some(new)) = (
+            tokens(segments, source),
+            lookup(segments, source),
+            lookup(segments, target),
+        ) else {
+            continue;
+        };
+
+        for field in ACCESS_FIELDS {
+            let mut tokens = tokens.clone();
+            tokens.push(Token::Key(field.to_owned()));
+
+            match (old.get(field), new.get(field)) {
+                (Some(_), None) => replaced.push((tokens, "remove", None)),
+                (old, Some(new)) if old != Some(new) => {
+                    replaced.push((tokens, "add", Some(new.clone())));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for change in changes {
+        let segments = segments(&change.path);
+        let Some(tokens) = tokens(&segments, source) else {
+            continue;
+        };
+
+        // already replaced as a whole
+        if let Some((field, member)) = segments.split_last() {
+            if ACCESS_FIELDS.contains(&field.as_str()) && reshaped.iter().any(|r| r == member) {
+                continue;
+            }
+        }
+        let new = || Some(lookup(&segments, target).unwrap_or(&change.new).clone());
+
+        match (&change.old, &change.new) {
+            (Value::Null, _) => added.push((tokens, "add", new())),
+            (_, Value::Null) => removed.push((tokens, "remove", None)),
+            _ => replaced.push((tokens, "replace", new())),
+        }
+    }
+
+    // later items first, so removing them doesn't move the ones that are still to be removed
+    removed.sort_by(|a, b| b.0.cmp(&a.0));
+
+    replaced
+        .into_iter()
+        .chain(removed)
+        .chain(added)
+        .map(|(tokens, op, value)| Operation {
+            op,
+            path: Token::pointer(&tokens),
+            value,
+        })
+        .collect()
+}
+
+/// Sorts the lists of named items in `doc` by their `order` and name, the positions [`operations`] address.
+fn sort_by_order(doc: &mut Value) {
+    match doc {
+        Value::Array(items) => {
+            let name = |item: &Value| item.get("name").and_then(Value::as_str).map(str::to_owned);
+            if !items.is_empty() && items.iter().all(|i| name(i).is_some()) {
+                items.sort_by(|a, b| {
+                    let order = |i: &Value| i.get("order").and_then(Value::as_f64).unwrap_or(0.0);
+                    order(a)
+                        .total_cmp(&order(b))
+                        .then_with(|| name(a).cmp(&name(b)))
+                });
+            }
+
+            items.iter_mut().for_each(sort_by_order);
+        }
+        Value::Object(map) => map.values_mut().for_each(sort_by_order),
+        _ => {}
+    }
+}
+
+/// The item an operation of [`operations`] changes, e.g. `classes/LuaEntity`, resolved in the unpatched `doc`.
+///
+/// Added items are named by their value, changes outside of lists of named items by their first two segments.
+fn item_of(doc: &Value, path: &str, value: Option<&Value>) -> String {
+    let mut segments = path.trim_start_matches('/').splitn(3, '/');
+    let section = segments.next().unwrap_or_default();
+    let Some(segment) = segments.next() else {
+        return section.to_owned();
+    };
+
+    let name = if segment == "-" {
+        value.and_then(|v| v.get("name"))
+    } else {
+        doc.get(section)
+            .and_then(|items| items.get(segment.parse::<usize>().ok()?))
+            .and_then(|item| item.get("name"))
+    };
+
+    let name = name.and_then(Value::as_str).map_or_else(
+        || segment.replace("~1", "/").replace("~0", "~"),
+        str::to_owned,
+    );
+
+    format!("{section}/{name}")
+}
+
+/// Applies the RFC 6902 `add`, `remove` and `replace` operations of [`operations`] to `doc` in order.
+///
+/// The lists of named items of `doc` are sorted by their `order` first, like the docs list them.
+/// With `only` items like `classes/LuaEntity` or whole sections like `concepts`,
+/// only the operations changing these items are applied. Returns the number of applied operations.
+pub fn apply(doc: &mut Value, operations: &[Value], only: &[String]) -> anyhow::Result<usize> {
+    sort_by_order(doc);
+
+    let selected = operations
+        .iter()
+        .map(|operation| {
+            let path = operation
+                .get("path")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow::anyhow!("Operation without a path: {operation}"))?;
+            let item = item_of(doc, path, operation.get("value"));
+            let wanted = only.is_empty()
+                || only.iter().any(|o| {
+                    *o == item
+                        || item
+                            .split_once('/')
+                            .is_some_and(|(section, _)| o == section)
+                });
+
+            Ok(wanted.then_some((operation, path)))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut count = 0;
+    for (operation, path) in selected.into_iter().flatten() {
+        let op = operation
+            .get("op")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let value = operation.get("value").cloned();
+        let (parent, last) = path.rsplit_once('/').unwrap_or_default();
+        let last = last.replace("~1", "/").replace("~0", "~");
+        let index = last.parse::<usize>().ok();
+
+        if !["add", "remove", "replace"].contains(&op) {
+            anyhow::bail!("Unsupported operation `{op}` at {path}");
+        }
+
+        let replace = |slot: Option<&mut Value>, value| slot.map(|slot| *slot = value).is_some();
+        let applied = match (op, doc.pointer_mut(parent), value) {
+            ("add", Some(Value::Array(items)), Some(value)) if last == "-" => {
+                items.push(value);
+                true
+            }
+            ("add", Some(Value::Array(items)), Some(value))
+                if index.is_some_and(|i| i <= items.len()) =>
+            {
+                items.insert(index.unwrap_or_default(), value);
+                true
+            }
+            ("replace", Some(Value::Array(items)), Some(value)) => {
+                replace(index.and_then(|i| items.get_mut(i)), value)
+            }
+            ("remove", Some(Value::Array(items)), None)
+                if index.is_some_and(|i| i < items.len()) =>
+            {
+                items.remove(index.unwrap_or_default());
+                true
+            }
+            ("add", Some(Value::Object(map)), Some(value)) => {
+                map.insert(last, value);
+                true
+            }
+            ("replace", Some(Value::Object(map)), Some(value)) => {
+                replace(map.get_mut(&last), value)
+            }
+            ("remove", Some(Value::Object(map)), None) => map.remove(&last).is_some(),
+            _ => false,
+        };
+
+        if !applied {
+            anyhow::bail!("Can't {op} {path}, the patch doesn't fit the doc");
+        }
+        count += 1;
+    }
+
+    Ok(count)
+}
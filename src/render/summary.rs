@@ -0,0 +1,273 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use super::{
+    deprecation::{Deprecation, Transition},
+    flat::FlatChange,
+};
+
+/// Number of changed items listed as notable per section.
+const NOTABLE: usize = 5;
+
+/// Lists of named members whose added / removed members are counted individually.
+const MEMBERS: [(&str, &str, &str); 8] = [
+    ("methods", "method", "methods"),
+    ("attributes", "attribute", "attributes"),
+    ("operators", "operator", "operators"),
+    ("properties", "property", "properties"),
+    ("values", "value", "values"),
+    ("subkeys", "subkey", "subkeys"),
+    ("parameters", "parameter", "parameters"),
+    ("return_values", "return value", "return values"),
+];
+
+/// Compact changelog of a diff, aggregated per section of the docs.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub sections: Vec<SectionSummary>,
+    /// Items that became deprecated or stopped being deprecated, listed before the sections
+    pub deprecations: Vec<Deprecation>,
+}
+
+#[derive(Debug, Default)]
+pub struct SectionSummary {
+    /// Key of the section in the docs, e.g. `classes`
+    pub section: String,
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    /// Changed items with the most changes, most changed first
+    pub notable: Vec<ItemSummary>,
+}
+
+#[derive(Debug, Default)]
+pub struct ItemSummary {
+    pub name: String,
+    /// Number of individual changes of the item
+    pub changes: usize,
+    /// Readable descriptions of the changes, e.g. `+3 methods` or `data changed`
+    pub parts: Vec<String>,
+}
+
+#[derive(Default)]
+struct ItemChanges {
+    added: bool,
+    removed: bool,
+    changes: usize,
+    // sub key -> (added, removed, changed members)
+    members: BTreeMap<String, (usize, usize, BTreeSet<String>)>,
+    fields: Vec<String>,
+}
+
+impl Summary {
+    /// Aggregates flat changes, see [`super::flat::flatten`].
+    #[must_use]
+    pub fn new(changes: &[FlatChange]) -> Self {
+        let mut sections = BTreeMap::<&str, BTreeMap<&str, ItemChanges>>::new();
+
+        for change in changes {
+            let mut path = change.path.split('/');
+            let (Some(section), Some(name)) = (path.next(), path.next()) else {
+                continue;
+            };
+            let rest = path.collect::<Vec<_>>();
+
+            let item = sections
+                .entry(section)
+                .or_default()
+                .entry(name)
+                .or_default();
+            item.changes += 1;
+
+            match rest[..] {
+                [] => {
+                    item.added = change.old.is_null();
+                    item.removed = change.new.is_null();
+                }
+                [sub] | [sub, ..] if !MEMBERS.iter().any(|(key, ..)| *key == sub) => {
+                    item.field(sub);
+                }
+                [sub, _] if change.old.is_null() || change.new.is_null() => {
+                    let counts = item.members.entry(sub.to_owned()).or_default();
+
+                    if change.old.is_null() {
+                        counts.0 += 1;
+                    } else {
+                        counts.1 += 1;
+                    }
+                }
+                [sub, member, ..] => {
+                    item.members
+                        .entry(sub.to_owned())
+                        .or_default()
+                        .2
+                        .insert(member.to_owned());
+                }
+                [sub] => item.field(sub),
+            }
+        }
+
+        let sections = sections
+            .into_iter()
+            .map(|(section, items)| {
+                let mut res = SectionSummary {
+                    section: section.to_owned(),
+                    ..Default::default()
+                };
+
+                for (name, item) in items {
+                    if item.added {
+                        res.added += 1;
+                    } else if item.removed {
+                        res.removed += 1;
+                    } else {
+                        res.changed += 1;
+                        res.notable.push(ItemSummary {
+                            name: name.to_owned(),
+                            changes: item.changes,
+                            parts: item.parts(),
+                        });
+                    }
+                }
+
+                res.notable.sort_by_key(|i| std::cmp::Reverse(i.changes));
+                res.notable.truncate(NOTABLE);
+                res
+            })
+            .collect();
+
+        Self {
+            sections,
+            deprecations: Vec::new(),
+        }
+    }
+
+    /// Lists these deprecation changes before the sections, see [`super::deprecation::find`].
+    #[must_use]
+    pub fn with_deprecations(mut self, deprecations: Vec<Deprecation>) -> Self {
+        self.deprecations = deprecations;
+        self
+    }
+}
+
+impl ItemChanges {
+    fn field(&mut self, field: &str) {
+        if !self.fields.iter().any(|f| f == field) {
+            self.fields.push(field.to_owned());
+        }
+    }
+
+    fn parts(&self) -> Vec<String> {
+        let mut res = Vec::new();
+
+        for (key, singular, plural) in MEMBERS {
+            let Some((added, removed, changed)) = self.members.get(key) else {
+                continue;
+            };
+            let noun = |count| if count == 1 { singular } else { plural };
+
+            if *added > 0 {
+                res.push(format!("+{added} {}", noun(*added)));
+            }
+
+            if *removed > 0 {
+                res.push(format!("-{removed} {}", noun(*removed)));
+            }
+
+            if !changed.is_empty() {
+                res.push(format!("{} {} changed", changed.len(), noun(changed.len())));
+            }
+        }
+
+        res.extend(self.fields.iter().map(|f| format!("{f} changed")));
+        res
+    }
+}
+
+/// Singular or plural name of the items of a section, e.g. `class` for `classes`.
+fn noun(section: &str, count: usize) -> String {
+    let noun = if count == 1 {
+        section
+            .strip_suffix("sses")
+            .map(|s| format!("{s}ss"))
+            .or_else(|| section.strip_suffix('s').map(str::to_owned))
+            .unwrap_or_else(|| section.to_owned())
+    } else {
+        section.to_owned()
+    };
+
+    noun.replace('_', " ")
+}
+
+impl fmt::Display for SectionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut counts = Vec::new();
+
+        if self.added > 0 {
+            counts.push(format!(
+                "+{} {}",
+                self.added,
+                noun(&self.section, self.added)
+            ));
+        }
+
+        if self.removed > 0 {
+            counts.push(format!(
+                "-{} {}",
+                self.removed,
+                noun(&self.section, self.removed)
+            ));
+        }
+
+        if self.changed > 0 {
+            counts.push(format!(
+                "{} {} changed",
+                self.changed,
+                noun(&self.section, self.changed)
+            ));
+        }
+
+        write!(f, "{}", counts.join(", "))?;
+
+        if !self.notable.is_empty() {
+            let notable = self
+                .notable
+                .iter()
+                .map(|i| format!("{} ({})", i.name, i.parts.join(", ")))
+                .collect::<Vec<_>>();
+
+            write!(f, "; notable: {}", notable.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.sections.is_empty() && self.deprecations.is_empty() {
+            return writeln!(f, "no changes");
+        }
+
+        for transition in [Transition::Deprecated, Transition::Undeprecated] {
+            let names = self
+                .deprecations
+                .iter()
+                .filter(|d| d.transition == transition)
+                .map(Deprecation::name)
+                .collect::<Vec<_>>();
+
+            if !names.is_empty() {
+                writeln!(f, "{transition}: {}", names.join(", "))?;
+            }
+        }
+
+        for section in &self.sections {
+            writeln!(f, "{section}")?;
+        }
+
+        Ok(())
+    }
+}
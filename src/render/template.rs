@@ -0,0 +1,513 @@
+//! Changelogs laid out by user templates in a small subset of the Tera / Jinja syntax.
+//!
+//! - `{{ item.name }}` prints a value, strings as they are, `null` as nothing and anything else as JSON.
+//!   Values are looked up by dotted paths of keys and list indices, e.g. `section.children.0.name`.
+//! - `{{ item.changes | length }}` passes the value through filters:
+//!   `json`, `length`, `upper`, `lower`, `first`, `last`, `join(", ")` and `default("none")`.
+//! - `{% for item in section.children %}...{% endfor %}` repeats its body for each element of a list,
+//!   `loop.index`, `loop.first`, `loop.last` and `loop.length` describe the current iteration.
+//! - `{% if item.status == "added" %}...{% elif not item.old %}...{% else %}...{% endif %}`
+//!   compares a value with a JSON literal using `==` or `!=`, or checks whether it's truthy:
+//!   anything but `null`, `false`, `0`, `""`, `[]` and `{}`.
+//! - `{# comment #}` is left out.
+//!
+//! A `-` at the inside of a tag, like `{%- for ... -%}`, trims the whitespace before or after the tag.
+//! Values that don't exist are `null`, so they print nothing and aren't truthy.
+//!
+//! The context of the template is built by [`context`] from the diff as a tree, see [`super::tree::Node`].
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::tree::{Node, NodeKind};
+use crate::format::with_old_values;
+
+/// A parsed template, see the [module docs](self) for its syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Print(Expr),
+    For {
+        var: String,
+        over: Expr,
+        body: Vec<Self>,
+    },
+    If {
+        branches: Vec<(Cond, Vec<Self>)>,
+        otherwise: Vec<Self>,
+    },
+}
+
+/// A dotted path to a value and the filters it's passed through.
+#[derive(Debug, Clone, PartialEq)]
+struct Expr {
+    path: Vec<String>,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Json,
+    Length,
+    Upper,
+    Lower,
+    First,
+    Last,
+    Join(String),
+    Default(Value),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Cond {
+    Truthy(Expr),
+    Not(Expr),
+    Compare {
+        left: Expr,
+        equal: bool,
+        right: Value,
+    },
+}
+
+/// A block tag ending or continuing the enclosing block and its line, e.g. `endfor` or `elif x`.
+type End = Option<(String, usize)>;
+
+/// A piece of the template source: text or the content of a tag and the line it starts on.
+enum Token {
+    Text(String),
+    Print(String, usize),
+    Block(String, usize),
+}
+
+impl Template {
+    /// Parses the template `source`, fails with the line of the first syntax error.
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut pos = 0;
+        let (parts, end) = parse_parts(&tokens, &mut pos)?;
+
+        if let Some((tag, line)) = end {
+            bail!("line {line}: unexpected `{{% {tag} %}}`");
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Renders the template with the `context`, usually the one of [`context`].
+    #[must_use]
+    pub fn render(&self, context: &Value) -> String {
+        let mut out = String::new();
+        render_parts(&self.parts, context, &mut Vec::new(), &mut out);
+
+        out
+    }
+}
+
+/// Splits the source into text and tags, applying the whitespace control of the tags.
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    let mut trim_next = false;
+
+    while !rest.is_empty() {
+        let start = ["{{", "{%", "{#"]
+            .iter()
+            .filter_map(|open| rest.find(open))
+            .min()
+            .unwrap_or(rest.len());
+
+        let (text, tag) = rest.split_at(start);
+        let mut text = if trim_next { text.trim_start() } else { text };
+        trim_next = false;
+
+        if tag.is_empty() {
+            tokens.push(Token::Text(text.to_owned()));
+            break;
+        }
+
+        let offset = source.len() - rest.len() + start;
+        let line = source[..offset].matches('\n').count() + 1;
+        let close = match &tag[..2] {
+            "{{" => "}}",
+            "{%" => "%}",
+            _ => "#}",
+        };
+        let Some(end) = tag.find(close) else {
+            bail!("line {line}: unclosed `{}`", &tag[..2]);
+        };
+
+        let mut content = &tag[2..end];
+        if let Some(inner) = content.strip_prefix('-') {
+            text = text.trim_end();
+            content = inner;
+        }
+        if let Some(inner) = content.strip_suffix('-') {
+            trim_next = true;
+            content = inner;
+        }
+
+        if !text.is_empty() {
+            tokens.push(Token::Text(text.to_owned()));
+        }
+        match close {
+            "}}" => tokens.push(Token::Print(content.trim().to_owned(), line)),
+            "%}" => tokens.push(Token::Block(content.trim().to_owned(), line)),
+            _ => {}
+        }
+
+        rest = &tag[end + 2..];
+    }
+
+    Ok(tokens)
+}
+
+/// Parses parts until a block tag that ends or continues the enclosing block, which is returned with its line.
+fn parse_parts(tokens: &[Token], pos: &mut usize) -> Result<(Vec<Part>, End)> {
+    let mut parts = Vec::new();
+
+    while let Some(token) = tokens.get(*pos) {
+        *pos += 1;
+
+        match token {
+            Token::Text(text) => parts.push(Part::Text(text.clone())),
+            Token::Print(content, line) => parts.push(Part::Print(parse_expr(content, *line)?)),
+            Token::Block(content, line) => {
+                let (keyword, rest) = content
+                    .split_once(char::is_whitespace)
+                    .map_or((content.as_str(), ""), |(k, r)| (k, r.trim()));
+
+                match keyword {
+                    "for" => parts.push(parse_for(rest, *line, tokens, pos)?),
+                    "if" => parts.push(parse_if(rest, *line, tokens, pos)?),
+                    "elif" | "else" | "endif" | "endfor" => {
+                        return Ok((parts, Some((content.clone(), *line))));
+                    }
+                    _ => bail!("line {line}: unknown tag `{{% {content} %}}`"),
+                }
+            }
+        }
+    }
+
+    Ok((parts, None))
+}
+
+fn parse_for(head: &str, line: usize, tokens: &[Token], pos: &mut usize) -> Result<Part> {
+    let Some((var, over)) = head.split_once(" in ") else {
+        bail!("line {line}: expected `{{% for <name> in <value> %}}`");
+    };
+    let over = parse_expr(over, line)?;
+
+    let (body, end) = parse_parts(tokens, pos)?;
+    match end {
+        Some((tag, _)) if tag == "endfor" => Ok(Part::For {
+            var: var.trim().to_owned(),
+            over,
+            body,
+        }),
+        Some((tag, line)) => bail!("line {line}: unexpected `{{% {tag} %}}` in a for loop"),
+        None => bail!("line {line}: missing `{{% endfor %}}`"),
+    }
+}
+
+fn parse_if(head: &str, line: usize, tokens: &[Token], pos: &mut usize) -> Result<Part> {
+    let mut branches = Vec::new();
+    let mut cond = parse_cond(head, line)?;
+
+    loop {
+        let (body, end) = parse_parts(tokens, pos)?;
+        let Some((tag, tag_line)) = end else {
+            bail!("line {line}: missing `{{% endif %}}`");
+        };
+        branches.push((cond, body));
+
+        if let Some(head) = tag.strip_prefix("elif ") {
+            cond = parse_cond(head.trim(), tag_line)?;
+            continue;
+        }
+
+        let otherwise = match tag.as_str() {
+            "endif" => Vec::new(),
+            "else" => match parse_parts(tokens, pos)? {
+                (otherwise, Some((tag, _))) if tag == "endif" => otherwise,
+                (_, Some((tag, line))) => {
+                    bail!("line {line}: unexpected `{{% {tag} %}}` after else")
+                }
+                (_, None) => bail!("line {line}: missing `{{% endif %}}`"),
+            },
+            _ => bail!("line {tag_line}: unexpected `{{% {tag} %}}` in an if"),
+        };
+
+        return Ok(Part::If {
+            branches,
+            otherwise,
+        });
+    }
+}
+
+fn parse_cond(source: &str, line: usize) -> Result<Cond> {
+    for (op, equal) in [("==", true), ("!=", false)] {
+        if let Some((left, right)) = source.split_once(op) {
+            let right = serde_json::from_str(right.trim()).map_err(|e| {
+                anyhow::anyhow!("line {line}: `{}` isn't a JSON literal: {e}", right.trim())
+            })?;
+
+            return Ok(Cond::Compare {
+                left: parse_expr(left, line)?,
+                equal,
+                right,
+            });
+        }
+    }
+
+    match source.strip_prefix("not ") {
+        Some(expr) => Ok(Cond::Not(parse_expr(expr, line)?)),
+        None => Ok(Cond::Truthy(parse_expr(source, line)?)),
+    }
+}
+
+fn parse_expr(source: &str, line: usize) -> Result<Expr> {
+    let mut pieces = source.split('|').map(str::trim);
+    let path = pieces.next().unwrap_or_default();
+
+    if path.is_empty()
+        || !path
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+    {
+        bail!("line {line}: `{path}` isn't a path like `item.name`");
+    }
+
+    let filters = pieces
+        .map(|filter| {
+            let (name, arg) = match filter.split_once('(') {
+                Some((name, arg)) => {
+                    let Some(arg) = arg.strip_suffix(')') else {
+                        bail!("line {line}: unclosed `(` of the filter `{filter}`");
+                    };
+                    let arg = serde_json::from_str::<Value>(arg.trim()).map_err(|e| {
+                        anyhow::anyhow!("line {line}: `{arg}` isn't a JSON literal: {e}")
+                    })?;
+
+                    (name.trim(), Some(arg))
+                }
+                None => (filter, None),
+            };
+
+            Ok(match (name, arg) {
+                ("json", None) => Filter::Json,
+                ("length", None) => Filter::Length,
+                ("upper", None) => Filter::Upper,
+                ("lower", None) => Filter::Lower,
+                ("first", None) => Filter::First,
+                ("last", None) => Filter::Last,
+                ("join", Some(Value::String(sep))) => Filter::Join(sep),
+                ("default", Some(value)) => Filter::Default(value),
+                _ => bail!("line {line}: unknown filter `{filter}`"),
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(Expr {
+        path: path.split('.').map(str::to_owned).collect(),
+        filters,
+    })
+}
+
+/// The printed form of a value: strings as they are, `null` as nothing and anything else as JSON.
+fn text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(o) => !o.is_empty(),
+    }
+}
+
+/// Evaluates `expr`, the first segment of its path is looked up in the loop variables first.
+fn eval(expr: &Expr, context: &Value, scopes: &[(String, Value)]) -> Value {
+    let mut segments = expr.path.iter();
+    let first = segments.next().map(String::as_str).unwrap_or_default();
+    let start = scopes
+        .iter()
+        .rev()
+        .find(|(name, _)| name == first)
+        .map_or_else(|| context.get(first), |(_, value)| Some(value));
+
+    let value = segments
+        .try_fold(start, |value, segment| {
+            Some(match value? {
+                Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+                other => other.get(segment),
+            })
+        })
+        .flatten()
+        .cloned()
+        .unwrap_or_default();
+
+    expr.filters
+        .iter()
+        .fold(value, |value, filter| match filter {
+            Filter::Json => Value::String(value.to_string()),
+            Filter::Length => match &value {
+                Value::Array(a) => a.len().into(),
+                Value::Object(o) => o.len().into(),
+                Value::String(s) => s.chars().count().into(),
+                _ => 0.into(),
+            },
+            Filter::Upper => Value::String(text(&value).to_uppercase()),
+            Filter::Lower => Value::String(text(&value).to_lowercase()),
+            Filter::First => value.get(0).cloned().unwrap_or_default(),
+            Filter::Last => value
+                .as_array()
+                .and_then(|a| a.last())
+                .cloned()
+                .unwrap_or_default(),
+            Filter::Join(sep) => Value::String(
+                value
+                    .as_array()
+                    .map(|a| a.iter().map(text).collect::<Vec<_>>().join(sep))
+                    .unwrap_or_default(),
+            ),
+            Filter::Default(default) if !truthy(&value) => default.clone(),
+            Filter::Default(_) => value,
+        })
+}
+
+fn check(cond: &Cond, context: &Value, scopes: &[(String, Value)]) -> bool {
+    match cond {
+        Cond::Truthy(expr) => truthy(&eval(expr, context, scopes)),
+        Cond::Not(expr) => !truthy(&eval(expr, context, scopes)),
+        Cond::Compare { left, equal, right } => (eval(left, context, scopes) == *right) == *equal,
+    }
+}
+
+fn render_parts(
+    parts: &[Part],
+    context: &Value,
+    scopes: &mut Vec<(String, Value)>,
+    out: &mut String,
+) {
+    for part in parts {
+        match part {
+            Part::Text(t) => out.push_str(t),
+            Part::Print(expr) => out.push_str(&text(&eval(expr, context, scopes))),
+            Part::For { var, over, body } => {
+                let items = match eval(over, context, scopes) {
+                    Value::Array(items) => items,
+                    _ => Vec::new(),
+                };
+
+                for (i, item) in items.iter().enumerate() {
+                    let state = json!({
+                        "index": i + 1,
+                        "first": i == 0,
+                        "last": i + 1 == items.len(),
+                        "length": items.len(),
+                    });
+                    scopes.push(("loop".to_owned(), state));
+                    scopes.push((var.clone(), item.clone()));
+
+                    render_parts(body, context, scopes, out);
+
+                    scopes.truncate(scopes.len() - 2);
+                }
+            }
+            Part::If {
+                branches,
+                otherwise,
+            } => {
+                let body = branches
+                    .iter()
+                    .find(|(cond, _)| check(cond, context, scopes))
+                    .map_or(otherwise, |(_, body)| body);
+
+                render_parts(body, context, scopes, out);
+            }
+        }
+    }
+}
+
+/// A node of the diff tree as template context.
+///
+/// Items also list the `changes` below them with their paths relative to the item,
+/// e.g. `methods/teleport/parameters`, so templates don't have to walk the tree.
+fn node(n: &Node) -> Value {
+    let kind = match n.kind {
+        NodeKind::Root => "root",
+        NodeKind::Section => "section",
+        NodeKind::Item => "item",
+        NodeKind::Field => "field",
+    };
+
+    let mut res = json!({
+        "name": n.name,
+        "kind": kind,
+        "path": n.path.join("/"),
+        "status": n.status,
+        "old": n.old,
+        "new": n.new,
+        "children": n.children.iter().map(node).collect::<Vec<_>>(),
+    });
+
+    if n.kind == NodeKind::Item {
+        res["changes"] = n
+            .iter()
+            .filter(|c| c.is_leaf() && c.path.len() > n.path.len())
+            .map(|c| {
+                json!({
+                    "path": c.path.get(n.path.len()..).unwrap_or_default().join("/"),
+                    "status": c.status,
+                    "old": c.old,
+                    "new": c.new,
+                })
+            })
+            .collect();
+    }
+
+    res
+}
+
+/// The context of a template: the `header` of the diff and its `sections` as trees of changes.
+///
+/// Each node has a `name`, `kind` (`section`, `item` or `field`), `path`, `status` (`added`, `removed`
+/// or `changed`), the `old` and `new` values of leaves and its `children`. Items also have their `changes`.
+pub fn context<D, S>(diff: &D, source: &S, target: &S) -> Result<Value>
+where
+    D: Serialize,
+    S: Serialize,
+{
+    let tree = super::tree(diff, source, target)?;
+    let header = with_old_values(false, || serde_json::to_value(diff))?
+        .get("header")
+        .cloned()
+        .unwrap_or_default();
+
+    Ok(json!({
+        "header": header,
+        "sections": tree.children.iter().map(node).collect::<Vec<_>>(),
+    }))
+}
+
+/// Renders the diff with a user template, see [`context`] for what the template can use.
+pub fn render<D, S>(template: &Template, diff: &D, source: &S, target: &S) -> Result<String>
+where
+    D: Serialize,
+    S: Serialize,
+{
+    Ok(template.render(&context(diff, source, target)?))
+}
@@ -0,0 +1,170 @@
+use anyhow::Result;
+use serde::Deserialize;
+
+use super::{
+    flat::FlatChange,
+    visit::{DiffNode, Visitor},
+};
+
+/// Theme of items that match no other theme.
+pub const OTHER: &str = "Other";
+
+/// A theme and the prefixes of the names of its items, like classes, prototypes or events.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    pub prefixes: Vec<String>,
+}
+
+/// Mapping of item name prefixes to themes for release notes style changelogs.
+///
+/// Prefixes are matched case-insensitively and the longest matching prefix wins,
+/// e.g. `LuaEntityPrototype` is themed by `LuaEntity` rather than `Lua`.
+/// Deserializes from a list of themes: `[{"name": "GUI", "prefixes": ["LuaGui", "on_gui"]}]`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Themes(pub Vec<Theme>);
+
+impl Default for Themes {
+    fn default() -> Self {
+        let theme = |name: &str, prefixes: &[&str]| Theme {
+            name: name.to_owned(),
+            prefixes: prefixes.iter().map(|&p| p.to_owned()).collect(),
+        };
+
+        Self(vec![
+            theme(
+                "GUI",
+                &[
+                    "LuaGui",
+                    "LuaStyle",
+                    "Gui",
+                    "on_gui",
+                    "gui_",
+                    "StyleSpecification",
+                ],
+            ),
+            theme(
+                "Entities",
+                &[
+                    "LuaEntity",
+                    "Entity",
+                    "on_entity",
+                    "entity_",
+                    "on_built_entity",
+                ],
+            ),
+            theme("Forces", &["LuaForce", "Force", "on_force", "force_"]),
+            theme(
+                "Surfaces",
+                &[
+                    "LuaSurface",
+                    "Surface",
+                    "on_surface",
+                    "surface_",
+                    "LuaTile",
+                    "Tile",
+                    "on_chunk",
+                ],
+            ),
+            theme(
+                "Trains",
+                &[
+                    "LuaTrain",
+                    "Train",
+                    "on_train",
+                    "train_",
+                    "LuaRail",
+                    "Rail",
+                    "rail_",
+                    "Locomotive",
+                    "CargoWagon",
+                    "FluidWagon",
+                    "ArtilleryWagon",
+                    "RollingStock",
+                ],
+            ),
+            theme(
+                "Circuits",
+                &[
+                    "LuaCircuitNetwork",
+                    "CircuitNetwork",
+                    "CircuitCondition",
+                    "circuit_",
+                    "wire_",
+                    "LuaWire",
+                    "LuaCombinator",
+                    "LuaArithmeticCombinator",
+                    "LuaDeciderCombinator",
+                    "LuaConstantCombinator",
+                    "ArithmeticCombinator",
+                    "DeciderCombinator",
+                    "ConstantCombinator",
+                    "SelectorCombinator",
+                    "Combinator",
+                ],
+            ),
+        ])
+    }
+}
+
+impl Themes {
+    /// Name of the theme of the item, [`OTHER`] if no prefix matches.
+    #[must_use]
+    pub fn theme(&self, item: &str) -> &str {
+        let item = item.to_lowercase();
+
+        self.0
+            .iter()
+            .flat_map(|t| t.prefixes.iter().map(move |p| (t, p)))
+            .filter(|(_, p)| item.starts_with(&p.to_lowercase()))
+            .max_by_key(|(_, p)| p.len())
+            .map_or(OTHER, |(t, _)| &t.name)
+    }
+
+    /// Buckets the changes by the theme of their item, in the order of the themes and [`OTHER`] last.
+    ///
+    /// The changes keep their order within a theme, themes without changes are left out.
+    #[must_use]
+    pub fn group(&self, changes: Vec<FlatChange>) -> Grouped {
+        let mut groups = self
+            .0
+            .iter()
+            .map(|t| (t.name.clone(), Vec::new()))
+            .chain(std::iter::once((OTHER.to_owned(), Vec::new())))
+            .collect::<Vec<_>>();
+
+        for change in changes {
+            let theme = change
+                .path
+                .split('/')
+                .nth(1)
+                .map_or(OTHER, |item| self.theme(item));
+
+            if let Some((_, group)) = groups.iter_mut().find(|(name, _)| name == theme) {
+                group.push(change);
+            }
+        }
+
+        groups.retain(|(_, group)| !group.is_empty());
+
+        Grouped(groups)
+    }
+}
+
+/// Changes bucketed by theme, see [`Themes::group`].
+///
+/// Each theme is announced with [`Visitor::group`] before its changes are visited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grouped(pub Vec<(String, Vec<FlatChange>)>);
+
+impl DiffNode for Grouped {
+    fn accept(&self, visitor: &mut dyn Visitor) -> Result<()> {
+        for (theme, changes) in &self.0 {
+            visitor.group(theme)?;
+            changes.accept(visitor)?;
+        }
+
+        Ok(())
+    }
+}
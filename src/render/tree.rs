@@ -0,0 +1,132 @@
+use serde_json::Value;
+
+use super::flat::FlatChange;
+use crate::format::ItemStatus;
+
+/// What a [`Node`] of the diff tree stands for, determined by its depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    /// The whole diff
+    Root,
+    /// A section of the docs like `classes`
+    Section,
+    /// An item of a section like the class `LuaEntity`
+    Item,
+    /// Anything below an item like its `methods`, a method or one of its fields
+    Field,
+}
+
+impl NodeKind {
+    const fn at(depth: usize) -> Self {
+        match depth {
+            0 => Self::Root,
+            1 => Self::Section,
+            2 => Self::Item,
+            _ => Self::Field,
+        }
+    }
+}
+
+/// A node of the diff as a tree, the changes are its leaves.
+///
+/// Unlike the typed diffs every level has the same shape, so the tree can be walked
+/// without knowing the diff types of the stage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub kind: NodeKind,
+    /// Last segment of the path, empty for the root
+    pub name: String,
+    /// Segments of the path in the docs, e.g. `["classes", "LuaEntity", "methods", "teleport"]`
+    pub path: Vec<String>,
+    /// Whether the value was added or removed, nodes with children are always changed
+    pub status: ItemStatus,
+    /// Value in the source docs, `null` for nodes with children and added values
+    pub old: Value,
+    /// Value in the target docs, `null` for nodes with children and removed values
+    pub new: Value,
+    pub children: Vec<Self>,
+}
+
+impl Node {
+    fn new(path: Vec<String>) -> Self {
+        Self {
+            kind: NodeKind::at(path.len()),
+            name: path.last().cloned().unwrap_or_default(),
+            path,
+            status: ItemStatus::Changed,
+            old: Value::Null,
+            new: Value::Null,
+            children: Vec::new(),
+        }
+    }
+
+    /// Builds the tree of the changes of [`super::flat::flatten`].
+    #[must_use]
+    pub fn from_changes(changes: &[FlatChange]) -> Self {
+        let mut root = Self::new(Vec::new());
+
+        for change in changes {
+            let path = change
+                .path
+                .split('/')
+                .map(|p| p.replace("~1", "/").replace("~0", "~"))
+                .collect::<Vec<_>>();
+
+            let mut node = &mut root;
+            for depth in 1..=path.len() {
+                let name = &path[depth - 1];
+
+                // changes are grouped by path, the child is usually the last one
+                let pos = node
+                    .children
+                    .iter()
+                    .rposition(|c| &c.name == name)
+                    .unwrap_or_else(|| {
+                        node.children.push(Self::new(path[..depth].to_vec()));
+                        node.children.len() - 1
+                    });
+                node = &mut node.children[pos];
+            }
+
+            node.status = match (&change.old, &change.new) {
+                (Value::Null, _) => ItemStatus::Added,
+                (_, Value::Null) => ItemStatus::Removed,
+                _ => ItemStatus::Changed,
+            };
+            node.old = change.old.clone();
+            node.new = change.new.clone();
+        }
+
+        root
+    }
+
+    #[must_use]
+    pub const fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// The descendant at `path` relative to this node.
+    #[must_use]
+    pub fn get(&self, path: &[&str]) -> Option<&Self> {
+        path.iter().try_fold(self, |node, name| {
+            node.children.iter().find(|c| c.name == *name)
+        })
+    }
+
+    /// This node and all its descendants, parents before their children.
+    pub fn iter(&self) -> impl Iterator<Item = &Self> {
+        let mut stack = vec![self];
+
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children.iter().rev());
+            Some(node)
+        })
+    }
+
+    /// The changes of the tree, its leaves.
+    pub fn changes(&self) -> impl Iterator<Item = &Self> {
+        self.iter()
+            .filter(|n| n.is_leaf() && n.kind != NodeKind::Root)
+    }
+}
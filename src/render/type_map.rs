@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::flat::FlatChange;
+
+/// Keys of the docs that hold types, changes below them are changes of types.
+const TYPE_KEYS: [&str; 3] = ["type", "read_type", "write_type"];
+
+/// Keys of complex types that hold text or flags instead of types.
+const NOT_TYPES: [&str; 6] = [
+    "complex_type",
+    "description",
+    "name",
+    "order",
+    "optional",
+    "full_format",
+];
+
+/// Mapping of Factorio type names to the types of another language, e.g. `uint` to `number`,
+/// for changelogs of typedefs that use the vocabulary of their language.
+///
+/// Names without a mapping are kept. Values of literal types are kept as part of whole types,
+/// a change of only the value of a literal type can't be told apart from a change of a type name.
+/// Deserializes from an object of type names: `{"uint": "number", "LuaEntity": "LuaEntity"}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct TypeMapper(pub BTreeMap<String, String>);
+
+impl TypeMapper {
+    /// The mapped name of the type `name`.
+    #[must_use]
+    pub fn map<'a>(&'a self, name: &'a str) -> &'a str {
+        self.0.get(name).map_or(name, String::as_str)
+    }
+
+    /// Maps the type names of a serialized type, including those nested in complex types.
+    pub fn map_type(&self, ty: &mut Value) {
+        match ty {
+            Value::String(name) => {
+                if let Some(mapped) = self.0.get(name.as_str()) {
+                    mapped.clone_into(name);
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|i| self.map_type(i)),
+            Value::Object(map) => {
+                let literal = map.get("complex_type").and_then(Value::as_str) == Some("literal");
+
+                for (key, value) in map {
+                    if NOT_TYPES.contains(&key.as_str()) || (literal && key == "value") {
+                        continue;
+                    }
+
+                    self.map_type(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Maps the types held by a serialized item or member, like the types of the parameters of a method.
+    pub fn map_types_of(&self, value: &mut Value) {
+        match value {
+            Value::Array(items) => items.iter_mut().for_each(|i| self.map_types_of(i)),
+            Value::Object(map) => {
+                for (key, value) in map {
+                    if TYPE_KEYS.contains(&key.as_str()) {
+                        self.map_type(value);
+                    } else {
+                        self.map_types_of(value);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Maps the old and new values of the changes of types and the types of changed members,
+    /// other changes are kept.
+    pub fn apply(&self, changes: &mut [FlatChange]) {
+        if self.0.is_empty() {
+            return;
+        }
+
+        for change in changes {
+            let mut segments = change.path.split('/');
+            if !segments.any(|s| TYPE_KEYS.contains(&s)) {
+                self.map_types_of(&mut change.old);
+                self.map_types_of(&mut change.new);
+                continue;
+            }
+            // text and flags of complex types, e.g. the description of a `type` complex type
+            if segments.next_back().is_some_and(|s| NOT_TYPES.contains(&s)) {
+                continue;
+            }
+
+            self.map_type(&mut change.old);
+            self.map_type(&mut change.new);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::Result;
+
+use super::flat::FlatChange;
+
+/// A diff that can be walked with a [`Visitor`].
+pub trait DiffNode {
+    fn accept(&self, visitor: &mut dyn Visitor) -> Result<()>;
+}
+
+/// Callbacks for walking a [`DiffNode`].
+///
+/// Sections, items and members that contain changes are entered before their changes are visited
+/// and left afterwards, e.g. `classes`, `classes/LuaEntity`, `classes/LuaEntity/methods`.
+pub trait Visitor {
+    /// Enters the node at `path`, the last segment is the name of the node.
+    fn enter(&mut self, path: &[&str]) -> Result<()> {
+        let _ = path;
+        Ok(())
+    }
+
+    fn change(&mut self, change: &FlatChange) -> Result<()>;
+
+    /// Starts the group `name` of changes, e.g. a theme of [`super::themes::Grouped`].
+    ///
+    /// Groups hold whole sections, the changes of a group are entered and left on their own.
+    fn group(&mut self, name: &str) -> Result<()> {
+        let _ = name;
+        Ok(())
+    }
+
+    /// Leaves the node at `path`, all its changes have been visited.
+    fn leave(&mut self, path: &[&str]) -> Result<()> {
+        let _ = path;
+        Ok(())
+    }
+}
+
+/// Custom output of a diff, e.g. for chat messages or forum posts.
+pub trait Renderer {
+    fn render(&mut self, diff: &dyn DiffNode) -> Result<()>;
+}
+
+/// Changes in the order of [`super::flat::flatten`], which groups them by path.
+impl DiffNode for [FlatChange] {
+    fn accept(&self, visitor: &mut dyn Visitor) -> Result<()> {
+        let mut open = Vec::<&str>::new();
+
+        for change in self {
+            let path = change.path.split('/').collect::<Vec<_>>();
+            let parents = &path[..path.len() - 1];
+
+            let common = open.iter().zip(parents).take_while(|(a, b)| a == b).count();
+
+            while open.len() > common {
+                visitor.leave(&open)?;
+                open.pop();
+            }
+
+            for segment in &parents[common..] {
+                open.push(segment);
+                visitor.enter(&open)?;
+            }
+
+            visitor.change(change)?;
+        }
+
+        while !open.is_empty() {
+            visitor.leave(&open)?;
+            open.pop();
+        }
+
+        Ok(())
+    }
+}
+
+impl DiffNode for Vec<FlatChange> {
+    fn accept(&self, visitor: &mut dyn Visitor) -> Result<()> {
+        self.as_slice().accept(visitor)
+    }
+}
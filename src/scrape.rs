@@ -0,0 +1,423 @@
+//! Best-effort runtime docs scraped from the HTML doc pages of versions before the machine-readable docs.
+//!
+//! Only classes, events and defines are scraped, concepts and global functions are left empty.
+//! Types are kept as written on the pages, apart from arrays, dictionaries and unions.
+
+use std::{path::Path, sync::LazyLock};
+
+use regex::Regex;
+use serde_json::{json, Map, Value};
+
+use crate::Result;
+
+/// Api version of the scraped docs, the oldest supported runtime api version.
+const API_VERSION: u8 = 5;
+
+/// Whether `dir` holds the HTML doc pages of an old version, like the `doc-html` directory of the game.
+#[must_use]
+pub fn is_html_docs(dir: &Path) -> bool {
+    dir.join("Classes.html").is_file()
+}
+
+/// Scrapes the HTML doc pages in `dir` into a runtime doc, serialized as JSON.
+///
+/// The version is taken from the pages if they mention it, else from the name of `dir`
+/// or its parent if `dir` is a `doc-html` directory.
+pub fn runtime(dir: &Path) -> Result<Vec<u8>> {
+    let read = |page: &str| std::fs::read_to_string(dir.join(page)).unwrap_or_default();
+
+    let index = std::fs::read_to_string(dir.join("Classes.html"))?;
+    let classes = class_names(&index)
+        .iter()
+        .filter_map(|name| class(name, &read(&format!("{name}.html"))))
+        .enumerate()
+        .map(|(order, mut class)| {
+            class["order"] = order.into();
+            class
+        })
+        .collect::<Vec<_>>();
+
+    let doc = json!({
+        "application": "factorio",
+        "stage": "runtime",
+        "application_version": version(&index, dir),
+        "api_version": API_VERSION,
+        "classes": classes,
+        "events": events(&read("events.html")),
+        "concepts": [],
+        "defines": defines(&read("defines.html")),
+        "global_objects": [],
+        "global_functions": [],
+    });
+
+    Ok(serde_json::to_vec(&doc)?)
+}
+
+fn version(index: &str, dir: &Path) -> String {
+    static VERSION: LazyLock<Option<Regex>> =
+        LazyLock::new(|| Regex::new(r"Factorio (\d+\.\d+\.\d+)").ok());
+
+    if let Some(version) = VERSION
+        .as_ref()
+        .and_then(|re| re.captures(index))
+        .map(|c| c[1].to_owned())
+    {
+        return version;
+    }
+
+    let dir = if dir.ends_with("doc-html") {
+        dir.parent().unwrap_or(dir)
+    } else {
+        dir
+    };
+
+    dir.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_owned()
+}
+
+/// Names of the classes linked from the class index, in order of their first link.
+fn class_names(index: &str) -> Vec<String> {
+    static LINK: LazyLock<Option<Regex>> =
+        LazyLock::new(|| Regex::new(r#"href="(Lua\w+)\.html""#).ok());
+
+    let mut names = Vec::<String>::new();
+    for name in LINK
+        .as_ref()
+        .into_iter()
+        .flat_map(|re| re.captures_iter(index))
+        .map(|c| c[1].to_owned())
+    {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    names
+}
+
+/// A class from its page, `None` if the page is missing.
+#[must_use]
+pub fn class(name: &str, html: &str) -> Option<Value> {
+    static PARENT: LazyLock<Option<Regex>> =
+        LazyLock::new(|| Regex::new(r#"extends\s*<a href="(\w+)\.html""#).ok());
+
+    if html.is_empty() {
+        return None;
+    }
+
+    let mut methods = Vec::new();
+    let mut attributes = Vec::new();
+
+    for element in elements(html) {
+        let Some(member) = element.id.strip_prefix(&format!("{name}.")) else {
+            continue;
+        };
+        let header = text(element.header);
+        let signature = header.strip_prefix(member).unwrap_or(&header).trim_start();
+
+        if signature.starts_with('(') || signature.starts_with('{') {
+            methods.push(method(member, signature, &element, methods.len()));
+        } else if signature.starts_with("::") {
+            attributes.push(attribute(member, signature, &element, attributes.len()));
+        }
+    }
+
+    let mut class = Map::new();
+    class.insert("name".to_owned(), name.into());
+    class.insert("order".to_owned(), 0.into());
+    class.insert(
+        "description".to_owned(),
+        section(html, r#"<div class="brief-description">"#)
+            .map(text)
+            .unwrap_or_default()
+            .into(),
+    );
+    if let Some(parent) = PARENT.as_ref().and_then(|re| re.captures(html)) {
+        class.insert("parent".to_owned(), parent[1].into());
+    }
+    class.insert("abstract".to_owned(), false.into());
+    class.insert("methods".to_owned(), methods.into());
+    class.insert("attributes".to_owned(), attributes.into());
+
+    Some(class.into())
+}
+
+fn method(name: &str, signature: &str, element: &Element, order: usize) -> Value {
+    let parameters = element.detail("Parameters").map(fields).unwrap_or_default();
+
+    let format = if signature.starts_with('{') {
+        json!({"takes_table": true, "table_optional": false})
+    } else {
+        json!({"takes_table": false})
+    };
+
+    let return_values = signature
+        .split_once('→')
+        .map(|(_, ty)| ty.trim())
+        .filter(|ty| !ty.is_empty())
+        .map(|ty| {
+            vec![json!({
+                "order": 0,
+                "description": element.detail("Return value").map(text).unwrap_or_default(),
+                "type": ty_value(ty),
+                "optional": false,
+            })]
+        })
+        .unwrap_or_default();
+
+    json!({
+        "name": name,
+        "order": order,
+        "description": element.description(),
+        "parameters": parameters,
+        "format": format,
+        "return_values": return_values,
+    })
+}
+
+fn attribute(name: &str, signature: &str, element: &Element, order: usize) -> Value {
+    let signature = signature.trim_start_matches("::").trim();
+    let (ty, mode) = signature
+        .rsplit_once('[')
+        .map_or((signature, ""), |(ty, mode)| (ty.trim(), mode));
+
+    json!({
+        "name": name,
+        "order": order,
+        "description": element.description(),
+        "type": ty_value(ty),
+        "read": mode.contains('R'),
+        "write": mode.contains('W'),
+        "optional": false,
+    })
+}
+
+/// Events of the events page.
+#[must_use]
+pub fn events(html: &str) -> Vec<Value> {
+    elements(html)
+        .iter()
+        .filter(|e| !e.id.contains('.'))
+        .enumerate()
+        .map(|(order, element)| {
+            json!({
+                "name": element.id,
+                "order": order,
+                "description": element.description(),
+                "data": fields(element.content),
+            })
+        })
+        .collect()
+}
+
+/// Defines of the defines page, values of nested defines are their subkeys.
+#[must_use]
+pub fn defines(html: &str) -> Vec<Value> {
+    let mut root = Vec::<Value>::new();
+
+    'elements: for element in elements(html) {
+        let Some(path) = element.id.strip_prefix("defines.") else {
+            continue;
+        };
+        let mut segments = path.split('.').collect::<Vec<_>>();
+        let Some(name) = segments.pop() else {
+            continue;
+        };
+
+        let mut list = &mut root;
+        for segment in segments {
+            let Some(index) = list.iter().position(|d| d["name"] == segment) else {
+                continue 'elements;
+            };
+
+            let define = &mut list[index];
+            if define.get("subkeys").is_none() {
+                // defines with nested defines have no values of their own
+                if let Some(define) = define.as_object_mut() {
+                    define.remove("values");
+                    define.insert("subkeys".to_owned(), Value::Array(Vec::new()));
+                }
+            }
+
+            let Some(subkeys) = define["subkeys"].as_array_mut() else {
+                continue 'elements;
+            };
+            list = subkeys;
+        }
+
+        list.push(json!({
+            "name": name,
+            "order": list.len(),
+            "description": element.description(),
+            "values": [],
+        }));
+    }
+
+    // leaves are the values of their parents
+    root.iter_mut().for_each(into_values);
+    root
+}
+
+fn into_values(define: &mut Value) {
+    let Some(subkeys) = define.get_mut("subkeys").and_then(Value::as_array_mut) else {
+        return;
+    };
+
+    if subkeys.iter().all(|s| s.get("subkeys").is_none()) {
+        let values = subkeys
+            .iter()
+            .map(|v| json!({"name": v["name"], "order": v["order"], "description": v["description"]}))
+            .collect::<Vec<_>>();
+
+        if let Some(define) = define.as_object_mut() {
+            define.remove("subkeys");
+            define.insert("values".to_owned(), values.into());
+        }
+    } else {
+        subkeys.iter_mut().for_each(into_values);
+    }
+}
+
+/// Type as written on the pages, e.g. `array of LuaEntity` or `string or LuaEntity`.
+fn ty_value(ty: &str) -> Value {
+    let ty = ty.trim();
+
+    if let Some(value) = ty.strip_prefix("array of ") {
+        return json!({"complex_type": "array", "value": ty_value(value)});
+    }
+
+    if let Some((key, value)) = ty
+        .strip_prefix("dictionary ")
+        .and_then(|kv| kv.split_once('→'))
+    {
+        return json!({
+            "complex_type": "dictionary",
+            "key": ty_value(key),
+            "value": ty_value(value),
+        });
+    }
+
+    if ty.contains(" or ") {
+        let options = ty.split(" or ").map(ty_value).collect::<Vec<_>>();
+        return json!({"complex_type": "union", "options": options, "full_format": false});
+    }
+
+    ty.into()
+}
+
+/// Named and typed fields, like parameters or event data: `name :: type (optional): description`.
+fn fields(html: &str) -> Vec<Value> {
+    static FIELD: LazyLock<Option<Regex>> = LazyLock::new(|| {
+        Regex::new(
+            r#"(?s)<span class="param-name">(.*?)</span>\s*::\s*<span class="param-type">(.*?)</span>(.*?)(?:</div>|$)"#,
+        )
+        .ok()
+    });
+
+    FIELD
+        .as_ref()
+        .into_iter()
+        .flat_map(|re| re.captures_iter(html))
+        .enumerate()
+        .map(|(order, field)| {
+            let rest = text(&field[3]);
+            let optional = rest.starts_with("(optional)");
+            let description = rest
+                .trim_start_matches("(optional)")
+                .trim_start()
+                .trim_start_matches(':')
+                .trim();
+
+            json!({
+                "name": text(&field[1]),
+                "order": order,
+                "description": description,
+                "type": ty_value(&text(&field[2])),
+                "optional": optional,
+            })
+        })
+        .collect()
+}
+
+/// A documented member of a page: `<div class="element" id="...">`.
+struct Element<'a> {
+    id: &'a str,
+    header: &'a str,
+    content: &'a str,
+}
+
+impl<'a> Element<'a> {
+    fn description(&self) -> String {
+        let end = self
+            .content
+            .find(r#"<div class="detail">"#)
+            .unwrap_or(self.content.len());
+
+        text(&self.content[..end])
+    }
+
+    /// Content of the detail with the `title`, e.g. `Parameters`.
+    fn detail(&self, title: &str) -> Option<&'a str> {
+        let header = format!(">{title}</div>");
+        let start = self.content.find(&header)? + header.len();
+        let rest = &self.content[start..];
+        let end = rest
+            .find(r#"<div class="detail-header">"#)
+            .unwrap_or(rest.len());
+
+        Some(&rest[..end])
+    }
+}
+
+fn elements(html: &str) -> Vec<Element<'_>> {
+    const START: &str = r#"<div class="element" id=""#;
+
+    html.split(START)
+        .skip(1)
+        .filter_map(|element| {
+            let (id, rest) = element.split_once('"')?;
+            let header = section(rest, r#"<div class="element-header">"#).unwrap_or_default();
+            let content = rest
+                .split_once(r#"<div class="element-content">"#)
+                .map_or("", |(_, content)| content);
+
+            Some(Element {
+                id,
+                header,
+                content,
+            })
+        })
+        .collect()
+}
+
+/// Content of the first `div` starting with `start`, up to the next closing `div`.
+fn section<'a>(html: &'a str, start: &str) -> Option<&'a str> {
+    let (_, rest) = html.split_once(start)?;
+
+    Some(
+        rest.split_once("</div>")
+            .map_or(rest, |(content, _)| content),
+    )
+}
+
+/// Text of the HTML with tags removed, entities decoded and whitespace collapsed.
+fn text(html: &str) -> String {
+    static TAG: LazyLock<Option<Regex>> = LazyLock::new(|| Regex::new(r"<[^>]*>").ok());
+
+    let stripped = TAG.as_ref().map_or(std::borrow::Cow::Borrowed(html), |re| {
+        re.replace_all(html, "")
+    });
+
+    stripped
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&rarr;", "→")
+        .replace("&amp;", "&")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,100 @@
+//! Sanity check of a doc diffed against itself, which has to find no changes.
+
+use std::fmt;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{
+    format::{
+        prototype::PrototypeDoc, runtime::RuntimeDoc, with_old_values, DiffOptions, FieldPolicy,
+    },
+    render::flat,
+    Comparison, Docs,
+};
+
+/// Problems found by diffing a doc against itself, empty if it passed.
+///
+/// Changes of a doc compared to itself point to nondeterministic parsing,
+/// items that depend on the order of hash maps or floats that don't compare equal to themselves.
+#[derive(Debug, Default, Serialize)]
+pub struct SelfCheck {
+    /// Paths of the changes in the diff of the doc with itself, see [`flat::FlatChange::path`]
+    pub changes: Vec<String>,
+    /// Number of items the streamed diff of the doc with itself found changed
+    pub streamed: usize,
+    /// Whether parsing the doc twice gave docs that serialize differently
+    pub unstable: bool,
+}
+
+impl SelfCheck {
+    /// Parses the raw doc twice and diffs both parses, both at once and streamed, comparing all fields.
+    ///
+    /// Fails if the doc can't be read at all.
+    pub fn run(raw: &[u8]) -> Result<Self> {
+        let options = DiffOptions {
+            policy: FieldPolicy::all(),
+            exact_types: true,
+            ..Default::default()
+        };
+
+        let stage = Docs::detect(raw)?;
+        let mut res = match stage {
+            Docs::Runtime => Self::of::<RuntimeDoc>(raw, options)?,
+            Docs::Prototype => Self::of::<PrototypeDoc>(raw, options)?,
+        };
+        res.streamed = stage.stream(raw, raw, options, false, &mut std::io::sink())?;
+
+        Ok(res)
+    }
+
+    fn of<T>(raw: &[u8], options: DiffOptions) -> Result<Self>
+    where
+        T: crate::format::Doc
+            + std::ops::Deref<Target = crate::format::Common>
+            + serde::de::DeserializeOwned
+            + Serialize,
+        T::Diff: Serialize,
+    {
+        let c = Comparison::<T>::new(raw, raw, options)?;
+        let (source, target) = (
+            serde_json::to_value(&c.source)?,
+            serde_json::to_value(&c.target)?,
+        );
+
+        let changes = flat::flatten(
+            &with_old_values(false, || serde_json::to_value(&c.diff))?,
+            &source,
+            &target,
+        );
+
+        Ok(Self {
+            changes: changes.into_iter().map(|c| c.path).collect(),
+            streamed: 0,
+            unstable: serde_json::to_vec(&c.source)? != serde_json::to_vec(&c.target)?,
+        })
+    }
+
+    #[must_use]
+    pub const fn passed(&self) -> bool {
+        self.changes.is_empty() && self.streamed == 0 && !self.unstable
+    }
+}
+
+impl fmt::Display for SelfCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.unstable {
+            writeln!(f, "parsing the doc twice gave different docs")?;
+        }
+
+        for path in &self.changes {
+            writeln!(f, "changed: {path}")?;
+        }
+
+        if self.streamed > 0 {
+            writeln!(f, "{} items changed in the streamed diff", self.streamed)?;
+        }
+
+        Ok(())
+    }
+}
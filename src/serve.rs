@@ -0,0 +1,343 @@
+use std::{collections::VecDeque, path::PathBuf};
+
+use anyhow::Result;
+use clap::{Args, ValueEnum as _};
+use fapi_diff::{
+    format::{Context, DiffOptions, FieldPolicy},
+    render::OutputFormat,
+    Docs,
+};
+use tiny_http::{Header, Response, Server};
+
+#[derive(Args, Clone)]
+pub struct ServeArgs {
+    /// Directory with one sub directory per version, laid out like the local docs.
+    /// If not specified, the docs are fetched from lua-api.factorio.com
+    #[clap(long, verbatim_doc_comment)]
+    pub docs_dir: Option<PathBuf>,
+
+    /// Address to listen on
+    #[clap(long, default_value = "127.0.0.1:8080")]
+    pub bind: String,
+
+    /// Number of diffs to keep, the least recently requested one is dropped first
+    #[clap(long, default_value_t = 32)]
+    pub cache_size: usize,
+}
+
+/// A requested diff, identifies cached responses.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct DiffRequest {
+    stage: Docs,
+    source: String,
+    target: String,
+    format: OutputFormat,
+    with_old: bool,
+    full: bool,
+    exact_types: bool,
+    inherit: bool,
+    resolve_inheritance: bool,
+    metrics: bool,
+    anchor_links: bool,
+    context: Context,
+    word_diff: bool,
+    keep_link_changes: bool,
+    directional: bool,
+}
+
+impl DiffRequest {
+    /// Parses `/diff/<stage>/<source>/<target>?format=json&with_old=true&full=true&exact_types=true&inherit=true`.
+    fn parse(url: &str) -> Result<Self, (u16, String)> {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+
+        let segments = path
+            .trim_matches('/')
+            .split('/')
+            .map(|s| decode(s, false))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (Some("diff"), [stage, source, target]) = (
+            segments.first().map(String::as_str),
+            segments.get(1..).unwrap_or_default(),
+        ) else {
+            return Err((404, "expected /diff/<stage>/<source>/<target>".to_owned()));
+        };
+
+        let mut res = Self {
+            stage: Docs::from_str(stage, true).map_err(|e| (400, e))?,
+            source: version(source)?,
+            target: version(target)?,
+            format: OutputFormat::default(),
+            with_old: false,
+            full: false,
+            exact_types: false,
+            inherit: false,
+            resolve_inheritance: false,
+            metrics: false,
+            anchor_links: false,
+            context: Context::None,
+            word_diff: false,
+            keep_link_changes: false,
+            directional: false,
+        };
+
+        for param in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = param.split_once('=').unwrap_or((param, "true"));
+            let (key, value) = (decode(key, true)?, decode(value, true)?);
+            let (key, value) = (key.as_str(), value.as_str());
+
+            let flag = || {
+                value
+                    .parse::<bool>()
+                    .map_err(|_| (400, format!("invalid value for {key}: {value}")))
+            };
+
+            match key {
+                "format" => {
+                    res.format = OutputFormat::from_str(value, true).map_err(|e| (400, e))?;
+                }
+                "with_old" => res.with_old = flag()?,
+                "full" => res.full = flag()?,
+                "exact_types" => res.exact_types = flag()?,
+                "inherit" => res.inherit = flag()?,
+                "resolve_inheritance" => res.resolve_inheritance = flag()?,
+                "metrics" => res.metrics = flag()?,
+                "anchor_links" => res.anchor_links = flag()?,
+                "word_diff" => res.word_diff = flag()?,
+                "keep_link_changes" => res.keep_link_changes = flag()?,
+                "directional" => res.directional = flag()?,
+                "context" => {
+                    res.context = Context::from_str(value, true).map_err(|e| (400, e))?;
+                }
+                _ => return Err((400, format!("unknown query parameter: {key}"))),
+            }
+        }
+
+        Ok(res)
+    }
+
+    fn options(&self) -> DiffOptions {
+        DiffOptions {
+            policy: if self.full {
+                FieldPolicy::all()
+            } else {
+                FieldPolicy::default()
+            },
+            exact_types: self.exact_types,
+            inherit: self.inherit,
+            resolve_inheritance: self.resolve_inheritance,
+            metrics: self.metrics,
+            anchor_links: self.anchor_links,
+            context: self.context,
+            word_diff: self.word_diff,
+            keep_link_changes: self.keep_link_changes,
+            directional: self.directional,
+        }
+    }
+}
+
+/// A requested version, `latest` or a dotted version number like `1.1.110`.
+///
+/// Anything else is rejected, so paths like `../../etc` or `/etc` can't name files outside of `--docs-dir`
+/// and URLs can't make the server fetch other sites.
+fn version(segment: &str) -> Result<String, (u16, String)> {
+    if segment == "latest" || fapi_diff::versions::parts(segment).is_some() {
+        Ok(segment.to_owned())
+    } else {
+        Err((
+            400,
+            format!("invalid version {segment:?}, expected `latest` or a version like 1.1.110"),
+        ))
+    }
+}
+
+/// Decodes the percent-encoded URL component `s`, `+` is a space in query components.
+fn decode(s: &str, query: bool) -> Result<String, (u16, String)> {
+    let invalid = || (400, format!("invalid percent-encoding: {s}"));
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+
+    while let Some((&b, tail)) = rest.split_first() {
+        rest = tail;
+
+        match b {
+            b'%' => {
+                let hex = rest.get(..2).ok_or_else(invalid)?;
+                let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+                bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+                rest = &rest[2..];
+            }
+            b'+' if query => bytes.push(b' '),
+            _ => bytes.push(b),
+        }
+    }
+
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+/// Content type of a response in `format`.
+const fn content_type(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Json | OutputFormat::Flat | OutputFormat::JsonPatch => "application/json",
+        OutputFormat::Ndjson => "application/x-ndjson",
+        OutputFormat::Markdown => "text/markdown; charset=utf-8",
+        OutputFormat::Csv => "text/csv; charset=utf-8",
+        OutputFormat::Tsv => "text/tab-separated-values; charset=utf-8",
+        OutputFormat::BBCode => "text/plain; charset=utf-8",
+        #[cfg(feature = "tui")]
+        OutputFormat::Tui => "text/plain; charset=utf-8",
+    }
+}
+
+/// The most recently requested diffs, the least recently requested one is dropped first.
+struct Diffs {
+    capacity: usize,
+    entries: VecDeque<(DiffRequest, String)>,
+}
+
+impl Diffs {
+    const fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, req: &DiffRequest) -> Option<&String> {
+        let pos = self.entries.iter().position(|(r, _)| r == req)?;
+        let entry = self.entries.remove(pos)?;
+        self.entries.push_front(entry);
+
+        self.entries.front().map(|(_, body)| body)
+    }
+
+    fn insert(&mut self, req: DiffRequest, body: String) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.entries.truncate(self.capacity - 1);
+        self.entries.push_front((req, body));
+    }
+}
+
+pub fn run(args: &ServeArgs) -> Result<()> {
+    let server = match Server::http(&args.bind) {
+        Ok(s) => s,
+        Err(e) => anyhow::bail!("Failed to listen on {}: {e}", args.bind),
+    };
+
+    eprintln!("Listening on http://{}", args.bind);
+
+    let mut diffs = Diffs::new(args.cache_size);
+
+    for request in server.incoming_requests() {
+        let res = DiffRequest::parse(request.url()).and_then(|req| {
+            let format = req.format;
+            if let Some(body) = diffs.get(&req) {
+                return Ok((format, body.clone()));
+            }
+
+            let body = diff(args, &req)?;
+            diffs.insert(req, body.clone());
+
+            Ok((format, body))
+        });
+        let (status, content_type, body) = match res {
+            Ok((format, body)) => (200, content_type(format), body),
+            Err((status, body)) => (status, "text/plain; charset=utf-8", body),
+        };
+
+        let response = Response::from_string(body).with_status_code(status);
+        let response = match Header::from_bytes("Content-Type", content_type) {
+            Ok(h) => response.with_header(h),
+            Err(()) => response,
+        };
+
+        if let Err(e) = request.respond(response) {
+            eprintln!("Failed to respond: {e}");
+        }
+    }
+
+    Ok(())
+}
+
+fn diff(args: &ServeArgs, req: &DiffRequest) -> Result<String, (u16, String)> {
+    let fetch = |version: &str| {
+        args.docs_dir
+            .as_ref()
+            .map_or_else(
+                || crate::get(req.stage, version),
+                |dir| crate::get_local(req.stage, &dir.join(version)),
+            )
+            .map_err(|e| {
+                (
+                    404,
+                    format!("Failed to get {} docs {version}: {e}", req.stage),
+                )
+            })
+    };
+
+    let (source, target) = (fetch(&req.source)?, fetch(&req.target)?);
+
+    let res = req
+        .stage
+        .diff(&source, &target, req.options(), req.format, req.with_old)
+        .map_err(|e| (500, e.to_string()));
+
+    super::print_warnings();
+
+    res
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::DiffRequest;
+
+    fn status(url: &str) -> Option<u16> {
+        DiffRequest::parse(url).err().map(|(status, _)| status)
+    }
+
+    #[test]
+    fn versions_are_accepted() {
+        let req = DiffRequest::parse("/diff/runtime/1.1.110/latest").expect("valid request");
+
+        assert_eq!(
+            (req.source.as_str(), req.target.as_str()),
+            ("1.1.110", "latest")
+        );
+        assert_eq!(status("/diff/runtime/1%2E1%2E110/2.0.8"), None);
+    }
+
+    #[test]
+    fn parent_directories_are_rejected() {
+        assert_eq!(status("/diff/runtime/../2.0.8"), Some(400));
+        assert_eq!(status("/diff/runtime/1.1.110/%2E%2E"), Some(400));
+        assert_eq!(status("/diff/runtime/..%2F..%2Fetc/2.0.8"), Some(400));
+    }
+
+    #[test]
+    fn slashes_are_rejected() {
+        assert_eq!(status("/diff/runtime/%2Fetc%2Fpasswd/2.0.8"), Some(400));
+        assert_eq!(status("/diff/runtime/1.1.110%2F..%2Fx/2.0.8"), Some(400));
+    }
+
+    #[test]
+    fn backslashes_are_rejected() {
+        assert_eq!(status("/diff/runtime/..%5C..%5Cetc/2.0.8"), Some(400));
+        assert_eq!(status("/diff/runtime/C:%5Cdocs/2.0.8"), Some(400));
+    }
+
+    #[test]
+    fn urls_are_rejected() {
+        assert_eq!(
+            status("/diff/runtime/https:%2F%2Fexample.com%2Fdocs.json/2.0.8"),
+            Some(400)
+        );
+        assert_eq!(
+            status("/diff/runtime/1.1.110/http:%2F%2F127.0.0.1"),
+            Some(400)
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Diffs split into one file per changed item, for static sites that build a page per item.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::{
+    format::{DiffEntry, ItemStatus},
+    render::{flat::FlatChange, markdown::Markdown, visit::Renderer, JsonStyle, OutputFormat},
+    Error, Result,
+};
+
+/// Name of the index file listing the files of all changed items.
+pub const INDEX: &str = "index.json";
+
+/// A changed item of a split diff, as listed in the index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IndexEntry {
+    /// Section of the docs the item belongs to, e.g. `class` or `prototype`
+    pub kind: &'static str,
+    pub name: String,
+    pub status: ItemStatus,
+    /// Path of the file of the item relative to the output directory, e.g. `classes/LuaEntity.json`
+    pub file: String,
+}
+
+/// Writes each entry to its own file in `dir`, e.g. `classes/LuaEntity.json`, and the index of all files.
+///
+/// JSON files hold the entry like a line of [`OutputFormat::Ndjson`], markdown files the changelog of
+/// the item, rendered from the `changes` below it. Other formats can't be split.
+/// Returns the index, which is written as JSON in `style` to [`INDEX`].
+pub fn write(
+    dir: &Path,
+    format: OutputFormat,
+    style: JsonStyle,
+    entries: &[DiffEntry],
+    changes: &[FlatChange],
+) -> Result<Vec<IndexEntry>> {
+    let extension = match format {
+        OutputFormat::Json => "json",
+        OutputFormat::Markdown => "md",
+        _ => {
+            return Err(Error::Render(anyhow::anyhow!(
+                "Only the json and markdown formats can be split into files"
+            )))
+        }
+    };
+
+    let mut index = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        let section = entry.section();
+        let file = format!("{section}/{}.{extension}", file_name(&entry.name));
+        std::fs::create_dir_all(dir.join(section))?;
+
+        let content = if format == OutputFormat::Json {
+            style.to_string(entry)?
+        } else {
+            let path = format!(
+                "{section}/{}",
+                entry.name.replace('~', "~0").replace('/', "~1")
+            );
+            let item = changes
+                .iter()
+                .filter(|c| {
+                    c.path == path
+                        || c.path
+                            .strip_prefix(&path)
+                            .is_some_and(|rest| rest.starts_with('/'))
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let mut out = Vec::new();
+            Markdown::new(&mut out).render(&item)?;
+
+            String::from_utf8(out).map_err(anyhow::Error::from)?
+        };
+        std::fs::write(dir.join(&file), content)?;
+
+        index.push(IndexEntry {
+            kind: entry.kind,
+            name: entry.name.clone(),
+            status: entry.status,
+            file,
+        });
+    }
+
+    std::fs::write(dir.join(INDEX), style.to_string(&index)?)?;
+
+    Ok(index)
+}
+
+/// Item name usable as a file name on all platforms.
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c => c,
+        })
+        .collect()
+}
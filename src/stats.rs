@@ -0,0 +1,164 @@
+//! Statistics of a single doc, independent of diffing.
+
+use std::{collections::BTreeMap, fmt};
+
+use serde::Serialize;
+
+use crate::{
+    format::{
+        prototype::PrototypeDoc,
+        runtime::{Method, RuntimeDoc},
+        Common,
+    },
+    Docs,
+};
+
+/// Counts of the items of a doc and metrics derived from them.
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    #[serde(flatten)]
+    pub common: Common,
+    /// Number of items per kind, e.g. `classes` or `methods`
+    pub counts: BTreeMap<&'static str, usize>,
+    /// Averages derived from the counts, e.g. `parameters_per_method`
+    pub derived: BTreeMap<&'static str, f64>,
+}
+
+impl Stats {
+    /// Reads the stats of a raw doc of any stage.
+    pub fn parse(raw: &[u8], what: &str) -> crate::Result<Self> {
+        Ok(match Docs::detect(raw)? {
+            Docs::Runtime => (&crate::parse::<RuntimeDoc>(raw, what)?).into(),
+            Docs::Prototype => (&crate::parse::<PrototypeDoc>(raw, what)?).into(),
+        })
+    }
+}
+
+#[allow(clippy::cast_precision_loss)] // the counts are far below 2^52
+fn average(total: usize, count: usize) -> f64 {
+    if count == 0 {
+        return 0.0;
+    }
+
+    total as f64 / count as f64
+}
+
+impl From<&RuntimeDoc> for Stats {
+    fn from(doc: &RuntimeDoc) -> Self {
+        let classes = doc.classes.values();
+        let methods = classes.clone().flat_map(|c| c.methods.values());
+        let parameters = methods
+            .clone()
+            .chain(doc.global_functions.values())
+            .map(|m: &Method| m.parameters.len())
+            .sum::<usize>();
+
+        let counts = BTreeMap::from([
+            ("classes", doc.classes.len()),
+            (
+                "abstract_classes",
+                classes.clone().filter(|c| c.abstract_).count(),
+            ),
+            ("methods", methods.count()),
+            (
+                "attributes",
+                classes.clone().map(|c| c.attributes.len()).sum(),
+            ),
+            ("operators", classes.map(|c| c.operators.len()).sum()),
+            ("events", doc.events.len()),
+            ("concepts", doc.concepts.len()),
+            ("defines", doc.defines.len()),
+            (
+                "define_values",
+                crate::defines::paths(doc.defines.values()).len(),
+            ),
+            ("global_objects", doc.global_objects.len()),
+            ("global_functions", doc.global_functions.len()),
+        ]);
+
+        let derived = BTreeMap::from([
+            (
+                "methods_per_class",
+                average(counts["methods"], counts["classes"]),
+            ),
+            (
+                "attributes_per_class",
+                average(counts["attributes"], counts["classes"]),
+            ),
+            (
+                "parameters_per_method",
+                average(parameters, counts["methods"] + counts["global_functions"]),
+            ),
+        ]);
+
+        Self {
+            common: (**doc).clone(),
+            counts,
+            derived,
+        }
+    }
+}
+
+impl From<&PrototypeDoc> for Stats {
+    fn from(doc: &PrototypeDoc) -> Self {
+        let prototypes = doc.prototypes.values();
+
+        let counts = BTreeMap::from([
+            ("prototypes", doc.prototypes.len()),
+            (
+                "abstract_prototypes",
+                prototypes.clone().filter(|p| p.abstract_).count(),
+            ),
+            (
+                "deprecated_prototypes",
+                prototypes.clone().filter(|p| p.deprecated).count(),
+            ),
+            ("properties", prototypes.map(|p| p.properties.len()).sum()),
+            ("types", doc.types.len()),
+            ("defines", doc.defines.len()),
+            (
+                "define_values",
+                crate::defines::paths(doc.defines.values()).len(),
+            ),
+        ]);
+
+        let derived = BTreeMap::from([(
+            "properties_per_prototype",
+            average(counts["properties"], counts["prototypes"]),
+        )]);
+
+        Self {
+            common: (**doc).clone(),
+            counts,
+            derived,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} @ {}: {:?}",
+            self.common.application, self.common.application_version, self.common.stage
+        )?;
+
+        let width = self
+            .counts
+            .keys()
+            .chain(self.derived.keys())
+            .map(|k| k.len())
+            .max()
+            .unwrap_or_default();
+
+        for (name, count) in &self.counts {
+            writeln!(f, " - {name:width$} {count}")?;
+        }
+
+        for (name, value) in &self.derived {
+            writeln!(f, " - {name:width$} {value:.2}")?;
+        }
+
+        Ok(())
+    }
+}
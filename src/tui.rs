@@ -0,0 +1,355 @@
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+use serde_json::Value;
+
+use crate::render::flat::{self, FlatChange, UnionChange};
+
+/// How much a change is likely to affect mods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Severity {
+    /// Documentation only changes
+    Info,
+    /// Additions and other compatible changes
+    Minor,
+    /// Removals and type changes
+    Major,
+}
+
+impl Severity {
+    const fn next(self) -> Self {
+        match self {
+            Self::Info => Self::Minor,
+            Self::Minor => Self::Major,
+            Self::Major => Self::Info,
+        }
+    }
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Minor => "minor",
+            Self::Major => "major",
+        }
+    }
+
+    const fn color(self) -> Color {
+        match self {
+            Self::Info => Color::DarkGray,
+            Self::Minor => Color::Yellow,
+            Self::Major => Color::Red,
+        }
+    }
+
+    /// `unions` are the options added to and removed from the unions of the diff,
+    /// changes of union options are as severe as the most severe of them.
+    fn of(change: &FlatChange, unions: &[UnionChange]) -> Self {
+        const DOCS: [&str; 5] = ["description", "examples", "lists", "images", "order"];
+        const TYPES: [&str; 4] = ["type", "complex_type", "optional", "read"];
+
+        let mut segments = change.path.split('/');
+
+        // attributes that lost read or write access break mods that use them that way
+        if let Some(access) = change.access_change() {
+            return if access.is_breaking() {
+                Self::Major
+            } else {
+                Self::Minor
+            };
+        }
+
+        // widened inputs and narrowed outputs can't break mods
+        if let Some(path) = change.union() {
+            let mut options = unions.iter().filter(|u| u.path == path).peekable();
+            if options.peek().is_some() {
+                return if options.any(UnionChange::is_breaking) {
+                    Self::Major
+                } else {
+                    Self::Minor
+                };
+            }
+        }
+
+        if let Some(direction) = change.direction() {
+            // loosened inputs and tightened outputs can't break mods
+            let input = segments.any(|s| s == "parameters" || s == "properties");
+
+            return match direction {
+                "now_required" if !input => Self::Minor,
+                "now_optional" if input => Self::Minor,
+                "now_writable" => Self::Minor,
+                _ => Self::Major,
+            };
+        }
+
+        if segments.clone().any(|s| DOCS.contains(&s)) {
+            Self::Info
+        } else if change.new.is_null() || segments.any(|s| TYPES.contains(&s)) {
+            Self::Major
+        } else {
+            Self::Minor
+        }
+    }
+}
+
+/// A changed top level item, e.g. a class or prototype.
+struct Item {
+    name: String,
+    changes: Vec<(Severity, FlatChange)>,
+}
+
+impl Item {
+    fn severity(&self) -> Severity {
+        self.changes
+            .iter()
+            .map(|(s, _)| *s)
+            .max()
+            .unwrap_or(Severity::Info)
+    }
+}
+
+struct App {
+    items: Vec<Item>,
+    visible: Vec<usize>,
+    list: ListState,
+    scroll: u16,
+    query: String,
+    searching: bool,
+    min_severity: Severity,
+}
+
+impl App {
+    fn new(changes: Vec<FlatChange>, unions: &[UnionChange]) -> Self {
+        let mut items: Vec<Item> = Vec::new();
+
+        for change in changes {
+            // the first two segments identify the item, e.g. `classes/LuaEntity`
+            let name = change
+                .path
+                .splitn(3, '/')
+                .take(2)
+                .collect::<Vec<_>>()
+                .join("/");
+            let severity = Severity::of(&change, unions);
+
+            match items.iter_mut().find(|i| i.name == name) {
+                Some(item) => item.changes.push((severity, change)),
+                None => items.push(Item {
+                    name,
+                    changes: vec![(severity, change)],
+                }),
+            }
+        }
+
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut app = Self {
+            items,
+            visible: Vec::new(),
+            list: ListState::default(),
+            scroll: 0,
+            query: String::new(),
+            searching: false,
+            min_severity: Severity::Info,
+        };
+        app.refilter();
+
+        app
+    }
+
+    fn refilter(&mut self) {
+        let query = self.query.to_lowercase();
+
+        self.visible = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| i.severity() >= self.min_severity)
+            .filter(|(_, i)| i.name.to_lowercase().contains(&query))
+            .map(|(idx, _)| idx)
+            .collect();
+
+        self.list.select((!self.visible.is_empty()).then_some(0));
+        self.scroll = 0;
+    }
+
+    fn selected(&self) -> Option<&Item> {
+        self.list
+            .selected()
+            .and_then(|s| self.visible.get(s))
+            .map(|idx| &self.items[*idx])
+    }
+
+    /// Handles a key press, returns `false` when the browser should close.
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        if self.searching {
+            match key {
+                KeyCode::Enter | KeyCode::Esc => self.searching = false,
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.refilter();
+                }
+                KeyCode::Char(c) => {
+                    self.query.push(c);
+                    self.refilter();
+                }
+                _ => {}
+            }
+
+            return true;
+        }
+
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Char('s') => {
+                self.min_severity = self.min_severity.next();
+                self.refilter();
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.list.select_next();
+                self.scroll = 0;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.list.select_previous();
+                self.scroll = 0;
+            }
+            KeyCode::PageDown | KeyCode::Char('J') => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::PageUp | KeyCode::Char('K') => self.scroll = self.scroll.saturating_sub(10),
+            _ => {}
+        }
+
+        true
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Min(0)]).areas(main);
+
+        let items = self
+            .visible
+            .iter()
+            .map(|idx| {
+                let item = &self.items[*idx];
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{} ", item.name)),
+                    Span::styled(
+                        format!("({})", item.changes.len()),
+                        Style::new().fg(item.severity().color()),
+                    ),
+                ]))
+            })
+            .collect::<Vec<_>>();
+
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::bordered().title(format!(" Changed items ({}) ", self.visible.len())))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            left,
+            &mut self.list,
+        );
+
+        let (title, lines) = self.selected().map_or_else(
+            || (String::new(), Vec::new()),
+            |item| (format!(" {} ", item.name), self.detail_lines(item)),
+        );
+
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(Block::bordered().title(title))
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0)),
+            right,
+        );
+
+        let status_line = if self.searching {
+            Line::from(format!("/{}", self.query))
+        } else {
+            Line::from(format!(
+                " [/] search: {:?}  [s] min severity: {}  [j/k] select  [J/K] scroll  [q] quit",
+                self.query,
+                self.min_severity.label()
+            ))
+            .dim()
+        };
+
+        frame.render_widget(status_line, status);
+    }
+
+    fn detail_lines(&self, item: &Item) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+
+        for (severity, change) in &item.changes {
+            if *severity < self.min_severity {
+                continue;
+            }
+
+            let path = change
+                .path
+                .strip_prefix(&item.name)
+                .unwrap_or(&change.path)
+                .trim_start_matches('/');
+
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("[{}] ", severity.label()),
+                    Style::new().fg(severity.color()),
+                ),
+                Span::styled(
+                    if path.is_empty() { "/" } else { path }.to_owned(),
+                    Style::new().bold(),
+                ),
+            ]));
+
+            if !change.old.is_null() {
+                lines.push(Line::from(format!("  - {}", compact(&change.old))).red());
+            }
+
+            if !change.new.is_null() {
+                lines.push(Line::from(format!("  + {}", compact(&change.new))).green());
+            }
+
+            lines.push(Line::default());
+        }
+
+        lines
+    }
+}
+
+fn compact(value: &Value) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// Opens an interactive browser for the diff.
+///
+/// `source` and `target` are the compared docs, they are used to resolve old / new values.
+pub fn browse(diff: &Value, source: &Value, target: &Value) -> anyhow::Result<()> {
+    let changes = flat::flatten(diff, source, target);
+    let unions = flat::union_changes(&changes, diff, source, target);
+    let mut app = App::new(changes, &unions);
+
+    let mut terminal = ratatui::init();
+    let res = run(&mut terminal, &mut app);
+    ratatui::restore();
+
+    res
+}
+
+fn run(terminal: &mut DefaultTerminal, app: &mut App) -> anyhow::Result<()> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && !app.handle_key(key.code) {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Versions of the docs published on lua-api.factorio.com and the latest releases of the game.
+
+use std::{collections::BTreeSet, fmt, path::PathBuf, sync::LazyLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+/// Index page listing all published versions of the docs.
+pub const INDEX_URL: &str = "https://lua-api.factorio.com/";
+
+/// Latest stable and experimental versions of the game.
+pub const RELEASES_URL: &str = "https://factorio.com/api/latest-releases";
+
+/// Parts of a dotted version number like `1.1.107`, `None` for anything else.
+#[must_use]
+pub fn parts(version: &str) -> Option<Vec<u64>> {
+    version.split('.').map(|p| p.parse().ok()).collect()
+}
+
+/// Versions linked on the index page of the published docs, oldest first.
+#[must_use]
+pub fn parse_index(html: &str) -> Vec<String> {
+    static LINK: LazyLock<Option<Regex>> = LazyLock::new(|| {
+        Regex::new(r#"href="(?:https://lua-api\.factorio\.com)?\.?/?(\d+(?:\.\d+)+)/"#).ok()
+    });
+
+    sorted(
+        LINK.iter()
+            .flat_map(|link| link.captures_iter(html))
+            .map(|c| c[1].to_owned()),
+    )
+}
+
+/// File the published versions are cached in for shell completions,
+/// `fapi-diff/versions.txt` in the user's cache directory.
+#[must_use]
+pub fn cache_file() -> Option<PathBuf> {
+    let var = |name| {
+        std::env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+    let dir = var("XDG_CACHE_HOME")
+        .or_else(|| var("LOCALAPPDATA"))
+        .or_else(|| var("HOME").map(|home| home.join(".cache")))?;
+
+    Some(dir.join("fapi-diff").join("versions.txt"))
+}
+
+/// Versions cached by [`store`], oldest first, empty if none were cached.
+#[must_use]
+pub fn cached() -> Vec<String> {
+    cache_file()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|cached| sorted(cached.lines().map(str::to_owned)))
+        .unwrap_or_default()
+}
+
+/// Caches the published versions for shell completions, one per line.
+pub fn store(published: &[String]) -> Result<()> {
+    let Some(path) = cache_file() else {
+        return Ok(());
+    };
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, published.join("\n"))?;
+
+    Ok(())
+}
+
+/// Dotted versions sorted oldest first without duplicates.
+fn sorted(versions: impl IntoIterator<Item = String>) -> Vec<String> {
+    versions
+        .into_iter()
+        .filter_map(|v| Some((parts(&v)?, v)))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|(_, v)| v)
+        .collect()
+}
+
+/// The latest stable and experimental versions of the game.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct Releases {
+    pub stable: Option<String>,
+    pub experimental: Option<String>,
+}
+
+impl Releases {
+    /// Reads the latest releases of the base game from the JSON of [`RELEASES_URL`].
+    pub fn parse(raw: &[u8]) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct Builds {
+            alpha: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        struct Raw {
+            stable: Option<Builds>,
+            experimental: Option<Builds>,
+        }
+
+        let raw = crate::parse::<Raw>(raw, "latest releases")?;
+
+        Ok(Self {
+            stable: raw.stable.and_then(|b| b.alpha),
+            experimental: raw.experimental.and_then(|b| b.alpha),
+        })
+    }
+}
+
+/// A version of the docs, published or only available locally.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Version {
+    pub version: String,
+    /// Listed on the index page of the published docs
+    pub published: bool,
+    /// Available in the local docs directory
+    pub local: bool,
+}
+
+/// All known versions of the docs and the latest releases, see [`Versions::new`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Versions {
+    pub versions: Vec<Version>,
+    pub stable: Option<String>,
+    pub experimental: Option<String>,
+}
+
+impl Versions {
+    /// Merges the `published` and `local` versions, oldest first.
+    #[must_use]
+    pub fn new(published: &[String], local: &[String], releases: Releases) -> Self {
+        let versions = sorted(published.iter().chain(local).cloned())
+            .into_iter()
+            .map(|version| Version {
+                published: published.contains(&version),
+                local: local.contains(&version),
+                version,
+            })
+            .collect();
+
+        Self {
+            versions,
+            stable: releases.stable,
+            experimental: releases.experimental,
+        }
+    }
+
+    /// The latest stable and experimental versions if both have docs, e.g. to diff them.
+    #[must_use]
+    pub fn pair(&self) -> Option<(&str, &str)> {
+        let has_docs = |v: &str| self.versions.iter().any(|d| d.version == v);
+
+        match (self.stable.as_deref(), self.experimental.as_deref()) {
+            (Some(stable), Some(experimental)) if has_docs(stable) && has_docs(experimental) => {
+                Some((stable, experimental))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Versions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for v in &self.versions {
+            let tags = [
+                (self.stable.as_ref() == Some(&v.version), "stable"),
+                (
+                    self.experimental.as_ref() == Some(&v.version),
+                    "experimental",
+                ),
+                (!v.published, "unpublished"),
+                (v.local, "local"),
+            ]
+            .into_iter()
+            .filter_map(|(set, tag)| set.then_some(tag))
+            .collect::<Vec<_>>();
+
+            if tags.is_empty() {
+                writeln!(f, "{}", v.version)?;
+            } else {
+                writeln!(f, "{} ({})", v.version, tags.join(", "))?;
+            }
+        }
+
+        Ok(())
+    }
+}
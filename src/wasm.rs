@@ -0,0 +1,29 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{embed::Options, Docs};
+
+/// Diffs two docs of the given `stage` (`prototype` or `runtime`).
+///
+/// `options` is an optional JSON object like
+/// `{"categories": ["names", "types"], "exact_types": false, "inherit": true, "resolve_inheritance": false, "metrics": false, "anchor_links": false, "context": "none", "word_diff": false, "keep_link_changes": false, "directional": false, "format": "flat", "with_old": true}`.
+#[wasm_bindgen]
+#[allow(clippy::needless_pass_by_value)] // wasm_bindgen can't pass optional strings by reference
+pub fn diff(
+    stage: &str,
+    source: &str,
+    target: &str,
+    options: Option<String>,
+) -> Result<String, JsError> {
+    let stage = <Docs as clap::ValueEnum>::from_str(stage, true).map_err(|e| JsError::new(&e))?;
+    let options = Options::parse(options.as_deref())?;
+
+    stage
+        .diff(
+            source.as_bytes(),
+            target.as_bytes(),
+            options.diff_options(),
+            options.format,
+            options.with_old,
+        )
+        .map_err(|e| JsError::new(&e.to_string()))
+}
@@ -0,0 +1,390 @@
+//! Reading the docs out of zip archives like the downloadable docs bundle.
+//!
+//! Only what the bundle needs is supported: stored and deflated entries without zip64 extensions.
+
+use crate::{Docs, Error, Result};
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+
+/// Whether `raw` starts like a zip archive.
+#[must_use]
+pub fn is_zip(raw: &[u8]) -> bool {
+    raw.starts_with(&LOCAL_HEADER.to_le_bytes())
+}
+
+/// Reads the doc of `stage` from the archive, the `<stage>-api.json` with the shortest path.
+pub fn read_stage(raw: &[u8], stage: Docs) -> Result<Vec<u8>> {
+    let file = format!("{stage}-api.json");
+    let error = |reason: &str| Error::Archive {
+        file: file.clone(),
+        reason: reason.to_owned(),
+    };
+
+    let entry = entries(raw)
+        .map_err(error)?
+        .into_iter()
+        .filter(|e| e.name.rsplit('/').next() == Some(file.as_str()))
+        .min_by_key(|e| e.name.len())
+        .ok_or_else(|| error("not found"))?;
+
+    entry.read(raw).map_err(error)
+}
+
+/// A file in the central directory of an archive.
+struct Entry {
+    name: String,
+    method: u16,
+    crc: u32,
+    compressed_size: usize,
+    size: usize,
+    offset: usize,
+}
+
+fn u16_at(raw: &[u8], pos: usize) -> Result<u16, &'static str> {
+    raw.get(pos..pos + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or("truncated archive")
+}
+
+fn u32_at(raw: &[u8], pos: usize) -> Result<u32, &'static str> {
+    raw.get(pos..pos + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or("truncated archive")
+}
+
+fn usize_at(raw: &[u8], pos: usize) -> Result<usize, &'static str> {
+    match u32_at(raw, pos)? {
+        u32::MAX => Err("zip64 archives are not supported"),
+        v => usize::try_from(v).map_err(|_| "archive too large"),
+    }
+}
+
+fn entries(raw: &[u8]) -> Result<Vec<Entry>, &'static str> {
+    // the end of central directory record is followed by a comment of up to 64 KiB
+    let end = (0..=raw.len().saturating_sub(22))
+        .rev()
+        .take(0x1_0000 + 22)
+        .find(|&pos| u32_at(raw, pos) == Ok(END_OF_CENTRAL_DIRECTORY))
+        .ok_or("not a zip archive")?;
+
+    let count = u16_at(raw, end + 10)?;
+    let mut pos = usize_at(raw, end + 16)?;
+    let mut res = Vec::with_capacity(usize::from(count));
+
+    for _ in 0..count {
+        if u32_at(raw, pos)? != CENTRAL_HEADER {
+            return Err("malformed central directory");
+        }
+
+        let name_len = usize::from(u16_at(raw, pos + 28)?);
+        let extra_len = usize::from(u16_at(raw, pos + 30)?);
+        let comment_len = usize::from(u16_at(raw, pos + 32)?);
+        let name = raw
+            .get(pos + 46..pos + 46 + name_len)
+            .ok_or("truncated archive")?;
+
+        res.push(Entry {
+            name: String::from_utf8_lossy(name).into_owned(),
+            method: u16_at(raw, pos + 10)?,
+            crc: u32_at(raw, pos + 16)?,
+            compressed_size: usize_at(raw, pos + 20)?,
+            size: usize_at(raw, pos + 24)?,
+            offset: usize_at(raw, pos + 42)?,
+        });
+
+        pos += 46 + name_len + extra_len + comment_len;
+    }
+
+    Ok(res)
+}
+
+impl Entry {
+    fn read(&self, raw: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if u32_at(raw, self.offset)? != LOCAL_HEADER {
+            return Err("malformed local header");
+        }
+
+        // the local header has its own name and extra field lengths
+        let start = self.offset
+            + 30
+            + usize::from(u16_at(raw, self.offset + 26)?)
+            + usize::from(u16_at(raw, self.offset + 28)?);
+        let data = raw
+            .get(start..start + self.compressed_size)
+            .ok_or("truncated archive")?;
+
+        let res = match self.method {
+            0 => data.to_vec(),
+            8 => inflate(data, self.size)?,
+            _ => return Err("unsupported compression method"),
+        };
+
+        if res.len() != self.size || crc32(&res) != self.crc {
+            return Err("checksum mismatch");
+        }
+
+        Ok(res)
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+
+    for (n, entry) in (0u32..).zip(table.iter_mut()) {
+        *entry = (0..8).fold(n, |c, _| {
+            if c & 1 == 1 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            }
+        });
+    }
+
+    !data.iter().fold(!0u32, |crc, &b| {
+        table[usize::from(crc.to_le_bytes()[0] ^ b)] ^ (crc >> 8)
+    })
+}
+
+/// Reads the bits of a deflate stream, least significant bit first.
+struct Bits<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buf: u32,
+    count: u32,
+}
+
+impl Bits<'_> {
+    fn bits(&mut self, n: u32) -> Result<u32, &'static str> {
+        while self.count < n {
+            let byte = self.data.get(self.pos).ok_or("truncated deflate stream")?;
+            self.buf |= u32::from(*byte) << self.count;
+            self.pos += 1;
+            self.count += 8;
+        }
+
+        let res = self.buf & ((1 << n) - 1);
+        self.buf = self.buf.checked_shr(n).unwrap_or_default();
+        self.count -= n;
+
+        Ok(res)
+    }
+
+    /// Bits as an index into the tables below.
+    fn index(&mut self, n: u8) -> Result<usize, &'static str> {
+        usize::try_from(self.bits(u32::from(n))?).map_err(|_| "invalid deflate stream")
+    }
+}
+
+/// Canonical Huffman code, decoded like zlib's `puff`.
+struct Huffman {
+    /// Number of codes of each length
+    counts: [u16; 16],
+    /// Symbols ordered by their code
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+
+        for &len in lengths {
+            counts[usize::from(len)] += 1;
+        }
+
+        let mut symbols = (0u16..)
+            .zip(lengths)
+            .filter(|(_, &len)| len > 0)
+            .collect::<Vec<_>>();
+        symbols.sort_by_key(|(symbol, &len)| (len, *symbol));
+
+        Self {
+            counts,
+            symbols: symbols.into_iter().map(|(symbol, _)| symbol).collect(),
+        }
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16, &'static str> {
+        let (mut code, mut first, mut index) = (0u32, 0u32, 0u32);
+
+        for &count in &self.counts[1..] {
+            code |= bits.bits(1)?;
+            let count = u32::from(count);
+
+            if code < first + count {
+                let symbol = usize::try_from(index + code - first).map_err(|_| "invalid code")?;
+                return self.symbols.get(symbol).copied().ok_or("invalid code");
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err("invalid code")
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order of the code length code lengths of dynamic blocks.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Reason for entries that inflate to more than the central directory states.
+const TOO_LARGE: &str = "entry is larger than its declared size";
+
+/// Decompresses a raw deflate stream, `size` is the expected size of the output.
+fn inflate(data: &[u8], size: usize) -> Result<Vec<u8>, &'static str> {
+    let mut bits = Bits {
+        data,
+        pos: 0,
+        buf: 0,
+        count: 0,
+    };
+    let mut out = Vec::with_capacity(size);
+
+    loop {
+        let last = bits.bits(1)? == 1;
+
+        match bits.bits(2)? {
+            0 => {
+                // stored blocks start at a byte boundary
+                bits.buf = 0;
+                bits.count = 0;
+                let len = u16_at(data, bits.pos)?;
+                if u16_at(data, bits.pos + 2)? != !len {
+                    return Err("invalid stored block length");
+                }
+
+                let (start, len) = (bits.pos + 4, usize::from(len));
+                if out.len() + len > size {
+                    return Err(TOO_LARGE);
+                }
+
+                out.extend_from_slice(data.get(start..start + len).ok_or("truncated block")?);
+                bits.pos = start + len;
+            }
+            1 => {
+                let mut lengths = [8u8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+
+                block(
+                    &mut bits,
+                    &mut out,
+                    size,
+                    &Huffman::new(&lengths),
+                    &Huffman::new(&[5; 30]),
+                )?;
+            }
+            2 => {
+                let (lengths, distances) = dynamic_codes(&mut bits)?;
+                block(&mut bits, &mut out, size, &lengths, &distances)?;
+            }
+            _ => return Err("invalid block type"),
+        }
+
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+/// Reads the literal / length and distance codes of a dynamic block.
+fn dynamic_codes(bits: &mut Bits) -> Result<(Huffman, Huffman), &'static str> {
+    let literals = bits.index(5)? + 257;
+    let distances = bits.index(5)? + 1;
+    let code_lengths = bits.index(4)? + 4;
+
+    let mut lengths = [0u8; 19];
+    for &i in &CODE_LENGTH_ORDER[..code_lengths] {
+        lengths[i] = u8::try_from(bits.bits(3)?).map_err(|_| "invalid code length")?;
+    }
+    let code_length_code = Huffman::new(&lengths);
+
+    let mut lengths = Vec::with_capacity(literals + distances);
+    while lengths.len() < literals + distances {
+        let (value, repeat) = match code_length_code.decode(bits)? {
+            symbol @ 0..=15 => (u8::try_from(symbol).map_err(|_| "invalid code length")?, 1),
+            16 => (
+                *lengths.last().ok_or("repeat without a length")?,
+                3 + bits.index(2)?,
+            ),
+            17 => (0, 3 + bits.index(3)?),
+            _ => (0, 11 + bits.index(7)?),
+        };
+
+        lengths.extend(std::iter::repeat_n(value, repeat));
+    }
+
+    if lengths.len() > literals + distances {
+        return Err("too many code lengths");
+    }
+
+    Ok((
+        Huffman::new(&lengths[..literals]),
+        Huffman::new(&lengths[literals..]),
+    ))
+}
+
+/// Decodes the symbols of a compressed block until its end.
+///
+/// Fails as soon as the output grows past `size` instead of inflating a malicious entry without bound.
+fn block(
+    bits: &mut Bits,
+    out: &mut Vec<u8>,
+    size: usize,
+    lengths: &Huffman,
+    distances: &Huffman,
+) -> Result<(), &'static str> {
+    loop {
+        let symbol = usize::from(lengths.decode(bits)?);
+
+        if symbol != 256 && out.len() >= size {
+            return Err(TOO_LARGE);
+        }
+
+        match symbol {
+            0..=255 => out.push(u8::try_from(symbol).map_err(|_| "invalid literal")?),
+            256 => return Ok(()),
+            _ => {
+                let i = symbol - 257;
+                let len = usize::from(*LENGTH_BASE.get(i).ok_or("invalid length")?)
+                    + bits.index(LENGTH_EXTRA[i])?;
+
+                let i = usize::from(distances.decode(bits)?);
+                let distance = usize::from(*DISTANCE_BASE.get(i).ok_or("invalid distance")?)
+                    + bits.index(DISTANCE_EXTRA[i])?;
+
+                let start = out
+                    .len()
+                    .checked_sub(distance)
+                    .ok_or("distance too far back")?;
+                if out.len() + len > size {
+                    return Err(TOO_LARGE);
+                }
+
+                // the copied range may overlap the output it extends
+                for i in start..start + len {
+                    out.push(out[i]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Attributes that can be read or written in other ways are reported as a single access change.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{
+        runtime::{Access, AccessChange, RuntimeDoc},
+        DiffOptions, FieldPolicy, Invert,
+    },
+    Comparison, Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+/// The fixture of `version` and a copy with the `health` attribute of `LuaEntity` changed by `change`.
+fn docs(version: &str, change: impl FnOnce(&mut Value)) -> (Vec<u8>, Vec<u8>) {
+    (
+        common::raw(version, Docs::Runtime),
+        common::edited(version, Docs::Runtime, |doc| {
+            change(common::member(doc, "LuaEntity", "attributes", "health"));
+        }),
+    )
+}
+
+fn comparison((source, target): (Vec<u8>, Vec<u8>)) -> Comparison<RuntimeDoc> {
+    let options = DiffOptions {
+        policy: FieldPolicy::all(),
+        ..Default::default()
+    };
+
+    Comparison::new(&source, &target, options).expect("docs can be diffed")
+}
+
+fn health(c: &Comparison<RuntimeDoc>) -> Value {
+    serde_json::to_value(&c.diff).expect("diff is serializable")["classes"]["LuaEntity"]
+        .as_array()
+        .and_then(|diff| diff.iter().find_map(|d| d.get("attributes")))
+        .expect("attributes changed")["health"]
+        .clone()
+}
+
+fn flat(c: &Comparison<RuntimeDoc>) -> Vec<fapi_diff::render::flat::FlatChange> {
+    fapi_diff::render::flat::flatten(
+        &serde_json::to_value(&c.diff).expect("diff is serializable"),
+        &serde_json::to_value(&c.source).expect("doc is serializable"),
+        &serde_json::to_value(&c.target).expect("doc is serializable"),
+    )
+}
+
+#[test]
+fn read_only_write_type() {
+    let c = comparison(docs("2.0.8", |health| {
+        health
+            .as_object_mut()
+            .expect("attribute is an object")
+            .remove("write_type");
+    }));
+
+    let diff = health(&c);
+    let access = diff
+        .as_array()
+        .and_then(|d| d.iter().find_map(|d| d.get("access_changed")))
+        .expect("access changed");
+    assert_eq!(access, &json!({"from": "read_write", "to": "read"}));
+
+    let changes = flat(&c);
+    assert_eq!(changes.len(), 1, "{changes:?}");
+    assert_eq!(
+        changes[0].path,
+        "classes/LuaEntity/attributes/health/access"
+    );
+    assert_eq!(changes[0].direction(), Some("now_read_only"));
+
+    let change = changes[0].access_change().expect("access change");
+    assert!(change.is_breaking());
+    assert!(!change.invert().is_breaking());
+}
+
+#[test]
+fn read_only_flag() {
+    let c = comparison(docs("1.1.110", |health| health["write"] = json!(false)));
+
+    let changes = flat(&c);
+    assert_eq!(changes.len(), 1, "{changes:?}");
+    assert_eq!(changes[0].old, json!("read_write"));
+    assert_eq!(changes[0].new, json!("read"));
+}
+
+#[test]
+fn breaking_changes() {
+    let change = |from, to| AccessChange { from, to };
+
+    assert!(change(Access::ReadWrite, Access::Read).is_breaking());
+    assert!(change(Access::ReadWrite, Access::Write).is_breaking());
+    assert!(change(Access::Read, Access::Write).is_breaking());
+    assert!(!change(Access::Read, Access::ReadWrite).is_breaking());
+    assert!(!change(Access::None, Access::Read).is_breaking());
+}
@@ -0,0 +1,141 @@
+//! Patches of `--format json-patch` applied to docs, fully and filtered by item.
+
+#![allow(clippy::expect_used)]
+
+use std::process::Command;
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions, FieldPolicy},
+    render::{patch, JsonStyle, OutputFormat},
+    Comparison, Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+fn options() -> DiffOptions {
+    DiffOptions {
+        policy: FieldPolicy::all(),
+        ..Default::default()
+    }
+}
+
+fn operations() -> Vec<Value> {
+    let out = Comparison::<RuntimeDoc>::new(
+        &common::raw("1.1.100", Docs::Runtime),
+        &common::raw("1.1.110", Docs::Runtime),
+        options(),
+    )
+    .expect("docs are diffed")
+    .render(OutputFormat::JsonPatch, false, JsonStyle::default())
+    .expect("patch renders");
+
+    serde_json::from_str(&out).expect("patch is JSON")
+}
+
+/// Paths of the changes left between the patched doc and the target doc.
+fn remaining(patched: &Value) -> Vec<String> {
+    let out = Comparison::<RuntimeDoc>::new(
+        &common::to_vec(patched),
+        &common::raw("1.1.110", Docs::Runtime),
+        options(),
+    )
+    .expect("docs are diffed")
+    .render(OutputFormat::Flat, false, JsonStyle::default())
+    .expect("diff renders");
+
+    serde_json::from_str::<Vec<Value>>(&out)
+        .expect("output is JSON")
+        .iter()
+        .filter_map(|c| c["path"].as_str().map(str::to_owned))
+        .collect()
+}
+
+#[test]
+fn whole_patch() {
+    let operations = operations();
+    let mut base = common::json("1.1.100", Docs::Runtime);
+    let applied = patch::apply(&mut base, &operations, &[]).expect("patch applies");
+
+    assert_eq!(applied, operations.len());
+    assert_eq!(remaining(&base), Vec::<String>::new());
+}
+
+#[test]
+fn only_some_items() {
+    let operations = operations();
+    let mut base = common::json("1.1.100", Docs::Runtime);
+    let applied = patch::apply(&mut base, &operations, &["classes/LuaControl".to_owned()])
+        .expect("patch applies");
+
+    assert!(applied > 0 && applied < operations.len());
+    let left = remaining(&base);
+    assert!(!left.is_empty());
+    assert!(left.iter().all(|p| !p.starts_with("classes/LuaControl/")));
+    assert!(left.iter().any(|p| p.starts_with("classes/LuaEntity/")));
+
+    // whole sections select all of their items
+    let mut base = common::json("1.1.100", Docs::Runtime);
+    patch::apply(&mut base, &operations, &["classes".to_owned()]).expect("patch applies");
+    assert!(remaining(&base).iter().all(|p| !p.starts_with("classes/")));
+}
+
+#[test]
+fn mismatched_patches_fail() {
+    let mut doc = json!({"classes": [{"name": "LuaEntity", "order": 0}]});
+
+    let error = patch::apply(
+        &mut doc,
+        &[json!({"op": "remove", "path": "/classes/3"})],
+        &[],
+    )
+    .expect_err("index is out of bounds");
+    assert!(
+        error.to_string().contains("Can't remove /classes/3"),
+        "{error}"
+    );
+
+    let error = patch::apply(
+        &mut doc,
+        &[json!({"op": "move", "from": "/classes/0", "path": "/classes/-"})],
+        &[],
+    )
+    .expect_err("moves aren't supported");
+    assert!(
+        error.to_string().contains("Unsupported operation `move`"),
+        "{error}"
+    );
+}
+
+#[test]
+fn command() {
+    let dir = std::env::temp_dir().join(format!("fapi-diff-apply-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("failed to create the temp dir");
+    let (patch, out) = (dir.join("patch.json"), dir.join("patched.json"));
+    std::fs::write(
+        &patch,
+        serde_json::to_vec(&operations()).expect("patch serializes"),
+    )
+    .expect("failed to write the patch");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .arg("apply")
+        .arg(common::path("1.1.100", Docs::Runtime))
+        .arg(&patch)
+        .args(["--only", "classes/LuaEntity,concepts/MapPosition", "--out"])
+        .arg(&out)
+        .output()
+        .expect("failed to run fapi-diff");
+    let patched = std::fs::read(&out);
+    std::fs::remove_dir_all(&dir).ok();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains(" operations applied"), "{stderr}");
+
+    let patched =
+        serde_json::from_slice::<Value>(&patched.expect("doc is written")).expect("doc is JSON");
+    let left = remaining(&patched);
+    assert!(left.iter().all(|p| !p.starts_with("classes/LuaEntity/")));
+    assert!(left.iter().any(|p| p.starts_with("classes/LuaControl/")));
+}
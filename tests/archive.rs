@@ -0,0 +1,73 @@
+//! Maintaining a directory of diffs between consecutive versions.
+
+#![allow(clippy::expect_used)]
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use fapi_diff::Docs;
+
+mod common;
+
+/// A temp dir with the golden docs of 1.1.110 and 2.0.8 in `docs` and an empty `out`.
+fn archive_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fapi-diff-archive-{name}-{}", std::process::id()));
+    for version in ["1.1.110", "2.0.8"] {
+        let docs = dir.join(format!("docs/{version}/doc-html"));
+        std::fs::create_dir_all(&docs).expect("failed to create the temp dir");
+
+        for stage in [Docs::Runtime, Docs::Prototype] {
+            std::fs::copy(
+                common::path(version, stage),
+                docs.join(format!("{stage}-api.json")),
+            )
+            .expect("failed to copy the fixture");
+        }
+    }
+
+    dir
+}
+
+/// Runs `fapi-diff archive` in `dir`, returns whether it succeeded and stderr.
+fn archive(dir: &Path, args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .arg("archive")
+        .arg("--docs-dir")
+        .arg(dir.join("docs"))
+        .arg("--out")
+        .arg(dir.join("out"))
+        .args(args)
+        .output()
+        .expect("failed to run fapi-diff");
+
+    (
+        output.status.success(),
+        String::from_utf8(output.stderr).expect("stderr is UTF-8"),
+    )
+}
+
+#[test]
+fn existing_diffs_count_towards_min_changes() {
+    let dir = archive_dir("min-changes");
+    let pair = dir.join("out/1.1.110_2.0.8");
+
+    // the golden docs have 17 changes in both stages together
+    let (success, stderr) = archive(&dir, &["--min-changes", "17"]);
+    assert!(success, "{stderr}");
+    let runtime = std::fs::read(pair.join("runtime.json")).expect("runtime diff is written");
+
+    std::fs::remove_file(pair.join("prototype.json")).expect("prototype diff is written");
+    let (success, stderr) = archive(&dir, &["--min-changes", "17"]);
+    let written = (
+        std::fs::read(pair.join("runtime.json")),
+        pair.join("prototype.json").is_file(),
+    );
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(success, "{stderr}");
+
+    // the existing runtime diff is kept as it is and its changes count for the prototype diff
+    assert_eq!(written.0.expect("runtime diff is kept"), runtime);
+    assert!(written.1, "{stderr}");
+}
@@ -0,0 +1,147 @@
+#![allow(clippy::expect_used)]
+
+use std::path::PathBuf;
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions},
+    progress::Progress,
+    Comparison, Docs, StageDiff,
+};
+
+mod common;
+
+fn path(version: &str, stage: Docs) -> PathBuf {
+    PathBuf::from(common::path(version, stage))
+}
+
+fn pair(from: &str, to: &str, stage: Docs) -> (PathBuf, PathBuf) {
+    (path(from, stage), path(to, stage))
+}
+
+#[test]
+fn diffs_are_in_order_of_the_pairs() {
+    let pairs = [
+        pair("1.1.100", "1.1.110", Docs::Runtime),
+        pair("1.1.110", "2.0.8", Docs::Prototype),
+        pair("1.1.110", "2.0.8", Docs::Runtime),
+        pair("1.1.100", "1.1.110", Docs::Prototype),
+    ];
+
+    let diffs = fapi_diff::diff_many(&pairs, &DiffOptions::default());
+    let stages = diffs
+        .iter()
+        .map(|d| d.as_ref().expect("pair is diffed").stage())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        stages,
+        [
+            Docs::Runtime,
+            Docs::Prototype,
+            Docs::Runtime,
+            Docs::Prototype
+        ]
+    );
+
+    let raw = |stage| std::fs::read(path("1.1.100", stage)).expect("fixture exists");
+    let expected = Comparison::<RuntimeDoc>::new(
+        &raw(Docs::Runtime),
+        &std::fs::read(path("1.1.110", Docs::Runtime)).expect("fixture exists"),
+        DiffOptions::default(),
+    )
+    .expect("docs are diffed");
+    assert_eq!(
+        serde_json::to_value(diffs[0].as_ref().expect("pair is diffed")).expect("serializable"),
+        serde_json::to_value(&expected.diff).expect("serializable")
+    );
+}
+
+#[test]
+fn failed_pairs_dont_stop_the_others() {
+    let pairs = [
+        (
+            path("1.1.100", Docs::Runtime),
+            PathBuf::from("missing.json"),
+        ),
+        (
+            path("1.1.100", Docs::Runtime),
+            path("1.1.110", Docs::Prototype),
+        ),
+        pair("1.1.100", "1.1.110", Docs::Runtime),
+    ];
+
+    let diffs = fapi_diff::diff_many(&pairs, &DiffOptions::default());
+    assert!(matches!(diffs[0], Err(fapi_diff::Error::Io(_))));
+    assert!(matches!(
+        diffs[1],
+        Err(fapi_diff::Error::StageMismatch { .. })
+    ));
+    assert!(matches!(diffs[2], Ok(StageDiff::Runtime(_))));
+}
+
+#[test]
+fn ignored_items_apply_to_all_pairs() {
+    let pairs = vec![pair("1.1.100", "1.1.110", Docs::Runtime); 3];
+
+    let diffs = fapi_diff::format::with_ignored(&["LuaEntity".to_owned()], || {
+        fapi_diff::diff_many(&pairs, &DiffOptions::default())
+    })
+    .expect("pattern is valid");
+
+    for diff in diffs {
+        let Ok(StageDiff::Runtime(diff)) = diff else {
+            panic!("pair is a runtime diff");
+        };
+        assert!(!diff.classes.contains_key("LuaEntity"));
+        assert!(diff.classes.contains_key("LuaControl"));
+    }
+}
+
+#[test]
+fn change_counts_include_changed_members() {
+    let pairs = [
+        pair("1.1.100", "1.1.110", Docs::Runtime),
+        pair("1.1.110", "2.0.8", Docs::Prototype),
+    ];
+    let counts = |metrics| {
+        let options = DiffOptions {
+            metrics,
+            ..Default::default()
+        };
+
+        fapi_diff::diff_many(&pairs, &options)
+            .into_iter()
+            .map(|d| d.expect("pair is diffed").change_count())
+            .collect::<Vec<_>>()
+    };
+
+    // LuaControl and LuaEntity changed one method each
+    assert_eq!(counts(false), [4, 5]);
+    assert_eq!(counts(true), [4, 8]);
+}
+
+#[test]
+fn progress_of_each_pair() {
+    let pairs = [
+        pair("1.1.100", "1.1.110", Docs::Runtime),
+        pair("1.1.110", "2.0.8", Docs::Prototype),
+        pair("1.1.110", "2.0.8", Docs::Runtime),
+    ];
+
+    let (mut events, mut diffs) = (Vec::new(), Vec::new());
+    fapi_diff::diff_many_with_progress(
+        &pairs,
+        &DiffOptions::default(),
+        |progress| events.push(progress),
+        |i, _| diffs.push(i),
+    );
+
+    for i in 0..pairs.len() {
+        let started = events.iter().position(|e| *e == Progress::Started(i));
+        let finished = events.iter().position(|e| *e == Progress::Finished(i));
+        assert!(started.is_some(), "{i} started");
+        assert!(started < finished, "{i} finished after it started");
+    }
+    assert_eq!(events.len(), 2 * pairs.len());
+    diffs.sort_unstable();
+    assert_eq!(diffs, [0, 1, 2]);
+}
@@ -0,0 +1,78 @@
+//! The `builtin` complex type is equivalent to the simple type `builtin`.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{prototype::PrototypeDoc, runtime::RuntimeDoc, DiffOptions},
+    Comparison,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+fn runtime(type_: &Value) -> Vec<u8> {
+    common::concept_doc("BuiltinConcept", type_)
+}
+
+fn prototype(type_: &Value) -> Vec<u8> {
+    common::type_doc("BuiltinType", type_)
+}
+
+fn options(exact_types: bool) -> DiffOptions {
+    DiffOptions {
+        exact_types,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn runtime_equivalent() {
+    let (simple, complex) = (json!("builtin"), json!({"complex_type": "builtin"}));
+
+    for (source, target) in [(&simple, &complex), (&complex, &simple)] {
+        let c = Comparison::<RuntimeDoc>::new(&runtime(source), &runtime(target), options(false))
+            .expect("docs are valid");
+
+        assert!(c.diff.concepts.is_empty());
+    }
+}
+
+#[test]
+fn runtime_exact() {
+    let c = Comparison::<RuntimeDoc>::new(
+        &runtime(&json!("builtin")),
+        &runtime(&json!({"complex_type": "builtin"})),
+        options(true),
+    )
+    .expect("docs are valid");
+
+    assert!(c.diff.concepts.contains_key("BuiltinConcept"));
+}
+
+#[test]
+fn prototype_equivalent() {
+    let (simple, complex) = (json!("builtin"), json!({"complex_type": "builtin"}));
+
+    for (source, target) in [(&simple, &complex), (&complex, &simple)] {
+        let c =
+            Comparison::<PrototypeDoc>::new(&prototype(source), &prototype(target), options(false))
+                .expect("docs are valid");
+
+        assert!(c.diff.types.is_empty());
+    }
+}
+
+#[test]
+fn prototype_changed() {
+    let c = Comparison::<PrototypeDoc>::new(
+        &prototype(&json!({"complex_type": "builtin"})),
+        &prototype(&json!("double")),
+        options(false),
+    )
+    .expect("docs are valid");
+
+    assert_eq!(
+        serde_json::to_value(&c.diff.types).expect("diff is serializable"),
+        json!({"BuiltinType": [{"type": "double"}]})
+    );
+}
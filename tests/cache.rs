@@ -0,0 +1,64 @@
+//! The on disk cache of parsed docs: hits, invalidation by content and corrupted entries.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{cache::Cache, format::runtime::RuntimeDoc, Docs};
+
+mod common;
+
+fn version(doc: &RuntimeDoc) -> serde_json::Value {
+    serde_json::to_value(doc).expect("doc serializes")["application_version"].clone()
+}
+
+#[test]
+fn hits_invalidation_and_corruption() {
+    let dir = std::env::temp_dir().join(format!("fapi-diff-cache-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let cache = Cache::new(&dir);
+    let (old, new) = (
+        common::raw("1.1.100", Docs::Runtime),
+        common::raw("1.1.110", Docs::Runtime),
+    );
+
+    let path = cache.path::<RuntimeDoc>(&old);
+    assert!(path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.len() == 64 + ".msgpack".len()));
+
+    let parsed = cache
+        .load::<RuntimeDoc>(&old, "source")
+        .expect("doc parses");
+    assert_eq!(version(&parsed), "1.1.100");
+    assert!(path.exists());
+
+    // a hit returns the cached doc without parsing the raw content again
+    let other = cache
+        .load::<RuntimeDoc>(&new, "target")
+        .expect("doc parses");
+    let packed = rmp_serde::to_vec_named(&other).expect("doc packs");
+    std::fs::write(&path, packed).expect("cache is writable");
+    let cached = cache.load::<RuntimeDoc>(&old, "source").expect("doc loads");
+    assert_eq!(version(&cached), "1.1.110");
+
+    // changed raw content is stored under a different name
+    let mut changed = old.clone();
+    changed.push(b'\n');
+    assert_ne!(cache.path::<RuntimeDoc>(&changed), path);
+    let reparsed = cache
+        .load::<RuntimeDoc>(&changed, "source")
+        .expect("doc parses");
+    assert_eq!(version(&reparsed), "1.1.100");
+    assert!(cache.path::<RuntimeDoc>(&changed).exists());
+
+    // a corrupted entry is parsed again and replaced
+    std::fs::write(&path, b"not msgpack").expect("cache is writable");
+    let recovered = cache
+        .load::<RuntimeDoc>(&old, "source")
+        .expect("doc parses");
+    assert_eq!(version(&recovered), "1.1.100");
+    let repaired = std::fs::read(&path).expect("cache entry exists");
+    assert_ne!(repaired, b"not msgpack");
+
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,110 @@
+#![allow(clippy::expect_used)]
+
+use std::process::Command;
+
+use fapi_diff::{defines::StageMismatch, Docs};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::FIXTURES;
+
+fn check(runtime: &Value, prototype: &Value) -> anyhow::Result<StageMismatch> {
+    StageMismatch::check(&common::to_vec(runtime), &common::to_vec(prototype))
+}
+
+fn define(name: &str, values: &[&str]) -> Value {
+    json!({
+        "name": name,
+        "order": 0,
+        "description": "",
+        "values": values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| json!({"name": v, "order": i, "description": ""}))
+            .collect::<Vec<_>>(),
+    })
+}
+
+#[test]
+fn same_defines() {
+    let (mut runtime, mut prototype) = (
+        common::json("2.0.8", Docs::Runtime),
+        common::json("2.0.8", Docs::Prototype),
+    );
+    runtime["defines"] = json!([define("direction", &["north", "south"])]);
+    prototype["defines"] = runtime["defines"].clone();
+
+    let mismatch = check(&runtime, &prototype).expect("check failed");
+    assert!(mismatch.is_empty());
+}
+
+#[test]
+fn missing_values() {
+    let (mut runtime, mut prototype) = (
+        common::json("2.0.8", Docs::Runtime),
+        common::json("2.0.8", Docs::Prototype),
+    );
+    runtime["defines"] = json!([define("direction", &["north", "south"])]);
+    prototype["defines"] = json!([
+        define("direction", &["north", "east"]),
+        define("mouse_button_type", &["left"]),
+    ]);
+
+    let mismatch = check(&runtime, &prototype).expect("check failed");
+    assert_eq!(mismatch.runtime_only, ["defines.direction.south"]);
+    assert_eq!(
+        mismatch.prototype_only,
+        ["defines.direction.east", "defines.mouse_button_type.left"]
+    );
+}
+
+#[test]
+fn rejects_unfit_docs() {
+    let (runtime, prototype) = (
+        common::json("2.0.8", Docs::Runtime),
+        common::json("2.0.8", Docs::Prototype),
+    );
+
+    // swapped stages
+    assert!(check(&prototype, &runtime).is_err());
+
+    // different versions
+    let mut other = prototype;
+    other["application_version"] = json!("2.0.9");
+    assert!(check(&runtime, &other).is_err());
+
+    // prototype docs before 2.0 have no defines
+    let err = check(
+        &common::json("1.1.110", Docs::Runtime),
+        &common::json("1.1.110", Docs::Prototype),
+    )
+    .expect_err("prototype doc has no defines");
+    assert!(err.to_string().contains("no defines"));
+}
+
+#[test]
+fn docs_directory() {
+    let output = Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .args(["check-defines", &format!("{FIXTURES}/2.0.8"), "--json"])
+        .output()
+        .expect("failed to run fapi-diff");
+
+    assert!(
+        output.status.success(),
+        "fapi-diff failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let mismatch = serde_json::from_slice::<Value>(&output.stdout).expect("mismatch is valid JSON");
+    let expected = check(
+        &common::json("2.0.8", Docs::Runtime),
+        &common::json("2.0.8", Docs::Prototype),
+    )
+    .expect("check failed");
+
+    assert_eq!(
+        mismatch,
+        serde_json::to_value(&expected).expect("mismatch serializes")
+    );
+}
@@ -0,0 +1,106 @@
+//! Helpers shared by the integration tests for reading the fixtures and building edited copies of them.
+
+#![allow(dead_code, clippy::expect_used)]
+
+use fapi_diff::Docs;
+use serde_json::{json, Value};
+
+/// Docs of the versions 1.1.100, 1.1.110 and 2.0.8, in `<version>/doc-html/<stage>-api.json`.
+pub const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/golden");
+
+/// Path of the golden doc of `stage` in `version`.
+pub fn path(version: &str, stage: Docs) -> String {
+    format!("{FIXTURES}/{version}/doc-html/{stage}-api.json")
+}
+
+/// The raw golden doc of `stage` in `version`.
+pub fn raw(version: &str, stage: Docs) -> Vec<u8> {
+    std::fs::read(path(version, stage)).expect("fixture exists")
+}
+
+/// The golden doc of `stage` in `version` as JSON.
+pub fn json(version: &str, stage: Docs) -> Value {
+    serde_json::from_slice(&raw(version, stage)).expect("fixture is JSON")
+}
+
+/// A raw copy of the golden doc of `stage` in `version`, changed by `edit`.
+pub fn edited(version: &str, stage: Docs, edit: impl FnOnce(&mut Value)) -> Vec<u8> {
+    let mut doc = json(version, stage);
+    edit(&mut doc);
+
+    to_vec(&doc)
+}
+
+pub fn to_vec(doc: &Value) -> Vec<u8> {
+    serde_json::to_vec(doc).expect("doc is serializable")
+}
+
+/// A doc of `stage` in `version` with nothing but the items of `lists`, e.g. `json!({"concepts": [...]})`.
+pub fn synthetic(stage: Docs, version: &str, lists: &Value) -> Vec<u8> {
+    let mut doc = json!({
+        "application": "factorio",
+        "stage": stage.to_string(),
+        "application_version": version,
+        "api_version": if version.starts_with("1.") { 5 } else { 6 },
+    });
+    let names = match stage {
+        Docs::Runtime => &[
+            "classes",
+            "events",
+            "concepts",
+            "defines",
+            "global_objects",
+            "global_functions",
+        ][..],
+        Docs::Prototype => &["prototypes", "types", "defines"][..],
+    };
+
+    for name in names {
+        doc[name] = lists.get(name).cloned().unwrap_or_else(|| json!([]));
+    }
+
+    to_vec(&doc)
+}
+
+/// The item called `name` in the list `list` of `parent`, e.g. a class of a doc or a method of a class.
+pub fn find<'a>(parent: &'a mut Value, list: &str, name: &str) -> &'a mut Value {
+    parent[list]
+        .as_array_mut()
+        .and_then(|items| items.iter_mut().find(|i| i["name"] == name))
+        .unwrap_or_else(|| panic!("{list} contain {name}"))
+}
+
+/// The class `name` of a runtime doc.
+pub fn class<'a>(doc: &'a mut Value, name: &str) -> &'a mut Value {
+    find(doc, "classes", name)
+}
+
+/// The member `name` in the list `list` of a class, e.g. `member(doc, "LuaEntity", "methods", "die")`.
+pub fn member<'a>(doc: &'a mut Value, class: &str, list: &str, name: &str) -> &'a mut Value {
+    find(self::class(doc, class), list, name)
+}
+
+/// A 2.0.8 runtime doc with nothing but the concept `name` of type `type_`.
+pub fn concept_doc(name: &str, type_: &Value) -> Vec<u8> {
+    synthetic(
+        Docs::Runtime,
+        "2.0.8",
+        &json!({"concepts": [{"name": name, "order": 0, "description": "", "type": type_}]}),
+    )
+}
+
+/// A 2.0.8 prototype doc with nothing but the type `name` of type `type_`.
+pub fn type_doc(name: &str, type_: &Value) -> Vec<u8> {
+    synthetic(
+        Docs::Prototype,
+        "2.0.8",
+        &json!({"types": [{
+            "name": name,
+            "order": 0,
+            "description": "",
+            "abstract": false,
+            "inline": false,
+            "type": type_,
+        }]}),
+    )
+}
@@ -0,0 +1,97 @@
+//! Shell completions, the man page and the cached versions completed by the shells.
+
+#![allow(clippy::expect_used)]
+
+use std::process::Command;
+
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .args(args)
+        .output()
+        .expect("failed to run fapi-diff");
+    assert!(
+        output.status.success(),
+        "fapi-diff failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).expect("stdout is UTF-8")
+}
+
+#[test]
+fn bash() {
+    let script = run(&["completions", "bash"]);
+
+    assert!(script.contains("complete -F _fapi_diff fapi-diff"));
+    assert!(script.contains("        compare|prototype|runtime)\n"));
+    // the tui feature adds its own format at the end
+    assert!(script.contains(
+        "--format) COMPREPLY=($(compgen -W \"json flat markdown bbcode ndjson csv tsv json-patch"
+    ));
+    assert!(script.contains("$(fapi-diff versions --cached 2>/dev/null)"));
+
+    // the script is valid if bash is around to check it
+    let dir = std::env::temp_dir().join(format!("fapi-diff-completions-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("failed to create the temp dir");
+    let path = dir.join("fapi-diff.bash");
+    std::fs::write(&path, &script).expect("failed to write the script");
+    let checked = Command::new("bash").arg("-n").arg(&path).status();
+    std::fs::remove_dir_all(&dir).ok();
+    if let Ok(status) = checked {
+        assert!(status.success());
+    }
+}
+
+#[test]
+fn other_shells() {
+    let zsh = run(&["completions", "zsh"]);
+    assert!(zsh.starts_with("#compdef fapi-diff\n"));
+    assert!(zsh.contains("compdef _fapi-diff fapi-diff"));
+
+    let fish = run(&["completions", "fish"]);
+    assert!(fish.contains(
+        "complete -c fapi-diff -n '__fish_seen_subcommand_from compare prototype runtime' -l format -xa 'json flat"
+    ));
+    assert!(fish.contains("(fapi-diff versions --cached 2>/dev/null)"));
+
+    let powershell = run(&["completions", "powershell"]);
+    assert!(powershell.contains("Register-ArgumentCompleter -Native -CommandName 'fapi-diff'"));
+    assert!(powershell.contains("@(& fapi-diff versions --cached 2>$null)"));
+}
+
+#[test]
+fn manpage() {
+    let page = run(&["manpage"]);
+
+    assert!(page.starts_with(".TH FAPI\\-DIFF 1"));
+    assert!(page.contains(".SS compare\n"));
+    assert!(page.contains(
+        "\\fBfapi\\-diff compare\\fR [\\fIOPTIONS\\fR] \\fI<STAGE>\\fR \\fI<SOURCE>\\fR \\fI<TARGET>\\fR"
+    ));
+    assert!(page.contains("\\fB\\-\\-format\\fR=\\fIFORMAT\\fR"));
+    assert!(page.contains("[possible values: json, flat, markdown"));
+    assert!(!page.lines().any(|l| l.starts_with('\'')));
+}
+
+#[test]
+fn cached_versions() {
+    let dir = std::env::temp_dir().join(format!("fapi-diff-cached-{}", std::process::id()));
+    let cache = dir.join("cache/fapi-diff");
+    std::fs::create_dir_all(&cache).expect("failed to create the temp dir");
+    std::fs::create_dir_all(dir.join("docs/2.0.8")).expect("failed to create the temp dir");
+    std::fs::write(cache.join("versions.txt"), "1.1.110\n0.18.0\n").expect("failed to write cache");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .env("XDG_CACHE_HOME", dir.join("cache"))
+        .args(["versions", "--cached", "--docs-dir"])
+        .arg(dir.join("docs"))
+        .output()
+        .expect("failed to run fapi-diff");
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).expect("stdout is UTF-8"),
+        "0.18.0\n1.1.110\n2.0.8\n"
+    );
+}
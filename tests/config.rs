@@ -0,0 +1,116 @@
+//! Default options of the subcommands read from a config file.
+
+#![allow(clippy::expect_used)]
+
+use std::{path::PathBuf, process::Command};
+
+use fapi_diff::Docs;
+use serde_json::{json, Value};
+
+mod common;
+
+/// A temp dir holding `fapi-diff.toml` with `config`.
+fn config_dir(name: &str, config: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fapi-diff-config-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("failed to create the temp dir");
+    std::fs::write(dir.join("fapi-diff.toml"), config).expect("failed to write the config");
+
+    dir
+}
+
+/// Runs `fapi-diff` in `dir` comparing the runtime fixtures, returns whether it succeeded, stdout and stderr.
+fn compare(dir: &PathBuf, args: &[&str]) -> (bool, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .current_dir(dir)
+        .args(["compare", "runtime"])
+        .arg(common::path("1.1.110", Docs::Runtime))
+        .arg(common::path("2.0.8", Docs::Runtime))
+        .args(args)
+        .output()
+        .expect("failed to run fapi-diff");
+
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).expect("stdout is UTF-8"),
+        String::from_utf8(output.stderr).expect("stderr is UTF-8"),
+    )
+}
+
+#[test]
+fn defaults_from_working_directory() {
+    let dir = config_dir("cwd", "[compare]\nformat = \"markdown\"\nfull = true\n");
+
+    let (success, stdout, stderr) = compare(&dir, &[]);
+    assert!(success, "{stderr}");
+    assert!(stdout.starts_with("## classes"), "{stdout}");
+
+    // the command line takes precedence
+    let (success, stdout, stderr) = compare(&dir, &["--format", "json"]);
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(success, "{stderr}");
+    serde_json::from_str::<Value>(&stdout).expect("output is JSON");
+}
+
+#[test]
+fn explicit_config_file() {
+    let dir = config_dir("explicit", "[compare]\nformat = \"markdown\"\n");
+    let config = dir.join("other.toml");
+    std::fs::write(
+        &config,
+        "[compare]\nformat = \"json\"\ncompact = true\nonly = [\"events\"]\n",
+    )
+    .expect("failed to write the config");
+
+    let (success, stdout, stderr) = compare(
+        &dir,
+        &["--config", config.to_str().expect("temp dir is UTF-8")],
+    );
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(success, "{stderr}");
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+
+    let diff = serde_json::from_str::<Value>(&stdout).expect("output is JSON");
+    assert_eq!(diff["classes"], json!({}), "{diff}");
+    assert!(diff["events"].get("on_built_entity").is_some(), "{diff}");
+}
+
+#[test]
+fn unknown_options() {
+    let dir = config_dir("unknown", "[compare]\nformats = \"markdown\"\n");
+
+    let (success, _, stderr) = compare(&dir, &[]);
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(!success);
+    assert!(
+        stderr.contains("Unknown option `formats` of `compare`"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn overriding_flag_on_command_line() {
+    let dir = config_dir(
+        "overriding",
+        "[compare]\nformat = \"json\"\ncompact = true\n",
+    );
+
+    let (success, stdout, stderr) = compare(&dir, &["--pretty"]);
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(success, "{stderr}");
+    assert!(stdout.lines().count() > 1, "{stdout}");
+}
+
+#[test]
+fn conflicting_option_on_command_line() {
+    let dir = config_dir(
+        "conflicting",
+        "[compare]\nformat = \"json\"\ncompact = true\nskip = [\"events\"]\n",
+    );
+
+    let (success, stdout, stderr) = compare(&dir, &["--only", "events"]);
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(success, "{stderr}");
+
+    let diff = serde_json::from_str::<Value>(&stdout).expect("output is JSON");
+    assert!(diff["events"].get("on_built_entity").is_some(), "{diff}");
+}
@@ -0,0 +1,91 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{DiffOptions, FieldPolicy},
+    Docs,
+};
+use serde_json::json;
+
+mod common;
+
+/// Number of ndjson entries of the diff.
+fn streamed(stage: Docs, options: DiffOptions) -> usize {
+    stage
+        .stream(
+            &common::raw("1.1.110", stage),
+            &common::raw("2.0.8", stage),
+            options,
+            false,
+            &mut Vec::new(),
+        )
+        .expect("stream failed")
+}
+
+#[test]
+fn runtime_counts() {
+    let counts = Docs::Runtime
+        .count(
+            &common::raw("1.1.110", Docs::Runtime),
+            &common::raw("2.0.8", Docs::Runtime),
+            DiffOptions::default(),
+        )
+        .expect("count failed");
+
+    assert_eq!(
+        serde_json::to_value(&counts).expect("counts serialize"),
+        json!({
+            "total": 7,
+            "sections": {
+                "classes": {"added": 1, "removed": 0, "changed": 2},
+                "concepts": {"added": 0, "removed": 0, "changed": 2},
+                "defines": {"added": 0, "removed": 0, "changed": 1},
+                "events": {"added": 0, "removed": 0, "changed": 1},
+            }
+        })
+    );
+}
+
+#[test]
+fn counts_match_streamed_entries() {
+    for stage in [Docs::Runtime, Docs::Prototype] {
+        for options in [
+            DiffOptions::default(),
+            DiffOptions {
+                policy: FieldPolicy::all(),
+                ..Default::default()
+            },
+        ] {
+            let counts = stage
+                .count(
+                    &common::raw("1.1.110", stage),
+                    &common::raw("2.0.8", stage),
+                    options,
+                )
+                .expect("count failed");
+
+            assert_eq!(counts.total, streamed(stage, options), "{stage}");
+        }
+    }
+}
+
+#[test]
+fn malformed_items_fail() {
+    let source = common::edited("1.1.110", Docs::Runtime, |doc| {
+        common::class(doc, "LuaEntity")["methods"] = json!("bogus");
+    });
+
+    // an unparsable item isn't counted as added
+    let error = Docs::Runtime
+        .count(
+            &source,
+            &common::raw("2.0.8", Docs::Runtime),
+            DiffOptions::default(),
+        )
+        .expect_err("malformed items can't be counted");
+    assert!(
+        error
+            .to_string()
+            .starts_with("Failed to deserialize source at classes[\"LuaEntity\"].methods"),
+        "{error}"
+    );
+}
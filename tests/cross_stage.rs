@@ -0,0 +1,110 @@
+//! Names documented in both stages that changed in only one of them.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    cross_stage::{self, Inconsistency},
+    format::{prototype::PrototypeDoc, runtime::RuntimeDoc, DiffOptions, ItemStatus},
+    Comparison, Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+/// The 2.0.8 runtime doc with the `MapPosition` concept of the given type.
+fn runtime(type_: &Value) -> Vec<u8> {
+    common::edited("2.0.8", Docs::Runtime, |doc| {
+        common::find(doc, "concepts", "MapPosition")["type"] = type_.clone();
+    })
+}
+
+/// The 2.0.8 prototype doc with a `MapPosition` type of the given type.
+fn prototype(type_: &Value) -> Vec<u8> {
+    common::edited("2.0.8", Docs::Prototype, |doc| {
+        doc["types"]
+            .as_array_mut()
+            .expect("types are a list")
+            .push(json!({
+                "name": "MapPosition",
+                "order": 1000,
+                "description": "",
+                "abstract": false,
+                "inline": false,
+                "type": type_,
+            }));
+    })
+}
+
+fn check(runtime: (&[u8], &[u8]), prototype: (&[u8], &[u8])) -> Vec<Inconsistency> {
+    let runtime = Comparison::<RuntimeDoc>::new(runtime.0, runtime.1, DiffOptions::default())
+        .expect("runtime docs are diffed");
+    let prototype =
+        Comparison::<PrototypeDoc>::new(prototype.0, prototype.1, DiffOptions::default())
+            .expect("prototype docs are diffed");
+
+    cross_stage::check(&runtime, &prototype)
+}
+
+#[test]
+fn changed_in_one_stage() {
+    let (old, new) = (
+        json!({"complex_type": "tuple", "values": ["double", "double"]}),
+        json!({"complex_type": "tuple", "values": ["float", "float"]}),
+    );
+    let (old_runtime, new_runtime) = (runtime(&old), runtime(&new));
+    let (old_prototype, new_prototype) = (prototype(&old), prototype(&new));
+
+    assert_eq!(
+        check(
+            (&old_runtime, &new_runtime),
+            (&old_prototype, &old_prototype)
+        ),
+        [Inconsistency {
+            name: "MapPosition".to_owned(),
+            changed_in: Docs::Runtime,
+            status: ItemStatus::Changed,
+        }]
+    );
+    assert_eq!(
+        check(
+            (&old_runtime, &old_runtime),
+            (&old_prototype, &new_prototype)
+        ),
+        [Inconsistency {
+            name: "MapPosition".to_owned(),
+            changed_in: Docs::Prototype,
+            status: ItemStatus::Changed,
+        }]
+    );
+
+    // changes in both stages are consistent
+    assert_eq!(
+        check(
+            (&old_runtime, &new_runtime),
+            (&old_prototype, &new_prototype)
+        ),
+        []
+    );
+}
+
+#[test]
+fn removed_in_one_stage() {
+    let type_ = json!({"complex_type": "tuple", "values": ["double", "double"]});
+    let prototype = prototype(&type_);
+    let without = common::raw("2.0.8", Docs::Prototype);
+    let runtime = runtime(&type_);
+
+    let report = check((&runtime, &runtime), (&prototype, &without));
+    assert_eq!(
+        report,
+        [Inconsistency {
+            name: "MapPosition".to_owned(),
+            changed_in: Docs::Prototype,
+            status: ItemStatus::Removed,
+        }]
+    );
+    assert_eq!(
+        serde_json::to_value(&report).expect("report is serializable"),
+        json!([{"name": "MapPosition", "changed_in": "prototype", "status": "removed"}])
+    );
+}
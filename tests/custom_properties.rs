@@ -0,0 +1,87 @@
+#![allow(clippy::expect_used)]
+
+use std::process::Command;
+
+use serde_json::{json, Value};
+
+fn diff(source: &str, target: &str, args: &[&str]) -> Value {
+    let fixtures = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/custom_properties"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .arg("prototype")
+        .arg(format!("{fixtures}/{source}"))
+        .arg(format!("{fixtures}/{target}"))
+        .arg("--local")
+        .args(args)
+        .output()
+        .expect("failed to run fapi-diff");
+
+    assert!(
+        output.status.success(),
+        "fapi-diff failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    serde_json::from_slice(&output.stdout).expect("output is not valid json")
+}
+
+fn custom_properties(diff: &Value) -> &Value {
+    &diff["prototypes"]["ItemPrototype"][0]["custom_properties"]
+}
+
+#[test]
+fn added() {
+    let diff = diff("none", "any_basic", &[]);
+
+    assert_eq!(
+        custom_properties(&diff),
+        &json!({"added": [{"key_type": "string"}, {"value_type": "AnyBasic"}]})
+    );
+}
+
+#[test]
+fn removed() {
+    let diff = diff("any_basic", "none", &["--with-old"]);
+
+    assert_eq!(
+        custom_properties(&diff),
+        &json!({"removed": [
+            {"key_type": {"old": "string", "new": ""}},
+            {"value_type": {"old": "AnyBasic", "new": ""}},
+        ]})
+    );
+}
+
+#[test]
+fn changed() {
+    let diff = diff("any_basic", "double", &["--with-old"]);
+
+    assert_eq!(
+        custom_properties(&diff),
+        &json!({"changed": [{"value_type": {"old": "AnyBasic", "new": "double"}}]})
+    );
+}
+
+#[test]
+fn unchanged() {
+    let diff = diff("double", "double", &[]);
+
+    assert_eq!(diff["prototypes"], json!({}));
+}
+
+#[test]
+fn changed_flat() {
+    let diff = diff("any_basic", "double", &["--format", "flat"]);
+
+    assert_eq!(
+        diff,
+        json!([{
+            "path": "prototypes/ItemPrototype/custom_properties/value_type",
+            "old": "AnyBasic",
+            "new": "double",
+        }])
+    );
+}
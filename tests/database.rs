@@ -0,0 +1,42 @@
+#![cfg(feature = "sql-export")]
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    database::Database,
+    format::{runtime::RuntimeDoc, DiffOptions},
+    Comparison, Docs,
+};
+
+mod common;
+
+#[test]
+fn script_has_docs_members_and_changes() {
+    let (source, target) = (
+        common::raw("1.1.100", Docs::Runtime),
+        common::raw("1.1.110", Docs::Runtime),
+    );
+    let tree = Comparison::<RuntimeDoc>::new(&source, &target, DiffOptions::default())
+        .expect("fixtures are runtime docs")
+        .tree()
+        .expect("diff is serializable");
+
+    let mut db = Database::new();
+    db.add_doc(
+        "1.1.100",
+        &fapi_diff::parse(&source, "source").expect("valid JSON"),
+    );
+    db.add_changes("1.1.100", "1.1.110", Docs::Runtime, &tree);
+    let sql = db.finish();
+
+    assert!(sql.starts_with("DROP TABLE IF EXISTS docs;"));
+    assert!(sql.ends_with("COMMIT;\n"));
+    assert!(sql.contains("INSERT INTO docs VALUES ('1.1.100', 'runtime', "));
+    assert!(sql.contains(
+        "INSERT OR REPLACE INTO members VALUES ('1.1.100', 'runtime', 'classes', 'LuaEntity', 'methods', 'teleport', "
+    ));
+    assert!(sql.contains(
+        "INSERT INTO changes VALUES ('1.1.100', '1.1.110', 'runtime', \
+         'classes/LuaEntity/methods/teleport/parameters/raise_teleported', 'classes', 'LuaEntity', 'teleport', \
+         'parameters/raise_teleported', 'added', NULL, "
+    ));
+}
@@ -0,0 +1,48 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::Docs;
+use serde_json::json;
+
+mod common;
+
+fn deprecations(source: &serde_json::Value, target: &serde_json::Value) -> serde_json::Value {
+    serde_json::to_value(
+        fapi_diff::render::deprecations(source, target).expect("docs are serializable"),
+    )
+    .expect("deprecations are serializable")
+}
+
+#[test]
+fn deprecated_prototype() {
+    let source = common::json("2.0.8", Docs::Prototype);
+    let mut target = source.clone();
+    target["prototypes"][0]["deprecated"] = json!(true);
+    let name = source["prototypes"][0]["name"].as_str().expect("named");
+
+    assert_eq!(
+        deprecations(&source, &target),
+        json!([{"path": format!("prototypes/{name}"), "transition": "deprecated"}])
+    );
+    assert_eq!(
+        deprecations(&target, &source),
+        json!([{"path": format!("prototypes/{name}"), "transition": "undeprecated"}])
+    );
+}
+
+#[test]
+fn deprecated_runtime_description() {
+    let source = common::json("2.0.8", Docs::Runtime);
+    let mut target = source.clone();
+    target["classes"][0]["methods"][0]["description"] =
+        json!("**Deprecated**, use something else.");
+    let class = source["classes"][0]["name"].as_str().expect("named");
+    let method = source["classes"][0]["methods"][0]["name"]
+        .as_str()
+        .expect("named");
+
+    assert_eq!(
+        deprecations(&source, &target),
+        json!([{"path": format!("classes/{class}/methods/{method}"), "transition": "deprecated"}])
+    );
+    assert_eq!(deprecations(&source, &source), json!([]));
+}
@@ -0,0 +1,69 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::format::{runtime::Class, DiffableVec, Named};
+use fapi_diff::Docs;
+use serde::Serialize;
+
+mod common;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Item {
+    name: String,
+    value: u32,
+}
+
+impl Named for Item {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+fn item(name: &str, value: u32) -> Item {
+    Item {
+        name: name.to_owned(),
+        value,
+    }
+}
+
+#[test]
+fn user_items() {
+    let mut items = DiffableVec::from(vec![item("b", 1), item("a", 2)]);
+
+    assert_eq!(items.get("a"), Some(&item("a", 2)));
+    assert_eq!(
+        items.iter_sorted().map(Named::name).collect::<Vec<_>>(),
+        ["a", "b"]
+    );
+
+    let fingerprint = items.fingerprint("b");
+    assert!(fingerprint.is_some());
+    assert_eq!(items.insert(item("b", 3)), Some(item("b", 1)));
+    assert_ne!(items.fingerprint("b"), fingerprint);
+
+    assert_eq!(items.insert(item("c", 4)), None);
+    assert_eq!(items.remove("a"), Some(item("a", 2)));
+    assert_eq!(items.fingerprint("a"), None);
+
+    assert_eq!(Vec::from(items), [item("b", 3), item("c", 4)]);
+}
+
+#[test]
+fn edited_doc_items() {
+    let doc = common::json("2.0.8", Docs::Runtime);
+    let classes =
+        serde_json::from_value::<Vec<Class>>(doc["classes"].clone()).expect("fixture has classes");
+
+    let old = DiffableVec::from(classes);
+    let mut new = old.clone();
+    let mut names = old.keys().cloned().collect::<Vec<_>>();
+    names.sort_unstable();
+
+    // an unchanged insert keeps the item unchanged
+    let first = old.get(&names[0]).expect("class exists").clone();
+    new.insert(first);
+    new.remove(&names[1]);
+
+    let diff = old.diff(&new);
+    assert_eq!(diff.keys().collect::<Vec<_>>(), [&names[1]]);
+    assert_eq!(old.removed(&new), [names[1].clone()]);
+}
@@ -0,0 +1,98 @@
+//! Optionality and writability changes reported by their direction.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions, FieldPolicy, Invert},
+    render::{JsonStyle, OutputFormat},
+    Comparison, Docs,
+};
+use serde_json::json;
+
+mod common;
+
+fn docs() -> (Vec<u8>, Vec<u8>) {
+    let target = common::edited("2.0.8", Docs::Runtime, |doc| {
+        // `surface` of `teleport` is no longer optional
+        let teleport = common::member(doc, "LuaEntity", "methods", "teleport");
+        common::find(teleport, "parameters", "surface")["optional"] = json!(false);
+
+        // `health` can no longer be written
+        common::member(doc, "LuaEntity", "attributes", "health")
+            .as_object_mut()
+            .expect("attribute is an object")
+            .remove("write_type");
+    });
+
+    (common::raw("2.0.8", Docs::Runtime), target)
+}
+
+fn comparison(directional: bool) -> Comparison<RuntimeDoc> {
+    let (source, target) = docs();
+    let options = DiffOptions {
+        policy: FieldPolicy::all(),
+        directional,
+        ..Default::default()
+    };
+
+    Comparison::new(&source, &target, options).expect("docs can be diffed")
+}
+
+#[test]
+fn changes_by_direction() {
+    let diff = serde_json::to_string(&comparison(true).diff).expect("diff is serializable");
+
+    assert!(diff.contains(r#"{"optionality":"now_required"}"#), "{diff}");
+    assert!(
+        diff.contains(r#"{"writability":"now_read_only"}"#),
+        "{diff}"
+    );
+    assert!(!diff.contains(r#""optional":"#), "{diff}");
+}
+
+#[test]
+fn inverted_direction() {
+    let inverted = comparison(true).diff.invert();
+    let diff = serde_json::to_string(&inverted).expect("diff is serializable");
+
+    assert!(diff.contains(r#"{"optionality":"now_optional"}"#), "{diff}");
+    assert!(diff.contains(r#"{"writability":"now_writable"}"#), "{diff}");
+}
+
+#[test]
+fn plain_changes_without_option() {
+    let diff = serde_json::to_string(&comparison(false).diff).expect("diff is serializable");
+
+    assert!(!diff.contains("optionality"), "{diff}");
+    assert!(!diff.contains("writability"), "{diff}");
+    assert!(diff.contains(r#""optional":"#), "{diff}");
+}
+
+#[test]
+fn flat_changes_and_changelog() {
+    let c = comparison(true);
+    let flat = fapi_diff::render::flat::flatten(
+        &serde_json::to_value(&c.diff).expect("diff is serializable"),
+        &serde_json::to_value(&c.source).expect("doc is serializable"),
+        &serde_json::to_value(&c.target).expect("doc is serializable"),
+    );
+
+    let directions = flat
+        .iter()
+        .filter_map(|change| Some((change.path.as_str(), change.direction()?)))
+        .collect::<Vec<_>>();
+    assert!(directions.contains(&(
+        "classes/LuaEntity/methods/teleport/parameters/surface/optional",
+        "now_required"
+    )));
+    assert!(directions.contains(&(
+        "classes/LuaEntity/attributes/health/access",
+        "now_read_only"
+    )));
+
+    let markdown = c
+        .render(OutputFormat::Markdown, false, JsonStyle::default())
+        .expect("diff renders");
+    assert!(markdown.contains("now required"), "{markdown}");
+    assert!(markdown.contains("now read only"), "{markdown}");
+}
@@ -0,0 +1,94 @@
+//! Diffs enumerate changed members deterministically, renderers list them in the order of the docs.
+
+#![allow(clippy::expect_used)]
+
+use std::hash::{BuildHasher, RandomState};
+
+use fapi_diff::{
+    format::{prototype::LiteralValue, runtime::RuntimeDoc, DiffOptions, FieldPolicy},
+    Comparison, Docs,
+};
+
+mod common;
+
+fn docs() -> (Vec<u8>, Vec<u8>) {
+    // the docs list the methods in another order than their names
+    let target = common::edited("2.0.8", Docs::Runtime, |doc| {
+        for (order, name) in ["teleport", "get_fluid", "die"].iter().enumerate() {
+            let method = common::member(doc, "LuaEntity", "methods", name);
+            method["order"] = order.into();
+            method["description"] = format!("Changed {name}.").into();
+        }
+    });
+
+    (common::raw("2.0.8", Docs::Runtime), target)
+}
+
+fn comparison() -> Comparison<RuntimeDoc> {
+    let (source, target) = docs();
+    let options = DiffOptions {
+        policy: FieldPolicy::all(),
+        ..Default::default()
+    };
+
+    Comparison::new(&source, &target, options).expect("docs can be diffed")
+}
+
+#[test]
+fn flat_changes_in_doc_order() {
+    let c = comparison();
+    let flat = fapi_diff::render::flat::flatten(
+        &serde_json::to_value(&c.diff).expect("diff is serializable"),
+        &serde_json::to_value(&c.source).expect("doc is serializable"),
+        &serde_json::to_value(&c.target).expect("doc is serializable"),
+    );
+
+    let methods = flat
+        .iter()
+        .filter_map(|c| c.path.strip_prefix("classes/LuaEntity/methods/"))
+        .filter_map(|p| p.split('/').next())
+        .fold(Vec::new(), |mut methods, m| {
+            if methods.last() != Some(&m) {
+                methods.push(m);
+            }
+            methods
+        });
+    assert_eq!(methods, ["teleport", "get_fluid", "die"]);
+}
+
+#[test]
+fn json_diff_is_deterministic() {
+    let first = serde_json::to_string(&comparison().diff).expect("diff is serializable");
+
+    for _ in 0..5 {
+        let again = serde_json::to_string(&comparison().diff).expect("diff is serializable");
+        assert_eq!(first, again);
+    }
+
+    // changed members are keyed by name in sorted order
+    let die = first.find(r#""die""#).expect("die changed");
+    let teleport = first.find(r#""teleport""#).expect("teleport changed");
+    assert!(die < teleport);
+}
+
+#[test]
+fn literal_order_matches_equality_and_hash() {
+    let hasher = RandomState::new();
+    let pairs = [
+        (LiteralValue::Float(f64::NAN), LiteralValue::Float(f64::NAN)),
+        (LiteralValue::Float(0.0), LiteralValue::Float(-0.0)),
+        (LiteralValue::UInt(1), LiteralValue::Int(1)),
+        (LiteralValue::Float(1.5), LiteralValue::Float(2.5)),
+    ];
+
+    for (a, b) in pairs {
+        assert_eq!(a == b, a.cmp(&b).is_eq(), "{a:?} and {b:?}");
+        assert_eq!(a.partial_cmp(&b), Some(a.cmp(&b)));
+        if a == b {
+            assert_eq!(hasher.hash_one(&a), hasher.hash_one(&b));
+        }
+    }
+
+    assert_eq!(LiteralValue::Float(f64::NAN), LiteralValue::Float(f64::NAN));
+    assert!(LiteralValue::Float(1.5) < LiteralValue::Float(2.5));
+}
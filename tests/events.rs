@@ -0,0 +1,49 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{events::EventsDiff, format::runtime::RuntimeDoc, Docs};
+use serde_json::{json, Value};
+
+mod common;
+
+fn parse(doc: Value) -> RuntimeDoc {
+    serde_json::from_value(doc).expect("fixture is a runtime doc")
+}
+
+#[test]
+fn breaking_payload_changes_are_flagged() {
+    let mut source = common::json("1.1.110", Docs::Runtime);
+    source["events"][0]["data"][0]["type"] = json!({
+        "complex_type": "union",
+        "options": ["uint", "string"],
+        "full_format": false,
+    });
+    let mut target = source.clone();
+    // on_tick: tick is narrowed to a uint, name becomes optional, the define value is gone
+    target["events"][0]["data"][0]["type"] = json!("uint");
+    target["events"][0]["data"][1]["optional"] = json!(true);
+    target["defines"][0]["values"]
+        .as_array_mut()
+        .expect("define values are a list")
+        .retain(|v| v["name"] != "on_tick");
+
+    let diff = EventsDiff::new(&parse(source), &parse(target));
+
+    assert_eq!(
+        serde_json::to_value(&diff).expect("diff is serializable"),
+        json!({
+            "changes": [
+                {"event": "on_tick", "change": "id_removed", "breaking": true},
+                {"event": "on_tick", "field": "name", "change": "now_optional", "breaking": true},
+                {
+                    "event": "on_tick",
+                    "field": "tick",
+                    "change": "type_narrowed",
+                    "old": r#"{"complex_type":"union","options":["uint","string"],"full_format":false}"#,
+                    "new": "uint",
+                    "breaking": true,
+                },
+            ],
+            "breaking": ["on_tick"],
+        })
+    );
+}
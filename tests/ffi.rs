@@ -0,0 +1,57 @@
+//! Calls the C ABI like a foreign caller would.
+
+#![cfg(feature = "ffi")]
+#![allow(unsafe_code, clippy::expect_used)]
+
+use std::ffi::{CStr, CString};
+
+use fapi_diff::{
+    ffi::{fapi_diff_free, fapi_diff_last_error, fapi_diff_runtime},
+    Docs,
+};
+
+mod common;
+
+fn doc(version: &str) -> CString {
+    CString::new(common::raw(version, Docs::Runtime)).expect("fixture contains NUL")
+}
+
+#[test]
+fn runtime_diff() {
+    let (source, target) = (doc("1.1.100"), doc("1.1.110"));
+    let options = CString::new(r#"{"format": "flat"}"#).expect("options contain NUL");
+
+    // SAFETY: all arguments are valid C strings and the result is released once
+    let diff = unsafe {
+        let ptr = fapi_diff_runtime(source.as_ptr(), target.as_ptr(), options.as_ptr());
+        assert!(!ptr.is_null(), "diff failed");
+        assert!(fapi_diff_last_error().is_null());
+
+        let diff = CStr::from_ptr(ptr).to_str().map(str::to_owned);
+        fapi_diff_free(ptr);
+        diff
+    }
+    .expect("diff is not valid UTF-8");
+
+    let diff = serde_json::from_str::<serde_json::Value>(&diff).expect("diff is not JSON");
+    assert!(diff.as_array().is_some_and(|c| !c.is_empty()));
+}
+
+#[test]
+fn errors() {
+    let source = doc("1.1.100");
+    let prototype =
+        CString::new(common::raw("1.1.100", Docs::Prototype)).expect("fixture contains NUL");
+
+    // SAFETY: all arguments are valid C strings or NULL
+    let error = unsafe {
+        assert!(fapi_diff_runtime(source.as_ptr(), std::ptr::null(), std::ptr::null()).is_null());
+        assert!(fapi_diff_runtime(source.as_ptr(), prototype.as_ptr(), std::ptr::null()).is_null());
+
+        CStr::from_ptr(fapi_diff_last_error())
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    assert_eq!(error, "Target is a prototype doc, not a runtime doc");
+}
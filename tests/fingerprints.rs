@@ -0,0 +1,54 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions, FieldPolicy},
+    Comparison, Docs,
+};
+use serde_json::Value;
+
+mod common;
+
+fn compare(source: &Value, target: &Value) -> Comparison<RuntimeDoc> {
+    Comparison::new(
+        &common::to_vec(source),
+        &common::to_vec(target),
+        DiffOptions {
+            policy: FieldPolicy::all(),
+            ..Default::default()
+        },
+    )
+    .expect("docs are valid")
+}
+
+#[test]
+fn equal_items_share_fingerprints() {
+    let raw = common::json("2.0.8", Docs::Runtime);
+    let c = compare(&raw, &raw);
+
+    for name in c.source.classes.keys() {
+        assert!(c.source.classes.fingerprint(name).is_some());
+        assert_eq!(
+            c.source.classes.fingerprint(name),
+            c.target.classes.fingerprint(name)
+        );
+    }
+    assert!(c.diff.classes.is_empty());
+}
+
+#[test]
+fn changed_items_are_still_diffed() {
+    let source = common::json("2.0.8", Docs::Runtime);
+    let mut target = source.clone();
+    target["classes"][0]["description"] = "A changed description.".into();
+    let name = source["classes"][0]["name"]
+        .as_str()
+        .expect("classes have names");
+
+    let c = compare(&source, &target);
+
+    assert_ne!(
+        c.source.classes.fingerprint(name),
+        c.target.classes.fingerprint(name)
+    );
+    assert_eq!(c.diff.classes.keys().collect::<Vec<_>>(), [name]);
+}
@@ -0,0 +1,112 @@
+//! Snapshot tests of the diff outputs for trimmed docs of real versions.
+//!
+//! The snapshots live in `tests/snapshots`, regenerate them after intended changes of the output with
+//! `FAPI_DIFF_BLESS=1 cargo test --test golden` and review the changes before committing them.
+
+#![allow(clippy::expect_used)]
+
+use std::{path::Path, process::Command};
+
+mod common;
+
+use common::FIXTURES;
+
+const SNAPSHOTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots");
+
+fn check(name: &str, stage: &str, source: &str, target: &str, args: &[&str]) {
+    let output = Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .arg(stage)
+        // `none` stands for an empty doc instead of a fixture
+        .arg(if source == "none" {
+            source.to_owned()
+        } else {
+            format!("{FIXTURES}/{source}")
+        })
+        .arg(format!("{FIXTURES}/{target}"))
+        .arg("--local")
+        .args(args)
+        .output()
+        .expect("failed to run fapi-diff");
+
+    assert!(
+        output.status.success(),
+        "fapi-diff failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let actual = String::from_utf8(output.stdout)
+        .expect("output is not valid utf-8")
+        // the snapshots don't change with the version of the crate
+        .replace(
+            concat!(
+                "\"generated_with\": \"fapi-diff ",
+                env!("CARGO_PKG_VERSION")
+            ),
+            "\"generated_with\": \"fapi-diff <version>",
+        );
+    let path = Path::new(SNAPSHOTS).join(name);
+
+    if std::env::var_os("FAPI_DIFF_BLESS").is_some() {
+        std::fs::create_dir_all(SNAPSHOTS).expect("failed to create the snapshot directory");
+        std::fs::write(&path, &actual).expect("failed to write the snapshot");
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing snapshot {}, create it with FAPI_DIFF_BLESS=1",
+            path.display()
+        )
+    });
+
+    if actual != expected {
+        let line = actual
+            .lines()
+            .zip(expected.lines())
+            .position(|(a, e)| a != e)
+            .unwrap_or_else(|| actual.lines().count().min(expected.lines().count()));
+
+        panic!(
+            "output differs from {} at line {}:\n  expected: {:?}\n  actual:   {:?}\n\
+             regenerate the snapshot with FAPI_DIFF_BLESS=1 if the change is intended",
+            path.display(),
+            line + 1,
+            expected.lines().nth(line).unwrap_or_default(),
+            actual.lines().nth(line).unwrap_or_default(),
+        );
+    }
+}
+
+macro_rules! golden {
+    ($($name:ident: $stage:literal $source:literal -> $target:literal [$($arg:literal),*] => $file:literal;)*) => {
+        $(
+            #[test]
+            fn $name() {
+                check($file, $stage, $source, $target, &[$($arg),*]);
+            }
+        )*
+    };
+}
+
+golden! {
+    runtime_json: "runtime" "1.1.100" -> "1.1.110" ["--sort-keys"] => "runtime_1.1.100_1.1.110.json";
+    runtime_json_full: "runtime" "1.1.100" -> "1.1.110" ["--sort-keys", "--full", "--with-old"] => "runtime_1.1.100_1.1.110_full.json";
+    runtime_flat: "runtime" "1.1.100" -> "1.1.110" ["--format", "flat"] => "runtime_1.1.100_1.1.110_flat.json";
+    runtime_only: "runtime" "1.1.100" -> "1.1.110" ["--sort-keys", "--only", "classes,events"] => "runtime_1.1.100_1.1.110_only.json";
+    runtime_ndjson: "runtime" "1.1.100" -> "1.1.110" ["--format", "ndjson"] => "runtime_1.1.100_1.1.110.ndjson";
+    runtime_csv: "runtime" "1.1.100" -> "1.1.110" ["--format", "csv"] => "runtime_1.1.100_1.1.110.csv";
+    runtime_2_0_json: "runtime" "1.1.110" -> "2.0.8" ["--sort-keys"] => "runtime_1.1.110_2.0.8.json";
+    runtime_2_0_flat: "runtime" "1.1.110" -> "2.0.8" ["--format", "flat", "--anchor-links"] => "runtime_1.1.110_2.0.8_flat.json";
+    runtime_2_0_markdown: "runtime" "1.1.110" -> "2.0.8" ["--format", "markdown"] => "runtime_1.1.110_2.0.8.md";
+    runtime_2_0_bbcode: "runtime" "1.1.110" -> "2.0.8" ["--format", "bbcode"] => "runtime_1.1.110_2.0.8.bbcode";
+    runtime_2_0_flat_defines: "runtime" "1.1.110" -> "2.0.8" ["--sort-keys", "--flat-defines"] => "runtime_1.1.110_2.0.8_flat_defines.json";
+    runtime_2_0_summary: "runtime" "1.1.110" -> "2.0.8" ["--summary"] => "runtime_1.1.110_2.0.8_summary.txt";
+    prototype_json: "prototype" "1.1.100" -> "1.1.110" ["--sort-keys"] => "prototype_1.1.100_1.1.110.json";
+    prototype_baseline: "prototype" "none" -> "1.1.100" ["--sort-keys"] => "prototype_none_1.1.100.json";
+    prototype_2_0_json: "prototype" "1.1.110" -> "2.0.8" ["--sort-keys", "--with-metrics"] => "prototype_1.1.110_2.0.8.json";
+    prototype_2_0_flat: "prototype" "1.1.110" -> "2.0.8" ["--format", "flat"] => "prototype_1.1.110_2.0.8_flat.json";
+    prototype_2_0_context: "prototype" "1.1.110" -> "2.0.8" ["--format", "ndjson", "--context", "full-item"] => "prototype_1.1.110_2.0.8_context.ndjson";
+    prototype_2_0_tsv: "prototype" "1.1.110" -> "2.0.8" ["--format", "tsv"] => "prototype_1.1.110_2.0.8.tsv";
+    prototype_2_0_resolved: "prototype" "1.1.110" -> "2.0.8" ["--sort-keys", "--resolve-inheritance"] => "prototype_1.1.110_2.0.8_resolved.json";
+    prototype_2_0_summary: "prototype" "1.1.110" -> "2.0.8" ["--summary"] => "prototype_1.1.110_2.0.8_summary.txt";
+}
@@ -0,0 +1,51 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{prototype::PrototypeDoc, DiffOptions},
+    graph::{Graph, GraphFormat},
+    Comparison, Docs,
+};
+
+mod common;
+
+#[test]
+fn delta_colors_changed_prototypes() {
+    let (source, target) = (
+        common::raw("1.1.110", Docs::Prototype),
+        common::raw("2.0.8", Docs::Prototype),
+    );
+    let diff = Comparison::<PrototypeDoc>::new(&source, &target, DiffOptions::default())
+        .expect("fixtures are prototype docs")
+        .tree()
+        .expect("diff is serializable");
+
+    let graph = Graph::delta(
+        Graph::parse(&source, "source").expect("source is a doc"),
+        Graph::parse(&target, "target").expect("target is a doc"),
+        &diff,
+    )
+    .changed_only();
+
+    assert_eq!(
+        graph.render(GraphFormat::Mermaid),
+        "flowchart BT
+    EnemySpawnerPrototype
+    EntityPrototype
+    ItemCountType
+    PrototypeBase
+    SpacePlatformHubPrototype
+    EnemySpawnerPrototype --> EntityPrototype
+    EntityPrototype --> PrototypeBase
+    SpacePlatformHubPrototype --> EntityPrototype
+    classDef added fill:#98fb98
+    class SpacePlatformHubPrototype added
+    classDef removed fill:#ffb6c1
+    class EnemySpawnerPrototype removed
+    classDef changed fill:#eedd82
+    class EntityPrototype,ItemCountType changed
+"
+    );
+    assert!(graph
+        .render(GraphFormat::Dot)
+        .contains("\"SpacePlatformHubPrototype\" -> \"EntityPrototype\";"));
+}
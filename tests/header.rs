@@ -0,0 +1,95 @@
+//! The header of the diffs describing the compared docs.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{
+        prototype::PrototypeDoc, runtime::RuntimeDoc, ApiVersions, DiffOptions, Invert, Stage,
+    },
+    render, Comparison, Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+#[test]
+fn versions_of_the_compared_docs() {
+    let c = Comparison::<PrototypeDoc>::new(
+        &common::raw("1.1.110", Docs::Prototype),
+        &common::raw("2.0.8", Docs::Prototype),
+        DiffOptions::default(),
+    )
+    .expect("docs are diffed");
+
+    let header = c.diff.header.clone().expect("diff has a header");
+    assert_eq!(header.stage, Stage::Prototype);
+    assert_eq!(header.from_version, "1.1.110");
+    assert_eq!(header.to_version, "2.0.8");
+    assert_eq!(header.api_versions, ApiVersions { from: 5, to: 6 });
+    assert_eq!(
+        header.generated_with,
+        concat!("fapi-diff ", env!("CARGO_PKG_VERSION"))
+    );
+
+    let inverted = c.diff.invert().header.expect("inverted diff has a header");
+    assert_eq!(inverted.from_version, "2.0.8");
+    assert_eq!(inverted.to_version, "1.1.110");
+    assert_eq!(inverted.api_versions, ApiVersions { from: 6, to: 5 });
+}
+
+#[test]
+fn only_in_the_json_formats() {
+    let c = Comparison::<RuntimeDoc>::new(
+        &common::raw("1.1.100", Docs::Runtime),
+        &common::raw("1.1.110", Docs::Runtime),
+        DiffOptions::default(),
+    )
+    .expect("docs are diffed");
+
+    let out = c
+        .render(
+            render::OutputFormat::Json,
+            false,
+            render::JsonStyle::default(),
+        )
+        .expect("diff renders");
+    let diff = serde_json::from_str::<Value>(&out).expect("output is JSON");
+    assert_eq!(
+        diff["header"],
+        json!({
+            "application": "factorio",
+            "stage": "runtime",
+            "from_version": "1.1.100",
+            "to_version": "1.1.110",
+            "api_versions": {"from": 5, "to": 5},
+            "generated_with": concat!("fapi-diff ", env!("CARGO_PKG_VERSION")),
+        })
+    );
+
+    let out = c
+        .render(
+            render::OutputFormat::Flat,
+            false,
+            render::JsonStyle::default(),
+        )
+        .expect("diff renders");
+    assert!(!out.contains("\"header"), "{out}");
+}
+
+#[test]
+fn older_diffs_have_none() {
+    let diff = serde_json::from_value::<fapi_diff::format::runtime::RuntimeDocDiff>(json!({
+        "diff_format_version": 3,
+        "classes": {},
+        "events": {},
+        "concepts": {},
+        "defines": {},
+        "global_objects": {},
+        "global_functions": {},
+    }))
+    .expect("diff deserializes");
+    assert!(diff.header.is_none());
+
+    let out = serde_json::to_value(&diff).expect("diff serializes");
+    assert!(out.get("header").is_none());
+}
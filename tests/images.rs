@@ -0,0 +1,102 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{
+        runtime::{RuntimeDoc, RuntimeDocDiff},
+        DiffOptions, Doc, FieldPolicy, Invert,
+    },
+    Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+/// The 1.1.110 runtime doc with the images of `LuaEntity` replaced.
+fn doc(images: &Value) -> RuntimeDoc {
+    let mut doc = common::json("1.1.110", Docs::Runtime);
+    common::class(&mut doc, "LuaEntity")["images"] = images.clone();
+
+    serde_json::from_value(doc).expect("fixture is a runtime doc")
+}
+
+/// Images are only diffed with all field categories.
+fn diff(source: &RuntimeDoc, target: &RuntimeDoc) -> RuntimeDocDiff {
+    let options = DiffOptions {
+        policy: FieldPolicy::all(),
+        ..Default::default()
+    };
+    options.apply(|| source.diff(target))
+}
+
+fn images(diff: &Value) -> &Value {
+    diff["classes"]["LuaEntity"]
+        .as_array()
+        .expect("class diff is a list of changes")
+        .iter()
+        .find_map(|c| c.get("images"))
+        .expect("images changed")
+}
+
+#[test]
+fn images_are_diffed_by_file_name() {
+    let source = doc(&json!([
+        {"filename": "entity-ghost.png", "caption": "A ghost"},
+        {"filename": "old.png"},
+    ]));
+    let target = doc(&json!([
+        {"filename": "new.png"},
+        {"filename": "entity-ghost.png", "caption": "A ghost of an entity"},
+    ]));
+
+    let diff = diff(&source, &target);
+    let value = serde_json::to_value(&diff).expect("diff is serializable");
+    assert_eq!(
+        images(&value),
+        &json!({
+            "added": [{"filename": "new.png", "caption": null}],
+            "removed": [{"filename": "old.png", "caption": null}],
+            "captions": {"entity-ghost.png": "A ghost of an entity"},
+        })
+    );
+
+    let changes = fapi_diff::render::flat::flatten(
+        &value,
+        &serde_json::to_value(&source).expect("doc is serializable"),
+        &serde_json::to_value(&target).expect("doc is serializable"),
+    )
+    .into_iter()
+    .map(|c| (c.path, c.old, c.new))
+    .collect::<Vec<_>>();
+    assert_eq!(
+        changes,
+        [
+            (
+                "classes/LuaEntity/images/new.png".to_owned(),
+                Value::Null,
+                json!({"filename": "new.png", "caption": null}),
+            ),
+            (
+                "classes/LuaEntity/images/old.png".to_owned(),
+                json!({"filename": "old.png", "caption": null}),
+                Value::Null,
+            ),
+            (
+                "classes/LuaEntity/images/entity-ghost.png/caption".to_owned(),
+                json!("A ghost"),
+                json!("A ghost of an entity"),
+            ),
+        ]
+    );
+
+    let inverted = serde_json::to_value(diff.invert()).expect("diff is serializable");
+    assert_eq!(images(&inverted)["added"][0]["filename"], "old.png");
+}
+
+#[test]
+fn moved_images_are_unchanged() {
+    let source = doc(&json!([{"filename": "a.png"}, {"filename": "b.png"}]));
+    let target = doc(&json!([{"filename": "b.png"}, {"filename": "a.png"}]));
+
+    let diff = serde_json::to_value(diff(&source, &target)).expect("diff is serializable");
+    assert!(diff["classes"].get("LuaEntity").is_none());
+}
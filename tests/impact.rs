@@ -0,0 +1,144 @@
+//! Uses of removed and changed APIs found in the Lua files of a mod by `impact`.
+
+#![allow(clippy::expect_used)]
+
+use std::{path::PathBuf, process::Command};
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions, FieldPolicy},
+    impact::{self, ApiIndex, Impact},
+    Comparison, Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+const CONTROL: &str = r#"local entity = surface.create_entity({name = "car"})
+local name = entity.name
+entity.health = 10
+local found = surface.find_entities({})
+game.print("health: "..health)
+script.on_event(defines.events.on_tick, function() end)
+"#;
+
+/// The fixture with a description only change of `LuaEntity.name`,
+/// a type change of `LuaEntity.health` and `LuaSurface.find_entities` removed.
+fn changed() -> Vec<u8> {
+    common::edited("1.1.110", Docs::Runtime, |doc| {
+        common::member(doc, "LuaEntity", "attributes", "name")["description"] =
+            json!("The prototype name of the entity.");
+        common::member(doc, "LuaEntity", "attributes", "health")["type"] = json!("double");
+
+        common::class(doc, "LuaSurface")["methods"]
+            .as_array_mut()
+            .expect("class has methods")
+            .retain(|m| m["name"] != "find_entities");
+    })
+}
+
+/// A mod with `control.lua` in a fresh temp dir.
+fn mod_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fapi-diff-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("failed to create the temp dir");
+    std::fs::write(dir.join("control.lua"), CONTROL).expect("failed to write the mod");
+
+    dir
+}
+
+#[test]
+fn changed_members() {
+    let c = Comparison::<RuntimeDoc>::new(
+        &common::raw("1.1.110", Docs::Runtime),
+        &changed(),
+        // descriptions are diffed as well, they still aren't reported
+        DiffOptions {
+            policy: FieldPolicy::all(),
+            ..Default::default()
+        },
+    )
+    .expect("docs are diffed");
+
+    let mut index = ApiIndex::default();
+    index.add_runtime(&c.source, &c.target, &c.diff);
+
+    let dir = mod_dir("impact-lib");
+    let usages = impact::scan(&dir, &index);
+    std::fs::remove_dir_all(&dir).ok();
+
+    let usages = usages
+        .expect("mod is scanned")
+        .into_iter()
+        .map(|u| {
+            (
+                u.line,
+                u.code,
+                u.apis
+                    .into_iter()
+                    .map(|a| (a.path, a.impact))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // neither the description only change of `name` nor the concatenated `health` are reported
+    assert_eq!(
+        usages,
+        [
+            (
+                3,
+                "entity.health".to_owned(),
+                vec![(
+                    "classes/LuaEntity/attributes/health".to_owned(),
+                    Impact::Changed
+                )]
+            ),
+            (
+                4,
+                "surface.find_entities".to_owned(),
+                vec![(
+                    "classes/LuaSurface/methods/find_entities".to_owned(),
+                    Impact::Removed
+                )]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn command() {
+    let docs = std::env::temp_dir().join(format!("fapi-diff-impact-docs-{}", std::process::id()));
+    let (from, to) = (docs.join("from"), docs.join("to"));
+    for (dir, doc) in [
+        (&from, common::raw("1.1.110", Docs::Runtime)),
+        (&to, changed()),
+    ] {
+        std::fs::create_dir_all(dir.join("doc-html")).expect("failed to create the docs dir");
+        std::fs::write(dir.join("doc-html/runtime-api.json"), doc)
+            .expect("failed to write the doc");
+    }
+    let dir = mod_dir("impact-cli");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .args(["impact", "--local", "--mod"])
+        .arg(&dir)
+        .arg("--from")
+        .arg(&from)
+        .arg("--to")
+        .arg(&to)
+        .output()
+        .expect("failed to run fapi-diff");
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::remove_dir_all(&docs).ok();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(
+        stderr.contains("2 usages of changed APIs found"),
+        "{stderr}"
+    );
+
+    let usages = serde_json::from_slice::<Value>(&output.stdout).expect("output is JSON");
+    assert_eq!(usages[0]["file"], "control.lua");
+    assert_eq!(usages[0]["line"], 3);
+    assert_eq!(usages[1]["apis"][0]["impact"], "removed");
+}
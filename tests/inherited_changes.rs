@@ -0,0 +1,64 @@
+//! Subclasses of classes whose members changed are annotated with the changed parent when `inherit` is set.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions},
+    Comparison, Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+/// The 2.0.8 runtime doc with the methods of `LuaControl`, the parent of `LuaEntity`, changed by `mutate`.
+fn with_parent_methods(mutate: impl FnOnce(&mut Vec<Value>)) -> Vec<u8> {
+    common::edited("2.0.8", Docs::Runtime, |doc| {
+        mutate(
+            common::class(doc, "LuaControl")["methods"]
+                .as_array_mut()
+                .expect("methods are a list"),
+        );
+    })
+}
+
+fn doc() -> Vec<u8> {
+    common::raw("2.0.8", Docs::Runtime)
+}
+
+fn classes(source: &[u8], target: &[u8], inherit: bool) -> Value {
+    let options = DiffOptions {
+        inherit,
+        ..Default::default()
+    };
+    let c = Comparison::<RuntimeDoc>::new(source, target, options).expect("docs are diffed");
+
+    serde_json::to_value(&c.diff.classes).expect("diff is serializable")
+}
+
+#[test]
+fn parent_members_changed() {
+    let gained = with_parent_methods(|methods| {
+        let mut method = methods[0].clone();
+        method["name"] = json!("greet");
+        methods.push(method);
+    });
+    let lost = with_parent_methods(|methods| methods.retain(|m| m["name"] != "clear_cursor"));
+
+    for target in [gained, lost] {
+        let diff = classes(&doc(), &target, true);
+        assert!(diff.get("LuaControl").is_some());
+        assert_eq!(
+            diff["LuaEntity"],
+            json!([{"related_changes": [{"parent_class": "LuaControl"}]}])
+        );
+
+        let diff = classes(&doc(), &target, false);
+        assert!(diff.get("LuaControl").is_some());
+        assert!(diff.get("LuaEntity").is_none());
+    }
+}
+
+#[test]
+fn unchanged_parents_are_not_linked() {
+    assert_eq!(classes(&doc(), &doc(), true), json!({}));
+}
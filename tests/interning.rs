@@ -0,0 +1,92 @@
+//! Interned strings, which are only shared within a scope so long-running hosts don't keep them all.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    cache::Cache,
+    format::{intern, runtime::RuntimeDoc, DiffOptions, Interned, Named},
+    Comparison, Docs,
+};
+
+mod common;
+
+#[test]
+fn strings_are_shared_within_a_scope() {
+    intern::scope(|| {
+        let a = Interned::new("LuaEntity");
+        let b = intern::scope(|| Interned::new("LuaEntity"));
+
+        assert!(std::ptr::eq(a.as_str(), b.as_str()));
+        assert_eq!(intern::interned_count(), 1);
+    });
+}
+
+#[test]
+fn strings_are_not_kept_outside_of_a_scope() {
+    let a = Interned::new("LuaEntity");
+    let b = Interned::new("LuaEntity");
+
+    assert_eq!(a, b);
+    assert!(!std::ptr::eq(a.as_str(), b.as_str()));
+    assert_eq!(intern::interned_count(), 0);
+}
+
+#[test]
+fn table_is_dropped_after_a_run() {
+    let source = common::raw("1.1.100", Docs::Runtime);
+    let target = common::raw("1.1.110", Docs::Runtime);
+
+    let comparison = Comparison::<RuntimeDoc>::new(&source, &target, DiffOptions::default())
+        .expect("docs are valid");
+    assert_eq!(intern::interned_count(), 0);
+
+    Docs::Runtime
+        .count(&source, &target, DiffOptions::default())
+        .expect("docs are valid");
+    assert_eq!(intern::interned_count(), 0);
+
+    // the strings are still shared between the docs
+    let source = comparison.source.classes["LuaEntity"].name();
+    let target = comparison.target.classes["LuaEntity"].name();
+    assert!(std::ptr::eq(source, target));
+}
+
+#[test]
+fn cached_docs_share_their_strings() {
+    let dir = std::env::temp_dir().join(format!("fapi-diff-interning-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let cache = Cache::new(&dir);
+    let source = common::raw("1.1.100", Docs::Runtime);
+    let target = common::raw("1.1.110", Docs::Runtime);
+
+    // the first run fills the cache, the second one reads both docs from it
+    for _ in 0..2 {
+        let comparison = Comparison::<RuntimeDoc>::with_cache(
+            &source,
+            &target,
+            DiffOptions::default(),
+            Some(&cache),
+        )
+        .expect("docs are valid");
+        assert_eq!(intern::interned_count(), 0);
+
+        let source = comparison.source.classes["LuaEntity"].name();
+        let target = comparison.target.classes["LuaEntity"].name();
+        assert!(std::ptr::eq(source, target));
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn table_is_dropped_after_a_panic() {
+    let result = std::panic::catch_unwind(|| {
+        intern::scope(|| {
+            let _ = Interned::new("LuaEntity");
+            panic!("failed run");
+        });
+    });
+
+    assert!(result.is_err());
+    assert_eq!(intern::interned_count(), 0);
+}
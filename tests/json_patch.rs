@@ -0,0 +1,130 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{prototype::PrototypeDoc, runtime::RuntimeDoc, DiffOptions, Doc, FieldPolicy},
+    render::{self, JsonStyle, OutputFormat},
+    Comparison, Docs,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::ops::Deref;
+
+mod common;
+
+fn name(item: &Value) -> Option<&str> {
+    item.get("name").and_then(Value::as_str)
+}
+
+/// Sorts all lists of named items by `key`.
+fn sort_by<K: Ord>(value: &mut Value, key: &impl Fn(&Value) -> K) {
+    match value {
+        Value::Array(items) => {
+            if !items.is_empty() && items.iter().all(|i| name(i).is_some()) {
+                items.sort_by_key(key);
+            }
+            for item in items {
+                sort_by(item, key);
+            }
+        }
+        Value::Object(map) => {
+            for value in map.values_mut() {
+                sort_by(value, key);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn by_order(value: &mut Value) {
+    sort_by(value, &|i: &Value| {
+        let order = i.get("order").and_then(Value::as_f64).unwrap_or(0.0);
+        ((order * 1000.0) as i64, name(i).map(str::to_owned))
+    });
+}
+
+fn by_name(value: &mut Value) {
+    sort_by(value, &|i: &Value| name(i).map(str::to_owned));
+}
+
+/// Minimal RFC 6902 implementation of the operations used by the output.
+fn apply(doc: &mut Value, operations: &[Value]) {
+    for operation in operations {
+        let path = operation["path"].as_str().expect("path is a string");
+        let (parent, last) = path.rsplit_once('/').expect("path has a parent");
+        let last = last.replace("~1", "/").replace("~0", "~");
+        let parent = doc.pointer_mut(parent).expect("parent exists");
+
+        match (operation["op"].as_str(), parent) {
+            (Some("add"), Value::Array(items)) if last == "-" => {
+                items.push(operation["value"].clone());
+            }
+            (Some("add"), Value::Array(items)) => {
+                items.insert(last.parse().expect("index"), operation["value"].clone());
+            }
+            (Some("add" | "replace"), Value::Object(map)) => {
+                map.insert(last, operation["value"].clone());
+            }
+            (Some("replace"), Value::Array(items)) => {
+                items[last.parse::<usize>().expect("index")] = operation["value"].clone();
+            }
+            (Some("remove"), Value::Array(items)) => {
+                items.remove(last.parse().expect("index"));
+            }
+            (Some("remove"), Value::Object(map)) => {
+                map.remove(&last);
+            }
+            (op, _) => panic!("unexpected operation {op:?} at {path}"),
+        }
+    }
+}
+
+fn check<T>(stage: Docs, source: &str, target: &str)
+where
+    T: Doc + Deref<Target = fapi_diff::format::Common> + DeserializeOwned + Serialize,
+    T::Diff: Serialize,
+{
+    let raw = |version| common::raw(version, stage);
+    let options = DiffOptions {
+        policy: FieldPolicy::all(),
+        ..Default::default()
+    };
+    let c = Comparison::<T>::new(&raw(source), &raw(target), options).expect("fixtures are docs");
+
+    let patch = c
+        .render(OutputFormat::JsonPatch, false, JsonStyle::default())
+        .expect("diff is serializable");
+    let operations: Vec<Value> = serde_json::from_str(&patch).expect("patch is JSON");
+    assert!(!operations.is_empty());
+
+    let mut expected = serde_json::to_value(&c.target).expect("target is serializable");
+    by_name(&mut expected);
+
+    // the operations address items by their position in the docs, which list them by their order
+    let mut doc = serde_json::to_value(&c.source).expect("source is serializable");
+    by_order(&mut doc);
+    apply(&mut doc, &operations);
+    by_name(&mut doc);
+    assert_eq!(doc, expected, "{source} -> {target}");
+
+    // `apply` patches the raw source doc like the CLI does
+    let mut doc = serde_json::from_slice::<Value>(&raw(source)).expect("source is JSON");
+    let applied = render::patch::apply(&mut doc, &operations, &[]).expect("patch fits the source");
+    assert_eq!(applied, operations.len());
+    let mut doc =
+        serde_json::to_value(serde_json::from_value::<T>(doc).expect("patched doc parses"))
+            .expect("patched doc is serializable");
+    by_name(&mut doc);
+    assert_eq!(doc, expected, "{source} -> {target}");
+}
+
+#[test]
+fn patch_turns_source_into_target() {
+    check::<RuntimeDoc>(Docs::Runtime, "1.1.100", "1.1.110");
+    check::<PrototypeDoc>(Docs::Prototype, "1.1.110", "2.0.8");
+}
+
+#[test]
+fn patch_changes_header_and_attribute_shape() {
+    // api version 5 to 6, attributes and operators have a `read_type` and `write_type` instead of `type`, `read` and `write`
+    check::<RuntimeDoc>(Docs::Runtime, "1.1.110", "2.0.8");
+}
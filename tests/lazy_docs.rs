@@ -0,0 +1,74 @@
+//! Streamed diffs of lazily parsed docs, whose items are only deserialized while they're diffed.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{format::DiffOptions, Docs};
+use serde_json::Value;
+
+mod common;
+
+/// The doc with the methods of `LuaEntity` replaced by a string.
+fn malformed(version: &str) -> Vec<u8> {
+    common::edited(version, Docs::Runtime, |doc| {
+        common::class(doc, "LuaEntity")["methods"] = Value::from("bogus");
+    })
+}
+
+#[test]
+fn malformed_items_fail() {
+    for (source, target, what) in [
+        (
+            malformed("1.1.100"),
+            common::raw("1.1.110", Docs::Runtime),
+            "source",
+        ),
+        (
+            common::raw("1.1.100", Docs::Runtime),
+            malformed("1.1.110"),
+            "target",
+        ),
+    ] {
+        let mut out = Vec::new();
+        let error = Docs::Runtime
+            .stream(&source, &target, DiffOptions::default(), false, &mut out)
+            .expect_err("malformed items can't be diffed");
+
+        let message = error.to_string();
+        assert!(
+            message.starts_with(&format!(
+                "Failed to deserialize {what} at classes[\"LuaEntity\"].methods"
+            )),
+            "{message}"
+        );
+        // the item isn't reported as added or removed instead
+        assert!(
+            !String::from_utf8_lossy(&out).contains("\"name\":\"LuaEntity\""),
+            "{}",
+            String::from_utf8_lossy(&out)
+        );
+    }
+}
+
+#[test]
+fn unnamed_items_fail() {
+    let source = common::edited("1.1.110", Docs::Runtime, |doc| {
+        common::class(doc, "LuaEntity")
+            .as_object_mut()
+            .expect("classes are objects")
+            .remove("name");
+    });
+    let target = common::raw("1.1.110", Docs::Runtime);
+
+    let mut out = Vec::new();
+    let error = Docs::Runtime
+        .stream(&source, &target, DiffOptions::default(), false, &mut out)
+        .expect_err("items without a name can't be diffed");
+
+    let message = error.to_string();
+    assert!(
+        message.starts_with("Failed to deserialize source at classes["),
+        "{message}"
+    );
+    // the item isn't reported as removed instead
+    assert!(out.is_empty(), "{}", String::from_utf8_lossy(&out));
+}
@@ -0,0 +1,140 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions, Doc, FieldPolicy},
+    Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+/// Replaces all descriptions of the doc.
+fn describe(value: &mut Value, description: &str) -> usize {
+    match value {
+        Value::Object(map) => {
+            let own = map.get_mut("description").map_or(0, |d| {
+                *d = json!(description);
+                1
+            });
+
+            own + map
+                .values_mut()
+                .map(|v| describe(v, description))
+                .sum::<usize>()
+        }
+        Value::Array(items) => items.iter_mut().map(|v| describe(v, description)).sum(),
+        _ => 0,
+    }
+}
+
+/// The fixture with links in the old format and in the new format in all descriptions.
+fn docs() -> (RuntimeDoc, RuntimeDoc, usize) {
+    let mut source = common::json("2.0.8", Docs::Runtime);
+    let mut target = source.clone();
+    describe(&mut source, "Teleports the [entity](LuaEntity.html).");
+    let count = describe(&mut target, "Teleports the [entity](runtime:LuaEntity).");
+
+    let parse = |doc| serde_json::from_value::<RuntimeDoc>(doc).expect("fixture is a runtime doc");
+    (parse(source), parse(target), count)
+}
+
+fn options(keep_link_changes: bool) -> DiffOptions {
+    DiffOptions {
+        policy: FieldPolicy::all(),
+        keep_link_changes,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn mass_link_changes_are_collapsed() {
+    let (source, target, count) = docs();
+    assert!(count >= 10, "the fixture has too few descriptions");
+
+    let diff = options(false).apply(|| source.diff(&target));
+    assert_eq!(diff.reformatted_descriptions, count);
+    assert!(diff.classes.is_empty() && diff.concepts.is_empty());
+
+    let mut entries = Vec::new();
+    options(false)
+        .apply(|| source.diff_entries(&target, &mut |e| entries.push(e)))
+        .expect("docs are diffed");
+    let entries = serde_json::to_value(entries).expect("entries are serializable");
+    assert_eq!(
+        entries,
+        json!([{
+            "kind": "reformatting",
+            "name": "descriptions",
+            "status": "changed",
+            "changes": {"links_only": count},
+        }])
+    );
+}
+
+#[test]
+fn link_changes_can_be_kept() {
+    let (source, target, _) = docs();
+
+    let diff = options(true).apply(|| source.diff(&target));
+    assert_eq!(diff.reformatted_descriptions, 0);
+    assert!(!diff.classes.is_empty());
+}
+
+#[test]
+fn normalized_markup_is_not_a_change() {
+    let mut source = common::json("2.0.8", Docs::Runtime);
+    source["classes"][0]["description"] =
+        json!("Teleports the [entity](LuaEntity.html), see [LuaEntity::teleport].");
+    let mut target = source.clone();
+    target["classes"][0]["description"] =
+        json!("Teleports  the **entity**, see LuaEntity::teleport.");
+
+    let parse = |doc| serde_json::from_value::<RuntimeDoc>(doc).expect("fixture is a runtime doc");
+    let (source, target) = (parse(source), parse(target));
+    let diff = |builtin, rules: &[String]| {
+        fapi_diff::format::with_normalized(builtin, rules, || {
+            options(false).apply(|| source.diff(&target)).classes.len()
+        })
+        .expect("rules are valid")
+    };
+
+    assert_eq!(diff(false, &[]), 1);
+    assert_eq!(diff(true, &[]), 1);
+    assert_eq!(diff(true, &[r"\*\*".to_owned()]), 0);
+}
+
+#[test]
+fn counts_agree_with_collapsed_entries() {
+    let (source, target, _) = docs();
+    let counts = |source: &RuntimeDoc, target: &RuntimeDoc| {
+        options(false)
+            .apply(|| source.count_changes(target))
+            .expect("docs are counted")
+            .total
+    };
+    let entries = |source: &RuntimeDoc, target: &RuntimeDoc| {
+        let mut entries = 0;
+        options(false)
+            .apply(|| {
+                source.diff_entries(target, &mut |e| {
+                    entries += usize::from(e.kind != "reformatting");
+                })
+            })
+            .expect("docs are diffed");
+        entries
+    };
+
+    assert_eq!(counts(&source, &target), 0);
+    assert_eq!(entries(&source, &target), 0);
+
+    // a single link only change isn't a reformatting, its item counts as changed
+    let mut source = common::json("2.0.8", Docs::Runtime);
+    let mut target = source.clone();
+    source["classes"][0]["description"] = json!("Teleports the [entity](LuaEntity.html).");
+    target["classes"][0]["description"] = json!("Teleports the [entity](runtime:LuaEntity).");
+    let parse = |doc| serde_json::from_value::<RuntimeDoc>(doc).expect("fixture is a runtime doc");
+    let (source, target) = (parse(source), parse(target));
+
+    assert_eq!(counts(&source, &target), 1);
+    assert_eq!(entries(&source, &target), 1);
+}
@@ -0,0 +1,79 @@
+#![allow(clippy::expect_used)]
+
+use std::{path::PathBuf, process::Command};
+
+use fapi_diff::Docs;
+use serde_json::{json, Value};
+
+mod common;
+
+/// A target doc with a concept of an unknown complex type, warned about while streaming the diff.
+fn future_target(dir: &std::path::Path) -> PathBuf {
+    let doc = common::edited("2.0.8", Docs::Runtime, |doc| {
+        doc["concepts"]
+            .as_array_mut()
+            .expect("fixture has concepts")
+            .push(json!({
+                "name": "FutureConcept",
+                "order": 0,
+                "description": "",
+                "type": {"complex_type": "bitfield", "bits": 8, "value": "uint8"},
+            }));
+    });
+
+    let path = dir.join("runtime-api.json");
+    std::fs::write(&path, doc).expect("failed to write the target");
+
+    path
+}
+
+fn stderr(name: &str, log_format: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("fapi-diff-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("failed to create the temp dir");
+    let target = future_target(&dir);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .args(["--log-format", log_format, "compare", "runtime"])
+        .arg(common::path("2.0.8", Docs::Runtime))
+        .arg(&target)
+        .args(["--format", "ndjson"])
+        .output()
+        .expect("failed to run fapi-diff");
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(
+        output.status.success(),
+        "fapi-diff failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stderr).expect("stderr is UTF-8")
+}
+
+#[test]
+fn json_lines() {
+    let stderr = stderr("log-json", "json");
+
+    let warnings = stderr
+        .lines()
+        .filter_map(|l| serde_json::from_str::<Value>(l).ok())
+        .collect::<Vec<_>>();
+    let warning = warnings
+        .iter()
+        .find(|w| w["path"] == "concepts/FutureConcept/type")
+        .expect("FutureConcept is warned about");
+
+    assert_eq!(warning["level"], "warning");
+    assert!(warning["message"]
+        .as_str()
+        .is_some_and(|m| m.contains("unknown complex type bitfield")));
+}
+
+#[test]
+fn text() {
+    let stderr = stderr("log-text", "text");
+
+    assert!(stderr
+        .lines()
+        .any(|l| l == "warning: concepts/FutureConcept/type: unknown complex type bitfield"));
+}
@@ -0,0 +1,101 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::format::{runtime::RuntimeDocDiff, DIFF_FORMAT_VERSION};
+use serde_json::{json, Value};
+
+const SNAPSHOTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots");
+
+fn snapshot(name: &str) -> Value {
+    let raw = std::fs::read(format!("{SNAPSHOTS}/{name}.json")).expect("snapshot exists");
+    serde_json::from_slice(&raw).expect("snapshot is JSON")
+}
+
+/// The diff as it was serialized before diffs were versioned.
+fn unversioned(mut diff: Value) -> Value {
+    let map = diff.as_object_mut().expect("diff is an object");
+    for key in ["diff_format_version", "added", "removed"] {
+        map.remove(key);
+    }
+    diff
+}
+
+#[test]
+fn diffs_are_versioned() {
+    let diff = snapshot("runtime_1.1.100_1.1.110");
+    assert_eq!(diff["diff_format_version"], json!(DIFF_FORMAT_VERSION));
+
+    let diff: RuntimeDocDiff =
+        serde_json::from_value(unversioned(diff)).expect("old diffs deserialize");
+    assert_eq!(diff.diff_format_version, 1);
+}
+
+#[test]
+fn unversioned_diffs_are_upgraded() {
+    let mut diff = unversioned(snapshot("prototype_1.1.110_2.0.8"));
+
+    let version = fapi_diff::migrate::migrate(&mut diff, "diff").expect("diff is migrated");
+    assert_eq!(version, 1);
+    assert_eq!(diff["diff_format_version"], json!(DIFF_FORMAT_VERSION));
+    assert_eq!(
+        diff["added"],
+        json!({"prototypes": [], "types": [], "defines": []})
+    );
+
+    // migrating again doesn't change anything
+    let migrated = diff.clone();
+    let version = fapi_diff::migrate::migrate(&mut diff, "diff").expect("diff is migrated");
+    assert_eq!(version, u64::from(DIFF_FORMAT_VERSION));
+    assert_eq!(diff, migrated);
+}
+
+#[test]
+fn combined_diffs_are_upgraded() {
+    let mut diff = json!({
+        "runtime": unversioned(snapshot("runtime_1.1.100_1.1.110")),
+        "prototype": snapshot("prototype_1.1.100_1.1.110"),
+        "cross_stage": [],
+    });
+
+    let version = fapi_diff::migrate::migrate(&mut diff, "diff").expect("diff is migrated");
+    assert_eq!(version, 1);
+    assert_eq!(diff["runtime"]["added"]["global_functions"], json!([]));
+    assert_eq!(diff["cross_stage"], json!([]));
+}
+
+#[test]
+fn newer_diffs_are_rejected() {
+    let mut diff = snapshot("runtime_1.1.100_1.1.110");
+    diff["diff_format_version"] = json!(DIFF_FORMAT_VERSION + 1);
+
+    let err = fapi_diff::migrate::migrate(&mut diff, "diff").expect_err("diff is too new");
+    assert!(matches!(
+        err,
+        fapi_diff::Error::UnsupportedDiffFormat { .. }
+    ));
+}
+
+#[test]
+fn image_lists_are_upgraded() {
+    let mut diff = snapshot("runtime_1.1.100_1.1.110");
+    diff["diff_format_version"] = json!(2);
+    diff["classes"]["LuaEntity"] = json!([{"images": {
+        "old": [{"filename": "a.png", "caption": null}, {"filename": "b.png", "caption": "B"}],
+        "new": [{"filename": "b.png", "caption": "C"}, {"filename": "d.png", "caption": null}],
+    }}]);
+    diff["classes"]["LuaControl"] = json!([{"images": [{"filename": "e.png", "caption": null}]}]);
+
+    fapi_diff::migrate::migrate(&mut diff, "diff").expect("diff is migrated");
+    assert_eq!(
+        diff["classes"]["LuaEntity"],
+        json!([{"images": {
+            "added": [{"filename": "d.png", "caption": null}],
+            "removed": [{"filename": "a.png", "caption": null}],
+            "captions": {"b.png": {"old": "B", "new": "C"}},
+        }}])
+    );
+    // without old values all images are new
+    assert_eq!(
+        diff["classes"]["LuaControl"],
+        json!([{"images": {"added": [{"filename": "e.png", "caption": null}]}}])
+    );
+}
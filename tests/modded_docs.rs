@@ -0,0 +1,54 @@
+#![allow(clippy::expect_used)]
+
+use std::process::Command;
+
+use fapi_diff::Docs;
+use serde_json::{json, Value};
+
+mod common;
+
+/// Writes the runtime fixture as docs of a mod, with the first class removed from the target.
+fn modded_docs(dir: &std::path::Path) -> (std::path::PathBuf, std::path::PathBuf) {
+    let mut source = common::json("2.0.8", Docs::Runtime);
+    source["application"] = json!("my-mod");
+
+    let mut target = source.clone();
+    target["classes"]
+        .as_array_mut()
+        .expect("classes are a list")
+        .remove(0);
+
+    let paths = (dir.join("source.json"), dir.join("target.json"));
+    std::fs::write(&paths.0, source.to_string()).expect("failed to write the source");
+    std::fs::write(&paths.1, target.to_string()).expect("failed to write the target");
+
+    paths
+}
+
+#[test]
+fn modded_docs_have_no_links() {
+    let dir = std::env::temp_dir().join(format!("fapi-diff-modded-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("failed to create the temp dir");
+    let (source, target) = modded_docs(&dir);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .arg(&target)
+        .arg(&source)
+        .args(["--format", "flat", "--anchor-links"])
+        .output()
+        .expect("failed to run fapi-diff");
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(
+        output.status.success(),
+        "fapi-diff failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let changes: Value = serde_json::from_slice(&output.stdout).expect("output is valid JSON");
+    let changes = changes.as_array().expect("flat output is a list");
+
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].get("url"), None);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("my-mod @ 2.0.8"));
+}
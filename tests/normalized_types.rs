@@ -0,0 +1,105 @@
+//! `type` wrappers without a description and single option unions are equivalent to the type they wrap,
+//! unless `exact_types` is set.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{prototype::PrototypeDoc, runtime::RuntimeDoc, DiffOptions},
+    Comparison,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+fn runtime(type_: &Value) -> Vec<u8> {
+    common::concept_doc("WrappedConcept", type_)
+}
+
+fn prototype(type_: &Value) -> Vec<u8> {
+    common::type_doc("WrappedType", type_)
+}
+
+fn options(exact_types: bool) -> DiffOptions {
+    DiffOptions {
+        exact_types,
+        ..Default::default()
+    }
+}
+
+/// Types that wrap `string`, named for the assertion messages.
+fn wrappers() -> [(&'static str, Value); 2] {
+    [
+        (
+            "type wrapper",
+            json!({"complex_type": "type", "value": "string", "description": ""}),
+        ),
+        (
+            "single option union",
+            json!({"complex_type": "union", "options": ["string"], "full_format": false}),
+        ),
+    ]
+}
+
+#[test]
+fn runtime_unwrapped() {
+    let simple = json!("string");
+
+    for (name, wrapper) in wrappers() {
+        for (source, target) in [(&simple, &wrapper), (&wrapper, &simple)] {
+            let c =
+                Comparison::<RuntimeDoc>::new(&runtime(source), &runtime(target), options(false))
+                    .expect("docs are valid");
+            assert!(c.diff.concepts.is_empty(), "{name}");
+
+            let c =
+                Comparison::<RuntimeDoc>::new(&runtime(source), &runtime(target), options(true))
+                    .expect("docs are valid");
+            assert!(c.diff.concepts.contains_key("WrappedConcept"), "{name}");
+        }
+    }
+}
+
+#[test]
+fn prototype_unwrapped() {
+    let simple = json!("string");
+
+    for (name, wrapper) in wrappers() {
+        for (source, target) in [(&simple, &wrapper), (&wrapper, &simple)] {
+            let c = Comparison::<PrototypeDoc>::new(
+                &prototype(source),
+                &prototype(target),
+                options(false),
+            )
+            .expect("docs are valid");
+            assert!(c.diff.types.is_empty(), "{name}");
+
+            let c = Comparison::<PrototypeDoc>::new(
+                &prototype(source),
+                &prototype(target),
+                options(true),
+            )
+            .expect("docs are valid");
+            assert!(c.diff.types.contains_key("WrappedType"), "{name}");
+        }
+    }
+}
+
+#[test]
+fn described_wrappers_are_kept() {
+    let (simple, described) = (
+        json!("string"),
+        json!({"complex_type": "type", "value": "string", "description": "The name."}),
+    );
+
+    let c = Comparison::<RuntimeDoc>::new(&runtime(&simple), &runtime(&described), options(false))
+        .expect("docs are valid");
+    assert!(c.diff.concepts.contains_key("WrappedConcept"));
+
+    let c = Comparison::<PrototypeDoc>::new(
+        &prototype(&simple),
+        &prototype(&described),
+        options(false),
+    )
+    .expect("docs are valid");
+    assert!(c.diff.types.contains_key("WrappedType"));
+}
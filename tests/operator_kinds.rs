@@ -0,0 +1,135 @@
+//! Operators that switch between method and attribute form report the switch next to their new form,
+//! operators of neither form are kept as their raw JSON.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, Doc, Invert},
+    Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+/// The fixture with its `LuaEntity::index` attribute operator and with it replaced by `operator`.
+fn docs_with(operator: &Value) -> (RuntimeDoc, RuntimeDoc) {
+    let source = common::json("2.0.8", Docs::Runtime);
+    let mut target = source.clone();
+    common::class(&mut target, "LuaEntity")["operators"] = json!([operator]);
+
+    (
+        serde_json::from_value(source).expect("source is a runtime doc"),
+        serde_json::from_value(target).expect("target is a runtime doc"),
+    )
+}
+
+/// The fixture with its `LuaEntity::index` attribute operator and with it as a method.
+fn docs() -> (RuntimeDoc, RuntimeDoc) {
+    docs_with(&json!({
+        "name": "index",
+        "order": 0,
+        "description": "Get an entity by index.",
+        "parameters": [{
+            "name": "index",
+            "order": 0,
+            "description": "",
+            "type": "uint",
+            "optional": false,
+        }],
+        "format": {"takes_table": false},
+        "return_values": [{"order": 0, "description": "", "type": "LuaEntity", "optional": false}],
+    }))
+}
+
+fn index(diff: &Value) -> &[Value] {
+    let operators = diff["classes"]["LuaEntity"]
+        .as_array()
+        .and_then(|c| c.iter().find_map(|c| c.get("operators")))
+        .expect("operators changed");
+
+    operators["index"].as_array().expect("index changed")
+}
+
+#[test]
+fn attribute_to_method() {
+    let (source, target) = docs();
+    let diff = serde_json::to_value(source.diff(&target)).expect("diff is serializable");
+
+    let changes = index(&diff);
+    assert_eq!(
+        changes[0],
+        json!({"kind_changed": {"from": "attribute", "to": "method"}})
+    );
+    assert!(changes[1].get("method").is_some());
+
+    let flat = fapi_diff::render::flat::flatten(
+        &diff,
+        &serde_json::to_value(&source).expect("doc is serializable"),
+        &serde_json::to_value(&target).expect("doc is serializable"),
+    );
+    let index = flat
+        .iter()
+        .filter(|c| c.path.starts_with("classes/LuaEntity/operators"))
+        .collect::<Vec<_>>();
+    assert_eq!(index.len(), 1);
+    assert_eq!(index[0].path, "classes/LuaEntity/operators/index");
+    assert_eq!(index[0].old["read_type"], json!("LuaEntity"));
+    assert_eq!(index[0].new["parameters"]["index"]["type"], json!("uint"));
+}
+
+#[test]
+fn inverted_switch() {
+    let (source, target) = docs();
+
+    let back = serde_json::to_value(target.diff(&source)).expect("diff is serializable");
+    let inverted =
+        serde_json::to_value(source.diff(&target).invert()).expect("diff is serializable");
+
+    assert_eq!(
+        index(&back)[0],
+        json!({"kind_changed": {"from": "method", "to": "attribute"}})
+    );
+    assert_eq!(index(&back)[0], index(&inverted)[0]);
+}
+
+#[test]
+fn same_kind() {
+    let (source, _) = docs();
+    let diff = serde_json::to_value(source.diff(&source)).expect("diff is serializable");
+
+    assert!(diff["classes"].get("LuaEntity").is_none());
+}
+
+#[test]
+fn unknown_operator() {
+    let operator = json!({"name": "index", "order": 0, "description": "", "kind": "future"});
+    let (source, target) = docs_with(&operator);
+
+    let diff = serde_json::to_value(source.diff(&target)).expect("diff is serializable");
+    assert_eq!(index(&diff), [json!({"unknown": operator})]);
+
+    let warnings = fapi_diff::format::take_warnings();
+    assert!(warnings.iter().any(|w| {
+        w.path.as_deref() == Some("classes/LuaEntity/operators/index")
+            && w.message.contains("\"future\"")
+    }));
+
+    // the unknown operator is kept as it is
+    let mut target = serde_json::to_value(&target).expect("doc is serializable");
+    assert_eq!(
+        common::class(&mut target, "LuaEntity")["operators"],
+        json!([operator])
+    );
+}
+
+#[test]
+fn removed_operator() {
+    let (source, target) = docs();
+    let mut target = serde_json::to_value(&target).expect("doc is serializable");
+    common::class(&mut target, "LuaEntity")["operators"] = json!([]);
+    let target = serde_json::from_value::<RuntimeDoc>(target).expect("target is a runtime doc");
+
+    let diff = serde_json::to_value(source.diff(&target)).expect("diff is serializable");
+    assert!(diff["classes"]["LuaEntity"].is_array());
+    assert_eq!(fapi_diff::format::take_warnings(), []);
+}
@@ -0,0 +1,74 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{format::runtime::RuntimeDoc, partial, Comparison, Docs, Error};
+use serde_json::{json, Value};
+
+mod common;
+
+fn full() -> Vec<u8> {
+    common::raw("2.0.8", Docs::Runtime)
+}
+
+/// `LuaEntity` of the fixture with a changed description of `teleport`.
+fn patched_class() -> Value {
+    let mut doc = common::json("2.0.8", Docs::Runtime);
+    common::member(&mut doc, "LuaEntity", "methods", "teleport")["description"] =
+        json!("Moves the entity.");
+    common::class(&mut doc, "LuaEntity").take()
+}
+
+fn diff(source: &[u8], target: &[u8]) -> Value {
+    let (source, target) =
+        partial::complete_pair(source, target, Docs::Runtime).expect("docs can be completed");
+    let c = Comparison::<RuntimeDoc>::new(
+        &source,
+        &target,
+        fapi_diff::format::DiffOptions {
+            policy: fapi_diff::format::FieldPolicy::all(),
+            ..Default::default()
+        },
+    )
+    .expect("completed docs are valid");
+
+    serde_json::to_value(&c.diff).expect("diff is serializable")
+}
+
+#[test]
+fn single_item_against_full_doc() {
+    let full = full();
+
+    for partial in [
+        patched_class(),
+        json!([patched_class()]),
+        json!({"classes": [patched_class()]}),
+    ] {
+        let partial = serde_json::to_vec(&partial).expect("partial doc is serializable");
+        let diff = diff(&full, &partial);
+
+        assert_eq!(
+            diff["classes"],
+            json!({"LuaEntity": [{"methods": {"teleport": [{"description": "Moves the entity."}]}}]})
+        );
+        assert_eq!(diff["removed"]["classes"], json!([]));
+        assert_eq!(diff["added"]["classes"], json!([]));
+    }
+}
+
+#[test]
+fn full_docs_are_unchanged() {
+    let full = full();
+    let (source, target) =
+        partial::complete_pair(&full, &full, Docs::Runtime).expect("docs can be completed");
+
+    assert_eq!(*source, *full);
+    assert_eq!(*target, *full);
+}
+
+#[test]
+fn unknown_item() {
+    let partial = serde_json::to_vec(&json!({"name": "Mystery"})).expect("serializable");
+    let err = partial::complete(&partial, Docs::Runtime, None, "target")
+        .expect_err("the section can't be told");
+
+    assert!(matches!(err, Error::Partial { .. }));
+}
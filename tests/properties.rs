@@ -0,0 +1,426 @@
+//! Property tests over randomly generated docs.
+//!
+//! Docs are generated from fixed seeds so failures are reproducible, the seed is part of the failure message.
+
+#![allow(clippy::expect_used)]
+
+mod common;
+
+use fapi_diff::{
+    format::{
+        missing_old_values, prototype::PrototypeDoc, runtime::RuntimeDoc, with_old_values,
+        DiffOptions, Doc, FieldPolicy, Invert,
+    },
+    parse, Docs,
+};
+use serde_json::{json, Value};
+
+const SEEDS: u64 = 64;
+
+/// Small deterministic random number generator (`SplitMix64`).
+struct Rng(u64);
+
+impl Rng {
+    const fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        usize::try_from(self.next() % n as u64).expect("n fits into usize")
+    }
+
+    fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+
+    /// Up to `max` items generated by `f`, with unique names if they're named.
+    fn list(&mut self, max: usize, mut f: impl FnMut(&mut Self, usize) -> Value) -> Vec<Value> {
+        (0..self.below(max + 1)).map(|i| f(self, i)).collect()
+    }
+}
+
+const NAMES: [&str; 8] = [
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel",
+];
+const SIMPLE_TYPES: [&str; 6] = [
+    "string",
+    "uint",
+    "double",
+    "boolean",
+    "LuaEntity",
+    "MapPosition",
+];
+const VISIBILITY: [&str; 3] = ["space_age", "base", "quality"];
+
+fn common(rng: &mut Rng, name: &str, order: usize) -> Value {
+    json!({
+        "name": name,
+        "order": order,
+        "description": if rng.chance(50) { rng.pick(&NAMES) } else { "" },
+    })
+}
+
+fn named(rng: &mut Rng, prefix: &str, i: usize, fields: Value) -> Value {
+    let mut res = common(rng, &format!("{prefix}{i}"), i);
+    let (Value::Object(res_map), Value::Object(fields)) = (&mut res, fields) else {
+        unreachable!("items are objects");
+    };
+    res_map.extend(fields);
+
+    res
+}
+
+fn runtime_type(rng: &mut Rng, depth: usize) -> Value {
+    if depth == 0 || rng.chance(60) {
+        return json!(rng.pick(&SIMPLE_TYPES));
+    }
+
+    match rng.below(4) {
+        0 => json!({"complex_type": "array", "value": runtime_type(rng, depth - 1)}),
+        1 => json!({
+            "complex_type": "union",
+            "options": rng.list(3, |rng, _| runtime_type(rng, depth - 1)),
+            "full_format": rng.chance(50),
+        }),
+        2 => json!({
+            "complex_type": "dictionary",
+            "key": runtime_type(rng, depth - 1),
+            "value": runtime_type(rng, depth - 1),
+        }),
+        _ => {
+            json!({"complex_type": "tuple", "values": rng.list(3, |rng, _| runtime_type(rng, depth - 1))})
+        }
+    }
+}
+
+fn parameter(rng: &mut Rng, i: usize) -> Value {
+    let fields = json!({"type": runtime_type(rng, 2), "optional": rng.chance(30)});
+
+    named(rng, "param_", i, fields)
+}
+
+fn visibility(rng: &mut Rng) -> Vec<&'static str> {
+    VISIBILITY.into_iter().filter(|_| rng.chance(20)).collect()
+}
+
+fn method(rng: &mut Rng, i: usize) -> Value {
+    let fields = json!({
+        "visibility": visibility(rng),
+        "parameters": rng.list(3, parameter),
+        "format": {"takes_table": rng.chance(20)},
+        "return_values": rng.list(2, |rng, i| json!({
+            "order": i,
+            "description": "",
+            "type": runtime_type(rng, 1),
+            "optional": rng.chance(20),
+        })),
+    });
+
+    named(rng, "method_", i, fields)
+}
+
+fn attribute(rng: &mut Rng, i: usize) -> Value {
+    let fields = json!({
+        "visibility": visibility(rng),
+        "read_type": runtime_type(rng, 1),
+        "optional": rng.chance(30),
+    });
+
+    named(rng, "attribute_", i, fields)
+}
+
+fn runtime_doc(rng: &mut Rng) -> Value {
+    json!({
+        "application": "factorio",
+        "stage": "runtime",
+        "application_version": "2.0.8",
+        "api_version": 6,
+        "classes": rng.list(4, |rng, i| {
+            let fields = json!({
+                "visibility": visibility(rng),
+                "abstract": rng.chance(20),
+                "methods": rng.list(4, method),
+                "attributes": rng.list(4, attribute),
+            });
+            named(rng, "Class", i, fields)
+        }),
+        "events": rng.list(3, |rng, i| {
+            let fields = json!({"data": rng.list(3, parameter)});
+            named(rng, "on_event_", i, fields)
+        }),
+        "concepts": rng.list(3, |rng, i| {
+            let fields = json!({"type": runtime_type(rng, 3)});
+            named(rng, "Concept", i, fields)
+        }),
+        "defines": rng.list(2, |rng, i| {
+            let fields = json!({"values": rng.list(4, |rng, i| common(rng, &format!("value_{i}"), i))});
+            named(rng, "define_", i, fields)
+        }),
+        "global_objects": rng.list(2, parameter),
+        "global_functions": rng.list(2, method),
+    })
+}
+
+fn prototype_type(rng: &mut Rng, depth: usize) -> Value {
+    if depth == 0 || rng.chance(60) {
+        return json!(rng.pick(&["string", "uint32", "double", "bool", "FileName", "Color"]));
+    }
+
+    match rng.below(3) {
+        0 => json!({"complex_type": "array", "value": prototype_type(rng, depth - 1)}),
+        1 => json!({
+            "complex_type": "union",
+            "options": rng.list(3, |rng, _| prototype_type(rng, depth - 1)),
+            "full_format": rng.chance(50),
+        }),
+        _ => json!({"complex_type": "literal", "value": rng.pick(&NAMES)}),
+    }
+}
+
+fn property(rng: &mut Rng, i: usize) -> Value {
+    let fields = json!({
+        "visibility": visibility(rng),
+        "override": rng.chance(10),
+        "type": prototype_type(rng, 2),
+        "optional": rng.chance(50),
+    });
+
+    named(rng, "property_", i, fields)
+}
+
+fn prototype_doc(rng: &mut Rng) -> Value {
+    json!({
+        "application": "factorio",
+        "stage": "prototype",
+        "application_version": "2.0.8",
+        "api_version": 6,
+        "prototypes": rng.list(4, |rng, i| {
+            let fields = json!({
+                "visibility": visibility(rng),
+                "abstract": rng.chance(30),
+                "deprecated": rng.chance(10),
+                "properties": rng.list(5, property),
+            });
+            named(rng, "Prototype", i, fields)
+        }),
+        "types": rng.list(4, |rng, i| {
+            let fields = json!({
+                "abstract": false,
+                "inline": rng.chance(20),
+                "type": prototype_type(rng, 3),
+            });
+            named(rng, "Type", i, fields)
+        }),
+    })
+}
+
+/// Options that report every change.
+fn everything() -> DiffOptions {
+    DiffOptions {
+        policy: FieldPolicy::all(),
+        exact_types: true,
+        ..Default::default()
+    }
+}
+
+/// Pairs of independently generated docs, which share some item names.
+fn pairs(generate: fn(&mut Rng) -> Value) -> impl Iterator<Item = (u64, Vec<u8>, Vec<u8>)> {
+    (0..SEEDS).map(move |seed| {
+        let mut rng = Rng(seed);
+        let source = serde_json::to_vec(&generate(&mut rng)).expect("docs are serializable");
+        let target = serde_json::to_vec(&generate(&mut rng)).expect("docs are serializable");
+
+        (seed, source, target)
+    })
+}
+
+fn round_trips<T>(generate: fn(&mut Rng) -> Value)
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    for (seed, raw, _) in pairs(generate) {
+        let doc = parse::<T>(&raw, "doc").expect("generated docs are valid");
+        let reserialized = serde_json::to_vec(&doc).expect("docs are serializable");
+
+        assert_eq!(
+            parse::<T>(&reserialized, "reserialized doc").expect("serialized docs are valid"),
+            doc,
+            "seed {seed}"
+        );
+    }
+}
+
+/// Number of changed items in a serialized doc diff.
+fn changed_items(diff: &Value) -> usize {
+    diff.as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(key, _)| !["added", "removed", "header"].contains(&key.as_str()))
+        .filter_map(|(_, section)| section.as_object())
+        .map(serde_json::Map::len)
+        .sum()
+}
+
+/// Diffs of positional lists like return values are in the order of the source list, which inverting keeps.
+/// Sorts their entries so only what changed is compared.
+fn unordered_lists(value: &mut Value) {
+    match value {
+        Value::Array(items) => {
+            items.iter_mut().for_each(unordered_lists);
+            if items.iter().all(Value::is_array) {
+                items.sort_by_key(Value::to_string);
+            }
+        }
+        Value::Object(map) => map.values_mut().for_each(unordered_lists),
+        _ => {}
+    }
+}
+
+/// Checks that inverting the diff from `a` to `b` gives the diff from `b` to `a`, and that inverting it back is lossless.
+///
+/// `normalize` is applied to both before comparing them.
+fn check_inversion<T>(what: &str, a: &T, b: &T, normalize: fn(&mut Value))
+where
+    T: Doc,
+    T::Diff: serde::Serialize + serde::de::DeserializeOwned + Invert,
+{
+    let (diff, reverse) = everything().apply(|| (a.diff(b), b.diff(a)));
+    let (diff, mut reverse) = with_old_values(true, || {
+        (
+            serde_json::to_value(&diff).expect("diffs are serializable"),
+            serde_json::to_value(&reverse).expect("diffs are serializable"),
+        )
+    });
+
+    let inverted = serde_json::from_value::<T::Diff>(diff.clone())
+        .expect("diffs with old values can be deserialized")
+        .invert();
+    let mut inverted_value =
+        with_old_values(true, || serde_json::to_value(&inverted)).expect("diffs are serializable");
+    normalize(&mut inverted_value);
+    normalize(&mut reverse);
+    assert_eq!(
+        inverted_value, reverse,
+        "{what}: inverted diff differs from the reverse diff"
+    );
+    assert_eq!(
+        with_old_values(true, || serde_json::to_value(inverted.invert()))
+            .expect("diffs are serializable"),
+        diff,
+        "{what}: inverting twice changed the diff"
+    );
+}
+
+fn check_diffs<T>(stage: Docs, generate: fn(&mut Rng) -> Value)
+where
+    T: Doc + serde::de::DeserializeOwned,
+    T::Diff: serde::Serialize + serde::de::DeserializeOwned + Invert,
+{
+    for (seed, source, target) in pairs(generate) {
+        let (a, b) = (
+            parse::<T>(&source, "source").expect("generated docs are valid"),
+            parse::<T>(&target, "target").expect("generated docs are valid"),
+        );
+
+        let (same, diff) = everything().apply(|| (a.diff(&a), a.diff(&b)));
+        let (same, diff) = with_old_values(true, || {
+            (
+                serde_json::to_value(&same).expect("diffs are serializable"),
+                serde_json::to_value(&diff).expect("diffs are serializable"),
+            )
+        });
+
+        assert_eq!(
+            changed_items(&same),
+            0,
+            "seed {seed}: doc differs from itself"
+        );
+
+        let mut out = Vec::new();
+        let streamed = stage
+            .stream(&source, &target, everything(), false, &mut out)
+            .expect("generated docs can be streamed");
+        assert_eq!(
+            streamed,
+            changed_items(&diff),
+            "seed {seed}: streamed and full diff disagree"
+        );
+
+        check_inversion(&format!("seed {seed}"), &a, &b, unordered_lists);
+    }
+}
+
+/// Checks the inversion between each pair of golden docs of `stage`.
+fn check_golden_inversions<T>(stage: Docs)
+where
+    T: Doc + serde::de::DeserializeOwned,
+    T::Diff: serde::Serialize + serde::de::DeserializeOwned + Invert,
+{
+    for (source, target) in [("1.1.100", "1.1.110"), ("1.1.110", "2.0.8")] {
+        let (a, b) = (
+            parse::<T>(&common::raw(source, stage), "source").expect("fixtures are valid"),
+            parse::<T>(&common::raw(target, stage), "target").expect("fixtures are valid"),
+        );
+
+        check_inversion(&format!("{stage} {source} -> {target}"), &a, &b, |_| {});
+    }
+}
+
+#[test]
+fn runtime_round_trip() {
+    round_trips::<RuntimeDoc>(runtime_doc);
+}
+
+#[test]
+fn prototype_round_trip() {
+    round_trips::<PrototypeDoc>(prototype_doc);
+}
+
+#[test]
+fn runtime_diffs() {
+    check_diffs::<RuntimeDoc>(Docs::Runtime, runtime_doc);
+}
+
+#[test]
+fn prototype_diffs() {
+    check_diffs::<PrototypeDoc>(Docs::Prototype, prototype_doc);
+}
+
+#[test]
+fn runtime_golden_inversions() {
+    check_golden_inversions::<RuntimeDoc>(Docs::Runtime);
+}
+
+#[test]
+fn prototype_golden_inversions() {
+    check_golden_inversions::<PrototypeDoc>(Docs::Prototype);
+}
+
+#[test]
+fn diffs_without_old_values_are_detected() {
+    let (a, b) = (
+        parse::<RuntimeDoc>(&common::raw("1.1.110", Docs::Runtime), "source")
+            .expect("fixtures are valid"),
+        parse::<RuntimeDoc>(&common::raw("2.0.8", Docs::Runtime), "target")
+            .expect("fixtures are valid"),
+    );
+    let diff = everything().apply(|| a.diff(&b));
+
+    for with_old in [false, true] {
+        let raw = with_old_values(with_old, || serde_json::to_vec(&diff))
+            .expect("diffs are serializable");
+        let (_, missing_old) = missing_old_values(|| {
+            parse::<<RuntimeDoc as Doc>::Diff>(&raw, "diff").expect("diffs can be deserialized")
+        });
+
+        assert_eq!(missing_old, !with_old, "with old values: {with_old}");
+    }
+}
@@ -0,0 +1,98 @@
+//! The Python wrapper in `python/fapi_diff` calling the C ABI of the cdylib.
+
+#![cfg(feature = "python")]
+#![allow(clippy::expect_used)]
+
+use std::{path::PathBuf, process::Command};
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions},
+    render::{JsonStyle, OutputFormat},
+    Comparison, Docs,
+};
+use serde_json::Value;
+
+mod common;
+
+use common::FIXTURES;
+
+/// The cdylib built next to the test binary.
+fn library() -> PathBuf {
+    let deps = std::env::current_exe()
+        .expect("test binary has a path")
+        .parent()
+        .expect("test binary is in a directory")
+        .to_path_buf();
+
+    deps.join(format!(
+        "{}fapi_diff{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    ))
+}
+
+/// Runs the Python `script` with the wrapper importable, `None` if there's no Python.
+fn python(script: &str) -> Option<std::process::Output> {
+    let output = Command::new("python3")
+        .args(["-c", script])
+        .env("PYTHONPATH", concat!(env!("CARGO_MANIFEST_DIR"), "/python"))
+        .env("FAPI_DIFF_LIB", library())
+        .env("FIXTURES", FIXTURES)
+        .output();
+
+    match output {
+        Ok(output) => Some(output),
+        Err(e) => {
+            eprintln!("skipping the Python wrapper test, python3 can't be run: {e}");
+            None
+        }
+    }
+}
+
+#[test]
+fn runtime_round_trip() {
+    let Some(output) = python(
+        r#"
+import json, os
+import fapi_diff
+
+def doc(version):
+    with open(f"{os.environ['FIXTURES']}/{version}/doc-html/runtime-api.json", "rb") as f:
+        return f.read()
+
+source, target = doc("1.1.100"), doc("1.1.110")
+# dicts and raw JSON are both accepted
+flat = fapi_diff.diff_runtime(json.loads(source), target, format="flat")
+
+try:
+    fapi_diff.diff_prototype(source, target)
+    error = None
+except fapi_diff.FapiDiffError as e:
+    error = str(e)
+
+print(json.dumps({"flat": flat, "error": error}))
+"#,
+    ) else {
+        return;
+    };
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let res = serde_json::from_slice::<Value>(&output.stdout).expect("output is JSON");
+
+    let raw = |version: &str| common::raw(version, Docs::Runtime);
+    let expected =
+        Comparison::<RuntimeDoc>::new(&raw("1.1.100"), &raw("1.1.110"), DiffOptions::default())
+            .expect("docs are diffed")
+            .render(OutputFormat::Flat, false, JsonStyle::default())
+            .expect("diff renders");
+
+    assert_eq!(
+        res["flat"],
+        serde_json::from_str::<Value>(&expected).expect("diff is JSON")
+    );
+    assert_eq!(res["error"], "Source is a runtime doc, not a prototype doc");
+}
@@ -0,0 +1,64 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{query::query, Error};
+use serde_json::json;
+
+mod common;
+
+use common::FIXTURES;
+
+fn doc(file: &str) -> Vec<u8> {
+    std::fs::read(format!("{FIXTURES}/{file}")).expect("failed to read fixture")
+}
+
+#[test]
+fn method_by_name() {
+    let method = query(
+        &doc("2.0.8/doc-html/runtime-api.json"),
+        "classes.LuaEntity.methods.teleport",
+        "doc",
+    )
+    .expect("query failed");
+
+    assert_eq!(method["name"], json!("teleport"));
+    assert_eq!(
+        method["description"],
+        json!("Teleports the entity to a given position.")
+    );
+}
+
+#[test]
+fn nested_define_values() {
+    let value = query(
+        &doc("2.0.8/doc-html/runtime-api.json"),
+        "defines.events.on_tick",
+        "doc",
+    )
+    .expect("query failed");
+
+    assert_eq!(value, json!({"name": "on_tick", "order": 0}));
+}
+
+#[test]
+fn wildcard_keys_by_name() {
+    let names = query(
+        &doc("2.0.8/doc-html/runtime-api.json"),
+        "classes.*.methods.die.name",
+        "doc",
+    )
+    .expect("query failed");
+
+    assert_eq!(names, json!({"LuaEntity": "die"}));
+}
+
+#[test]
+fn unknown_segment() {
+    let err = query(
+        &doc("2.0.8/doc-html/runtime-api.json"),
+        "classes.LuaEntity.methods.fly",
+        "doc",
+    )
+    .expect_err("fly isn't a method");
+
+    assert!(matches!(err, Error::UnknownPath { segment, .. } if segment == "fly"));
+}
@@ -0,0 +1,91 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions, Doc, FieldPolicy},
+    Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+/// The raw 1.1.110 runtime doc and a copy where `LuaEntity::die` raises two more events,
+/// one of which changed in the same release.
+fn docs() -> (Vec<u8>, Vec<u8>) {
+    let raise = |name: &str| json!({"name": name, "order": 1, "description": "", "timeframe": "instantly", "optional": true});
+    let target = common::edited("1.1.110", Docs::Runtime, |doc| {
+        let die = common::member(doc, "LuaEntity", "methods", "die");
+        let raises = die["raises"].as_array_mut().expect("raises are a list");
+        raises.push(raise("on_built_entity"));
+        raises.push(raise("script_raised_destroy"));
+
+        common::find(doc, "events", "on_built_entity")["description"] =
+            json!("Called when a player builds something.");
+    });
+
+    (common::raw("1.1.110", Docs::Runtime), target)
+}
+
+/// Event descriptions are only diffed with all field categories.
+fn options() -> DiffOptions {
+    DiffOptions {
+        policy: FieldPolicy::all(),
+        ..Default::default()
+    }
+}
+
+fn raised_events(method: &Value) -> &Value {
+    method
+        .as_array()
+        .expect("method diff is a list of changes")
+        .iter()
+        .find_map(|c| c.get("raised_events"))
+        .expect("raised events are linked")
+}
+
+fn expected() -> Value {
+    json!([
+        {"event": "on_built_entity", "status": "changed"},
+        {"event": "script_raised_destroy", "status": "undocumented"},
+    ])
+}
+
+#[test]
+fn raised_events_are_linked() {
+    let (source, target) = docs();
+    let parse = |raw: &[u8]| serde_json::from_slice::<RuntimeDoc>(raw).expect("doc is valid");
+
+    let diff = options().apply(|| parse(&source).diff(&parse(&target)));
+    let diff = serde_json::to_value(diff).expect("diff is serializable");
+    let methods = diff["classes"]["LuaEntity"]
+        .as_array()
+        .expect("class diff is a list of changes")
+        .iter()
+        .find_map(|c| c.get("methods"))
+        .expect("methods changed");
+
+    assert_eq!(raised_events(&methods["die"]), &expected());
+}
+
+#[test]
+fn raised_events_are_linked_while_streaming() {
+    let (source, target) = docs();
+    let mut out = Vec::new();
+    Docs::Runtime
+        .stream(&source, &target, options(), false, &mut out)
+        .expect("docs are diffed");
+
+    let entry = String::from_utf8(out)
+        .expect("output is UTF-8")
+        .lines()
+        .map(|l| serde_json::from_str::<Value>(l).expect("entry is JSON"))
+        .find(|e| e["name"] == "LuaEntity")
+        .expect("LuaEntity changed");
+    let methods = entry["changes"]
+        .as_array()
+        .expect("class diff is a list of changes")
+        .iter()
+        .find_map(|c| c.get("methods"))
+        .expect("methods changed");
+
+    assert_eq!(raised_events(&methods["die"]), &expected());
+}
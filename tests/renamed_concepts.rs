@@ -0,0 +1,89 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, Doc},
+    Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+/// The 1.1.110 runtime doc and a copy where `BoundingBox` is renamed to `BoundingArea`
+/// with a note in the description of the renamed concept.
+fn docs() -> (RuntimeDoc, RuntimeDoc) {
+    let source = common::json("1.1.110", Docs::Runtime);
+    let mut target = source.clone();
+
+    let concept = common::find(&mut target, "concepts", "BoundingBox");
+    concept["name"] = json!("BoundingArea");
+    concept["description"] =
+        json!("Two positions. Renamed from [BoundingBox](runtime:BoundingBox).");
+
+    let doc = |v| serde_json::from_value(v).expect("fixture is a runtime doc");
+    (doc(source), doc(target))
+}
+
+#[test]
+fn renamed_concepts_are_diffed_against_their_old_version() {
+    let (source, target) = docs();
+    let diff = serde_json::to_value(source.diff(&target)).expect("diff is serializable");
+
+    assert_eq!(
+        diff["renamed"],
+        json!({"concepts": {"BoundingBox": "BoundingArea"}})
+    );
+    assert_eq!(diff["added"]["concepts"], json!([]));
+    assert_eq!(diff["removed"]["concepts"], json!([]));
+    assert!(diff["concepts"].get("BoundingBox").is_none());
+
+    let changes = fapi_diff::render::flat::flatten(
+        &diff,
+        &serde_json::to_value(&source).expect("doc is serializable"),
+        &serde_json::to_value(&target).expect("doc is serializable"),
+    )
+    .into_iter()
+    .map(|c| (c.path, c.old, c.new))
+    .collect::<Vec<_>>();
+    assert!(changes.contains(&(
+        "concepts/BoundingArea/name".to_owned(),
+        json!("BoundingBox"),
+        json!("BoundingArea"),
+    )));
+    assert!(changes
+        .iter()
+        .all(|(path, ..)| path.starts_with("concepts/BoundingArea/")));
+}
+
+#[test]
+fn renamed_concepts_are_patched_in_place() {
+    let (source, target) = docs();
+    let patch =
+        fapi_diff::Comparison::from_docs(source, target, fapi_diff::format::DiffOptions::default())
+            .render(
+                fapi_diff::render::OutputFormat::JsonPatch,
+                false,
+                fapi_diff::render::JsonStyle::default(),
+            )
+            .expect("diff is rendered");
+    let patch: Value = serde_json::from_str(&patch).expect("patch is JSON");
+
+    assert!(patch
+        .as_array()
+        .expect("patch is a list of operations")
+        .iter()
+        .any(|op| op["op"] == "replace"
+            && op["path"].as_str().is_some_and(|p| p.ends_with("/name"))
+            && op["value"] == "BoundingArea"));
+}
+
+#[test]
+fn an_empty_marker_keeps_renamed_concepts_apart() {
+    let (source, target) = docs();
+    let diff = fapi_diff::format::with_rename_marker("", || source.diff(&target))
+        .expect("marker is valid");
+    let diff = serde_json::to_value(diff).expect("diff is serializable");
+
+    assert!(diff.get("renamed").is_none());
+    assert_eq!(diff["added"]["concepts"], json!(["BoundingArea"]));
+    assert_eq!(diff["removed"]["concepts"], json!(["BoundingBox"]));
+}
@@ -0,0 +1,132 @@
+//! Byte-identical output of `--reproducible` runs.
+
+#![allow(clippy::expect_used)]
+
+use std::process::Command;
+
+use fapi_diff::render::{self, JsonStyle};
+use fapi_diff::Docs;
+use serde_json::{json, Value};
+
+mod common;
+
+use common::FIXTURES;
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .args(args)
+        .output()
+        .expect("failed to run fapi-diff");
+    assert!(
+        output.status.success(),
+        "fapi-diff failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+fn compare(stage: Docs, format: &str) -> Vec<u8> {
+    run(&[
+        "compare",
+        &stage.to_string(),
+        &common::path("1.1.110", stage),
+        &common::path("2.0.8", stage),
+        "--format",
+        format,
+        "--with-old",
+        "--reproducible",
+    ])
+}
+
+#[test]
+fn runs_are_byte_identical() {
+    for stage in [Docs::Runtime, Docs::Prototype] {
+        for format in [
+            "json",
+            "flat",
+            "markdown",
+            "bbcode",
+            "ndjson",
+            "csv",
+            "json-patch",
+        ] {
+            let first = compare(stage, format);
+            assert!(!first.is_empty(), "{stage} {format}");
+            assert_eq!(first, compare(stage, format), "{stage} {format}");
+        }
+    }
+}
+
+#[test]
+fn json_round_trips() {
+    let style = JsonStyle {
+        reproducible: true,
+        ..Default::default()
+    };
+
+    for format in ["json", "flat"] {
+        let out = String::from_utf8(compare(Docs::Prototype, format)).expect("output is UTF-8");
+        let value = serde_json::from_str::<Value>(&out).expect("output is JSON");
+        assert_eq!(
+            style.to_string(&value).expect("value serializes") + "\n",
+            out,
+            "{format}"
+        );
+    }
+
+    let style = JsonStyle {
+        pretty: false,
+        ..style
+    };
+    let out = String::from_utf8(compare(Docs::Runtime, "ndjson")).expect("output is UTF-8");
+    for line in out.lines() {
+        let value = serde_json::from_str::<Value>(line).expect("line is JSON");
+        assert_eq!(style.to_string(&value).expect("value serializes"), line);
+    }
+}
+
+#[test]
+fn floats_are_normalized() {
+    let mut value = json!({"b": [0.1 + 0.2, -0.0, 2.5e-7], "a": 3, "c": 1.0});
+    render::normalize(&mut value);
+    assert_eq!(value, json!({"a": 3, "b": [0.3, 0.0, 0.0], "c": 1.0}));
+
+    let style = JsonStyle {
+        pretty: false,
+        reproducible: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        style.to_string(&value).expect("value serializes"),
+        r#"{"a":3,"b":[0.3,0.0,0.0],"c":1.0}"#
+    );
+}
+
+#[test]
+fn feed_without_modification_times() {
+    let dir = std::env::temp_dir().join(format!("fapi-diff-reproducible-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("failed to create the temp dir");
+    let out = dir.join("feed.xml");
+
+    let mut feeds = (0..2).map(|_| {
+        run(&[
+            "feed",
+            "--docs-dir",
+            FIXTURES,
+            "--out",
+            out.to_str().expect("temp dir is UTF-8"),
+            "--reproducible",
+        ]);
+        std::fs::read_to_string(&out).expect("feed is written")
+    });
+    let (first, second) = (feeds.next(), feeds.next());
+    std::fs::remove_dir_all(&dir).ok();
+
+    let first = first.expect("feed is written");
+    assert_eq!(Some(&first), second.as_ref());
+    assert!(
+        first.contains("<updated>1970-01-01T00:00:00Z</updated>"),
+        "{first}"
+    );
+}
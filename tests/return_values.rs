@@ -0,0 +1,69 @@
+//! Return values are matched by their description and type, so reordering them isn't a type change.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions, FieldPolicy},
+    Comparison, Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+/// The doc with the return values of `LuaEntity.die` changed by `f`.
+fn with_die(f: impl FnOnce(&mut Vec<Value>)) -> Vec<u8> {
+    common::edited("2.0.8", Docs::Runtime, |doc| {
+        f(
+            common::member(doc, "LuaEntity", "methods", "die")["return_values"]
+                .as_array_mut()
+                .expect("die has return values"),
+        );
+    })
+}
+
+/// Changes of the return values of `LuaEntity.die`.
+fn changes(target: &[u8]) -> Value {
+    let c = Comparison::<RuntimeDoc>::new(
+        &common::raw("2.0.8", Docs::Runtime),
+        target,
+        DiffOptions {
+            policy: FieldPolicy::all(),
+            ..Default::default()
+        },
+    )
+    .expect("docs are diffed");
+
+    let diff = serde_json::to_value(&c.diff.classes).expect("diff serializes");
+    diff.pointer("/LuaEntity")
+        .and_then(Value::as_array)
+        .and_then(|d| d.iter().find_map(|c| c.pointer("/methods/die")))
+        .and_then(Value::as_array)
+        .and_then(|d| d.iter().find_map(|c| c.get("return_values")))
+        .cloned()
+        .unwrap_or_default()
+}
+
+#[test]
+fn swapped() {
+    let target = with_die(|values| {
+        values.reverse();
+        // the docs renumber the order, it always matches the position
+        for (i, value) in values.iter_mut().enumerate() {
+            value["order"] = json!(i);
+        }
+    });
+
+    let changes = changes(&target);
+    assert!(!changes.is_null(), "the order changed");
+    assert!(
+        !changes.to_string().contains("\"type\""),
+        "swapped return values changed types: {changes}"
+    );
+}
+
+#[test]
+fn type_changes_are_kept() {
+    let target = with_die(|values| values[1]["type"] = json!("LuaPlayer"));
+
+    assert_eq!(changes(&target), json!([[], [{"type": "LuaPlayer"}]]));
+}
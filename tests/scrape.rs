@@ -0,0 +1,141 @@
+//! Runtime docs scraped from the HTML pages of versions before the machine-readable docs.
+
+#![cfg(feature = "scrape")]
+#![allow(clippy::expect_used)]
+
+use std::path::Path;
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions},
+    scrape, Comparison, Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+fn scraped() -> Value {
+    let dir = Path::new(FIXTURES).join("scrape/1.0.0/doc-html");
+    assert!(scrape::is_html_docs(&dir));
+
+    serde_json::from_slice(&scrape::runtime(&dir).expect("pages are scraped")).expect("doc is JSON")
+}
+
+fn item<'a>(list: &'a Value, name: &str) -> &'a Value {
+    list.as_array()
+        .and_then(|l| l.iter().find(|i| i["name"] == name))
+        .expect("item exists")
+}
+
+#[test]
+fn header() {
+    let doc = scraped();
+
+    assert_eq!(doc["application_version"], json!("1.0.0"));
+    assert_eq!(doc["stage"], json!("runtime"));
+    assert_eq!(doc["api_version"], json!(5));
+}
+
+#[test]
+fn classes() {
+    let doc = scraped();
+
+    // classes without a page are left out
+    let names = doc["classes"]
+        .as_array()
+        .expect("classes are a list")
+        .iter()
+        .map(|c| c["name"].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(names, [json!("LuaControl"), json!("LuaEntity")]);
+
+    let entity = item(&doc["classes"], "LuaEntity");
+    assert_eq!(entity["parent"], json!("LuaControl"));
+
+    let direction = item(&entity["attributes"], "direction");
+    assert_eq!(direction["type"], json!("defines.direction"));
+    assert_eq!(direction["read"], json!(true));
+    assert_eq!(direction["write"], json!(true));
+    assert_eq!(item(&entity["attributes"], "name")["write"], json!(false));
+
+    let rails = item(&entity["methods"], "get_connected_rails");
+    assert_eq!(
+        rails["return_values"][0]["type"],
+        json!({"complex_type": "array", "value": "LuaEntity"})
+    );
+
+    let set_recipe = item(&entity["methods"], "set_recipe");
+    assert_eq!(set_recipe["format"]["takes_table"], json!(true));
+    assert_eq!(
+        set_recipe["parameters"],
+        json!([
+            {
+                "name": "recipe",
+                "order": 0,
+                "description": "The new recipe.",
+                "type": {"complex_type": "union", "options": ["string", "LuaRecipe"], "full_format": false},
+                "optional": false,
+            },
+            {
+                "name": "quality",
+                "order": 1,
+                "description": "Quality of the recipe & its results.",
+                "type": "string",
+                "optional": true,
+            },
+        ])
+    );
+
+    let control = item(&doc["classes"], "LuaControl");
+    let get_inventory = item(&control["methods"], "get_inventory");
+    assert_eq!(
+        get_inventory["description"],
+        json!("Get an inventory belonging to this entity.")
+    );
+    assert_eq!(
+        get_inventory["parameters"][0]["type"],
+        json!("defines.inventory")
+    );
+    assert_eq!(
+        get_inventory["return_values"][0]["description"],
+        json!("The inventory or nil if none with the given index was found.")
+    );
+}
+
+#[test]
+fn events_and_defines() {
+    let doc = scraped();
+
+    let built = item(&doc["events"], "on_built_entity");
+    assert_eq!(
+        built["description"],
+        json!("Called when player builds something.")
+    );
+    assert_eq!(
+        item(&built["data"], "tags")["type"],
+        json!({"complex_type": "dictionary", "key": "string", "value": "Any"})
+    );
+    assert_eq!(item(&built["data"], "tags")["optional"], json!(true));
+
+    let inventory = item(&doc["defines"], "inventory");
+    assert_eq!(inventory["values"][1]["name"], json!("chest"));
+
+    let acceleration = item(&item(&doc["defines"], "riding")["subkeys"], "acceleration");
+    assert_eq!(
+        acceleration["values"][1],
+        json!({"name": "accelerating", "order": 1, "description": "Accelerating forward."})
+    );
+}
+
+#[test]
+fn diffs_against_json_docs() {
+    let scraped = serde_json::to_vec(&scraped()).expect("doc is serializable");
+    let target = common::raw("1.1.110", Docs::Runtime);
+
+    let c = Comparison::<RuntimeDoc>::new(&scraped, &target, DiffOptions::default())
+        .expect("scraped docs can be diffed");
+    let diff = serde_json::to_value(&c.diff).expect("diff is serializable");
+
+    assert!(diff["classes"]["LuaEntity"].is_array());
+}
@@ -0,0 +1,64 @@
+//! Docs diffed against themselves by `self-check`.
+
+#![allow(clippy::expect_used)]
+
+use std::process::Command;
+
+use fapi_diff::{self_check::SelfCheck, Docs};
+use serde_json::{json, Value};
+
+mod common;
+
+#[test]
+fn fixtures_pass() {
+    for version in ["1.1.100", "1.1.110", "2.0.8"] {
+        for stage in [Docs::Runtime, Docs::Prototype] {
+            let raw = common::raw(version, stage);
+
+            let check = SelfCheck::run(&raw).expect("doc is checked");
+            assert!(check.passed(), "{version} {stage}: {check}");
+            assert_eq!(check.to_string(), "");
+        }
+    }
+}
+
+#[test]
+fn problems_fail() {
+    let check = SelfCheck {
+        changes: vec!["classes/LuaEntity/methods/teleport".to_owned()],
+        streamed: 1,
+        unstable: true,
+    };
+
+    assert!(!check.passed());
+    assert_eq!(
+        check.to_string(),
+        "parsing the doc twice gave different docs\n\
+         changed: classes/LuaEntity/methods/teleport\n\
+         1 items changed in the streamed diff\n"
+    );
+}
+
+#[test]
+fn command() {
+    let run = |path: &str, json: bool| {
+        Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+            .arg("self-check")
+            .arg(path)
+            .args(json.then_some("--json"))
+            .output()
+            .expect("failed to run fapi-diff")
+    };
+
+    let output = run(&common::path("2.0.8", Docs::Runtime), true);
+    assert!(output.status.success());
+    let check = serde_json::from_slice::<Value>(&output.stdout).expect("output is JSON");
+    assert_eq!(
+        check,
+        json!({"changes": [], "streamed": 0, "unstable": false})
+    );
+
+    // docs that can't be read fail
+    let output = run(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"), false);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,103 @@
+//! Diffs split into one file per changed item.
+
+#![allow(clippy::expect_used)]
+
+use std::path::PathBuf;
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions},
+    render::{JsonStyle, OutputFormat},
+    split::INDEX,
+    Comparison, Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+fn comparison() -> Comparison<RuntimeDoc> {
+    Comparison::new(
+        &common::raw("1.1.110", Docs::Runtime),
+        &common::raw("2.0.8", Docs::Runtime),
+        DiffOptions::default(),
+    )
+    .expect("fixtures are docs")
+}
+
+fn out_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("fapi-diff-split-{name}-{}", std::process::id()))
+}
+
+fn read_json(path: PathBuf) -> Value {
+    serde_json::from_slice(&std::fs::read(path).expect("file was written")).expect("file is JSON")
+}
+
+#[test]
+fn json_file_per_item() {
+    let dir = out_dir("json");
+    let c = comparison();
+    let index = c
+        .write_split(
+            &dir,
+            OutputFormat::Json,
+            DiffOptions::default(),
+            false,
+            JsonStyle::default(),
+        )
+        .expect("split diff is written");
+
+    let entity = read_json(dir.join("classes/LuaEntity.json"));
+    let written_index = read_json(dir.join(INDEX));
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(entity["kind"], json!("class"));
+    assert_eq!(entity["name"], json!("LuaEntity"));
+    assert_eq!(entity["status"], json!("changed"));
+    assert!(entity["changes"].is_array());
+
+    assert_eq!(written_index, serde_json::to_value(&index).expect("index"));
+    assert!(index
+        .iter()
+        .any(|e| e.name == "on_built_entity" && e.file == "events/on_built_entity.json"));
+}
+
+#[test]
+fn markdown_file_per_item() {
+    let dir = out_dir("markdown");
+    comparison()
+        .write_split(
+            &dir,
+            OutputFormat::Markdown,
+            DiffOptions::default(),
+            false,
+            JsonStyle::default(),
+        )
+        .expect("split diff is written");
+
+    let entity = std::fs::read_to_string(dir.join("classes/LuaEntity.md")).expect("file written");
+    let index = read_json(dir.join(INDEX));
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(entity.starts_with("## classes\n\n### `LuaEntity`\n"));
+    // only the changes of the item itself
+    assert!(!entity.contains("LuaSurface"));
+    assert!(index
+        .as_array()
+        .expect("index is a list")
+        .iter()
+        .any(|e| e["file"] == json!("classes/LuaEntity.md")));
+}
+
+#[test]
+fn other_formats_are_rejected() {
+    let dir = out_dir("csv");
+    let res = comparison().write_split(
+        &dir,
+        OutputFormat::Csv,
+        DiffOptions::default(),
+        false,
+        JsonStyle::default(),
+    );
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(res.is_err());
+}
@@ -0,0 +1,47 @@
+#![allow(clippy::expect_used)]
+
+use std::process::Command;
+
+use serde_json::{json, Value};
+
+mod common;
+
+use common::FIXTURES;
+
+fn stats(file: &str) -> Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .arg("stats")
+        .arg(format!("{FIXTURES}/{file}"))
+        .arg("--json")
+        .output()
+        .expect("failed to run fapi-diff");
+
+    assert!(
+        output.status.success(),
+        "fapi-diff failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    serde_json::from_slice(&output.stdout).expect("stats are valid JSON")
+}
+
+#[test]
+fn runtime_stats() {
+    let stats = stats("2.0.8/doc-html/runtime-api.json");
+
+    assert_eq!(stats["stage"], json!("runtime"));
+    assert_eq!(stats["counts"]["classes"], json!(4));
+    assert_eq!(stats["counts"]["methods"], json!(9));
+    assert_eq!(stats["counts"]["define_values"], json!(7));
+    assert_eq!(stats["derived"]["methods_per_class"], json!(2.25));
+}
+
+#[test]
+fn prototype_stats() {
+    let stats = stats("2.0.8/doc-html/prototype-api.json");
+
+    assert_eq!(stats["stage"], json!("prototype"));
+    assert_eq!(stats["counts"]["prototypes"], json!(4));
+    assert_eq!(stats["counts"]["properties"], json!(12));
+    assert_eq!(stats["derived"]["properties_per_prototype"], json!(3.0));
+}
@@ -0,0 +1,83 @@
+//! Prototype types that became or stopped being structs report their properties as a whole.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{prototype::PrototypeDoc, Doc, Invert},
+    Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+fn docs() -> (PrototypeDoc, PrototypeDoc) {
+    let source = common::json("2.0.8", Docs::Prototype);
+    let mut target = source.clone();
+    let file_name = common::find(&mut target, "types", "FileName");
+    file_name["type"] = json!({"complex_type": "struct"});
+    file_name["properties"] = json!([{
+        "name": "path",
+        "order": 0,
+        "description": "",
+        "override": false,
+        "type": "string",
+        "optional": false,
+    }]);
+
+    (
+        serde_json::from_value(source).expect("source is a prototype doc"),
+        serde_json::from_value(target).expect("target is a prototype doc"),
+    )
+}
+
+fn file_name(diff: &Value) -> &Value {
+    &diff["types"]["FileName"]
+}
+
+#[test]
+fn gained_structure() {
+    let (source, target) = docs();
+    let diff = serde_json::to_value(source.diff(&target)).expect("diff is serializable");
+
+    let changes = file_name(&diff).as_array().expect("FileName changed");
+    assert!(changes.iter().all(|c| c.get("properties").is_none()));
+
+    let structure = changes
+        .iter()
+        .find_map(|c| c.get("structure"))
+        .expect("FileName gained structure");
+    assert_eq!(structure["gained"][0]["name"], json!("path"));
+
+    let flat = fapi_diff::render::flat::flatten(
+        &diff,
+        &serde_json::to_value(&source).expect("doc is serializable"),
+        &serde_json::to_value(&target).expect("doc is serializable"),
+    );
+    let properties = flat
+        .iter()
+        .find(|c| c.path == "types/FileName/properties")
+        .expect("properties are a flat change");
+    assert_eq!(properties.old, Value::Null);
+    assert_eq!(properties.new["path"]["type"], json!("string"));
+}
+
+#[test]
+fn lost_structure_is_inverted_gain() {
+    let (source, target) = docs();
+
+    let lost = serde_json::to_value(target.diff(&source)).expect("diff is serializable");
+    let inverted =
+        serde_json::to_value(source.diff(&target).invert()).expect("diff is serializable");
+
+    let structure = |diff: &Value| {
+        file_name(diff)
+            .as_array()
+            .and_then(|c| c.iter().find_map(|c| c.get("structure")))
+            .cloned()
+    };
+    assert_eq!(structure(&lost), structure(&inverted));
+    assert_eq!(
+        structure(&lost).expect("FileName lost structure")["lost"][0]["name"],
+        json!("path")
+    );
+}
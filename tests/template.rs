@@ -0,0 +1,124 @@
+//! Changelogs laid out by user templates.
+
+#![cfg(feature = "templates")]
+#![allow(clippy::expect_used)]
+
+use std::process::Command;
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions},
+    render::template::{self, Template},
+    Comparison, Docs,
+};
+use serde_json::json;
+
+mod common;
+
+fn render(source: &str, context: &serde_json::Value) -> String {
+    Template::parse(source)
+        .expect("template is valid")
+        .render(context)
+}
+
+#[test]
+fn syntax() {
+    let context = json!({
+        "title": "Changes",
+        "items": [
+            {"name": "LuaEntity", "status": "changed", "tags": ["a", "b"]},
+            {"name": "LuaControl", "status": "added", "tags": []},
+        ],
+    });
+
+    assert_eq!(
+        render(
+            "# {{ title | upper }}\n\
+             {%- for item in items %}\n\
+             {{ loop.index }}/{{ loop.length }} {{ item.name }}\
+             {% if item.status == \"added\" %} (new){% elif not item.tags %}!{% else %} [{{ item.tags | join(\", \") }}]{% endif %}\
+             {%- endfor %}\n\
+             {# left out #}{{ missing | default(\"none\") }} {{ items | length }} {{ items.0.tags | json }}",
+            &context
+        ),
+        "# CHANGES\n1/2 LuaEntity [a, b]\n2/2 LuaControl (new)\nnone 2 [\"a\",\"b\"]"
+    );
+}
+
+#[test]
+fn syntax_errors_have_lines() {
+    let error = |source: &str| {
+        Template::parse(source)
+            .expect_err("template is invalid")
+            .to_string()
+    };
+
+    assert_eq!(
+        error("a\n{% for x in y %}\n{{ x }}"),
+        "line 2: missing `{% endfor %}`"
+    );
+    assert_eq!(
+        error("a\n\n{{ x | shout }}"),
+        "line 3: unknown filter `shout`"
+    );
+    assert_eq!(error("{% endif %}"), "line 1: unexpected `{% endif %}`");
+    assert_eq!(error("{{ x"), "line 1: unclosed `{{`");
+}
+
+#[test]
+fn diff_context() {
+    let raw = |version: &str| common::raw(version, Docs::Runtime);
+    let c = Comparison::<RuntimeDoc>::new(&raw("1.1.100"), &raw("1.1.110"), DiffOptions::default())
+        .expect("docs are diffed");
+
+    let context = template::context(&c.diff, &c.source, &c.target).expect("context is built");
+    assert_eq!(context["header"]["to_version"], "1.1.110");
+    assert_eq!(context["sections"][0]["kind"], "section");
+    assert_eq!(context["sections"][0]["children"][0]["kind"], "item");
+
+    let out = c
+        .render_template(
+            &Template::parse(
+                "{{ header.from_version }} -> {{ header.to_version }}\n\
+                 {%- for section in sections %}{% for item in section.children %}\n\
+                 {{ item.path }}: {{ item.changes | length }}\
+                 {%- endfor %}{% endfor %}\n",
+            )
+            .expect("template is valid"),
+        )
+        .expect("template renders");
+
+    assert!(out.starts_with("1.1.100 -> 1.1.110\n"), "{out}");
+    assert!(out.contains("\nclasses/LuaControl: 1\n"), "{out}");
+}
+
+#[test]
+fn command() {
+    let dir = std::env::temp_dir().join(format!("fapi-diff-template-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("failed to create the temp dir");
+    let path = dir.join("changelog.tera");
+    std::fs::write(
+        &path,
+        "{% for section in sections %}{{ section.name }}\n{% endfor %}",
+    )
+    .expect("failed to write the template");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .args(["compare", "runtime"])
+        .arg(common::path("1.1.100", Docs::Runtime))
+        .arg(common::path("1.1.110", Docs::Runtime))
+        .arg("--template")
+        .arg(&path)
+        .output()
+        .expect("failed to run fapi-diff");
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).expect("stdout is UTF-8"),
+        "classes\ndefines\nevents\n"
+    );
+}
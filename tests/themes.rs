@@ -0,0 +1,145 @@
+//! Changelogs grouped by the themes of the changed items.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions},
+    render::{
+        flat::FlatChange,
+        themes::{Theme, Themes, OTHER},
+        OutputFormat,
+    },
+    Comparison, Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+fn comparison() -> Comparison<RuntimeDoc> {
+    Comparison::new(
+        &common::raw("1.1.110", Docs::Runtime),
+        &common::raw("2.0.8", Docs::Runtime),
+        DiffOptions::default(),
+    )
+    .expect("fixtures are docs")
+}
+
+fn change(path: &str) -> FlatChange {
+    FlatChange {
+        path: path.to_owned(),
+        old: Value::Null,
+        new: json!("added"),
+        url: None,
+    }
+}
+
+#[test]
+fn longest_prefix_wins() {
+    let themes = Themes::default();
+
+    assert_eq!(themes.theme("LuaGuiElement"), "GUI");
+    assert_eq!(themes.theme("on_gui_click"), "GUI");
+    assert_eq!(themes.theme("LuaEntityPrototype"), "Entities");
+    assert_eq!(themes.theme("LuaTrain"), "Trains");
+    assert_eq!(themes.theme("LuaCircuitNetwork"), "Circuits");
+    assert_eq!(themes.theme("LuaBootstrap"), OTHER);
+
+    let themes = Themes(vec![
+        Theme {
+            name: "Lua".to_owned(),
+            prefixes: vec!["lua".to_owned()],
+        },
+        Theme {
+            name: "Forces".to_owned(),
+            prefixes: vec!["LuaForce".to_owned()],
+        },
+    ]);
+    assert_eq!(themes.theme("LuaForce"), "Forces");
+    assert_eq!(themes.theme("LuaSurface"), "Lua");
+}
+
+#[test]
+fn groups_in_theme_order() {
+    let themes = Themes::default();
+    let grouped = themes.group(vec![
+        change("classes/LuaBootstrap/methods/on_init"),
+        change("classes/LuaTrain/attributes/speed"),
+        change("classes/LuaGuiElement/attributes/style"),
+        change("events/on_gui_click"),
+        change("classes/LuaTrain/attributes/state"),
+    ]);
+
+    let themes = grouped
+        .0
+        .iter()
+        .map(|(theme, changes)| {
+            let paths = changes.iter().map(|c| c.path.as_str()).collect::<Vec<_>>();
+            (theme.as_str(), paths)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        themes,
+        [
+            (
+                "GUI",
+                vec![
+                    "classes/LuaGuiElement/attributes/style",
+                    "events/on_gui_click"
+                ]
+            ),
+            (
+                "Trains",
+                vec![
+                    "classes/LuaTrain/attributes/speed",
+                    "classes/LuaTrain/attributes/state"
+                ]
+            ),
+            (OTHER, vec!["classes/LuaBootstrap/methods/on_init"]),
+        ]
+    );
+}
+
+#[test]
+fn markdown_sections_per_theme() {
+    let markdown = comparison()
+        .render_themed(OutputFormat::Markdown, &Themes::default())
+        .expect("diff renders");
+
+    assert!(markdown.starts_with("## Entities\n"));
+    let surfaces = markdown.find("\n## Surfaces\n").expect("surfaces changed");
+    let other = markdown.find("\n## Other\n").expect("other items changed");
+    assert!(surfaces < other);
+    assert!(!markdown.contains("## GUI\n"));
+
+    // sections and items are nested below the themes
+    let surfaces = &markdown[surfaces..other];
+    assert!(surfaces.contains("### classes\n"));
+    assert!(surfaces.contains("#### `LuaSurface`\n"));
+}
+
+#[test]
+fn bbcode_sections_per_theme() {
+    let bbcode = comparison()
+        .render_themed(OutputFormat::BBCode, &Themes::default())
+        .expect("diff renders");
+
+    assert!(bbcode.contains("[size=200][b]Entities[/b][/size]"));
+}
+
+#[test]
+fn themes_from_json() {
+    let themes = serde_json::from_value::<Themes>(
+        json!([{"name": "Platforms", "prefixes": ["LuaSpacePlatform"]}]),
+    )
+    .expect("themes deserialize");
+
+    let markdown = comparison()
+        .render_themed(OutputFormat::Markdown, &themes)
+        .expect("diff renders");
+    assert!(markdown.starts_with("## Platforms\n"));
+    assert!(!markdown.contains("## Entities\n"));
+
+    assert!(comparison()
+        .render_themed(OutputFormat::Json, &themes)
+        .is_err());
+}
@@ -0,0 +1,54 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions, Doc, FieldPolicy, ItemStatus},
+    render::tree::NodeKind,
+    Docs,
+};
+use serde_json::json;
+
+mod common;
+
+fn doc(version: &str) -> RuntimeDoc {
+    serde_json::from_slice(&common::raw(version, Docs::Runtime)).expect("fixture is a runtime doc")
+}
+
+#[test]
+fn changes_are_leaves() {
+    let (source, target) = (doc("1.1.100"), doc("1.1.110"));
+    let options = DiffOptions {
+        policy: FieldPolicy::all(),
+        ..Default::default()
+    };
+    let diff = options.apply(|| source.diff(&target));
+    let tree = fapi_diff::render::tree(&diff, &source, &target).expect("diff is serializable");
+
+    assert_eq!(tree.kind, NodeKind::Root);
+    assert!(tree.changes().count() > 0);
+    assert!(tree.changes().all(|n| n.kind != NodeKind::Section));
+
+    let class = tree
+        .get(&["classes", "LuaEntity"])
+        .expect("LuaEntity changed");
+    assert_eq!(class.kind, NodeKind::Item);
+    assert_eq!(class.status, ItemStatus::Changed);
+
+    let parameter = class
+        .get(&["methods", "teleport", "parameters", "raise_teleported"])
+        .expect("the parameter was added");
+    assert_eq!(
+        parameter.path,
+        [
+            "classes",
+            "LuaEntity",
+            "methods",
+            "teleport",
+            "parameters",
+            "raise_teleported"
+        ]
+    );
+    assert_eq!(parameter.kind, NodeKind::Field);
+    assert_eq!(parameter.status, ItemStatus::Added);
+    assert!(parameter.old.is_null());
+    assert_eq!(parameter.new["type"], json!("boolean"));
+}
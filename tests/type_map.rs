@@ -0,0 +1,144 @@
+//! Type names of changelogs mapped to the types of another language.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions, FieldPolicy},
+    render::{flat::FlatChange, type_map::TypeMapper, JsonStyle, OutputFormat},
+    Comparison, Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+fn comparison() -> Comparison<RuntimeDoc> {
+    let target = common::edited("2.0.8", Docs::Runtime, |doc| {
+        let get_fluid = common::member(doc, "LuaEntity", "methods", "get_fluid");
+        let index = &mut get_fluid["parameters"][0];
+        index["type"] = json!("uint64");
+        index["description"] = json!("A uint32 index.");
+
+        let find_entities = common::member(doc, "LuaSurface", "methods", "find_entities");
+        find_entities["return_values"][0]["type"] = json!({
+            "complex_type": "union",
+            "options": ["LuaEntity", {"complex_type": "literal", "value": "uint32"}],
+            "full_format": false,
+        });
+    });
+
+    let options = DiffOptions {
+        policy: FieldPolicy::all(),
+        ..Default::default()
+    };
+
+    Comparison::new(&common::raw("2.0.8", Docs::Runtime), &target, options)
+        .expect("docs can be diffed")
+}
+
+fn types() -> TypeMapper {
+    serde_json::from_value(json!({"uint32": "number", "uint64": "bigint", "LuaEntity": "Entity"}))
+        .expect("type map is valid")
+}
+
+#[test]
+fn maps_type_names() {
+    let types = types();
+    assert_eq!(types.map("uint32"), "number");
+    assert_eq!(types.map("MapPosition"), "MapPosition");
+
+    let mut ty = json!({
+        "complex_type": "union",
+        "options": [
+            {"complex_type": "array", "value": "LuaEntity"},
+            {"complex_type": "literal", "value": "uint32", "description": "uint32"},
+        ],
+        "full_format": false,
+    });
+    types.map_type(&mut ty);
+    assert_eq!(
+        ty,
+        json!({
+            "complex_type": "union",
+            "options": [
+                {"complex_type": "array", "value": "Entity"},
+                {"complex_type": "literal", "value": "uint32", "description": "uint32"},
+            ],
+            "full_format": false,
+        })
+    );
+}
+
+#[test]
+fn flat_changes() {
+    let c = comparison();
+    let flat = c
+        .render_mapped(OutputFormat::Flat, false, JsonStyle::default(), &types())
+        .expect("diff renders");
+    let flat = serde_json::from_str::<Vec<Value>>(&flat).expect("flat output is JSON");
+    let change = |path: &str| {
+        flat.iter()
+            .find(|c| c["path"] == path)
+            .cloned()
+            .expect("change exists")
+    };
+
+    let index = change("classes/LuaEntity/methods/get_fluid/parameters/index/type");
+    assert_eq!(
+        (&index["old"], &index["new"]),
+        (&json!("number"), &json!("bigint"))
+    );
+
+    // descriptions aren't types
+    let description = change("classes/LuaEntity/methods/get_fluid/parameters/index/description");
+    assert_eq!(description["new"], json!("A uint32 index."));
+
+    // the return value changed as a whole
+    let returned = change("classes/LuaSurface/methods/find_entities/return_values/0");
+    assert_eq!(
+        returned["old"]["type"],
+        json!({"complex_type": "array", "value": "Entity"})
+    );
+    assert_eq!(
+        returned["new"]["type"]["options"],
+        json!(["Entity", {"complex_type": "literal", "value": "uint32"}])
+    );
+}
+
+#[test]
+fn changelog_and_json() {
+    let c = comparison();
+
+    let markdown = c
+        .render_mapped(
+            OutputFormat::Markdown,
+            false,
+            JsonStyle::default(),
+            &types(),
+        )
+        .expect("diff renders");
+    assert!(markdown.contains("`bigint`"), "{markdown}");
+    assert!(!markdown.contains("`uint64`"), "{markdown}");
+
+    // the JSON diff stays true to the docs
+    let json = c
+        .render_mapped(OutputFormat::Json, false, JsonStyle::default(), &types())
+        .expect("diff renders");
+    let unmapped = c
+        .render(OutputFormat::Json, false, JsonStyle::default())
+        .expect("diff renders");
+    assert_eq!(json, unmapped);
+}
+
+#[test]
+fn empty_mapper_keeps_changes() {
+    let change = FlatChange {
+        path: "classes/LuaEntity/attributes/health/read_type".to_owned(),
+        old: json!("float"),
+        new: json!("double"),
+        url: None,
+    };
+
+    let mut changes = vec![change.clone()];
+    TypeMapper::default().apply(&mut changes);
+    assert_eq!(changes, [change]);
+}
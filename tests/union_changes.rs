@@ -0,0 +1,326 @@
+//! Options added to and removed from unions, labeled as widening or narrowing inputs, outputs or both.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{prototype::PrototypeDoc, runtime::RuntimeDoc, DiffOptions},
+    render::flat::{self, FlatChange, Position, UnionChange, UnionChangeKind},
+    Comparison, Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+fn union(options: &[Value]) -> Value {
+    json!({"complex_type": "union", "options": options, "full_format": false})
+}
+
+fn literal(value: &str) -> Value {
+    json!({"complex_type": "literal", "value": value})
+}
+
+fn doc(property: &Value, type_: &Value) -> Vec<u8> {
+    common::synthetic(
+        Docs::Prototype,
+        "2.0.8",
+        &json!({
+            "prototypes": [{
+                "name": "UnionPrototype",
+                "order": 0,
+                "description": "",
+                "abstract": false,
+                "deprecated": false,
+                "properties": [{
+                    "name": "mode",
+                    "order": 0,
+                    "description": "",
+                    "override": false,
+                    "type": property,
+                    "optional": false,
+                }],
+            }],
+            "types": [{
+                "name": "UnionType",
+                "order": 0,
+                "description": "",
+                "abstract": false,
+                "inline": false,
+                "type": type_,
+            }],
+        }),
+    )
+}
+
+/// A 1.1 runtime doc whose class members and concept have the given types.
+fn runtime(parameter: &Value, returned: &Value, attribute: &Value, concept: &Value) -> Vec<u8> {
+    let attribute = |name: &str, write: bool| {
+        json!({
+            "name": name,
+            "order": 0,
+            "description": "",
+            "type": attribute,
+            "read": true,
+            "write": write,
+            "optional": false,
+        })
+    };
+
+    common::synthetic(
+        Docs::Runtime,
+        "1.1.110",
+        &json!({
+            "classes": [{
+                "name": "LuaUnion",
+                "order": 0,
+                "description": "",
+                "abstract": false,
+                "methods": [{
+                    "name": "convert",
+                    "order": 0,
+                    "description": "",
+                    "parameters": [{
+                        "name": "value",
+                        "order": 0,
+                        "description": "",
+                        "type": parameter,
+                        "optional": false,
+                    }],
+                    "format": {"takes_table": false},
+                    "return_values": [{"order": 0, "description": "", "type": returned, "optional": false}],
+                }],
+                "attributes": [attribute("mode", true), attribute("state", false)],
+            }],
+            "concepts": [{"name": "UnionConcept", "order": 0, "description": "", "type": concept}],
+        }),
+    )
+}
+
+fn union_changes<T>(source: &[u8], target: &[u8]) -> Vec<UnionChange>
+where
+    T: fapi_diff::format::Doc
+        + std::ops::Deref<Target = fapi_diff::format::Common>
+        + serde::de::DeserializeOwned
+        + serde::Serialize,
+    T::Diff: serde::Serialize,
+{
+    let c = Comparison::<T>::new(source, target, DiffOptions::default()).expect("docs are diffed");
+    let (diff, source, target) = (
+        serde_json::to_value(&c.diff).expect("diff serializes"),
+        serde_json::to_value(&c.source).expect("doc serializes"),
+        serde_json::to_value(&c.target).expect("doc serializes"),
+    );
+
+    flat::union_changes(
+        &flat::flatten(&diff, &source, &target),
+        &diff,
+        &source,
+        &target,
+    )
+}
+
+/// `(path, kind, position, breaking)` of each union change.
+fn labels(changes: &[UnionChange]) -> Vec<(&str, UnionChangeKind, Position, bool)> {
+    changes
+        .iter()
+        .map(|c| (c.path.as_str(), c.kind, c.position, c.is_breaking()))
+        .collect()
+}
+
+#[test]
+fn prototype_types_are_inputs() {
+    let source = doc(
+        &union(&[literal("fast"), literal("slow")]),
+        &union(&[json!("string"), json!("uint32")]),
+    );
+    let target = doc(
+        &union(&[literal("fast"), literal("normal"), literal("slow")]),
+        &union(&[json!("uint32")]),
+    );
+
+    let changes = union_changes::<PrototypeDoc>(&source, &target);
+    assert_eq!(
+        changes,
+        vec![
+            UnionChange {
+                path: "prototypes/UnionPrototype/properties/mode/type".to_owned(),
+                option: literal("normal"),
+                kind: UnionChangeKind::Widened,
+                position: Position::Input,
+            },
+            UnionChange {
+                path: "types/UnionType/type".to_owned(),
+                option: json!("string"),
+                kind: UnionChangeKind::Narrowed,
+                position: Position::Input,
+            },
+        ]
+    );
+    // more accepted values can't break mods, mods that write the removed option break
+    assert!(!changes[0].is_breaking());
+    assert!(changes[1].is_breaking());
+
+    let changes = union_changes::<PrototypeDoc>(&target, &source);
+    assert_eq!(
+        labels(&changes),
+        [
+            (
+                "prototypes/UnionPrototype/properties/mode/type",
+                UnionChangeKind::Narrowed,
+                Position::Input,
+                true
+            ),
+            (
+                "types/UnionType/type",
+                UnionChangeKind::Widened,
+                Position::Input,
+                false
+            ),
+        ]
+    );
+}
+
+#[test]
+fn runtime_positions() {
+    let (small, large) = (
+        union(&[json!("string")]),
+        union(&[json!("string"), json!("uint")]),
+    );
+    let source = runtime(&small, &large, &large, &small);
+    let target = runtime(&large, &small, &small, &large);
+
+    assert_eq!(
+        labels(&union_changes::<RuntimeDoc>(&source, &target)),
+        [
+            (
+                "classes/LuaUnion/methods/convert/parameters/value/type",
+                UnionChangeKind::Widened,
+                Position::Input,
+                false
+            ),
+            (
+                "classes/LuaUnion/methods/convert/return_values/0/type",
+                UnionChangeKind::Narrowed,
+                Position::Output,
+                false
+            ),
+            (
+                "classes/LuaUnion/attributes/mode/type",
+                UnionChangeKind::Narrowed,
+                Position::Both,
+                true
+            ),
+            (
+                "classes/LuaUnion/attributes/state/type",
+                UnionChangeKind::Narrowed,
+                Position::Output,
+                false
+            ),
+            (
+                "concepts/UnionConcept/type",
+                UnionChangeKind::Widened,
+                Position::Both,
+                true
+            ),
+        ]
+    );
+}
+
+#[test]
+fn described_and_reordered_options_are_unchanged() {
+    let source = doc(
+        &union(&[literal("fast"), literal("slow")]),
+        &union(&[json!("string"), json!("uint32")]),
+    );
+    let target = doc(
+        &union(&[literal("slow"), literal("fast")]),
+        &union(&[
+            json!({"complex_type": "type", "value": "string", "description": "The name."}),
+            json!("uint32"),
+        ]),
+    );
+
+    assert_eq!(union_changes::<PrototypeDoc>(&source, &target), Vec::new());
+}
+
+#[test]
+fn positions() {
+    for (path, position) in [
+        (
+            "classes/LuaEntity/methods/teleport/parameters/position/type",
+            Position::Input,
+        ),
+        (
+            "classes/LuaEntity/attributes/color/write_type",
+            Position::Input,
+        ),
+        (
+            "classes/LuaEntity/attributes/color/read_type",
+            Position::Output,
+        ),
+        ("classes/LuaEntity/attributes/color/type", Position::Both),
+        (
+            "classes/LuaEntity/methods/get_inventory/return_values/0/type",
+            Position::Output,
+        ),
+        ("events/on_built_entity/data/entity/type", Position::Output),
+        ("concepts/MapPosition/type", Position::Both),
+        ("types/ItemID/type", Position::Input),
+        (
+            "prototypes/ItemPrototype/properties/stack_size/type",
+            Position::Input,
+        ),
+    ] {
+        assert_eq!(Position::of(path), position, "{path}");
+    }
+}
+
+#[test]
+fn union_paths() {
+    let change = |path: &str, old: Value, new: Value| FlatChange {
+        path: path.to_owned(),
+        old,
+        new,
+        url: None,
+    };
+
+    assert_eq!(
+        change(
+            "concepts/Filter/type/parameters/filter/type/options/3/value",
+            json!("furnace"),
+            json!("lab")
+        )
+        .union()
+        .as_deref(),
+        Some("concepts/Filter/type/parameters/filter/type")
+    );
+    assert_eq!(
+        change(
+            "types/UnionType/type",
+            union(&[json!("string")]),
+            union(&[json!("uint32")])
+        )
+        .union()
+        .as_deref(),
+        Some("types/UnionType/type")
+    );
+    // return values are reported as a whole
+    assert_eq!(
+        change(
+            "classes/LuaEntity/methods/die/return_values/0",
+            json!({"order": 0, "type": union(&[json!("string")])}),
+            json!({"order": 0, "type": union(&[json!("uint32")])})
+        )
+        .union()
+        .as_deref(),
+        Some("classes/LuaEntity/methods/die/return_values/0/type")
+    );
+    assert_eq!(
+        change(
+            "types/UnionType/type",
+            json!("string"),
+            union(&[json!("uint32")])
+        )
+        .union(),
+        None
+    );
+}
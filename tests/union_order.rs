@@ -0,0 +1,105 @@
+//! Union options have no meaningful order, they're diffed and reported in a canonical order.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{prototype::PrototypeDoc, runtime::RuntimeDoc, DiffOptions},
+    Comparison, Docs,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+fn runtime(type_: Option<&Value>) -> Vec<u8> {
+    let concepts = type_
+        .map(|t| vec![json!({"name": "UnionConcept", "order": 0, "description": "", "type": t})])
+        .unwrap_or_default();
+
+    common::synthetic(Docs::Runtime, "2.0.8", &json!({"concepts": concepts}))
+}
+
+fn prototype(type_: &Value) -> Vec<u8> {
+    common::type_doc("UnionType", type_)
+}
+
+fn union(options: &[Value]) -> Value {
+    json!({"complex_type": "union", "options": options, "full_format": false})
+}
+
+fn options(exact_types: bool) -> DiffOptions {
+    DiffOptions {
+        exact_types,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn runtime_reordered() {
+    let array = json!({"complex_type": "array", "value": "string"});
+    let source = union(&[json!("string"), array.clone(), json!("LuaSurface")]);
+    let target = union(&[array, json!("LuaSurface"), json!("string")]);
+
+    let c = Comparison::<RuntimeDoc>::new(
+        &runtime(Some(&source)),
+        &runtime(Some(&target)),
+        options(false),
+    )
+    .expect("docs are valid");
+    assert!(c.diff.concepts.is_empty());
+
+    let c = Comparison::<RuntimeDoc>::new(
+        &runtime(Some(&source)),
+        &runtime(Some(&target)),
+        options(true),
+    )
+    .expect("docs are valid");
+    assert!(c.diff.concepts.contains_key("UnionConcept"));
+}
+
+#[test]
+fn runtime_added_in_canonical_order() {
+    let array = json!({"complex_type": "array", "value": "string"});
+    let orders = [
+        union(&[json!("string"), array.clone(), json!("LuaSurface")]),
+        union(&[array, json!("LuaSurface"), json!("string")]),
+    ];
+
+    let diffs = orders
+        .iter()
+        .map(|target| {
+            let c = Comparison::<RuntimeDoc>::new(
+                &runtime(None),
+                &runtime(Some(target)),
+                options(false),
+            )
+            .expect("docs are valid");
+
+            serde_json::to_value(&c.diff.concepts).expect("diff is serializable")
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(diffs[0], diffs[1]);
+
+    let field = |diff: &Value, key: &str| {
+        diff.as_array()
+            .and_then(|d| d.iter().find_map(|c| c.get(key)))
+            .cloned()
+    };
+    let options = field(&diffs[0]["UnionConcept"], "type")
+        .and_then(|t| field(&t, "options"))
+        .expect("added concept has union options");
+    assert_eq!(options[0], json!("LuaSurface"));
+    assert_eq!(options[1], json!("string"));
+}
+
+#[test]
+fn prototype_reordered() {
+    let source = union(&[json!("string"), json!("uint32")]);
+    let target = union(&[json!("uint32"), json!("string")]);
+
+    let c =
+        Comparison::<PrototypeDoc>::new(&prototype(&source), &prototype(&target), options(false))
+            .expect("docs are valid");
+
+    assert!(c.diff.types.is_empty());
+}
@@ -0,0 +1,100 @@
+//! Complex types this version doesn't know are kept as their raw JSON and diffed as a whole.
+
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{prototype::PrototypeDoc, runtime::RuntimeDoc, DiffOptions},
+    Comparison,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+fn runtime(type_: &Value) -> Vec<u8> {
+    common::concept_doc("FutureConcept", type_)
+}
+
+fn prototype(type_: &Value) -> Vec<u8> {
+    common::type_doc("FutureType", type_)
+}
+
+fn future(bits: u32) -> Value {
+    json!({"complex_type": "bitfield", "bits": bits, "value": "uint8"})
+}
+
+/// All `raw` changes of a serialized diff.
+fn raw_changes(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Object(map) => map
+            .iter()
+            .flat_map(|(k, v)| {
+                if k == "raw" {
+                    vec![v.clone()]
+                } else {
+                    raw_changes(v)
+                }
+            })
+            .collect(),
+        Value::Array(items) => items.iter().flat_map(raw_changes).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[test]
+fn runtime_raw_diff() {
+    let c = Comparison::<RuntimeDoc>::new(
+        &runtime(&future(8)),
+        &runtime(&future(16)),
+        DiffOptions::default(),
+    )
+    .expect("docs with unknown complex types are valid");
+
+    let diff = serde_json::to_value(&c.diff).expect("diff is serializable");
+    assert_eq!(raw_changes(&diff), [future(16)]);
+
+    let warnings = fapi_diff::format::take_warnings();
+    assert!(warnings
+        .iter()
+        .any(|w| w.message == "unknown complex type bitfield"));
+
+    // the unknown type is kept as it is
+    let target = serde_json::to_value(&c.target).expect("doc is serializable");
+    assert_eq!(target["concepts"][0]["type"], future(16));
+}
+
+#[test]
+fn runtime_unchanged() {
+    let c = Comparison::<RuntimeDoc>::new(
+        &runtime(&future(8)),
+        &runtime(&future(8)),
+        DiffOptions::default(),
+    )
+    .expect("docs with unknown complex types are valid");
+
+    assert!(c.diff.concepts.is_empty());
+}
+
+#[test]
+fn prototype_kind_change() {
+    let array = json!({"complex_type": "array", "value": "uint8"});
+    let c = Comparison::<PrototypeDoc>::new(
+        &prototype(&array),
+        &prototype(&future(8)),
+        DiffOptions::default(),
+    )
+    .expect("docs with unknown complex types are valid");
+
+    // a change of the kind is reported as the whole new type
+    let diff = serde_json::to_value(&c.diff).expect("diff is serializable");
+    assert_eq!(diff["types"]["FutureType"], json!([{"type": future(8)}]));
+
+    let flat = fapi_diff::render::flat::flatten(
+        &diff,
+        &serde_json::to_value(&c.source).expect("doc is serializable"),
+        &serde_json::to_value(&c.target).expect("doc is serializable"),
+    );
+    assert_eq!(flat.len(), 1);
+    assert_eq!(flat[0].path, "types/FutureType/type");
+    assert_eq!(flat[0].old, array);
+    assert_eq!(flat[0].new, future(8));
+}
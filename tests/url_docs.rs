@@ -0,0 +1,89 @@
+#![allow(clippy::expect_used)]
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    process::{Command, Output},
+};
+
+use fapi_diff::Docs;
+
+mod common;
+
+/// Serves the runtime doc of `version` at `/runtime-api.json` of the returned base URL.
+fn serve(version: &str) -> String {
+    let doc = common::raw(version, Docs::Runtime);
+    let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+    let addr = listener.local_addr().expect("listener has an address");
+
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = String::new();
+            let mut reader = BufReader::new(&stream);
+            reader.read_line(&mut request).ok();
+            // skip the headers
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                line.clear();
+            }
+
+            let (status, body) = if request.starts_with("GET /runtime-api.json ") {
+                ("200 OK", doc.as_slice())
+            } else {
+                ("404 Not Found", &b""[..])
+            };
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .ok();
+            stream.write_all(body).ok();
+        }
+    });
+
+    format!("http://{addr}")
+}
+
+fn compare(source: &str, target: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fapi-diff"))
+        .args(["compare", "runtime", source, target, "--count-only"])
+        .output()
+        .expect("failed to run fapi-diff")
+}
+
+fn counts(output: &Output) -> serde_json::Value {
+    assert!(
+        output.status.success(),
+        "fapi-diff failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    serde_json::from_slice(&output.stdout).expect("counts are valid JSON")
+}
+
+#[test]
+fn local_file_against_url() {
+    let url = format!("{}/runtime-api.json", serve("2.0.8"));
+    let (source, target) = (
+        common::path("1.1.110", Docs::Runtime),
+        common::path("2.0.8", Docs::Runtime),
+    );
+
+    let expected = counts(&compare(&source, &target));
+    assert_eq!(counts(&compare(&source, &url)), expected);
+    assert_ne!(expected["total"], 0);
+
+    // the file may be the target as well
+    let url = format!("{}/runtime-api.json", serve("1.1.110"));
+    assert_eq!(counts(&compare(&url, &target)), expected);
+}
+
+#[test]
+fn missing_url_fails() {
+    let url = format!("{}/missing.json", serve("2.0.8"));
+    let output = compare(&common::path("2.0.8", Docs::Runtime), &url);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("404"));
+}
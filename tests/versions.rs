@@ -0,0 +1,77 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::versions::{parse_index, Releases, Versions};
+use serde_json::json;
+
+const INDEX: &str = r#"<html><body>
+<a href="/latest/">Latest version</a>
+<ul>
+  <li><a href="/2.0.8/">2.0.8</a></li>
+  <li><a href="/1.1.110/index.html">1.1.110</a></li>
+  <li><a href="1.1.100/">1.1.100</a></li>
+  <li><a href="https://lua-api.factorio.com/2.0.10/">2.0.10</a></li>
+  <li><a href="/2.0.8/classes.html">Classes</a></li>
+</ul>
+</body></html>"#;
+
+fn releases() -> Releases {
+    Releases::parse(
+        json!({
+            "experimental": {"alpha": "2.0.10", "expansion": "2.0.10", "headless": "2.0.10"},
+            "stable": {"alpha": "2.0.8", "expansion": "2.0.8", "headless": "2.0.8"},
+        })
+        .to_string()
+        .as_bytes(),
+    )
+    .expect("releases are valid")
+}
+
+#[test]
+fn index_versions() {
+    assert_eq!(
+        parse_index(INDEX),
+        ["1.1.100", "1.1.110", "2.0.8", "2.0.10"]
+    );
+}
+
+#[test]
+fn latest_releases() {
+    let releases = releases();
+
+    assert_eq!(releases.stable.as_deref(), Some("2.0.8"));
+    assert_eq!(releases.experimental.as_deref(), Some("2.0.10"));
+    assert!(Releases::parse(b"[]").is_err());
+}
+
+#[test]
+fn merged_versions() {
+    let versions = Versions::new(
+        &parse_index(INDEX),
+        &["1.1.110".to_owned(), "2.0.9".to_owned()],
+        releases(),
+    );
+
+    assert_eq!(
+        versions.to_string(),
+        "1.1.100\n\
+         1.1.110 (local)\n\
+         2.0.8 (stable)\n\
+         2.0.9 (unpublished, local)\n\
+         2.0.10 (experimental)\n"
+    );
+    assert_eq!(versions.pair(), Some(("2.0.8", "2.0.10")));
+
+    let json = serde_json::to_value(&versions).expect("versions serialize");
+    assert_eq!(
+        json["versions"][1],
+        json!({"version": "1.1.110", "published": true, "local": true})
+    );
+    assert_eq!(json["stable"], json!("2.0.8"));
+}
+
+#[test]
+fn pair_needs_docs() {
+    let versions = Versions::new(&["2.0.8".to_owned()], &[], releases());
+
+    assert_eq!(versions.pair(), None);
+}
@@ -0,0 +1,53 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{
+    format::{runtime::RuntimeDoc, DiffOptions, Doc, FieldPolicy},
+    Docs,
+};
+use serde_json::json;
+
+mod common;
+
+/// Changes of the first class of the fixture after replacing its description.
+fn class_changes(description: &str, word_diff: bool) -> serde_json::Value {
+    let mut source = common::json("2.0.8", Docs::Runtime);
+    source["classes"][0]["description"] = json!("Read the docs of the entity to learn more.");
+    let mut target = source.clone();
+    target["classes"][0]["description"] = json!(description);
+    let class = source["classes"][0]["name"].as_str().expect("named");
+
+    let parse = |doc| serde_json::from_value::<RuntimeDoc>(doc).expect("fixture is a runtime doc");
+    let (source, target) = (parse(source.clone()), parse(target));
+    let options = DiffOptions {
+        policy: FieldPolicy::all(),
+        word_diff,
+        ..Default::default()
+    };
+    let diff = options.apply(|| source.diff(&target));
+
+    serde_json::to_value(&diff.classes[class]).expect("diff is serializable")
+}
+
+#[test]
+fn changed_words() {
+    assert_eq!(
+        class_changes("Read the manual of the entity to learn more.", true),
+        json!([{"description": {
+            "new": "Read the manual of the entity to learn more.",
+            "words": [
+                {"same": "Read the "},
+                {"removed": "docs"},
+                {"added": "manual"},
+                {"same": " of the entity to learn more."},
+            ],
+        }}])
+    );
+}
+
+#[test]
+fn words_are_optional() {
+    assert_eq!(
+        class_changes("Read the manual of the entity to learn more.", false),
+        json!([{"description": "Read the manual of the entity to learn more."}])
+    );
+}
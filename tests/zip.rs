@@ -0,0 +1,130 @@
+#![allow(clippy::expect_used)]
+
+use fapi_diff::{zip, Docs};
+
+mod common;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+#[test]
+fn reads_both_stages() {
+    let archive = std::fs::read(format!("{FIXTURES}/zip/2.0.8.zip")).expect("fixture exists");
+    assert!(zip::is_zip(&archive));
+
+    for stage in [Docs::Runtime, Docs::Prototype] {
+        let expected = common::raw("2.0.8", stage);
+
+        assert_eq!(
+            zip::read_stage(&archive, stage).expect("archive contains the doc"),
+            expected,
+            "{stage} doc differs"
+        );
+    }
+}
+
+#[test]
+fn rejects_other_files() {
+    let doc = common::raw("2.0.8", Docs::Runtime);
+
+    assert!(!zip::is_zip(&doc));
+    assert!(zip::read_stage(&doc, Docs::Runtime).is_err());
+}
+
+/// An archive with a single deflated `runtime-api.json` whose central directory states `size`.
+fn archive(deflated: &[u8], size: u32) -> Vec<u8> {
+    let name = b"runtime-api.json";
+    let name_len = u16::try_from(name.len()).expect("name is short");
+    let compressed = u32::try_from(deflated.len()).expect("entry is small");
+
+    let mut raw = Vec::new();
+    raw.extend(0x0403_4b50u32.to_le_bytes());
+    raw.extend([20, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    raw.extend(compressed.to_le_bytes());
+    raw.extend(size.to_le_bytes());
+    raw.extend(name_len.to_le_bytes());
+    raw.extend([0, 0]);
+    raw.extend(name);
+    raw.extend(deflated);
+
+    let central = u32::try_from(raw.len()).expect("archive is small");
+    raw.extend(0x0201_4b50u32.to_le_bytes());
+    raw.extend([20, 0, 20, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    raw.extend(compressed.to_le_bytes());
+    raw.extend(size.to_le_bytes());
+    raw.extend(name_len.to_le_bytes());
+    raw.extend([0; 12]);
+    raw.extend(0u32.to_le_bytes());
+    raw.extend(name);
+
+    let central_len = u32::try_from(raw.len()).expect("archive is small") - central;
+    raw.extend(0x0605_4b50u32.to_le_bytes());
+    raw.extend([0, 0, 0, 0, 1, 0, 1, 0]);
+    raw.extend(central_len.to_le_bytes());
+    raw.extend(central.to_le_bytes());
+    raw.extend([0, 0]);
+
+    raw
+}
+
+fn reason(archive: &[u8]) -> String {
+    zip::read_stage(archive, Docs::Runtime)
+        .expect_err("entry is rejected")
+        .to_string()
+}
+
+/// Packs deflate bits, Huffman codes are written starting with their most significant bit.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    count: usize,
+}
+
+impl BitWriter {
+    fn bits(&mut self, value: u32, n: usize) {
+        for i in 0..n {
+            if self.count.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if let Some(last) = self.bytes.last_mut() {
+                *last |= u8::from(value >> i & 1 == 1) << (self.count % 8);
+            }
+            self.count += 1;
+        }
+    }
+
+    fn code(&mut self, code: u32, n: usize) {
+        for i in (0..n).rev() {
+            self.bits(code >> i & 1, 1);
+        }
+    }
+}
+
+#[test]
+fn rejects_oversized_entries() {
+    // a fixed Huffman block repeating one byte far beyond the declared size
+    let mut bits = BitWriter::default();
+    bits.bits(1, 1);
+    bits.bits(1, 2);
+    bits.code(0x30 + u32::from(b'a'), 8);
+    for _ in 0..100_000 {
+        // length 258, distance 1
+        bits.code(0xc5, 8);
+        bits.code(0, 5);
+    }
+    bits.code(0, 7);
+
+    assert!(reason(&archive(&bits.bytes, 16)).ends_with("entry is larger than its declared size"));
+
+    // a stored block longer than the declared size
+    let mut stored = vec![1, 32, 0, !32, !0];
+    stored.extend([b'a'; 32]);
+    assert!(reason(&archive(&stored, 16)).ends_with("entry is larger than its declared size"));
+}
+
+#[test]
+fn rejects_invalid_stored_lengths() {
+    let mut stored = vec![1, 4, 0, 4, 0];
+    stored.extend(b"{}{}");
+
+    assert!(reason(&archive(&stored, 4)).ends_with("invalid stored block length"));
+}